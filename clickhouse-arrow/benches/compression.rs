@@ -72,6 +72,38 @@ fn query_arrow(
     );
 }
 
+/// Block size of the many-small-blocks query, so each result arrives in many small chunks.
+const SMALL_BLOCK_SIZE: usize = 128;
+
+fn query_small_blocks(
+    compression: CompressionMethod,
+    rows: usize,
+    client: &ArrowClient,
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    rt: &Runtime,
+) {
+    let query = format!(
+        "SELECT number, toString(number) AS s FROM numbers({rows}) SETTINGS max_block_size = \
+         {SMALL_BLOCK_SIZE}"
+    );
+    let _ = group.bench_with_input(
+        BenchmarkId::new(format!("clickhouse_arrow_{compression}"), rows),
+        &(query, client),
+        |b, (query, client)| {
+            b.to_async(rt).iter(|| async move {
+                let mut stream = client
+                    .query(query.as_str(), None)
+                    .await
+                    .inspect_err(|e| print_msg(format!("Query error: {e:?}")))
+                    .unwrap();
+                while let Some(result) = stream.next().await {
+                    drop(result.unwrap());
+                }
+            });
+        },
+    );
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let rt = Runtime::new().unwrap();
 
@@ -184,6 +216,17 @@ fn criterion_benchmark(c: &mut Criterion) {
         );
 
         query_group.finish();
+
+        // Benchmark results of many small blocks, dominated by per-chunk overhead
+        let mut small_blocks_group = c.benchmark_group("QuerySmallBlocksCompression");
+        for (compression, client) in [
+            (CompressionMethod::None, &arrow_client_none),
+            (CompressionMethod::LZ4, &arrow_client_lz4),
+            (CompressionMethod::ZSTD, &arrow_client_zstd),
+        ] {
+            query_small_blocks(compression, rows, client, &mut small_blocks_group, &rt);
+        }
+        small_blocks_group.finish();
     }
 
     if std::env::var(common::DISABLE_CLEANUP_ENV).is_ok_and(|e| e.eq_ignore_ascii_case("true")) {
@@ -0,0 +1,139 @@
+//! Serialization/deserialization benchmarks against the in-process mock server.
+//!
+//! No `ClickHouse` container is required: the mock server decodes inserted blocks and replays
+//! canned batches over loopback, so numbers only reflect client-side work and are stable in CI.
+#![expect(unused_crate_dependencies)]
+mod common;
+
+use arrow::record_batch::RecordBatch;
+use clickhouse_arrow::prelude::*;
+use clickhouse_arrow::test_utils::arrow_tests;
+use clickhouse_arrow::test_utils::mock_server::MockServer;
+use clickhouse_arrow::{ArrowOptions, CompressionMethod};
+use criterion::measurement::WallTime;
+use criterion::{BenchmarkGroup, BenchmarkId, Criterion, criterion_group, criterion_main};
+use futures_util::StreamExt;
+use tokio::runtime::Runtime;
+
+use self::common::{init, print_msg};
+
+const MOCK_TABLE: &str = "loopback";
+
+fn insert_arrow(
+    compression: &str,
+    rows: usize,
+    client: &ArrowClient,
+    batch: &RecordBatch,
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    rt: &Runtime,
+) {
+    let query = format!("INSERT INTO {MOCK_TABLE} FORMAT NATIVE");
+    let _ = group.bench_with_input(
+        BenchmarkId::new(format!("clickhouse_arrow_{compression}"), rows),
+        &(&query, client),
+        |b, (query, client)| {
+            b.to_async(rt).iter_batched(
+                || batch.clone(),
+                |batch| async move {
+                    let mut stream = client
+                        .insert(query.as_str(), batch, None)
+                        .await
+                        .inspect_err(|e| print_msg(format!("Insert error\n{e:?}")))
+                        .unwrap();
+                    while let Some(result) = stream.next().await {
+                        result.unwrap();
+                    }
+                },
+                criterion::BatchSize::SmallInput,
+            );
+        },
+    );
+}
+
+fn query_arrow(
+    compression: &str,
+    rows: usize,
+    client: &ArrowClient,
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    rt: &Runtime,
+) {
+    let query = format!("SELECT * FROM {MOCK_TABLE}_{rows}");
+    let _ = group.bench_with_input(
+        BenchmarkId::new(format!("clickhouse_arrow_{compression}"), rows),
+        &(query, client),
+        |b, (query, client)| {
+            b.to_async(rt).iter(|| async move {
+                let mut stream = client
+                    .query(query.as_str(), None)
+                    .await
+                    .inspect_err(|e| print_msg(format!("Query error: {e:?}")))
+                    .unwrap();
+                while let Some(result) = stream.next().await {
+                    drop(result.unwrap());
+                }
+            });
+        },
+    );
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    // Init tracing
+    init();
+
+    let row_counts = [10_000, 100_000, 200_000];
+    let compressions = [
+        ("none", CompressionMethod::None),
+        ("lz4", CompressionMethod::LZ4),
+        ("zstd", CompressionMethod::ZSTD),
+    ];
+
+    // Register one canned response per row count, batches are generated once up front
+    let batches = row_counts
+        .iter()
+        .map(|&rows| (rows, arrow_tests::create_test_batch(rows, false)))
+        .collect::<Vec<_>>();
+    let server = rt
+        .block_on(
+            batches
+                .iter()
+                .fold(MockServer::builder(), |builder, (rows, batch)| {
+                    builder.with_response(format!("FROM {MOCK_TABLE}_{rows}"), vec![batch.clone()])
+                })
+                .start(),
+        )
+        .expect("mock server");
+    print_msg(format!("Started mock server on {}", server.addr()));
+
+    let mut insert_group = c.benchmark_group("LoopbackInsert");
+    for (name, compression) in compressions {
+        let client = rt
+            .block_on(server.arrow_client_builder().with_compression(compression).build_arrow())
+            .expect("mock client");
+        for (rows, batch) in &batches {
+            insert_arrow(name, *rows, &client, batch, &mut insert_group, &rt);
+        }
+    }
+    insert_group.finish();
+
+    let mut query_group = c.benchmark_group("LoopbackQuery");
+    for (name, compression) in compressions {
+        let client = rt
+            .block_on(
+                server
+                    .arrow_client_builder()
+                    .with_compression(compression)
+                    .with_arrow_options(ArrowOptions::default().with_strings_as_strings(true))
+                    .build_arrow(),
+            )
+            .expect("mock client");
+        for (rows, _) in &batches {
+            query_arrow(name, *rows, &client, &mut query_group, &rt);
+        }
+    }
+    query_group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
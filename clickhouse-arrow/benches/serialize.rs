@@ -0,0 +1,167 @@
+//! Write path benchmarks for the Arrow serializers.
+//!
+//! Each group serializes a single-column fixture through the same code path used for inserts,
+//! without a server, so results isolate the serializer for one `ClickHouse` type. Throughput is
+//! reported in rows.
+//!
+//! Use criterion baselines to guard performance changes:
+//!
+//! ```bash
+//! # On the base branch
+//! cargo bench -F test-utils --bench serialize -- --save-baseline main
+//! # On the change, fails if any benchmark regressed beyond the noise threshold
+//! just bench-serialize-check main
+//! ```
+#![expect(unused_crate_dependencies)]
+// Benchmark code: casts are safe for test data sizes
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(unused_results)]
+
+use std::hint::black_box;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::buffer::OffsetBuffer;
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use clickhouse_arrow::test_utils::codec::serialize_batch;
+use clickhouse_arrow::{ArrowOptions, Type};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+
+/// Row counts per fixture
+const ROW_COUNTS: &[usize] = &[8_192, 65_536];
+
+/// Elements per row of `Array` and `Map` fixtures
+const NESTED_LEN: usize = 4;
+
+/// Distinct values of `LowCardinality` fixtures
+const CARDINALITY: usize = 64;
+
+/// A single-column batch and the `ClickHouse` type it is inserted as.
+struct Fixture {
+    name:   &'static str,
+    batch:  RecordBatch,
+    header: Vec<(String, Type)>,
+}
+
+impl Fixture {
+    fn new(name: &'static str, array: ArrayRef, type_: Type) -> Self {
+        let nullable = array.null_count() > 0;
+        let field = Field::new("col", array.data_type().clone(), nullable);
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(vec![field])), vec![array]).unwrap();
+        Self { name, batch, header: vec![("col".to_string(), type_)] }
+    }
+}
+
+fn strings(rows: usize, distinct: usize) -> StringArray {
+    (0..rows).map(|i| Some(format!("value_{:08}", i % distinct))).collect()
+}
+
+fn fixtures(rows: usize) -> Vec<Fixture> {
+    let int64 = Int64Array::from_iter_values((0..rows).map(|i| i as i64));
+    let float64 = Float64Array::from_iter_values((0..rows).map(|i| i as f64 * 0.5));
+    let nullable_int64 =
+        (0..rows).map(|i| (i % 10 != 0).then_some(i as i64)).collect::<Int64Array>();
+    let nullable_string =
+        (0..rows).map(|i| (i % 10 != 0).then(|| format!("value_{i:08}"))).collect::<StringArray>();
+
+    let list_values = Int32Array::from_iter_values((0..rows * NESTED_LEN).map(|i| i as i32));
+    let list = ListArray::new(
+        Arc::new(Field::new_list_field(DataType::Int32, false)),
+        OffsetBuffer::from_lengths(std::iter::repeat_n(NESTED_LEN, rows)),
+        Arc::new(list_values),
+        None,
+    );
+
+    let map_entries = StructArray::from(vec![
+        (
+            Arc::new(Field::new("keys", DataType::Utf8, false)),
+            Arc::new(strings(rows * NESTED_LEN, NESTED_LEN)) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("values", DataType::Int64, false)),
+            Arc::new(Int64Array::from_iter_values((0..rows * NESTED_LEN).map(|i| i as i64))),
+        ),
+    ]);
+    let map = MapArray::new(
+        Arc::new(Field::new("entries", map_entries.data_type().clone(), false)),
+        OffsetBuffer::from_lengths(std::iter::repeat_n(NESTED_LEN, rows)),
+        map_entries,
+        None,
+        false,
+    );
+
+    let tuple = StructArray::from(vec![
+        (Arc::new(Field::new("c0", DataType::Int64, false)), Arc::new(int64.clone()) as ArrayRef),
+        (Arc::new(Field::new("c1", DataType::Utf8, false)), Arc::new(strings(rows, rows))),
+    ]);
+
+    let low_cardinality_dict =
+        strings(rows, CARDINALITY).iter().collect::<DictionaryArray<Int32Type>>();
+
+    vec![
+        Fixture::new("int64", Arc::new(int64), Type::Int64),
+        Fixture::new("float64", Arc::new(float64), Type::Float64),
+        Fixture::new("string", Arc::new(strings(rows, rows)), Type::String),
+        Fixture::new("nullable_int64", Arc::new(nullable_int64), Type::Int64.into_nullable()),
+        Fixture::new("nullable_string", Arc::new(nullable_string), Type::String.into_nullable()),
+        Fixture::new(
+            "low_cardinality_dense",
+            Arc::new(strings(rows, CARDINALITY)),
+            Type::LowCardinality(Box::new(Type::String)),
+        ),
+        Fixture::new(
+            "low_cardinality_dictionary",
+            Arc::new(low_cardinality_dict),
+            Type::LowCardinality(Box::new(Type::String)),
+        ),
+        Fixture::new("array_int32", Arc::new(list), Type::Array(Box::new(Type::Int32))),
+        Fixture::new(
+            "map_string_int64",
+            Arc::new(map),
+            Type::Map(Box::new(Type::String), Box::new(Type::Int64)),
+        ),
+        Fixture::new(
+            "tuple_int64_string",
+            Arc::new(tuple),
+            Type::Tuple(vec![Type::Int64, Type::String]),
+        ),
+    ]
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    let options = ArrowOptions::default().with_strings_as_strings(true);
+
+    for &rows in ROW_COUNTS {
+        for fixture in fixtures(rows) {
+            let mut group = c.benchmark_group(format!("serialize/{}", fixture.name));
+            group.throughput(Throughput::Elements(rows as u64));
+
+            // Reuse the buffer so the allocator is not measured
+            let mut buffer = Vec::new();
+            serialize_batch(&fixture.batch, Some(&fixture.header), options, &mut buffer).unwrap();
+            let capacity = buffer.len();
+
+            group.bench_with_input(BenchmarkId::from_parameter(rows), &fixture, |b, fixture| {
+                b.iter(|| {
+                    buffer.clear();
+                    buffer.reserve(capacity);
+                    serialize_batch(
+                        black_box(&fixture.batch),
+                        Some(&fixture.header),
+                        options,
+                        &mut buffer,
+                    )
+                    .unwrap();
+                    black_box(buffer.len())
+                });
+            });
+            group.finish();
+        }
+    }
+}
+
+criterion_group!(benches, bench_serialize);
+criterion_main!(benches);
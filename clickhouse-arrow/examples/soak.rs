@@ -0,0 +1,311 @@
+//! Soak test for long-lived connections.
+//!
+//! Runs a mixed insert/query workload over a single long-lived client for hours, sampling the
+//! process RSS, the shared buffer pool, and open file descriptors, to catch the slow growth that
+//! only shows up in always-on ingestion services.
+//!
+//! ```bash
+//! SOAK_DURATION=4h cargo run --release --example soak --features test-utils
+//! ```
+//!
+//! Configuration, via env vars:
+//! - `SOAK_DURATION`: Total run time, ie `90s`, `30m`, `4h` (default: `10m`)
+//! - `SOAK_INTERVAL`: Time between samples (default: `30s`)
+//! - `SOAK_WARMUP`: Time before the baseline sample, letting pools and caches fill (default: `1m`)
+//! - `INSERT_WORKERS`: Concurrent insert loops (default: 4)
+//! - `QUERY_WORKERS`: Concurrent query loops (default: 2)
+//! - `BATCH_SIZE`: Rows per inserted batch, supports K/M/MM (default: `10K`)
+//! - `SOAK_MAX_RSS_GROWTH_MB`: Fail if RSS grows more than this after warmup (default: none)
+//! - `SOAK_MAX_FD_GROWTH`: Fail if open FDs grow more than this after warmup (default: 16)
+//!
+//! RSS and FD counts are read from `/proc/self` and are only available on Linux.
+#![expect(unused_crate_dependencies)]
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use clickhouse_arrow::prelude::*;
+use clickhouse_arrow::simd::BUFFER_POOL;
+use clickhouse_arrow::spawn::SpawnedTask;
+use clickhouse_arrow::test_utils::{ClickHouseContainer, arrow_tests};
+use comfy_table::Table;
+use comfy_table::presets::UTF8_FULL;
+use common::scale_utils::{format_number, parse_number};
+use futures_util::StreamExt;
+
+/// Workload counters shared by the workers.
+#[derive(Default)]
+struct Counters {
+    rows_inserted: AtomicU64,
+    inserts:       AtomicU64,
+    queries:       AtomicU64,
+    rows_read:     AtomicU64,
+    errors:        AtomicU64,
+}
+
+/// A point-in-time sample of the process.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    elapsed:      Duration,
+    rss_kb:       Option<u64>,
+    fds:          Option<usize>,
+    pool_buffers: usize,
+    inserts:      u64,
+    queries:      u64,
+    errors:       u64,
+}
+
+impl Sample {
+    fn take(start: Instant, counters: &Counters) -> Self {
+        let pool = BUFFER_POOL.stats();
+        Self {
+            elapsed:      start.elapsed(),
+            rss_kb:       rss_kb(),
+            fds:          open_fds(),
+            pool_buffers: pool.tiny_count
+                + pool.small_count
+                + pool.medium_count
+                + pool.large_count
+                + pool.xlarge_count,
+            inserts:      counters.inserts.load(Ordering::Relaxed),
+            queries:      counters.queries.load(Ordering::Relaxed),
+            errors:       counters.errors.load(Ordering::Relaxed),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn row(&self) -> Vec<String> {
+        vec![
+            format!("{:.0}s", self.elapsed.as_secs_f64()),
+            self.rss_kb.map_or_else(|| "n/a".into(), |kb| format!("{:.1}", kb as f64 / 1024.0)),
+            self.fds.map_or_else(|| "n/a".into(), |fds| fds.to_string()),
+            self.pool_buffers.to_string(),
+            format_number(usize::try_from(self.inserts).unwrap_or(usize::MAX)),
+            format_number(usize::try_from(self.queries).unwrap_or(usize::MAX)),
+            self.errors.to_string(),
+        ]
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::any::Any + Send>> {
+    common::run_example_with_cleanup(|ch| async move { run(ch).await.unwrap() }, None).await?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_lines)]
+#[allow(clippy::cast_precision_loss)]
+async fn run(ch: &'static ClickHouseContainer) -> Result<()> {
+    let duration = env_duration("SOAK_DURATION", Duration::from_secs(10 * 60));
+    let interval = env_duration("SOAK_INTERVAL", Duration::from_secs(30));
+    let warmup = env_duration("SOAK_WARMUP", Duration::from_secs(60));
+    let insert_workers = env_number("INSERT_WORKERS", 4);
+    let query_workers = env_number("QUERY_WORKERS", 2);
+    let batch_size = env_number("BATCH_SIZE", 10_000);
+    let max_rss_growth_mb =
+        std::env::var("SOAK_MAX_RSS_GROWTH_MB").ok().and_then(|v| v.parse().ok());
+    let max_fd_growth = env_number("SOAK_MAX_FD_GROWTH", 16);
+
+    common::print_banner("Soak Test", Some(72));
+    eprintln!(
+        "duration={duration:?} interval={interval:?} warmup={warmup:?} \
+         insert_workers={insert_workers} query_workers={query_workers} batch_size={batch_size}"
+    );
+
+    let db = "example_soak_test";
+    let client = arrow_tests::setup_test_arrow_client(ch.get_native_url(), &ch.user, &ch.password)
+        .with_compression(CompressionMethod::LZ4)
+        .build::<ArrowFormat>()
+        .await?;
+    arrow_tests::setup_database(db, &client).await?;
+    let batch = arrow_tests::create_test_batch(batch_size, false);
+    let table = arrow_tests::setup_table(&client, db, &batch.schema()).await?;
+
+    let counters = Arc::new(Counters::default());
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut tasks = Vec::with_capacity(insert_workers + query_workers);
+
+    for _ in 0..insert_workers {
+        let (client, table, batch) = (client.clone(), table.clone(), batch.clone());
+        let (counters, stop) = (Arc::clone(&counters), Arc::clone(&stop));
+        tasks.push(SpawnedTask::spawn(async move {
+            let query = format!("INSERT INTO {table} FORMAT Native");
+            while !stop.load(Ordering::Relaxed) {
+                let result = async {
+                    let mut stream = client.insert(query.as_str(), batch.clone(), None).await?;
+                    while let Some(result) = stream.next().await {
+                        result?;
+                    }
+                    Ok::<_, Error>(())
+                }
+                .await;
+                if let Err(error) = result {
+                    eprintln!("Insert error: {error}");
+                    let _ = counters.errors.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                let _ = counters.inserts.fetch_add(1, Ordering::Relaxed);
+                let _ =
+                    counters.rows_inserted.fetch_add(batch.num_rows() as u64, Ordering::Relaxed);
+            }
+        }));
+    }
+
+    for i in 0..query_workers {
+        let (client, table) = (client.clone(), table.clone());
+        let (counters, stop) = (Arc::clone(&counters), Arc::clone(&stop));
+        tasks.push(SpawnedTask::spawn(async move {
+            // Alternate a wide scan with a cheap aggregate to mix block sizes
+            let queries = [
+                format!("SELECT * FROM {table} ORDER BY id DESC LIMIT {batch_size}"),
+                format!("SELECT count(), uniq(id) FROM {table}"),
+            ];
+            let mut n = i;
+            while !stop.load(Ordering::Relaxed) {
+                n += 1;
+                let result = async {
+                    let mut stream =
+                        client.query(queries[n % queries.len()].as_str(), None).await?;
+                    let mut rows = 0;
+                    while let Some(batch) = stream.next().await {
+                        rows += batch?.num_rows() as u64;
+                    }
+                    Ok::<_, Error>(rows)
+                }
+                .await;
+                match result {
+                    Ok(rows) => {
+                        let _ = counters.queries.fetch_add(1, Ordering::Relaxed);
+                        let _ = counters.rows_read.fetch_add(rows, Ordering::Relaxed);
+                    }
+                    Err(error) => {
+                        eprintln!("Query error: {error}");
+                        let _ = counters.errors.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }));
+    }
+
+    // Sample until the deadline, truncating the table so server disk usage stays flat
+    let start = Instant::now();
+    let mut samples = Vec::new();
+    let mut baseline = None;
+    let mut ticker = tokio::time::interval(interval);
+    let _ = ticker.tick().await;
+    while start.elapsed() < duration {
+        let _ = ticker.tick().await;
+        let sample = Sample::take(start, &counters);
+        eprintln!(
+            "[{:>6.0}s] rss={} fds={} pool_buffers={} inserts={} queries={} errors={}",
+            sample.elapsed.as_secs_f64(),
+            sample.rss_kb.map_or_else(|| "n/a".into(), |kb| format!("{}MB", kb / 1024)),
+            sample.fds.map_or_else(|| "n/a".into(), |fds| fds.to_string()),
+            sample.pool_buffers,
+            sample.inserts,
+            sample.queries,
+            sample.errors,
+        );
+        if baseline.is_none() && sample.elapsed >= warmup {
+            baseline = Some(sample);
+        }
+        samples.push(sample);
+        client.execute(format!("TRUNCATE TABLE {table}"), None).await?;
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    for task in tasks {
+        task.join_unwind().await.expect("Soak worker panicked");
+    }
+    let last = Sample::take(start, &counters);
+    samples.push(last);
+
+    let mut table_out = Table::new();
+    let _ = table_out.load_preset(UTF8_FULL).set_header(vec![
+        "Elapsed",
+        "RSS (MB)",
+        "FDs",
+        "Pool buffers",
+        "Inserts",
+        "Queries",
+        "Errors",
+    ]);
+    for sample in &samples {
+        let _ = table_out.add_row(sample.row());
+    }
+    eprintln!("{table_out}");
+
+    let rows_inserted = counters.rows_inserted.load(Ordering::Relaxed);
+    let rows_read = counters.rows_read.load(Ordering::Relaxed);
+    let secs = last.elapsed.as_secs_f64();
+    eprintln!(
+        "Inserted {} rows ({:.0}/s), read {} rows ({:.0}/s)",
+        format_number(usize::try_from(rows_inserted).unwrap_or(usize::MAX)),
+        rows_inserted as f64 / secs,
+        format_number(usize::try_from(rows_read).unwrap_or(usize::MAX)),
+        rows_read as f64 / secs,
+    );
+
+    client.execute(format!("DROP TABLE IF EXISTS {table}"), None).await?;
+
+    // Growth after warmup, compared to the baseline
+    let Some(baseline) = baseline else {
+        eprintln!("Run ended before warmup, skipping growth checks");
+        return Ok(());
+    };
+    if let (Some(base), Some(end)) = (baseline.rss_kb, last.rss_kb) {
+        let growth_mb = (end as f64 - base as f64) / 1024.0;
+        let hours = (last.elapsed - baseline.elapsed).as_secs_f64() / 3600.0;
+        eprintln!("RSS growth after warmup: {growth_mb:.1}MB ({:.1}MB/h)", growth_mb / hours);
+        if let Some(max) = max_rss_growth_mb {
+            assert!(growth_mb <= max, "RSS grew {growth_mb:.1}MB, more than {max}MB");
+        }
+    }
+    if let (Some(base), Some(end)) = (baseline.fds, last.fds) {
+        eprintln!("FD growth after warmup: {}", end.saturating_sub(base));
+        assert!(
+            end <= base + max_fd_growth,
+            "Open FDs grew from {base} to {end}, more than {max_fd_growth}"
+        );
+    }
+
+    Ok(())
+}
+
+/// Resident set size of this process in KB, from `/proc/self/status`.
+fn rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Number of open file descriptors of this process, from `/proc/self/fd`.
+fn open_fds() -> Option<usize> { std::fs::read_dir("/proc/self/fd").ok().map(Iterator::count) }
+
+/// Parse a duration env var such as `90s`, `30m`, `4h`, or plain seconds.
+fn env_duration(name: &str, default: Duration) -> Duration {
+    let Ok(value) = std::env::var(name) else {
+        return default;
+    };
+    let value = value.trim();
+    let (number, unit) = value.split_at(value.trim_end_matches(char::is_alphabetic).len());
+    let Ok(number) = number.parse::<u64>() else {
+        eprintln!("Invalid {name}: {value}, using {default:?}");
+        return default;
+    };
+    match unit {
+        "" | "s" => Duration::from_secs(number),
+        "m" => Duration::from_secs(number * 60),
+        "h" => Duration::from_secs(number * 3600),
+        _ => {
+            eprintln!("Invalid {name} unit: {unit}, using {default:?}");
+            default
+        }
+    }
+}
+
+fn env_number(name: &str, default: usize) -> usize {
+    std::env::var(name).ok().and_then(|v| parse_number(&v)).unwrap_or(default)
+}
@@ -1,13 +1,29 @@
 //! ## Logic for interfacing between Arrow and `ClickHouse`
 pub mod block;
 mod builder;
+pub mod columns;
+pub mod compat;
+pub mod conversion;
 mod deserialize;
+pub mod explode;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod lazy;
+pub mod lineage;
+pub mod merge;
+pub mod ndjson;
+pub mod ordering;
+pub mod rescale;
 pub(crate) mod schema;
 mod serialize;
+#[cfg(feature = "serde")]
+pub mod typed;
 pub(crate) mod types;
 pub mod utils;
 
 // Re-exports
 pub use arrow;
 pub(crate) use deserialize::ArrowDeserializerState;
+pub use lineage::{BATCH_INDEX_KEY, BATCH_QUERY_ID_KEY, BATCH_SERVER_KEY, BatchMetadata};
+pub use rescale::{DecimalOverflow, DecimalRescale};
 pub use types::ch_to_arrow_type;
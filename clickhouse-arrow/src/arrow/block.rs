@@ -1,13 +1,12 @@
-use std::str::FromStr;
 use std::sync::Arc;
 
-use arrow::array::{Array, new_empty_array};
+use arrow::array::{Array, ArrayData, ArrayRef, Scalar, make_array, new_empty_array};
 use arrow::datatypes::*;
-use arrow::record_batch::RecordBatch;
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use super::builder::TypedBuilder;
-use super::deserialize::{ArrowDeserializerState, ClickHouseArrowDeserializer};
+use super::deserialize::{ArrowDeserializerState, ClickHouseArrowDeserializer, skip, sparse};
 use super::serialize::ClickHouseArrowSerializer;
 use super::types::arrow_to_ch_type;
 pub use super::types::{
@@ -21,13 +20,16 @@ use crate::formats::{DeserializerState, SerializerState};
 use crate::geo::normalize_geo_type;
 use crate::io::{ClickHouseBytesRead, ClickHouseBytesWrite, ClickHouseRead, ClickHouseWrite};
 use crate::native::block_info::BlockInfo;
-use crate::native::protocol::DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION;
+use crate::native::protocol::{
+    DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION, MAX_STRING_SIZE,
+};
 use crate::native::sparse::{
-    SparseDeserializeState, expand_sparse_array, read_sparse_offsets, read_sparse_offsets_sync,
+    encode_dense_array, encode_sparse_array, expand_sparse_array, read_sparse_offsets,
+    read_sparse_offsets_sync,
 };
 use crate::prelude::*;
 use crate::serialize::ClickHouseNativeSerializer;
-use crate::{ArrowOptions, Result, Type};
+use crate::{ArrowOptions, NullPolicy, Result, Type};
 
 /// Implementation of `ProtocolData` for Arrow `RecordBatch`es.
 ///
@@ -91,16 +93,25 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             let name = field.name();
             let data_type = field.data_type();
             let nullable = field.is_nullable();
-            let maybe_type = header.and_then(|h| h.iter().find(|(n, _)| n == name)).map(|(_, t)| t);
+            let maybe_type = header.and_then(|h| header_type(h, i, name));
             let type_ = if let Some(t) = maybe_type {
                 t
             } else {
-                &arrow_to_ch_type(data_type, nullable, Some(options))?
+                &arrow_to_ch_type(data_type, nullable, Some(options))
+                    .map_err(|e| e.with_column(name, i))?
             };
             // Simplify geo types
             let is_geo =
                 matches!(type_, Type::Point | Type::Polygon | Type::MultiPolygon | Type::Ring);
             let type_ = if is_geo { &normalize_geo_type(type_).unwrap() } else { type_ };
+            // Apply the null policy if the column has nulls the target type cannot hold
+            let resolved =
+                resolve_nulls(name, column, type_, options).map_err(|e| e.with_column(name, i))?;
+            let (column, type_) = resolved.as_ref().map_or((column, type_), |(c, t)| (c, t));
+            // Interpret naive timestamps as wall-clock time in the column's timezone
+            let localized =
+                localize_timestamps(column, type_, options).map_err(|e| e.with_column(name, i))?;
+            let column = localized.as_ref().unwrap_or(column);
 
             if debug_arrow() {
                 trace!(name, ?data_type, nullable, ?type_, "serializing column {i}");
@@ -123,7 +134,10 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             }
 
             type_.serialize_prefix_async(writer, &mut state).await?;
-            type_.serialize_async(writer, column, data_type, &mut state).await?;
+            type_
+                .serialize_async(writer, column, data_type, &mut state)
+                .await
+                .map_err(|e| e.with_column(name, i))?;
         }
 
         Ok(())
@@ -159,16 +173,25 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             let name = field.name();
             let data_type = field.data_type();
             let nullable = field.is_nullable();
-            let maybe_type = header.and_then(|h| h.iter().find(|(n, _)| n == name)).map(|(_, t)| t);
+            let maybe_type = header.and_then(|h| header_type(h, i, name));
             let type_ = if let Some(t) = maybe_type {
                 t
             } else {
-                &arrow_to_ch_type(data_type, nullable, Some(options))?
+                &arrow_to_ch_type(data_type, nullable, Some(options))
+                    .map_err(|e| e.with_column(name, i))?
             };
             // Simplify geo types
             let is_geo =
                 matches!(type_, Type::Point | Type::Polygon | Type::MultiPolygon | Type::Ring);
             let type_ = if is_geo { &normalize_geo_type(type_).unwrap() } else { type_ };
+            // Apply the null policy if the column has nulls the target type cannot hold
+            let resolved =
+                resolve_nulls(name, column, type_, options).map_err(|e| e.with_column(name, i))?;
+            let (column, type_) = resolved.as_ref().map_or((column, type_), |(c, t)| (c, t));
+            // Interpret naive timestamps as wall-clock time in the column's timezone
+            let localized =
+                localize_timestamps(column, type_, options).map_err(|e| e.with_column(name, i))?;
+            let column = localized.as_ref().unwrap_or(column);
 
             if debug_arrow() {
                 trace!(name, ?data_type, nullable, ?type_, "serializing column {i}");
@@ -191,7 +214,9 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
             }
 
             type_.serialize_prefix(writer, &mut state);
-            type_.serialize(writer, column, data_type, &mut state)?;
+            type_
+                .serialize(writer, column, data_type, &mut state)
+                .map_err(|e| e.with_column(name, i))?;
         }
 
         Ok(())
@@ -205,7 +230,7 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
         options: ArrowOptions,
         state: &mut DeserializerState<ArrowDeserializerState>,
     ) -> Result<RecordBatch> {
-        let _ = BlockInfo::read_async(reader).await.inspect_err(|error| {
+        let info = BlockInfo::read_async(reader).await.inspect_err(|error| {
             error!(?error, "failed to read block info");
         })?;
 
@@ -213,148 +238,109 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
         let (columns, rows) =
             (reader.read_var_uint().await? as usize, reader.read_var_uint().await? as usize);
 
+        if columns > MAX_STRING_SIZE || rows > MAX_STRING_SIZE {
+            return Err(Error::Protocol(format!(
+                "block too large: {columns} columns, {rows} rows"
+            )));
+        }
+
         if columns == 0 && rows == 0 {
             return Ok(RecordBatch::new_empty(Arc::new(Schema::empty())));
         } else if debug_arrow() {
             debug!(columns, rows, "Deserializing arrow");
         }
 
-        let mut prefix_state = DeserializerState::default();
-
         let deser = state.deserializer();
         let _ = deser.with_capacity(columns, rows);
 
+        // Builders are assigned to decoded columns in order, which is stable across the blocks of
+        // a query as both the columns and projection are
+        let mut slot = 0;
         for i in 0..columns {
-            // eprintln!("[DEBUG] Starting to read column {}", i);
-            let name = reader.read_utf8_string().await?;
-            let type_name = reader.read_utf8_string().await?;
-            let internal_type = Type::from_str(&type_name)?;
-            let (arrow_type, is_nullable) = internal_type.arrow_type(Some(options))?;
-
-            // Verify the resulting type against the arrow type, otherwise the builders will fail
-            let type_hint =
-                super::types::normalize_type(&internal_type, &arrow_type).unwrap_or(internal_type);
-            let field = Field::new(name, arrow_type, is_nullable);
+            // Headers repeat in every block, so reuse the field and type decoded for this column
+            let name = reader.read_string().await?;
+            let type_name = reader.read_string().await?;
+            let (field, type_hint) = deser.headers.resolve(i, &name, &type_name, options)?;
 
             if debug_arrow() {
                 trace!(?field, ?type_hint, ?options, "deserializing column {i}");
             }
 
-            // Check for sparse/custom serialization
-            // Protocol: if has_custom=1, next byte is KindStackBinarySerializationType
-            // SPARSE=1 means the kind stack is {Default, Sparse}
-            let serialization_kind = if revision
-                >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION
-            {
-                let has_custom = reader.read_u8().await? != 0;
-                // eprintln!("[DEBUG] Column '{}' (index {}): has_custom={}", field.name(), i,
-                // has_custom);
-                if has_custom {
-                    // KindStackBinarySerializationType enum:
-                    // 0 = DEFAULT, 1 = SPARSE, 2 = DETACHED, 3 = DETACHED_OVER_SPARSE,
-                    // 4 = REPLICATED, 5 = COMBINATION
-                    let kind = reader.read_u8().await?;
-                    // eprintln!("[DEBUG] Column '{}' has custom serialization kind={}",
-                    // field.name(), kind);
-                    if debug_arrow() {
-                        trace!(name = %field.name(), kind, "column has custom serialization");
-                    }
-                    if kind == 5 {
-                        // COMBINATION: read variable-length stack
-                        // Format: VarUInt count, then count x UInt8 kinds
-                        let count = reader.read_var_uint().await?;
-                        if debug_arrow() {
-                            trace!(name = %field.name(), count, "COMBINATION serialization stack");
-                        }
-                        for _ in 0..count {
-                            let _inner_kind = reader.read_u8().await?;
-                        }
-                        // For now, treat combinations containing SPARSE (kind=1) as sparse
-                        // This is a simplification - proper handling would inspect the stack
-                        1 // Treat as SPARSE for now
-                    } else {
-                        kind
-                    }
-                } else {
-                    0 // DEFAULT
-                }
+            // Check for sparse/custom serialization, including the kinds of nested tuple elements
+            let kinds = if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
+                read_serialization_kinds(reader, &type_hint).await?
             } else {
-                0 // DEFAULT (no custom serialization support)
+                SerializationKinds::default() // No custom serialization support
             };
-            let is_sparse = serialization_kind == 1; // SPARSE
+            if debug_arrow() && kinds.is_custom() {
+                trace!(name = %field.name(), ?kinds, "column has custom serialization");
+            }
+
+            // Columns outside the projection are skipped without decoding when possible
+            let projected = deser.is_projected(field.name());
+            if !projected && (rows == 0 || (!kinds.is_custom() && skip::is_skippable(&type_hint))) {
+                if rows > 0 {
+                    skip::skip_async(&type_hint, reader, rows).await?;
+                }
+                continue;
+            }
 
             let array = if rows > 0 {
-                let dt = field.data_type();
                 let builders = &mut deser.builders;
-
-                if is_sparse {
-                    // Sparse serialization: read offsets first, then only non-default values
-                    let mut sparse_state = SparseDeserializeState::default();
-                    let offsets = read_sparse_offsets(reader, rows, &mut sparse_state).await?;
-                    let sparse_rows = offsets.len();
-                    // eprintln!("[DEBUG] Sparse column '{}': {} offsets for {} total rows,
-                    // first_offsets={:?}",           field.name(), sparse_rows,
-                    // rows,           &offsets[..std::cmp::min(5,
-                    // offsets.len())]);
-
-                    if debug_arrow() {
-                        trace!(
-                            ?field,
-                            total_rows = rows,
-                            sparse_rows,
-                            "deserializing sparse column {i}"
-                        );
-                    }
-
-                    // Deserialize only the non-default values
-                    let builder = if let Some(b) = builders.get_mut(i) {
-                        b
-                    } else {
-                        builders.push(TypedBuilder::try_new(&type_hint, dt)?);
-                        builders.last_mut().unwrap()
-                    };
-
-                    let row_buffer = &mut deser.buffer;
-                    // eprintln!("[DEBUG] Reading prefix for sparse column '{}'", field.name());
-                    type_hint.deserialize_prefix_async(reader, &mut prefix_state).await?;
-                    // eprintln!("[DEBUG] Reading {} sparse values for column '{}'", sparse_rows,
-                    // field.name());
-                    let sparse_array = type_hint
-                        .deserialize_arrow_async(builder, reader, dt, sparse_rows, &[], row_buffer)
-                        .await
-                        .inspect_err(|error| {
-                            error!(?error, ?field, "col {i} sparse deserialize");
-                        })?;
-                    // eprintln!("[DEBUG] Read sparse array for '{}', len={}", field.name(),
-                    // sparse_array.len());
-
-                    // Expand sparse array to full size with defaults
-                    expand_sparse_array(&sparse_array, &offsets, rows)?
+                let builder = if let Some(b) = builders.get_mut(slot) {
+                    b
                 } else {
-                    // Normal (non-sparse) deserialization
-                    let builder = if let Some(b) = builders.get_mut(i) {
-                        b
-                    } else {
-                        builders.push(TypedBuilder::try_new(&type_hint, dt)?);
-                        builders.last_mut().unwrap()
-                    };
-
-                    let row_buffer = &mut deser.buffer;
-                    type_hint.deserialize_prefix_async(reader, &mut prefix_state).await?;
-                    type_hint
-                        .deserialize_arrow_async(builder, reader, dt, rows, &[], row_buffer)
-                        .await
-                        .inspect_err(|error| error!(?error, ?field, "col {i} deserialize"))?
-                }
+                    builders.push(
+                        TypedBuilder::try_new(&type_hint, field.data_type())
+                            .map_err(|e| e.with_column(field.name(), i))?,
+                    );
+                    builders.last_mut().unwrap()
+                };
+                read_column_async(
+                    reader,
+                    &field,
+                    &type_hint,
+                    &kinds,
+                    rows,
+                    options,
+                    builder,
+                    &mut deser.buffer,
+                )
+                .await
+                .inspect_err(|error| error!(?error, ?field, "col {i} deserialize"))
+                .map_err(|e| e.with_column(field.name(), i))?
             } else {
                 new_empty_array(field.data_type())
             };
+            slot += 1;
 
-            let _ = deser.push_array(array).push_field(Arc::new(field));
+            // Unskippable columns outside the projection are decoded and dropped
+            if !projected {
+                continue;
+            }
+
+            // Run end encoded columns change the field's type
+            let array = encode_dense_array(array, options)?;
+            let field = if array.data_type() == field.data_type() {
+                field
+            } else {
+                Arc::new(Field::clone(&field).with_data_type(array.data_type().clone()))
+            };
+            let (field, array) = deser.rescale(field, array, i)?;
+            let _ = deser.push_array(array).push_field(field);
         }
+        // Release scratch space beyond the retained capacity, with i128 as the upper bound
+        deser.buffer.shrink_to(options.retained_builder_rows.saturating_mul(16));
 
         let (fields, arrays) = state.deserializer().take();
-        Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+        // Surface block info (e.g. GROUP BY overflow rows) via schema metadata, if configured
+        let mut schema = Schema::new(fields);
+        if options.block_info_metadata {
+            schema = schema.with_metadata(info.to_metadata());
+        }
+        // The row count must be explicit in case the projection excludes every column
+        let batch_options = RecordBatchOptions::new().with_row_count(Some(rows));
+        Ok(RecordBatch::try_new_with_options(Arc::new(schema), arrays, &batch_options)?)
     }
 
     #[instrument(level = "trace", name = "clickhouse.deserialize.arrow" skip_all)]
@@ -364,7 +350,7 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
         options: ArrowOptions,
         state: &mut DeserializerState<ArrowDeserializerState>,
     ) -> Result<RecordBatch> {
-        let _ = BlockInfo::read(reader).inspect_err(|error| {
+        let info = BlockInfo::read(reader).inspect_err(|error| {
             error!(?error, "failed to read block info");
         })?;
 
@@ -372,62 +358,50 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
         let (columns, rows) =
             (reader.try_get_var_uint()? as usize, reader.try_get_var_uint()? as usize);
 
+        if columns > MAX_STRING_SIZE || rows > MAX_STRING_SIZE {
+            return Err(Error::Protocol(format!(
+                "block too large: {columns} columns, {rows} rows"
+            )));
+        }
+
         if columns == 0 && rows == 0 {
             return Ok(RecordBatch::new_empty(Arc::new(Schema::empty())));
         } else if debug_arrow() {
             debug!(columns, rows, "Deserializing arrow");
         }
 
+        // Every column and row occupies at least one byte, so never preallocate past the input
         let deser = state.deserializer();
-        let _ = deser.with_capacity(columns, rows);
+        let _ = deser.with_capacity(columns.min(reader.remaining()), rows.min(reader.remaining()));
 
+        // Builders are assigned to decoded columns in order, see `read_async`
+        let mut slot = 0;
         for i in 0..columns {
+            // Headers repeat in every block, see `read_async`
             let name = reader.try_get_string()?;
-            let name = String::from_utf8_lossy(&name);
             let type_name = reader.try_get_string()?;
-            let internal_type = Type::from_str(String::from_utf8_lossy(&type_name).as_ref())?;
-            let (arrow_type, is_nullable) = internal_type.arrow_type(Some(options))?;
-
-            // Verify the resulting type against the arrow type, otherwise the builders will fail
-            let type_hint =
-                super::types::normalize_type(&internal_type, &arrow_type).unwrap_or(internal_type);
-            let field = Field::new(name.as_ref(), arrow_type, is_nullable);
+            let (field, type_hint) = deser.headers.resolve(i, &name, &type_name, options)?;
 
             if debug_arrow() {
                 trace!(?field, ?type_hint, ?options, "deserializing column {i}");
             }
 
-            // Check for sparse/custom serialization
-            // Protocol: if has_custom=1, next byte is KindStackBinarySerializationType
-            // SPARSE=1 means the kind stack is {Default, Sparse}
-            let serialization_kind =
-                if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                    let has_custom = reader.try_get_u8()? != 0;
-                    if has_custom {
-                        // KindStackBinarySerializationType enum:
-                        // 0 = DEFAULT, 1 = SPARSE, 2 = DETACHED, 3 = DETACHED_OVER_SPARSE,
-                        // 4 = REPLICATED, 5 = COMBINATION
-                        let kind = reader.try_get_u8()?;
-                        if kind == 5 {
-                            // COMBINATION: read variable-length stack
-                            // Format: VarUInt count, then count x UInt8 kinds
-                            let count = reader.try_get_var_uint()?;
-                            for _ in 0..count {
-                                let _inner_kind = reader.try_get_u8()?;
-                            }
-                            // For now, treat combinations containing SPARSE (kind=1) as sparse
-                            // This is a simplification - proper handling would inspect the stack
-                            1 // Treat as SPARSE for now
-                        } else {
-                            kind
-                        }
-                    } else {
-                        0 // DEFAULT
-                    }
-                } else {
-                    0 // DEFAULT (no custom serialization support)
-                };
-            let is_sparse = serialization_kind == 1; // SPARSE
+            // Check for sparse/custom serialization, including the kinds of nested tuple elements
+            let kinds = if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
+                read_serialization_kinds_sync(reader, &type_hint)?
+            } else {
+                SerializationKinds::default() // No custom serialization support
+            };
+            let is_sparse = kinds.sparse;
+
+            // Columns outside the projection are skipped without decoding when possible
+            let projected = deser.is_projected(field.name());
+            if !projected && (rows == 0 || (!kinds.is_custom() && skip::is_skippable(&type_hint))) {
+                if rows > 0 {
+                    skip::skip(&type_hint, reader, rows)?;
+                }
+                continue;
+            }
 
             let array = if rows > 0 {
                 let dt = field.data_type();
@@ -435,8 +409,7 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
 
                 if is_sparse {
                     // Sparse serialization: read offsets first, then only non-default values
-                    let mut sparse_state = SparseDeserializeState::default();
-                    let offsets = read_sparse_offsets_sync(reader, rows, &mut sparse_state)?;
+                    let offsets = read_sparse_offsets_sync(reader, rows)?;
                     let sparse_rows = offsets.len();
 
                     if debug_arrow() {
@@ -449,10 +422,13 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
                     }
 
                     // Deserialize only the non-default values
-                    let builder = if let Some(b) = builders.get_mut(i) {
+                    let builder = if let Some(b) = builders.get_mut(slot) {
                         b
                     } else {
-                        builders.push(TypedBuilder::try_new(&type_hint, dt)?);
+                        builders.push(
+                            TypedBuilder::try_new(&type_hint, dt)
+                                .map_err(|e| e.with_column(field.name(), i))?,
+                        );
                         builders.last_mut().unwrap()
                     };
 
@@ -461,33 +437,260 @@ impl ProtocolData<RecordBatch, ArrowDeserializerState> for RecordBatch {
                         .deserialize_arrow(builder, reader, dt, sparse_rows, &[], &mut deser.buffer)
                         .inspect_err(|error| {
                             error!(?error, ?type_hint, ?field, "sparse deserialize {i}");
-                        })?;
+                        })
+                        .map_err(|e| e.with_column(field.name(), i))?;
+                    builder.retain_capacity(&sparse_array, options.retained_builder_rows);
 
-                    // Expand sparse array to full size with defaults
-                    expand_sparse_array(&sparse_array, &offsets, rows)?
+                    if options.sparse_as_run_end_encoded {
+                        encode_sparse_array(&sparse_array, &offsets, rows)?
+                    } else {
+                        // Expand sparse array to full size with defaults
+                        expand_sparse_array(&sparse_array, &offsets, rows)?
+                    }
                 } else {
                     // Normal (non-sparse) deserialization
-                    let builder = if let Some(b) = builders.get_mut(i) {
+                    let builder = if let Some(b) = builders.get_mut(slot) {
                         b
                     } else {
-                        builders.push(TypedBuilder::try_new(&type_hint, dt)?);
+                        builders.push(
+                            TypedBuilder::try_new(&type_hint, dt)
+                                .map_err(|e| e.with_column(field.name(), i))?,
+                        );
                         builders.last_mut().unwrap()
                     };
 
                     type_hint.deserialize_prefix(reader)?;
-                    type_hint
-                        .deserialize_arrow(builder, reader, dt, rows, &[], &mut deser.buffer)
-                        .inspect_err(|error| error!(?error, ?type_hint, ?field, "deserialize {i}"))?
+                    // Tuple elements may still be sparse
+                    let array = sparse::deserialize(
+                        &type_hint,
+                        &kinds,
+                        builder,
+                        reader,
+                        dt,
+                        rows,
+                        &mut deser.buffer,
+                    )
+                    .inspect_err(|error| error!(?error, ?type_hint, ?field, "deserialize {i}"))
+                    .map_err(|e| e.with_column(field.name(), i))?;
+                    // Presize the builder for the next block of the query
+                    builder.retain_capacity(&array, options.retained_builder_rows);
+                    array
                 }
             } else {
                 new_empty_array(field.data_type())
             };
+            slot += 1;
 
-            let _ = deser.push_array(array).push_field(Arc::new(field));
+            // Unskippable columns outside the projection are decoded and dropped
+            if !projected {
+                continue;
+            }
+
+            // Run end encoded columns change the field's type
+            let array = encode_dense_array(array, options)?;
+            let field = if array.data_type() == field.data_type() {
+                field
+            } else {
+                Arc::new(Field::clone(&field).with_data_type(array.data_type().clone()))
+            };
+            let (field, array) = deser.rescale(field, array, i)?;
+            let _ = deser.push_array(array).push_field(field);
         }
+        // Release scratch space beyond the retained capacity, with i128 as the upper bound
+        deser.buffer.shrink_to(options.retained_builder_rows.saturating_mul(16));
 
         let (fields, arrays) = deser.take();
-        Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+        // Surface block info (e.g. GROUP BY overflow rows) via schema metadata, if configured
+        let mut schema = Schema::new(fields);
+        if options.block_info_metadata {
+            schema = schema.with_metadata(info.to_metadata());
+        }
+        // The row count must be explicit in case the projection excludes every column
+        let batch_options = RecordBatchOptions::new().with_row_count(Some(rows));
+        Ok(RecordBatch::try_new_with_options(Arc::new(schema), arrays, &batch_options)?)
+    }
+}
+
+/// The header type of column `i` named `name`.
+///
+/// Result column names may repeat (e.g. `SELECT 1, 1`), so the column at the same position is
+/// preferred over the first column of the same name.
+pub(super) fn header_type<'a>(
+    header: &'a [(String, Type)],
+    i: usize,
+    name: &str,
+) -> Option<&'a Type> {
+    header
+        .get(i)
+        .filter(|(n, _)| n == name)
+        .or_else(|| header.iter().find(|(n, _)| n == name))
+        .map(|(_, t)| t)
+}
+
+/// The maximum number of null row indices reported by [`NullPolicy::Error`].
+const MAX_REPORTED_NULL_ROWS: usize = 10;
+
+/// Apply the configured [`NullPolicy`] to a column whose target type cannot hold nulls.
+///
+/// Returns `None` if the column can be serialized as is, otherwise the column and type to write.
+///
+/// # Errors
+/// - Returns `ArrowSerialize` if the policy is [`NullPolicy::Error`] and the column has nulls.
+/// - Returns `Arrow` if default values cannot be substituted for the column's data type.
+pub(super) fn resolve_nulls(
+    name: &str,
+    column: &ArrayRef,
+    type_: &Type,
+    options: ArrowOptions,
+) -> Result<Option<(ArrayRef, Type)>> {
+    if column.logical_null_count() == 0 || accepts_nulls(type_, options) {
+        return Ok(None);
+    }
+
+    match options.null_policy {
+        NullPolicy::Error => {
+            let count = column.logical_null_count();
+            let rows: Vec<usize> = column
+                .logical_nulls()
+                .map(|n| {
+                    (0..n.len()).filter(|&i| n.is_null(i)).take(MAX_REPORTED_NULL_ROWS).collect()
+                })
+                .unwrap_or_default();
+            let more = if count > rows.len() { ", ..." } else { "" };
+            Err(Error::ArrowSerialize(format!(
+                "Column {name} of non-nullable type {type_} contains {count} null(s) at rows \
+                 {rows:?}{more}"
+            )))
+        }
+        NullPolicy::Default => {
+            let mask = arrow::compute::is_not_null(column)?;
+            let default = Scalar::new(default_array(column.data_type())?);
+            let filled = arrow::compute::kernels::zip::zip(&mask, column, &default)?;
+            Ok(Some((filled, type_.clone())))
+        }
+        NullPolicy::Nullable => Ok(Some((Arc::clone(column), type_.clone().into_nullable()))),
+    }
+}
+
+/// Reinterpret a timezone-naive timestamp column as wall-clock time in the timezone of its
+/// `DateTime`/`DateTime64` target, if [`ArrowOptions::localize_naive_timestamps`] is enabled.
+///
+/// Returns `None` if the column can be serialized as is.
+///
+/// # Errors
+/// - Returns `Arrow` if a local time does not exist or is ambiguous in the target timezone, ie
+///   falls within a DST transition, instead of silently turning it into a null.
+pub(super) fn localize_timestamps(
+    column: &ArrayRef,
+    type_: &Type,
+    options: ArrowOptions,
+) -> Result<Option<ArrayRef>> {
+    if !options.localize_naive_timestamps {
+        return Ok(None);
+    }
+    let DataType::Timestamp(unit, None) = column.data_type() else {
+        return Ok(None);
+    };
+    let (Type::DateTime(tz) | Type::DateTime64(_, tz)) = type_.strip_null() else {
+        return Ok(None);
+    };
+    if *tz == chrono_tz::Tz::UTC {
+        return Ok(None);
+    }
+    let localized = DataType::Timestamp(*unit, Some(Arc::from(tz.name())));
+    let options = arrow::compute::CastOptions { safe: false, ..Default::default() };
+    Ok(Some(arrow::compute::cast_with_options(column, &localized, &options)?))
+}
+
+/// Whether a column of `type_` can be sent with nulls.
+///
+/// Arrays are excluded as `nullable_array_default_empty` governs how their nulls are written.
+fn accepts_nulls(type_: &Type, options: ArrowOptions) -> bool {
+    match type_ {
+        Type::Nullable(_) | Type::Variant(_) | Type::Dynamic { .. } | Type::Object => true,
+        Type::LowCardinality(inner) => inner.is_nullable(),
+        Type::Array(_) => options.nullable_array_default_empty,
+        _ => false,
+    }
+}
+
+/// A single-row array holding the default value of `data_type`.
+fn default_array(data_type: &DataType) -> Result<ArrayRef> {
+    let data = match data_type {
+        // Key 0 must point at a default value
+        DataType::Dictionary(key_type, value_type) => {
+            let values = default_array(value_type)?;
+            let keys = ArrayData::new_null(key_type, 1);
+            ArrayData::builder(data_type.clone())
+                .len(1)
+                .add_buffer(keys.buffers()[0].clone())
+                .add_child_data(values.to_data())
+                .build()?
+        }
+        DataType::Struct(fields) => {
+            let children = fields
+                .iter()
+                .map(|f| default_array(f.data_type()).map(|a| a.to_data()))
+                .collect::<Result<Vec<_>>>()?;
+            ArrayData::builder(data_type.clone()).len(1).child_data(children).build()?
+        }
+        // Zeroed buffers are the default for primitives, and empty for variable length types
+        _ => ArrayData::new_null(data_type, 1).into_builder().nulls(None).build()?,
+    };
+    Ok(make_array(data))
+}
+
+/// Decode the data of a column of `rows` rows, following its name, type, and serialization kinds.
+///
+/// # Errors
+/// - Returns `ArrowDeserialize` if the builder or data type do not match the column's type.
+/// - Returns `SparseOffsets` if sparse offsets are malformed.
+#[expect(clippy::too_many_arguments)]
+pub(super) async fn read_column_async<R: ClickHouseRead>(
+    reader: &mut R,
+    field: &Field,
+    type_hint: &Type,
+    kinds: &SerializationKinds,
+    rows: usize,
+    options: ArrowOptions,
+    builder: &mut TypedBuilder,
+    buffer: &mut Vec<u8>,
+) -> Result<ArrayRef> {
+    let dt = field.data_type();
+    let mut prefix_state = DeserializerState::default();
+
+    if kinds.sparse {
+        // Sparse serialization: read offsets first, then only non-default values
+        let offsets = read_sparse_offsets(reader, rows).await?;
+        let sparse_rows = offsets.len();
+
+        if debug_arrow() {
+            trace!(?field, total_rows = rows, sparse_rows, "deserializing sparse column");
+        }
+
+        // Deserialize only the non-default values
+        type_hint.deserialize_prefix_async(reader, &mut prefix_state).await?;
+        let sparse_array = type_hint
+            .deserialize_arrow_async(builder, reader, dt, sparse_rows, &[], buffer)
+            .await
+            .inspect_err(|error| error!(?error, ?field, "sparse deserialize"))?;
+        builder.retain_capacity(&sparse_array, options.retained_builder_rows);
+
+        if options.sparse_as_run_end_encoded {
+            encode_sparse_array(&sparse_array, &offsets, rows)
+        } else {
+            // Expand sparse array to full size with defaults
+            expand_sparse_array(&sparse_array, &offsets, rows)
+        }
+    } else {
+        // Normal (non-sparse) deserialization
+        type_hint.deserialize_prefix_async(reader, &mut prefix_state).await?;
+        // Tuple elements may still be sparse
+        let array =
+            sparse::deserialize_async(type_hint, kinds, builder, reader, dt, rows, buffer).await?;
+        // Presize the builder for the next block of the query
+        builder.retain_capacity(&array, options.retained_builder_rows);
+        Ok(array)
     }
 }
 
@@ -519,6 +722,49 @@ mod tests {
         .unwrap()
     }
 
+    // Helper to create a batch mixing skippable and unskippable (`LowCardinality`) columns
+    pub(super) fn create_projection_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new(
+                "category",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                false,
+            ),
+            Field::new("name", DataType::Utf8, true),
+            Field::new(
+                "tags",
+                DataType::List(Arc::new(Field::new(LIST_ITEM_FIELD_NAME, DataType::Int32, true))),
+                true,
+            ),
+        ]));
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int32Array::from(vec![1, 2, 3])),
+            Arc::new(DictionaryArray::<Int32Type>::from_iter(vec!["cat", "dog", "cat"])),
+            Arc::new(StringArray::from(vec![Some("alice"), None, Some("bob")])),
+            Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+                Some(vec![Some(1), Some(2)]),
+                None,
+                Some(vec![Some(3)]),
+            ])),
+        ])
+        .unwrap()
+    }
+
+    /// Asserts that `projected` holds the `category` and `tags` columns of
+    /// [`create_projection_batch`], in that order.
+    pub(super) fn assert_projected(projected: &RecordBatch, batch: &RecordBatch) {
+        assert_eq!(projected.num_rows(), batch.num_rows());
+        let schema = projected.schema();
+        let names = schema.fields().iter().map(|f| f.name().as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["category", "tags"]);
+        assert_eq!(
+            cast(projected.column(0), &DataType::Utf8).unwrap().as_ref(),
+            cast(batch.column(1), &DataType::Utf8).unwrap().as_ref()
+        );
+        assert_eq!(projected.column(1).as_ref(), batch.column(3).as_ref());
+    }
+
     #[tokio::test]
     async fn test_serialize_record_batch() {
         let batch = create_test_batch();
@@ -567,6 +813,149 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_deserialize_projection() {
+        let batch = create_projection_batch();
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut buffer = Vec::new();
+        for _ in 0..2 {
+            batch
+                .clone()
+                .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+                .await
+                .unwrap();
+        }
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        state.deserializer().projection =
+            Some(["tags", "category", "missing"].map(String::from).into());
+        let mut reader = Cursor::new(buffer);
+        // Builders must stay aligned with the projected columns across blocks
+        for _ in 0..2 {
+            let projected = RecordBatch::read_async(
+                &mut reader,
+                DBMS_TCP_PROTOCOL_VERSION,
+                arrow_options,
+                &mut state,
+            )
+            .await
+            .unwrap();
+            assert_projected(&projected, &batch);
+        }
+        assert_eq!(reader.position(), reader.get_ref().len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_reuses_headers() {
+        let batch = create_test_batch();
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut buffer = Vec::new();
+        for _ in 0..2 {
+            batch
+                .clone()
+                .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+                .await
+                .unwrap();
+        }
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        let mut reader = Cursor::new(buffer);
+        let mut schemas = Vec::new();
+        for _ in 0..2 {
+            let read = RecordBatch::read_async(
+                &mut reader,
+                DBMS_TCP_PROTOCOL_VERSION,
+                arrow_options,
+                &mut state,
+            )
+            .await
+            .unwrap();
+            assert_eq!(read.schema(), batch.schema());
+            schemas.push(read.schema());
+        }
+        // Both blocks share the fields decoded from the first block's headers
+        for (first, second) in schemas[0].fields().iter().zip(schemas[1].fields()) {
+            assert!(Arc::ptr_eq(first, second));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_projection_no_columns() {
+        let batch = create_projection_batch();
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut buffer = Vec::new();
+        batch
+            .clone()
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .await
+            .unwrap();
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        state.deserializer().projection = Some(Arc::from([]));
+        let mut reader = Cursor::new(buffer);
+        let projected = RecordBatch::read_async(
+            &mut reader,
+            DBMS_TCP_PROTOCOL_VERSION,
+            arrow_options,
+            &mut state,
+        )
+        .await
+        .unwrap();
+        assert_eq!(projected.num_columns(), 0);
+        assert_eq!(projected.num_rows(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_decimal_rescale() {
+        use crate::arrow::{DecimalOverflow, DecimalRescale};
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("price", DataType::Decimal128(38, 10), false),
+            Field::new("qty", DataType::Decimal128(38, 10), false),
+        ]));
+        // 1.23456789, 999999.00005 and 10^20 at scale 10
+        let values = vec![12_345_678_900, 9_999_990_000_500_000, 10_i128.pow(30)];
+        let column = Decimal128Array::from(values).with_precision_and_scale(38, 10).unwrap();
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(column.clone()) as ArrayRef,
+            Arc::new(column),
+        ])
+        .unwrap();
+
+        let arrow_options = ArrowOptions::default();
+        let mut buffer = Vec::new();
+        batch
+            .clone()
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .await
+            .unwrap();
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        state.deserializer().decimal_rescale = Some(Arc::from([(
+            "price".to_string(),
+            DecimalRescale::new(18, 4).with_overflow(DecimalOverflow::Null),
+        )]));
+        let mut reader = Cursor::new(buffer);
+        let rescaled = RecordBatch::read_async(
+            &mut reader,
+            DBMS_TCP_PROTOCOL_VERSION,
+            arrow_options,
+            &mut state,
+        )
+        .await
+        .unwrap();
+
+        let price = rescaled.schema().field(0).clone();
+        assert_eq!(price.data_type(), &DataType::Decimal128(18, 4));
+        assert!(price.is_nullable());
+        let expected = Decimal128Array::from(vec![Some(12_346), Some(9_999_990_001), None])
+            .with_precision_and_scale(18, 4)
+            .unwrap();
+        assert_eq!(rescaled.column(0).as_ref(), &expected as &dyn Array);
+        // Columns without a rescale are decoded unchanged
+        assert_eq!(rescaled.column(1).as_ref(), batch.column(1).as_ref());
+    }
+
     #[tokio::test]
     async fn test_serialize_empty_batch() {
         let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
@@ -1532,6 +1921,141 @@ mod tests {
         );
     }
 
+    /// A `RecordBatch` of 64-bit offset string, binary, and list columns.
+    pub(super) fn large_offsets_batch() -> RecordBatch {
+        let item = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, DataType::LargeUtf8, true));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::LargeUtf8, true),
+            Field::new("data", DataType::LargeBinary, false),
+            Field::new("tags", DataType::LargeList(Arc::clone(&item)), false),
+        ]));
+        let tags = LargeListArray::new(
+            item,
+            OffsetBuffer::<i64>::from_lengths([2, 0, 1]),
+            Arc::new(LargeStringArray::from(vec![Some("a"), None, Some("c")])),
+            None,
+        );
+        RecordBatch::try_new(schema, vec![
+            Arc::new(LargeStringArray::from(vec![Some("x"), None, Some("z")])),
+            Arc::new(LargeBinaryArray::from_vec(vec![b"\xFF\xFE" as &[u8], b"", b"\x00"])),
+            Arc::new(tags),
+        ])
+        .unwrap()
+    }
+
+    /// A `MapArray` of `keys` to `values` split into maps of `lengths` entries.
+    fn map_array(keys: ArrayRef, values: ArrayRef, lengths: &[usize]) -> ArrayRef {
+        let fields = Fields::from(vec![
+            Field::new(STRUCT_KEY_FIELD_NAME, keys.data_type().clone(), false),
+            Field::new(STRUCT_VALUE_FIELD_NAME, values.data_type().clone(), false),
+        ]);
+        let entries = StructArray::new(fields.clone(), vec![keys, values], None);
+        let field = Arc::new(Field::new(MAP_FIELD_NAME, DataType::Struct(fields), false));
+        let offsets = OffsetBuffer::<i32>::from_lengths(lengths.iter().copied());
+        Arc::new(MapArray::new(field, offsets, entries, None, false))
+    }
+
+    /// The `UUID` keys of [`map_keys_batch`].
+    pub(super) const MAP_KEY_UUIDS: [&str; 2] =
+        ["550e8400-e29b-41d4-a716-446655440000", "6ba7b810-9dad-11d1-80b4-00c04fd430c8"];
+
+    /// A `RecordBatch` of map columns whose keys need converting to the key types of the header.
+    pub(super) fn map_keys_batch() -> (RecordBatch, Vec<(String, Type)>) {
+        let columns = vec![
+            map_array(
+                Arc::new(Int64Array::from(vec![1, 2, 300])),
+                Arc::new(StringArray::from(vec!["a", "b", "c"])),
+                &[2, 1],
+            ),
+            map_array(
+                Arc::new(StringArray::from(MAP_KEY_UUIDS.to_vec())),
+                Arc::new(Int32Array::from(vec![1, 2])),
+                &[1, 1],
+            ),
+            map_array(
+                Arc::new(Date32Array::from(vec![19_000, 19_001])),
+                Arc::new(Int32Array::from(vec![1, 2])),
+                &[0, 2],
+            ),
+        ];
+        let batch =
+            RecordBatch::try_from_iter(["by_id", "by_uuid", "by_day"].into_iter().zip(columns))
+                .unwrap();
+        let header = vec![
+            ("by_id".to_string(), Type::Map(Box::new(Type::UInt16), Box::new(Type::String))),
+            ("by_uuid".to_string(), Type::Map(Box::new(Type::Uuid), Box::new(Type::Int32))),
+            ("by_day".to_string(), Type::Map(Box::new(Type::Date), Box::new(Type::Int32))),
+        ];
+        (batch, header)
+    }
+
+    /// Check the keys of a `RecordBatch` read back from [`map_keys_batch`].
+    pub(super) fn assert_map_keys(batch: &RecordBatch) {
+        assert_eq!(batch.num_rows(), 2);
+        let uuids = MAP_KEY_UUIDS.map(|u| uuid::Uuid::parse_str(u).unwrap().into_bytes());
+        let expected: [ArrayRef; 3] = [
+            Arc::new(UInt16Array::from(vec![1, 2, 300])),
+            Arc::new(FixedSizeBinaryArray::try_from_iter(uuids.into_iter()).unwrap()),
+            Arc::new(Date32Array::from(vec![19_000, 19_001])),
+        ];
+        for (column, expected) in batch.columns().iter().zip(expected) {
+            assert_eq!(column.as_map().keys(), &expected);
+        }
+        assert_eq!(batch.column(2).as_map().value_offsets(), &[0, 0, 2]);
+    }
+
+    /// Tests round-trip serialization and deserialization of maps with integer, `UUID` and date
+    /// keys written from other Arrow types.
+    #[tokio::test]
+    async fn test_round_trip_map_key_types() {
+        let (batch, header) = map_keys_batch();
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut buffer = Cursor::new(Vec::new());
+        batch
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
+            .await
+            .unwrap();
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        let mut reader = Cursor::new(buffer.into_inner());
+        let deserialized = RecordBatch::read_async(
+            &mut reader,
+            DBMS_TCP_PROTOCOL_VERSION,
+            arrow_options,
+            &mut state,
+        )
+        .await
+        .unwrap();
+        assert_map_keys(&deserialized);
+    }
+
+    /// Tests round-trip serialization and deserialization of `LargeUtf8`, `LargeBinary` and
+    /// `LargeList` columns with `large_offsets`.
+    #[tokio::test]
+    async fn test_round_trip_large_offsets() {
+        let batch = large_offsets_batch();
+        let arrow_options =
+            ArrowOptions::default().with_strings_as_strings(true).with_large_offsets(true);
+        let mut buffer = Cursor::new(Vec::new());
+        batch
+            .clone()
+            .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+            .await
+            .unwrap();
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        let mut reader = Cursor::new(buffer.into_inner());
+        let deserialized = RecordBatch::read_async(
+            &mut reader,
+            DBMS_TCP_PROTOCOL_VERSION,
+            arrow_options,
+            &mut state,
+        )
+        .await
+        .unwrap();
+        assert_eq!(deserialized, batch);
+    }
+
     /// Tests round-trip serialization and deserialization of a `RecordBatch` with max/min Int32
     /// values.
     #[tokio::test]
@@ -1590,10 +2114,11 @@ mod tests {
             .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options)
             .await;
         assert!(matches!(
-            result,
+            result.as_ref().map_err(Error::root_cause),
             Err(Error::ArrowSerialize(e))
             if e.contains("Expected one of")
         ));
+        assert_eq!(result.unwrap_err().column(), Some(("id", 0)));
     }
 
     /// Test low cardinality nullable string round trip
@@ -1686,6 +2211,20 @@ mod tests {
             .as_ref()
         );
     }
+
+    #[test]
+    fn test_header_type_prefers_position() {
+        let header = vec![
+            ("1".to_string(), Type::UInt8),
+            ("x".to_string(), Type::String),
+            ("1".to_string(), Type::Int64),
+        ];
+        assert_eq!(header_type(&header, 0, "1"), Some(&Type::UInt8));
+        assert_eq!(header_type(&header, 2, "1"), Some(&Type::Int64));
+        // Falls back to a lookup by name when positions differ
+        assert_eq!(header_type(&header, 0, "x"), Some(&Type::String));
+        assert_eq!(header_type(&header, 1, "missing"), None);
+    }
 }
 
 #[cfg(test)]
@@ -1694,7 +2233,7 @@ mod tests_sync {
     use std::sync::Arc;
 
     use arrow::array::*;
-    use arrow::buffer::OffsetBuffer;
+    use arrow::buffer::{NullBuffer, OffsetBuffer};
     use arrow::compute::cast;
     use arrow::datatypes::*;
     use bytes::BufMut;
@@ -1751,6 +2290,152 @@ mod tests_sync {
         }
     }
 
+    #[test]
+    fn test_deserialize_projection() {
+        let batch = super::tests::create_projection_batch();
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut buffer = Vec::new();
+        for _ in 0..2 {
+            batch
+                .clone()
+                .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+                .unwrap();
+        }
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        state.deserializer().projection =
+            Some(["tags", "category", "missing"].map(String::from).into());
+        let mut reader = Cursor::new(buffer);
+        // Builders must stay aligned with the projected columns across blocks
+        for _ in 0..2 {
+            let projected = RecordBatch::read(
+                &mut reader,
+                DBMS_TCP_PROTOCOL_VERSION,
+                arrow_options,
+                &mut state,
+            )
+            .unwrap();
+            super::tests::assert_projected(&projected, &batch);
+        }
+        assert_eq!(reader.position(), reader.get_ref().len() as u64);
+    }
+
+    #[test]
+    fn test_deserialize_reuses_headers() {
+        let batch = create_test_batch();
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut buffer = Vec::new();
+        for _ in 0..2 {
+            batch
+                .clone()
+                .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options)
+                .unwrap();
+        }
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        let mut reader = Cursor::new(buffer);
+        let mut read = || {
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, arrow_options, &mut state)
+                .unwrap()
+        };
+        let (first, second) = (read(), read());
+        assert_eq!(first.schema(), batch.schema());
+        assert_eq!(second.schema(), batch.schema());
+        for (a, b) in first.schema().fields().iter().zip(second.schema().fields()) {
+            assert!(Arc::ptr_eq(a, b));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_sparse_column_run_end_encoded() {
+        // Column `v` of 6 rows, [0, 0, 7, 0, 9, 0], sent with sparse serialization
+        let mut buffer = Vec::new();
+        BlockInfo::default().write(&mut buffer).unwrap();
+        buffer.put_var_uint(1).unwrap();
+        buffer.put_var_uint(6).unwrap();
+        buffer.put_string("v").unwrap();
+        buffer.put_string("Int32").unwrap();
+        buffer.put_u8(1); // has custom serialization
+        buffer.put_u8(1); // SPARSE
+        buffer.put_var_uint(2).unwrap();
+        buffer.put_var_uint(1).unwrap();
+        buffer.put_var_uint(1 | crate::native::sparse::END_OF_GRANULE_FLAG).unwrap();
+        buffer.put_i32_le(7);
+        buffer.put_i32_le(9);
+
+        let read = |options: ArrowOptions| {
+            let mut state = DeserializerState::default().with_arrow_options(options);
+            let mut reader = Cursor::new(buffer.clone());
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state).unwrap()
+        };
+
+        let dense = read(ArrowOptions::default());
+        assert_eq!(dense.schema().field(0).data_type(), &DataType::Int32);
+        assert_eq!(dense.column(0).as_primitive::<Int32Type>().values(), &[0, 0, 7, 0, 9, 0]);
+
+        let encoded = read(ArrowOptions::default().with_sparse_as_run_end_encoded(true));
+        assert!(matches!(encoded.schema().field(0).data_type(), DataType::RunEndEncoded(_, _)));
+        let run_array = encoded.column(0).as_run::<Int32Type>();
+        assert_eq!(run_array.len(), 6);
+        assert_eq!(run_array.run_ends().values(), &[2, 3, 4, 5, 6]);
+        assert_eq!(run_array.values().as_primitive::<Int32Type>().values(), &[0, 7, 0, 9, 0]);
+
+        // A block of the same column sent dense has the same type
+        let options = ArrowOptions::default().with_sparse_as_run_end_encoded(true);
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("v", DataType::Int32, false)])),
+            vec![Arc::new(Int32Array::from(vec![7, 7, 0]))],
+        )
+        .unwrap();
+        let mut dense_buffer = Vec::new();
+        batch.write(&mut dense_buffer, DBMS_TCP_PROTOCOL_VERSION, None, options).unwrap();
+        let mut state = DeserializerState::default().with_arrow_options(options);
+        let mut reader = Cursor::new(dense_buffer);
+        let dense =
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state).unwrap();
+        assert_eq!(dense.schema().field(0).data_type(), encoded.schema().field(0).data_type());
+        let run_array = dense.column(0).as_run::<Int32Type>();
+        assert_eq!(run_array.run_ends().values(), &[2, 3]);
+        assert_eq!(run_array.values().as_primitive::<Int32Type>().values(), &[7, 0]);
+    }
+
+    #[test]
+    fn test_deserialize_block_info_metadata() {
+        let batch = create_test_batch();
+        let options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut encoded = Vec::new();
+        batch.clone().write(&mut encoded, DBMS_TCP_PROTOCOL_VERSION, None, options).unwrap();
+
+        // Block info is not surfaced unless enabled
+        let mut state = DeserializerState::default().with_arrow_options(options);
+        let mut reader = Cursor::new(encoded.clone());
+        let read =
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state).unwrap();
+        assert!(read.schema().metadata().is_empty());
+
+        // Once enabled, default block info is surfaced too, so schemas don't vary within a stream
+        let options = options.with_block_info_metadata(true);
+        let mut state = DeserializerState::default().with_arrow_options(options);
+        let mut reader = Cursor::new(encoded.clone());
+        let read =
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state).unwrap();
+        assert_eq!(BlockInfo::from_metadata(read.schema().metadata()), Some(BlockInfo::default()));
+
+        // Replace the default block info with an overflow block
+        let mut default_info = Vec::new();
+        BlockInfo::default().write(&mut default_info).unwrap();
+        let info = BlockInfo { is_overflows: true, bucket_num: 5 };
+        let mut buffer = Vec::new();
+        info.write(&mut buffer).unwrap();
+        buffer.extend_from_slice(&encoded[default_info.len()..]);
+
+        let mut reader = Cursor::new(buffer);
+        let read =
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state).unwrap();
+        assert_eq!(read.num_rows(), batch.num_rows());
+        assert_eq!(BlockInfo::from_metadata(read.schema().metadata()), Some(info));
+    }
+
     #[test]
     fn test_serialize_empty_batch() {
         let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
@@ -1830,6 +2515,31 @@ mod tests_sync {
         assert!(matches!(result, Err(Error::Protocol(_))));
     }
 
+    #[test]
+    fn test_deserialize_error_column_context() {
+        let mut buffer = Vec::new();
+        BlockInfo::default().write(&mut buffer).unwrap();
+        buffer.put_var_uint(2).unwrap(); // Columns
+        buffer.put_var_uint(0).unwrap(); // Rows
+        buffer.put_string("a").unwrap();
+        buffer.put_string("Int32").unwrap();
+        buffer.push(0); // Serialization kind
+        buffer.put_string("b").unwrap();
+        buffer.put_string("NotAType").unwrap();
+        let mut state = DeserializerState::default();
+        let result = RecordBatch::read(
+            &mut Cursor::new(buffer),
+            DBMS_TCP_PROTOCOL_VERSION,
+            ArrowOptions::default(),
+            &mut state,
+        );
+
+        let error = result.unwrap_err();
+        assert_eq!(error.column(), Some(("b", 1)));
+        assert!(matches!(error.root_cause(), Error::TypeParseError(_)));
+        assert!(error.to_string().starts_with("column 1 'b':"), "{error}");
+    }
+
     /// Tests round-trip serialization and deserialization of a single-column `Int32` `RecordBatch`
     #[test]
     fn test_round_trip_single_column_int32() {
@@ -2548,6 +3258,47 @@ mod tests_sync {
         );
     }
 
+    /// Tests round-trip serialization and deserialization of `LargeUtf8`, `LargeBinary` and
+    /// `LargeList` columns, read back with 32-bit offsets when `large_offsets` is off.
+    #[test]
+    fn test_round_trip_large_offsets() {
+        let batch = super::tests::large_offsets_batch();
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut buffer = Vec::new();
+        batch.clone().write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, arrow_options).unwrap();
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        let mut reader = Cursor::new(buffer);
+        let deserialized =
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, arrow_options, &mut state)
+                .unwrap();
+
+        let schema = deserialized.schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(1).data_type(), &DataType::Binary);
+        assert!(matches!(schema.field(2).data_type(), DataType::List(_)));
+        for (column, expected) in deserialized.columns().iter().zip(batch.columns()) {
+            assert_eq!(&cast(column, expected.data_type()).unwrap(), expected);
+        }
+    }
+
+    /// Tests round-trip serialization and deserialization of maps with integer, `UUID` and date
+    /// keys written from other Arrow types.
+    #[test]
+    fn test_round_trip_map_key_types() {
+        let (batch, header) = super::tests::map_keys_batch();
+        let arrow_options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut buffer = Vec::new();
+        batch.write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), arrow_options).unwrap();
+
+        let mut state = DeserializerState::default().with_arrow_options(arrow_options);
+        let mut reader = Cursor::new(buffer);
+        let deserialized =
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, arrow_options, &mut state)
+                .unwrap();
+        super::tests::assert_map_keys(&deserialized);
+    }
+
     /// Tests round-trip serialization and deserialization of a `RecordBatch` with max/min Int32
     /// values.
     #[test]
@@ -2599,10 +3350,11 @@ mod tests_sync {
             arrow_options,
         );
         assert!(matches!(
-            result,
+            result.as_ref().map_err(Error::root_cause),
             Err(Error::ArrowSerialize(e))
             if e.contains("Expected one of")
         ));
+        assert_eq!(result.unwrap_err().column(), Some(("id", 0)));
     }
 
     /// Test low cardinality nullable string round trip
@@ -2687,4 +3439,231 @@ mod tests_sync {
             .as_ref()
         );
     }
+
+    fn nulls_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int32Array::from(vec![Some(1), None, Some(3), None])),
+            Arc::new(StringArray::from(vec![None, Some("b"), Some("c"), Some("d")])),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_null_policy_error_reports_rows() {
+        let header = vec![("id".to_string(), Type::Int32), ("name".to_string(), Type::String)];
+        let mut buffer = Vec::new();
+        let result = nulls_batch().write(
+            &mut buffer,
+            DBMS_TCP_PROTOCOL_VERSION,
+            Some(&header),
+            ArrowOptions::default(),
+        );
+        let Err(Error::ArrowSerialize(message)) = result.as_ref().map_err(Error::root_cause) else {
+            panic!("Expected serialize error, got {result:?}");
+        };
+        assert!(message.contains("Column id"), "{message}");
+        assert!(message.contains("2 null(s) at rows [1, 3]"), "{message}");
+    }
+
+    #[test]
+    fn test_null_policy_default_fills_defaults() {
+        let header = vec![("id".to_string(), Type::Int32), ("name".to_string(), Type::String)];
+        let options = ArrowOptions::default()
+            .with_strings_as_strings(true)
+            .with_null_policy(NullPolicy::Default);
+        let mut buffer = Vec::new();
+        nulls_batch()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), options)
+            .unwrap();
+
+        let mut state = DeserializerState::default().with_arrow_options(options);
+        let mut reader = Cursor::new(buffer);
+        let result =
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state).unwrap();
+        assert_eq!(result.schema().field(0).data_type(), &DataType::Int32);
+        assert!(!result.schema().field(0).is_nullable());
+        assert_eq!(result.column(0).as_primitive::<Int32Type>().values(), &[1, 0, 3, 0]);
+        let names = result.column(1).as_string::<i32>().iter().flatten().collect::<Vec<_>>();
+        assert_eq!(names, ["", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_null_policy_nullable_wraps_type() {
+        let header = vec![("id".to_string(), Type::Int32), ("name".to_string(), Type::String)];
+        let options = ArrowOptions::default().with_null_policy(NullPolicy::Nullable);
+        let mut buffer = Vec::new();
+        nulls_batch()
+            .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), options)
+            .unwrap();
+
+        let mut state = DeserializerState::default().with_arrow_options(options);
+        let mut reader = Cursor::new(buffer);
+        let result =
+            RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state).unwrap();
+        assert!(result.schema().field(0).is_nullable());
+        assert_eq!(result.column(0).null_count(), 2);
+        assert_eq!(result.column(1).null_count(), 1);
+    }
+
+    #[test]
+    fn test_localize_naive_timestamps() {
+        let tz = chrono_tz::Tz::America__New_York;
+        let header = vec![("ts".to_string(), Type::DateTime(tz))];
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "ts",
+            DataType::Timestamp(TimeUnit::Second, None),
+            false,
+        )]));
+        // 2024-01-01T12:00:00 as wall-clock time
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(TimestampSecondArray::from(vec![
+            1_704_110_400,
+        ]))])
+        .unwrap();
+
+        let write = |options: ArrowOptions| {
+            let mut buffer = Vec::new();
+            batch
+                .clone()
+                .write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), options)
+                .unwrap();
+            let mut state = DeserializerState::default().with_arrow_options(options);
+            let mut reader = Cursor::new(buffer);
+            let result =
+                RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state)
+                    .unwrap();
+            assert_eq!(
+                result.schema().field(0).data_type(),
+                &DataType::Timestamp(TimeUnit::Second, Some(Arc::from("America/New_York")))
+            );
+            result.column(0).as_primitive::<TimestampSecondType>().value(0)
+        };
+
+        // Naive values are treated as UTC by default
+        assert_eq!(write(ArrowOptions::default()), 1_704_110_400);
+        // Localized, 12:00 in New York is 17:00 UTC
+        let options = ArrowOptions::default().with_localize_naive_timestamps(true);
+        assert_eq!(write(options), 1_704_128_400);
+    }
+
+    #[test]
+    fn test_localize_naive_timestamps_dst_transition() {
+        let type_ = Type::DateTime(chrono_tz::Tz::America__New_York);
+        let options = ArrowOptions::default().with_localize_naive_timestamps(true);
+        let localize = |value: i64| {
+            let column: ArrayRef = Arc::new(TimestampSecondArray::from(vec![value]));
+            localize_timestamps(&column, &type_, options)
+        };
+
+        // 2024-03-10T02:30:00 does not exist, clocks skip from 02:00 to 03:00
+        assert!(localize(1_710_037_800).is_err());
+        // 2024-11-03T01:30:00 is ambiguous, clocks go back from 02:00 to 01:00
+        assert!(localize(1_730_597_400).is_err());
+        // 2024-03-10T03:30:00 EDT is 07:30 UTC
+        let localized = localize(1_710_041_400).unwrap().unwrap();
+        assert_eq!(localized.as_primitive::<TimestampSecondType>().value(0), 1_710_055_800);
+    }
+
+    /// Rows of a list column as strings, with null lists as empty, as `ClickHouse` stores them.
+    fn list_rows(array: &ArrayRef) -> Vec<Vec<Option<String>>> {
+        let list = array.as_list::<i32>();
+        (0..list.len())
+            .map(|i| {
+                if list.is_null(i) {
+                    return vec![];
+                }
+                let values = cast(&list.value(i), &DataType::Utf8).unwrap();
+                values.as_string::<i32>().iter().map(|v| v.map(str::to_string)).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_array_nullable_round_trip() {
+        let int_item = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, DataType::Int32, true));
+        let str_item = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, DataType::Utf8, true));
+        let dict_type = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+        let dict_item = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, dict_type.clone(), true));
+        let header = vec![
+            ("a".to_string(), Type::Array(Box::new(Type::Nullable(Box::new(Type::Int32))))),
+            ("s".to_string(), Type::Array(Box::new(Type::Nullable(Box::new(Type::String))))),
+            (
+                "lc".to_string(),
+                Type::Array(Box::new(Type::LowCardinality(Box::new(Type::Nullable(Box::new(
+                    Type::String,
+                )))))),
+            ),
+        ];
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::List(Arc::clone(&int_item)), true),
+            Field::new("s", DataType::List(Arc::clone(&str_item)), true),
+            Field::new("lc", DataType::List(Arc::clone(&dict_item)), true),
+        ]));
+
+        // Lengths of 0..=3 with every 5th empty, every 11th list null (some referencing values),
+        // and inner values null in runs of 13
+        let batch = |rows: usize, lengths: &dyn Fn(usize) -> usize| {
+            let offsets = OffsetBuffer::from_lengths((0..rows).map(lengths));
+            let total = offsets.last().map_or(0, |&o| o.as_usize());
+            let list_nulls = Some(NullBuffer::from_iter((0..rows).map(|i| i % 11 != 7)));
+            let ints = Int32Array::from_iter(
+                (0..total).map(|v| ((v / 13) % 2 == 1).then_some(i32::try_from(v).unwrap())),
+            );
+            let strings = cast(&ints, &DataType::Utf8).unwrap();
+            let dicts = cast(&strings, &dict_type).unwrap();
+            RecordBatch::try_new(Arc::clone(&schema), vec![
+                Arc::new(ListArray::new(
+                    Arc::clone(&int_item),
+                    offsets.clone(),
+                    Arc::new(ints),
+                    list_nulls.clone(),
+                )),
+                Arc::new(ListArray::new(
+                    Arc::clone(&str_item),
+                    offsets.clone(),
+                    strings,
+                    list_nulls.clone(),
+                )),
+                Arc::new(ListArray::new(Arc::clone(&dict_item), offsets, dicts, list_nulls)),
+            ])
+            .unwrap()
+        };
+
+        let mixed = batch(200, &|i| if i % 5 == 0 { 0 } else { i % 4 });
+        let batches = [
+            mixed.clone(),
+            mixed.slice(13, 100),
+            mixed.slice(199, 1),
+            batch(50, &|_| 0),
+            batch(40, &|i| usize::from(i < 30) * 3),
+        ];
+        let options = ArrowOptions::default();
+        for (i, batch) in batches.into_iter().enumerate() {
+            let expected = batch.columns().iter().map(list_rows).collect::<Vec<_>>();
+            let mut buffer = Vec::new();
+            batch.write(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, Some(&header), options).unwrap();
+
+            let mut state = DeserializerState::default().with_arrow_options(options);
+            let mut reader = Cursor::new(buffer);
+            let result =
+                RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options, &mut state)
+                    .unwrap();
+            assert_eq!(reader.position(), reader.get_ref().len() as u64, "batch {i}");
+            for (column, expected) in result.columns().iter().zip(&expected) {
+                assert_eq!(&list_rows(column), expected, "batch {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_array_dictionary() {
+        let data_type = DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8));
+        let default = default_array(&data_type).unwrap();
+        let dict = default.as_dictionary::<Int8Type>();
+        assert_eq!(dict.keys().values(), &[0]);
+        assert_eq!(dict.values().as_string::<i32>().value(0), "");
+    }
 }
@@ -104,8 +104,10 @@ pub(crate) enum TypedBuilder {
 
     // String and Binary types
     String(StringBuilder),
+    LargeString(LargeStringBuilder),
     Object(StringBuilder),
     Binary(BinaryBuilder),
+    LargeBinary(LargeBinaryBuilder),
     FixedSizeBinary(FixedSizeBinaryBuilder),
 
     // Dictionary types for enums
@@ -172,6 +174,17 @@ impl TypedBuilder {
             return Ok(Self::Map((kbuilder, vbuilder)));
         }
 
+        // 64-bit offsets, see `ArrowOptions::large_offsets`
+        match (type_, data_type) {
+            (Type::String, DataType::LargeUtf8) => {
+                return Ok(Self::LargeString(LargeStringBuilder::with_capacity(ROWS, ROWS * 64)));
+            }
+            (Type::Binary, DataType::LargeBinary) => {
+                return Ok(Self::LargeBinary(LargeBinaryBuilder::with_capacity(ROWS, ROWS * 64)));
+            }
+            _ => {}
+        }
+
         // Rest of the types
         Ok(typed_build!(type_, {
             // Numeric
@@ -290,6 +303,75 @@ impl TypedBuilder {
     }
 }
 
+impl TypedBuilder {
+    /// Reserve capacity for the next block of a query after the builder finished `array`.
+    ///
+    /// Finishing an Arrow builder moves its buffers into the finished array, leaving the builder
+    /// without capacity. Reserving the size of the last block, up to `max_rows` rows, lets the
+    /// next block of a long scan decode without regrowing the buffers. Variable length values are
+    /// reserved in proportion to the bytes of the finished array. Builders of other types grow as
+    /// they are filled.
+    pub(crate) fn retain_capacity(&mut self, array: &dyn Array, max_rows: usize) {
+        let rows = array.len().min(max_rows);
+        if rows == 0 {
+            return;
+        }
+        let data_capacity = |bytes: usize| bytes.saturating_mul(rows) / array.len();
+
+        match self {
+            Self::Int8(b) => b.reserve(rows),
+            Self::Int16(b) => b.reserve(rows),
+            Self::Int32(b) => b.reserve(rows),
+            Self::Int64(b) => b.reserve(rows),
+            Self::UInt8(b) => b.reserve(rows),
+            Self::UInt16(b) => b.reserve(rows),
+            Self::UInt32(b) => b.reserve(rows),
+            Self::UInt64(b) => b.reserve(rows),
+            Self::Float32(b) => b.reserve(rows),
+            Self::Float64(b) => b.reserve(rows),
+            Self::Decimal32(b) | Self::Decimal64(b) | Self::Decimal128(b) => b.reserve(rows),
+            Self::Decimal256(b) => b.reserve(rows),
+            Self::Date(b) | Self::Date32(b) => b.reserve(rows),
+            Self::DateTime(b) | Self::DateTimeS(b) => b.reserve(rows),
+            Self::DateTimeMs(b) => b.reserve(rows),
+            Self::DateTimeMu(b) => b.reserve(rows),
+            Self::DateTimeNano(b) => b.reserve(rows),
+            Self::String(b) | Self::Object(b) => {
+                if let Some(strings) = array.as_string_opt::<i32>() {
+                    let bytes = data_capacity(strings.value_data().len());
+                    *b = StringBuilder::with_capacity(rows, bytes);
+                }
+            }
+            Self::LargeString(b) => {
+                if let Some(strings) = array.as_string_opt::<i64>() {
+                    let bytes = data_capacity(strings.value_data().len());
+                    *b = LargeStringBuilder::with_capacity(rows, bytes);
+                }
+            }
+            Self::Binary(b) => {
+                if let Some(binary) = array.as_binary_opt::<i32>() {
+                    let bytes = data_capacity(binary.value_data().len());
+                    *b = BinaryBuilder::with_capacity(rows, bytes);
+                }
+            }
+            Self::LargeBinary(b) => {
+                if let Some(binary) = array.as_binary_opt::<i64>() {
+                    let bytes = data_capacity(binary.value_data().len());
+                    *b = LargeBinaryBuilder::with_capacity(rows, bytes);
+                }
+            }
+            Self::Tuple(builders) => {
+                if let Some(tuple) = array.as_struct_opt() {
+                    for (builder, column) in builders.iter_mut().zip(tuple.columns()) {
+                        builder.retain_capacity(column.as_ref(), max_rows);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 impl std::fmt::Debug for TypedBuilder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -440,6 +522,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_typed_builder_large_offset_types() {
+        let builder = TypedBuilder::try_new(&Type::String, &DataType::LargeUtf8).unwrap();
+        assert!(matches!(builder, TypedBuilder::LargeString(_)));
+
+        let nullable = Type::Nullable(Box::new(Type::Binary));
+        let builder = TypedBuilder::try_new(&nullable, &DataType::LargeBinary).unwrap();
+        assert!(matches!(builder, TypedBuilder::LargeBinary(_)));
+
+        let list = DataType::LargeList(Arc::new(Field::new("item", DataType::LargeUtf8, true)));
+        let builder = TypedBuilder::try_new(&Type::Array(Box::new(Type::String)), &list).unwrap();
+        let TypedBuilder::List(TypedListBuilder::LargeList(inner)) = builder else {
+            panic!("Expected LargeList builder");
+        };
+        assert!(matches!(*inner, TypedBuilder::LargeString(_)));
+    }
+
     #[test]
     fn test_typed_builder_large_int_types() {
         let test_cases = vec![
@@ -623,4 +722,46 @@ mod tests {
         let builder = TypedBuilder::try_new(&type_, &data_type).unwrap();
         assert!(matches!(builder, TypedBuilder::DateTime(_)));
     }
+
+    #[test]
+    fn test_retain_capacity() {
+        let mut builder = TypedBuilder::try_new(&Type::Int64, &DataType::Int64).unwrap();
+        let TypedBuilder::Int64(b) = &mut builder else { panic!("expected Int64 builder") };
+        b.append_slice(&[1; 100]);
+        let array = b.finish();
+        builder.retain_capacity(&array, 1_000);
+        let TypedBuilder::Int64(b) = &builder else { panic!("expected Int64 builder") };
+        assert!(b.capacity() >= 100);
+
+        // Tuple elements are presized per element, within the limit
+        let fields = Fields::from(vec![
+            Field::new("a", DataType::UInt8, false),
+            Field::new("b", DataType::Float64, false),
+        ]);
+        let type_ = Type::Tuple(vec![Type::UInt8, Type::Float64]);
+        let mut builder = TypedBuilder::try_new(&type_, &DataType::Struct(fields.clone())).unwrap();
+        let TypedBuilder::Tuple(builders) = &mut builder else {
+            panic!("expected Tuple builder")
+        };
+        let [TypedBuilder::UInt8(a), TypedBuilder::Float64(b)] = builders.as_mut_slice() else {
+            panic!("expected tuple element builders");
+        };
+        a.append_slice(&[1; 500]);
+        b.append_slice(&[1.0; 500]);
+        let tuple =
+            StructArray::new(fields, vec![Arc::new(a.finish()), Arc::new(b.finish())], None);
+        builder.retain_capacity(&tuple, 200);
+        let TypedBuilder::Tuple(builders) = &builder else { panic!("expected Tuple builder") };
+        let [TypedBuilder::UInt8(a), TypedBuilder::Float64(b)] = builders.as_slice() else {
+            panic!("expected tuple element builders");
+        };
+        assert!((200..500).contains(&a.capacity()));
+        assert!((200..500).contains(&b.capacity()));
+
+        // A limit of 0 retains nothing
+        let mut builder = TypedBuilder::Int64(Int64Builder::with_capacity(0));
+        builder.retain_capacity(&array, 0);
+        let TypedBuilder::Int64(b) = &builder else { panic!("expected Int64 builder") };
+        assert_eq!(b.capacity(), 0);
+    }
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use arrow::array::*;
 use arrow::datatypes::*;
 
@@ -39,9 +41,32 @@ impl LowCardinalityKeyBuilder {
     }
 }
 
+/// The global dictionary of a `LowCardinality` column.
+///
+/// The server may send a dictionary once and refer to it from the following blocks of the query
+/// until it sends an update. The decoded values are kept with the column's builder, so every block
+/// shares them instead of decoding them again. Builders are dropped when a query finishes and
+/// before the next one starts, so a dictionary never outlives the query it was sent for.
+#[derive(Debug, Default)]
+pub(crate) struct GlobalDictionary {
+    /// Incremented whenever the server sends a new dictionary, zero before the first one
+    pub(crate) version: u64,
+    pub(crate) values:  Option<ArrayRef>,
+}
+
+impl GlobalDictionary {
+    /// Replace the dictionary with `values`, sent by the server.
+    pub(crate) fn update(&mut self, values: ArrayRef) -> ArrayRef {
+        self.version += 1;
+        self.values = Some(Arc::clone(&values));
+        values
+    }
+}
+
 pub(crate) struct LowCardinalityBuilder {
-    pub(crate) key_builder:   LowCardinalityKeyBuilder,
-    pub(crate) value_builder: Box<TypedBuilder>,
+    pub(crate) key_builder:       LowCardinalityKeyBuilder,
+    pub(crate) value_builder:     Box<TypedBuilder>,
+    pub(crate) global_dictionary: GlobalDictionary,
 }
 
 impl LowCardinalityBuilder {
@@ -56,7 +81,11 @@ impl LowCardinalityBuilder {
 
         let key_builder = LowCardinalityKeyBuilder::try_new(key_type)?;
         let value_builder = Box::new(TypedBuilder::try_new(type_, value_type)?);
-        Ok(LowCardinalityBuilder { key_builder, value_builder })
+        Ok(LowCardinalityBuilder {
+            key_builder,
+            value_builder,
+            global_dictionary: GlobalDictionary::default(),
+        })
     }
 }
 
@@ -64,8 +93,8 @@ impl std::fmt::Debug for LowCardinalityBuilder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "LowCardinalityBuilder(key={:?},value={:?})",
-            self.key_builder, self.value_builder
+            "LowCardinalityBuilder(key={:?},value={:?},global_dictionary={})",
+            self.key_builder, self.value_builder, self.global_dictionary.version
         )
     }
 }
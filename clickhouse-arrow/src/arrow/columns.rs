@@ -0,0 +1,147 @@
+//! ## Result column names
+//!
+//! `ClickHouse` names result columns after their alias, or after the expression they were derived
+//! from when unaliased. These names are kept exactly as sent by the server, matching the output of
+//! `clickhouse-client`, which means they are not guaranteed to be unique: `SELECT 1, 1` returns
+//! two columns both named `1`.
+//!
+//! Name based lookups such as [`RecordBatch::column_by_name`] only ever see the first of a
+//! duplicated name. [`ColumnNames`] maps every name to all of the positions it occupies so that
+//! duplicates can be detected and addressed by position instead.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use clickhouse_arrow::arrow::columns::ColumnNames;
+//!
+//! let batch = client.query("SELECT 1, 1, 2 AS two", None).await?.collect_result().await?;
+//! let names = ColumnNames::from_batch(&batch[0]);
+//! assert_eq!(names.positions("1"), &[0, 1]);
+//! assert_eq!(names.duplicates().collect::<Vec<_>>(), vec![("1", &[0, 1][..])]);
+//! ```
+use arrow::array::{ArrayRef, RecordBatch};
+use arrow::datatypes::Schema;
+
+use crate::FxIndexMap;
+
+/// Positions of each column name in a result, in order of first appearance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnNames {
+    positions: FxIndexMap<String, Vec<usize>>,
+    len:       usize,
+}
+
+impl ColumnNames {
+    /// Map the column names of an Arrow schema.
+    pub fn from_schema(schema: &Schema) -> Self {
+        Self::from_names(schema.fields().iter().map(|f| f.name().as_str()))
+    }
+
+    /// Map the column names of a record batch.
+    pub fn from_batch(batch: &RecordBatch) -> Self { Self::from_schema(batch.schema_ref()) }
+
+    /// Map column names given in column order.
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut positions = FxIndexMap::<String, Vec<usize>>::default();
+        let mut len = 0;
+        for (i, name) in names.into_iter().enumerate() {
+            positions.entry(name.to_string()).or_default().push(i);
+            len = i + 1;
+        }
+        Self { positions, len }
+    }
+
+    /// The number of columns mapped, including duplicates.
+    pub fn len(&self) -> usize { self.len }
+
+    /// Whether no columns were mapped.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Every position the column `name` occupies, empty if it is not present.
+    pub fn positions(&self, name: &str) -> &[usize] {
+        self.positions.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether any column name appears more than once.
+    pub fn has_duplicates(&self) -> bool { self.positions.len() != self.len }
+
+    /// Column names appearing more than once, with all of their positions.
+    pub fn duplicates(&self) -> impl Iterator<Item = (&str, &[usize])> {
+        self.positions
+            .iter()
+            .filter(|(_, positions)| positions.len() > 1)
+            .map(|(name, positions)| (name.as_str(), positions.as_slice()))
+    }
+}
+
+/// Name based column access that does not hide duplicated names.
+pub trait ColumnsByName {
+    /// Every column named `name`, in column order.
+    fn columns_by_name(&self, name: &str) -> Vec<&ArrayRef>;
+}
+
+impl ColumnsByName for RecordBatch {
+    fn columns_by_name(&self, name: &str) -> Vec<&ArrayRef> {
+        self.schema_ref()
+            .fields()
+            .iter()
+            .zip(self.columns())
+            .filter(|(field, _)| field.name() == name)
+            .map(|(_, column)| column)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field};
+
+    use super::*;
+
+    fn duplicated_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("1", DataType::Int32, false),
+            Field::new("plus(1, 1)", DataType::Int32, false),
+            Field::new("1", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int32Array::from(vec![1])),
+            Arc::new(Int32Array::from(vec![2])),
+            Arc::new(Int32Array::from(vec![3])),
+            Arc::new(StringArray::from(vec!["a"])),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_column_names_duplicates() {
+        let names = ColumnNames::from_batch(&duplicated_batch());
+        assert_eq!(names.len(), 4);
+        assert!(names.has_duplicates());
+        assert_eq!(names.positions("1"), &[0, 2]);
+        assert_eq!(names.positions("plus(1, 1)"), &[1]);
+        assert!(names.positions("missing").is_empty());
+        assert_eq!(names.duplicates().collect::<Vec<_>>(), vec![("1", &[0, 2][..])]);
+    }
+
+    #[test]
+    fn test_column_names_unique() {
+        let names = ColumnNames::from_names(["a", "b"]);
+        assert!(!names.has_duplicates());
+        assert_eq!(names.duplicates().count(), 0);
+        assert!(ColumnNames::from_names([]).is_empty());
+    }
+
+    #[test]
+    fn test_columns_by_name() {
+        let batch = duplicated_batch();
+        let columns = batch.columns_by_name("1");
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[1].as_any().downcast_ref::<Int32Array>().unwrap().value(0), 3);
+        assert!(batch.columns_by_name("missing").is_empty());
+    }
+}
@@ -0,0 +1,446 @@
+//! Compatibility reports between Arrow schemas and `ClickHouse` tables.
+//!
+//! [`compare_schemas`] checks each field of an Arrow schema against the column of the same name
+//! in a table and gives it a [`Compatibility`] verdict, along with the `ClickHouse` type the field
+//! maps to on its own. This is useful when onboarding a pipeline: the report shows up front which
+//! columns insert as-is, which need a cast, and which would lose data or fail.
+//!
+//! [`CompatibilityReport`] implements [`std::fmt::Display`] as a table for printing.
+use std::fmt;
+
+use arrow::compute::can_cast_types;
+use arrow::datatypes::{DataType, Schema, TimeUnit};
+
+use super::types::{arrow_to_ch_type, ch_to_arrow_type};
+use crate::{ArrowOptions, Type};
+
+/// How well an Arrow field fits a `ClickHouse` column, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Compatibility {
+    /// The field's type is the column's Arrow type.
+    Exact,
+    /// The field must be cast to the column's Arrow type, without losing data.
+    NeedsCast,
+    /// The field can be cast to the column's Arrow type, but values may be truncated, rounded,
+    /// rejected, or have their nulls replaced by defaults.
+    Lossy,
+    /// The field cannot be written to the column.
+    Incompatible,
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Compatibility::Exact => "exact",
+            Compatibility::NeedsCast => "needs-cast",
+            Compatibility::Lossy => "lossy",
+            Compatibility::Incompatible => "incompatible",
+        })
+    }
+}
+
+/// The verdict for one Arrow field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnCompatibility {
+    /// The field and column name.
+    pub name:            String,
+    /// The Arrow field's type.
+    pub arrow_type:      DataType,
+    /// Whether the Arrow field is nullable.
+    pub nullable:        bool,
+    /// The column's type, or `None` if the table has no column of this name.
+    pub clickhouse_type: Option<Type>,
+    /// The verdict.
+    pub compatibility:   Compatibility,
+    /// The `ClickHouse` type the field maps to, if it maps to one.
+    pub suggested_type:  Option<Type>,
+    /// Why the verdict is not [`Compatibility::Exact`].
+    pub note:            Option<String>,
+}
+
+/// Per-column verdicts from [`compare_schemas`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatibilityReport {
+    /// A verdict for each field of the Arrow schema, in schema order.
+    pub columns:    Vec<ColumnCompatibility>,
+    /// Columns of the table absent from the Arrow schema, which are left to their defaults.
+    pub table_only: Vec<String>,
+}
+
+impl CompatibilityReport {
+    /// The worst verdict across all columns, or [`Compatibility::Exact`] if there are none.
+    pub fn worst(&self) -> Compatibility {
+        self.columns.iter().map(|c| c.compatibility).max().unwrap_or(Compatibility::Exact)
+    }
+
+    /// Whether every field can be written to the table, possibly with a lossy cast.
+    pub fn is_compatible(&self) -> bool { self.worst() < Compatibility::Incompatible }
+
+    /// The columns with the given verdict.
+    pub fn with_compatibility(
+        &self,
+        compatibility: Compatibility,
+    ) -> impl Iterator<Item = &ColumnCompatibility> {
+        self.columns.iter().filter(move |c| c.compatibility == compatibility)
+    }
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .columns
+            .iter()
+            .map(|c| {
+                [
+                    c.name.clone(),
+                    format!("{}{}", c.arrow_type, if c.nullable { " (nullable)" } else { "" }),
+                    c.clickhouse_type.as_ref().map_or_else(|| "-".into(), ToString::to_string),
+                    c.compatibility.to_string(),
+                    c.suggested_type.as_ref().map_or_else(|| "-".into(), ToString::to_string),
+                    c.note.clone().unwrap_or_default(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let header = ["column", "arrow", "clickhouse", "verdict", "suggested", "note"];
+        write_table(f, header, &rows)?;
+        for name in &self.table_only {
+            writeln!(f, "{name}: only in table, default value is used")?;
+        }
+        Ok(())
+    }
+}
+
+/// Write `rows` under `header` as left-aligned columns.
+pub(super) fn write_table<const N: usize>(
+    f: &mut fmt::Formatter<'_>,
+    header: [&str; N],
+    rows: &[[String; N]],
+) -> fmt::Result {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let mut write_row = |cells: &[&str]| {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(f, "{}", line.trim_end())
+    };
+    write_row(&header)?;
+    for row in rows {
+        write_row(&row.each_ref().map(String::as_str))?;
+    }
+    Ok(())
+}
+
+/// Compare an Arrow schema against the columns of a `ClickHouse` table, given as names and types
+/// in table order.
+///
+/// `options` are the [`ArrowOptions`] the table's types would be read with, which decide e.g.
+/// whether `String` columns are `Utf8` or `Binary`.
+pub fn compare_schemas(
+    arrow_schema: &Schema,
+    table: &[(String, Type)],
+    options: Option<ArrowOptions>,
+) -> CompatibilityReport {
+    let columns = arrow_schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let (arrow_type, nullable) = (field.data_type(), field.is_nullable());
+            let suggested_type = arrow_to_ch_type(arrow_type, nullable, options).ok();
+            let clickhouse_type =
+                table.iter().find(|(name, _)| name == field.name()).map(|(_, t)| t.clone());
+            let (compatibility, note) = match &clickhouse_type {
+                Some(type_) => compare_column(arrow_type, nullable, type_, options),
+                None => (Compatibility::Incompatible, Some("column not in table".into())),
+            };
+            ColumnCompatibility {
+                name: field.name().clone(),
+                arrow_type: arrow_type.clone(),
+                nullable,
+                clickhouse_type,
+                compatibility,
+                suggested_type,
+                note,
+            }
+        })
+        .collect();
+    let table_only = table
+        .iter()
+        .filter(|(name, _)| arrow_schema.field_with_name(name).is_err())
+        .map(|(name, _)| name.clone())
+        .collect();
+    CompatibilityReport { columns, table_only }
+}
+
+fn compare_column(
+    from: &DataType,
+    nullable: bool,
+    type_: &Type,
+    options: Option<ArrowOptions>,
+) -> (Compatibility, Option<String>) {
+    let (to, to_nullable) = match ch_to_arrow_type(type_, options) {
+        Ok(mapped) => mapped,
+        Err(error) => return (Compatibility::Incompatible, Some(error.to_string())),
+    };
+
+    let (mut compatibility, mut note) = if types_match(from, &to, type_) {
+        (Compatibility::Exact, None)
+    } else if is_lossless_cast(from, &to) {
+        (Compatibility::NeedsCast, Some(format!("cast {from} to {to}")))
+    } else if can_cast_types(from, &to) {
+        (Compatibility::Lossy, Some(format!("cast {from} to {to} may lose or reject values")))
+    } else {
+        (Compatibility::Incompatible, Some(format!("{from} cannot be cast to {to}")))
+    };
+
+    if nullable && !to_nullable && compatibility < Compatibility::Lossy {
+        compatibility = Compatibility::Lossy;
+        note = Some("column is not nullable, nulls are written as default values".into());
+    }
+    (compatibility, note)
+}
+
+/// Whether `from` is written to a column read as `to` without a cast.
+fn types_match(from: &DataType, to: &DataType, type_: &Type) -> bool {
+    let is_string =
+        |t: &Type| matches!(t.strip_null().strip_low_cardinality().strip_null(), Type::String);
+    match (from, to) {
+        _ if from == to => true,
+        // `String` columns accept either, depending on `strings_as_strings`
+        (
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Binary | DataType::LargeBinary,
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Binary | DataType::LargeBinary,
+        ) if is_string(type_) => true,
+        // 64-bit offsets are written as is, depending on `large_offsets`
+        (DataType::Binary | DataType::LargeBinary, DataType::Binary | DataType::LargeBinary) => true,
+        (
+            DataType::List(from) | DataType::LargeList(from),
+            DataType::List(to) | DataType::LargeList(to),
+        ) => {
+            let Type::Array(inner) = type_.strip_null() else { return false };
+            (!from.is_nullable() || to.is_nullable())
+                && types_match(from.data_type(), to.data_type(), inner)
+        }
+        (DataType::Dictionary(_, from), DataType::Dictionary(_, to)) => {
+            types_match(from, to, type_.strip_null().strip_low_cardinality())
+        }
+        _ => false,
+    }
+}
+
+/// Integer width in bits and signedness.
+fn integer(data_type: &DataType) -> Option<(u32, bool)> {
+    Some(match data_type {
+        DataType::Int8 => (8, true),
+        DataType::Int16 => (16, true),
+        DataType::Int32 => (32, true),
+        DataType::Int64 => (64, true),
+        DataType::UInt8 => (8, false),
+        DataType::UInt16 => (16, false),
+        DataType::UInt32 => (32, false),
+        DataType::UInt64 => (64, false),
+        _ => return None,
+    })
+}
+
+/// Float mantissa precision in bits.
+fn float_precision(data_type: &DataType) -> Option<u32> {
+    match data_type {
+        DataType::Float16 => Some(11),
+        DataType::Float32 => Some(24),
+        DataType::Float64 => Some(53),
+        _ => None,
+    }
+}
+
+fn decimal(data_type: &DataType) -> Option<(u8, i8)> {
+    match data_type {
+        DataType::Decimal32(p, s)
+        | DataType::Decimal64(p, s)
+        | DataType::Decimal128(p, s)
+        | DataType::Decimal256(p, s) => Some((*p, *s)),
+        _ => None,
+    }
+}
+
+fn time_unit_rank(unit: TimeUnit) -> u8 {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 1,
+        TimeUnit::Microsecond => 2,
+        TimeUnit::Nanosecond => 3,
+    }
+}
+
+/// Whether every value of `from` casts to `to` without losing information.
+fn is_lossless_cast(from: &DataType, to: &DataType) -> bool {
+    use DataType as D;
+
+    if from == to {
+        return true;
+    }
+    if let D::Dictionary(_, from) = from {
+        return is_lossless_cast(from, to);
+    }
+    if let D::Dictionary(_, to) = to {
+        return is_lossless_cast(from, to);
+    }
+    let is_text = |t: &D| matches!(t, D::Utf8 | D::LargeUtf8 | D::Utf8View);
+    let is_binary = |t: &D| matches!(t, D::Binary | D::LargeBinary | D::BinaryView);
+    if is_text(from) {
+        return is_text(to) || is_binary(to);
+    }
+    if is_binary(from) {
+        return is_binary(to);
+    }
+
+    if let Some((from_bits, from_signed)) = integer(from) {
+        if let Some((to_bits, to_signed)) = integer(to) {
+            return match (from_signed, to_signed) {
+                (true, true) | (false, false) => to_bits >= from_bits,
+                (false, true) => to_bits > from_bits,
+                (true, false) => false,
+            };
+        }
+        if let Some(precision) = float_precision(to) {
+            return precision >= from_bits - u32::from(from_signed);
+        }
+        if let Some((p, s)) = decimal(to) {
+            // Digits needed for the largest value of each integer width
+            let digits = match from_bits {
+                8 => 3,
+                16 => 5,
+                32 => 10,
+                _ if from_signed => 19,
+                _ => 20,
+            };
+            return i16::from(p) - i16::from(s) >= digits;
+        }
+        return false;
+    }
+    if let (Some(from), Some(to)) = (float_precision(from), float_precision(to)) {
+        return to >= from;
+    }
+    if let (Some((p1, s1)), Some((p2, s2))) = (decimal(from), decimal(to)) {
+        return s2 >= s1 && i16::from(p2) - i16::from(s2) >= i16::from(p1) - i16::from(s1);
+    }
+    match (from, to) {
+        (D::Date32, D::Date64) => true,
+        (D::Timestamp(from_unit, from_tz), D::Timestamp(to_unit, to_tz)) => {
+            from_tz == to_tz && time_unit_rank(*to_unit) >= time_unit_rank(*from_unit)
+        }
+        (D::List(from), D::List(to) | D::LargeList(to))
+        | (D::LargeList(from), D::LargeList(to)) => {
+            is_lossless_cast(from.data_type(), to.data_type())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::Field;
+
+    use super::*;
+
+    fn table() -> Vec<(String, Type)> {
+        vec![
+            ("id".into(), Type::UInt64),
+            ("name".into(), Type::String),
+            ("score".into(), Type::Float32),
+            ("flag".into(), Type::UInt8),
+            ("tags".into(), Type::Array(Box::new(Type::String))),
+            ("created".into(), Type::DateTime(chrono_tz::Tz::UTC)),
+            ("extra".into(), Type::Nullable(Box::new(Type::String))),
+        ]
+    }
+
+    #[test]
+    fn test_compare_schemas() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("score", DataType::Int16, false),
+            Field::new("flag", DataType::Int64, false),
+            Field::new("tags", DataType::Date32, false),
+            Field::new("created", DataType::Utf8, true),
+            Field::new("unknown", DataType::Boolean, false),
+        ]);
+        let report = compare_schemas(&schema, &table(), None);
+        let verdicts = report.columns.iter().map(|c| c.compatibility).collect::<Vec<_>>();
+        assert_eq!(verdicts, vec![
+            Compatibility::Exact,
+            Compatibility::Exact,
+            Compatibility::NeedsCast,
+            Compatibility::Lossy,
+            Compatibility::Incompatible,
+            Compatibility::Lossy,
+            Compatibility::Incompatible,
+        ]);
+        assert_eq!(report.table_only, vec!["extra".to_string()]);
+        assert_eq!(report.worst(), Compatibility::Incompatible);
+        assert!(!report.is_compatible());
+        assert_eq!(report.with_compatibility(Compatibility::Lossy).count(), 2);
+        assert_eq!(report.columns[6].suggested_type, Some(Type::UInt8));
+        assert_eq!(report.columns[6].clickhouse_type, None);
+
+        let printed = report.to_string();
+        assert!(printed.starts_with("column"));
+        assert!(printed.contains("needs-cast"));
+        assert!(printed.contains("extra: only in table"));
+    }
+
+    #[test]
+    fn test_nullable_into_non_nullable_is_lossy() {
+        let schema = Schema::new(vec![Field::new("id", DataType::UInt64, true)]);
+        let report = compare_schemas(&schema, &table(), None);
+        assert_eq!(report.columns[0].compatibility, Compatibility::Lossy);
+        assert!(report.columns[0].note.as_deref().unwrap().contains("nullable"));
+
+        let schema = Schema::new(vec![Field::new("extra", DataType::Utf8, false)]);
+        let report = compare_schemas(&schema, &table(), None);
+        assert_eq!(report.columns[0].compatibility, Compatibility::Exact);
+        assert!(report.is_compatible());
+    }
+
+    #[test]
+    fn test_large_offsets_are_exact() {
+        let item = |data_type| std::sync::Arc::new(Field::new("item", data_type, false));
+        let schema = Schema::new(vec![
+            Field::new("name", DataType::LargeUtf8, false),
+            Field::new("tags", DataType::LargeList(item(DataType::LargeBinary)), false),
+        ]);
+        let report = compare_schemas(&schema, &table(), None);
+        assert_eq!(report.worst(), Compatibility::Exact);
+    }
+
+    #[test]
+    fn test_is_lossless_cast() {
+        assert!(is_lossless_cast(&DataType::Int32, &DataType::Int64));
+        assert!(is_lossless_cast(&DataType::UInt32, &DataType::Int64));
+        assert!(!is_lossless_cast(&DataType::Int32, &DataType::UInt64));
+        assert!(!is_lossless_cast(&DataType::Int64, &DataType::Float64));
+        assert!(is_lossless_cast(&DataType::Int32, &DataType::Float64));
+        assert!(is_lossless_cast(&DataType::Int32, &DataType::Decimal128(12, 2)));
+        assert!(!is_lossless_cast(&DataType::Int32, &DataType::Decimal128(10, 2)));
+        assert!(is_lossless_cast(&DataType::Decimal128(10, 2), &DataType::Decimal128(12, 3)));
+        assert!(!is_lossless_cast(&DataType::Decimal128(10, 2), &DataType::Decimal128(12, 1)));
+        assert!(is_lossless_cast(&DataType::Utf8, &DataType::Binary));
+        assert!(!is_lossless_cast(&DataType::Binary, &DataType::Utf8));
+        assert!(is_lossless_cast(
+            &DataType::Timestamp(TimeUnit::Second, None),
+            &DataType::Timestamp(TimeUnit::Millisecond, None)
+        ));
+        assert!(!is_lossless_cast(
+            &DataType::Timestamp(TimeUnit::Nanosecond, None),
+            &DataType::Timestamp(TimeUnit::Millisecond, None)
+        ));
+    }
+}
@@ -0,0 +1,511 @@
+//! Dry runs of Arrow inserts.
+//!
+//! [`plan_conversion`] takes a `RecordBatch` through the same steps as an insert into a table:
+//! each column is matched to the table's column, the [`NullPolicy`] is applied, naive timestamps
+//! are localized, and the column is serialized to the native format. The output is discarded
+//! rather than sent, and the resulting [`ConversionReport`] lists what happens to each column and
+//! why a column would fail. Pipelines can check their batches against a table in CI instead of
+//! failing on insert in production.
+//!
+//! [`ArrowClient::validate_batch`](crate::ArrowClient::validate_batch) fetches the table's columns
+//! and additionally applies the client's insert masking and block validator.
+//!
+//! Each column that would fail comes with a [`SuggestedFix`]: a cast to the Arrow type the table's
+//! column is read as, a rename to a table column missing from the batch whose name differs only in
+//! case or separators, or dropping a column the table does not have.
+//! [`ConversionReport::into_result`] returns them as [`Error::SchemaMismatch`], so pipelines can
+//! remediate batches automatically.
+//!
+//! [`ConversionReport`] implements [`std::fmt::Display`] as a table for printing.
+use std::fmt;
+
+use arrow::array::{Array, ArrayRef, RecordBatch};
+use arrow::compute::can_cast_types;
+use arrow::datatypes::DataType;
+
+use super::block::{header_type, localize_timestamps, resolve_nulls};
+use super::compat::write_table;
+use super::serialize::ClickHouseArrowSerializer;
+use super::types::ch_to_arrow_type;
+use crate::formats::SerializerState;
+use crate::geo::normalize_geo_type;
+use crate::masking::AppliedMask;
+use crate::serialize::ClickHouseNativeSerializer;
+use crate::{ArrowOptions, Error, NullPolicy, Result, Type};
+
+/// A conversion applied to a column before it is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionAction {
+    /// The named insert masking transform replaces the column's values.
+    Mask(String),
+    /// This many nulls are replaced by default values, see [`NullPolicy::Default`].
+    FillNulls(usize),
+    /// The column is sent as `Nullable`, see [`NullPolicy::Nullable`].
+    SendNullable,
+    /// Timezone-naive timestamps are read as wall-clock time in the named timezone, see
+    /// [`ArrowOptions::localize_naive_timestamps`].
+    LocalizeTimestamps(String),
+}
+
+impl fmt::Display for ConversionAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionAction::Mask(transform) => write!(f, "mask {transform}"),
+            ConversionAction::FillNulls(count) => write!(f, "fill {count} null(s)"),
+            ConversionAction::SendNullable => f.write_str("send nullable"),
+            ConversionAction::LocalizeTimestamps(tz) => write!(f, "localize to {tz}"),
+        }
+    }
+}
+
+/// How to make a column that cannot be inserted insertable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuggestedFix {
+    /// Cast the column to this Arrow type, the type the table's column is read as.
+    Cast(DataType),
+    /// Rename the column to this table column, which the batch lacks.
+    Rename(String),
+    /// Drop the column, the table has no column of this name.
+    Drop,
+    /// No fix can be derived from the schemas, ie the column has nulls the table's column cannot
+    /// hold, or values that do not fit it.
+    Manual,
+}
+
+impl SuggestedFix {
+    /// The kind of fix: `"cast"`, `"rename"`, `"drop"` or `"manual"`.
+    pub fn action(&self) -> &'static str {
+        match self {
+            SuggestedFix::Cast(_) => "cast",
+            SuggestedFix::Rename(_) => "rename",
+            SuggestedFix::Drop => "drop",
+            SuggestedFix::Manual => "manual",
+        }
+    }
+
+    /// The type to cast to or the name to rename to, if any.
+    pub fn target(&self) -> Option<String> {
+        match self {
+            SuggestedFix::Cast(data_type) => Some(data_type.to_string()),
+            SuggestedFix::Rename(name) => Some(name.clone()),
+            SuggestedFix::Drop | SuggestedFix::Manual => None,
+        }
+    }
+}
+
+impl fmt::Display for SuggestedFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuggestedFix::Cast(data_type) => write!(f, "cast to {data_type}"),
+            SuggestedFix::Rename(name) => write!(f, "rename to {name}"),
+            SuggestedFix::Drop => f.write_str("drop"),
+            SuggestedFix::Manual => f.write_str("no automatic fix"),
+        }
+    }
+}
+
+/// The plan for one column of a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnConversion {
+    /// The column name.
+    pub name:            String,
+    /// The Arrow type of the column.
+    pub arrow_type:      DataType,
+    /// The table column's type, or `None` if the table has no column of this name.
+    pub clickhouse_type: Option<Type>,
+    /// The conversions applied before the column is written, in order.
+    pub actions:         Vec<ConversionAction>,
+    /// Why the column cannot be inserted, if it cannot.
+    pub error:           Option<String>,
+    /// How to make the column insertable, if it cannot be inserted.
+    pub fix:             Option<SuggestedFix>,
+}
+
+impl ColumnConversion {
+    /// Whether the column can be inserted.
+    pub fn is_ok(&self) -> bool { self.error.is_none() }
+}
+
+/// Per-column plans from [`plan_conversion`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionReport {
+    /// The number of rows in the batch.
+    pub rows:       usize,
+    /// A plan for each column of the batch, in batch order.
+    pub columns:    Vec<ColumnConversion>,
+    /// Columns of the table absent from the batch, which are left to their defaults.
+    pub table_only: Vec<String>,
+    /// Why the client's block validator rejects the batch, if it does.
+    pub validation: Option<String>,
+}
+
+impl ConversionReport {
+    /// Whether the batch can be inserted.
+    pub fn is_ok(&self) -> bool {
+        self.validation.is_none() && self.columns.iter().all(ColumnConversion::is_ok)
+    }
+
+    /// The columns that cannot be inserted.
+    pub fn failed(&self) -> impl Iterator<Item = &ColumnConversion> {
+        self.columns.iter().filter(|c| !c.is_ok())
+    }
+
+    /// The columns that cannot be inserted with their suggested fixes, `None` if every column
+    /// can be inserted.
+    pub fn mismatch(&self) -> Option<SchemaMismatch> {
+        let columns = self
+            .failed()
+            .map(|c| ColumnMismatch {
+                name:            c.name.clone(),
+                arrow_type:      c.arrow_type.clone(),
+                clickhouse_type: c.clickhouse_type.clone(),
+                error:           c.error.clone().unwrap_or_default(),
+                fix:             c.fix.clone().unwrap_or(SuggestedFix::Manual),
+            })
+            .collect::<Vec<_>>();
+        (!columns.is_empty()).then_some(SchemaMismatch { columns })
+    }
+
+    /// Return the report if the batch can be inserted.
+    ///
+    /// # Errors
+    /// - Returns [`Error::SchemaMismatch`] listing each failing column and its suggested fix.
+    /// - Returns [`Error::ArrowSerialize`] with the validation error if only the block validator
+    ///   rejects the batch.
+    pub fn into_result(self) -> Result<Self> {
+        if let Some(mismatch) = self.mismatch() {
+            return Err(Error::SchemaMismatch(Box::new(mismatch)));
+        }
+        if let Some(error) = &self.validation {
+            let message = format!("Batch cannot be inserted, validation: {error}");
+            return Err(Error::ArrowSerialize(message));
+        }
+        Ok(self)
+    }
+}
+
+/// A column of a batch that cannot be inserted, see [`SchemaMismatch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnMismatch {
+    /// The column name.
+    pub name:            String,
+    /// The Arrow type of the column.
+    pub arrow_type:      DataType,
+    /// The table column's type, or `None` if the table has no column of this name.
+    pub clickhouse_type: Option<Type>,
+    /// Why the column cannot be inserted.
+    pub error:           String,
+    /// How to make the column insertable.
+    pub fix:             SuggestedFix,
+}
+
+/// The columns of a batch that cannot be inserted into a table, returned by
+/// [`ConversionReport::into_result`] as [`Error::SchemaMismatch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaMismatch {
+    /// Each column that cannot be inserted, in batch order.
+    pub columns: Vec<ColumnMismatch>,
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.columns.iter().enumerate() {
+            let clickhouse_type =
+                c.clickhouse_type.as_ref().map_or_else(|| "-".into(), ToString::to_string);
+            let (name, arrow_type, error, fix) = (&c.name, &c.arrow_type, &c.error, &c.fix);
+            let separator = if i == 0 { "" } else { "; " };
+            write!(f, "{separator}{name} ({arrow_type} to {clickhouse_type}): {error}, {fix}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .columns
+            .iter()
+            .map(|c| {
+                let actions = c.actions.iter().map(ToString::to_string).collect::<Vec<_>>();
+                [
+                    c.name.clone(),
+                    c.arrow_type.to_string(),
+                    c.clickhouse_type.as_ref().map_or_else(|| "-".into(), ToString::to_string),
+                    if actions.is_empty() { "as-is".into() } else { actions.join(", ") },
+                    c.error.clone().unwrap_or_else(|| "ok".into()),
+                    c.fix.as_ref().map_or_else(|| "-".into(), ToString::to_string),
+                ]
+            })
+            .collect::<Vec<_>>();
+        write_table(f, ["column", "arrow", "clickhouse", "actions", "result", "fix"], &rows)?;
+        for name in &self.table_only {
+            writeln!(f, "{name}: only in table, default value is used")?;
+        }
+        if let Some(error) = &self.validation {
+            writeln!(f, "validation failed: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Plan the insert of `batch` into a table with the given columns, in table order, without
+/// sending anything.
+///
+/// `options` are the client's [`ArrowOptions`], which decide e.g. the [`NullPolicy`]. Every
+/// column is serialized as it would be on insert, so a column that fails here fails on insert.
+pub fn plan_conversion(
+    batch: &RecordBatch,
+    table: &[(String, Type)],
+    options: ArrowOptions,
+) -> ConversionReport {
+    let schema = batch.schema();
+    let table_only = table
+        .iter()
+        .filter(|(name, _)| schema.field_with_name(name).is_err())
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    let columns = schema
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .enumerate()
+        .map(|(i, (field, column))| {
+            let (name, data_type) = (field.name(), field.data_type());
+            let clickhouse_type = header_type(table, i, name).cloned();
+            let mut actions = vec![];
+            let error = match &clickhouse_type {
+                Some(type_) => plan_column(name, column, data_type, type_, options, &mut actions)
+                    .err()
+                    .map(|error| error.to_string()),
+                None => Some("column not in table".into()),
+            };
+            let fix = error.as_ref().map(|_| {
+                suggest_fix(name, data_type, clickhouse_type.as_ref(), &table_only, options)
+            });
+            ColumnConversion {
+                name: name.clone(),
+                arrow_type: data_type.clone(),
+                clickhouse_type,
+                actions,
+                error,
+                fix,
+            }
+        })
+        .collect();
+    ConversionReport { rows: batch.num_rows(), columns, table_only, validation: None }
+}
+
+/// Add the masks applied to a batch before it was planned to the report.
+pub(crate) fn record_masks(report: &mut ConversionReport, applied: Vec<AppliedMask>) {
+    for mask in applied {
+        if let Some(column) = report.columns.iter_mut().find(|c| c.name == mask.column) {
+            column.actions.insert(0, ConversionAction::Mask(mask.transform));
+        }
+    }
+}
+
+/// Suggest a fix for a column that cannot be inserted into a column of type `type_`, or into the
+/// table at all if `None`.
+fn suggest_fix(
+    name: &str,
+    data_type: &DataType,
+    type_: Option<&Type>,
+    table_only: &[String],
+    options: ArrowOptions,
+) -> SuggestedFix {
+    let Some(type_) = type_ else {
+        // Match names ignoring case and separators, ie `userId` and `user_id`
+        let key = |name: &str| -> String {
+            name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+        };
+        let name = key(name);
+        return table_only
+            .iter()
+            .find(|column| key(column) == name)
+            .map_or(SuggestedFix::Drop, |column| SuggestedFix::Rename(column.clone()));
+    };
+    match ch_to_arrow_type(type_, Some(options)) {
+        Ok((to, _)) if &to != data_type && can_cast_types(data_type, &to) => SuggestedFix::Cast(to),
+        _ => SuggestedFix::Manual,
+    }
+}
+
+/// Take a column through the steps of an insert, recording its conversions in `actions`.
+fn plan_column(
+    name: &str,
+    column: &ArrayRef,
+    data_type: &DataType,
+    type_: &Type,
+    options: ArrowOptions,
+    actions: &mut Vec<ConversionAction>,
+) -> Result<()> {
+    let is_geo = matches!(type_, Type::Point | Type::Polygon | Type::MultiPolygon | Type::Ring);
+    let type_ = &if is_geo { normalize_geo_type(type_)? } else { type_.clone() };
+
+    let resolved = resolve_nulls(name, column, type_, options)?;
+    if resolved.is_some() {
+        actions.push(match options.null_policy {
+            NullPolicy::Nullable => ConversionAction::SendNullable,
+            _ => ConversionAction::FillNulls(column.logical_null_count()),
+        });
+    }
+    let (column, type_) = resolved.as_ref().map_or((column, type_), |(c, t)| (c, t));
+
+    let localized = localize_timestamps(column, type_, options)?;
+    if localized.is_some()
+        && let (Type::DateTime(tz) | Type::DateTime64(_, tz)) = type_.strip_null()
+    {
+        actions.push(ConversionAction::LocalizeTimestamps(tz.name().to_string()));
+    }
+    let column = localized.as_ref().unwrap_or(column);
+
+    if column.is_empty() {
+        return Ok(());
+    }
+    let mut state = SerializerState::default().with_arrow_options(options);
+    let mut discard = Vec::new();
+    type_.serialize_prefix(&mut discard, &mut state);
+    type_.serialize(&mut discard, column, data_type, &mut state)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int32Array, Int64Array, StringArray, TimestampSecondArray, UInt64Array};
+
+    use super::*;
+
+    fn table() -> Vec<(String, Type)> {
+        vec![
+            ("id".into(), Type::UInt64),
+            ("name".into(), Type::String),
+            ("score".into(), Type::Int32),
+            ("created".into(), Type::DateTime(chrono_tz::Tz::Europe__Berlin)),
+            ("extra".into(), Type::Nullable(Box::new(Type::String))),
+        ]
+    }
+
+    fn batch() -> RecordBatch {
+        RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            ("name", Arc::new(StringArray::from(vec![Some("a"), None]))),
+            ("score", Arc::new(Int32Array::from(vec![Some(1), None]))),
+            ("created", Arc::new(TimestampSecondArray::from(vec![0, 60]))),
+            ("unknown", Arc::new(Int32Array::from(vec![1, 2]))),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plan_conversion() {
+        let options = ArrowOptions::default()
+            .with_strings_as_strings(true)
+            .with_null_policy(NullPolicy::Default)
+            .with_localize_naive_timestamps(true);
+        let report = plan_conversion(&batch(), &table(), options);
+        assert_eq!(report.rows, 2);
+        assert_eq!(report.table_only, vec!["extra".to_string()]);
+
+        let actions = report.columns.iter().map(|c| c.actions.clone()).collect::<Vec<_>>();
+        assert_eq!(actions, vec![
+            vec![],
+            vec![ConversionAction::FillNulls(1)],
+            vec![ConversionAction::FillNulls(1)],
+            vec![ConversionAction::LocalizeTimestamps("Europe/Berlin".into())],
+            vec![],
+        ]);
+
+        // Int64 is not written to UInt64 as is, and `unknown` is not in the table
+        let failed = report.failed().map(|c| c.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(failed, vec!["id", "unknown"]);
+        assert_eq!(report.columns[4].error.as_deref(), Some("column not in table"));
+        assert!(!report.is_ok());
+        assert!(matches!(report.clone().into_result(), Err(Error::SchemaMismatch(_))));
+
+        let printed = report.to_string();
+        assert!(printed.starts_with("column"));
+        assert!(printed.contains("fill 1 null(s)"));
+        assert!(printed.contains("extra: only in table"));
+    }
+
+    #[test]
+    fn test_schema_mismatch_fixes() {
+        let table = table();
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            ("Extra", Arc::new(StringArray::from(vec!["a", "b"]))),
+            ("unknown", Arc::new(Int32Array::from(vec![1, 2]))),
+            ("score", Arc::new(Int32Array::from(vec![Some(1), None]))),
+        ])
+        .unwrap();
+        let report = plan_conversion(&batch, &table, ArrowOptions::default());
+        let Err(Error::SchemaMismatch(mismatch)) = report.into_result() else {
+            panic!("expected a schema mismatch");
+        };
+        let fixes = mismatch.columns.iter().map(|c| (c.name.as_str(), &c.fix)).collect::<Vec<_>>();
+        assert_eq!(fixes, vec![
+            ("id", &SuggestedFix::Cast(DataType::UInt64)),
+            ("Extra", &SuggestedFix::Rename("extra".into())),
+            ("unknown", &SuggestedFix::Drop),
+            // Nulls in a non-nullable column are not fixed by a cast
+            ("score", &SuggestedFix::Manual),
+        ]);
+        assert_eq!(mismatch.columns[0].clickhouse_type, Some(Type::UInt64));
+        assert_eq!(mismatch.columns[0].arrow_type, DataType::Int64);
+        assert_eq!(
+            mismatch.columns.iter().map(|c| (c.fix.action(), c.fix.target())).collect::<Vec<_>>(),
+            vec![
+                ("cast", Some("UInt64".into())),
+                ("rename", Some("extra".into())),
+                ("drop", None),
+                ("manual", None),
+            ]
+        );
+        assert!(mismatch.to_string().contains("unknown (Int32 to -): column not in table, drop"));
+
+        // Only the validator failing is not a schema mismatch
+        let batch =
+            RecordBatch::try_from_iter([("id", Arc::new(UInt64Array::from(vec![1])) as ArrayRef)])
+                .unwrap();
+        let mut report = plan_conversion(&batch, &table, ArrowOptions::default());
+        assert!(report.mismatch().is_none());
+        report.validation = Some("too few rows".into());
+        assert!(matches!(report.into_result(), Err(Error::ArrowSerialize(_))));
+    }
+
+    #[test]
+    fn test_plan_conversion_null_policies() {
+        let batch = RecordBatch::try_from_iter([(
+            "score",
+            Arc::new(Int32Array::from(vec![Some(1), None])) as ArrayRef,
+        )])
+        .unwrap();
+        let table = vec![("score".to_string(), Type::Int32)];
+
+        let report = plan_conversion(&batch, &table, ArrowOptions::default());
+        assert!(report.columns[0].error.as_deref().is_some_and(|e| e.contains("null")));
+
+        let options = ArrowOptions::default().with_null_policy(NullPolicy::Nullable);
+        let report = plan_conversion(&batch, &table, options);
+        assert_eq!(report.columns[0].actions, vec![ConversionAction::SendNullable]);
+        assert!(report.into_result().is_ok());
+    }
+
+    #[test]
+    fn test_record_masks() {
+        let table = vec![("name".to_string(), Type::String)];
+        let batch = RecordBatch::try_from_iter([(
+            "name",
+            Arc::new(StringArray::from(vec!["a"])) as ArrayRef,
+        )])
+        .unwrap();
+        let mut report = plan_conversion(&batch, &table, ArrowOptions::default());
+        record_masks(&mut report, vec![AppliedMask {
+            column:    "name".into(),
+            transform: "redact".into(),
+            values:    1,
+        }]);
+        assert_eq!(report.columns[0].actions, vec![ConversionAction::Mask("redact".into())]);
+    }
+}
@@ -12,11 +12,14 @@
 /// respecting nullability and maintaining deserialization state.
 mod binary;
 mod enums;
+mod header;
 mod list;
 mod low_cardinality;
 mod map;
 mod null;
 mod primitive;
+pub(super) mod skip;
+pub(super) mod sparse;
 mod tuple;
 
 use std::sync::Arc;
@@ -24,18 +27,27 @@ use std::sync::Arc;
 use arrow::array::*;
 use arrow::datatypes::*;
 
+use self::header::HeaderCache;
 use super::builder::TypedBuilder;
+use super::rescale::{DecimalOverflow, DecimalRescale};
 use super::types::ch_to_arrow_type;
 use crate::geo::normalize_geo_type;
 use crate::io::{ClickHouseBytesRead, ClickHouseRead};
+use crate::native::sparse::encode_dense_array;
 use crate::{ArrowOptions, Error, Result, Type};
 
 #[derive(Default)]
 pub(crate) struct ArrowDeserializerState {
-    pub(crate) builders: Vec<TypedBuilder>,
-    pub(crate) buffer:   Vec<u8>,
-    fields:              Vec<FieldRef>,
-    arrays:              Vec<ArrayRef>,
+    pub(crate) builders:        Vec<TypedBuilder>,
+    pub(crate) buffer:          Vec<u8>,
+    /// Names of the columns to decode for the current query, or `None` to decode all columns
+    pub(crate) projection:      Option<Arc<[String]>>,
+    /// Decimal columns converted to another precision and scale for the current query
+    pub(crate) decimal_rescale: Option<Arc<[(String, DecimalRescale)]>>,
+    /// Column headers decoded from earlier blocks of the current query
+    pub(crate) headers:         HeaderCache,
+    fields:                     Vec<FieldRef>,
+    arrays:                     Vec<ArrayRef>,
 }
 
 impl ArrowDeserializerState {
@@ -51,7 +63,7 @@ impl ArrowDeserializerState {
             self.arrays.reserve(field_cap - self.arrays.capacity());
         }
         // Choose the size of i128 as an upper bound (i128)
-        let min_buffer_size = rows_cap * 16;
+        let min_buffer_size = rows_cap.saturating_mul(16);
         if self.buffer.capacity() < min_buffer_size {
             self.buffer.reserve(min_buffer_size - self.buffer.capacity());
         }
@@ -70,6 +82,60 @@ impl ArrowDeserializerState {
         self
     }
 
+    /// Whether the column `name` is part of the current projection.
+    #[inline]
+    pub(crate) fn is_projected(&self, name: &str) -> bool {
+        self.projection.as_ref().is_none_or(|columns| columns.iter().any(|c| c == name))
+    }
+
+    /// Convert the decoded column `i` if the current query rescales it, returning the field and
+    /// array to add to the block.
+    pub(crate) fn rescale(
+        &self,
+        field: FieldRef,
+        array: ArrayRef,
+        i: usize,
+    ) -> Result<(FieldRef, ArrayRef)> {
+        let Some(rescale) = self
+            .decimal_rescale
+            .as_ref()
+            .and_then(|columns| columns.iter().find(|(c, _)| c == field.name()))
+            .map(|(_, rescale)| rescale)
+        else {
+            return Ok((field, array));
+        };
+        let array = rescale.rescale(&array).map_err(|e| e.with_column(field.name(), i))?;
+        let nullable = field.is_nullable() || rescale.overflow == DecimalOverflow::Null;
+        let field = Field::clone(&field).with_data_type(array.data_type().clone());
+        Ok((Arc::new(field.with_nullable(nullable)), array))
+    }
+
+    /// The schema of a block without rows whose header has `columns`, as decoded for the current
+    /// query, ie with its projection and decimal rescaling applied.
+    ///
+    /// # Errors
+    /// Returns an error if a column's type cannot be mapped to Arrow with `options`.
+    pub(crate) fn header_schema(
+        &mut self,
+        columns: &[(String, Type)],
+        options: ArrowOptions,
+    ) -> Result<SchemaRef> {
+        let mut fields = Vec::with_capacity(columns.len());
+        for (i, (name, type_)) in columns.iter().enumerate() {
+            let type_name = type_.to_string();
+            let (field, _) =
+                self.headers.resolve(i, name.as_bytes(), type_name.as_bytes(), options)?;
+            if !self.is_projected(field.name()) {
+                continue;
+            }
+            // Matches the type of decoded columns, ie with `sparse_as_run_end_encoded`
+            let array = encode_dense_array(new_empty_array(field.data_type()), options)?;
+            let field = Arc::new(Field::clone(&field).with_data_type(array.data_type().clone()));
+            fields.push(self.rescale(field, array, i)?.0);
+        }
+        Ok(Arc::new(Schema::new(fields)))
+    }
+
     pub(crate) fn take(&mut self) -> (Vec<FieldRef>, Vec<ArrayRef>) {
         (std::mem::take(&mut self.fields), std::mem::take(&mut self.arrays))
     }
@@ -377,7 +443,7 @@ impl ClickHouseArrowDeserializer for Type {
             // Geo types
             Type::Polygon | Type::MultiPolygon | Type::Point | Type::Ring => {
                 // Geo types should be converted earlier, this is a fallback
-                let normalized = normalize_geo_type(self).unwrap();
+                let normalized = normalize_geo_type(self)?;
                 let (normalized_dt, _) = ch_to_arrow_type(&normalized, None)?;
                 Box::pin(normalized.deserialize_arrow_async(
                     builder,
@@ -456,8 +522,10 @@ impl ClickHouseArrowDeserializer for Type {
             B::Decimal128(b) => i => { opt_value!(b, i, nulls, primitive!(Decimal128 => reader)) },
             B::Decimal256(b) => i => { opt_value!(b, i, nulls, primitive!(Decimal256 => reader)) },
             B::String(b) => i => { opt_value!(b, i, nulls, binary!(String => reader)) },
+            B::LargeString(b) => i => { opt_value!(b, i, nulls, binary!(String => reader)) },
             B::Object(b) => i => { opt_value!(b, i, nulls, binary!(Object => reader)) },
-            B::Binary(b) => i => { opt_value!(b, i, nulls, binary!(Binary => reader)) }
+            B::Binary(b) => i => { opt_value!(b, i, nulls, binary!(Binary => reader)) },
+            B::LargeBinary(b) => i => { opt_value!(b, i, nulls, binary!(Binary => reader)) }
         }
         // Pass through
         _ => {()});
@@ -473,7 +541,7 @@ impl ClickHouseArrowDeserializer for Type {
                     Type::Ipv4 => i => { opt_value!(ok => b, i, nulls, binary!(Ipv4 => reader)) },
                     Type::Ipv6 => i => { opt_value!(ok => b, i, nulls, binary!(Ipv6 => reader)) },
                     Type::Uuid => i => {
-                        opt_value!(ok => b, i, nulls, binary!(Fixed(16) => reader))
+                        opt_value!(ok => b, i, nulls, binary!(Uuid => reader))
                     },
                     // Special numeric types that need to be read as bytes
                     Type::Int128 | Type::UInt128 => i => {
@@ -585,8 +653,10 @@ impl ClickHouseArrowDeserializer for Type {
                     B::DateTimeNano(b) => { Arc::new(b.finish()) as ArrayRef }   ,
                     // String/Binary
                     B::String(b) => { Arc::new(b.finish()) as ArrayRef },
+                    B::LargeString(b) => { Arc::new(b.finish()) as ArrayRef },
                     B::Object(b) => { Arc::new(b.finish()) as ArrayRef },
                     B::Binary(b) => { Arc::new(b.finish()) as ArrayRef },
+                    B::LargeBinary(b) => { Arc::new(b.finish()) as ArrayRef },
                     // Fixed sized binary, Int256, UInt256, UUID, Ipv4, etc
                     B::FixedSizeBinary(b) => { Arc::new(b.finish()) as ArrayRef },
                     // Enums
@@ -913,6 +983,40 @@ mod tests {
         let array = array.unwrap();
         assert!(array.is_empty());
     }
+
+    #[test]
+    fn test_header_schema() {
+        let columns = vec![
+            ("id".to_string(), Type::UInt64),
+            ("name".to_string(), Type::Nullable(Box::new(Type::String))),
+            ("price".to_string(), Type::Decimal128(10)),
+        ];
+        let options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut state = ArrowDeserializerState::default();
+        let schema = state.header_schema(&columns, options).unwrap();
+        assert_eq!(schema.fields().len(), 3);
+        assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+        assert!(schema.field(1).is_nullable());
+
+        // The projection and decimal rescaling of the query apply
+        let mut state = ArrowDeserializerState {
+            projection: Some(Arc::from(["price".to_string()])),
+            decimal_rescale: Some(Arc::from([("price".to_string(), DecimalRescale::new(18, 4))])),
+            ..Default::default()
+        };
+        let schema = state.header_schema(&columns, options).unwrap();
+        assert_eq!(schema.fields().len(), 1);
+        assert_eq!(schema.field(0).data_type(), &DataType::Decimal128(18, 4));
+
+        // Columns that could be sparse are run end encoded, as in every decoded block
+        let options = options.with_sparse_as_run_end_encoded(true);
+        let schema = state.header_schema(&columns, options).unwrap();
+        let DataType::RunEndEncoded(run_ends, values) = schema.field(0).data_type() else {
+            panic!("expected a run end encoded column: {schema:?}");
+        };
+        assert_eq!(run_ends.data_type(), &DataType::Int32);
+        assert_eq!(values.data_type(), &DataType::Decimal128(18, 4));
+    }
 }
 
 #[cfg(test)]
@@ -61,6 +61,15 @@ macro_rules! binary {
             buf
         }
     }};
+    // Undo the swapped halves of the `ClickHouse` layout, see `crate::simd::swap_uuid_halves`
+    (Uuid => $reader:expr) => {{
+        {
+            let mut buf = [0u8; 16];
+            $reader.try_copy_to_slice(&mut buf)?;
+            $crate::simd::swap_uuid_halves(&mut buf);
+            buf
+        }
+    }};
     (Ipv4 => $reader:expr) => {{
         {
             let ipv4_int = $reader.try_get_u32_le()?;
@@ -120,6 +129,15 @@ macro_rules! binary_async {
             buf
         }
     }};
+    // Undo the swapped halves of the `ClickHouse` layout, see `crate::simd::swap_uuid_halves`
+    (Uuid => $reader:expr) => {{
+        {
+            let mut buf = [0u8; 16];
+            let _ = $reader.read_exact(&mut buf).await?;
+            $crate::simd::swap_uuid_halves(&mut buf);
+            buf
+        }
+    }};
     (Ipv4 => $reader:expr) => {{
         {
             let ipv4_int = $reader.read_u32_le().await?;
@@ -217,6 +235,18 @@ pub(crate) async fn deserialize_async<R: ClickHouseRead>(
         }
         Arc::new(b.finish())
     }},
+    B::LargeString(b) => {{
+        for i in 0..rows {
+           super::opt_value!(b, i, nulls, binary_async!(String => reader));
+        }
+        Arc::new(b.finish())
+    }},
+    B::LargeBinary(b) => {{
+        for i in 0..rows {
+           super::opt_value!(b, i, nulls, binary_async!(Binary => reader));
+        }
+        Arc::new(b.finish())
+    }},
     B::Object(b) => {{
         for i in 0..rows {
            super::opt_value!(b, i, nulls, binary_async!(Object => reader));
@@ -243,7 +273,13 @@ pub(crate) async fn deserialize_async<R: ClickHouseRead>(
                 }
                 Arc::new(b.finish())
             },
-            Type::Uuid | Type::Int128 | Type::UInt128 => {
+            Type::Uuid => {
+                for i in 0..rows {
+                   super::opt_value!(ok => b, i, nulls, binary_async!(Uuid => reader));
+                }
+                Arc::new(b.finish())
+            },
+            Type::Int128 | Type::UInt128 => {
                 for i in 0..rows {
                    super::opt_value!(ok => b, i, nulls, binary_async!(Fixed(16) => reader));
                 }
@@ -495,10 +531,11 @@ mod tests {
         let rows = 2;
         let null_mask = vec![];
         let input = vec![
-            // UUIDs: [00010203-0405-0607-0809-0a0b0c0d0e0f, 10111213-1415-1617-1819-1a1b1c1d1e1f]
-            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
-            0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b,
-            0x1c, 0x1d, 0x1e, 0x1f,
+            // UUIDs with swapped halves: [00010203-0405-0607-0809-0a0b0c0d0e0f,
+            // 10111213-1415-1617-1819-1a1b1c1d1e1f]
+            0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05,
+            0x06, 0x07, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x10, 0x11, 0x12, 0x13,
+            0x14, 0x15, 0x16, 0x17,
         ];
         let mut reader = Cursor::new(input);
 
@@ -527,13 +564,13 @@ mod tests {
         let rows = 3;
         let null_mask = vec![0, 1, 0]; // [not null, null, not null]
         let input = vec![
-            // UUIDs: [00010203-0405-0607-0809-0a0b0c0d0e0f, [0;16],
+            // UUIDs with swapped halves: [00010203-0405-0607-0809-0a0b0c0d0e0f, [0;16],
             // 10111213-1415-1617-1819-1a1b1c1d1e1f]
-            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
-            0x0e, 0x0f, // non-null
+            0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05,
+            0x06, 0x07, // non-null
             0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // null (zeroed)
-            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d,
-            0x1e, 0x1f, // non-null
+            0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15,
+            0x16, 0x17, // non-null
         ];
         let mut reader = Cursor::new(input);
 
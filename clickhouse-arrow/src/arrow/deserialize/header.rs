@@ -0,0 +1,142 @@
+//! Per query cache of decoded column headers.
+//!
+//! Every block of a query result repeats the name and type of each column. Decoding a header
+//! allocates the name, parses the type and maps it to an Arrow field, so wide tables returning
+//! thousands of blocks repeat that work for identical headers. The decoded header of each column
+//! is kept for the rest of the query and reused whenever a later block sends the same name and
+//! type at the same position, sharing one `FieldRef` and parsed `Type` across all blocks.
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::datatypes::{Field, FieldRef};
+
+use crate::arrow::types::normalize_type;
+use crate::{ArrowOptions, Result, Type};
+
+/// A column header decoded from an earlier block of the current query.
+#[derive(Debug, Clone)]
+struct ColumnHeader {
+    /// The type name as sent by the server.
+    type_name: Arc<str>,
+    /// The Arrow field of the column, also holding its name.
+    field:     FieldRef,
+    /// The parsed type, normalized against the field's Arrow type.
+    type_hint: Arc<Type>,
+}
+
+/// Decoded column headers by column position, cleared when a query finishes.
+#[derive(Debug, Default)]
+pub(crate) struct HeaderCache {
+    columns: Vec<ColumnHeader>,
+    /// The options the headers were decoded with
+    options: Option<ArrowOptions>,
+}
+
+impl HeaderCache {
+    /// The field and type of column `i` of a block, decoding and caching the header unless the
+    /// previous block sent the same `name` and `type_name` at that position.
+    ///
+    /// # Errors
+    /// Returns an error if the name or type is not valid UTF-8, or the type cannot be parsed or
+    /// mapped to Arrow with `options`.
+    pub(crate) fn resolve(
+        &mut self,
+        i: usize,
+        name: &[u8],
+        type_name: &[u8],
+        options: ArrowOptions,
+    ) -> Result<(FieldRef, Arc<Type>)> {
+        // Fields depend on the options, which may differ from a query that never finished
+        if self.options != Some(options) {
+            self.columns.clear();
+            self.options = Some(options);
+        }
+        if let Some(header) = self.columns.get(i).filter(|header| {
+            header.field.name().as_bytes() == name && header.type_name.as_bytes() == type_name
+        }) {
+            return Ok((Arc::clone(&header.field), Arc::clone(&header.type_hint)));
+        }
+
+        let header = decode(i, name, type_name, options)?;
+        let resolved = (Arc::clone(&header.field), Arc::clone(&header.type_hint));
+        // Columns are resolved in order, so a new column is always the next position
+        if let Some(cached) = self.columns.get_mut(i) {
+            *cached = header;
+        } else {
+            self.columns.push(header);
+        }
+        Ok(resolved)
+    }
+
+    /// Drop the headers of the finished query.
+    pub(crate) fn clear(&mut self) {
+        self.columns.clear();
+        self.options = None;
+    }
+}
+
+fn decode(i: usize, name: &[u8], type_name: &[u8], options: ArrowOptions) -> Result<ColumnHeader> {
+    let name = String::from_utf8(name.to_vec())?;
+    let type_name: Arc<str> = std::str::from_utf8(type_name)?.into();
+    let internal_type = Type::from_str(&type_name).map_err(|e| e.with_column(&name, i))?;
+    let (arrow_type, is_nullable) =
+        internal_type.arrow_type(Some(options)).map_err(|e| e.with_column(&name, i))?;
+
+    // Verify the resulting type against the arrow type, otherwise the builders will fail
+    let type_hint = normalize_type(&internal_type, &arrow_type).unwrap_or(internal_type);
+    let field = Arc::new(Field::new(name, arrow_type, is_nullable));
+    Ok(ColumnHeader { type_name, field, type_hint: Arc::new(type_hint) })
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::DataType;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_reuses_matching_headers() {
+        let options = ArrowOptions::default().with_strings_as_strings(true);
+        let mut cache = HeaderCache::default();
+
+        let (id, id_type) = cache.resolve(0, b"id", b"UInt64", options).unwrap();
+        let (name, _) = cache.resolve(1, b"name", b"Nullable(String)", options).unwrap();
+        assert_eq!(id.data_type(), &DataType::UInt64);
+        assert_eq!(*id_type, Type::UInt64);
+        assert_eq!(name.data_type(), &DataType::Utf8);
+        assert!(name.is_nullable());
+        assert_eq!(cache.columns.len(), 2);
+
+        // The next block shares the decoded headers
+        let (next_id, next_type) = cache.resolve(0, b"id", b"UInt64", options).unwrap();
+        assert!(Arc::ptr_eq(&id, &next_id));
+        assert!(Arc::ptr_eq(&id_type, &next_type));
+
+        // A changed column replaces the cached header at its position
+        let (changed, _) = cache.resolve(1, b"name", b"String", options).unwrap();
+        assert!(!Arc::ptr_eq(&name, &changed));
+        assert!(!changed.is_nullable());
+        let (renamed, _) = cache.resolve(1, b"label", b"String", options).unwrap();
+        assert_eq!(renamed.name(), "label");
+        assert_eq!(cache.columns.len(), 2);
+
+        // Different options decode the headers again
+        let binary = ArrowOptions::default();
+        let (name, _) = cache.resolve(0, b"label", b"String", binary).unwrap();
+        assert_eq!(name.data_type(), &DataType::Binary);
+        assert_eq!(cache.columns.len(), 1);
+
+        cache.clear();
+        assert_eq!(cache.columns.len(), 0);
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let mut cache = HeaderCache::default();
+        let options = ArrowOptions::default();
+        assert!(cache.resolve(0, b"id", b"NotAType", options).is_err());
+        assert!(cache.resolve(0, b"\xff", b"UInt8", options).is_err());
+        assert!(cache.resolve(0, b"id", b"\xff", options).is_err());
+        assert_eq!(cache.columns.len(), 0);
+    }
+}
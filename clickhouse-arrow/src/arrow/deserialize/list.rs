@@ -27,7 +27,18 @@ macro_rules! bulk_offsets {
     ($r:expr, $rbuf:expr, $rows:expr) => {{
         $rbuf.clear();
         // Prepare buffer for: initial offset (8 bytes) + offset data (rows * 8 bytes)
-        let total_bytes_needed = 8 + ($rows * 8);
+        let total_bytes_needed = $rows
+            .checked_mul(8)
+            .and_then(|bytes: usize| bytes.checked_add(8))
+            .ok_or_else(|| Error::DeserializeError(format!("Too many offsets: {}", $rows)))?;
+        // Never allocate more than the remaining input could possibly fill
+        if $r.remaining() < total_bytes_needed - 8 {
+            return Err(Error::DeserializeError(format!(
+                "Not enough data for {} offsets: {} bytes remaining",
+                $rows,
+                $r.remaining()
+            )));
+        }
         if $rbuf.capacity() < total_bytes_needed {
             $rbuf.reserve(total_bytes_needed - $rbuf.capacity());
         }
@@ -42,7 +53,10 @@ macro_rules! bulk_offsets {
     (tokio; $r:expr, $rbuf:expr, $rows:expr) => {{
         $rbuf.clear();
         // Prepare buffer for: initial offset (8 bytes) + offset data (rows * 8 bytes)
-        let total_bytes_needed = 8 + ($rows * 8);
+        let total_bytes_needed = $rows
+            .checked_mul(8)
+            .and_then(|bytes: usize| bytes.checked_add(8))
+            .ok_or_else(|| Error::DeserializeError(format!("Too many offsets: {}", $rows)))?;
         if $rbuf.capacity() < total_bytes_needed {
             $rbuf.reserve(total_bytes_needed - $rbuf.capacity());
         }
@@ -57,6 +71,34 @@ macro_rules! bulk_offsets {
 }
 pub(super) use bulk_offsets;
 
+/// Convert raw `ClickHouse` offsets into a validated Arrow [`OffsetBuffer`].
+///
+/// `ClickHouse` offsets are cumulative `u64`s. Malformed input (decreasing offsets, or offsets
+/// overflowing the Arrow offset type) returns an error rather than panicking in Arrow.
+///
+/// Returns the offset buffer and the total number of inner values.
+pub(super) fn try_offset_buffer<O: OffsetSizeTrait>(
+    offsets: &[u64],
+) -> Result<(OffsetBuffer<O>, usize)> {
+    let mut previous = 0_u64;
+    let mut converted = Vec::with_capacity(offsets.len());
+    for &offset in offsets {
+        if offset < previous {
+            return Err(Error::DeserializeError(format!(
+                "Offsets must be non-decreasing: {offset} < {previous}"
+            )));
+        }
+        let value = usize::try_from(offset).ok().and_then(O::from_usize).ok_or_else(|| {
+            Error::DeserializeError(format!("Offset {offset} overflows offset type"))
+        })?;
+        converted.push(value);
+        previous = offset;
+    }
+    #[expect(clippy::cast_possible_truncation)] // Validated above
+    let total = previous as usize;
+    Ok((OffsetBuffer::new(ScalarBuffer::from(converted)), total))
+}
+
 /// Deserializes a `ClickHouse` `Array` type into an Arrow `ListArray`.
 ///
 /// Reads offsets (skipping the first `0`, as in serialization) and inner values from the input
@@ -115,8 +157,6 @@ pub(super) use bulk_offsets;
 /// assert_eq!(list_array.offsets().iter().copied().collect::<Vec<_>>(), vec![0, 2, 3, 5]);
 /// assert_eq!(list_array.nulls(), None);
 /// ```
-#[expect(clippy::cast_possible_wrap)]
-#[expect(clippy::cast_possible_truncation)]
 pub(crate) async fn deserialize_async<R: ClickHouseRead>(
     inner_type: &Type,
     builder: &mut TypedBuilder,
@@ -149,9 +189,7 @@ pub(crate) async fn deserialize_async<R: ClickHouseRead>(
             // Offsets
             let offset_bytes = bulk_offsets!(tokio; reader, rbuffer, rows);
             let offsets: &[u64] = bytemuck::cast_slice::<u8, u64>(&rbuffer[..offset_bytes]);
-            let offset_buffer =
-                OffsetBuffer::new(offsets.iter().map(|&o| o as $t).collect::<ScalarBuffer<_>>());
-            let total_values = *offsets.last().unwrap_or(&0) as usize;
+            let (offset_buffer, total_values) = try_offset_buffer::<$t>(offsets)?;
             // Recursively deserialize the inner array
             let inner_array = inner_type.deserialize_arrow_async(
                 $b,
@@ -167,7 +205,7 @@ pub(crate) async fn deserialize_async<R: ClickHouseRead>(
             // Construct the ListArray directly
             let inner_dt = inner_array.data_type().clone();
             let field = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, inner_dt, inner_nullable));
-            let list_array = $b_ty::new(field, offset_buffer, inner_array, null_buffer);
+            let list_array = $b_ty::try_new(field, offset_buffer, inner_array, null_buffer)?;
             // Verify length matches expected rows
             if list_array.len() != rows {
                 return Err(Error::DeserializeError(format!(
@@ -193,7 +231,7 @@ pub(crate) async fn deserialize_async<R: ClickHouseRead>(
                 .then_some(NullBuffer::from(nulls.iter().map(|&n| n == 0).collect::<Vec<bool>>()));
             let inner_dt = inner_array.data_type().clone();
             let field = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, inner_dt, inner_nullable));
-            let list_array = FixedSizeListArray::new(field, *size, inner_array, null_buffer);
+            let list_array = FixedSizeListArray::try_new(field, *size, inner_array, null_buffer)?;
             // Verify length matches expected rows
             if list_array.len() != rows {
                 return Err(Error::DeserializeError(format!(
@@ -206,8 +244,6 @@ pub(crate) async fn deserialize_async<R: ClickHouseRead>(
     }
 }
 
-#[expect(clippy::cast_possible_truncation)]
-#[expect(clippy::cast_possible_wrap)]
 #[allow(dead_code)] // TODO: remove once synchronous Arrow path is fully retired
 pub(super) fn deserialize<R: ClickHouseBytesRead>(
     builder: &mut TypedListBuilder,
@@ -235,9 +271,7 @@ pub(super) fn deserialize<R: ClickHouseBytesRead>(
             // Offsets
             let offset_bytes = bulk_offsets!(reader, rbuffer, rows);
             let offsets: &[u64] = bytemuck::cast_slice::<u8, u64>(&rbuffer[..offset_bytes]);
-            let offset_buffer =
-                OffsetBuffer::new(offsets.iter().map(|&o| o as $t).collect::<ScalarBuffer<_>>());
-            let total_values = *offsets.last().unwrap_or(&0) as usize;
+            let (offset_buffer, total_values) = try_offset_buffer::<$t>(offsets)?;
             // Recursively deserialize the inner array
             let inner_array = inner_type.deserialize_arrow(
                 $b,
@@ -253,7 +287,7 @@ pub(super) fn deserialize<R: ClickHouseBytesRead>(
             // Construct the ListArray directly
             let inner_dt = inner_array.data_type().clone();
             let field = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, inner_dt, inner_nullable));
-            let list_array = $b_ty::new(field, offset_buffer, inner_array, null_buffer);
+            let list_array = $b_ty::try_new(field, offset_buffer, inner_array, null_buffer)?;
             // Verify length matches expected rows
             if list_array.len() != rows {
                 return Err(Error::DeserializeError(format!(
@@ -278,7 +312,7 @@ pub(super) fn deserialize<R: ClickHouseBytesRead>(
                 .then_some(NullBuffer::from(nulls.iter().map(|&n| n == 0).collect::<Vec<bool>>()));
             let inner_dt = inner_array.data_type().clone();
             let field = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, inner_dt, inner_nullable));
-            let list_array = FixedSizeListArray::new(field, *size, inner_array, null_buffer);
+            let list_array = FixedSizeListArray::try_new(field, *size, inner_array, null_buffer)?;
             // Verify length matches expected rows
             if list_array.len() != rows {
                 return Err(Error::DeserializeError(format!(
@@ -1890,4 +1924,17 @@ mod tests_sync {
                 .unwrap();
         assert_eq!(values, &expected_dict);
     }
+
+    #[test]
+    fn test_try_offset_buffer_rejects_malformed_offsets() {
+        let (buffer, total) = try_offset_buffer::<i32>(&[1, 3, 3]).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(buffer.len(), 3);
+        // Decreasing
+        assert!(try_offset_buffer::<i32>(&[4, 2]).is_err());
+        // Overflows i32, but fits i64
+        let too_large = u64::from(u32::MAX);
+        assert!(try_offset_buffer::<i32>(&[too_large]).is_err());
+        assert!(try_offset_buffer::<i64>(&[too_large]).is_ok());
+    }
 }
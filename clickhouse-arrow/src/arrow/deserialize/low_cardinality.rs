@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use arrow::array::*;
+use arrow::compute::concat;
 use arrow::datatypes::*;
 use tokio::io::AsyncReadExt;
 
@@ -29,9 +30,13 @@ const SMALL_DICT_MASK_THRESHOLD: usize = 1024;
 ///   - `NEED_GLOBAL_DICTIONARY_BIT` (0x80000000): A global dictionary is included.
 ///   - `NEED_UPDATE_DICTIONARY_BIT` (0x100000000): The global dictionary needs updating.
 ///   - Lower 8 bits: Index type (`TUINT8=0x01`, `TUINT16=0x02`, `TUINT32=0x03`, `TUINT64=0x04`).
-/// - **Dictionary Size** (u64): Number of dictionary entries.
-/// - **Dictionary Values**: Serialized by the inner type’s deserializer (e.g., strings as
-///   `var_uint` length + bytes).
+/// - **Global Dictionary**: If `NEED_GLOBAL_DICTIONARY_BIT` is set and the dictionary is new or
+///   `NEED_UPDATE_DICTIONARY_BIT` is set, its size (u64) and values. Otherwise the dictionary
+///   decoded for an earlier block of the query is reused, see
+///   [`GlobalDictionary`](crate::arrow::builder::dictionary::GlobalDictionary).
+/// - **Additional Keys**: If `HAS_ADDITIONAL_KEYS_BIT` is set, their size (u64) and values,
+///   serialized by the inner type’s deserializer (e.g., strings as `var_uint` length + bytes).
+///   Indices refer to the global dictionary first, then the additional keys offset by its size.
 /// - **Chunk Rows** (u64): Number of rows in a chunk.
 /// - **Indices**: Variable-width integers (u8, u16, u32, or u64) referencing dictionary entries.
 ///
@@ -107,7 +112,13 @@ pub(crate) async fn deserialize_async<R: ClickHouseRead>(
         )));
     };
 
-    let LowCardinalityBuilder { key_builder: keys, value_builder } = lowcard_builder;
+    // `ClickHouse` writes nothing for an empty column, ie the values of empty arrays
+    if rows == 0 {
+        return Ok(new_empty_array(data_type));
+    }
+
+    let LowCardinalityBuilder { key_builder: keys, value_builder, global_dictionary } =
+        lowcard_builder;
 
     // Read flags to determine structure
     let flags = reader.read_u64_le().await?;
@@ -126,56 +137,40 @@ pub(crate) async fn deserialize_async<R: ClickHouseRead>(
         }
     };
 
-    let dict_size = reader.read_u64_le().await? as usize;
-
-    // Deserialize global dictionary or additional keys
-    let dictionary = if needs_global_dictionary || needs_update_dictionary || has_additional_keys {
-        // If the inner type is nullable, then the first value deserialized will be a "default"
-        // value. Use the null mask to enforce this. The serializer does not write a null
-        // v0.4.1: Use stack allocation for small dictionaries to avoid heap allocation
-        if inner.is_nullable() {
-            if dict_size <= SMALL_DICT_MASK_THRESHOLD {
-                // Stack-allocated path for small dictionaries (zero heap allocation)
-                let mut stack_mask = [0u8; SMALL_DICT_MASK_THRESHOLD];
-                stack_mask[0] = 1;
-                let mask_slice = &stack_mask[..dict_size];
-                inner
-                    .strip_null()
-                    .deserialize_arrow_async(
-                        value_builder,
-                        reader,
-                        value_type,
-                        dict_size,
-                        mask_slice,
-                        rbuffer,
-                    )
-                    .await?
-            } else {
-                // Heap-allocated path for large dictionaries
-                let mut mask = vec![0_u8; dict_size];
-                mask[0] = 1;
-                inner
-                    .strip_null()
-                    .deserialize_arrow_async(
-                        value_builder,
-                        reader,
-                        value_type,
-                        dict_size,
-                        &mask,
-                        rbuffer,
-                    )
-                    .await?
-            }
+    // The global dictionary is shared by the following blocks until the server updates it, so it
+    // is decoded once and reused
+    let global = if needs_global_dictionary {
+        let cached = global_dictionary.values.as_ref().filter(|_| !needs_update_dictionary);
+        Some(if let Some(values) = cached {
+            Arc::clone(values)
         } else {
-            inner
-                .strip_null()
-                .deserialize_arrow_async(value_builder, reader, value_type, dict_size, &[], rbuffer)
-                .await?
-        }
-    // No dictionary found
+            let size = reader.read_u64_le().await? as usize;
+            let values = read_dictionary_async(
+                inner,
+                value_builder,
+                value_type,
+                reader,
+                size,
+                true,
+                rbuffer,
+            )
+            .await?;
+            global_dictionary.update(values)
+        })
+    } else {
+        None
+    };
+    let additional = if has_additional_keys {
+        let size = reader.read_u64_le().await? as usize;
+        let first = !needs_global_dictionary;
+        Some(
+            read_dictionary_async(inner, value_builder, value_type, reader, size, first, rbuffer)
+                .await?,
+        )
     } else {
-        return Err(Error::DeserializeError("LowCardinality: no dictionary provided".to_string()));
+        None
     };
+    let dictionary = block_dictionary(additional, global)?;
 
     // Read number of rows in this chunk
     let num_rows = reader.read_u64_le().await? as usize;
@@ -244,7 +239,12 @@ pub(crate) fn deserialize<R: ClickHouseBytesRead>(
         return Err(Error::ArrowDeserialize(format!("LowCardinality: data type {data_type:?}")));
     };
 
-    let LowCardinalityBuilder { key_builder: keys, value_builder } = builder;
+    // `ClickHouse` writes nothing for an empty column, ie the values of empty arrays
+    if rows == 0 {
+        return Ok(new_empty_array(data_type));
+    }
+
+    let LowCardinalityBuilder { key_builder: keys, value_builder, global_dictionary } = builder;
 
     // Read flags to determine structure
     let flags = reader.try_get_u64_le()?;
@@ -263,55 +263,38 @@ pub(crate) fn deserialize<R: ClickHouseBytesRead>(
         }
     };
 
-    #[expect(clippy::cast_possible_truncation)]
-    let dict_size = reader.try_get_u64_le()? as usize;
-
-    // Deserialize global dictionary or additional keys
-    let dictionary = if needs_global_dictionary || needs_update_dictionary || has_additional_keys {
-        // If the inner type is nullable, then the first value deserialized will be a "default"
-        // value. Use the null mask to enforce this. The serializer does not write a null
-        // v0.4.1: Use stack allocation for small dictionaries to avoid heap allocation
-        if inner_type.is_nullable() {
-            if dict_size <= SMALL_DICT_MASK_THRESHOLD {
-                // Stack-allocated path for small dictionaries (zero heap allocation)
-                let mut stack_mask = [0u8; SMALL_DICT_MASK_THRESHOLD];
-                stack_mask[0] = 1;
-                let mask_slice = &stack_mask[..dict_size];
-                inner_type.strip_null().deserialize_arrow(
-                    value_builder,
-                    reader,
-                    value_type,
-                    dict_size,
-                    mask_slice,
-                    rbuffer,
-                )?
-            } else {
-                // Heap-allocated path for large dictionaries
-                let mut mask = vec![0_u8; dict_size];
-                mask[0] = 1;
-                inner_type.strip_null().deserialize_arrow(
-                    value_builder,
-                    reader,
-                    value_type,
-                    dict_size,
-                    &mask,
-                    rbuffer,
-                )?
-            }
+    // The global dictionary is shared by the following blocks until the server updates it, so it
+    // is decoded once and reused
+    let global = if needs_global_dictionary {
+        let cached = global_dictionary.values.as_ref().filter(|_| !needs_update_dictionary);
+        Some(if let Some(values) = cached {
+            Arc::clone(values)
         } else {
-            inner_type.strip_null().deserialize_arrow(
+            #[expect(clippy::cast_possible_truncation)]
+            let size = reader.try_get_u64_le()? as usize;
+            let values = read_dictionary(
+                inner_type,
                 value_builder,
-                reader,
                 value_type,
-                dict_size,
-                &[],
+                reader,
+                size,
+                true,
                 rbuffer,
-            )?
-        }
-    // No dictionary found
+            )?;
+            global_dictionary.update(values)
+        })
+    } else {
+        None
+    };
+    let additional = if has_additional_keys {
+        #[expect(clippy::cast_possible_truncation)]
+        let size = reader.try_get_u64_le()? as usize;
+        let first = !needs_global_dictionary;
+        Some(read_dictionary(inner_type, value_builder, value_type, reader, size, first, rbuffer)?)
     } else {
-        return Err(Error::DeserializeError("LowCardinality: no dictionary provided".to_string()));
+        None
     };
+    let dictionary = block_dictionary(additional, global)?;
 
     // Read number of rows in this chunk
     #[expect(clippy::cast_possible_truncation)]
@@ -364,6 +347,110 @@ pub(crate) fn deserialize<R: ClickHouseBytesRead>(
     }
 }
 
+/// Reads `size` dictionary values. If the dictionary comes `first` in the block's dictionary, the
+/// first value of a nullable dictionary stands for null.
+async fn read_dictionary_async<R: ClickHouseRead>(
+    inner: &Type,
+    value_builder: &mut TypedBuilder,
+    value_type: &DataType,
+    reader: &mut R,
+    size: usize,
+    first: bool,
+    rbuffer: &mut Vec<u8>,
+) -> Result<ArrayRef> {
+    // If the inner type is nullable, then the first value deserialized will be a "default"
+    // value. Use the null mask to enforce this. The serializer does not write a null
+    // v0.4.1: Use stack allocation for small dictionaries to avoid heap allocation
+    if !(first && inner.is_nullable()) || size == 0 {
+        return inner
+            .strip_null()
+            .deserialize_arrow_async(value_builder, reader, value_type, size, &[], rbuffer)
+            .await;
+    }
+    if size <= SMALL_DICT_MASK_THRESHOLD {
+        // Stack-allocated path for small dictionaries (zero heap allocation)
+        let mut stack_mask = [0u8; SMALL_DICT_MASK_THRESHOLD];
+        stack_mask[0] = 1;
+        inner
+            .strip_null()
+            .deserialize_arrow_async(
+                value_builder,
+                reader,
+                value_type,
+                size,
+                &stack_mask[..size],
+                rbuffer,
+            )
+            .await
+    } else {
+        // Heap-allocated path for large dictionaries
+        let mut mask = vec![0_u8; size];
+        mask[0] = 1;
+        inner
+            .strip_null()
+            .deserialize_arrow_async(value_builder, reader, value_type, size, &mask, rbuffer)
+            .await
+    }
+}
+
+/// Reads `size` dictionary values. If the dictionary comes `first` in the block's dictionary, the
+/// first value of a nullable dictionary stands for null.
+fn read_dictionary<R: ClickHouseBytesRead>(
+    inner: &Type,
+    value_builder: &mut TypedBuilder,
+    value_type: &DataType,
+    reader: &mut R,
+    size: usize,
+    first: bool,
+    rbuffer: &mut Vec<u8>,
+) -> Result<ArrayRef> {
+    if !(first && inner.is_nullable()) || size == 0 {
+        return inner.strip_null().deserialize_arrow(
+            value_builder,
+            reader,
+            value_type,
+            size,
+            &[],
+            rbuffer,
+        );
+    }
+    if size <= SMALL_DICT_MASK_THRESHOLD {
+        let mut stack_mask = [0u8; SMALL_DICT_MASK_THRESHOLD];
+        stack_mask[0] = 1;
+        inner.strip_null().deserialize_arrow(
+            value_builder,
+            reader,
+            value_type,
+            size,
+            &stack_mask[..size],
+            rbuffer,
+        )
+    } else {
+        let mut mask = vec![0_u8; size];
+        mask[0] = 1;
+        inner.strip_null().deserialize_arrow(
+            value_builder,
+            reader,
+            value_type,
+            size,
+            &mask,
+            rbuffer,
+        )
+    }
+}
+
+/// The dictionary the indices of a block refer to: the global dictionary, followed by the block's
+/// additional keys. Only the dictionary that comes first holds the null value of a nullable type.
+fn block_dictionary(additional: Option<ArrayRef>, global: Option<ArrayRef>) -> Result<ArrayRef> {
+    match (additional, global) {
+        (Some(additional), Some(global)) => Ok(concat(&[global.as_ref(), additional.as_ref()])?),
+        (Some(dictionary), None) | (None, Some(dictionary)) => Ok(dictionary),
+        (None, None) => {
+            Err(Error::DeserializeError("LowCardinality: no dictionary provided".to_string()))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
@@ -555,6 +642,125 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_deserialize_low_cardinality_shared_global_dictionary() {
+        let inner_type = Type::String;
+        let opts = Some(ArrowOptions::default().with_strings_as_strings(true));
+        let value_type = ch_to_arrow_type(&inner_type, opts).unwrap().0;
+        let data_type = DataType::Dictionary(Box::new(DataType::Int32), Box::new(value_type));
+        let mut builder =
+            TypedBuilder::try_new(&Type::LowCardinality(Box::new(inner_type.clone())), &data_type)
+                .unwrap();
+        let updated = vec![
+            0, 5, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | NeedGlobal | NeedUpdate
+            2, 0, 0, 0, 0, 0, 0, 0, // Global dict size: 2
+            1, b'a', 1, b'b', // Global dict: ["a", "b"]
+            2, 0, 0, 0, 0, 0, 0, 0, // Key count: 2
+            1, 0, // Indices: [1, 0]
+        ];
+        // Reuses the global dictionary of the previous block
+        let reused = vec![
+            0, 1, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | NeedGlobal
+            3, 0, 0, 0, 0, 0, 0, 0, // Key count: 3
+            0, 0, 1, // Indices: [0, 0, 1]
+        ];
+        let additional = vec![
+            0, 3, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | NeedGlobal | HasAdditionalKeys
+            1, 0, 0, 0, 0, 0, 0, 0, // Additional keys size: 1
+            1, b'c', // Additional keys: ["c"]
+            3, 0, 0, 0, 0, 0, 0, 0, // Key count: 3
+            2, 0, 1, // Indices: [2, 0, 1], additional keys after the global dictionary
+        ];
+        let mut results = vec![];
+        for (rows, input) in [(2, updated), (3, reused), (3, additional)] {
+            let array = deserialize_async(
+                &inner_type,
+                &mut builder,
+                &data_type,
+                &mut Cursor::new(input),
+                rows,
+                &[],
+                &mut vec![],
+            )
+            .await
+            .unwrap();
+            let dict_array = array.as_any().downcast_ref::<DictionaryArray<Int32Type>>().unwrap();
+            let values = dict_array
+                .downcast_dict::<StringArray>()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(str::to_string))
+                .collect::<Vec<_>>();
+            results.push((Arc::clone(dict_array.values()), values));
+        }
+
+        let strings = |values: &[&str]| -> Vec<Option<String>> {
+            values.iter().map(|v| Some((*v).to_string())).collect()
+        };
+        assert_eq!(results[0].1, strings(&["b", "a"]));
+        assert_eq!(results[1].1, strings(&["a", "a", "b"]));
+        assert_eq!(results[2].1, strings(&["c", "a", "b"]));
+        // The second block shares the decoded dictionary of the first
+        assert!(Arc::ptr_eq(&results[0].0, &results[1].0));
+        let TypedBuilder::LowCardinality(builder) = builder else {
+            panic!("expected a LowCardinality builder")
+        };
+        assert_eq!(builder.global_dictionary.version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_low_cardinality_nullable_global_dictionary() {
+        let inner_type = Type::Nullable(Box::new(Type::String));
+        let opts = Some(ArrowOptions::default().with_strings_as_strings(true));
+        let value_type = ch_to_arrow_type(&inner_type, opts).unwrap().0;
+        let data_type = DataType::Dictionary(Box::new(DataType::Int32), Box::new(value_type));
+        let mut builder =
+            TypedBuilder::try_new(&Type::LowCardinality(Box::new(inner_type.clone())), &data_type)
+                .unwrap();
+        let first = vec![
+            0, 7, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | NeedGlobal | HasAdditionalKeys | NeedUpdate
+            2, 0, 0, 0, 0, 0, 0, 0, // Global dict size: 2
+            0, 1, b'a', // Global dict: [null, "a"]
+            1, 0, 0, 0, 0, 0, 0, 0, // Additional keys size: 1
+            1, b'c', // Additional keys: ["c"], not null despite being at index 0
+            3, 0, 0, 0, 0, 0, 0, 0, // Key count: 3
+            2, 1, 0, // Indices: [2, 1, 0]
+        ];
+        // Reuses the global dictionary of the previous block, with other additional keys
+        let second = vec![
+            0, 3, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | NeedGlobal | HasAdditionalKeys
+            1, 0, 0, 0, 0, 0, 0, 0, // Additional keys size: 1
+            1, b'd', // Additional keys: ["d"]
+            3, 0, 0, 0, 0, 0, 0, 0, // Key count: 3
+            2, 0, 1, // Indices: [2, 0, 1]
+        ];
+        let mut results = vec![];
+        for input in [first, second] {
+            let array = deserialize_async(
+                &inner_type,
+                &mut builder,
+                &data_type,
+                &mut Cursor::new(input),
+                3,
+                &[],
+                &mut vec![],
+            )
+            .await
+            .unwrap();
+            let dict_array = array.as_any().downcast_ref::<DictionaryArray<Int32Type>>().unwrap();
+            let values = dict_array
+                .downcast_dict::<StringArray>()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(str::to_string))
+                .collect::<Vec<_>>();
+            results.push(values);
+        }
+
+        assert_eq!(results[0], vec![Some("c".into()), Some("a".into()), None]);
+        assert_eq!(results[1], vec![Some("d".into()), None, Some("a".into())]);
+    }
+
     #[tokio::test]
     async fn test_deserialize_low_cardinality_zero_rows() {
         let inner_type = Type::String;
@@ -10,7 +10,7 @@
 use std::sync::Arc;
 
 use arrow::array::{ArrayRef, MapArray, StructArray};
-use arrow::buffer::{NullBuffer, OffsetBuffer, ScalarBuffer};
+use arrow::buffer::NullBuffer;
 use arrow::datatypes::{DataType, Field, Fields};
 use tokio::io::AsyncReadExt;
 
@@ -41,7 +41,6 @@ use crate::{Error, Result, Type};
 /// # Errors
 /// - Returns `ArrowDeserialize` if the key or value type is unsupported or the data is malformed.
 /// - Returns `Io` if reading from the reader fails.
-#[expect(clippy::cast_possible_truncation)]
 pub(super) async fn deserialize_async<R: ClickHouseRead>(
     types: (&Type, &Type),
     builder: &mut TypedBuilder,
@@ -64,9 +63,7 @@ pub(super) async fn deserialize_async<R: ClickHouseRead>(
 
     let offset_bytes = super::list::bulk_offsets!(tokio; reader, rbuffer, rows);
     let offsets: &[u64] = bytemuck::cast_slice::<u8, u64>(&rbuffer[..offset_bytes]);
-    let offset_buffer =
-        OffsetBuffer::new(offsets.iter().map(|&o| o as i32).collect::<ScalarBuffer<_>>());
-    let total_pairs = *offsets.last().unwrap_or(&0) as usize;
+    let (offset_buffer, total_pairs) = super::list::try_offset_buffer::<i32>(offsets)?;
 
     let key_array = key_type
         .deserialize_arrow_async(
@@ -95,8 +92,7 @@ pub(super) async fn deserialize_async<R: ClickHouseRead>(
         DataType::Struct(Fields::from(vec![Arc::clone(key_field), Arc::clone(value_field)])),
         false,
     ));
-    let struct_fields =
-        vec![(Arc::clone(key_field), key_array), (Arc::clone(value_field), value_array)];
+    let struct_fields = Fields::from(vec![Arc::clone(key_field), Arc::clone(value_field)]);
 
     // Construct MapArray
     let null_buffer = if nulls.is_empty() {
@@ -105,8 +101,8 @@ pub(super) async fn deserialize_async<R: ClickHouseRead>(
         Some(NullBuffer::from(nulls.iter().map(|&n| n == 0).collect::<Vec<bool>>()))
     };
 
-    let struct_arr = StructArray::from(struct_fields);
-    Ok(Arc::new(MapArray::new(struct_field, offset_buffer, struct_arr, null_buffer, false)))
+    let struct_arr = StructArray::try_new(struct_fields, vec![key_array, value_array], None)?;
+    Ok(Arc::new(MapArray::try_new(struct_field, offset_buffer, struct_arr, null_buffer, false)?))
 }
 
 #[allow(dead_code)] // TODO: remove once synchronous Arrow path is fully retired
@@ -128,13 +124,7 @@ pub(super) fn deserialize<R: ClickHouseBytesRead>(
 
     let offset_bytes = super::list::bulk_offsets!(reader, rbuffer, rows);
     let offsets: &[u64] = bytemuck::cast_slice::<u8, u64>(&rbuffer[..offset_bytes]);
-    #[expect(clippy::cast_possible_truncation)]
-    let (offset_buffer, total_pairs) = {
-        (
-            OffsetBuffer::new(offsets.iter().map(|&o| o as i32).collect::<ScalarBuffer<_>>()),
-            *offsets.last().unwrap_or(&0) as usize,
-        )
-    };
+    let (offset_buffer, total_pairs) = super::list::try_offset_buffer::<i32>(offsets)?;
 
     // Read keys and values
     let key_array = kt.deserialize_arrow(key_b, reader, key_dt, total_pairs, &[], rbuffer)?;
@@ -146,8 +136,7 @@ pub(super) fn deserialize<R: ClickHouseBytesRead>(
         DataType::Struct(Fields::from(vec![Arc::clone(key_field), Arc::clone(value_field)])),
         false,
     ));
-    let struct_fields =
-        vec![(Arc::clone(key_field), key_array), (Arc::clone(value_field), value_array)];
+    let struct_fields = Fields::from(vec![Arc::clone(key_field), Arc::clone(value_field)]);
 
     // Construct MapArray
     let null_buffer = if nulls.is_empty() {
@@ -156,8 +145,8 @@ pub(super) fn deserialize<R: ClickHouseBytesRead>(
         Some(NullBuffer::from(nulls.iter().map(|&n| n == 0).collect::<Vec<bool>>()))
     };
 
-    let struct_arr = StructArray::from(struct_fields);
-    Ok(Arc::new(MapArray::new(struct_field, offset_buffer, struct_arr, null_buffer, false)))
+    let struct_arr = StructArray::try_new(struct_fields, vec![key_array, value_array], None)?;
+    Ok(Arc::new(MapArray::try_new(struct_field, offset_buffer, struct_arr, null_buffer, false)?))
 }
 
 #[cfg(test)]
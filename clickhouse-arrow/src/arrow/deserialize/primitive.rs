@@ -24,7 +24,17 @@ use crate::{Error, Result, Type};
 
 macro_rules! primitive_bulk {
     ($reader:expr, $rows:expr, $buf:expr, $type:ty) => {{
-        let byte_count = $rows * std::mem::size_of::<$type>();
+        let byte_count = $rows
+            .checked_mul(std::mem::size_of::<$type>())
+            .ok_or_else(|| $crate::Error::DeserializeError(format!("Too many rows: {}", $rows)))?;
+        // Never allocate more than the remaining input could possibly fill
+        if $reader.remaining() < byte_count {
+            return Err($crate::Error::DeserializeError(format!(
+                "Not enough data for {} rows: {} bytes remaining",
+                $rows,
+                $reader.remaining()
+            )));
+        }
         if $buf.capacity() < byte_count {
             $buf.reserve(byte_count - $buf.capacity());
         }
@@ -33,7 +43,9 @@ macro_rules! primitive_bulk {
         byte_count
     }};
     (tokio; $reader:expr, $rows:expr, $buf:expr, $type:ty) => {{
-        let byte_count = $rows * std::mem::size_of::<$type>();
+        let byte_count = $rows
+            .checked_mul(std::mem::size_of::<$type>())
+            .ok_or_else(|| $crate::Error::DeserializeError(format!("Too many rows: {}", $rows)))?;
         if $buf.capacity() < byte_count {
             $buf.reserve(byte_count - $buf.capacity());
         }
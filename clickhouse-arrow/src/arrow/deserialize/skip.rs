@@ -0,0 +1,293 @@
+/// Skipping over encoded columns that are not part of a query's projection.
+///
+/// Columns excluded by a projection (see [`crate::explain::QueryOptions::project`]) are
+/// consumed from the reader without being decoded. Fixed width values are skipped in a single
+/// read, strings by their length prefixes, and nested types by their offsets and null maps.
+/// Types with serialization state, such as `LowCardinality`, `Variant`, or `Dynamic`, are not
+/// skippable and must be decoded and dropped instead.
+use futures_util::future::BoxFuture;
+use tokio::io::AsyncReadExt;
+
+use crate::geo::normalize_geo_type;
+use crate::io::{ClickHouseBytesRead, ClickHouseRead};
+use crate::{Error, Result, Type};
+
+/// Returns the encoded width of a single value of a fixed width type.
+fn fixed_width(type_: &Type) -> Option<usize> {
+    Some(match type_ {
+        Type::Int8 | Type::UInt8 | Type::Enum8(_) => 1,
+        Type::Int16 | Type::UInt16 | Type::Date | Type::Enum16(_) | Type::BFloat16 => 2,
+        Type::Int32
+        | Type::UInt32
+        | Type::Float32
+        | Type::Decimal32(_)
+        | Type::Date32
+        | Type::DateTime(_)
+        | Type::Ipv4
+        | Type::Time => 4,
+        Type::Int64
+        | Type::UInt64
+        | Type::Float64
+        | Type::Decimal64(_)
+        | Type::DateTime64(_, _)
+        | Type::Time64(_) => 8,
+        Type::Int128
+        | Type::UInt128
+        | Type::Decimal128(_)
+        | Type::Uuid
+        | Type::Ipv6
+        | Type::Point => 16,
+        Type::Int256 | Type::UInt256 | Type::Decimal256(_) => 32,
+        Type::FixedSizedString(n) | Type::FixedSizedBinary(n) => *n,
+        _ => return None,
+    })
+}
+
+/// Whether a column of `type_` can be skipped without decoding it.
+pub(crate) fn is_skippable(type_: &Type) -> bool {
+    match type_ {
+        Type::String | Type::Binary | Type::Ring | Type::Polygon | Type::MultiPolygon => true,
+        Type::Nullable(inner) | Type::Array(inner) => is_skippable(inner),
+        Type::Map(key, value) => is_skippable(key) && is_skippable(value),
+        Type::Tuple(inner) => inner.iter().all(is_skippable),
+        _ => fixed_width(type_).is_some(),
+    }
+}
+
+fn unskippable(type_: &Type) -> Error {
+    Error::ArrowDeserialize(format!("Cannot skip column of type {type_}"))
+}
+
+/// Consume `len` bytes from the reader.
+async fn skip_bytes<R: ClickHouseRead>(reader: &mut R, mut len: usize) -> Result<()> {
+    let mut scratch = [0u8; 1024];
+    while len > 0 {
+        let chunk = len.min(scratch.len());
+        let _ = reader.read_exact(&mut scratch[..chunk]).await?;
+        len -= chunk;
+    }
+    Ok(())
+}
+
+/// Read the offsets of `rows` arrays, returning the total number of nested values.
+async fn skip_offsets<R: ClickHouseRead>(reader: &mut R, rows: usize) -> Result<usize> {
+    if rows == 0 {
+        return Ok(0);
+    }
+    // Offsets are cumulative, so only the last one is needed
+    skip_bytes(reader, (rows - 1) * 8).await?;
+    usize::try_from(reader.read_u64_le().await?)
+        .map_err(|_| Error::Protocol("Array offset out of range".into()))
+}
+
+/// Consume `rows` encoded values of `type_` from the reader without decoding them.
+///
+/// # Errors
+/// - Returns `ArrowDeserialize` if the type is not skippable, see [`is_skippable`].
+/// - Returns `Io` if the reader ends early.
+pub(crate) fn skip_async<'a, R: ClickHouseRead>(
+    type_: &'a Type,
+    reader: &'a mut R,
+    rows: usize,
+) -> BoxFuture<'a, Result<()>> {
+    Box::pin(async move {
+        if let Some(width) = fixed_width(type_) {
+            return skip_bytes(reader, rows * width).await;
+        }
+        match type_ {
+            Type::String | Type::Binary => {
+                for _ in 0..rows {
+                    #[expect(clippy::cast_possible_truncation)]
+                    let len = reader.read_var_uint().await? as usize;
+                    skip_bytes(reader, len).await?;
+                }
+            }
+            Type::Nullable(inner) => {
+                skip_bytes(reader, rows).await?;
+                skip_async(inner, reader, rows).await?;
+            }
+            Type::Array(inner) => {
+                let values = skip_offsets(reader, rows).await?;
+                skip_async(inner, reader, values).await?;
+            }
+            Type::Map(key, value) => {
+                let values = skip_offsets(reader, rows).await?;
+                skip_async(key, reader, values).await?;
+                skip_async(value, reader, values).await?;
+            }
+            Type::Tuple(inner) => {
+                for inner_type in inner {
+                    skip_async(inner_type, reader, rows).await?;
+                }
+            }
+            Type::Ring | Type::Polygon | Type::MultiPolygon => {
+                let normalized = normalize_geo_type(type_)?;
+                skip_async(&normalized, reader, rows).await?;
+            }
+            _ => return Err(unskippable(type_)),
+        }
+        Ok(())
+    })
+}
+
+/// Consume `len` bytes from the buffer.
+fn skip_bytes_sync<R: ClickHouseBytesRead>(reader: &mut R, len: usize) -> Result<()> {
+    if reader.remaining() < len {
+        return Err(Error::Protocol(format!("Unexpected EOF skipping {len} bytes")));
+    }
+    reader.advance(len);
+    Ok(())
+}
+
+/// Sync version of [`skip_offsets`] for bytes::Buf readers.
+fn skip_offsets_sync<R: ClickHouseBytesRead>(reader: &mut R, rows: usize) -> Result<usize> {
+    if rows == 0 {
+        return Ok(0);
+    }
+    skip_bytes_sync(reader, (rows - 1) * 8)?;
+    if reader.remaining() < 8 {
+        return Err(Error::Protocol("Unexpected EOF reading array offset".into()));
+    }
+    usize::try_from(reader.get_u64_le())
+        .map_err(|_| Error::Protocol("Array offset out of range".into()))
+}
+
+/// Sync version of [`skip_async`] for bytes::Buf readers.
+///
+/// # Errors
+/// - Returns `ArrowDeserialize` if the type is not skippable, see [`is_skippable`].
+/// - Returns `Protocol` if the buffer ends early.
+pub(crate) fn skip<R: ClickHouseBytesRead>(
+    type_: &Type,
+    reader: &mut R,
+    rows: usize,
+) -> Result<()> {
+    if let Some(width) = fixed_width(type_) {
+        return skip_bytes_sync(reader, rows * width);
+    }
+    match type_ {
+        Type::String | Type::Binary => {
+            for _ in 0..rows {
+                #[expect(clippy::cast_possible_truncation)]
+                let len = reader.try_get_var_uint()? as usize;
+                skip_bytes_sync(reader, len)?;
+            }
+        }
+        Type::Nullable(inner) => {
+            skip_bytes_sync(reader, rows)?;
+            skip(inner, reader, rows)?;
+        }
+        Type::Array(inner) => {
+            let values = skip_offsets_sync(reader, rows)?;
+            skip(inner, reader, values)?;
+        }
+        Type::Map(key, value) => {
+            let values = skip_offsets_sync(reader, rows)?;
+            skip(key, reader, values)?;
+            skip(value, reader, values)?;
+        }
+        Type::Tuple(inner) => {
+            for inner_type in inner {
+                skip(inner_type, reader, rows)?;
+            }
+        }
+        Type::Ring | Type::Polygon | Type::MultiPolygon => {
+            skip(&normalize_geo_type(type_)?, reader, rows)?;
+        }
+        _ => return Err(unskippable(type_)),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use arrow::array::*;
+    use arrow::datatypes::*;
+
+    use super::*;
+    use crate::arrow::serialize::ClickHouseArrowSerializer;
+    use crate::formats::SerializerState;
+
+    /// Serialize `array` as `type_` followed by a sentinel byte.
+    fn encode(type_: &Type, array: &ArrayRef) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let mut state = SerializerState::default();
+        type_.serialize(&mut buffer, array, array.data_type(), &mut state).unwrap();
+        buffer.push(0xAB);
+        buffer
+    }
+
+    fn fixtures() -> Vec<(Type, ArrayRef)> {
+        let strings = Arc::new(StringArray::from(vec![Some("a"), None, Some("long string")]));
+        let list = Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![Some(3)]),
+        ]));
+        let tuple = Arc::new(StructArray::from(vec![
+            (
+                Arc::new(Field::new("a", DataType::Int64, false)),
+                Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("b", DataType::Utf8, false)),
+                Arc::new(StringArray::from(vec!["x", "yy", "zzz"])) as ArrayRef,
+            ),
+        ]));
+        vec![
+            (Type::Int32, Arc::new(Int32Array::from(vec![1, 2, 3]))),
+            (Type::Nullable(Box::new(Type::String)), strings),
+            (Type::Array(Box::new(Type::Int32)), list),
+            (Type::Tuple(vec![Type::Int64, Type::String]), tuple),
+        ]
+    }
+
+    #[test]
+    fn test_is_skippable() {
+        assert!(is_skippable(&Type::Int32));
+        assert!(is_skippable(&Type::Array(Box::new(Type::Nullable(Box::new(Type::String))))));
+        assert!(is_skippable(&Type::Map(Box::new(Type::String), Box::new(Type::UInt64))));
+        assert!(is_skippable(&Type::Polygon));
+        assert!(!is_skippable(&Type::LowCardinality(Box::new(Type::String))));
+        assert!(!is_skippable(&Type::Array(Box::new(Type::LowCardinality(Box::new(
+            Type::String
+        ))))));
+        assert!(!is_skippable(&Type::Dynamic { max_types: None }));
+    }
+
+    #[tokio::test]
+    async fn test_skip_async() {
+        for (type_, array) in fixtures() {
+            let bytes = encode(&type_, &array);
+            let mut reader = Cursor::new(bytes);
+            skip_async(&type_, &mut reader, array.len()).await.unwrap();
+            assert_eq!(reader.read_u8().await.unwrap(), 0xAB, "{type_}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skip_async_truncated() {
+        let mut reader = Cursor::new(vec![0u8; 7]);
+        assert!(skip_async(&Type::Int32, &mut reader, 2).await.is_err());
+    }
+
+    #[test]
+    fn test_skip() {
+        for (type_, array) in fixtures() {
+            let bytes = encode(&type_, &array);
+            let mut reader = bytes::Bytes::from(bytes);
+            skip(&type_, &mut reader, array.len()).unwrap();
+            assert_eq!(&reader[..], [0xAB], "{type_}");
+        }
+    }
+
+    #[test]
+    fn test_skip_unskippable() {
+        let mut reader = bytes::Bytes::from_static(&[0; 16]);
+        let type_ = Type::LowCardinality(Box::new(Type::String));
+        assert!(skip(&type_, &mut reader, 1).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+/// Deserialization logic for columns whose nested elements use sparse serialization.
+///
+/// `ClickHouse` can choose sparse serialization independently for each element of a `Tuple`
+/// (e.g. `Tuple(a Int64, b String)` where `a` is mostly defaults within a part). Each sparse
+/// element is written as its offsets followed by only the non-default values, so the elements
+/// must be read one at a time and expanded before the `StructArray` is assembled.
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::datatypes::DataType;
+use futures_util::future::BoxFuture;
+
+use super::ClickHouseArrowDeserializer;
+use crate::arrow::builder::TypedBuilder;
+use crate::io::{ClickHouseBytesRead, ClickHouseRead};
+use crate::native::sparse::{
+    SerializationKinds, expand_sparse_array, read_sparse_offsets, read_sparse_offsets_sync,
+};
+use crate::{Error, Result, Type};
+
+/// Deserializes a column according to its serialization `kinds`, expanding sparse elements.
+///
+/// Sparse elements are always expanded to dense arrays, as the data type of a `Struct` child must
+/// match its field.
+///
+/// # Errors
+/// - Returns `ArrowDeserialize` if the builder or data type do not match the tuple.
+/// - Returns `SparseOffsets` if sparse offsets are malformed.
+pub(crate) fn deserialize_async<'a, R: ClickHouseRead>(
+    type_: &'a Type,
+    kinds: &'a SerializationKinds,
+    builder: &'a mut TypedBuilder,
+    reader: &'a mut R,
+    data_type: &'a DataType,
+    rows: usize,
+    rbuffer: &'a mut Vec<u8>,
+) -> BoxFuture<'a, Result<ArrayRef>> {
+    Box::pin(async move {
+        if kinds.sparse {
+            let offsets = read_sparse_offsets(reader, rows).await?;
+            let values = type_
+                .deserialize_arrow_async(builder, reader, data_type, offsets.len(), &[], rbuffer)
+                .await?;
+            return expand_sparse_array(&values, &offsets, rows);
+        }
+
+        if !kinds.has_nested_sparse() {
+            return type_
+                .deserialize_arrow_async(builder, reader, data_type, rows, &[], rbuffer)
+                .await;
+        }
+
+        let (inner, builders, fields) = tuple_parts(type_, builder, data_type)?;
+        let mut arrays = Vec::with_capacity(inner.len());
+        let elements = inner.iter().zip(&kinds.elements).zip(fields.iter());
+        for (b, ((inner_type, inner_kinds), field)) in builders.iter_mut().zip(elements) {
+            arrays.push(
+                deserialize_async(
+                    inner_type,
+                    inner_kinds,
+                    b,
+                    reader,
+                    field.data_type(),
+                    rows,
+                    rbuffer,
+                )
+                .await?,
+            );
+        }
+        Ok(Arc::new(StructArray::try_new(fields.clone(), arrays, None)?) as ArrayRef)
+    })
+}
+
+/// Sync version of [`deserialize_async`] for bytes::Buf readers.
+///
+/// # Errors
+/// - Returns `ArrowDeserialize` if the builder or data type do not match the tuple.
+/// - Returns `SparseOffsets` if sparse offsets are malformed.
+pub(crate) fn deserialize<R: ClickHouseBytesRead>(
+    type_: &Type,
+    kinds: &SerializationKinds,
+    builder: &mut TypedBuilder,
+    reader: &mut R,
+    data_type: &DataType,
+    rows: usize,
+    rbuffer: &mut Vec<u8>,
+) -> Result<ArrayRef> {
+    if kinds.sparse {
+        let offsets = read_sparse_offsets_sync(reader, rows)?;
+        let values =
+            type_.deserialize_arrow(builder, reader, data_type, offsets.len(), &[], rbuffer)?;
+        return expand_sparse_array(&values, &offsets, rows);
+    }
+
+    if !kinds.has_nested_sparse() {
+        return type_.deserialize_arrow(builder, reader, data_type, rows, &[], rbuffer);
+    }
+
+    let (inner, builders, fields) = tuple_parts(type_, builder, data_type)?;
+    let mut arrays = Vec::with_capacity(inner.len());
+    let elements = inner.iter().zip(&kinds.elements).zip(fields.iter());
+    for (b, ((inner_type, inner_kinds), field)) in builders.iter_mut().zip(elements) {
+        arrays.push(deserialize(
+            inner_type,
+            inner_kinds,
+            b,
+            reader,
+            field.data_type(),
+            rows,
+            rbuffer,
+        )?);
+    }
+    Ok(Arc::new(StructArray::try_new(fields.clone(), arrays, None)?))
+}
+
+/// Destructure a `Tuple` column into its element types, builders, and fields.
+fn tuple_parts<'a>(
+    type_: &'a Type,
+    builder: &'a mut TypedBuilder,
+    data_type: &'a DataType,
+) -> Result<(&'a [Type], &'a mut Vec<TypedBuilder>, &'a arrow::datatypes::Fields)> {
+    match (type_, builder, data_type) {
+        (Type::Tuple(inner), TypedBuilder::Tuple(builders), DataType::Struct(fields))
+            if inner.len() == builders.len() && inner.len() == fields.len() =>
+        {
+            Ok((inner, builders, fields))
+        }
+        (type_, _, data_type) => Err(Error::ArrowDeserialize(format!(
+            "Nested serialization kinds require a tuple, found {type_:?} as {data_type:?}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use arrow::array::{AsArray, Int64Array, StringArray};
+    use arrow::datatypes::Int64Type;
+
+    use super::*;
+    use crate::ArrowOptions;
+    use crate::arrow::types::ch_to_arrow_type;
+    use crate::native::sparse::END_OF_GRANULE_FLAG;
+
+    fn var_uint(mut value: u64, out: &mut Vec<u8>) {
+        loop {
+            #[expect(clippy::cast_possible_truncation)]
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                break;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    /// `Tuple(Int64, String)` over 4 rows where the `Int64` element is sparse: [0, 5, 0, 0]
+    fn sparse_tuple_input() -> (Type, SerializationKinds, Vec<u8>) {
+        let type_ = Type::Tuple(vec![Type::Int64, Type::String]);
+        let kinds = SerializationKinds {
+            sparse:   false,
+            custom:   false,
+            elements: vec![
+                SerializationKinds { sparse: true, custom: true, elements: vec![] },
+                SerializationKinds::default(),
+            ],
+        };
+        let mut data = Vec::new();
+        var_uint(1, &mut data);
+        var_uint(2 | END_OF_GRANULE_FLAG, &mut data);
+        data.extend(5_i64.to_le_bytes());
+        for s in ["a", "b", "c", "d"] {
+            var_uint(1, &mut data);
+            data.extend(s.as_bytes());
+        }
+        (type_, kinds, data)
+    }
+
+    fn assert_sparse_tuple(array: &ArrayRef) {
+        let struct_array = array.as_struct();
+        assert_eq!(struct_array.len(), 4);
+        assert_eq!(
+            struct_array.column(0).as_primitive::<Int64Type>(),
+            &Int64Array::from(vec![0, 5, 0, 0])
+        );
+        assert_eq!(
+            struct_array.column(1).as_string::<i32>(),
+            &StringArray::from(vec!["a", "b", "c", "d"])
+        );
+    }
+
+    #[test]
+    fn test_deserialize_tuple_with_sparse_element() {
+        let (type_, kinds, data) = sparse_tuple_input();
+        let opts = Some(ArrowOptions::default().with_strings_as_strings(true));
+        let (data_type, _) = ch_to_arrow_type(&type_, opts).unwrap();
+        let mut builder = TypedBuilder::try_new(&type_, &data_type).unwrap();
+        let mut reader = Cursor::new(data);
+        let array =
+            deserialize(&type_, &kinds, &mut builder, &mut reader, &data_type, 4, &mut vec![])
+                .unwrap();
+        assert_sparse_tuple(&array);
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_async_tuple_with_sparse_element() {
+        let (type_, kinds, data) = sparse_tuple_input();
+        let opts = Some(ArrowOptions::default().with_strings_as_strings(true));
+        let (data_type, _) = ch_to_arrow_type(&type_, opts).unwrap();
+        let mut builder = TypedBuilder::try_new(&type_, &data_type).unwrap();
+        let mut reader = Cursor::new(data);
+        let mut rbuffer = vec![];
+        let array = deserialize_async(
+            &type_,
+            &kinds,
+            &mut builder,
+            &mut reader,
+            &data_type,
+            4,
+            &mut rbuffer,
+        )
+        .await
+        .unwrap();
+        assert_sparse_tuple(&array);
+    }
+
+    #[test]
+    fn test_deserialize_nested_kinds_require_tuple() {
+        let kinds = SerializationKinds {
+            sparse:   false,
+            custom:   false,
+            elements: vec![SerializationKinds { sparse: true, custom: true, elements: vec![] }],
+        };
+        let mut builder = TypedBuilder::try_new(&Type::Int64, &DataType::Int64).unwrap();
+        let mut reader = Cursor::new(vec![0_u8; 8]);
+        let result = deserialize(
+            &Type::Int64,
+            &kinds,
+            &mut builder,
+            &mut reader,
+            &DataType::Int64,
+            1,
+            &mut vec![],
+        );
+        assert!(matches!(result, Err(Error::ArrowDeserialize(_))));
+    }
+}
@@ -0,0 +1,317 @@
+//! Explode `Map` columns into `Struct` columns with one field per key.
+//!
+//! Some downstream tools cannot handle Arrow's `MapArray`, but do handle structs. A
+//! [`MapExploder`] rewrites each `Map(K, V)` column of a batch into a `Struct` with one nullable
+//! field of type `V` per observed key, named after the key. Rows without a key are null in that
+//! field.
+//!
+//! Keys are collected across batches in the order they are first seen, so a stream's schema only
+//! ever grows: a batch contains a field for every key seen in it or in any earlier batch. The
+//! number of fields is capped by [`MapExplodeOptions::max_keys`], with keys past the cap handled
+//! by [`MapOverflow`].
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::buffer::OffsetBuffer;
+use arrow::compute::{cast, take};
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use indexmap::IndexSet;
+
+use crate::{Error, Result};
+
+/// The name of the field holding entries past [`MapExplodeOptions::max_keys`] when using
+/// [`MapOverflow::Keep`].
+pub const MAP_OVERFLOW_FIELD_NAME: &str = "__overflow";
+
+/// The default maximum number of fields an exploded map column may have.
+pub const DEFAULT_MAX_MAP_KEYS: usize = 128;
+
+/// How keys past [`MapExplodeOptions::max_keys`] are handled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MapOverflow {
+    /// Fail the batch (default).
+    #[default]
+    Error,
+    /// Silently drop the entries.
+    Drop,
+    /// Keep the entries in an extra `Map` field named [`MAP_OVERFLOW_FIELD_NAME`].
+    Keep,
+}
+
+/// Configures which `Map` columns are exploded into `Struct` columns, see [`MapExploder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapExplodeOptions {
+    /// Names of the columns to explode, or `None` for every `Map` column.
+    pub columns:  Option<Vec<String>>,
+    /// The maximum number of key fields per column.
+    pub max_keys: usize,
+    /// How keys past `max_keys` are handled.
+    pub overflow: MapOverflow,
+}
+
+impl Default for MapExplodeOptions {
+    fn default() -> Self { Self::new() }
+}
+
+impl MapExplodeOptions {
+    /// Explode every `Map` column into at most [`DEFAULT_MAX_MAP_KEYS`] fields, failing on more.
+    pub fn new() -> Self {
+        Self { columns: None, max_keys: DEFAULT_MAX_MAP_KEYS, overflow: MapOverflow::Error }
+    }
+
+    /// Only explode the named columns.
+    #[must_use]
+    pub fn with_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the maximum number of key fields per column.
+    #[must_use]
+    pub fn with_max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys;
+        self
+    }
+
+    /// Set how keys past the maximum are handled.
+    #[must_use]
+    pub fn with_overflow(mut self, overflow: MapOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    fn includes(&self, name: &str) -> bool {
+        self.columns.as_ref().is_none_or(|columns| columns.iter().any(|c| c == name))
+    }
+}
+
+/// Explodes `Map` columns of a stream of batches into `Struct` columns.
+///
+/// Keys are tracked per column across calls to [`MapExploder::explode`], so use one exploder per
+/// result stream.
+///
+/// # Example
+/// ```rust,ignore
+/// use clickhouse_arrow::arrow::explode::{MapExplodeOptions, MapExploder, MapOverflow};
+///
+/// let mut exploder = MapExploder::new(
+///     MapExplodeOptions::new().with_max_keys(16).with_overflow(MapOverflow::Keep),
+/// );
+/// let batch = exploder.explode(batch)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MapExploder {
+    options: MapExplodeOptions,
+    keys:    HashMap<String, IndexSet<String>>,
+}
+
+impl MapExploder {
+    /// Create an exploder that has not seen any keys yet.
+    pub fn new(options: MapExplodeOptions) -> Self { Self { options, keys: HashMap::new() } }
+
+    /// Explode the `Map` columns of `batch` selected by the options.
+    ///
+    /// # Errors
+    /// - Returns `ArrowDeserialize` if a column has more keys than allowed and the overflow policy
+    ///   is [`MapOverflow::Error`].
+    /// - Returns `Arrow` if a column's keys cannot be represented as strings.
+    pub fn explode(&mut self, batch: RecordBatch) -> Result<RecordBatch> {
+        let schema = batch.schema();
+        if !schema.fields().iter().any(|f| self.is_exploded(f)) {
+            return Ok(batch);
+        }
+
+        let mut fields = Vec::with_capacity(schema.fields().len());
+        let mut columns = Vec::with_capacity(schema.fields().len());
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            if self.is_exploded(field) {
+                let exploded = self.explode_column(field.name(), column.as_map())?;
+                fields.push(Arc::new(Field::new(
+                    field.name(),
+                    exploded.data_type().clone(),
+                    field.is_nullable(),
+                )));
+                columns.push(exploded);
+            } else {
+                fields.push(Arc::clone(field));
+                columns.push(Arc::clone(column));
+            }
+        }
+
+        let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+        let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+        Ok(RecordBatch::try_new_with_options(Arc::new(schema), columns, &options)?)
+    }
+
+    fn is_exploded(&self, field: &Field) -> bool {
+        matches!(field.data_type(), DataType::Map(_, _)) && self.options.includes(field.name())
+    }
+
+    fn explode_column(&mut self, name: &str, map: &MapArray) -> Result<ArrayRef> {
+        let (max_keys, overflow) = (self.options.max_keys, self.options.overflow);
+        let keys = cast(map.keys(), &DataType::Utf8)?;
+        let keys = keys.as_string::<i32>();
+        let observed = self.keys.entry(name.to_string()).or_default();
+
+        // Entry index of each key per row, and entries past the cap
+        let mut indices: Vec<Vec<Option<u64>>> = vec![vec![None; map.len()]; observed.len()];
+        let mut overflow_entries = Vec::new();
+        let mut overflow_offsets = Vec::with_capacity(map.len() + 1);
+        overflow_offsets.push(0_i32);
+        for (row, window) in map.value_offsets().windows(2).enumerate() {
+            for entry in window[0]..window[1] {
+                let entry = usize::try_from(entry).unwrap_or_default();
+                let Some(key) = keys.is_valid(entry).then(|| keys.value(entry)) else {
+                    continue;
+                };
+                let position = match observed.get_index_of(key) {
+                    Some(position) => position,
+                    None if observed.len() < max_keys => {
+                        let _ = observed.insert(key.to_string());
+                        indices.push(vec![None; map.len()]);
+                        observed.len() - 1
+                    }
+                    None => match overflow {
+                        MapOverflow::Error => {
+                            return Err(Error::ArrowDeserialize(format!(
+                                "Map column {name} has more than {max_keys} keys, first \
+                                 overflowing key: {key}"
+                            )));
+                        }
+                        MapOverflow::Drop => continue,
+                        MapOverflow::Keep => {
+                            overflow_entries.push(entry as u64);
+                            continue;
+                        }
+                    },
+                };
+                indices[position][row] = Some(entry as u64);
+            }
+            overflow_offsets.push(i32::try_from(overflow_entries.len()).map_err(|_| {
+                Error::ArrowDeserialize(format!("Map column {name} overflow too large"))
+            })?);
+        }
+
+        let values = map.values();
+        let mut fields = Vec::with_capacity(observed.len() + 1);
+        let mut children = Vec::with_capacity(observed.len() + 1);
+        for (key, indices) in observed.iter().zip(indices) {
+            fields.push(Field::new(key, values.data_type().clone(), true));
+            children.push(take(values, &UInt64Array::from(indices), None)?);
+        }
+        if overflow == MapOverflow::Keep {
+            let DataType::Map(entries_field, sorted) = map.data_type() else {
+                unreachable!("Exploded column is a map");
+            };
+            let entries = take(map.entries(), &UInt64Array::from(overflow_entries), None)?;
+            let remainder = MapArray::try_new(
+                Arc::clone(entries_field),
+                OffsetBuffer::new(overflow_offsets.into()),
+                entries.as_struct().clone(),
+                None,
+                *sorted,
+            )?;
+            fields.push(Field::new(MAP_OVERFLOW_FIELD_NAME, map.data_type().clone(), false));
+            children.push(Arc::new(remainder));
+        }
+
+        let nulls = map.nulls().cloned();
+        let exploded = if fields.is_empty() {
+            StructArray::new_empty_fields(map.len(), nulls)
+        } else {
+            StructArray::try_new(fields.into(), children, nulls)?
+        };
+        Ok(Arc::new(exploded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map_array(rows: &[&[(&str, i32)]]) -> MapArray {
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        for row in rows {
+            for (key, value) in *row {
+                builder.keys().append_value(key);
+                builder.values().append_value(*value);
+            }
+            builder.append(true).unwrap();
+        }
+        builder.finish()
+    }
+
+    fn batch(rows: &[&[(&str, i32)]]) -> RecordBatch {
+        let map = Arc::new(map_array(rows)) as ArrayRef;
+        let id = Arc::new(Int32Array::from_iter_values(0..i32::try_from(rows.len()).unwrap()));
+        RecordBatch::try_from_iter([("id", id as ArrayRef), ("tags", map)]).unwrap()
+    }
+
+    fn field_values(batch: &RecordBatch, key: &str) -> Vec<Option<i32>> {
+        let tags = batch.column_by_name("tags").unwrap().as_struct();
+        tags.column_by_name(key).unwrap().as_primitive::<Int32Type>().iter().collect()
+    }
+
+    #[test]
+    fn test_explode_map_to_struct() {
+        let mut exploder = MapExploder::new(MapExplodeOptions::new());
+        let result = exploder.explode(batch(&[&[("a", 1), ("b", 2)], &[], &[("b", 3)]])).unwrap();
+
+        assert_eq!(result.column(0).data_type(), &DataType::Int32);
+        let DataType::Struct(fields) = result.column(1).data_type() else {
+            panic!("Expected struct, got {:?}", result.column(1).data_type());
+        };
+        assert_eq!(fields.iter().map(|f| f.name().as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(field_values(&result, "a"), [Some(1), None, None]);
+        assert_eq!(field_values(&result, "b"), [Some(2), None, Some(3)]);
+
+        // Keys are remembered across batches, new keys are appended
+        let result = exploder.explode(batch(&[&[("c", 4)]])).unwrap();
+        let tags = result.column(1).as_struct();
+        assert_eq!(tags.column_names(), ["a", "b", "c"]);
+        assert_eq!(field_values(&result, "a"), [None]);
+        assert_eq!(field_values(&result, "c"), [Some(4)]);
+    }
+
+    #[test]
+    fn test_explode_map_overflow() {
+        let rows: &[&[(&str, i32)]] = &[&[("a", 1), ("b", 2)], &[("c", 3), ("a", 4)]];
+        let options = MapExplodeOptions::new().with_max_keys(1);
+
+        let result = MapExploder::new(options.clone()).explode(batch(rows));
+        assert!(matches!(result, Err(Error::ArrowDeserialize(m)) if m.contains("key: b")));
+
+        let mut exploder = MapExploder::new(options.clone().with_overflow(MapOverflow::Drop));
+        let result = exploder.explode(batch(rows)).unwrap();
+        assert_eq!(result.column(1).as_struct().column_names(), ["a"]);
+        assert_eq!(field_values(&result, "a"), [Some(1), Some(4)]);
+
+        let mut exploder = MapExploder::new(options.with_overflow(MapOverflow::Keep));
+        let result = exploder.explode(batch(rows)).unwrap();
+        let tags = result.column(1).as_struct();
+        assert_eq!(tags.column_names(), ["a", MAP_OVERFLOW_FIELD_NAME]);
+        let remainder = tags.column(1).as_map();
+        assert_eq!(remainder.value_offsets(), &[0, 1, 2]);
+        let keys = remainder.keys().as_string::<i32>().iter().flatten().collect::<Vec<_>>();
+        assert_eq!(keys, ["b", "c"]);
+    }
+
+    #[test]
+    fn test_explode_selected_columns() {
+        let mut exploder = MapExploder::new(MapExplodeOptions::new().with_columns(["other"]));
+        let result = exploder.explode(batch(&[&[("a", 1)]])).unwrap();
+        assert!(matches!(result.column(1).data_type(), DataType::Map(_, _)));
+
+        // Empty maps explode to a struct without fields
+        let mut exploder = MapExploder::new(MapExplodeOptions::new());
+        let result = exploder.explode(batch(&[&[], &[]])).unwrap();
+        assert_eq!(result.column(1).as_struct().num_columns(), 0);
+        assert_eq!(result.num_rows(), 2);
+    }
+}
@@ -0,0 +1,227 @@
+//! ## Export query results through the Arrow C Stream interface
+//!
+//! [`BlockingRecordBatchReader`] adapts an async stream of [`RecordBatch`]es into a synchronous
+//! [`RecordBatchReader`], which can then be exported as an [`FFI_ArrowArrayStream`]. This is the
+//! format expected by local engines such as `DuckDB` (`register_arrow`) or `DataFusion`
+//! (`StreamingTable` backed by an FFI stream), allowing `ClickHouse` data to be federated into
+//! them without buffering the full result.
+//!
+//! Batches are pulled from the server only as the consumer reads them.
+//!
+//! # Examples
+//! ```rust,ignore
+//! use clickhouse_arrow::arrow::ffi::BlockingRecordBatchReader;
+//! use clickhouse_arrow::prelude::*;
+//!
+//! let handle = tokio::runtime::Handle::current();
+//! let response = client.query("SELECT * FROM events", None).await?;
+//!
+//! // Hand the stream to a synchronous consumer on a blocking thread
+//! let ffi_stream = tokio::task::spawn_blocking(move || {
+//!     response.into_blocking_reader(handle).map(BlockingRecordBatchReader::into_ffi_stream)
+//! })
+//! .await??;
+//!
+//! // `Box::into_raw` produces the `ArrowArrayStream*` expected by C consumers
+//! let ptr = Box::into_raw(Box::new(ffi_stream));
+//! ```
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+
+use arrow::array::{RecordBatch, RecordBatchReader};
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::ArrowError;
+pub use arrow::ffi_stream::FFI_ArrowArrayStream;
+use futures_util::{Stream, StreamExt};
+use tokio::runtime::Handle;
+
+use crate::Result;
+
+type BatchStream = Pin<Box<dyn Stream<Item = Result<RecordBatch>> + Send + 'static>>;
+
+/// A synchronous [`RecordBatchReader`] over an async stream of query results.
+///
+/// The first batch is read eagerly when the reader is created so that the schema is known up
+/// front, as required by the Arrow C Stream interface. Readers created from a
+/// [`ClickHouseResponse`](crate::ClickHouseResponse) report the schema of the result's header
+/// block for queries that return no rows, other streams report an empty schema.
+///
+/// NOTE: All reads block on the provided runtime [`Handle`], so the reader must not be created
+/// or read from within an async context. Use `tokio::task::spawn_blocking` or a dedicated thread.
+pub struct BlockingRecordBatchReader {
+    schema: SchemaRef,
+    first:  Option<RecordBatch>,
+    stream: BatchStream,
+    handle: Handle,
+}
+
+impl BlockingRecordBatchReader {
+    /// Create a reader over `stream`, blocking until the first batch (or end of stream) arrives.
+    ///
+    /// # Errors
+    /// Returns an error if the first batch fails to be received.
+    pub fn try_new<S>(stream: S, handle: Handle) -> Result<Self>
+    where
+        S: Stream<Item = Result<RecordBatch>> + Send + 'static,
+    {
+        Self::try_new_with_schema(stream, handle, || None)
+    }
+
+    /// Like [`Self::try_new`], taking the schema from `empty_schema` if the stream ends without
+    /// any batch. An empty schema is reported if it returns `None`.
+    pub(crate) fn try_new_with_schema<S>(
+        stream: S,
+        handle: Handle,
+        empty_schema: impl FnOnce() -> Option<Result<SchemaRef>>,
+    ) -> Result<Self>
+    where
+        S: Stream<Item = Result<RecordBatch>> + Send + 'static,
+    {
+        let mut stream: BatchStream = Box::pin(stream);
+        let first = handle.block_on(stream.next()).transpose()?;
+        let schema = match first.as_ref() {
+            Some(batch) => batch.schema(),
+            None => empty_schema().transpose()?.unwrap_or_else(|| Arc::new(Schema::empty())),
+        };
+        Ok(Self { schema, first, stream, handle })
+    }
+
+    /// Receive the next batch, preserving the original `ClickHouse` error.
+    ///
+    /// # Errors
+    /// Returns an error if the underlying query stream fails.
+    pub fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        if let Some(batch) = self.first.take() {
+            return Ok(Some(batch));
+        }
+        self.handle.block_on(self.stream.next()).transpose()
+    }
+
+    /// Like [`Self::next_batch`], but waits at most `timeout` for the next batch, returning
+    /// [`Poll::Pending`] if none arrived in time. No data is lost on timeout, the batch is
+    /// returned by a later call.
+    ///
+    /// Useful for synchronous callers that need to regain control periodically while waiting on
+    /// a slow query, ie to check for interrupts. The runtime must have its time driver enabled.
+    pub fn next_batch_timeout(&mut self, timeout: Duration) -> Poll<Result<Option<RecordBatch>>> {
+        if let Some(batch) = self.first.take() {
+            return Poll::Ready(Ok(Some(batch)));
+        }
+        match self.handle.block_on(tokio::time::timeout(timeout, self.stream.next())) {
+            Ok(batch) => Poll::Ready(batch.transpose()),
+            Err(_) => Poll::Pending,
+        }
+    }
+
+    /// Export the reader as an [`FFI_ArrowArrayStream`].
+    #[must_use]
+    pub fn into_ffi_stream(self) -> FFI_ArrowArrayStream {
+        FFI_ArrowArrayStream::new(Box::new(self))
+    }
+}
+
+impl std::fmt::Debug for BlockingRecordBatchReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockingRecordBatchReader").field("schema", &self.schema).finish()
+    }
+}
+
+impl Iterator for BlockingRecordBatchReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().map_err(|e| ArrowError::ExternalError(Box::new(e))).transpose()
+    }
+}
+
+impl RecordBatchReader for BlockingRecordBatchReader {
+    fn schema(&self) -> SchemaRef { Arc::clone(&self.schema) }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::ffi_stream::ArrowArrayStreamReader;
+    use futures_util::stream;
+
+    use super::*;
+    use crate::Error;
+    use crate::test_utils::int32_batch;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread().build().unwrap()
+    }
+
+    #[test]
+    fn test_reader_yields_all_batches() {
+        let rt = runtime();
+        let batches = vec![Ok(int32_batch(vec![1, 2])), Ok(int32_batch(vec![3]))];
+        let reader =
+            BlockingRecordBatchReader::try_new(stream::iter(batches), rt.handle().clone()).unwrap();
+        assert_eq!(reader.schema().fields().len(), 1);
+        let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(rows, 3);
+    }
+
+    #[test]
+    fn test_reader_empty_stream() {
+        let rt = runtime();
+        let reader =
+            BlockingRecordBatchReader::try_new(stream::empty(), rt.handle().clone()).unwrap();
+        assert!(reader.schema().fields().is_empty());
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[test]
+    fn test_reader_empty_stream_with_schema() {
+        let rt = runtime();
+        let schema = int32_batch(vec![]).schema();
+        let expected = Arc::clone(&schema);
+        let reader = BlockingRecordBatchReader::try_new_with_schema(
+            stream::empty(),
+            rt.handle().clone(),
+            move || Some(Ok(expected)),
+        )
+        .unwrap();
+        assert_eq!(reader.schema(), schema);
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[test]
+    fn test_reader_preserves_errors() {
+        let rt = runtime();
+        let batches = vec![Ok(int32_batch(vec![1])), Err(Error::Protocol("boom".into()))];
+        let mut reader =
+            BlockingRecordBatchReader::try_new(stream::iter(batches), rt.handle().clone()).unwrap();
+        assert!(reader.next_batch().unwrap().is_some());
+        assert!(matches!(reader.next_batch(), Err(Error::Protocol(_))));
+    }
+
+    #[test]
+    fn test_reader_next_batch_timeout() {
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let batches = stream::iter(vec![Ok(int32_batch(vec![1])), Ok(int32_batch(vec![2]))])
+            .chain(stream::pending());
+        let mut reader = BlockingRecordBatchReader::try_new(batches, rt.handle().clone()).unwrap();
+        let timeout = Duration::from_millis(10);
+        assert!(matches!(reader.next_batch_timeout(timeout), Poll::Ready(Ok(Some(_)))));
+        assert!(matches!(reader.next_batch_timeout(timeout), Poll::Ready(Ok(Some(_)))));
+        // The stream stalls, so the reader times out instead of blocking forever
+        assert!(reader.next_batch_timeout(timeout).is_pending());
+    }
+
+    #[test]
+    fn test_ffi_stream_roundtrip() {
+        let rt = runtime();
+        let batches = vec![Ok(int32_batch(vec![1, 2, 3])), Ok(int32_batch(vec![4]))];
+        let ffi_stream =
+            BlockingRecordBatchReader::try_new(stream::iter(batches), rt.handle().clone())
+                .unwrap()
+                .into_ffi_stream();
+        let reader = ArrowArrayStreamReader::try_new(ffi_stream).unwrap();
+        assert_eq!(reader.schema().field(0).name(), "v");
+        let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(rows, 4);
+    }
+}
@@ -0,0 +1,422 @@
+//! ## Lazily decoded record batches
+//!
+//! [`LazyBatch`] retains the encoded bytes of each column of a native block and only decodes a
+//! column into an Arrow array when it is first accessed. Workflows that read wide tables but touch
+//! few columns per batch avoid the cost of decoding, and the memory of holding, the columns they
+//! never access.
+//!
+//! Lazy batches are returned by clients built with
+//! [`ClientBuilder::build_lazy_arrow`](crate::ClientBuilder::build_lazy_arrow). Columns whose end
+//! can only be found by decoding them, such as `LowCardinality` columns or columns using sparse
+//! serialization, are decoded eagerly when the block is read.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use clickhouse_arrow::prelude::*;
+//!
+//! let client = Client::builder().with_endpoint("localhost:9000").build_lazy_arrow().await?;
+//! let query = "SELECT * FROM wide_table".to_string();
+//! let mut stream = client.query_raw(query, None::<QueryParams>, Qid::new()).await?;
+//! while let Some(batch) = stream.next().await {
+//!     let batch = batch?;
+//!     // Only the `id` column is decoded
+//!     let ids = batch.column_by_name("id")?;
+//! }
+//! ```
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+
+use arrow::array::{Array, ArrayRef, RecordBatch, RecordBatchOptions, new_empty_array};
+use arrow::datatypes::{Field, FieldRef, Schema, SchemaRef};
+use bytes::Bytes;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use super::block::read_column_async;
+use super::builder::TypedBuilder;
+use super::deserialize::{ClickHouseArrowDeserializer, skip};
+use crate::formats::DataSize;
+use crate::io::ClickHouseRead;
+use crate::native::block_info::BlockInfo;
+use crate::native::protocol::{
+    DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION, MAX_STRING_SIZE,
+};
+use crate::native::sparse::{SerializationKinds, read_serialization_kinds};
+use crate::{ArrowOptions, Error, Result, Type};
+
+/// A column of a [`LazyBatch`], either decoded or retained in its encoded form.
+#[derive(Debug)]
+enum LazyColumn {
+    Decoded(ArrayRef),
+    Encoded { type_hint: Type, data: Bytes, array: OnceLock<ArrayRef> },
+}
+
+#[derive(Debug)]
+struct LazyBlock {
+    schema:  SchemaRef,
+    rows:    usize,
+    columns: Vec<LazyColumn>,
+}
+
+/// A record batch whose columns are decoded from the native format on first access.
+///
+/// Cloning a `LazyBatch` is cheap, and clones share decoded columns. See the
+/// [module documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct LazyBatch {
+    inner: Arc<LazyBlock>,
+}
+
+impl LazyBatch {
+    /// Returns the schema of the batch.
+    pub fn schema(&self) -> SchemaRef { Arc::clone(&self.inner.schema) }
+
+    /// Returns the number of rows in the batch.
+    pub fn num_rows(&self) -> usize { self.inner.rows }
+
+    /// Returns the number of columns in the batch.
+    pub fn num_columns(&self) -> usize { self.inner.columns.len() }
+
+    /// Returns whether the column at `index` has been decoded.
+    ///
+    /// # Panics
+    /// - Panics if `index` is out of bounds.
+    pub fn is_decoded(&self, index: usize) -> bool {
+        match &self.inner.columns[index] {
+            LazyColumn::Decoded(_) => true,
+            LazyColumn::Encoded { array, .. } => array.get().is_some(),
+        }
+    }
+
+    /// Returns the column at `index`, decoding it if it has not been accessed before.
+    ///
+    /// # Errors
+    /// - Returns `Client` if `index` is out of bounds.
+    /// - Returns `ArrowDeserialize` or `Protocol` if the column's data cannot be decoded.
+    pub fn column(&self, index: usize) -> Result<ArrayRef> {
+        let column = self.inner.columns.get(index).ok_or_else(|| {
+            Error::Client(format!(
+                "Column index {index} out of bounds for batch of {} columns",
+                self.num_columns()
+            ))
+        })?;
+        match column {
+            LazyColumn::Decoded(array) => Ok(Arc::clone(array)),
+            LazyColumn::Encoded { type_hint, data, array } => {
+                if let Some(array) = array.get() {
+                    return Ok(Arc::clone(array));
+                }
+                let field = self.inner.schema.field(index);
+                let decoded = decode_column(field, type_hint, data, self.inner.rows)?;
+                // A concurrent access may have decoded the column first, both are identical
+                Ok(Arc::clone(array.get_or_init(|| decoded)))
+            }
+        }
+    }
+
+    /// Returns the column named `name`, decoding it if it has not been accessed before.
+    ///
+    /// # Errors
+    /// - Returns `Client` if the batch has no column named `name`.
+    /// - Returns `ArrowDeserialize` or `Protocol` if the column's data cannot be decoded.
+    pub fn column_by_name(&self, name: &str) -> Result<ArrayRef> {
+        let (index, _) = self
+            .inner
+            .schema
+            .column_with_name(name)
+            .ok_or_else(|| Error::Client(format!("Column {name} not found in batch")))?;
+        self.column(index)
+    }
+
+    /// Decodes the columns at `indices` into a [`RecordBatch`].
+    ///
+    /// # Errors
+    /// - Returns `Client` if an index is out of bounds.
+    /// - Returns `ArrowDeserialize` or `Protocol` if a column's data cannot be decoded.
+    pub fn project(&self, indices: &[usize]) -> Result<RecordBatch> {
+        let schema = Arc::new(self.inner.schema.project(indices)?);
+        let columns = indices.iter().map(|&i| self.column(i)).collect::<Result<Vec<_>>>()?;
+        let options = RecordBatchOptions::new().with_row_count(Some(self.inner.rows));
+        Ok(RecordBatch::try_new_with_options(schema, columns, &options)?)
+    }
+
+    /// Decodes every column into a [`RecordBatch`].
+    ///
+    /// # Errors
+    /// - Returns `ArrowDeserialize` or `Protocol` if a column's data cannot be decoded.
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        self.project(&(0..self.num_columns()).collect::<Vec<_>>())
+    }
+
+    /// Reads a native block, retaining the encoded data of columns that can be skipped.
+    pub(crate) async fn read_async<R: ClickHouseRead>(
+        reader: &mut R,
+        revision: u64,
+        options: ArrowOptions,
+    ) -> Result<Option<LazyBatch>> {
+        let info = BlockInfo::read_async(reader).await?;
+
+        #[expect(clippy::cast_possible_truncation)]
+        let (columns, rows) =
+            (reader.read_var_uint().await? as usize, reader.read_var_uint().await? as usize);
+
+        if columns > MAX_STRING_SIZE || rows > MAX_STRING_SIZE {
+            return Err(Error::Protocol(format!(
+                "block too large: {columns} columns, {rows} rows"
+            )));
+        }
+
+        if columns == 0 && rows == 0 {
+            return Ok(None);
+        }
+
+        let mut fields: Vec<FieldRef> = Vec::with_capacity(columns);
+        let mut lazy_columns = Vec::with_capacity(columns);
+        let mut buffer = Vec::new();
+        for _ in 0..columns {
+            let name = reader.read_utf8_string().await?;
+            let internal_type = Type::from_str(&reader.read_utf8_string().await?)?;
+            let (arrow_type, is_nullable) = internal_type.arrow_type(Some(options))?;
+            let type_hint =
+                super::types::normalize_type(&internal_type, &arrow_type).unwrap_or(internal_type);
+            let field = Field::new(name, arrow_type, is_nullable);
+
+            let kinds = if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
+                read_serialization_kinds(reader, &type_hint).await?
+            } else {
+                SerializationKinds::default()
+            };
+
+            let column = if rows == 0 {
+                LazyColumn::Decoded(new_empty_array(field.data_type()))
+            } else if !kinds.is_custom() && skip::is_skippable(&type_hint) {
+                // Skip over the column while capturing its bytes
+                let mut capture = CaptureReader { inner: reader, captured: Vec::new() };
+                skip::skip_async(&type_hint, &mut capture, rows).await?;
+                let data = Bytes::from(capture.captured);
+                LazyColumn::Encoded { type_hint, data, array: OnceLock::new() }
+            } else {
+                let mut builder = TypedBuilder::try_new(&type_hint, field.data_type())?;
+                let array = read_column_async(
+                    reader,
+                    &field,
+                    &type_hint,
+                    &kinds,
+                    rows,
+                    options,
+                    &mut builder,
+                    &mut buffer,
+                )
+                .await?;
+                LazyColumn::Decoded(array)
+            };
+
+            // Run end encoded sparse columns change the field's type
+            let field = match &column {
+                LazyColumn::Decoded(array) if array.data_type() != field.data_type() => {
+                    field.with_data_type(array.data_type().clone())
+                }
+                _ => field,
+            };
+            fields.push(Arc::new(field));
+            lazy_columns.push(column);
+        }
+
+        let mut schema = Schema::new(fields);
+        if options.block_info_metadata {
+            schema = schema.with_metadata(info.to_metadata());
+        }
+        let inner = LazyBlock { schema: Arc::new(schema), rows, columns: lazy_columns };
+        Ok(Some(LazyBatch { inner: Arc::new(inner) }))
+    }
+}
+
+impl DataSize for LazyBatch {
+    fn data_size(&self) -> usize {
+        self.inner
+            .columns
+            .iter()
+            .map(|column| match column {
+                LazyColumn::Decoded(array) => array.get_array_memory_size(),
+                LazyColumn::Encoded { data, array, .. } => {
+                    data.len() + array.get().map_or(0, |a| a.get_array_memory_size())
+                }
+            })
+            .sum()
+    }
+
+    fn data_rows(&self) -> usize { self.num_rows() }
+}
+
+impl TryFrom<LazyBatch> for RecordBatch {
+    type Error = Error;
+
+    fn try_from(batch: LazyBatch) -> Result<Self> { batch.to_record_batch() }
+}
+
+/// Decode the retained bytes of a skippable column.
+fn decode_column(field: &Field, type_hint: &Type, data: &Bytes, rows: usize) -> Result<ArrayRef> {
+    let mut reader = data.clone();
+    let mut builder = TypedBuilder::try_new(type_hint, field.data_type())?;
+    let mut buffer = Vec::new();
+    type_hint.deserialize_arrow(
+        &mut builder,
+        &mut reader,
+        field.data_type(),
+        rows,
+        &[],
+        &mut buffer,
+    )
+}
+
+/// Reader adapter recording every byte read through it.
+struct CaptureReader<'a, R> {
+    inner:    &'a mut R,
+    captured: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CaptureReader<'_, R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let start = buf.filled().len();
+        let poll = Pin::new(&mut *this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            this.captured.extend_from_slice(&buf.filled()[start..]);
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use arrow::array::*;
+    use arrow::compute::cast;
+    use arrow::datatypes::*;
+
+    use super::*;
+    use crate::arrow::types::LIST_ITEM_FIELD_NAME;
+    use crate::formats::protocol_data::ProtocolData;
+    use crate::native::protocol::DBMS_TCP_PROTOCOL_VERSION;
+
+    fn create_test_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new(
+                "category",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                false,
+            ),
+            Field::new("name", DataType::Utf8, true),
+            Field::new(
+                "tags",
+                DataType::List(Arc::new(Field::new(LIST_ITEM_FIELD_NAME, DataType::Int32, true))),
+                true,
+            ),
+        ]));
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int32Array::from(vec![1, 2, 3])),
+            Arc::new(DictionaryArray::<Int32Type>::from_iter(vec!["cat", "dog", "cat"])),
+            Arc::new(StringArray::from(vec![Some("alice"), None, Some("bob")])),
+            Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+                Some(vec![Some(1), Some(2)]),
+                None,
+                Some(vec![Some(3)]),
+            ])),
+        ])
+        .unwrap()
+    }
+
+    async fn encode(batch: &RecordBatch, blocks: usize, options: ArrowOptions) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        for _ in 0..blocks {
+            batch
+                .clone()
+                .write_async(&mut buffer, DBMS_TCP_PROTOCOL_VERSION, None, options)
+                .await
+                .unwrap();
+        }
+        buffer
+    }
+
+    #[tokio::test]
+    async fn test_lazy_batch_decodes_on_access() {
+        let batch = create_test_batch();
+        let options = ArrowOptions::default().with_strings_as_strings(true);
+        let buffer = encode(&batch, 2, options).await;
+
+        let mut reader = Cursor::new(buffer);
+        for _ in 0..2 {
+            let lazy = LazyBatch::read_async(&mut reader, DBMS_TCP_PROTOCOL_VERSION, options)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(lazy.num_rows(), 3);
+            assert_eq!(lazy.num_columns(), 4);
+
+            // Skippable columns are retained, `LowCardinality` must be decoded to find its end
+            assert!(!lazy.is_decoded(0));
+            assert!(lazy.is_decoded(1));
+            assert!(!lazy.is_decoded(2));
+            assert!(!lazy.is_decoded(3));
+
+            let name = lazy.column_by_name("name").unwrap();
+            assert_eq!(name.as_ref(), batch.column(2).as_ref());
+            assert!(lazy.is_decoded(2));
+            assert!(!lazy.is_decoded(0));
+
+            // Clones share decoded columns
+            let clone = lazy.clone();
+            assert!(clone.is_decoded(2));
+
+            let decoded = lazy.to_record_batch().unwrap();
+            assert_eq!(decoded.column(0).as_ref(), batch.column(0).as_ref());
+            assert_eq!(
+                cast(decoded.column(1), &DataType::Utf8).unwrap().as_ref(),
+                cast(batch.column(1), &DataType::Utf8).unwrap().as_ref()
+            );
+            assert_eq!(decoded.column(3).as_ref(), batch.column(3).as_ref());
+        }
+        assert_eq!(reader.position(), reader.get_ref().len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_batch_project() {
+        let batch = create_test_batch();
+        let options = ArrowOptions::default().with_strings_as_strings(true);
+        let buffer = encode(&batch, 1, options).await;
+
+        let lazy =
+            LazyBatch::read_async(&mut Cursor::new(buffer), DBMS_TCP_PROTOCOL_VERSION, options)
+                .await
+                .unwrap()
+                .unwrap();
+        let projected = lazy.project(&[3, 0]).unwrap();
+        assert_eq!(projected.schema().field(0).name(), "tags");
+        assert_eq!(projected.column(0).as_ref(), batch.column(3).as_ref());
+        assert_eq!(projected.column(1).as_ref(), batch.column(0).as_ref());
+        assert!(!lazy.is_decoded(2));
+
+        assert!(lazy.column(4).is_err());
+        assert!(lazy.column_by_name("missing").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lazy_batch_empty_block() {
+        let options = ArrowOptions::default();
+        let empty = RecordBatch::new_empty(Arc::new(Schema::empty()));
+        let buffer = encode(&empty, 1, options).await;
+        let lazy =
+            LazyBatch::read_async(&mut Cursor::new(buffer), DBMS_TCP_PROTOCOL_VERSION, options)
+                .await
+                .unwrap();
+        assert!(lazy.is_none());
+    }
+}
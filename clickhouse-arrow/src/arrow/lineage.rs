@@ -0,0 +1,98 @@
+//! Per-batch lineage metadata for Arrow query results.
+//!
+//! With [`ArrowOptions::with_batch_metadata`](crate::ArrowOptions::with_batch_metadata) enabled,
+//! every `RecordBatch` returned by a query identifies the query and its position in the result in
+//! its schema metadata. Together, the query id and block index uniquely identify a batch, which
+//! exactly-once sinks can use to skip batches they have already committed.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::record_batch::RecordBatch;
+use uuid::Uuid;
+
+use crate::Qid;
+
+/// Schema metadata key carrying the id of the query that returned a batch.
+pub const BATCH_QUERY_ID_KEY: &str = "clickhouse.query_id";
+/// Schema metadata key carrying the index of a batch within its query's result, starting at 0.
+pub const BATCH_INDEX_KEY: &str = "clickhouse.block_index";
+/// Schema metadata key carrying the display name of the server that returned a batch, usually its
+/// hostname.
+pub const BATCH_SERVER_KEY: &str = "clickhouse.server";
+
+/// The lineage of a query result batch, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BatchMetadata {
+    /// The query that returned the batch.
+    pub qid:    Qid,
+    /// The index of the batch within the query's result, starting at 0.
+    pub index:  u64,
+    /// The display name of the server, if it sent one.
+    pub server: Option<String>,
+}
+
+impl BatchMetadata {
+    /// Convert to schema metadata entries, as attached to Arrow query results.
+    pub fn to_metadata(&self) -> HashMap<String, String> {
+        let mut metadata = HashMap::from([
+            (BATCH_QUERY_ID_KEY.to_string(), self.qid.to_string()),
+            (BATCH_INDEX_KEY.to_string(), self.index.to_string()),
+        ]);
+        if let Some(server) = &self.server {
+            let _ = metadata.insert(BATCH_SERVER_KEY.to_string(), server.clone());
+        }
+        metadata
+    }
+
+    /// Recover batch metadata from schema metadata, if present.
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Option<Self> {
+        let qid = Uuid::parse_str(metadata.get(BATCH_QUERY_ID_KEY)?).ok()?.into();
+        let index = metadata.get(BATCH_INDEX_KEY)?.parse().ok()?;
+        let server = metadata.get(BATCH_SERVER_KEY).cloned();
+        Some(Self { qid, index, server })
+    }
+
+    /// Recover the metadata attached to `batch`, if any.
+    pub fn from_batch(batch: &RecordBatch) -> Option<Self> {
+        Self::from_metadata(batch.schema_ref().metadata())
+    }
+
+    /// Add this metadata to the schema metadata of `batch`.
+    pub(crate) fn attach(&self, batch: RecordBatch) -> RecordBatch {
+        let mut metadata = batch.schema_ref().metadata().clone();
+        metadata.extend(self.to_metadata());
+        let schema = Arc::new(batch.schema_ref().as_ref().clone().with_metadata(metadata));
+        // The schema only gained metadata, so it remains compatible with the columns
+        batch.with_schema(schema).expect("Adding schema metadata keeps the batch valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+
+    #[test]
+    fn test_batch_metadata_round_trip() {
+        let schema = Arc::new(
+            Schema::new(vec![Field::new("id", DataType::Int32, false)])
+                .with_metadata(HashMap::from([("existing".into(), "kept".into())])),
+        );
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(Int32Array::from(vec![1, 2]))]).unwrap();
+        assert_eq!(BatchMetadata::from_batch(&batch), None);
+
+        let metadata = BatchMetadata { qid: Qid::new(), index: 3, server: Some("ch-1".into()) };
+        let batch = metadata.attach(batch);
+        assert_eq!(BatchMetadata::from_batch(&batch), Some(metadata.clone()));
+        assert_eq!(batch.schema_ref().metadata()["existing"], "kept");
+        assert_eq!(batch.num_rows(), 2);
+
+        let anonymous = BatchMetadata { server: None, ..metadata };
+        let entries = anonymous.to_metadata();
+        assert!(!entries.contains_key(BATCH_SERVER_KEY));
+        assert_eq!(BatchMetadata::from_metadata(&entries), Some(anonymous));
+    }
+}
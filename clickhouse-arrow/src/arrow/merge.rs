@@ -0,0 +1,306 @@
+//! Merge partial `GROUP BY` results on the client.
+//!
+//! When a query runs with `distributed_group_by_no_merge`, or is fanned out across several
+//! connections or shards by the client, each source returns its own aggregate for a group key.
+//! An [`AggregateMerger`] combines these partial results into one row per key using Arrow compute
+//! kernels, which makes scatter-gather query patterns possible without a coordinating server.
+//!
+//! Only aggregates whose partial results can be combined with another aggregate are supported:
+//! `sum` and `count` merge with [`MergeOp::Sum`], `min` and `max` with [`MergeOp::Min`] and
+//! [`MergeOp::Max`], and `any` with [`MergeOp::Any`]. Averages should be computed from merged
+//! sums and counts.
+use std::ops::Range;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::compute::{
+    SortColumn, concat_batches, lexsort_to_indices, max, min, partition, sum, take,
+    take_record_batch,
+};
+use arrow::datatypes::{ArrowNativeTypeOp, ArrowNumericType, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use futures_util::{Stream, TryStreamExt};
+
+use crate::{Error, Result};
+
+/// How the partial values of an aggregate column are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MergeOp {
+    /// Add the partial values, for `sum` and `count`.
+    Sum,
+    /// Keep the smallest partial value.
+    Min,
+    /// Keep the largest partial value.
+    Max,
+    /// Keep the first partial value, for `any` or columns that are constant per key.
+    Any,
+}
+
+/// Merges partial aggregate batches into one row per group key.
+///
+/// Batches are buffered with [`AggregateMerger::push`] and merged by
+/// [`AggregateMerger::finish`]. Every pushed batch must have the same schema. Columns that are
+/// neither keys nor configured aggregates are merged with [`MergeOp::Any`]. Rows in the result
+/// are ordered by their keys.
+///
+/// # Example
+/// ```rust,ignore
+/// use clickhouse_arrow::arrow::merge::{AggregateMerger, MergeOp};
+///
+/// let merger = AggregateMerger::new(["region"])
+///     .with_aggregate("total", MergeOp::Sum)
+///     .with_aggregate("latest", MergeOp::Max);
+/// let sql = "SELECT region, sum(x) AS total, max(t) AS latest FROM t GROUP BY region";
+/// let shards = futures_util::stream::select_all([
+///     shard_a.query(sql, None).await?,
+///     shard_b.query(sql, None).await?,
+/// ]);
+/// let merged = merger.merge_stream(shards).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AggregateMerger {
+    keys:       Vec<String>,
+    aggregates: Vec<(String, MergeOp)>,
+    batches:    Vec<RecordBatch>,
+}
+
+impl AggregateMerger {
+    /// Create a merger grouping by the `keys` columns.
+    pub fn new<I, S>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { keys: keys.into_iter().map(Into::into).collect(), ..Default::default() }
+    }
+
+    /// Merge the `column` aggregate with `op`.
+    #[must_use]
+    pub fn with_aggregate(mut self, column: impl Into<String>, op: MergeOp) -> Self {
+        self.aggregates.push((column.into(), op));
+        self
+    }
+
+    /// Buffer a batch of partial results.
+    ///
+    /// # Errors
+    /// Returns an error if the batch's schema differs from previously pushed batches.
+    pub fn push(&mut self, batch: RecordBatch) -> Result<()> {
+        if let Some(first) = self.batches.first()
+            && first.schema() != batch.schema()
+        {
+            return Err(Error::ArrowTypeMismatch {
+                expected: format!("{:?}", first.schema()),
+                provided: format!("{:?}", batch.schema()),
+            });
+        }
+        self.batches.push(batch);
+        Ok(())
+    }
+
+    /// Merge a stream of partial result batches, e.g. one or more query responses.
+    ///
+    /// # Errors
+    /// Returns an error if the stream fails or the batches cannot be merged.
+    pub async fn merge_stream<S>(mut self, stream: S) -> Result<Option<RecordBatch>>
+    where
+        S: Stream<Item = Result<RecordBatch>>,
+    {
+        let mut stream = std::pin::pin!(stream);
+        while let Some(batch) = stream.try_next().await? {
+            self.push(batch)?;
+        }
+        self.finish()
+    }
+
+    /// Merge the buffered batches, returning `None` if no batches were pushed.
+    ///
+    /// # Errors
+    /// Returns an error if a key or aggregate column is missing, or an aggregate column's type is
+    /// not supported by its [`MergeOp`].
+    pub fn finish(self) -> Result<Option<RecordBatch>> {
+        let Some(schema) = self.batches.first().map(RecordBatch::schema) else {
+            return Ok(None);
+        };
+        let batch = concat_batches(&schema, &self.batches)?;
+        if self.keys.is_empty() {
+            let ranges = [0..batch.num_rows()];
+            return self.merge_ranges(&schema, &batch, &ranges).map(Some);
+        }
+
+        // Sort by the keys so equal keys are adjacent, then merge each run of equal keys
+        let key_columns =
+            self.keys.iter().map(|key| column(&schema, &batch, key)).collect::<Result<Vec<_>>>()?;
+        let sort_columns = key_columns
+            .iter()
+            .map(|values| SortColumn { values: Arc::clone(values), options: None })
+            .collect::<Vec<_>>();
+        let indices = lexsort_to_indices(&sort_columns, None)?;
+        let sorted = take_record_batch(&batch, &indices)?;
+        let sorted_keys = self
+            .keys
+            .iter()
+            .map(|key| column(&schema, &sorted, key))
+            .collect::<Result<Vec<_>>>()?;
+        let ranges = partition(&sorted_keys)?.ranges();
+        self.merge_ranges(&schema, &sorted, &ranges).map(Some)
+    }
+
+    fn merge_ranges(
+        &self,
+        schema: &SchemaRef,
+        batch: &RecordBatch,
+        ranges: &[Range<usize>],
+    ) -> Result<RecordBatch> {
+        for (name, _) in &self.aggregates {
+            let _ = column(schema, batch, name)?;
+        }
+        let columns = schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .enumerate()
+            .map(|(index, (field, array))| {
+                let op = self
+                    .aggregates
+                    .iter()
+                    .find(|(name, _)| name == field.name())
+                    .map_or(MergeOp::Any, |(_, op)| *op);
+                merge_column(array, ranges, op)
+                    .map_err(|error| error.with_column(field.name().as_str(), index))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+    }
+}
+
+fn column(schema: &SchemaRef, batch: &RecordBatch, name: &str) -> Result<ArrayRef> {
+    let index = schema
+        .index_of(name)
+        .map_err(|_| Error::Client(format!("Column '{name}' not found in partial results")))?;
+    Ok(Arc::clone(batch.column(index)))
+}
+
+/// Combine each range of `array` into a single value with `op`.
+fn merge_column(array: &ArrayRef, ranges: &[Range<usize>], op: MergeOp) -> Result<ArrayRef> {
+    if op == MergeOp::Any {
+        let first = UInt32Array::from_iter_values(
+            ranges.iter().map(|r| u32::try_from(r.start).unwrap_or(u32::MAX)),
+        );
+        return Ok(take(array, &first, None)?);
+    }
+
+    downcast_primitive_array!(
+        array => Ok(merge_primitive(array, ranges, op)),
+        data_type => Err(Error::ArrowUnsupportedType(format!(
+            "Cannot merge {data_type} with {op:?}"
+        )))
+    )
+}
+
+fn merge_primitive<T>(array: &PrimitiveArray<T>, ranges: &[Range<usize>], op: MergeOp) -> ArrayRef
+where
+    T: ArrowNumericType,
+    T::Native: ArrowNativeTypeOp + PartialOrd,
+{
+    let merged = ranges
+        .iter()
+        .map(|r| {
+            let slice = array.slice(r.start, r.len());
+            match op {
+                MergeOp::Sum => sum(&slice),
+                MergeOp::Min => min(&slice),
+                MergeOp::Max => max(&slice),
+                MergeOp::Any => slice.iter().next().flatten(),
+            }
+        })
+        .collect::<PrimitiveArray<T>>()
+        .with_data_type(array.data_type().clone());
+    Arc::new(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::{DataType, Field, Schema};
+    use futures_util::stream;
+
+    use super::*;
+
+    fn partial(regions: Vec<&str>, totals: Vec<u64>, latest: Vec<Option<i32>>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, false),
+            Field::new("total", DataType::UInt64, false),
+            Field::new("latest", DataType::Int32, true),
+            Field::new("label", DataType::Utf8, false),
+        ]));
+        let labels = regions.iter().map(|r| format!("label-{r}")).collect::<Vec<_>>();
+        RecordBatch::try_new(schema, vec![
+            Arc::new(StringArray::from(regions)),
+            Arc::new(UInt64Array::from(totals)),
+            Arc::new(Int32Array::from(latest)),
+            Arc::new(StringArray::from(labels)),
+        ])
+        .unwrap()
+    }
+
+    fn merger() -> AggregateMerger {
+        AggregateMerger::new(["region"])
+            .with_aggregate("total", MergeOp::Sum)
+            .with_aggregate("latest", MergeOp::Max)
+    }
+
+    #[tokio::test]
+    async fn test_merge_stream() {
+        let shards = stream::iter([
+            Ok(partial(vec!["eu", "us"], vec![1, 2], vec![Some(5), None])),
+            Ok(partial(vec!["us", "ap", "eu"], vec![10, 3, 20], vec![Some(7), None, Some(1)])),
+        ]);
+        let merged = merger().merge_stream(shards).await.unwrap().unwrap();
+        assert_eq!(merged.num_rows(), 3);
+        let regions = merged.column(0).as_string::<i32>();
+        let totals = merged.column(1).as_primitive::<arrow::datatypes::UInt64Type>();
+        let latest = merged.column(2).as_primitive::<arrow::datatypes::Int32Type>();
+        let labels = merged.column(3).as_string::<i32>();
+        assert_eq!(regions.iter().flatten().collect::<Vec<_>>(), vec!["ap", "eu", "us"]);
+        assert_eq!(totals.values().to_vec(), vec![3, 21, 12]);
+        assert_eq!(latest.iter().collect::<Vec<_>>(), vec![None, Some(5), Some(7)]);
+        assert_eq!(labels.value(1), "label-eu");
+    }
+
+    #[test]
+    fn test_merge_without_keys() {
+        let mut merger = AggregateMerger::new(Vec::<String>::new())
+            .with_aggregate("total", MergeOp::Sum)
+            .with_aggregate("latest", MergeOp::Min);
+        merger.push(partial(vec!["eu"], vec![1], vec![Some(5)])).unwrap();
+        merger.push(partial(vec!["us"], vec![2], vec![Some(3)])).unwrap();
+        let merged = merger.finish().unwrap().unwrap();
+        assert_eq!(merged.num_rows(), 1);
+        assert_eq!(merged.column(1).as_primitive::<arrow::datatypes::UInt64Type>().value(0), 3);
+        assert_eq!(merged.column(2).as_primitive::<arrow::datatypes::Int32Type>().value(0), 3);
+    }
+
+    #[test]
+    fn test_merge_errors() {
+        assert!(merger().finish().unwrap().is_none());
+
+        let mut missing = AggregateMerger::new(["nope"]);
+        missing.push(partial(vec!["eu"], vec![1], vec![None])).unwrap();
+        assert!(matches!(missing.finish(), Err(Error::Client(_))));
+
+        let mut unsupported = AggregateMerger::new(["total"]).with_aggregate("label", MergeOp::Sum);
+        unsupported.push(partial(vec!["eu"], vec![1], vec![None])).unwrap();
+        let error = unsupported.finish().unwrap_err();
+        assert_eq!(error.column(), Some(("label", 3)));
+        assert!(matches!(error.root_cause(), Error::ArrowUnsupportedType(_)));
+
+        let other = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("region", DataType::Utf8, false)])),
+            vec![Arc::new(StringArray::from(vec!["eu"]))],
+        )
+        .unwrap();
+        let mut mismatched = merger();
+        mismatched.push(partial(vec!["eu"], vec![1], vec![None])).unwrap();
+        assert!(matches!(mismatched.push(other), Err(Error::ArrowTypeMismatch { .. })));
+    }
+}
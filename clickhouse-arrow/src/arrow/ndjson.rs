@@ -0,0 +1,637 @@
+//! Render `RecordBatch`es as newline-delimited JSON (JSON Lines).
+//!
+//! An [`NdjsonEncoder`] writes one JSON object per row, keyed by column name. Values that have
+//! no native JSON representation are rendered according to [`NdjsonOptions`]:
+//! - Dates, times and timestamps as ISO 8601 strings or as their integer epoch values.
+//! - Decimals as exact strings or as JSON numbers.
+//! - Binary values as base64, hex or lossy UTF-8 strings.
+//!
+//! Non-finite floats are rendered as `null`, `Map` columns as objects keyed by the (stringified)
+//! map keys, and lists as arrays. Used by [`crate::ArrowClient::query_to_ndjson`] to export
+//! results batch by batch without buffering the full result.
+use std::fmt::Display;
+use std::io::Write;
+
+use arrow::array::*;
+use arrow::buffer::{NullBuffer, OffsetBuffer};
+use arrow::compute::cast;
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+
+use crate::{Error, Result};
+
+/// How dates, times and timestamps are rendered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TemporalFormat {
+    /// ISO 8601 strings, ie `"2024-01-31"` or `"2024-01-31T12:00:00+00:00"` (default).
+    #[default]
+    Iso8601,
+    /// Integers in the column's own unit, ie days for `Date32` or nanoseconds for
+    /// `Timestamp(Nanosecond, _)`.
+    Epoch,
+}
+
+/// How decimals are rendered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DecimalFormat {
+    /// Exact strings, ie `"123.45"` (default).
+    #[default]
+    String,
+    /// JSON numbers, which many JSON parsers read as lossy floats.
+    Number,
+}
+
+/// How binary values are rendered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    /// Standard, padded base64 strings (default).
+    #[default]
+    Base64,
+    /// Lowercase hex strings.
+    Hex,
+    /// UTF-8 strings, replacing invalid sequences with `U+FFFD`.
+    Utf8Lossy,
+}
+
+/// Configures how values are rendered by an [`NdjsonEncoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NdjsonOptions {
+    /// How dates, times and timestamps are rendered.
+    pub temporal:       TemporalFormat,
+    /// How decimals are rendered.
+    pub decimal:        DecimalFormat,
+    /// How binary values are rendered.
+    pub binary:         BinaryFormat,
+    /// Whether null fields are written as `"field":null` rather than omitted.
+    pub explicit_nulls: bool,
+}
+
+impl Default for NdjsonOptions {
+    fn default() -> Self { Self::new() }
+}
+
+impl NdjsonOptions {
+    /// ISO 8601 temporals, exact decimal strings, base64 binary and explicit nulls.
+    pub fn new() -> Self {
+        Self {
+            temporal:       TemporalFormat::Iso8601,
+            decimal:        DecimalFormat::String,
+            binary:         BinaryFormat::Base64,
+            explicit_nulls: true,
+        }
+    }
+
+    /// Set how dates, times and timestamps are rendered.
+    #[must_use]
+    pub fn with_temporal(mut self, temporal: TemporalFormat) -> Self {
+        self.temporal = temporal;
+        self
+    }
+
+    /// Set how decimals are rendered.
+    #[must_use]
+    pub fn with_decimal(mut self, decimal: DecimalFormat) -> Self {
+        self.decimal = decimal;
+        self
+    }
+
+    /// Set how binary values are rendered.
+    #[must_use]
+    pub fn with_binary(mut self, binary: BinaryFormat) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /// Set whether null fields are written rather than omitted.
+    #[must_use]
+    pub fn with_explicit_nulls(mut self, explicit_nulls: bool) -> Self {
+        self.explicit_nulls = explicit_nulls;
+        self
+    }
+}
+
+/// Encodes `RecordBatch`es as newline-delimited JSON.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NdjsonEncoder {
+    options: NdjsonOptions,
+}
+
+impl NdjsonEncoder {
+    /// Create an encoder rendering values according to `options`.
+    pub fn new(options: NdjsonOptions) -> Self { Self { options } }
+
+    /// Append one JSON object per row of `batch` to `out`, each terminated by a newline.
+    ///
+    /// # Errors
+    /// - Returns `ArrowUnsupportedType` if a column's type cannot be rendered as JSON.
+    /// - Returns `Arrow` if a column fails to convert to its rendered representation.
+    pub fn encode(&self, batch: &RecordBatch, out: &mut Vec<u8>) -> Result<()> {
+        let fields = batch
+            .schema()
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .map(|(field, column)| Ok((quoted(field.name()), make_encoder(column, &self.options)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let row = StructEncoder { fields, explicit_nulls: self.options.explicit_nulls };
+        for idx in 0..batch.num_rows() {
+            row.encode(idx, out);
+            out.push(b'\n');
+        }
+        Ok(())
+    }
+}
+
+trait Encode: Send + Sync {
+    /// Append the JSON representation of the non-null value at `idx`.
+    fn encode(&self, idx: usize, out: &mut Vec<u8>);
+}
+
+/// Wraps an encoder, rendering null values as `null`.
+struct NullableEncoder {
+    nulls:   Option<NullBuffer>,
+    encoder: Box<dyn Encode>,
+}
+
+impl NullableEncoder {
+    fn is_null(&self, idx: usize) -> bool { self.nulls.as_ref().is_some_and(|n| n.is_null(idx)) }
+
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        if self.is_null(idx) {
+            out.extend_from_slice(b"null");
+        } else {
+            self.encoder.encode(idx, out);
+        }
+    }
+}
+
+fn make_encoder(array: &ArrayRef, options: &NdjsonOptions) -> Result<NullableEncoder> {
+    let nulls = array.logical_nulls();
+    let encoder: Box<dyn Encode> = match array.data_type() {
+        DataType::Null => Box::new(NullEncoder),
+        DataType::Boolean => Box::new(BooleanEncoder(array.as_boolean().clone())),
+        DataType::Int8 => Box::new(NumberEncoder(array.as_primitive::<Int8Type>().clone())),
+        DataType::Int16 => Box::new(NumberEncoder(array.as_primitive::<Int16Type>().clone())),
+        DataType::Int32 => Box::new(NumberEncoder(array.as_primitive::<Int32Type>().clone())),
+        DataType::Int64 => Box::new(NumberEncoder(array.as_primitive::<Int64Type>().clone())),
+        DataType::UInt8 => Box::new(NumberEncoder(array.as_primitive::<UInt8Type>().clone())),
+        DataType::UInt16 => Box::new(NumberEncoder(array.as_primitive::<UInt16Type>().clone())),
+        DataType::UInt32 => Box::new(NumberEncoder(array.as_primitive::<UInt32Type>().clone())),
+        DataType::UInt64 => Box::new(NumberEncoder(array.as_primitive::<UInt64Type>().clone())),
+        DataType::Float16 => Box::new(FloatEncoder(array.as_primitive::<Float16Type>().clone())),
+        DataType::Float32 => Box::new(FloatEncoder(array.as_primitive::<Float32Type>().clone())),
+        DataType::Float64 => Box::new(FloatEncoder(array.as_primitive::<Float64Type>().clone())),
+        DataType::Decimal32(..) => decimal_encoder::<Decimal32Type>(array, options.decimal),
+        DataType::Decimal64(..) => decimal_encoder::<Decimal64Type>(array, options.decimal),
+        DataType::Decimal128(..) => decimal_encoder::<Decimal128Type>(array, options.decimal),
+        DataType::Decimal256(..) => decimal_encoder::<Decimal256Type>(array, options.decimal),
+        DataType::Utf8 => Box::new(StringEncoder(array.as_string::<i32>().clone())),
+        DataType::LargeUtf8 => Box::new(StringEncoder(array.as_string::<i64>().clone())),
+        DataType::Utf8View => Box::new(StringEncoder(array.as_string_view().clone())),
+        DataType::Binary => {
+            Box::new(BinaryEncoder(array.as_binary::<i32>().clone(), options.binary))
+        }
+        DataType::LargeBinary => {
+            Box::new(BinaryEncoder(array.as_binary::<i64>().clone(), options.binary))
+        }
+        DataType::BinaryView => {
+            Box::new(BinaryEncoder(array.as_binary_view().clone(), options.binary))
+        }
+        DataType::FixedSizeBinary(_) => {
+            Box::new(BinaryEncoder(array.as_fixed_size_binary().clone(), options.binary))
+        }
+        DataType::Date32
+        | DataType::Date64
+        | DataType::Time32(_)
+        | DataType::Time64(_)
+        | DataType::Timestamp(..) => match options.temporal {
+            TemporalFormat::Iso8601 => text_encoder(array)?,
+            TemporalFormat::Epoch => {
+                // 32-bit temporals are reinterpreted as integers before widening
+                let epoch = if matches!(array.data_type(), DataType::Date32 | DataType::Time32(_)) {
+                    cast(&cast(array, &DataType::Int32)?, &DataType::Int64)?
+                } else {
+                    cast(array, &DataType::Int64)?
+                };
+                Box::new(NumberEncoder(epoch.as_primitive::<Int64Type>().clone()))
+            }
+        },
+        DataType::List(_) => list_encoder(array.as_list::<i32>(), options)?,
+        DataType::LargeList(_) => list_encoder(array.as_list::<i64>(), options)?,
+        DataType::FixedSizeList(..) => {
+            let list = array.as_fixed_size_list();
+            Box::new(FixedSizeListEncoder {
+                size:   usize::try_from(list.value_length()).unwrap_or_default(),
+                values: make_encoder(list.values(), options)?,
+            })
+        }
+        DataType::Struct(_) => {
+            let array = array.as_struct();
+            let fields = array
+                .fields()
+                .iter()
+                .zip(array.columns())
+                .map(|(field, column)| Ok((quoted(field.name()), make_encoder(column, options)?)))
+                .collect::<Result<Vec<_>>>()?;
+            Box::new(StructEncoder { fields, explicit_nulls: options.explicit_nulls })
+        }
+        DataType::Map(..) => {
+            let map = array.as_map();
+            let keys = cast(map.keys(), &DataType::Utf8)?;
+            Box::new(MapEncoder {
+                offsets: map.offsets().clone(),
+                keys:    StringEncoder(keys.as_string::<i32>().clone()),
+                values:  make_encoder(map.values(), options)?,
+            })
+        }
+        DataType::Dictionary(_, value_type) => {
+            let values = cast(array, value_type)?;
+            return make_encoder(&values, options);
+        }
+        DataType::Duration(_) | DataType::Interval(_) => text_encoder(array)?,
+        data_type => {
+            return Err(Error::ArrowUnsupportedType(format!("Cannot render {data_type} as JSON")));
+        }
+    };
+    Ok(NullableEncoder { nulls, encoder })
+}
+
+/// Render values as JSON strings using Arrow's display formatting.
+fn text_encoder(array: &ArrayRef) -> Result<Box<dyn Encode>> {
+    let text = cast(array, &DataType::Utf8)?;
+    Ok(Box::new(StringEncoder(text.as_string::<i32>().clone())))
+}
+
+fn decimal_encoder<T: DecimalType>(array: &ArrayRef, format: DecimalFormat) -> Box<dyn Encode> {
+    let array = array.as_primitive::<T>().clone();
+    let (precision, scale) = (array.precision(), array.scale());
+    Box::new(DecimalEncoder { array, precision, scale, format })
+}
+
+fn list_encoder<O: OffsetSizeTrait>(
+    list: &GenericListArray<O>,
+    options: &NdjsonOptions,
+) -> Result<Box<dyn Encode>> {
+    Ok(Box::new(ListEncoder {
+        offsets: list.offsets().clone(),
+        values:  make_encoder(list.values(), options)?,
+    }))
+}
+
+struct NullEncoder;
+
+impl Encode for NullEncoder {
+    fn encode(&self, _idx: usize, out: &mut Vec<u8>) { out.extend_from_slice(b"null"); }
+}
+
+struct BooleanEncoder(BooleanArray);
+
+impl Encode for BooleanEncoder {
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        out.extend_from_slice(if self.0.value(idx) { b"true" } else { b"false" });
+    }
+}
+
+struct NumberEncoder<T: ArrowPrimitiveType>(PrimitiveArray<T>);
+
+impl<T> Encode for NumberEncoder<T>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Display,
+{
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        let _ = write!(out, "{}", self.0.value(idx));
+    }
+}
+
+/// Non-finite values have no JSON representation and are rendered as `null`.
+struct FloatEncoder<T: ArrowPrimitiveType>(PrimitiveArray<T>);
+
+impl<T> Encode for FloatEncoder<T>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Display + Into<f64>,
+{
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        let value = self.0.value(idx);
+        if value.into().is_finite() {
+            let _ = write!(out, "{value}");
+        } else {
+            out.extend_from_slice(b"null");
+        }
+    }
+}
+
+struct DecimalEncoder<T: DecimalType> {
+    array:     PrimitiveArray<T>,
+    precision: u8,
+    scale:     i8,
+    format:    DecimalFormat,
+}
+
+impl<T: DecimalType> Encode for DecimalEncoder<T> {
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        let value = T::format_decimal(self.array.value(idx), self.precision, self.scale);
+        match self.format {
+            DecimalFormat::String => write_str(&value, out),
+            DecimalFormat::Number => out.extend_from_slice(value.as_bytes()),
+        }
+    }
+}
+
+/// String arrays of any offset or view layout.
+trait TextValues: Send + Sync {
+    fn text(&self, idx: usize) -> &str;
+}
+
+impl<O: OffsetSizeTrait> TextValues for GenericStringArray<O> {
+    fn text(&self, idx: usize) -> &str { self.value(idx) }
+}
+
+impl TextValues for StringViewArray {
+    fn text(&self, idx: usize) -> &str { self.value(idx) }
+}
+
+struct StringEncoder<A: TextValues>(A);
+
+impl<A: TextValues> Encode for StringEncoder<A> {
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) { write_str(self.0.text(idx), out); }
+}
+
+/// Binary arrays of any offset, view or fixed size layout.
+trait BinaryValues: Send + Sync {
+    fn bytes(&self, idx: usize) -> &[u8];
+}
+
+impl<O: OffsetSizeTrait> BinaryValues for GenericBinaryArray<O> {
+    fn bytes(&self, idx: usize) -> &[u8] { self.value(idx) }
+}
+
+impl BinaryValues for BinaryViewArray {
+    fn bytes(&self, idx: usize) -> &[u8] { self.value(idx) }
+}
+
+impl BinaryValues for FixedSizeBinaryArray {
+    fn bytes(&self, idx: usize) -> &[u8] { self.value(idx) }
+}
+
+struct BinaryEncoder<A: BinaryValues>(A, BinaryFormat);
+
+impl<A: BinaryValues> Encode for BinaryEncoder<A> {
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        let bytes = self.0.bytes(idx);
+        match self.1 {
+            BinaryFormat::Base64 => {
+                out.push(b'"');
+                write_base64(bytes, out);
+                out.push(b'"');
+            }
+            BinaryFormat::Hex => {
+                out.push(b'"');
+                for byte in bytes {
+                    let _ = write!(out, "{byte:02x}");
+                }
+                out.push(b'"');
+            }
+            BinaryFormat::Utf8Lossy => write_str(&String::from_utf8_lossy(bytes), out),
+        }
+    }
+}
+
+struct ListEncoder<O: OffsetSizeTrait> {
+    offsets: OffsetBuffer<O>,
+    values:  NullableEncoder,
+}
+
+impl<O: OffsetSizeTrait> Encode for ListEncoder<O> {
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        let (start, end) = (self.offsets[idx].as_usize(), self.offsets[idx + 1].as_usize());
+        write_array(&self.values, start..end, out);
+    }
+}
+
+struct FixedSizeListEncoder {
+    size:   usize,
+    values: NullableEncoder,
+}
+
+impl Encode for FixedSizeListEncoder {
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        write_array(&self.values, idx * self.size..(idx + 1) * self.size, out);
+    }
+}
+
+struct StructEncoder {
+    /// Pre-quoted field names and their encoders
+    fields:         Vec<(Vec<u8>, NullableEncoder)>,
+    explicit_nulls: bool,
+}
+
+impl Encode for StructEncoder {
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        out.push(b'{');
+        let mut first = true;
+        for (name, encoder) in &self.fields {
+            if !self.explicit_nulls && encoder.is_null(idx) {
+                continue;
+            }
+            if !first {
+                out.push(b',');
+            }
+            first = false;
+            out.extend_from_slice(name);
+            out.push(b':');
+            encoder.encode(idx, out);
+        }
+        out.push(b'}');
+    }
+}
+
+/// Maps are rendered as objects, with keys rendered as strings. Null keys render as `"null"`.
+struct MapEncoder {
+    offsets: OffsetBuffer<i32>,
+    keys:    StringEncoder<StringArray>,
+    values:  NullableEncoder,
+}
+
+impl Encode for MapEncoder {
+    fn encode(&self, idx: usize, out: &mut Vec<u8>) {
+        let (start, end) = (self.offsets[idx].as_usize(), self.offsets[idx + 1].as_usize());
+        out.push(b'{');
+        for entry in start..end {
+            if entry > start {
+                out.push(b',');
+            }
+            if self.keys.0.is_null(entry) {
+                out.extend_from_slice(b"\"null\"");
+            } else {
+                self.keys.encode(entry, out);
+            }
+            out.push(b':');
+            self.values.encode(entry, out);
+        }
+        out.push(b'}');
+    }
+}
+
+fn write_array(values: &NullableEncoder, range: std::ops::Range<usize>, out: &mut Vec<u8>) {
+    out.push(b'[');
+    for (i, idx) in range.enumerate() {
+        if i > 0 {
+            out.push(b',');
+        }
+        values.encode(idx, out);
+    }
+    out.push(b']');
+}
+
+fn quoted(value: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len() + 2);
+    write_str(value, &mut out);
+    out
+}
+
+/// Write `value` as a JSON string, escaping quotes, backslashes and control characters.
+fn write_str(value: &str, out: &mut Vec<u8>) {
+    out.push(b'"');
+    let bytes = value.as_bytes();
+    let mut start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let escape: &[u8] = match byte {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0x00..=0x1F => b"",
+            _ => continue,
+        };
+        out.extend_from_slice(&bytes[start..i]);
+        if escape.is_empty() {
+            let _ = write!(out, "\\u{byte:04x}");
+        } else {
+            out.extend_from_slice(escape);
+        }
+        start = i + 1;
+    }
+    out.extend_from_slice(&bytes[start..]);
+    out.push(b'"');
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Write `bytes` as standard, padded base64.
+fn write_base64(bytes: &[u8], out: &mut Vec<u8>) {
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for (i, shift) in [18, 12, 6, 0].into_iter().enumerate() {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[((n >> shift) & 0x3F) as usize]);
+            } else {
+                out.push(b'=');
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn encode(batch: &RecordBatch, options: NdjsonOptions) -> String {
+        let mut out = Vec::new();
+        NdjsonEncoder::new(options).encode(batch, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_write_base64() {
+        for (input, expected) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            let mut out = Vec::new();
+            write_base64(input, &mut out);
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_write_str_escapes() {
+        let mut out = Vec::new();
+        write_str("a\"b\\c\nd\u{1}é", &mut out);
+        assert_eq!(String::from_utf8(out).unwrap(), r#""a\"b\\c\nd\u0001é""#);
+    }
+
+    #[test]
+    fn test_encode_scalars() {
+        let batch = RecordBatch::try_from_iter(vec![
+            ("id", Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef),
+            ("name", Arc::new(StringArray::from(vec![Some("a"), None])) as ArrayRef),
+            ("score", Arc::new(Float64Array::from(vec![1.5, f64::NAN])) as ArrayRef),
+            (
+                "price",
+                Arc::new(
+                    Decimal128Array::from(vec![12345, -5]).with_precision_and_scale(10, 2).unwrap(),
+                ) as ArrayRef,
+            ),
+            ("raw", Arc::new(BinaryArray::from(vec![&b"foo"[..], b""])) as ArrayRef),
+            ("day", Arc::new(Date32Array::from(vec![0, 19_753])) as ArrayRef),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            encode(&batch, NdjsonOptions::new()),
+            concat!(
+                "{\"id\":1,\"name\":\"a\",\"score\":1.5,\"price\":\"123.45\",\"raw\":\"Zm9v\",",
+                "\"day\":\"1970-01-01\"}\n",
+                "{\"id\":2,\"name\":null,\"score\":null,\"price\":\"-0.05\",\"raw\":\"\",",
+                "\"day\":\"2024-01-31\"}\n",
+            )
+        );
+
+        let options = NdjsonOptions::new()
+            .with_temporal(TemporalFormat::Epoch)
+            .with_decimal(DecimalFormat::Number)
+            .with_binary(BinaryFormat::Hex)
+            .with_explicit_nulls(false);
+        assert_eq!(
+            encode(&batch, options),
+            "{\"id\":1,\"name\":\"a\",\"score\":1.5,\"price\":123.45,\"raw\":\"666f6f\",\"day\":\
+             0}\n{\"id\":2,\"score\":null,\"price\":-0.05,\"raw\":\"\",\"day\":19753}\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_nested() {
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), None]),
+            None,
+        ]);
+        let values = Int64Array::from(vec![1, 2]);
+        let map = MapArray::new_from_strings(["a", "b"].into_iter(), &values, &[0, 2, 2]).unwrap();
+        let strukt = StructArray::from(vec![(
+            Arc::new(Field::new("x", DataType::Utf8, false)),
+            Arc::new(StringArray::from(vec!["p", "q"])) as ArrayRef,
+        )]);
+        let batch = RecordBatch::try_from_iter(vec![
+            ("list", Arc::new(list) as ArrayRef),
+            ("map", Arc::new(map) as ArrayRef),
+            ("struct", Arc::new(strukt) as ArrayRef),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            encode(&batch, NdjsonOptions::new()),
+            "{\"list\":[1,null],\"map\":{\"a\":1,\"b\":2},\"struct\":{\"x\":\"p\"}}\n{\"list\":\
+             null,\"map\":{},\"struct\":{\"x\":\"q\"}}\n"
+        );
+    }
+}
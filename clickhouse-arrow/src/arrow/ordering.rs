@@ -0,0 +1,223 @@
+//! Client-side validation of query result ordering.
+//!
+//! Code that merges, deduplicates or resumes from query results often relies on their `ORDER BY`
+//! clause. A `Distributed` table or a misconfigured `optimize_read_in_order` can quietly break
+//! that assumption. With
+//! [`QueryOptions::assert_sorted_by`](crate::explain::QueryOptions::assert_sorted_by) every batch
+//! is checked against the expected sort keys as it arrives, including the boundary with the
+//! previous batch. The first row sorting before its predecessor fails the query with
+//! [`Error::UnorderedResult`], locating the row within the result.
+//!
+//! Rows are compared with Arrow's row format, the same ordering used by
+//! `ClusterClient::query_sharded_ordered`. `ClickHouse` sorts nulls last in either direction
+//! unless `NULLS FIRST` is given, which corresponds to `SortOptions { nulls_first: false, .. }`.
+use std::sync::Arc;
+
+use arrow::array::ArrayRef;
+use arrow::compute::SortOptions;
+use arrow::record_batch::RecordBatch;
+use arrow::row::{OwnedRow, RowConverter, SortField};
+use arrow::util::display::array_value_to_string;
+
+use crate::{Error, Result};
+
+/// The first out of order row of a query result, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnorderedResult {
+    /// Index of the row within the whole result.
+    pub row:       u64,
+    /// Index of the batch holding the row.
+    pub batch:     u64,
+    /// Index of the row within its batch.
+    pub batch_row: usize,
+    /// Names of the sort key columns.
+    pub keys:      Vec<String>,
+    /// Sort key values of the previous row, which may be the last row of the previous batch.
+    pub previous:  Vec<String>,
+    /// Sort key values of the out of order row.
+    pub current:   Vec<String>,
+}
+
+impl std::fmt::Display for UnorderedResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "row {} (batch {}, row {}) sorts before the previous row by ({}): ({}) after ({})",
+            self.row,
+            self.batch,
+            self.batch_row,
+            self.keys.join(", "),
+            self.current.join(", "),
+            self.previous.join(", ")
+        )
+    }
+}
+
+/// Checks that the batches of a query result are sorted by a set of key columns.
+#[derive(Debug)]
+pub struct OrderValidator {
+    keys:      Arc<[(String, SortOptions)]>,
+    converter: Option<RowConverter>,
+    /// The sort keys of the last row seen, and their rendered values.
+    last:      Option<(OwnedRow, Vec<String>)>,
+    rows:      u64,
+    batches:   u64,
+}
+
+impl OrderValidator {
+    /// Validate ordering by `keys`, the sort key columns in order of precedence.
+    pub fn new(keys: impl Into<Arc<[(String, SortOptions)]>>) -> Self {
+        Self { keys: keys.into(), converter: None, last: None, rows: 0, batches: 0 }
+    }
+
+    /// Check the next batch of the result.
+    ///
+    /// # Errors
+    /// Returns [`Error::UnorderedResult`] for the first row that sorts before its predecessor,
+    /// or [`Error::Client`] if a key column is missing from the batch.
+    pub fn check(&mut self, batch: &RecordBatch) -> Result<()> {
+        let index = self.batches;
+        self.batches += 1;
+        let num_rows = batch.num_rows();
+        if num_rows == 0 || self.keys.is_empty() {
+            return Ok(());
+        }
+
+        let columns = self
+            .keys
+            .iter()
+            .map(|(name, _)| {
+                batch.column_by_name(name).map(Arc::clone).ok_or_else(|| {
+                    Error::Client(format!("Sort column '{name}' not found in query results"))
+                })
+            })
+            .collect::<Result<Vec<ArrayRef>>>()?;
+        if self.converter.is_none() {
+            let fields = columns
+                .iter()
+                .zip(self.keys.iter())
+                .map(|(column, (_, options))| {
+                    SortField::new_with_options(column.data_type().clone(), *options)
+                })
+                .collect();
+            self.converter = Some(RowConverter::new(fields)?);
+        }
+        let converter = self.converter.as_ref().expect("converter initialized above");
+        let rows = converter.convert_columns(&columns)?;
+
+        let unordered = match &self.last {
+            Some((last, previous)) if rows.row(0) < last.row() => Some((0, previous.clone())),
+            _ => (1..num_rows)
+                .find(|&i| rows.row(i) < rows.row(i - 1))
+                .map(|i| render(&columns, i - 1).map(|previous| (i, previous)))
+                .transpose()?,
+        };
+        if let Some((batch_row, previous)) = unordered {
+            return Err(Error::UnorderedResult(Box::new(UnorderedResult {
+                row: self.rows + batch_row as u64,
+                batch: index,
+                batch_row,
+                keys: self.keys.iter().map(|(name, _)| name.clone()).collect(),
+                previous,
+                current: render(&columns, batch_row)?,
+            })));
+        }
+
+        self.last = Some((rows.row(num_rows - 1).owned(), render(&columns, num_rows - 1)?));
+        self.rows += num_rows as u64;
+        Ok(())
+    }
+}
+
+/// The sort key values of row `i`, for error messages.
+fn render(columns: &[ArrayRef], i: usize) -> Result<Vec<String>> {
+    Ok(columns.iter().map(|column| array_value_to_string(column, i)).collect::<Result<_, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+
+    fn batch(ids: Vec<Option<i64>>, names: Vec<&str>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ])
+        .unwrap()
+    }
+
+    fn ordered_by(keys: &[(&str, SortOptions)]) -> OrderValidator {
+        OrderValidator::new(
+            keys.iter().map(|(name, options)| ((*name).to_string(), *options)).collect::<Vec<_>>(),
+        )
+    }
+
+    const ASC: SortOptions = SortOptions { descending: false, nulls_first: false };
+    const DESC: SortOptions = SortOptions { descending: true, nulls_first: false };
+
+    #[test]
+    fn test_sorted_batches() {
+        let mut validator = ordered_by(&[("id", ASC), ("name", DESC)]);
+        validator.check(&batch(vec![Some(1), Some(1), Some(2)], vec!["b", "a", "z"])).unwrap();
+        validator.check(&batch(vec![], vec![])).unwrap();
+        validator.check(&batch(vec![Some(2), Some(3), None], vec!["z", "a", "a"])).unwrap();
+    }
+
+    #[test]
+    fn test_unordered_within_batch() {
+        let mut validator = ordered_by(&[("id", ASC), ("name", DESC)]);
+        validator.check(&batch(vec![Some(1), Some(2)], vec!["a", "a"])).unwrap();
+        let error = validator
+            .check(&batch(vec![Some(3), Some(3), Some(3)], vec!["b", "a", "c"]))
+            .unwrap_err();
+        let Error::UnorderedResult(unordered) = error else { panic!("unexpected error {error}") };
+        assert_eq!(*unordered, UnorderedResult {
+            row:       4,
+            batch:     1,
+            batch_row: 2,
+            keys:      vec!["id".into(), "name".into()],
+            previous:  vec!["3".into(), "a".into()],
+            current:   vec!["3".into(), "c".into()],
+        });
+        let message = "row 4 (batch 1, row 2) sorts before the previous row by (id, name): (3, c) \
+                       after (3, a)";
+        assert_eq!(unordered.to_string(), message);
+    }
+
+    #[test]
+    fn test_unordered_across_batches() {
+        let mut validator = ordered_by(&[("id", DESC)]);
+        validator.check(&batch(vec![Some(9), Some(5)], vec!["a", "b"])).unwrap();
+        let error = validator.check(&batch(vec![Some(7)], vec!["c"])).unwrap_err();
+        let Error::UnorderedResult(unordered) = error else { panic!("unexpected error {error}") };
+        assert_eq!((unordered.row, unordered.batch, unordered.batch_row), (2, 1, 0));
+        assert_eq!(unordered.previous, vec!["5".to_string()]);
+        assert_eq!(unordered.current, vec!["7".to_string()]);
+    }
+
+    #[test]
+    fn test_nulls_placement() {
+        // Nulls sort last by default in `ClickHouse`
+        let mut validator = ordered_by(&[("id", ASC)]);
+        let error = validator.check(&batch(vec![None, Some(1)], vec!["a", "b"])).unwrap_err();
+        assert!(matches!(error, Error::UnorderedResult(_)));
+
+        let nulls_first = SortOptions { descending: false, nulls_first: true };
+        let mut validator = ordered_by(&[("id", nulls_first)]);
+        validator.check(&batch(vec![None, Some(1)], vec!["a", "b"])).unwrap();
+    }
+
+    #[test]
+    fn test_missing_key_column() {
+        let mut validator = ordered_by(&[("missing", ASC)]);
+        let error = validator.check(&batch(vec![Some(1)], vec!["a"])).unwrap_err();
+        assert!(matches!(error, Error::Client(_)));
+    }
+}
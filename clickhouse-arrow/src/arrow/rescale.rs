@@ -0,0 +1,241 @@
+//! Client-side decimal rescaling of Arrow query results.
+//!
+//! `ClickHouse` often returns decimals wider or finer than a consumer needs, ie `Decimal(38, 10)`
+//! from an aggregation when the consumer stores `Decimal128(18, 4)`. With
+//! [`QueryOptions::rescale_decimal`](crate::explain::QueryOptions::rescale_decimal), named
+//! columns are converted to the target precision and scale as each block is decoded, instead of
+//! in a separate Arrow compute pass over the results.
+//!
+//! Reducing the scale rounds half away from zero. Values that do not fit the target precision are
+//! handled according to the [`DecimalOverflow`] policy.
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, AsArray, Decimal128Array, Decimal256Array, Int32Array, RunArray,
+};
+use arrow::datatypes::{
+    DECIMAL128_MAX_PRECISION, DECIMAL256_MAX_PRECISION, DataType, Decimal128Type, Decimal256Type,
+    Int32Type, i256,
+};
+
+use crate::{Error, Result};
+
+/// Handling of decimal values that do not fit the target precision of a [`DecimalRescale`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecimalOverflow {
+    /// Fail the query with a deserialize error (default).
+    #[default]
+    Error,
+    /// Replace the value with null. The rescaled column is nullable.
+    Null,
+    /// Clamp the value to the largest or smallest value of the target precision.
+    Saturate,
+}
+
+/// The target precision and scale of a rescaled decimal column, see the [module docs](self).
+///
+/// Targets with a precision of at most 38 produce `Decimal128` arrays, wider targets produce
+/// `Decimal256` arrays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecimalRescale {
+    /// Total number of digits of the target type.
+    pub precision: u8,
+    /// Number of digits after the decimal point of the target type.
+    pub scale:     i8,
+    /// Handling of values that do not fit `precision`.
+    pub overflow:  DecimalOverflow,
+}
+
+impl DecimalRescale {
+    /// Rescale to `precision` and `scale`, failing on overflow.
+    pub const fn new(precision: u8, scale: i8) -> Self {
+        Self { precision, scale, overflow: DecimalOverflow::Error }
+    }
+
+    /// Set the handling of values that do not fit the target precision.
+    #[must_use]
+    pub const fn with_overflow(mut self, overflow: DecimalOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// The Arrow type of rescaled columns.
+    pub fn data_type(&self) -> DataType {
+        if self.precision <= DECIMAL128_MAX_PRECISION {
+            DataType::Decimal128(self.precision, self.scale)
+        } else {
+            DataType::Decimal256(self.precision, self.scale)
+        }
+    }
+
+    /// Check that the target is a valid Arrow decimal type.
+    ///
+    /// # Errors
+    /// Returns [`Error::TypeConversion`] if the precision is 0 or above 76, or the scale exceeds
+    /// the precision.
+    pub fn validate(&self) -> Result<()> {
+        if self.precision == 0
+            || self.precision > DECIMAL256_MAX_PRECISION
+            || i16::from(self.scale) > i16::from(self.precision)
+        {
+            return Err(Error::TypeConversion(format!(
+                "invalid decimal rescale target Decimal({}, {})",
+                self.precision, self.scale
+            )));
+        }
+        Ok(())
+    }
+
+    /// Rescale a decoded decimal column to this target.
+    ///
+    /// # Errors
+    /// Returns an error if the target is invalid, `array` is not a decimal array, or a value
+    /// overflows the target precision under [`DecimalOverflow::Error`].
+    pub fn rescale(&self, array: &ArrayRef) -> Result<ArrayRef> {
+        self.validate()?;
+        // Run end encoded columns, see `ArrowOptions::sparse_as_run_end_encoded`
+        if let Some(run_array) = array.as_run_opt::<Int32Type>() {
+            let run_ends = Int32Array::new(run_array.run_ends().inner().clone(), None);
+            let values = self.rescale(run_array.values())?;
+            return Ok(Arc::new(RunArray::<Int32Type>::try_new(&run_ends, &values)?));
+        }
+        let (values, scale): (Vec<Option<i256>>, i8) = match array.data_type() {
+            DataType::Decimal128(_, scale) => (
+                array
+                    .as_primitive::<Decimal128Type>()
+                    .iter()
+                    .map(|v| v.map(i256::from_i128))
+                    .collect(),
+                *scale,
+            ),
+            DataType::Decimal256(_, scale) => {
+                (array.as_primitive::<Decimal256Type>().iter().collect(), *scale)
+            }
+            other => {
+                return Err(Error::TypeConversion(format!(
+                    "cannot rescale {other} column to Decimal({}, {})",
+                    self.precision, self.scale
+                )));
+            }
+        };
+
+        let max = pow10(u32::from(self.precision)).wrapping_sub(i256::ONE);
+        let values = values
+            .into_iter()
+            .map(|value| value.map(|v| self.rescale_value(v, scale, max)).transpose())
+            .map(|value| value.map(Option::flatten))
+            .collect::<Result<Vec<_>>>()?;
+
+        let array: ArrayRef = if self.precision <= DECIMAL128_MAX_PRECISION {
+            // Values within a precision of at most 38 digits always fit an i128
+            let values = values.into_iter().map(|v| v.and_then(|v| v.to_i128()));
+            Arc::new(
+                Decimal128Array::from_iter(values)
+                    .with_precision_and_scale(self.precision, self.scale)?,
+            )
+        } else {
+            Arc::new(
+                Decimal256Array::from_iter(values)
+                    .with_precision_and_scale(self.precision, self.scale)?,
+            )
+        };
+        Ok(array)
+    }
+
+    /// Rescale a single value from `scale`, returning `None` if it overflows to null.
+    fn rescale_value(&self, value: i256, scale: i8, max: i256) -> Result<Option<i256>> {
+        let shift = i16::from(self.scale) - i16::from(scale);
+        let factor = pow10(u32::from(shift.unsigned_abs()));
+        let rescaled = if shift >= 0 {
+            value.checked_mul(factor)
+        } else {
+            // Round half away from zero
+            let (quotient, remainder) = (value.wrapping_div(factor), value.wrapping_rem(factor));
+            let half = remainder.wrapping_abs().wrapping_mul(i256::from_i128(2)) >= factor;
+            Some(if half { quotient.wrapping_add(value.signum()) } else { quotient })
+        };
+
+        match rescaled {
+            Some(v) if v <= max && v >= max.wrapping_neg() => Ok(Some(v)),
+            _ => match self.overflow {
+                DecimalOverflow::Error => Err(Error::DeserializeError(format!(
+                    "decimal value {value} with scale {scale} overflows Decimal({}, {})",
+                    self.precision, self.scale
+                ))),
+                DecimalOverflow::Null => Ok(None),
+                DecimalOverflow::Saturate => {
+                    Ok(Some(if value.is_negative() { max.wrapping_neg() } else { max }))
+                }
+            },
+        }
+    }
+}
+
+/// `10^exp`, saturating past the range of `i256`.
+fn pow10(exp: u32) -> i256 { i256::from_i128(10).checked_pow(exp).unwrap_or(i256::MAX) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal128(values: Vec<Option<i128>>, precision: u8, scale: i8) -> ArrayRef {
+        Arc::new(Decimal128Array::from(values).with_precision_and_scale(precision, scale).unwrap())
+    }
+
+    #[test]
+    fn test_rescale_reduces_scale_with_rounding() {
+        // 1.23455, -1.23455, 2.00004, null at Decimal(38, 5)
+        let array = decimal128(vec![Some(123_455), Some(-123_455), Some(200_004), None], 38, 5);
+        let rescaled = DecimalRescale::new(18, 4).rescale(&array).unwrap();
+        assert_eq!(rescaled.data_type(), &DataType::Decimal128(18, 4));
+        let expected = decimal128(vec![Some(12_346), Some(-12_346), Some(20_000), None], 18, 4);
+        assert_eq!(&rescaled, &expected);
+    }
+
+    #[test]
+    fn test_rescale_increases_scale() {
+        let array = decimal128(vec![Some(15), Some(-7)], 9, 1);
+        let rescaled = DecimalRescale::new(76, 3).rescale(&array).unwrap();
+        let expected: ArrayRef = Arc::new(
+            Decimal256Array::from(vec![i256::from_i128(1_500), i256::from_i128(-700)])
+                .with_precision_and_scale(76, 3)
+                .unwrap(),
+        );
+        assert_eq!(&rescaled, &expected);
+    }
+
+    #[test]
+    fn test_rescale_overflow_policies() {
+        // 123.45 and -123.45 at Decimal(10, 2) do not fit Decimal(4, 2)
+        let array = decimal128(vec![Some(12_345), Some(-12_345), Some(99)], 10, 2);
+
+        let error = DecimalRescale::new(4, 2).rescale(&array).unwrap_err();
+        assert!(matches!(error, Error::DeserializeError(_)));
+
+        let nulled = DecimalRescale::new(4, 2)
+            .with_overflow(DecimalOverflow::Null)
+            .rescale(&array)
+            .unwrap();
+        assert_eq!(&nulled, &decimal128(vec![None, None, Some(99)], 4, 2));
+
+        let saturated = DecimalRescale::new(4, 2)
+            .with_overflow(DecimalOverflow::Saturate)
+            .rescale(&array)
+            .unwrap();
+        assert_eq!(&saturated, &decimal128(vec![Some(9_999), Some(-9_999), Some(99)], 4, 2));
+    }
+
+    #[test]
+    fn test_rescale_rejects_invalid_targets() {
+        let array = decimal128(vec![Some(1)], 9, 0);
+        assert!(DecimalRescale::new(0, 0).rescale(&array).is_err());
+        assert!(DecimalRescale::new(77, 0).rescale(&array).is_err());
+        assert!(DecimalRescale::new(4, 5).rescale(&array).is_err());
+
+        let strings: ArrayRef = Arc::new(arrow::array::StringArray::from(vec!["1.0"]));
+        assert!(matches!(
+            DecimalRescale::new(18, 4).rescale(&strings),
+            Err(Error::TypeConversion(_))
+        ));
+    }
+}
@@ -9,6 +9,7 @@ use futures_util::stream::StreamExt;
 
 use super::utils::array_to_string_iter;
 use crate::ArrowOptions;
+use crate::explore::system_table_filter;
 use crate::prelude::*;
 
 /// Fetches all tables for provided databases.
@@ -86,6 +87,36 @@ pub(crate) async fn fetch_databases(
     Ok(dbs)
 }
 
+/// Fetches the name and `ClickHouse` type of each column of a table, in table order. The
+/// current database is used if `database` is `None`.
+pub(crate) async fn fetch_column_types(
+    client: &Client<ArrowFormat>,
+    database: Option<&str>,
+    table: &str,
+    qid: Option<Qid>,
+) -> Result<Vec<(String, Type)>> {
+    let table = table.trim_matches(['`', '\'']);
+    let (filter, params) = system_table_filter(database, "table", table);
+    let query = format!("SELECT name, type FROM system.columns WHERE {filter} ORDER BY position");
+
+    let mut stream = client.query_params(query, Some(params), qid).await?;
+    let mut columns = Vec::new();
+    while let Some(batch) = stream.next().await.transpose()? {
+        let name_col = cast(batch.column(0), &DataType::Utf8)?;
+        let name_col = name_col.as_string_opt::<i32>().ok_or(Error::ArrowDeserialize(
+            "Could not deserialize name column for column types".into(),
+        ))?;
+        let type_col = cast(batch.column(1), &DataType::Utf8)?;
+        let type_col = type_col.as_string_opt::<i32>().ok_or(Error::ArrowDeserialize(
+            "Could not deserialize type column for column types".into(),
+        ))?;
+        for i in 0..batch.num_rows() {
+            columns.push((name_col.value(i).to_string(), Type::from_str(type_col.value(i))?));
+        }
+    }
+    Ok(columns)
+}
+
 /// Fetches schemas for all tables in a `ClickHouse` database (or a subset if tables are specified).
 pub(crate) async fn fetch_schema(
     client: &Client<ArrowFormat>,
@@ -1302,3 +1302,188 @@ mod tests_sync {
         assert_eq!(u64::from_le_bytes(bytes), 4);
     }
 }
+
+/// Randomized property tests: serializing a slice of an array must produce the same bytes as
+/// serializing a compact copy of the same rows.
+#[cfg(test)]
+mod tests_slices {
+    use std::sync::Arc;
+
+    use arrow::array::*;
+    use arrow::datatypes::{DataType, Field};
+
+    use super::*;
+
+    const ROWS: usize = 67;
+    const CASES: usize = 64;
+
+    /// Small deterministic xorshift generator, so failures are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        #[expect(clippy::cast_possible_truncation)]
+        fn below(&mut self, n: usize) -> usize { (self.next_u64() % n as u64) as usize }
+
+        fn chance(&mut self, one_in: usize) -> bool { self.below(one_in) == 0 }
+    }
+
+    fn fixtures(rng: &mut Rng) -> Vec<(Type, ArrayRef)> {
+        let nullable = |t: Type| Type::Nullable(Box::new(t));
+        let mut fixtures: Vec<(Type, ArrayRef)> = Vec::new();
+
+        #[expect(clippy::cast_possible_wrap, clippy::cast_possible_truncation)]
+        let ints = (0..ROWS).map(|i| (!rng.chance(4)).then_some(i as i32)).collect::<Vec<_>>();
+        fixtures.push((nullable(Type::Int32), Arc::new(Int32Array::from(ints.clone()))));
+        fixtures.push((
+            nullable(Type::Decimal128(0)),
+            Arc::new(Decimal128Array::from(
+                ints.iter().map(|v| v.map(i128::from)).collect::<Vec<_>>(),
+            )),
+        ));
+
+        let bools = (0..ROWS).map(|_| (!rng.chance(3)).then(|| rng.chance(2))).collect::<Vec<_>>();
+        fixtures.push((nullable(Type::UInt8), Arc::new(BooleanArray::from(bools))));
+
+        let strings = (0..ROWS)
+            .map(|i| (!rng.chance(4)).then(|| "x".repeat(rng.below(6)) + &i.to_string()))
+            .collect::<Vec<_>>();
+        fixtures.push((nullable(Type::String), Arc::new(StringArray::from(strings.clone()))));
+        fixtures.push((
+            Type::Binary,
+            Arc::new(BinaryArray::from_iter_values(strings.iter().flatten().map(String::as_bytes))),
+        ));
+        fixtures.push((
+            Type::FixedSizedBinary(4),
+            Arc::new(
+                FixedSizeBinaryArray::try_from_iter(
+                    (0..ROWS).map(|i| i.to_le_bytes()[..4].to_vec()),
+                )
+                .unwrap(),
+            ),
+        ));
+
+        let mut lists = ListBuilder::new(StringBuilder::new());
+        let mut large_lists = LargeListBuilder::new(Int64Builder::new());
+        let mut nested = ListBuilder::new(ListBuilder::new(Int32Builder::new()));
+        let mut fixed = FixedSizeListBuilder::new(Int32Builder::new(), 3);
+        let mut maps = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        for i in 0..ROWS {
+            #[expect(clippy::cast_possible_wrap, clippy::cast_possible_truncation)]
+            let value = i as i32;
+            // Null lists reference no values
+            let valid = !rng.chance(5);
+            for j in 0..rng.below(4) {
+                if valid {
+                    lists.values().append_value(format!("{i}_{j}"));
+                }
+                large_lists.values().append_option((!rng.chance(3)).then_some(i64::from(value)));
+                maps.keys().append_value(format!("k{j}"));
+                maps.values().append_value(value);
+                let inner = nested.values();
+                for _ in 0..rng.below(3) {
+                    inner.values().append_value(value);
+                }
+                inner.append(true);
+            }
+            lists.append(valid);
+            large_lists.append(true);
+            nested.append(true);
+            maps.append(true).unwrap();
+            fixed.values().append_slice(&[value, value + 1, value + 2]);
+            fixed.append(true);
+        }
+        let lists = lists.finish();
+        let list_views = ListViewArray::from(lists.clone());
+        fixtures.push((Type::Array(Box::new(Type::String)), Arc::new(lists)));
+        fixtures.push((Type::Array(Box::new(Type::String)), Arc::new(list_views)));
+        fixtures
+            .push((Type::Array(Box::new(nullable(Type::Int64))), Arc::new(large_lists.finish())));
+        fixtures.push((
+            Type::Array(Box::new(Type::Array(Box::new(Type::Int32)))),
+            Arc::new(nested.finish()),
+        ));
+        fixtures.push((Type::Array(Box::new(Type::Int32)), Arc::new(fixed.finish())));
+        fixtures.push((
+            Type::Map(Box::new(Type::String), Box::new(Type::Int32)),
+            Arc::new(maps.finish()),
+        ));
+
+        let tuple = StructArray::from(vec![
+            (Arc::new(Field::new("a", DataType::Int32, true)), Arc::clone(&fixtures[0].1)),
+            (Arc::new(Field::new("b", DataType::Utf8, true)), Arc::clone(&fixtures[3].1)),
+        ]);
+        fixtures.push((
+            Type::Tuple(vec![nullable(Type::Int32), nullable(Type::String)]),
+            Arc::new(tuple),
+        ));
+
+        fixtures
+    }
+
+    /// A copy of `array` with all buffers starting at offset 0.
+    ///
+    /// List views are compared against the equivalent list, whose serialization must match.
+    fn compact(array: &ArrayRef) -> ArrayRef {
+        if let Some(view) = array.as_any().downcast_ref::<ListViewArray>() {
+            let (field, offsets, sizes, values, nulls) = view.clone().into_parts();
+            let lengths = sizes.iter().map(|&s| usize::try_from(s).unwrap());
+            let list = ListArray::new(
+                field,
+                arrow::buffer::OffsetBuffer::from_lengths(lengths.clone()),
+                arrow::compute::take(
+                    values.as_ref(),
+                    &UInt32Array::from_iter_values(offsets.iter().zip(lengths).flat_map(
+                        |(&o, len)| {
+                            let o = u32::try_from(o).unwrap();
+                            o..o + u32::try_from(len).unwrap()
+                        },
+                    )),
+                    None,
+                )
+                .unwrap(),
+                nulls,
+            );
+            return Arc::new(list);
+        }
+        let indices = UInt32Array::from_iter_values(0..u32::try_from(array.len()).unwrap());
+        arrow::compute::take(array.as_ref(), &indices, None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_serialize_random_slices_match_compact_copies() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for (type_, array) in fixtures(&mut rng) {
+            for _ in 0..CASES {
+                let offset = rng.below(ROWS + 1);
+                let len = rng.below(ROWS - offset + 1);
+                let sliced = array.slice(offset, len);
+                let compacted = compact(&sliced);
+                let data_type = array.data_type();
+                let context = format!("{type_} as {data_type:?} at {offset}..{}", offset + len);
+
+                let mut expected = Vec::new();
+                let mut state = SerializerState::default();
+                type_
+                    .serialize(&mut expected, &compacted, compacted.data_type(), &mut state)
+                    .unwrap();
+
+                let mut actual = Vec::new();
+                let mut state = SerializerState::default();
+                type_.serialize(&mut actual, &sliced, data_type, &mut state).unwrap();
+                assert_eq!(actual, expected, "sync: {context}");
+
+                let mut actual = Vec::new();
+                let mut state = SerializerState::default();
+                type_.serialize_async(&mut actual, &sliced, data_type, &mut state).await.unwrap();
+                assert_eq!(actual, expected, "async: {context}");
+            }
+        }
+    }
+}
@@ -19,6 +19,9 @@
 /// - `StringArray`: Maps strings to indices.
 /// - `StringViewArray`: Maps strings to indices.
 ///
+/// String and binary labels are resolved for the whole array before any index is written, and
+/// invalid labels are reported together with the rows they appear in.
+///
 /// The serialization is optimized for small enum `pairs` (typically <100 elements) with
 /// efficient validation and minimal allocations (only a small `HashMap` for `StringArray`).
 ///
@@ -45,6 +48,8 @@
 ///     assert_eq!(writer, vec![1, 2, 1]); // Raw indices
 /// }
 /// ```
+use std::collections::HashMap;
+
 use arrow::array::*;
 use arrow::datatypes::*;
 use tokio::io::AsyncWriteExt;
@@ -110,6 +115,58 @@ pub(super) fn serialize<W: ClickHouseBytesWrite>(
     Ok(())
 }
 
+/// The maximum number of invalid labels, and of rows per invalid label, listed in errors.
+const MAX_REPORTED_LABELS: usize = 10;
+
+/// Resolves string labels to their enum values before anything is written.
+///
+/// Null labels resolve to `0`, with nullability handled by `null.rs`. Fails with the invalid
+/// labels and the rows they appear in, rather than at the first invalid label, so bad input can
+/// be located without re-running the insert.
+fn resolve_labels<'a, P: Copy + Default>(
+    labels: impl Iterator<Item = Option<&'a [u8]>>,
+    enum_values: &[(String, P)],
+) -> Result<Vec<P>> {
+    let value_map: HashMap<&[u8], P> =
+        enum_values.iter().map(|(s, v)| (s.as_bytes(), *v)).collect();
+    // Invalid label -> (occurrences, first rows)
+    let mut invalid: HashMap<&[u8], (usize, Vec<usize>)> = HashMap::new();
+    let values = labels
+        .enumerate()
+        .map(|(row, label)| {
+            let Some(label) = label else { return P::default() };
+            value_map.get(label).copied().unwrap_or_else(|| {
+                let (count, rows) = invalid.entry(label).or_default();
+                *count += 1;
+                if rows.len() < MAX_REPORTED_LABELS {
+                    rows.push(row);
+                }
+                P::default()
+            })
+        })
+        .collect::<Vec<_>>();
+    if invalid.is_empty() {
+        return Ok(values);
+    }
+
+    let mut invalid = invalid.into_iter().collect::<Vec<_>>();
+    invalid.sort_unstable_by_key(|(_, (_, rows))| rows[0]);
+    let listed = invalid
+        .iter()
+        .take(MAX_REPORTED_LABELS)
+        .map(|(label, (count, rows))| {
+            let rows = rows.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+            let more = count.saturating_sub(MAX_REPORTED_LABELS);
+            let more = if more > 0 { format!(" and {more} more") } else { String::new() };
+            format!("'{}' (rows {rows}{more})", String::from_utf8_lossy(label))
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let more = invalid.len().saturating_sub(MAX_REPORTED_LABELS);
+    let more = if more > 0 { format!(", and {more} more labels") } else { String::new() };
+    Err(Error::ArrowSerialize(format!("{} labels not in enum: {listed}{more}", invalid.len())))
+}
+
 /// Macro to generate serialization functions for `Enum8` and `Enum16` types.
 ///
 /// Generates functions that serialize `DictionaryArray`, `PrimitiveArray`, or `StringArray` to
@@ -152,6 +209,7 @@ macro_rules! write_enum_values {
         /// Optimized for small `enum_values` (typically <100 elements):
         /// - Uses linear search for `PrimitiveArray` validation, cache-friendly.
         /// - Builds a small `HashMap` for `StringArray` lookups, O(m) allocation where m is `enum_values.len()`.
+        /// - Resolves string labels into a buffer of indices before writing, see `resolve_labels`.
         /// - Writes sequentially to the writer, minimizing allocations and memory fragmentation.
         #[allow(unused_comparisons)]
         #[allow(clippy::too_many_lines)]
@@ -228,44 +286,16 @@ macro_rules! write_enum_values {
 
             $(
             if let Some(array) = column.as_string_opt::<$st>() {
-                let value_map: std::collections::HashMap<&str, $pt> = enum_values
-                    .iter()
-                    .map(|(s, v)| (s.as_str(), *v))
-                    .collect();
-                for i in 0..array.len() {
-                    if array.is_null(i) {
-                        writer.$write_fn(0).await?
-                    } else {
-                        let value = array.value(i);
-                        let key = value_map.get(value).copied().ok_or(
-                            Error::ArrowSerialize(format!(
-                                "String '{value}' not in enum"
-                            ))
-                        )?;
-                        writer.$write_fn(key).await?;
-                    }
+                let labels = array.iter().map(|label| label.map(str::as_bytes));
+                for code in resolve_labels(labels, enum_values)? {
+                    writer.$write_fn(code).await?;
                 }
                 return Ok(());
             }
 
             if let Some(array) = column.as_binary_opt::<$st>() {
-                let value_map: std::collections::HashMap<&str, $pt> = enum_values
-                    .iter()
-                    .map(|(s, v)| (s.as_str(), *v))
-                    .collect();
-                for i in 0..array.len() {
-                    if array.is_null(i) {
-                        writer.$write_fn(0).await?
-                    } else {
-                        let value = array.value(i);
-                        let value_str = ::std::str::from_utf8(value)?;
-                        let key = value_map.get(value_str).copied().ok_or(
-                            Error::ArrowSerialize(format!(
-                                "String '{value_str}' not in enum"
-                            ))
-                        )?;
-                        writer.$write_fn(key).await?;
-                    }
+                for code in resolve_labels(array.iter(), enum_values)? {
+                    writer.$write_fn(code).await?;
                 }
                 return Ok(());
             }
@@ -273,44 +303,16 @@ macro_rules! write_enum_values {
 
             // Views
             if let Some(array) = column.as_string_view_opt() {
-                let value_map: std::collections::HashMap<&str, $pt> = enum_values
-                    .iter()
-                    .map(|(s, v)| (s.as_str(), *v))
-                    .collect();
-                for i in 0..array.len() {
-                    if array.is_null(i) {
-                        writer.$write_fn(0).await?
-                    } else {
-                        let value = array.value(i);
-                        let key = value_map.get(value).copied().ok_or(
-                            Error::ArrowSerialize(format!(
-                                "String '{value}' not in enum"
-                            ))
-                        )?;
-                        writer.$write_fn(key).await?;
-                    }
+                let labels = array.iter().map(|label| label.map(str::as_bytes));
+                for code in resolve_labels(labels, enum_values)? {
+                    writer.$write_fn(code).await?;
                 }
                 return Ok(());
             }
 
             if let Some(array) = column.as_binary_view_opt() {
-                let value_map: std::collections::HashMap<&str, $pt> = enum_values
-                    .iter()
-                    .map(|(s, v)| (s.as_str(), *v))
-                    .collect();
-                for i in 0..array.len() {
-                    if array.is_null(i) {
-                        writer.$write_fn(0).await?
-                    } else {
-                        let value = array.value(i);
-                        let value_str = ::std::str::from_utf8(value)?;
-                        let key = value_map.get(value_str).copied().ok_or(
-                            Error::ArrowSerialize(format!(
-                                "String '{value_str}' not in enum"
-                            ))
-                        )?;
-                        writer.$write_fn(key).await?;
-                    }
+                for code in resolve_labels(array.iter(), enum_values)? {
+                    writer.$write_fn(code).await?;
                 }
                 return Ok(());
             }
@@ -401,44 +403,16 @@ macro_rules! put_enum_values {
 
             $(
             if let Some(array) = column.as_string_opt::<$st>() {
-                let value_map: std::collections::HashMap<&str, $pt> = enum_values
-                    .iter()
-                    .map(|(s, v)| (s.as_str(), *v))
-                    .collect();
-                for i in 0..array.len() {
-                    if array.is_null(i) {
-                        writer.$write_fn(0)
-                    } else {
-                        let value = array.value(i);
-                        let key = value_map.get(value).copied().ok_or(
-                            Error::ArrowSerialize(format!(
-                                "String '{value}' not in enum"
-                            ))
-                        )?;
-                        writer.$write_fn(key);
-                    }
+                let labels = array.iter().map(|label| label.map(str::as_bytes));
+                for code in resolve_labels(labels, enum_values)? {
+                    writer.$write_fn(code);
                 }
                 return Ok(());
             }
 
             if let Some(array) = column.as_binary_opt::<$st>() {
-                let value_map: std::collections::HashMap<&str, $pt> = enum_values
-                    .iter()
-                    .map(|(s, v)| (s.as_str(), *v))
-                    .collect();
-                for i in 0..array.len() {
-                    if array.is_null(i) {
-                        writer.$write_fn(0)
-                    } else {
-                        let value = array.value(i);
-                        let value_str = ::std::str::from_utf8(value)?;
-                        let key = value_map.get(value_str).copied().ok_or(
-                            Error::ArrowSerialize(format!(
-                                "String '{value_str}' not in enum"
-                            ))
-                        )?;
-                        writer.$write_fn(key);
-                    }
+                for code in resolve_labels(array.iter(), enum_values)? {
+                    writer.$write_fn(code);
                 }
                 return Ok(());
             }
@@ -446,44 +420,16 @@ macro_rules! put_enum_values {
 
             // Views
             if let Some(array) = column.as_string_view_opt() {
-                let value_map: std::collections::HashMap<&str, $pt> = enum_values
-                    .iter()
-                    .map(|(s, v)| (s.as_str(), *v))
-                    .collect();
-                for i in 0..array.len() {
-                    if array.is_null(i) {
-                        writer.$write_fn(0)
-                    } else {
-                        let value = array.value(i);
-                        let key = value_map.get(value).copied().ok_or(
-                            Error::ArrowSerialize(format!(
-                                "String '{value}' not in enum"
-                            ))
-                        )?;
-                        writer.$write_fn(key);
-                    }
+                let labels = array.iter().map(|label| label.map(str::as_bytes));
+                for code in resolve_labels(labels, enum_values)? {
+                    writer.$write_fn(code);
                 }
                 return Ok(());
             }
 
             if let Some(array) = column.as_binary_view_opt() {
-                let value_map: std::collections::HashMap<&str, $pt> = enum_values
-                    .iter()
-                    .map(|(s, v)| (s.as_str(), *v))
-                    .collect();
-                for i in 0..array.len() {
-                    if array.is_null(i) {
-                        writer.$write_fn(0)
-                    } else {
-                        let value = array.value(i);
-                        let value_str = ::std::str::from_utf8(value)?;
-                        let key = value_map.get(value_str).copied().ok_or(
-                            Error::ArrowSerialize(format!(
-                                "String '{value_str}' not in enum"
-                            ))
-                        )?;
-                        writer.$write_fn(key);
-                    }
+                for code in resolve_labels(array.iter(), enum_values)? {
+                    writer.$write_fn(code);
                 }
                 return Ok(());
             }
@@ -709,6 +655,37 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_write_enum8_invalid_labels() {
+        let pairs = vec![("a".to_string(), 1_i8), ("b".to_string(), 2_i8)];
+        let array =
+            Arc::new(StringArray::from(vec![Some("a"), Some("x"), None, Some("y"), Some("x")]))
+                as ArrayRef;
+        let mut writer = MockWriter::new();
+        let result = serialize_async(&Type::Enum8(pairs), &mut writer, &array).await;
+        assert!(matches!(
+            result,
+            Err(Error::ArrowSerialize(msg))
+            if msg == "2 labels not in enum: 'x' (rows 1, 4), 'y' (rows 3)"
+        ));
+        // Nothing is written for an array with invalid labels
+        assert!(writer.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_labels_truncates_report() {
+        let pairs = vec![("a".to_string(), 1_i16)];
+        let labels = (0..30).map(|i| format!("bad{}", i % 12)).collect::<Vec<_>>();
+        let error = resolve_labels(labels.iter().map(|l| Some(l.as_bytes())), &pairs).unwrap_err();
+        let msg = error.to_string();
+        assert!(msg.contains("12 labels not in enum: 'bad0' (rows 0, 12, 24), 'bad1'"));
+        assert!(msg.ends_with(", and 2 more labels"));
+
+        let labels = vec![Some(b"bad" as &[u8]); 15];
+        let error = resolve_labels(labels.into_iter(), &pairs).unwrap_err();
+        assert!(error.to_string().contains("(rows 0, 1, 2, 3, 4, 5, 6, 7, 8, 9 and 5 more)"));
+    }
+
     #[tokio::test]
     async fn test_serialize_enum_wrong_type() {
         let mut writer = MockWriter::new();
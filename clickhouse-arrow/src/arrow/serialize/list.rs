@@ -15,9 +15,11 @@
 ///
 /// The main `serialize` function handles four cases:
 /// - `ListArray`: Writes variable-length offsets and serializes inner values.
-/// - `ListViewArray`: Writes variable-length offsets and serializes inner values.
+/// - `ListViewArray`: Writes offsets computed from view sizes and serializes the viewed
+///   values.
 /// - `LargeListArray`: Writes variable-length offsets and serializes inner values.
-/// - `LargeListViewArray`: Writes variable-length offsets and serializes inner values.
+/// - `LargeListViewArray`: Writes offsets computed from view sizes and serializes the viewed
+///   values.
 /// - `FixedSizeListArray`: Writes computed offsets based on fixed length and serializes inner
 ///   values.
 ///
@@ -49,8 +51,11 @@
 ///     .await
 ///     .unwrap();
 /// ```
+use std::sync::Arc;
+
 use arrow::array::*;
-use arrow::datatypes::DataType;
+use arrow::buffer::OffsetBuffer;
+use arrow::datatypes::{ArrowNativeType, DataType};
 use tokio::io::AsyncWriteExt;
 
 use super::ClickHouseArrowSerializer;
@@ -68,7 +73,8 @@ const SMALL_OFFSET_THRESHOLD: usize = 64;
 /// Write i32 offsets as u64 little-endian in bulk (async).
 ///
 /// Uses stack allocation for small arrays, heap for large.
-/// `ClickHouse` expects offsets starting from index 1 (skipping the leading 0).
+/// `ClickHouse` expects offsets starting from index 1 (skipping the leading 0). Offsets are
+/// rebased to the first offset, as the offsets of a sliced array do not start at 0.
 #[inline]
 async fn write_offsets_bulk_i32_async<W: ClickHouseWrite>(
     writer: &mut W,
@@ -79,13 +85,13 @@ async fn write_offsets_bulk_i32_async<W: ClickHouseWrite>(
         return Ok(());
     }
 
-    // Skip first offset (always 0), convert rest to u64 LE bytes
+    // Skip first offset, convert rest (rebased) to u64 LE bytes
     if count <= SMALL_OFFSET_THRESHOLD {
         // Stack-allocated path for small arrays
         let mut buf = [0u8; SMALL_OFFSET_THRESHOLD * 8];
         for (i, &offset) in offsets[1..].iter().enumerate() {
             #[expect(clippy::cast_sign_loss)]
-            let val = offset as u64;
+            let val = (offset - offsets[0]) as u64;
             buf[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
         }
         writer.write_all(&buf[..count * 8]).await?;
@@ -94,7 +100,7 @@ async fn write_offsets_bulk_i32_async<W: ClickHouseWrite>(
         let mut buf = vec![0u8; count * 8];
         for (i, &offset) in offsets[1..].iter().enumerate() {
             #[expect(clippy::cast_sign_loss)]
-            let val = offset as u64;
+            let val = (offset - offsets[0]) as u64;
             buf[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
         }
         writer.write_all(&buf).await?;
@@ -113,12 +119,12 @@ async fn write_offsets_bulk_i64_async<W: ClickHouseWrite>(
         return Ok(());
     }
 
-    // Skip first offset (always 0), convert rest to u64 LE bytes
+    // Skip first offset, convert rest (rebased) to u64 LE bytes
     if count <= SMALL_OFFSET_THRESHOLD {
         let mut buf = [0u8; SMALL_OFFSET_THRESHOLD * 8];
         for (i, &offset) in offsets[1..].iter().enumerate() {
             #[expect(clippy::cast_sign_loss)]
-            let val = offset as u64;
+            let val = (offset - offsets[0]) as u64;
             buf[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
         }
         writer.write_all(&buf[..count * 8]).await?;
@@ -126,7 +132,7 @@ async fn write_offsets_bulk_i64_async<W: ClickHouseWrite>(
         let mut buf = vec![0u8; count * 8];
         for (i, &offset) in offsets[1..].iter().enumerate() {
             #[expect(clippy::cast_sign_loss)]
-            let val = offset as u64;
+            let val = (offset - offsets[0]) as u64;
             buf[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
         }
         writer.write_all(&buf).await?;
@@ -146,7 +152,7 @@ fn write_offsets_bulk_i32<W: ClickHouseBytesWrite>(writer: &mut W, offsets: &[i3
         let mut buf = [0u8; SMALL_OFFSET_THRESHOLD * 8];
         for (i, &offset) in offsets[1..].iter().enumerate() {
             #[expect(clippy::cast_sign_loss)]
-            let val = offset as u64;
+            let val = (offset - offsets[0]) as u64;
             buf[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
         }
         writer.put_slice(&buf[..count * 8]);
@@ -154,7 +160,7 @@ fn write_offsets_bulk_i32<W: ClickHouseBytesWrite>(writer: &mut W, offsets: &[i3
         let mut buf = vec![0u8; count * 8];
         for (i, &offset) in offsets[1..].iter().enumerate() {
             #[expect(clippy::cast_sign_loss)]
-            let val = offset as u64;
+            let val = (offset - offsets[0]) as u64;
             buf[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
         }
         writer.put_slice(&buf);
@@ -173,7 +179,7 @@ fn write_offsets_bulk_i64<W: ClickHouseBytesWrite>(writer: &mut W, offsets: &[i6
         let mut buf = [0u8; SMALL_OFFSET_THRESHOLD * 8];
         for (i, &offset) in offsets[1..].iter().enumerate() {
             #[expect(clippy::cast_sign_loss)]
-            let val = offset as u64;
+            let val = (offset - offsets[0]) as u64;
             buf[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
         }
         writer.put_slice(&buf[..count * 8]);
@@ -181,7 +187,7 @@ fn write_offsets_bulk_i64<W: ClickHouseBytesWrite>(writer: &mut W, offsets: &[i6
         let mut buf = vec![0u8; count * 8];
         for (i, &offset) in offsets[1..].iter().enumerate() {
             #[expect(clippy::cast_sign_loss)]
-            let val = offset as u64;
+            let val = (offset - offsets[0]) as u64;
             buf[i * 8..(i + 1) * 8].copy_from_slice(&val.to_le_bytes());
         }
         writer.put_slice(&buf);
@@ -245,6 +251,108 @@ fn write_fixed_offsets_bulk<W: ClickHouseBytesWrite>(
     }
 }
 
+/// Slice the child values of a list to the range referenced by its offsets.
+///
+/// Slicing a list array only slices its offsets, so the child values of a sliced list still
+/// contain the rows outside of the slice. Returns the values unchanged if no slicing is needed.
+fn sliced_values<O: OffsetSizeTrait>(values: &ArrayRef, offsets: &[O]) -> ArrayRef {
+    let (Some(first), Some(last)) = (offsets.first(), offsets.last()) else {
+        return Arc::clone(values);
+    };
+    let (start, end) = (first.as_usize(), last.as_usize());
+    if start == 0 && end == values.len() {
+        Arc::clone(values)
+    } else {
+        values.slice(start, end - start)
+    }
+}
+
+/// Drop the child values referenced by null lists, as `ClickHouse` writes them as empty arrays.
+///
+/// Arrow allows a null list slot to reference any range of child values, but `ClickHouse` arrays
+/// cannot be null, so those values must not be sent. Returns `None` if no null list references
+/// child values.
+fn empty_null_lists<O: OffsetSizeTrait>(
+    array: &GenericListArray<O>,
+) -> Result<Option<GenericListArray<O>>> {
+    let Some(nulls) = array.nulls().filter(|n| n.null_count() > 0) else {
+        return Ok(None);
+    };
+    let offsets = array.value_offsets();
+    if (0..array.len()).all(|i| nulls.is_valid(i) || offsets[i] == offsets[i + 1]) {
+        return Ok(None);
+    }
+
+    let values = sliced_values(array.values(), offsets);
+    let mut keep = Vec::with_capacity(values.len());
+    let mut lengths = Vec::with_capacity(array.len());
+    for (i, window) in offsets.windows(2).enumerate() {
+        let (valid, len) = (nulls.is_valid(i), (window[1] - window[0]).as_usize());
+        keep.extend(std::iter::repeat_n(valid, len));
+        lengths.push(if valid { len } else { 0 });
+    }
+    let values = arrow::compute::filter(&values, &BooleanArray::from(keep))?;
+    let field = match array.data_type() {
+        DataType::List(f) | DataType::LargeList(f) => Arc::clone(f),
+        dt => return Err(Error::ArrowSerialize(format!("Expected List, got {dt:?}"))),
+    };
+    Ok(Some(GenericListArray::try_new(
+        field,
+        OffsetBuffer::from_lengths(lengths),
+        values,
+        Some(nulls.clone()),
+    )?))
+}
+
+/// Convert a `ListViewArray` or `LargeListViewArray` into `ClickHouse` offsets and values.
+///
+/// Returns `None` if `values` is not a list view. Offsets are the cumulative view sizes encoded as
+/// u64 little-endian. If the views are contiguous (the common case, including slices of arrays
+/// built from lists), the child values are sliced without copying, otherwise the referenced values
+/// are gathered in view order.
+fn list_view_parts(values: &ArrayRef) -> Result<Option<(Vec<u8>, ArrayRef)>> {
+    fn parts<O: OffsetSizeTrait>(array: &GenericListViewArray<O>) -> Result<(Vec<u8>, ArrayRef)> {
+        // Null views are written as empty arrays, whatever values they reference
+        let ranges = array
+            .value_offsets()
+            .iter()
+            .zip(array.value_sizes())
+            .enumerate()
+            .map(|(i, (offset, size))| {
+                (offset.as_usize(), if array.is_null(i) { 0 } else { size.as_usize() })
+            })
+            .collect::<Vec<_>>();
+
+        let mut offsets = Vec::with_capacity(ranges.len() * 8);
+        let mut total = 0_u64;
+        for &(_, size) in &ranges {
+            total += size as u64;
+            offsets.extend_from_slice(&total.to_le_bytes());
+        }
+
+        let start = ranges.first().map_or(0, |&(offset, _)| offset);
+        let contiguous = ranges.windows(2).all(|w| w[0].0 + w[0].1 == w[1].0);
+        let values = if contiguous {
+            let end = ranges.last().map_or(start, |&(offset, size)| offset + size);
+            array.values().slice(start, end - start)
+        } else {
+            let indices = UInt64Array::from_iter_values(
+                ranges.iter().flat_map(|&(offset, size)| offset as u64..(offset + size) as u64),
+            );
+            arrow::compute::take(array.values(), &indices, None)?
+        };
+        Ok((offsets, values))
+    }
+
+    if let Some(array) = values.as_any().downcast_ref::<ListViewArray>() {
+        return parts(array).map(Some);
+    }
+    if let Some(array) = values.as_any().downcast_ref::<LargeListViewArray>() {
+        return parts(array).map(Some);
+    }
+    Ok(None)
+}
+
 /// Extracts the inner `Field` from a `List`, `ListView`, `LargeList`, `LargeListView`, or
 /// `FixedSizeList` data type.
 ///
@@ -298,14 +406,16 @@ pub(super) async fn serialize_async<W: ClickHouseWrite>(
     // Unwrap the inner type
     let inner_type = type_hint.strip_null().unwrap_array()?;
 
-    // ListArray and ListViewArray use i32 offsets
+    // ListArray uses i32 offsets
     macro_rules! write_list_array_i32 {
         ($( $array_ty:ty ),* $(,)?) => {{
             $(
             if let Some(array) = values.as_any().downcast_ref::<$array_ty>() {
+                let emptied = empty_null_lists(array)?;
+                let array = emptied.as_ref().unwrap_or(array);
                 let inner_dt = unwrap_array_data_type(data_type)?;
                 let offsets = array.value_offsets();
-                let values = array.values();
+                let values = &sliced_values(array.values(), offsets);
 
                 // Bulk write offsets (v0.4.1 optimization)
                 // Convert i32 -> u64 in batch instead of per-value writes
@@ -320,14 +430,16 @@ pub(super) async fn serialize_async<W: ClickHouseWrite>(
         }}
     }
 
-    // LargeListArray and LargeListViewArray use i64 offsets
+    // LargeListArray uses i64 offsets
     macro_rules! write_list_array_i64 {
         ($( $array_ty:ty ),* $(,)?) => {{
             $(
             if let Some(array) = values.as_any().downcast_ref::<$array_ty>() {
+                let emptied = empty_null_lists(array)?;
+                let array = emptied.as_ref().unwrap_or(array);
                 let inner_dt = unwrap_array_data_type(data_type)?;
                 let offsets = array.value_offsets();
-                let values = array.values();
+                let values = &sliced_values(array.values(), offsets);
 
                 // Bulk write offsets (v0.4.1 optimization)
                 // Convert i64 -> u64 in batch instead of per-value writes
@@ -343,10 +455,18 @@ pub(super) async fn serialize_async<W: ClickHouseWrite>(
     }
 
     // i32 offset arrays
-    write_list_array_i32!(ListArray, ListViewArray);
+    write_list_array_i32!(ListArray);
 
     // i64 offset arrays
-    write_list_array_i64!(LargeListArray, LargeListViewArray);
+    write_list_array_i64!(LargeListArray);
+
+    // List views - offsets are rebuilt from sizes, as views may overlap or be out of order
+    if let Some((offsets, values)) = list_view_parts(values)? {
+        let inner_dt = unwrap_array_data_type(data_type)?;
+        writer.write_all(&offsets).await?;
+        inner_type.serialize_async(writer, &values, inner_dt, state).await?;
+        return Ok(());
+    }
 
     // FixedSizeListArray - computed offsets
     if let Some(array) = values.as_any().downcast_ref::<FixedSizeListArray>() {
@@ -380,14 +500,16 @@ pub(super) fn serialize<W: ClickHouseBytesWrite>(
     // Unwrap the inner type
     let inner_type = type_hint.strip_null().unwrap_array()?;
 
-    // i32 offset arrays (ListArray, ListViewArray)
+    // i32 offset arrays (ListArray)
     macro_rules! put_list_array_i32 {
         ($( $array_ty:ty ),* $(,)?) => {{
             $(
             if let Some(array) = values.as_any().downcast_ref::<$array_ty>() {
+                let emptied = empty_null_lists(array)?;
+                let array = emptied.as_ref().unwrap_or(array);
                 let inner_dt = unwrap_array_data_type(data_type)?;
                 let offsets = array.value_offsets();
-                let values = array.values();
+                let values = &sliced_values(array.values(), offsets);
 
                 // Bulk write offsets (v0.4.1 optimization)
                 let offsets_slice: &[i32] = offsets.as_ref();
@@ -401,14 +523,16 @@ pub(super) fn serialize<W: ClickHouseBytesWrite>(
         }}
     }
 
-    // i64 offset arrays (LargeListArray, LargeListViewArray)
+    // i64 offset arrays (LargeListArray)
     macro_rules! put_list_array_i64 {
         ($( $array_ty:ty ),* $(,)?) => {{
             $(
             if let Some(array) = values.as_any().downcast_ref::<$array_ty>() {
+                let emptied = empty_null_lists(array)?;
+                let array = emptied.as_ref().unwrap_or(array);
                 let inner_dt = unwrap_array_data_type(data_type)?;
                 let offsets = array.value_offsets();
-                let values = array.values();
+                let values = &sliced_values(array.values(), offsets);
 
                 // Bulk write offsets (v0.4.1 optimization)
                 let offsets_slice: &[i64] = offsets.as_ref();
@@ -423,10 +547,18 @@ pub(super) fn serialize<W: ClickHouseBytesWrite>(
     }
 
     // i32 offset arrays
-    put_list_array_i32!(ListArray, ListViewArray);
+    put_list_array_i32!(ListArray);
 
     // i64 offset arrays
-    put_list_array_i64!(LargeListArray, LargeListViewArray);
+    put_list_array_i64!(LargeListArray);
+
+    // List views - offsets are rebuilt from sizes, as views may overlap or be out of order
+    if let Some((offsets, values)) = list_view_parts(values)? {
+        let inner_dt = unwrap_array_data_type(data_type)?;
+        writer.put_slice(&offsets);
+        inner_type.serialize(writer, &values, inner_dt, state)?;
+        return Ok(());
+    }
 
     // FixedSizeListArray - computed offsets
     if let Some(array) = values.as_any().downcast_ref::<FixedSizeListArray>() {
@@ -509,6 +641,32 @@ mod tests {
         test_type_serializer(expected, &type_, &field, &array).await;
     }
 
+    #[tokio::test]
+    async fn test_serialize_list_sliced() {
+        let type_ = wrap_array(Type::Int32);
+        let inner_field = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, DataType::Int32, false));
+        let field = Arc::new(Field::new("list", DataType::List(Arc::clone(&inner_field)), false));
+        let array = ListArray::new(
+            inner_field,
+            OffsetBuffer::new(vec![0, 2, 3, 5].into()),
+            Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5])) as ArrayRef,
+            None,
+        );
+
+        // [[3], [4, 5]]
+        let sliced = Arc::new(array.slice(1, 2)) as ArrayRef;
+        let expected = vec![
+            // Offsets: [1, 3] rebased to the slice
+            1, 0, 0, 0, 0, 0, 0, 0, // 1
+            3, 0, 0, 0, 0, 0, 0, 0, // 3
+            // Values: [3, 4, 5] only
+            3, 0, 0, 0, // 3
+            4, 0, 0, 0, // 4
+            5, 0, 0, 0, // 5
+        ];
+        test_type_serializer(expected, &type_, &field, &sliced).await;
+    }
+
     #[tokio::test]
     async fn test_serialize_list_nullable_int32() {
         let type_ = wrap_array(Type::Nullable(Box::new(Type::Int32)));
@@ -787,6 +945,32 @@ mod tests_sync {
         test_type_serializer(expected, &type_, &field, &array);
     }
 
+    #[test]
+    fn test_serialize_list_sliced() {
+        let type_ = wrap_array(Type::Int32);
+        let inner_field = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, DataType::Int32, false));
+        let field = Arc::new(Field::new("list", DataType::List(Arc::clone(&inner_field)), false));
+        let array = ListArray::new(
+            inner_field,
+            OffsetBuffer::new(vec![0, 2, 3, 5].into()),
+            Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5])) as ArrayRef,
+            None,
+        );
+
+        // [[3], [4, 5]]
+        let sliced = Arc::new(array.slice(1, 2)) as ArrayRef;
+        let expected = vec![
+            // Offsets: [1, 3] rebased to the slice
+            1, 0, 0, 0, 0, 0, 0, 0, // 1
+            3, 0, 0, 0, 0, 0, 0, 0, // 3
+            // Values: [3, 4, 5] only
+            3, 0, 0, 0, // 3
+            4, 0, 0, 0, // 4
+            5, 0, 0, 0, // 5
+        ];
+        test_type_serializer(expected, &type_, &field, &sliced);
+    }
+
     #[test]
     fn test_serialize_list_nullable_int32() {
         let type_ = wrap_array(Type::Nullable(Box::new(Type::Int32)));
@@ -1,8 +1,9 @@
 /// Serialization logic for `ClickHouse` `LowCardinality` types from Arrow arrays.
 ///
 /// This module provides functions to serialize Arrow `DictionaryArray` (with numeric keys) or
-/// string-like arrays (`Utf8`, `LargeUtf8`, `Utf8View`) into `ClickHouse`'s native format for
-/// `LowCardinality` types.
+/// dense string-like arrays (`Utf8`, `LargeUtf8`, `Utf8View`, `Binary`, `FixedSizeBinary`,
+/// ...) into `ClickHouse`'s native format for `LowCardinality` types. For dense arrays the
+/// dictionary is built client-side per block, so repetitive columns are not sent in full.
 ///
 /// The `serialize` function dispatches to `write_values` for `DictionaryArray` or
 /// `write_string_values` for string-like arrays. The native format includes:
@@ -138,8 +139,10 @@ pub(super) async fn serialize_async<W: ClickHouseWrite>(
             | DataType::Utf8View
             | DataType::Binary
             | DataType::LargeBinary
-            | DataType::BinaryView => {
-                write_string_values(writer, values, type_hint.is_nullable(), state).await?;
+            | DataType::BinaryView
+            | DataType::FixedSizeBinary(_) => {
+                let nullable = type_hint.is_nullable() || inner.is_nullable();
+                write_string_values(writer, inner, values, nullable, state).await?;
             }
             _ => {
                 return Err(Error::ArrowSerialize(format!(
@@ -200,8 +203,10 @@ pub(super) fn serialize<W: ClickHouseBytesWrite>(
             | DataType::Utf8View
             | DataType::Binary
             | DataType::LargeBinary
-            | DataType::BinaryView => {
-                put_string_values(writer, values, type_hint.is_nullable(), state)?;
+            | DataType::BinaryView
+            | DataType::FixedSizeBinary(_) => {
+                let nullable = type_hint.is_nullable() || inner.is_nullable();
+                put_string_values(writer, inner, values, nullable, state)?;
             }
             _ => {
                 return Err(Error::ArrowSerialize(format!(
@@ -444,132 +449,140 @@ fn put_values<W: ClickHouseBytesWrite, K: ArrowDictionaryKeyType>(
     Ok(())
 }
 
-/// Serializes a string-like array (`Utf8`, `LargeUtf8`, `Utf8View`) to `ClickHouse`’s format
+/// A `LowCardinality` dictionary built client-side from a dense (non-dictionary) array.
+///
+/// Repetitive columns are sent as unique values plus narrow keys rather than in full, so the
+/// server receives the same layout it would produce itself.
+struct DenseDictionary {
+    /// Unique values, preceded by the default value when the column is nullable.
+    values:      ArrayRef,
+    /// Data type used to serialize `values` with the inner type.
+    values_type: DataType,
+    /// Index into `values` of each row.
+    keys:        Vec<usize>,
+}
+
+impl DenseDictionary {
+    /// Build the dictionary of a string-like or fixed size binary array.
+    ///
+    /// Values are borrowed from the source array while deduplicating, so only the unique values
+    /// are copied. If `nullable`, index 0 is reserved for nulls, mirroring the dictionary path.
+    ///
+    /// # Errors
+    /// - Returns `ArrowSerialize` if the array is not string-like or fixed size binary.
+    fn try_new(values: &ArrayRef, nullable: bool) -> Result<Self> {
+        let nullable = nullable || values.null_count() > 0;
+        // Default value of fixed size binary dictionaries
+        let zeroes = match values.data_type() {
+            DataType::FixedSizeBinary(size) => {
+                vec![0_u8; usize::try_from(*size).unwrap_or_default()]
+            }
+            _ => Vec::new(),
+        };
+        let mut dict: Vec<&[u8]> = Vec::with_capacity(64.min(values.len()));
+        let mut index: HashMap<&[u8], usize> = HashMap::with_capacity(64.min(values.len()));
+        let mut keys = Vec::with_capacity(values.len());
+
+        macro_rules! collect_keys {
+            ($array:expr, $default:expr) => {{
+                let array = $array;
+                // Pre-seed with the default value, aka null
+                if nullable {
+                    dict.push($default);
+                }
+                for i in 0..array.len() {
+                    if array.is_null(i) {
+                        keys.push(0);
+                        continue;
+                    }
+                    let value: &[u8] = array.value(i).as_ref();
+                    let next = dict.len();
+                    let key = *index.entry(value).or_insert_with(|| {
+                        dict.push(value);
+                        next
+                    });
+                    keys.push(key);
+                }
+            }};
+        }
+
+        match values.data_type() {
+            DataType::Utf8 => collect_keys!(values.as_string::<i32>(), &[]),
+            DataType::LargeUtf8 => collect_keys!(values.as_string::<i64>(), &[]),
+            DataType::Utf8View => collect_keys!(values.as_string_view(), &[]),
+            DataType::Binary => collect_keys!(values.as_binary::<i32>(), &[]),
+            DataType::LargeBinary => collect_keys!(values.as_binary::<i64>(), &[]),
+            DataType::BinaryView => collect_keys!(values.as_binary_view(), &[]),
+            DataType::FixedSizeBinary(size) => {
+                collect_keys!(values.as_fixed_size_binary(), zeroes.as_slice());
+                let array = FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                    dict.into_iter().map(Some),
+                    *size,
+                )?;
+                let values_type = DataType::FixedSizeBinary(*size);
+                return Ok(Self { values: Arc::new(array), values_type, keys });
+            }
+            dt => {
+                return Err(Error::ArrowSerialize(format!("Expected string-like array, got {dt}")));
+            }
+        }
+
+        let values = Arc::new(BinaryArray::from_iter_values(dict)) as ArrayRef;
+        Ok(Self { values, values_type: DataType::Binary, keys })
+    }
+
+    /// Flags describing the narrowest key type that can index the dictionary.
+    fn flags(&self) -> u64 {
+        let dict_size = self.values.len();
+        (if dict_size > u32::MAX as usize {
+            TUINT64
+        } else if dict_size > u16::MAX as usize {
+            TUINT32
+        } else if dict_size > u8::MAX as usize {
+            TUINT16
+        } else {
+            TUINT8
+        }) | HAS_ADDITIONAL_KEYS_BIT
+    }
+}
+
+/// Serializes a dense string-like or fixed size binary array to `ClickHouse`’s `LowCardinality`
+/// format, building the dictionary client-side.
 ///
 /// # Arguments
 /// - `writer`: The async writer to serialize to.
-/// - `values`: The string-like array containing the data (`StringArray`, `LargeStringArray`, or
-///   `StringViewArray`).
-/// - `nullable`: Whether the values are nullable.
+/// - `inner_type`: The `ClickHouse` type of the dictionary values (e.g., `String`).
+/// - `values`: The dense array containing the data.
+/// - `nullable`: Whether the column is nullable.
 /// - `state`: A mutable `SerializerState` for serialization context.
 ///
-/// # Returns
-/// A `Result` indicating success or a `Error` if serialization fails.
-///
-/// Insert a value into a dictionary, returning its index.
-///
-/// Uses O(1) `HashMap` lookup instead of O(n) linear search for efficiency.
-/// Only allocates for new dictionary entries.
-#[inline]
-fn dict_insert_value(
-    value: &[u8],
-    dict: &mut Vec<Vec<u8>>,
-    dict_index: &mut HashMap<Vec<u8>, i32>,
-) -> i32 {
-    // Use get first to avoid allocation for existing keys
-    if let Some(&idx) = dict_index.get(value) {
-        idx
-    } else {
-        #[expect(clippy::cast_possible_wrap)]
-        #[expect(clippy::cast_possible_truncation)]
-        let idx = dict.len() as i32;
-        let owned = value.to_vec();
-        let _ = dict_index.insert(owned.clone(), idx);
-        dict.push(owned);
-        idx
-    }
-}
-
 /// # Errors
 /// - Returns `ArrowSerialize` if the input array is not a string-like type.
 /// - Returns `Io` if writing to the writer fails.
 async fn write_string_values<W: ClickHouseWrite>(
     writer: &mut W,
+    inner_type: &Type,
     values: &ArrayRef,
     nullable: bool,
     state: &mut SerializerState,
 ) -> Result<()> {
-    // Build dictionary with O(1) lookup using HashMap (keys are owned Vec<u8>)
-    let mut dict: Vec<Vec<u8>> = Vec::with_capacity(64.min(values.len()));
-    let mut dict_index: HashMap<Vec<u8>, i32> = HashMap::with_capacity(64.min(values.len()));
-    let mut keys = Vec::with_capacity(values.len());
-    let nullable = values.null_count() > 0 || nullable;
-
-    // Pre-seed with an empty string, aka default value
-    if nullable {
-        dict.push(Vec::new());
-        let _ = dict_index.insert(Vec::new(), 0);
-    }
-
-    macro_rules! handle_string_array {
-        ($array_ty:ty) => {{
-            let array = values.as_any().downcast_ref::<$array_ty>().expect("Verified below");
-            for i in 0..array.len() {
-                if array.is_null(i) {
-                    debug_assert!(nullable, "Null encountered in non-nullable array");
-                    keys.push(0);
-                } else {
-                    let value = array.value(i);
-                    let index = dict_insert_value(value.as_ref(), &mut dict, &mut dict_index);
-                    keys.push(index);
-                };
-            }
-        }};
-    }
-
-    macro_rules! handle_binary_array {
-        ($array_ty:ty) => {{
-            let array = values.as_any().downcast_ref::<$array_ty>().expect("Verified below");
-            for i in 0..array.len() {
-                if array.is_null(i) {
-                    debug_assert!(nullable, "Null encountered in non-nullable array");
-                    keys.push(0);
-                } else {
-                    let value = array.value(i);
-                    let index = dict_insert_value(value, &mut dict, &mut dict_index);
-                    keys.push(index);
-                };
-            }
-        }};
-    }
-
-    match values.data_type() {
-        DataType::Utf8 => handle_string_array!(StringArray),
-        DataType::LargeUtf8 => handle_string_array!(LargeStringArray),
-        DataType::Utf8View => handle_string_array!(StringViewArray),
-        DataType::Binary => handle_binary_array!(BinaryArray),
-        DataType::BinaryView => handle_binary_array!(BinaryViewArray),
-        DataType::LargeBinary => handle_binary_array!(LargeBinaryArray),
-        dt => {
-            return Err(Error::ArrowSerialize(format!("Expected string-like array, got {dt}",)));
-        }
-    }
-
-    let dict_size = dict.len();
-    let flags = (if dict_size > u32::MAX as usize {
-        TUINT64
-    } else if dict_size > u16::MAX as usize {
-        TUINT32
-    } else if dict_size > u8::MAX as usize {
-        TUINT16
-    } else {
-        TUINT8
-    }) | HAS_ADDITIONAL_KEYS_BIT;
+    let dictionary = DenseDictionary::try_new(values, nullable)?;
+    let flags = dictionary.flags();
 
     // Write flags and dictionary size
     writer.write_u64_le(flags).await?;
-    writer.write_u64_le(dict_size as u64).await?;
+    writer.write_u64_le(dictionary.values.len() as u64).await?;
 
     // Write dictionary values
-    let values_array = Arc::new(BinaryArray::from_iter_values(dict)) as ArrayRef;
-    Type::Binary.serialize_async(writer, &values_array, &DataType::Binary, state).await?;
+    dense_values_type(inner_type)
+        .serialize_async(writer, &dictionary.values, &dictionary.values_type, state)
+        .await?;
 
     // Write keys
-    writer.write_u64_le(keys.len() as u64).await?;
+    writer.write_u64_le(dictionary.keys.len() as u64).await?;
 
-    #[expect(clippy::cast_sign_loss)]
     #[expect(clippy::cast_possible_truncation)]
-    for key in keys {
+    for key in dictionary.keys {
         match flags & KEY_TYPE_MASK {
             TUINT64 => writer.write_u64_le(key as u64).await?,
             TUINT32 => writer.write_u32_le(key as u32).await?,
@@ -583,91 +596,31 @@ async fn write_string_values<W: ClickHouseWrite>(
 
 fn put_string_values<W: ClickHouseBytesWrite>(
     writer: &mut W,
+    inner_type: &Type,
     values: &ArrayRef,
     nullable: bool,
     state: &mut SerializerState,
 ) -> Result<()> {
-    // Build dictionary with O(1) lookup using HashMap (keys are owned Vec<u8>)
-    let mut dict: Vec<Vec<u8>> = Vec::with_capacity(64.min(values.len()));
-    let mut dict_index: HashMap<Vec<u8>, i32> = HashMap::with_capacity(64.min(values.len()));
-    let mut keys = Vec::with_capacity(values.len());
-    let nullable = values.null_count() > 0 || nullable;
-
-    // Pre-seed with an empty string, aka default value
-    if nullable {
-        dict.push(Vec::new());
-        let _ = dict_index.insert(Vec::new(), 0);
-    }
-
-    macro_rules! handle_string_array {
-        ($array_ty:ty) => {{
-            let array = values.as_any().downcast_ref::<$array_ty>().expect("Verified below");
-            for i in 0..array.len() {
-                if array.is_null(i) {
-                    debug_assert!(nullable, "Null encountered in non-nullable array");
-                    keys.push(0);
-                } else {
-                    let value = array.value(i);
-                    let index = dict_insert_value(value.as_ref(), &mut dict, &mut dict_index);
-                    keys.push(index);
-                };
-            }
-        }};
-    }
-
-    macro_rules! handle_binary_array {
-        ($array_ty:ty) => {{
-            let array = values.as_any().downcast_ref::<$array_ty>().expect("Verified below");
-            for i in 0..array.len() {
-                if array.is_null(i) {
-                    debug_assert!(nullable, "Null encountered in non-nullable array");
-                    keys.push(0);
-                } else {
-                    let value = array.value(i);
-                    let index = dict_insert_value(value, &mut dict, &mut dict_index);
-                    keys.push(index);
-                };
-            }
-        }};
-    }
-
-    match values.data_type() {
-        DataType::Utf8 => handle_string_array!(StringArray),
-        DataType::LargeUtf8 => handle_string_array!(LargeStringArray),
-        DataType::Utf8View => handle_string_array!(StringViewArray),
-        DataType::Binary => handle_binary_array!(BinaryArray),
-        DataType::BinaryView => handle_binary_array!(BinaryViewArray),
-        DataType::LargeBinary => handle_binary_array!(LargeBinaryArray),
-        dt => {
-            return Err(Error::ArrowSerialize(format!("Expected string-like array, got {dt}",)));
-        }
-    }
-
-    let dict_size = dict.len();
-    let flags = (if dict_size > u32::MAX as usize {
-        TUINT64
-    } else if dict_size > u16::MAX as usize {
-        TUINT32
-    } else if dict_size > u8::MAX as usize {
-        TUINT16
-    } else {
-        TUINT8
-    }) | HAS_ADDITIONAL_KEYS_BIT;
+    let dictionary = DenseDictionary::try_new(values, nullable)?;
+    let flags = dictionary.flags();
 
     // Write flags and dictionary size
     writer.put_u64_le(flags);
-    writer.put_u64_le(dict_size as u64);
+    writer.put_u64_le(dictionary.values.len() as u64);
 
     // Write dictionary values
-    let values_array = Arc::new(BinaryArray::from_iter_values(dict)) as ArrayRef;
-    Type::Binary.serialize(writer, &values_array, &DataType::Binary, state)?;
+    dense_values_type(inner_type).serialize(
+        writer,
+        &dictionary.values,
+        &dictionary.values_type,
+        state,
+    )?;
 
     // Write keys
-    writer.put_u64_le(keys.len() as u64);
+    writer.put_u64_le(dictionary.keys.len() as u64);
 
-    #[expect(clippy::cast_sign_loss)]
     #[expect(clippy::cast_possible_truncation)]
-    for key in keys {
+    for key in dictionary.keys {
         match flags & KEY_TYPE_MASK {
             TUINT64 => writer.put_u64_le(key as u64),
             TUINT32 => writer.put_u32_le(key as u32),
@@ -679,6 +632,16 @@ fn put_string_values<W: ClickHouseBytesWrite>(
     Ok(())
 }
 
+/// The type used to serialize the values of a [`DenseDictionary`].
+///
+/// Dictionary values are never null, nulls are represented by the reserved key 0.
+fn dense_values_type(inner_type: &Type) -> &Type {
+    match inner_type.strip_null() {
+        t @ (Type::FixedSizedString(_) | Type::FixedSizedBinary(_)) => t,
+        _ => &Type::Binary,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -971,6 +934,52 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_serialize_low_cardinality_nullable_inner_reserves_null_key() {
+        // Without nulls in the block, key 0 must still be reserved for nulls
+        let array = Arc::new(StringArray::from(vec!["a", "b", "a"])) as ArrayRef;
+        let expected = vec![
+            0, 2, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | HasAdditionalKeysBit
+            3, 0, 0, 0, 0, 0, 0, 0, // Dict size: 3
+            0, // Dict: "" (null)
+            1, b'a', // Dict: "a" (var_uint length)
+            1, b'b', // Dict: "b" (var_uint length)
+            3, 0, 0, 0, 0, 0, 0, 0, // Key count: 3
+            1, 2, 1, // Keys: [1, 2, 1]
+        ];
+        test_type_serializer(
+            expected,
+            &Type::LowCardinality(Box::new(Type::String.into_nullable())),
+            &DataType::Utf8,
+            &array,
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_serialize_low_cardinality_fixed_string() {
+        let expected = vec![
+            0, 2, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | HasAdditionalKeysBit
+            2, 0, 0, 0, 0, 0, 0, 0, // Dict size: 2
+            b'a', b'b', // Dict: "ab" (fixed length)
+            b'c', 0, // Dict: "c" (zero padded)
+            3, 0, 0, 0, 0, 0, 0, 0, // Key count: 3
+            0, 1, 0, // Keys: [0, 1, 0]
+        ];
+
+        // Dense strings into a fixed length dictionary
+        let array = Arc::new(StringArray::from(vec!["ab", "c", "ab"])) as ArrayRef;
+        let type_ = Type::LowCardinality(Box::new(Type::FixedSizedString(2)));
+        test_type_serializer(expected.clone(), &type_, &DataType::Utf8, &array).await;
+
+        // Fixed size binary arrays
+        let array = Arc::new(
+            FixedSizeBinaryArray::try_from_iter(vec![b"ab", b"c\0", b"ab"].into_iter()).unwrap(),
+        ) as ArrayRef;
+        let type_ = Type::LowCardinality(Box::new(Type::FixedSizedBinary(2)));
+        test_type_serializer(expected, &type_, &DataType::FixedSizeBinary(2), &array).await;
+    }
+
     #[tokio::test]
     async fn test_serialize_low_cardinality_nullable_variations() {
         async fn run_test(type_: &Type, dt: &DataType, array: &ArrayRef) {
@@ -1404,6 +1413,45 @@ mod tests_sync {
         );
     }
 
+    #[test]
+    fn test_serialize_low_cardinality_nullable_inner_reserves_null_key_sync() {
+        let array = Arc::new(StringArray::from(vec!["a", "b", "a"])) as ArrayRef;
+        let expected = vec![
+            0, 2, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | HasAdditionalKeysBit
+            3, 0, 0, 0, 0, 0, 0, 0, // Dict size: 3
+            0, // Dict: "" (null)
+            1, b'a', // Dict: "a" (var_uint length)
+            1, b'b', // Dict: "b" (var_uint length)
+            3, 0, 0, 0, 0, 0, 0, 0, // Key count: 3
+            1, 2, 1, // Keys: [1, 2, 1]
+        ];
+        test_type_serializer_sync(
+            expected,
+            &Type::LowCardinality(Box::new(Type::String.into_nullable())),
+            &DataType::Utf8,
+            &array,
+        );
+    }
+
+    #[test]
+    fn test_serialize_low_cardinality_fixed_string_sync() {
+        let array = Arc::new(StringArray::from(vec!["ab", "c", "ab"])) as ArrayRef;
+        let expected = vec![
+            0, 2, 0, 0, 0, 0, 0, 0, // Flags: UInt8 | HasAdditionalKeysBit
+            2, 0, 0, 0, 0, 0, 0, 0, // Dict size: 2
+            b'a', b'b', // Dict: "ab" (fixed length)
+            b'c', 0, // Dict: "c" (zero padded)
+            3, 0, 0, 0, 0, 0, 0, 0, // Key count: 3
+            0, 1, 0, // Keys: [0, 1, 0]
+        ];
+        test_type_serializer_sync(
+            expected,
+            &Type::LowCardinality(Box::new(Type::FixedSizedString(2))),
+            &DataType::Utf8,
+            &array,
+        );
+    }
+
     #[test]
     fn test_serialize_low_cardinality_nullable_variations_sync() {
         fn run_test(type_: &Type, dt: &DataType, array: &ArrayRef) {
@@ -1,8 +1,12 @@
-use arrow::array::{Array, ArrayRef, MapArray};
-use arrow::datatypes::DataType;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, FixedSizeBinaryArray, MapArray, StructArray};
+use arrow::compute::{CastOptions, can_cast_types, cast, cast_with_options};
+use arrow::datatypes::{DataType, TimeUnit};
 use tokio::io::AsyncWriteExt;
 
 use super::ClickHouseArrowSerializer;
+use crate::arrow::types::ch_to_arrow_type;
 use crate::formats::SerializerState;
 use crate::io::{ClickHouseBytesWrite, ClickHouseWrite};
 use crate::{Error, Result, Type};
@@ -70,10 +74,12 @@ pub(super) async fn serialize_async<W: ClickHouseWrite>(
         writer.write_u64_le(total_length).await?;
     }
 
-    // Serialize keys and values
-    let keys = map_array.keys();
-    let values = map_array.values();
-    key_type.serialize_async(writer, keys, fields[0].data_type(), state).await?;
+    // Serialize keys and values, limited to the entries referenced by a sliced map
+    let entries = sliced_entries(map_array);
+    let coerced = coerce_keys(key_type, entries.column(0))?;
+    let keys = coerced.as_ref().unwrap_or(entries.column(0));
+    let values = entries.column(1);
+    key_type.serialize_async(writer, keys, keys.data_type(), state).await?;
     value_type.serialize_async(writer, values, fields[1].data_type(), state).await?;
 
     Ok(())
@@ -120,21 +126,103 @@ pub(super) fn serialize<W: ClickHouseBytesWrite>(
         writer.put_u64_le(total_length);
     }
 
-    // Serialize keys and values
-    let keys = map_array.keys();
-    let values = map_array.values();
-    key_type.serialize(writer, keys, fields[0].data_type(), state)?;
+    // Serialize keys and values, limited to the entries referenced by a sliced map
+    let entries = sliced_entries(map_array);
+    let coerced = coerce_keys(key_type, entries.column(0))?;
+    let keys = coerced.as_ref().unwrap_or(entries.column(0));
+    let values = entries.column(1);
+    key_type.serialize(writer, keys, keys.data_type(), state)?;
     value_type.serialize(writer, values, fields[1].data_type(), state)?;
 
     Ok(())
 }
 
+/// Slice the entries of a map to the range referenced by its offsets.
+///
+/// Slicing a `MapArray` only slices its offsets, so the entries of a sliced map still contain the
+/// key-value pairs outside of the slice.
+fn sliced_entries(map_array: &MapArray) -> StructArray {
+    let offsets = map_array.value_offsets();
+    #[expect(clippy::cast_sign_loss)]
+    let (start, end) = (offsets[0] as usize, offsets[offsets.len() - 1] as usize);
+    let entries = map_array.entries();
+    if start == 0 && end == entries.len() {
+        entries.clone()
+    } else {
+        entries.slice(start, end - start)
+    }
+}
+
+/// Convert map keys to the Arrow type expected for a numeric, date, or `UUID` key type.
+///
+/// Map keys are often typed by whatever produced them rather than by the table, ie `Int64` keys
+/// from a Python dict bound for a `Map(UInt16, _)` column, or `UUID` keys as strings. Keys of other
+/// types, including all string-like keys, are written as is.
+///
+/// # Errors
+/// Returns `ArrowSerialize` if a key does not fit the key type or is not a valid `UUID`.
+fn coerce_keys(key_type: &Type, keys: &ArrayRef) -> Result<Option<ArrayRef>> {
+    let from = keys.data_type();
+    let target = match key_type.strip_null() {
+        Type::Uuid if matches!(from, DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View) => {
+            return parse_uuid_keys(keys).map(Some);
+        }
+        t @ (Type::Int8
+        | Type::Int16
+        | Type::Int32
+        | Type::Int64
+        | Type::UInt8
+        | Type::UInt16
+        | Type::UInt32
+        | Type::UInt64
+        | Type::Float32
+        | Type::Float64
+        | Type::Date
+        | Type::Date32) => ch_to_arrow_type(t, None)?.0,
+        // `DateTime64` accepts timestamps of any unit, `DateTime` only seconds. Timezones are kept
+        // so that instants are not shifted.
+        Type::DateTime(_) => match from {
+            DataType::Timestamp(_, tz) => DataType::Timestamp(TimeUnit::Second, tz.clone()),
+            _ => DataType::Timestamp(TimeUnit::Second, None),
+        },
+        _ => return Ok(None),
+    };
+    if from == &target || !can_cast_types(from, &target) {
+        return Ok(None);
+    }
+    let options = CastOptions { safe: false, ..Default::default() };
+    cast_with_options(keys, &target, &options).map(Some).map_err(|error| {
+        Error::ArrowSerialize(format!("Cannot convert {from} map keys to {key_type}: {error}"))
+    })
+}
+
+/// Parse string map keys into the 16 byte `UUID`s read back for `UUID` keys.
+fn parse_uuid_keys(keys: &ArrayRef) -> Result<ArrayRef> {
+    let strings = cast(keys, &DataType::Utf8)?;
+    let uuids = strings
+        .as_string::<i32>()
+        .iter()
+        .map(|key| {
+            key.map(|key| {
+                uuid::Uuid::parse_str(key).map(|uuid| uuid.into_bytes()).map_err(|error| {
+                    Error::ArrowSerialize(format!("Invalid UUID map key '{key}': {error}"))
+                })
+            })
+            .transpose()
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Arc::new(FixedSizeBinaryArray::try_from_sparse_iter_with_size(uuids.into_iter(), 16)?))
+}
+
 #[cfg(test)]
 mod tests {
     #![expect(clippy::clone_on_ref_ptr)]
     use std::sync::Arc;
 
-    use arrow::array::{ArrayRef, Float64Array, Int32Array, MapArray, StringArray, StructArray};
+    use arrow::array::{
+        ArrayRef, Date64Array, Float64Array, Int32Array, Int64Array, MapArray, StringArray,
+        StructArray,
+    };
     use arrow::buffer::OffsetBuffer;
     use arrow::datatypes::{DataType, Field, Fields};
 
@@ -149,6 +237,22 @@ mod tests {
         Type::Map(Box::new(key_type), Box::new(value_type))
     }
 
+    fn build_map(keys: ArrayRef, values: ArrayRef, offsets: Vec<i32>) -> ArrayRef {
+        let key_field = Field::new(STRUCT_KEY_FIELD_NAME, keys.data_type().clone(), false);
+        let value_field = Field::new(STRUCT_VALUE_FIELD_NAME, values.data_type().clone(), false);
+        let fields = Fields::from(vec![key_field, value_field]);
+        let entries = StructArray::new(fields.clone(), vec![keys, values], None);
+        let field = Arc::new(Field::new(MAP_FIELD_NAME, DataType::Struct(fields), false));
+        let offsets = OffsetBuffer::new(offsets.into());
+        Arc::new(MapArray::try_new(field, offsets, entries, None, false).unwrap())
+    }
+
+    fn uuid_key_bytes(uuid: &str) -> Vec<u8> {
+        // `ClickHouse` layout, halves swapped
+        let bytes = uuid::Uuid::parse_str(uuid).unwrap().into_bytes();
+        [&bytes[8..], &bytes[..8]].concat()
+    }
+
     #[tokio::test]
     async fn test_serialize_map_int32_string() {
         let key_field = Arc::new(Field::new(STRUCT_KEY_FIELD_NAME, DataType::Int32, false));
@@ -190,6 +294,43 @@ mod tests {
         assert_eq!(writer, expected);
     }
 
+    #[tokio::test]
+    async fn test_serialize_map_sliced() {
+        let key_field = Arc::new(Field::new(STRUCT_KEY_FIELD_NAME, DataType::Int32, false));
+        let value_field = Arc::new(Field::new(STRUCT_VALUE_FIELD_NAME, DataType::Utf8, false));
+        let fields = Fields::from(vec![key_field, value_field]);
+
+        let keys = Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef;
+        let values = Arc::new(StringArray::from(vec!["a", "b", "c"])) as ArrayRef;
+        let entries = StructArray::new(fields.clone(), vec![keys, values], None);
+        let field = Arc::new(Field::new(MAP_FIELD_NAME, DataType::Struct(fields.clone()), false));
+        let offsets = OffsetBuffer::new(vec![0, 2, 2, 3].into());
+        let map_array = MapArray::try_new(field, offsets, entries, None, false).unwrap();
+
+        // [{}, {3:"c"}]
+        let sliced = Arc::new(map_array.slice(1, 2)) as ArrayRef;
+
+        let mut writer = MockWriter::new();
+        let mut state = SerializerState::default();
+
+        serialize_async(
+            &wrap_map_type(Type::Int32, Type::String),
+            &mut writer,
+            &sliced,
+            sliced.data_type(),
+            &mut state,
+        )
+        .await
+        .unwrap();
+        let expected = vec![
+            // Offsets: [0, 1]
+            0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, // Keys: [3]
+            3, 0, 0, 0, // Values: ["c"]
+            1, 99,
+        ];
+        assert_eq!(writer, expected);
+    }
+
     #[tokio::test]
     async fn test_serialize_map_empty() {
         let key_field = Arc::new(Field::new(STRUCT_KEY_FIELD_NAME, DataType::Int32, false));
@@ -435,6 +576,126 @@ mod tests {
             if msg.contains("Expected Map data type for MapArray")
         ));
     }
+    #[tokio::test]
+    async fn test_serialize_map_coerced_integer_keys() {
+        // Int64 keys, ie from a Python dict, into a `Map(UInt16, String)` column
+        let keys = Arc::new(Int64Array::from(vec![1, 2, 300])) as ArrayRef;
+        let values = Arc::new(StringArray::from(vec!["a", "b", "c"])) as ArrayRef;
+        let map_array = build_map(keys, values, vec![0, 2, 3]);
+
+        let mut writer = MockWriter::new();
+        let mut state = SerializerState::default();
+        serialize_async(
+            &wrap_map_type(Type::UInt16, Type::String),
+            &mut writer,
+            &map_array,
+            map_array.data_type(),
+            &mut state,
+        )
+        .await
+        .unwrap();
+        let expected = vec![
+            // Offsets: [2, 3]
+            2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, // Keys: [1, 2, 300]
+            1, 0, 2, 0, 44, 1, // Values: ["a", "b", "c"]
+            1, 97, 1, 98, 1, 99,
+        ];
+        assert_eq!(writer, expected);
+
+        // Keys out of range of the key type are rejected
+        let keys = Arc::new(Int64Array::from(vec![70_000])) as ArrayRef;
+        let values = Arc::new(StringArray::from(vec!["a"])) as ArrayRef;
+        let map_array = build_map(keys, values, vec![0, 1]);
+        let result = serialize_async(
+            &wrap_map_type(Type::UInt16, Type::String),
+            &mut MockWriter::new(),
+            &map_array,
+            map_array.data_type(),
+            &mut state,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::ArrowSerialize(_))));
+    }
+
+    #[tokio::test]
+    async fn test_serialize_map_uuid_keys() {
+        let first = "550e8400-e29b-41d4-a716-446655440000";
+        let second = "6ba7b810-9dad-11d1-80b4-00c04fd430c8";
+        let values = Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef;
+
+        // Keys as strings and as 16 byte UUIDs write the same bytes
+        let string_keys = Arc::new(StringArray::from(vec![first, second])) as ArrayRef;
+        let binary_keys = Arc::new(
+            FixedSizeBinaryArray::try_from_iter(
+                [first, second].iter().map(|u| uuid::Uuid::parse_str(u).unwrap().into_bytes()),
+            )
+            .unwrap(),
+        ) as ArrayRef;
+
+        let mut expected = vec![2, 0, 0, 0, 0, 0, 0, 0];
+        expected.extend(uuid_key_bytes(first));
+        expected.extend(uuid_key_bytes(second));
+        expected.extend([1, 0, 0, 0, 2, 0, 0, 0]);
+
+        for keys in [string_keys, binary_keys] {
+            let map_array = build_map(keys, Arc::clone(&values), vec![0, 2]);
+            let mut writer = MockWriter::new();
+            let mut state = SerializerState::default();
+            serialize_async(
+                &wrap_map_type(Type::Uuid, Type::Int32),
+                &mut writer,
+                &map_array,
+                map_array.data_type(),
+                &mut state,
+            )
+            .await
+            .unwrap();
+            assert_eq!(writer, expected);
+        }
+
+        let keys = Arc::new(StringArray::from(vec!["not-a-uuid"])) as ArrayRef;
+        let map_array = build_map(keys, Arc::new(Int32Array::from(vec![1])), vec![0, 1]);
+        let result = serialize_async(
+            &wrap_map_type(Type::Uuid, Type::Int32),
+            &mut MockWriter::new(),
+            &map_array,
+            map_array.data_type(),
+            &mut SerializerState::default(),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::ArrowSerialize(msg)) if msg.contains("Invalid UUID map key")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_serialize_map_date_keys() {
+        // Date64 keys into a `Map(Date, Int32)` column, 2022-01-08 is day 19_000
+        let keys =
+            Arc::new(Date64Array::from(vec![19_000 * 86_400_000, 19_001 * 86_400_000])) as ArrayRef;
+        let values = Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef;
+        let map_array = build_map(keys, values, vec![0, 2]);
+
+        let mut writer = MockWriter::new();
+        let mut state = SerializerState::default();
+        serialize_async(
+            &wrap_map_type(Type::Date, Type::Int32),
+            &mut writer,
+            &map_array,
+            map_array.data_type(),
+            &mut state,
+        )
+        .await
+        .unwrap();
+        let expected = vec![
+            // Offsets: [2]
+            2, 0, 0, 0, 0, 0, 0, 0, // Keys: [19_000, 19_001]
+            0x38, 0x4a, 0x39, 0x4a, // Values: [1, 2]
+            1, 0, 0, 0, 2, 0, 0, 0,
+        ];
+        assert_eq!(writer, expected);
+    }
 }
 
 #[cfg(test)]
@@ -442,7 +703,10 @@ mod tests_sync {
     #![expect(clippy::clone_on_ref_ptr)]
     use std::sync::Arc;
 
-    use arrow::array::{ArrayRef, Float64Array, Int32Array, MapArray, StringArray, StructArray};
+    use arrow::array::{
+        ArrayRef, Date64Array, Float64Array, Int32Array, Int64Array, MapArray, StringArray,
+        StructArray,
+    };
     use arrow::buffer::OffsetBuffer;
     use arrow::datatypes::{DataType, Field, Fields};
 
@@ -457,6 +721,22 @@ mod tests_sync {
         Type::Map(Box::new(key_type), Box::new(value_type))
     }
 
+    fn build_map(keys: ArrayRef, values: ArrayRef, offsets: Vec<i32>) -> ArrayRef {
+        let key_field = Field::new(STRUCT_KEY_FIELD_NAME, keys.data_type().clone(), false);
+        let value_field = Field::new(STRUCT_VALUE_FIELD_NAME, values.data_type().clone(), false);
+        let fields = Fields::from(vec![key_field, value_field]);
+        let entries = StructArray::new(fields.clone(), vec![keys, values], None);
+        let field = Arc::new(Field::new(MAP_FIELD_NAME, DataType::Struct(fields), false));
+        let offsets = OffsetBuffer::new(offsets.into());
+        Arc::new(MapArray::try_new(field, offsets, entries, None, false).unwrap())
+    }
+
+    fn uuid_key_bytes(uuid: &str) -> Vec<u8> {
+        // `ClickHouse` layout, halves swapped
+        let bytes = uuid::Uuid::parse_str(uuid).unwrap().into_bytes();
+        [&bytes[8..], &bytes[..8]].concat()
+    }
+
     #[test]
     fn test_serialize_map_int32_string() {
         let key_field = Arc::new(Field::new(STRUCT_KEY_FIELD_NAME, DataType::Int32, false));
@@ -497,6 +777,42 @@ mod tests_sync {
         assert_eq!(writer, expected);
     }
 
+    #[test]
+    fn test_serialize_map_sliced() {
+        let key_field = Arc::new(Field::new(STRUCT_KEY_FIELD_NAME, DataType::Int32, false));
+        let value_field = Arc::new(Field::new(STRUCT_VALUE_FIELD_NAME, DataType::Utf8, false));
+        let fields = Fields::from(vec![key_field, value_field]);
+
+        let keys = Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef;
+        let values = Arc::new(StringArray::from(vec!["a", "b", "c"])) as ArrayRef;
+        let entries = StructArray::new(fields.clone(), vec![keys, values], None);
+        let field = Arc::new(Field::new(MAP_FIELD_NAME, DataType::Struct(fields.clone()), false));
+        let offsets = OffsetBuffer::new(vec![0, 2, 2, 3].into());
+        let map_array = MapArray::try_new(field, offsets, entries, None, false).unwrap();
+
+        // [{}, {3:"c"}]
+        let sliced = Arc::new(map_array.slice(1, 2)) as ArrayRef;
+
+        let mut writer = MockWriter::new();
+        let mut state = SerializerState::default();
+
+        serialize(
+            &wrap_map_type(Type::Int32, Type::String),
+            &mut writer,
+            &sliced,
+            sliced.data_type(),
+            &mut state,
+        )
+        .unwrap();
+        let expected = vec![
+            // Offsets: [0, 1]
+            0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, // Keys: [3]
+            3, 0, 0, 0, // Values: ["c"]
+            1, 99,
+        ];
+        assert_eq!(writer, expected);
+    }
+
     #[test]
     fn test_serialize_map_empty() {
         let key_field = Arc::new(Field::new(STRUCT_KEY_FIELD_NAME, DataType::Int32, false));
@@ -736,4 +1052,119 @@ mod tests_sync {
             if msg.contains("Expected Map data type for MapArray")
         ));
     }
+    #[test]
+    fn test_serialize_map_coerced_integer_keys() {
+        // Int64 keys, ie from a Python dict, into a `Map(UInt16, String)` column
+        let keys = Arc::new(Int64Array::from(vec![1, 2, 300])) as ArrayRef;
+        let values = Arc::new(StringArray::from(vec!["a", "b", "c"])) as ArrayRef;
+        let map_array = build_map(keys, values, vec![0, 2, 3]);
+
+        let mut writer = MockWriter::new();
+        let mut state = SerializerState::default();
+        serialize(
+            &wrap_map_type(Type::UInt16, Type::String),
+            &mut writer,
+            &map_array,
+            map_array.data_type(),
+            &mut state,
+        )
+        .unwrap();
+        let expected = vec![
+            // Offsets: [2, 3]
+            2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, // Keys: [1, 2, 300]
+            1, 0, 2, 0, 44, 1, // Values: ["a", "b", "c"]
+            1, 97, 1, 98, 1, 99,
+        ];
+        assert_eq!(writer, expected);
+
+        // Keys out of range of the key type are rejected
+        let keys = Arc::new(Int64Array::from(vec![70_000])) as ArrayRef;
+        let values = Arc::new(StringArray::from(vec!["a"])) as ArrayRef;
+        let map_array = build_map(keys, values, vec![0, 1]);
+        let result = serialize(
+            &wrap_map_type(Type::UInt16, Type::String),
+            &mut MockWriter::new(),
+            &map_array,
+            map_array.data_type(),
+            &mut state,
+        );
+        assert!(matches!(result, Err(Error::ArrowSerialize(_))));
+    }
+
+    #[test]
+    fn test_serialize_map_uuid_keys() {
+        let first = "550e8400-e29b-41d4-a716-446655440000";
+        let second = "6ba7b810-9dad-11d1-80b4-00c04fd430c8";
+        let values = Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef;
+
+        // Keys as strings and as 16 byte UUIDs write the same bytes
+        let string_keys = Arc::new(StringArray::from(vec![first, second])) as ArrayRef;
+        let binary_keys = Arc::new(
+            FixedSizeBinaryArray::try_from_iter(
+                [first, second].iter().map(|u| uuid::Uuid::parse_str(u).unwrap().into_bytes()),
+            )
+            .unwrap(),
+        ) as ArrayRef;
+
+        let mut expected = vec![2, 0, 0, 0, 0, 0, 0, 0];
+        expected.extend(uuid_key_bytes(first));
+        expected.extend(uuid_key_bytes(second));
+        expected.extend([1, 0, 0, 0, 2, 0, 0, 0]);
+
+        for keys in [string_keys, binary_keys] {
+            let map_array = build_map(keys, Arc::clone(&values), vec![0, 2]);
+            let mut writer = MockWriter::new();
+            let mut state = SerializerState::default();
+            serialize(
+                &wrap_map_type(Type::Uuid, Type::Int32),
+                &mut writer,
+                &map_array,
+                map_array.data_type(),
+                &mut state,
+            )
+            .unwrap();
+            assert_eq!(writer, expected);
+        }
+
+        let keys = Arc::new(StringArray::from(vec!["not-a-uuid"])) as ArrayRef;
+        let map_array = build_map(keys, Arc::new(Int32Array::from(vec![1])), vec![0, 1]);
+        let result = serialize(
+            &wrap_map_type(Type::Uuid, Type::Int32),
+            &mut MockWriter::new(),
+            &map_array,
+            map_array.data_type(),
+            &mut SerializerState::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(Error::ArrowSerialize(msg)) if msg.contains("Invalid UUID map key")
+        ));
+    }
+
+    #[test]
+    fn test_serialize_map_date_keys() {
+        // Date64 keys into a `Map(Date, Int32)` column, 2022-01-08 is day 19_000
+        let keys =
+            Arc::new(Date64Array::from(vec![19_000 * 86_400_000, 19_001 * 86_400_000])) as ArrayRef;
+        let values = Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef;
+        let map_array = build_map(keys, values, vec![0, 2]);
+
+        let mut writer = MockWriter::new();
+        let mut state = SerializerState::default();
+        serialize(
+            &wrap_map_type(Type::Date, Type::Int32),
+            &mut writer,
+            &map_array,
+            map_array.data_type(),
+            &mut state,
+        )
+        .unwrap();
+        let expected = vec![
+            // Offsets: [2]
+            2, 0, 0, 0, 0, 0, 0, 0, // Keys: [19_000, 19_001]
+            0x38, 0x4a, 0x39, 0x4a, // Values: [1, 2]
+            1, 0, 0, 0, 2, 0, 0, 0,
+        ];
+        assert_eq!(writer, expected);
+    }
 }
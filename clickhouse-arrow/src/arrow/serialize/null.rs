@@ -9,6 +9,7 @@
 use std::io::IoSlice;
 
 use arrow::array::ArrayRef;
+use arrow::buffer::NullBuffer;
 use tokio::io::AsyncWriteExt;
 
 use crate::formats::SerializerState;
@@ -16,6 +17,24 @@ use crate::io::{ClickHouseBytesWrite, ClickHouseWrite};
 use crate::simd::{PooledBuffer, expand_null_bitmap};
 use crate::{Result, Type};
 
+/// Expand an Arrow null buffer into a `ClickHouse` null mask, honoring the bit offset of sliced
+/// arrays.
+///
+/// `NullBuffer::validity` returns the packed bits without the offset applied. Byte-aligned
+/// offsets, which include all unsliced arrays, use the SIMD-accelerated expansion from the
+/// aligned byte. Other offsets fall back to per-value checks.
+#[inline]
+fn expand_nulls(null_buffer: &NullBuffer, null_mask: &mut [u8], len: usize) {
+    let offset = null_buffer.offset();
+    if offset % 8 == 0 {
+        expand_null_bitmap(&null_buffer.validity()[offset / 8..], null_mask, len);
+    } else {
+        for (i, byte) in null_mask.iter_mut().take(len).enumerate() {
+            *byte = u8::from(null_buffer.is_null(i));
+        }
+    }
+}
+
 /// Prepare expanded null bitmap (1=null, 0=valid) in a pooled buffer.
 #[inline]
 pub(super) fn prepare_null_bitmap(array: &ArrayRef) -> PooledBuffer {
@@ -24,8 +43,7 @@ pub(super) fn prepare_null_bitmap(array: &ArrayRef) -> PooledBuffer {
     null_mask.resize(len, 0);
 
     if let Some(null_buffer) = array.nulls() {
-        let bitmap_bytes = null_buffer.validity();
-        expand_null_bitmap(bitmap_bytes, &mut null_mask, len);
+        expand_nulls(null_buffer, &mut null_mask, len);
     }
 
     null_mask
@@ -87,12 +105,9 @@ pub(super) async fn serialize_nulls_async<W: ClickHouseWrite>(
 
     // Write null bitmap using SIMD-accelerated expansion
     if let Some(null_buffer) = array.nulls() {
-        // Get the packed bitmap bytes from Arrow
-        let bitmap_bytes = null_buffer.validity();
-        // SIMD-accelerated expansion: Arrow packed bits -> CH bytes
         // Arrow: bit=1 means valid, bit=0 means null
         // ClickHouse: byte=0 means valid, byte=1 means null
-        expand_null_bitmap(bitmap_bytes, &mut null_mask, len);
+        expand_nulls(null_buffer, &mut null_mask, len);
     }
     // else: null_mask is already all zeros (all valid)
 
@@ -122,10 +137,7 @@ pub(super) fn serialize_nulls<W: ClickHouseBytesWrite>(
 
     // Write null bitmap using SIMD-accelerated expansion
     if let Some(null_buffer) = array.nulls() {
-        // Get the packed bitmap bytes from Arrow
-        let bitmap_bytes = null_buffer.validity();
-        // SIMD-accelerated expansion: Arrow packed bits -> CH bytes
-        expand_null_bitmap(bitmap_bytes, &mut null_mask, len);
+        expand_nulls(null_buffer, &mut null_mask, len);
     }
     // else: null_mask is already all zeros (all valid)
 
@@ -162,6 +174,24 @@ mod tests {
         assert_eq!(writer, vec![0, 0, 0]); // All 0 for non-null
     }
 
+    #[tokio::test]
+    async fn test_write_nullability_sliced() {
+        let mut state = SerializerState::default();
+        let values = (0..20).map(|i| (i % 3 != 0).then_some(i)).collect::<Vec<_>>();
+        let array = Int32Array::from(values.clone());
+        // Unaligned (3) and byte-aligned (8) bit offsets
+        for offset in [3, 8] {
+            let sliced = Arc::new(array.slice(offset, 10)) as ArrayRef;
+            let mut writer = MockWriter::new();
+            serialize_nulls_async(&Type::Int32, &mut writer, &sliced, &mut state).await.unwrap();
+            let expected = values[offset..offset + 10]
+                .iter()
+                .map(|v| u8::from(v.is_none()))
+                .collect::<Vec<_>>();
+            assert_eq!(writer, expected);
+        }
+    }
+
     #[tokio::test]
     async fn test_write_nullability_empty() {
         let mut state = SerializerState::default();
@@ -235,6 +265,24 @@ mod tests_sync {
         assert_eq!(writer, vec![0, 0, 0]); // All 0 for non-null
     }
 
+    #[test]
+    fn test_write_nullability_sliced() {
+        let mut state = SerializerState::default();
+        let values = (0..20).map(|i| (i % 3 != 0).then_some(i)).collect::<Vec<_>>();
+        let array = Int32Array::from(values.clone());
+        // Unaligned (3) and byte-aligned (8) bit offsets
+        for offset in [3, 8] {
+            let sliced = Arc::new(array.slice(offset, 10)) as ArrayRef;
+            let mut writer = MockWriter::new();
+            serialize_nulls(&Type::Int32, &mut writer, &sliced, &mut state);
+            let expected = values[offset..offset + 10]
+                .iter()
+                .map(|v| u8::from(v.is_none()))
+                .collect::<Vec<_>>();
+            assert_eq!(writer, expected);
+        }
+    }
+
     #[test]
     fn test_write_nullability_empty() {
         let mut state = SerializerState::default();
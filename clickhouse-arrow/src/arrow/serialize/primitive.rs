@@ -49,6 +49,8 @@
 ///     .await
 ///     .unwrap();
 /// ```
+use std::sync::Arc;
+
 use arrow::array::*;
 use arrow::datatypes::{DataType, i256};
 use tokio::io::AsyncWriteExt;
@@ -188,7 +190,8 @@ nullable_primitive_vectored!(write_nullable_f64_vectored, Float64Array, f64);
 /// - Integers: `Int8`, `Int16`, `Int32`, `Int64`, `Int128`, `Int256`, `UInt8`, `UInt16`, `UInt32`,
 ///   `UInt64`, `UInt128`, `UInt256`.
 /// - Floats: `Float32`, `Float64`.
-/// - Decimals: `Decimal32`, `Decimal64`, `Decimal128`, `Decimal256`.
+/// - Decimals: `Decimal32`, `Decimal64`, `Decimal128`, `Decimal256`, from decimal arrays or exactly
+///   parsed strings.
 /// - Dates: `Date`, `DateTime`, `DateTime64` (with precision 0, 1-3, 4-6, 7-9).
 /// - IP addresses: `IPv4`, `IPv6`, `Uuid`.
 ///
@@ -211,6 +214,10 @@ pub(super) async fn serialize_async<W: ClickHouseWrite>(
     values: &ArrayRef,
     data_type: &DataType,
 ) -> Result<()> {
+    // Decimal strings, ie from JSON or CSV, are parsed exactly at the column's scale
+    let parsed = parse_decimal_strings(type_hint, values)?;
+    let values = parsed.as_ref().unwrap_or(values);
+
     match type_hint.strip_null() {
         // v0.4.0: Use bulk serialization for standard primitives (zero-copy)
         Type::Int8 => write_i8_bulk(values, writer).await?,
@@ -289,6 +296,10 @@ pub(super) fn serialize<W: ClickHouseBytesWrite>(
     values: &ArrayRef,
     data_type: &DataType,
 ) -> Result<()> {
+    // Decimal strings, ie from JSON or CSV, are parsed exactly at the column's scale
+    let parsed = parse_decimal_strings(type_hint, values)?;
+    let values = parsed.as_ref().unwrap_or(values);
+
     match type_hint.strip_null() {
         // v0.4.0: Use bulk serialization for standard primitives (zero-copy)
         Type::Int8 => put_i8_bulk(values, writer)?,
@@ -863,7 +874,7 @@ write_primitive_values!(write_date32_values, scalar i32::default(), write_i32_le
 write_primitive_values!(write_datetime_values, scalar u32::default(), write_u32_le, [
     (TimestampSecondArray, |v: i64| {
         #[expect(clippy::cast_lossless)]
-        if v > u32::MAX as i64 {
+        if v < 0 || v > u32::MAX as i64 {
             return Err(Error::ArrowSerialize(format!(
                 "DateTime out of range for TimestampSecond (ClickHouse uses u32): {v}"
             )));
@@ -874,27 +885,27 @@ write_primitive_values!(write_datetime_values, scalar u32::default(), write_u32_
 
 write_primitive_values!(write_datetime64_3_values, scalar u64::default(), write_u64_le, [
     (TimestampMillisecondArray, |v: i64| Ok::<_, Error>(v as u64)), // Milliseconds
-    (TimestampMicrosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1000)), // Convert to ms
-    (TimestampNanosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1_000_000)), // Convert to ms
-    (TimestampSecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1000)) // Convert to ms
+    (TimestampMicrosecondArray, |v: i64| rescale_timestamp(v, 6, 3)), // Convert to ms
+    (TimestampNanosecondArray, |v: i64| rescale_timestamp(v, 9, 3)), // Convert to ms
+    (TimestampSecondArray, |v: i64| rescale_timestamp(v, 0, 3)) // Convert to ms
 ]);
 write_primitive_values!(write_datetime64_6_values, scalar u64::default(), write_u64_le, [
     (TimestampMicrosecondArray, |v: i64| Ok::<_, Error>(v as u64)), // Microseconds
-    (TimestampMillisecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1000)), // Convert to us
-    (TimestampNanosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1000)), // Convert to us
-    (TimestampSecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1_000_000)) // Convert to us
+    (TimestampMillisecondArray, |v: i64| rescale_timestamp(v, 3, 6)), // Convert to us
+    (TimestampNanosecondArray, |v: i64| rescale_timestamp(v, 9, 6)), // Convert to us
+    (TimestampSecondArray, |v: i64| rescale_timestamp(v, 0, 6)) // Convert to us
 ]);
 write_primitive_values!(write_datetime64_9_values, scalar u64::default(), write_u64_le, [
     (TimestampNanosecondArray, |v: i64| Ok::<_, Error>(v as u64)), // Nanoseconds
-    (TimestampMillisecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1_000_000)), // Convert to ns
-    (TimestampMicrosecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1000)), // Convert to ns
-    (TimestampSecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1_000_000_000)) // Convert to ns
+    (TimestampMillisecondArray, |v: i64| rescale_timestamp(v, 3, 9)), // Convert to ns
+    (TimestampMicrosecondArray, |v: i64| rescale_timestamp(v, 6, 9)), // Convert to ns
+    (TimestampSecondArray, |v: i64| rescale_timestamp(v, 0, 9)) // Convert to ns
 ]);
 write_primitive_values!(write_datetime64_unknown_values, scalar u64::default(), write_u64_le, [
     (TimestampSecondArray, |v: i64| Ok::<_, Error>(v as u64)), // Seconds
-    (TimestampMillisecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1000)), // Convert to s
-    (TimestampMicrosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1_000_000)), // Convert to s
-    (TimestampNanosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1_000_000_000)) // Convert to s
+    (TimestampMillisecondArray, |v: i64| rescale_timestamp(v, 3, 0)), // Convert to s
+    (TimestampMicrosecondArray, |v: i64| rescale_timestamp(v, 6, 0)), // Convert to s
+    (TimestampNanosecondArray, |v: i64| rescale_timestamp(v, 9, 0)) // Convert to s
 ]);
 
 // Dates
@@ -918,7 +929,7 @@ put_primitive_values!(put_date32_values, scalar i32::default(), put_i32_le, [
 put_primitive_values!(put_datetime_values, scalar u32::default(), put_u32_le, [
     (TimestampSecondArray, |v: i64| {
         #[expect(clippy::cast_lossless)]
-        if v > u32::MAX as i64 {
+        if v < 0 || v > u32::MAX as i64 {
             return Err(Error::ArrowSerialize(format!(
                 "DateTime out of range for TimestampSecond (ClickHouse uses u32): {v}"
             )));
@@ -929,27 +940,27 @@ put_primitive_values!(put_datetime_values, scalar u32::default(), put_u32_le, [
 
 put_primitive_values!(put_datetime64_3_values, scalar u64::default(), put_u64_le, [
     (TimestampMillisecondArray, |v: i64| Ok::<_, Error>(v as u64)), // Milliseconds
-    (TimestampMicrosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1000)), // Convert to ms
-    (TimestampNanosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1_000_000)), // Convert to ms
-    (TimestampSecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1000)) // Convert to ms
+    (TimestampMicrosecondArray, |v: i64| rescale_timestamp(v, 6, 3)), // Convert to ms
+    (TimestampNanosecondArray, |v: i64| rescale_timestamp(v, 9, 3)), // Convert to ms
+    (TimestampSecondArray, |v: i64| rescale_timestamp(v, 0, 3)) // Convert to ms
 ]);
 put_primitive_values!(put_datetime64_6_values, scalar u64::default(), put_u64_le, [
     (TimestampMicrosecondArray, |v: i64| Ok::<_, Error>(v as u64)), // Microseconds
-    (TimestampMillisecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1000)), // Convert to us
-    (TimestampNanosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1000)), // Convert to us
-    (TimestampSecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1_000_000)) // Convert to us
+    (TimestampMillisecondArray, |v: i64| rescale_timestamp(v, 3, 6)), // Convert to us
+    (TimestampNanosecondArray, |v: i64| rescale_timestamp(v, 9, 6)), // Convert to us
+    (TimestampSecondArray, |v: i64| rescale_timestamp(v, 0, 6)) // Convert to us
 ]);
 put_primitive_values!(put_datetime64_9_values, scalar u64::default(), put_u64_le, [
     (TimestampNanosecondArray, |v: i64| Ok::<_, Error>(v as u64)), // Nanoseconds
-    (TimestampMillisecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1_000_000)), // Convert to ns
-    (TimestampMicrosecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1000)), // Convert to ns
-    (TimestampSecondArray, |v: i64| Ok::<_, Error>(v as u64 * 1_000_000_000)) // Convert to ns
+    (TimestampMillisecondArray, |v: i64| rescale_timestamp(v, 3, 9)), // Convert to ns
+    (TimestampMicrosecondArray, |v: i64| rescale_timestamp(v, 6, 9)), // Convert to ns
+    (TimestampSecondArray, |v: i64| rescale_timestamp(v, 0, 9)) // Convert to ns
 ]);
 put_primitive_values!(put_datetime64_unknown_values, scalar u64::default(), put_u64_le, [
     (TimestampSecondArray, |v: i64| Ok::<_, Error>(v as u64)), // Seconds
-    (TimestampMillisecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1000)), // Convert to s
-    (TimestampMicrosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1_000_000)), // Convert to s
-    (TimestampNanosecondArray, |v: i64| Ok::<_, Error>(v as u64 / 1_000_000_000)) // Convert to s
+    (TimestampMillisecondArray, |v: i64| rescale_timestamp(v, 3, 0)), // Convert to s
+    (TimestampMicrosecondArray, |v: i64| rescale_timestamp(v, 6, 0)), // Convert to s
+    (TimestampNanosecondArray, |v: i64| rescale_timestamp(v, 9, 0)) // Convert to s
 ]);
 
 // IPs
@@ -1008,6 +1019,115 @@ put_primitive_values!(put_ipv6_values, array [u8; 16], put_slice, [
 // Replaced by bulk serialization via write_f32_bulk/write_f64_bulk
 // Float16Array is not supported by ClickHouse, so no type coercion fallback needed
 
+/// Parses a string column bound for a `Decimal` column into a decimal array at the column's
+/// scale.
+///
+/// Returns `None` if the column is not a string column or the type is not a decimal.
+///
+/// # Errors
+/// Returns `ArrowSerialize` if a value is not a decimal number, has more fractional digits than
+/// the column's scale (other than trailing zeros), or has more digits than its precision.
+fn parse_decimal_strings(type_hint: &Type, values: &ArrayRef) -> Result<Option<ArrayRef>> {
+    let (precision, scale) = match type_hint.strip_null() {
+        Type::Decimal32(s) => (9, *s),
+        Type::Decimal64(s) => (18, *s),
+        Type::Decimal128(s) => (38, *s),
+        Type::Decimal256(s) => (76, *s),
+        _ => return Ok(None),
+    };
+    let strings: Box<dyn Iterator<Item = Option<&str>> + '_> = match values.data_type() {
+        DataType::Utf8 => Box::new(values.as_string::<i32>().iter()),
+        DataType::LargeUtf8 => Box::new(values.as_string::<i64>().iter()),
+        DataType::Utf8View => Box::new(values.as_string_view().iter()),
+        _ => return Ok(None),
+    };
+    let scale_i8 = i8::try_from(scale)
+        .map_err(|_| Error::ArrowSerialize(format!("Unsupported decimal scale: {scale}")))?;
+    let invalid = |v: &str| {
+        Error::ArrowSerialize(format!(
+            "Cannot parse '{v}' as {type_hint} without loss of precision"
+        ))
+    };
+
+    let array: ArrayRef = if precision > 38 {
+        let parsed = strings
+            .map(|v| {
+                v.map(|v| {
+                    unscaled_decimal(v, scale, precision)
+                        .and_then(|d| i256::from_string(&d))
+                        .ok_or_else(|| invalid(v))
+                })
+                .transpose()
+            })
+            .collect::<Result<Decimal256Array>>()?;
+        Arc::new(parsed.with_precision_and_scale(precision, scale_i8)?)
+    } else {
+        let parsed = strings
+            .map(|v| {
+                v.map(|v| {
+                    unscaled_decimal(v, scale, precision)
+                        .and_then(|d| d.parse::<i128>().ok())
+                        .ok_or_else(|| invalid(v))
+                })
+                .transpose()
+            })
+            .collect::<Result<Decimal128Array>>()?;
+        Arc::new(parsed.with_precision_and_scale(precision, scale_i8)?)
+    };
+    Ok(Some(array))
+}
+
+/// Converts a decimal string, ie `-12.50` or `1.25e3`, to its unscaled integer digits at `scale`.
+///
+/// Returns `None` if the string is malformed, would lose non-zero fractional digits, or has more
+/// than `precision` significant digits.
+fn unscaled_decimal(value: &str, scale: usize, precision: u8) -> Option<String> {
+    let value = value.trim();
+    let (negative, value) = match value.as_bytes().first()? {
+        b'-' => (true, &value[1..]),
+        b'+' => (false, &value[1..]),
+        _ => (false, value),
+    };
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((m, e)) => (m, e.parse::<i32>().ok()?),
+        None => (value, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if (integer.is_empty() && fraction.is_empty())
+        || !integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    // Shift the decimal point so exactly `scale` fractional digits remain
+    let mut digits = format!("{integer}{fraction}");
+    let shift =
+        i64::try_from(scale).ok()? + i64::from(exponent) - i64::try_from(fraction.len()).ok()?;
+    if shift >= 0 {
+        let shift = usize::try_from(shift).ok()?;
+        if shift > usize::from(precision) + digits.len() {
+            return None;
+        }
+        digits.extend(std::iter::repeat_n('0', shift));
+    } else {
+        let cut = digits.len().saturating_sub(usize::try_from(-shift).ok()?);
+        if digits[cut..].bytes().any(|b| b != b'0') {
+            return None;
+        }
+        digits.truncate(cut);
+    }
+
+    let digits = digits.trim_start_matches('0');
+    if digits.len() > usize::from(precision) {
+        return None;
+    }
+    Some(match (digits.is_empty(), negative) {
+        (true, _) => "0".into(),
+        (false, true) => format!("-{digits}"),
+        (false, false) => digits.into(),
+    })
+}
+
 /// Swaps the endianness of a 256-bit (32-byte) array.
 ///
 /// Converts between little-endian and big-endian for `Int256`, `UInt256`, and `Decimal256`.
@@ -1016,6 +1136,25 @@ fn swap_endian_256(mut input: [u8; 32]) -> [u8; 32] {
     input
 }
 
+/// Rescales a timestamp from `10^-from` to `10^-to` second ticks for `DateTime64`.
+///
+/// `DateTime64` is a signed `Int64`, so pre-epoch values are scaled as signed integers and
+/// rounded toward the past when precision is dropped, keeping the sub-second part non-negative.
+///
+/// # Errors
+/// Returns `ArrowSerialize` if the scaled value overflows `Int64`.
+#[expect(clippy::cast_sign_loss)]
+fn rescale_timestamp(v: i64, from: u32, to: u32) -> Result<u64> {
+    let scaled = if to >= from {
+        v.checked_mul(10_i64.pow(to - from))
+    } else {
+        Some(v.div_euclid(10_i64.pow(from - to)))
+    };
+    scaled.map(|v| v as u64).ok_or_else(|| {
+        Error::ArrowSerialize(format!("DateTime64({to}) out of range for timestamp {v}"))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -2792,4 +2931,74 @@ mod tests_sync {
             if msg.contains("Unsupported data type: String")
         ));
     }
+
+    #[test]
+    fn test_rescale_timestamp_pre_epoch() {
+        // 1969-12-31T23:59:59.999500 in microseconds floors to .999 ms
+        assert_eq!(rescale_timestamp(-500, 6, 3).unwrap().cast_signed(), -1);
+        assert_eq!(rescale_timestamp(-1_500_000, 6, 0).unwrap().cast_signed(), -2);
+        assert_eq!(rescale_timestamp(-2, 0, 9).unwrap().cast_signed(), -2_000_000_000);
+        assert_eq!(rescale_timestamp(1_500, 3, 0).unwrap(), 1);
+        assert!(rescale_timestamp(i64::MAX / 10, 0, 9).is_err());
+
+        let column = Arc::new(TimestampSecondArray::from(vec![-86_400, 0])) as ArrayRef;
+        let mut writer = Vec::new();
+        serialize(&Type::DateTime64(3, Tz::UTC), &mut writer, &column, column.data_type()).unwrap();
+        let values: Vec<i64> =
+            writer.chunks_exact(8).map(|b| i64::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(values, vec![-86_400_000, 0]);
+
+        // DateTime is unsigned
+        let result = serialize(&Type::DateTime(Tz::UTC), &mut writer, &column, column.data_type());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_unscaled_decimal() {
+        assert_eq!(unscaled_decimal("12.5", 2, 9).as_deref(), Some("1250"));
+        assert_eq!(unscaled_decimal(" -0.10 ", 1, 9).as_deref(), Some("-1"));
+        assert_eq!(unscaled_decimal("+.5", 1, 9).as_deref(), Some("5"));
+        assert_eq!(unscaled_decimal("7.", 0, 9).as_deref(), Some("7"));
+        assert_eq!(unscaled_decimal("1.25e3", 0, 9).as_deref(), Some("1250"));
+        assert_eq!(unscaled_decimal("125E-2", 2, 9).as_deref(), Some("125"));
+        assert_eq!(unscaled_decimal("-0.000", 0, 9).as_deref(), Some("0"));
+        assert_eq!(unscaled_decimal("0e-10", 2, 9).as_deref(), Some("0"));
+        assert_eq!(unscaled_decimal("999999999", 0, 9).as_deref(), Some("999999999"));
+
+        // Precision loss
+        assert!(unscaled_decimal("12.345", 2, 9).is_none());
+        assert!(unscaled_decimal("1e-10", 2, 9).is_none());
+        assert!(unscaled_decimal("1000000000", 0, 9).is_none());
+        assert!(unscaled_decimal("1e300", 0, 76).is_none());
+        // Malformed
+        for value in ["", "-", ".", "1.2.3", "abc", "1e", "0x10", "1,5", "NaN"] {
+            assert!(unscaled_decimal(value, 2, 9).is_none(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_serialize_decimal_from_strings() {
+        let column =
+            Arc::new(StringArray::from(vec![Some("12.5"), None, Some("-0.01")])) as ArrayRef;
+        let mut writer = MockWriter::new();
+        serialize(&Type::Decimal64(2), &mut writer, &column, column.data_type()).unwrap();
+        let values: Vec<i64> =
+            writer.chunks_exact(8).map(|b| i64::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(values, vec![1250, 0, -1]);
+
+        let column = Arc::new(LargeStringArray::from(vec!["-1.5"])) as ArrayRef;
+        let mut writer = MockWriter::new();
+        serialize(&Type::Decimal256(1), &mut writer, &column, column.data_type()).unwrap();
+        let mut expected = [0xFF; 32];
+        expected[31] = 0xF1; // -15, big-endian
+        assert_eq!(writer, expected);
+
+        let column = Arc::new(StringArray::from(vec!["1.005"])) as ArrayRef;
+        let mut writer = MockWriter::new();
+        let result = serialize(&Type::Decimal32(2), &mut writer, &column, column.data_type());
+        assert!(matches!(
+            result,
+            Err(Error::ArrowSerialize(msg)) if msg.contains("'1.005' as Decimal32(2)")
+        ));
+    }
 }
@@ -0,0 +1,125 @@
+//! Deserialize the rows of `RecordBatch`es into `serde` types.
+//!
+//! Rows are bridged through JSON: each batch is rendered by an [`NdjsonEncoder`] and each line
+//! deserialized with `serde_json`, so any `T: DeserializeOwned` whose fields match the result's
+//! column names can be read. Used by [`crate::ClickHouseResponse::into_typed`], for
+//! [`crate::ArrowClient`] users who occasionally need typed rows. The JSON round trip is slower
+//! than decoding rows natively, prefer a `NativeFormat` client with [`crate::Row`] types for
+//! row-oriented workloads.
+//!
+//! With [`TYPED_ROW_OPTIONS`], values deserialize as:
+//! - Strings, including `String` columns decoded as binary, as `String`.
+//! - Dates, times and timestamps as ISO 8601 strings, ie into `chrono` types.
+//! - Decimals as numbers, ie into `f64`.
+//! - Nullable columns as `Option`, lists as `Vec`, tuples as structs, and maps as maps.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! #[derive(serde::Deserialize)]
+//! struct Event {
+//!     id:   u64,
+//!     name: String,
+//! }
+//!
+//! let mut events = client.query("SELECT id, name FROM events", None).await?.into_typed::<Event>();
+//! while let Some(event) = events.next().await {
+//!     println!("{}", event?.name);
+//! }
+//! ```
+
+use arrow::record_batch::RecordBatch;
+use serde::de::DeserializeOwned;
+
+use super::ndjson::{BinaryFormat, DecimalFormat, NdjsonEncoder, NdjsonOptions, TemporalFormat};
+use crate::{Error, Result};
+
+/// The [`NdjsonOptions`] used to bridge rows into `serde` types.
+pub const TYPED_ROW_OPTIONS: NdjsonOptions = NdjsonOptions {
+    temporal:       TemporalFormat::Iso8601,
+    decimal:        DecimalFormat::Number,
+    binary:         BinaryFormat::Utf8Lossy,
+    explicit_nulls: true,
+};
+
+/// Deserialize each row of `batch` into `T`, rendering values according to `options`.
+///
+/// # Errors
+/// - Returns `ArrowUnsupportedType` if a column's type cannot be rendered as JSON.
+/// - Returns `DeserializeError` if a row does not deserialize into `T`.
+pub fn batch_to_typed<T: DeserializeOwned>(
+    batch: &RecordBatch,
+    options: NdjsonOptions,
+) -> Result<Vec<T>> {
+    let mut json = Vec::new();
+    NdjsonEncoder::new(options).encode(batch, &mut json)?;
+    json.split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(row, line)| {
+            serde_json::from_slice(line).map_err(|e| {
+                Error::DeserializeError(format!("Failed to deserialize row {row}: {e}"))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{BinaryArray, Decimal128Array, Int32Array, ListArray, StringArray};
+    use arrow::datatypes::{DataType, Field, Int32Type, Schema};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Row {
+        id:     i32,
+        name:   String,
+        label:  Option<String>,
+        price:  f64,
+        values: Vec<i32>,
+    }
+
+    #[test]
+    fn test_batch_to_typed() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Binary, false),
+            Field::new("label", DataType::Utf8, true),
+            Field::new("price", DataType::Decimal128(10, 2), false),
+            Field::new_list("values", Field::new_list_field(DataType::Int32, true), false),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(Int32Array::from(vec![1, 2])),
+            Arc::new(BinaryArray::from_vec(vec![b"a", b"b"])),
+            Arc::new(StringArray::from(vec![Some("x"), None])),
+            Arc::new(
+                Decimal128Array::from(vec![1250, 99]).with_precision_and_scale(10, 2).unwrap(),
+            ),
+            Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+                Some(vec![Some(1), Some(2)]),
+                Some(vec![]),
+            ])),
+        ])
+        .unwrap();
+
+        let rows = batch_to_typed::<Row>(&batch, TYPED_ROW_OPTIONS).unwrap();
+        assert_eq!(rows, vec![
+            Row {
+                id:     1,
+                name:   "a".into(),
+                label:  Some("x".into()),
+                price:  12.5,
+                values: vec![1, 2],
+            },
+            Row { id: 2, name: "b".into(), label: None, price: 0.99, values: vec![] },
+        ]);
+
+        // Mismatched rows report the failing row
+        let error = batch_to_typed::<(String,)>(&batch, TYPED_ROW_OPTIONS).unwrap_err();
+        assert!(error.to_string().contains("row 0"));
+    }
+}
@@ -191,12 +191,73 @@ pub(crate) fn normalize_type(type_: &Type, arrow_type: &DataType) -> Option<Type
 
             deferred_vec.map(Type::Tuple)
         }
+        (Type::Map(key, value), DataType::Map(entries, _)) => {
+            let DataType::Struct(fields) = entries.data_type() else { return None };
+            let (key_field, value_field) = (fields.first()?, fields.get(1)?);
+            let normalized_key = normalize_type(key, key_field.data_type());
+            let normalized_value = normalize_type(value, value_field.data_type());
+            if normalized_key.is_none() && normalized_value.is_none() {
+                return None;
+            }
+            Some(Type::Map(
+                Box::new(normalized_key.unwrap_or_else(|| key.as_ref().clone())),
+                Box::new(normalized_value.unwrap_or_else(|| value.as_ref().clone())),
+            ))
+        }
         _ => return None,
     };
 
     if nullable { type_.map(Type::into_nullable) } else { type_ }
 }
 
+/// Resolve the timezone of an Arrow timestamp to the equivalent `ClickHouse` timezone.
+///
+/// Arrow timezones are either IANA names or fixed offsets such as `+05:00`, while `ClickHouse`
+/// only accepts IANA names. Whole hour offsets map to the equivalent `Etc/GMT` zone. Timezones are
+/// resolved against the IANA database bundled by `chrono-tz`, never the host's.
+///
+/// # Errors
+/// Returns `TypeConversion` if the timezone is unknown or is an offset with no IANA equivalent,
+/// rather than silently shifting values to UTC.
+pub(crate) fn arrow_timezone(tz: &str) -> Result<chrono_tz::Tz> {
+    if let Ok(parsed) = chrono_tz::Tz::from_str(tz) {
+        return Ok(parsed);
+    }
+    fixed_offset_hours(tz)
+        .and_then(|hours| match hours {
+            0 => Some(chrono_tz::Tz::UTC),
+            // POSIX inverts the sign: `Etc/GMT-5` is 5 hours ahead of UTC
+            h => chrono_tz::Tz::from_str(&format!("Etc/GMT{:+}", -h)).ok(),
+        })
+        .ok_or_else(|| {
+            Error::TypeConversion(format!(
+                "Timezone '{tz}' has no ClickHouse equivalent, use an IANA name"
+            ))
+        })
+}
+
+/// Parse an `[+-]HH[[:]MM]` or `Z` offset, returning its hours if it is a whole number of hours.
+fn fixed_offset_hours(tz: &str) -> Option<i32> {
+    if tz.eq_ignore_ascii_case("z") {
+        return Some(0);
+    }
+    let (sign, rest) = match tz.as_bytes().first()? {
+        b'+' => (1, &tz[1..]),
+        b'-' => (-1, &tz[1..]),
+        _ => return None,
+    };
+    let digits = rest.replace(':', "");
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (digits[..2].parse::<i32>().ok()?, digits[2..].parse::<i32>().ok()?),
+        _ => return None,
+    };
+    (minutes == 0 && hours <= 14).then_some(sign * hours)
+}
+
 /// Convert an arrow [`arrow::datatypes::DataType`] to a clickhouse [`Type`].
 ///
 /// NOTE: `ClickHouse` defaults to `UTC` for timezones, hence this function does as well.
@@ -207,9 +268,7 @@ pub(crate) fn arrow_to_ch_type(
     mut is_nullable: bool,
     options: Option<ArrowOptions>,
 ) -> Result<Type> {
-    let tz_map = |tz: Option<&str>| {
-        tz.and_then(|s| chrono_tz::Tz::from_str(s).ok()).unwrap_or(chrono_tz::Tz::UTC)
-    };
+    let tz_map = |tz: Option<&str>| tz.map_or(Ok(chrono_tz::Tz::UTC), arrow_timezone);
 
     // Don't use wildcards here to ensure all types are handled explicitly.
     let inner_type = match data_type {
@@ -251,14 +310,16 @@ pub(crate) fn arrow_to_ch_type(
         DataType::Time64(TimeUnit::Nanosecond) | DataType::Duration(TimeUnit::Nanosecond) => {
             Type::DateTime64(9, chrono_tz::Tz::UTC)
         }
-        DataType::Timestamp(TimeUnit::Second, tz) => Type::DateTime(tz_map(Some(tz.as_deref().unwrap_or("UTC")))),
+        DataType::Timestamp(TimeUnit::Second, tz) => Type::DateTime(tz_map(tz.as_deref())?),
         DataType::Timestamp(TimeUnit::Millisecond, tz) => {
-            Type::DateTime64(3, tz_map(Some(tz.as_deref().unwrap_or("UTC"))))
+            Type::DateTime64(3, tz_map(tz.as_deref())?)
         }
         DataType::Timestamp(TimeUnit::Microsecond, tz) => {
-            Type::DateTime64(6, tz_map(Some(tz.as_deref().unwrap_or("UTC"))))
+            Type::DateTime64(6, tz_map(tz.as_deref())?)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, tz) => {
+            Type::DateTime64(9, tz_map(tz.as_deref())?)
         }
-        DataType::Timestamp(TimeUnit::Nanosecond, tz) => Type::DateTime64(9, tz_map(Some(tz.as_deref().unwrap_or("UTC")))),
         DataType::Time32(TimeUnit::Nanosecond) => Type::DateTime64(9, chrono_tz::Tz::UTC),
         DataType::FixedSizeBinary(s) => Type::FixedSizedBinary(*s as usize),
         DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => Type::String,
@@ -386,16 +447,16 @@ pub fn ch_to_arrow_type(ch_type: &Type, options: Option<ArrowOptions>) -> Result
         Type::Decimal64(s) => DataType::Decimal128(18, *s as i8),
         Type::Decimal128(s) => DataType::Decimal128(38, *s as i8),
         Type::Decimal256(s) => DataType::Decimal256(76, *s as i8),
-        Type::String => {
-            if options.is_some_and(|o| o.strings_as_strings) {
-                DataType::Utf8
-            } else {
-                DataType::Binary
-            }
-        }
+        Type::String => match options {
+            Some(o) if o.strings_as_strings && o.large_offsets => DataType::LargeUtf8,
+            Some(o) if o.strings_as_strings => DataType::Utf8,
+            Some(o) if o.large_offsets => DataType::LargeBinary,
+            _ => DataType::Binary,
+        },
         Type::FixedSizedString(len) | Type::FixedSizedBinary(len) => {
             DataType::FixedSizeBinary(*len as i32)
         }
+        Type::Binary if options.is_some_and(|o| o.large_offsets) => DataType::LargeBinary,
         Type::Binary => DataType::Binary,
         Type::Object => DataType::Utf8,
         Type::Date32 | Type::Date => DataType::Date32,
@@ -421,7 +482,12 @@ pub fn ch_to_arrow_type(ch_type: &Type, options: Option<ArrowOptions>) -> Result
                 ));
             }
             let (inner_arrow_type, is_null) = ch_to_arrow_type(inner_type, options)?;
-            DataType::List(Arc::new(Field::new(LIST_ITEM_FIELD_NAME, inner_arrow_type, is_null)))
+            let field = Arc::new(Field::new(LIST_ITEM_FIELD_NAME, inner_arrow_type, is_null));
+            if options.is_some_and(|o| o.large_offsets) {
+                DataType::LargeList(field)
+            } else {
+                DataType::List(field)
+            }
         }
         Type::Tuple(types) => {
             let fields: Vec<Field> = types
@@ -463,6 +529,8 @@ pub fn ch_to_arrow_type(ch_type: &Type, options: Option<ArrowOptions>) -> Result
             // LowCardinality itself cannot be nullable, so the nullability applies to the inner.
             is_null = inner_type.is_nullable();
 
+            // Dictionary values are bounded by the dictionary size, keep 32-bit offsets
+            let options = options.map(|o| o.with_large_offsets(false));
             DataType::Dictionary(
                 Box::new(DataType::Int32),
                 Box::new(ch_to_arrow_type(inner_type, options)?.0),
@@ -631,6 +699,27 @@ mod tests {
             Some(Type::LowCardinality(Box::new(Type::String)).into_nullable())
         );
 
+        // Map with normalized value type
+        let arrow_map = DataType::Map(
+            Arc::new(Field::new(
+                MAP_FIELD_NAME,
+                DataType::Struct(Fields::from(vec![
+                    Field::new(STRUCT_KEY_FIELD_NAME, DataType::UInt16, false),
+                    Field::new(STRUCT_VALUE_FIELD_NAME, DataType::Binary, false),
+                ])),
+                false,
+            )),
+            false,
+        );
+        assert_eq!(
+            normalize_type(&Type::Map(Box::new(Type::UInt16), Box::new(Type::String)), &arrow_map),
+            Some(Type::Map(Box::new(Type::UInt16), Box::new(Type::Binary)))
+        );
+        assert_eq!(
+            normalize_type(&Type::Map(Box::new(Type::UInt16), Box::new(Type::Binary)), &arrow_map),
+            None
+        );
+
         // Nullable with normalized inner type
         assert_eq!(
             normalize_type(&Type::Nullable(Box::new(Type::String)), &DataType::Binary),
@@ -951,6 +1040,38 @@ mod tests {
         assert!(is_nullable_nullable);
     }
 
+    /// Tests `ch_to_arrow_type` with `large_offsets`, keeping `LowCardinality` dictionaries on
+    /// 32-bit offsets.
+    #[test]
+    fn test_ch_to_arrow_type_large_offsets() {
+        let options = Some(ArrowOptions::default().with_large_offsets(true));
+        let strings = options.map(|o| o.with_strings_as_strings(true));
+
+        assert_eq!(ch_to_arrow_type(&Type::String, options).unwrap().0, DataType::LargeBinary);
+        assert_eq!(ch_to_arrow_type(&Type::String, strings).unwrap().0, DataType::LargeUtf8);
+        assert_eq!(ch_to_arrow_type(&Type::Binary, options).unwrap().0, DataType::LargeBinary);
+
+        let array = Type::Array(Box::new(Type::Nullable(Box::new(Type::String))));
+        assert_eq!(
+            ch_to_arrow_type(&array, strings).unwrap().0,
+            DataType::LargeList(Arc::new(Field::new(
+                LIST_ITEM_FIELD_NAME,
+                DataType::LargeUtf8,
+                true
+            )))
+        );
+
+        let low_cardinality = Type::LowCardinality(Box::new(Type::String));
+        assert_eq!(
+            ch_to_arrow_type(&low_cardinality, strings).unwrap().0,
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+        );
+
+        // Round trips back to the same ClickHouse types
+        let (arrow_type, _) = ch_to_arrow_type(&array, strings).unwrap();
+        assert_eq!(arrow_to_ch_type(&arrow_type, false, strings).unwrap(), array);
+    }
+
     /// Tests `arrow_to_ch_type` for `Struct(Nullable(Int32), String)` with outer nullability.
     #[test]
     fn test_roundtrip_struct() {
@@ -1247,4 +1368,29 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), Type::Date32);
     }
+
+    #[test]
+    fn test_arrow_timezone() {
+        assert_eq!(arrow_timezone("Asia/Kolkata").unwrap(), Tz::Asia__Kolkata);
+        assert_eq!(arrow_timezone("UTC").unwrap(), Tz::UTC);
+        assert_eq!(arrow_timezone("Z").unwrap(), Tz::UTC);
+        assert_eq!(arrow_timezone("+00:00").unwrap(), Tz::UTC);
+        // POSIX sign inversion
+        assert_eq!(arrow_timezone("+05:00").unwrap(), Tz::Etc__GMTMinus5);
+        assert_eq!(arrow_timezone("-0800").unwrap(), Tz::Etc__GMTPlus8);
+        assert_eq!(arrow_timezone("+14").unwrap(), Tz::Etc__GMTMinus14);
+
+        // No IANA equivalent, or unknown, must not silently become UTC
+        assert!(arrow_timezone("+05:30").is_err());
+        assert!(arrow_timezone("+15:00").is_err());
+        assert!(arrow_timezone("Mars/Olympus_Mons").is_err());
+        assert!(
+            arrow_to_ch_type(
+                &DataType::Timestamp(TimeUnit::Second, Some(Arc::from("Mars/Olympus_Mons"))),
+                false,
+                None
+            )
+            .is_err()
+        );
+    }
 }
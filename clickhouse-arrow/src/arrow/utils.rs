@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use arrow::array::*;
-use arrow::compute::cast;
+use arrow::compute::{cast, concat_batches};
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
 
@@ -69,6 +69,55 @@ pub fn split_record_batch(batch: RecordBatch, max: usize) -> Vec<RecordBatch> {
     chunks
 }
 
+/// Concatenates consecutive `RecordBatch`es into batches of up to `max_rows` rows and, if set,
+/// `max_bytes` bytes of in-memory data.
+///
+/// Batches are merged in order. A batch that would take the merged batch past either threshold,
+/// or whose schema differs from it, starts a new one, so batches already at the threshold pass
+/// through unchanged. Sizes are measured with `RecordBatch::get_array_memory_size`.
+///
+/// # Errors
+///
+/// Returns an error if concatenating the batches fails.
+pub fn coalesce_record_batches(
+    batches: Vec<RecordBatch>,
+    max_rows: usize,
+    max_bytes: Option<usize>,
+) -> Result<Vec<RecordBatch>> {
+    if batches.len() < 2 {
+        return Ok(batches);
+    }
+
+    let mut coalesced = Vec::with_capacity(batches.len());
+    let mut pending: Vec<RecordBatch> = Vec::new();
+    let (mut rows, mut bytes) = (0, 0);
+    for batch in batches {
+        let batch_bytes = batch.get_array_memory_size();
+        let full = rows + batch.num_rows() > max_rows
+            || max_bytes.is_some_and(|max| bytes + batch_bytes > max);
+        if !pending.is_empty() && (full || pending[0].schema() != batch.schema()) {
+            coalesced.push(concat_pending(&mut pending)?);
+            (rows, bytes) = (0, 0);
+        }
+        rows += batch.num_rows();
+        bytes += batch_bytes;
+        pending.push(batch);
+    }
+    if !pending.is_empty() {
+        coalesced.push(concat_pending(&mut pending)?);
+    }
+    Ok(coalesced)
+}
+
+/// Takes the pending batches of [`coalesce_record_batches`], concatenated into one.
+fn concat_pending(pending: &mut Vec<RecordBatch>) -> Result<RecordBatch> {
+    let mut batches = std::mem::take(pending);
+    if batches.len() == 1 {
+        return Ok(batches.remove(0));
+    }
+    Ok(concat_batches(&batches[0].schema(), &batches)?)
+}
+
 /// Converts a [`RecordBatch`] to an iterator of rows, where each row is a Vec of Values.
 ///
 /// # Arguments
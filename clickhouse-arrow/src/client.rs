@@ -11,38 +11,71 @@ mod chunk;
 #[cfg(feature = "cloud")]
 mod cloud;
 pub(crate) mod connection;
+mod credentials;
+mod endpoints;
 mod internal;
 mod options;
+mod probe;
 mod reader;
 mod response;
+#[cfg(feature = "ssh")]
+mod ssh;
 mod tcp;
+mod throttle;
+mod trust;
+mod unknown;
 mod writer;
 
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU16;
+use std::time::{Duration, Instant, SystemTime};
 
-use arrow::array::{ArrayRef, RecordBatch};
-use arrow::compute::take_record_batch;
-use arrow::datatypes::SchemaRef;
+use arrow::array::{ArrayRef, AsArray, RecordBatch};
+use arrow::compute::{cast, take_record_batch};
+use arrow::datatypes::{DataType, Int64Type, SchemaRef};
 use futures_util::{Stream, StreamExt, TryStreamExt, stream};
 use strum::AsRefStr;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tokio::sync::{broadcast, mpsc, oneshot};
 
 pub use self::builder::*;
 pub use self::connection::ConnectionStatus;
+pub use self::credentials::{
+    CREDENTIALS_PASSWORD_ENV_VAR, CREDENTIALS_USER_ENV_VAR, Credentials, CredentialsCallback,
+    CredentialsProvider, EnvCredentials, FileCredentials,
+};
+pub use self::endpoints::EndpointStrategy;
+use self::endpoints::Endpoints;
 pub(crate) use self::internal::{Message, Operation};
 pub use self::options::*;
+pub use self::probe::ProbeResult;
+use self::probe::{PROBE_QUERY, estimate_clock_skew, unix_micros};
 pub use self::response::*;
 pub use self::tcp::Destination;
+pub use self::throttle::QueueStats;
+use self::throttle::{QueryPermit, Throttle, hold_permit};
+pub use self::unknown::UNKNOWN_TYPE_METADATA_KEY;
+use crate::arrow::compat::{CompatibilityReport, compare_schemas};
+use crate::arrow::conversion::{ConversionReport, plan_conversion, record_masks};
+use crate::arrow::explode::MapExploder;
+use crate::arrow::ndjson::{NdjsonEncoder, NdjsonOptions};
+use crate::arrow::ordering::OrderValidator;
+use crate::arrow::rescale::DecimalRescale;
 use crate::arrow::utils::batch_to_rows;
 use crate::constants::*;
+use crate::defaults::{ColumnDefaults, ColumnDefaultsCache, GeneratedColumnPolicy};
 use crate::formats::{ClientFormat, NativeFormat};
+use crate::limits::{QueryTiming, ResultGuard};
+use crate::masking::InsertMasking;
 use crate::native::block::Block;
 use crate::native::protocol::{CompressionMethod, ProfileEvent};
 use crate::prelude::*;
 use crate::query::{ParsedQuery, QueryParams};
 use crate::schema::CreateOptions;
+use crate::spawn::SpawnedTask;
+use crate::telemetry::record_query_span;
+use crate::validation::BlockValidator;
 use crate::{Error, Progress, Result, Row};
 
 static CLIENT_ID: AtomicU16 = AtomicU16::new(0);
@@ -61,6 +94,13 @@ pub type NativeClient = Client<NativeFormat>;
 /// and analytics workflows.
 pub type ArrowClient = Client<ArrowFormat>;
 
+/// A `ClickHouse` client configured for lazily decoded Apache Arrow format.
+///
+/// This type alias provides a client that works with [`LazyBatch`]es, which
+/// decode columns into Arrow arrays only when first accessed, accelerating
+/// workflows that read wide tables but touch few columns per batch.
+pub type LazyArrowClient = Client<LazyArrowFormat>;
+
 /// Configuration for a `ClickHouse` connection, including tracing and cloud-specific settings.
 ///
 /// This struct is used to pass optional context to [`Client::connect`], enabling features
@@ -77,6 +117,46 @@ pub struct ConnectionContext {
     pub cloud: Option<Arc<std::sync::atomic::AtomicBool>>,
 }
 
+/// Callbacks and insert transforms configured on a [`ClientBuilder`].
+///
+/// Kept apart from [`ConnectionContext`] so the context stays a plain, copyable value.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ClientHooks {
+    /// Block validation run before each insert is serialized
+    pub(crate) validator:        Option<BlockValidator>,
+    /// Callback invoked after each statement completes
+    pub(crate) statement_hook:   Option<StatementHook>,
+    /// Callbacks invoked as connections are established, lost, and re-established
+    pub(crate) connection_hooks: Option<ConnectionHooks>,
+    /// Column transforms applied to inserts before they are validated
+    pub(crate) masking:          Option<InsertMasking>,
+    /// Provider of the credentials to connect with, replacing those of the [`ClientOptions`]
+    pub(crate) credentials:      Option<Arc<dyn CredentialsProvider>>,
+}
+
+/// Per-query overrides of the client's configuration, see [`Client::query_raw_inner`].
+#[derive(Default)]
+struct QueryOverrides {
+    /// Replaces the client's settings.
+    settings:        Option<Arc<Settings>>,
+    /// Replaces the connection's quota key.
+    quota_key:       Option<String>,
+    /// Names of the columns to decode.
+    projection:      Option<Arc<[String]>>,
+    /// Decimal columns converted while decoding.
+    decimal_rescale: Option<Arc<[(String, DecimalRescale)]>>,
+    /// Records the query's timings.
+    timing:          Option<Arc<QueryTiming>>,
+    /// Called with each progress update of the query.
+    progress:        Option<ProgressHook>,
+    /// Replaces the connection's read-ahead.
+    read_ahead:      Option<usize>,
+    /// Cancels the query on the server if the response is dropped before it completes.
+    cancel_on_drop:  bool,
+    /// Receives the columns of the result's header block.
+    header:          Option<oneshot::Sender<Vec<(String, Type)>>>,
+}
+
 /// Emitted clickhouse events from the underlying connection
 #[derive(Debug, Clone)]
 pub struct Event {
@@ -137,6 +217,12 @@ pub struct Client<T: ClientFormat> {
     connection:    Arc<connection::Connection<T>>,
     events:        Arc<broadcast::Sender<Event>>,
     settings:      Option<Arc<Settings>>,
+    validator:     Option<BlockValidator>,
+    masking:       Option<InsertMasking>,
+    max_block:     Option<usize>,
+    coalescing:    Option<InsertCoalescing>,
+    defaults:      ColumnDefaultsCache,
+    throttle:      Throttle,
 }
 
 impl<T: ClientFormat> Client<T> {
@@ -200,6 +286,19 @@ impl<T: ClientFormat> Client<T> {
     ///
     /// let client = Client::connect("localhost:9000", options, None, None).await?;
     /// ```
+    pub async fn connect<A: Into<Destination>>(
+        destination: A,
+        options: ClientOptions,
+        settings: Option<Arc<Settings>>,
+        context: Option<ConnectionContext>,
+    ) -> Result<Self> {
+        let endpoints = Endpoints::from(destination.into());
+        Self::connect_endpoints(endpoints, options, settings, context, ClientHooks::default()).await
+    }
+
+    /// Establishes a connection to the first available of `endpoints`, see [`Client::connect`].
+    ///
+    /// `hooks` carries the callbacks and insert transforms configured on a [`ClientBuilder`].
     #[instrument(
         level = "debug",
         name = "clickhouse.connect",
@@ -210,11 +309,12 @@ impl<T: ClientFormat> Client<T> {
         ),
         skip_all
     )]
-    pub async fn connect<A: Into<Destination>>(
-        destination: A,
-        options: ClientOptions,
+    pub(crate) async fn connect_endpoints(
+        endpoints: Endpoints,
+        mut options: ClientOptions,
         settings: Option<Arc<Settings>>,
         context: Option<ConnectionContext>,
+        hooks: ClientHooks,
     ) -> Result<Self> {
         let context = context.unwrap_or_default();
         let trace_ctx = context.trace.unwrap_or_default();
@@ -222,9 +322,15 @@ impl<T: ClientFormat> Client<T> {
 
         let client_id = CLIENT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
-        // Resolve the destination
-        let destination: Destination = destination.into();
-        let addrs = destination.resolve(options.ipv4_only).await?;
+        // Resolve the endpoints
+        let targets = endpoints.resolve(&options).await?;
+
+        // Fetch fresh credentials on every connect, so rotated passwords are picked up
+        if let Some(provider) = hooks.credentials.as_ref() {
+            let credentials = provider.credentials().await?;
+            options.username = credentials.username;
+            options.password = credentials.password;
+        }
 
         #[cfg(feature = "cloud")]
         {
@@ -235,24 +341,62 @@ impl<T: ClientFormat> Client<T> {
             }
         }
 
-        if let Some(addr) = addrs.first() {
-            let _ = Span::current()
-                .record("server.address", tracing::field::debug(&addr.ip()))
-                .record("server.port", addr.port());
-            debug!(server.address = %addr.ip(), server.port = addr.port(), "Initiating connection");
-        }
+        let addr = targets.primary();
+        let _ = Span::current()
+            .record("server.address", tracing::field::debug(&addr.ip()))
+            .record("server.port", addr.port());
+        debug!(server.address = %addr.ip(), server.port = addr.port(), "Initiating connection");
 
         let (event_tx, _) = broadcast::channel(EVENTS_CAPACITY);
         let events = Arc::new(event_tx);
         let conn_ev = Arc::clone(&events);
 
-        let conn =
-            connection::Connection::connect(client_id, addrs, options, conn_ev, trace_ctx).await?;
+        // Resolve the insert block size before options are moved into the connection
+        let max_block = options
+            .ext
+            .max_insert_block_size
+            .or_else(|| settings.as_ref().and_then(|s| s.get_usize("max_insert_block_size")));
+        let coalescing = options.ext.insert_coalescing;
+        let throttle = Throttle::new(
+            options.ext.max_concurrent_queries,
+            options.ext.query_queue,
+            options.ext.insert_rate_limit,
+        );
+
+        let conn_hooks = connection::Hooks {
+            statement:  hooks.statement_hook,
+            connection: hooks.connection_hooks.unwrap_or_default(),
+        };
+        // Kept by the connection to fetch fresh credentials when reconnecting
+        let conn = connection::Connection::connect(
+            client_id,
+            targets,
+            options,
+            conn_ev,
+            trace_ctx,
+            conn_hooks,
+            hooks.credentials,
+        )
+        .await?;
         let connection = Arc::new(conn);
 
         debug!("created connection successfully");
 
-        Ok(Client { client_id, connection, events, settings })
+        let validator = hooks.validator.filter(|v| !v.is_empty());
+        let masking = hooks.masking.filter(|m| !m.is_empty());
+        let defaults = ColumnDefaultsCache::default();
+        Ok(Client {
+            client_id,
+            connection,
+            events,
+            settings,
+            validator,
+            masking,
+            max_block,
+            coalescing,
+            defaults,
+            throttle,
+        })
     }
 
     /// Retrieves the status of the underlying `ClickHouse` connection.
@@ -279,6 +423,46 @@ impl<T: ClientFormat> Client<T> {
     /// ```
     pub fn status(&self) -> ConnectionStatus { self.connection.status() }
 
+    /// Retrieves the `(major, minor, patch)` version the server reported during the handshake.
+    pub fn server_version(&self) -> (u64, u64, u64) { self.connection.server_version() }
+
+    /// Metrics of the query slots shared by the client and its clones, including the depth of
+    /// the queue waiting for them. `None` unless concurrent queries are capped with
+    /// [`ClientBuilder::with_max_concurrent_queries`].
+    pub fn queue_stats(&self) -> Option<QueueStats> { self.throttle.queue_stats() }
+
+    /// A source of [`Self::queue_stats`] for [`crate::metrics::ClientMetrics::register_queue`],
+    /// without keeping the connection alive.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn queue_source(&self) -> impl Fn() -> Option<QueueStats> + Send + Sync + 'static {
+        let throttle = self.throttle.clone();
+        move || throttle.queue_stats()
+    }
+
+    /// Whether raw SQL is omitted from logs and spans, see
+    /// [`ClientBuilder::with_redact_queries`].
+    fn redact_queries(&self) -> bool { self.connection.metadata().redact_queries }
+
+    /// The client's settings with per-query `settings` and `comment` applied, or `None` if there
+    /// are no per-query overrides.
+    fn query_settings(
+        &self,
+        settings: Option<Settings>,
+        comment: Option<&str>,
+    ) -> Option<Arc<Settings>> {
+        if settings.is_none() && comment.is_none() {
+            return None;
+        }
+        let mut merged = self.settings.as_deref().cloned().unwrap_or_default();
+        if let Some(settings) = settings {
+            merged = merged.merge(settings);
+        }
+        Some(Arc::new(match comment {
+            Some(comment) => merged.with_log_comment(comment),
+            None => merged,
+        }))
+    }
+
     /// Subscribes to progress and profile events from `ClickHouse` queries.
     ///
     /// This method returns a [`broadcast::Receiver`] that delivers [`Event`] instances
@@ -377,6 +561,17 @@ impl<T: ClientFormat> Client<T> {
         self.conn().await?.shutdown().await
     }
 
+    /// Apply the configured insert masking to a block, if the insert's target table is known.
+    fn mask_insert(&self, query: &str, block: T::Data) -> Result<T::Data> {
+        let Some(masking) = self.masking.as_ref() else { return Ok(block) };
+        let Some((database, table)) = crate::masking::insert_table(query) else {
+            return Ok(block);
+        };
+        let database = database.as_deref().unwrap_or(self.connection.database());
+        let database = if database.is_empty() { "default" } else { database };
+        T::mask(block, masking, &format!("{database}.{table}"))
+    }
+
     /// Inserts a block of data into `ClickHouse` using the native protocol.
     ///
     /// This method sends an insert query with a single block of data, formatted according to
@@ -388,6 +583,11 @@ impl<T: ClientFormat> Client<T> {
     /// [`Client::subscribe_events`]). The returned stream yields `()` on success or an
     /// error if the insert fails.
     ///
+    /// A [`RecordBatch`] with more rows than the max insert block size is sent as multiple
+    /// zero-copy slices. The size is the one configured (see
+    /// [`ClientBuilder::with_max_insert_block_size`]), otherwise the server's
+    /// `max_insert_block_size`. Native [`Block`]s are always sent as-is.
+    ///
     /// # Parameters
     /// - `query`: The insert query (e.g., `"INSERT INTO my_table VALUES"`).
     /// - `block`: The data to insert, in the format specified by `T` ([`Block`] or
@@ -430,7 +630,9 @@ impl<T: ClientFormat> Client<T> {
             db.operation = "insert",
             db.format = T::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         ),
     )]
     pub async fn insert(
@@ -439,7 +641,17 @@ impl<T: ClientFormat> Client<T> {
         block: T::Data,
         qid: Option<Qid>,
     ) -> Result<impl Stream<Item = Result<()>> + '_> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
+        let (query, qid) = record_query(qid, query.into(), self.client_id, self.redact_queries());
+
+        // Mask sensitive columns, then reject invalid data before anything is sent
+        let block = self.mask_insert(&query, block)?;
+        if let Some(validator) = self.validator.as_ref() {
+            T::validate(&block, validator)?;
+        }
+
+        // Pace the insert and wait for a query slot, if configured
+        self.throttle.acquire_insert(std::slice::from_ref(&block)).await;
+        let permit = self.throttle.acquire_query().await?;
 
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
@@ -455,6 +667,13 @@ impl<T: ClientFormat> Client<T> {
                     params: None,
                     response: tx,
                     header: None,
+                    quota_key: None,
+                    projection: None,
+                    decimal_rescale: None,
+                    timing: None,
+                    progress: None,
+                    read_ahead: None,
+                    cancel_on_drop: false,
                 },
                 qid,
                 false,
@@ -467,11 +686,15 @@ impl<T: ClientFormat> Client<T> {
             .map_err(|_| Error::Protocol(format!("Failed to receive response for query {qid}")))?
             .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "Error receiving header"))?;
 
-        // Send data
+        // Send data, split into multiple blocks if it exceeds the max insert block size
         let (tx, rx) = oneshot::channel();
-        let _ = connection
-            .send_operation(Operation::Insert { data: block, response: tx }, qid, true)
-            .await?;
+        let mut blocks = self.split_blocks(vec![block]);
+        let operation = if blocks.len() == 1 {
+            Operation::Insert { data: blocks.remove(0), response: tx }
+        } else {
+            Operation::InsertMany { data: blocks, response: tx }
+        };
+        let _ = connection.send_operation(operation, qid, true).await?;
         rx.await.map_err(|_| {
             Error::Protocol(format!("Failed to receive response from insert {qid}"))
         })??;
@@ -480,7 +703,7 @@ impl<T: ClientFormat> Client<T> {
         #[cfg(feature = "inner_pool")]
         connection.finish(conn_idx, Operation::<T::Data>::weight_insert());
 
-        Ok(self.insert_response(responses, qid))
+        Ok(self.insert_response(responses, qid, permit))
     }
 
     /// Inserts multiple blocks of data into `ClickHouse` using the native protocol.
@@ -495,6 +718,9 @@ impl<T: ClientFormat> Client<T> {
     /// [`Client::subscribe_events`]). The returned stream yields `()` on success or an
     /// error if the insert fails. Use this method when inserting multiple batches of
     /// data to reduce overhead compared to multiple [`Client::insert`] calls.
+    /// Batches larger than the max insert block size are split as in [`Client::insert`], and
+    /// small batches are merged into larger blocks if
+    /// [`ClientBuilder::with_insert_coalescing`] is configured.
     ///
     /// # Parameters
     /// - `query`: The insert query (e.g., `"INSERT INTO my_table VALUES"`).
@@ -536,7 +762,9 @@ impl<T: ClientFormat> Client<T> {
             db.operation = "insert",
             db.format = T::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         ),
     )]
     pub async fn insert_many(
@@ -545,7 +773,20 @@ impl<T: ClientFormat> Client<T> {
         batch: Vec<T::Data>,
         qid: Option<Qid>,
     ) -> Result<impl Stream<Item = Result<()>> + '_> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
+        let (query, qid) = record_query(qid, query.into(), self.client_id, self.redact_queries());
+
+        // Mask sensitive columns, then reject invalid data before anything is sent
+        let batch = batch
+            .into_iter()
+            .map(|block| self.mask_insert(&query, block))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(validator) = self.validator.as_ref() {
+            batch.iter().try_for_each(|block| T::validate(block, validator))?;
+        }
+
+        // Pace the insert and wait for a query slot, if configured
+        self.throttle.acquire_insert(&batch).await;
+        let permit = self.throttle.acquire_query().await?;
 
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
@@ -560,6 +801,13 @@ impl<T: ClientFormat> Client<T> {
                     params: None,
                     response: tx,
                     header: None,
+                    quota_key: None,
+                    projection: None,
+                    decimal_rescale: None,
+                    timing: None,
+                    progress: None,
+                    read_ahead: None,
+                    cancel_on_drop: false,
                 },
                 qid,
                 false,
@@ -572,10 +820,11 @@ impl<T: ClientFormat> Client<T> {
             .map_err(|_| Error::Protocol(format!("Failed to receive response for query {qid}")))?
             .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "Error receiving header"))?;
 
-        // Send data
+        // Send data, merging small blocks and splitting those exceeding the max insert block size
         let (tx, rx) = oneshot::channel();
+        let data = self.split_blocks(self.coalesce_blocks(batch)?);
         let _ = connection
-            .send_operation(Operation::InsertMany { data: batch, response: tx }, qid, true)
+            .send_operation(Operation::InsertMany { data, response: tx }, qid, true)
             .await?;
         rx.await.map_err(|_| {
             Error::Protocol(format!("Failed to receive response from insert {qid}"))
@@ -585,7 +834,7 @@ impl<T: ClientFormat> Client<T> {
         #[cfg(feature = "inner_pool")]
         connection.finish(conn_idx, Operation::<T::Data>::weight_insert_many());
 
-        Ok(self.insert_response(responses, qid))
+        Ok(self.insert_response(responses, qid, permit))
     }
 
     /// Executes a raw `ClickHouse` query and streams raw data in the client's format.
@@ -637,7 +886,9 @@ impl<T: ClientFormat> Client<T> {
             db.operation = "query",
             db.format = T::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id = %qid
+            clickhouse.query.id = %qid,
+            clickhouse.query.fingerprint,
+            db.query.text
         ),
      )]
     pub async fn query_raw<P: Into<QueryParams>>(
@@ -646,6 +897,40 @@ impl<T: ClientFormat> Client<T> {
         params: Option<P>,
         qid: Qid,
     ) -> Result<impl Stream<Item = Result<T::Data>> + 'static> {
+        let _ = record_query_span(&Span::current(), &query, self.redact_queries());
+        self.query_raw_inner(query, params.map(Into::into), qid, QueryOverrides::default()).await
+    }
+
+    /// Shared implementation of [`Client::query_raw`], allowing per-query overrides of the
+    /// client's settings and the connection-level quota key, and a timing handle the connection
+    /// records time to first batch and total stream duration into.
+    async fn query_raw_inner(
+        &self,
+        query: String,
+        params: Option<QueryParams>,
+        qid: Qid,
+        overrides: QueryOverrides,
+    ) -> Result<impl Stream<Item = Result<T::Data>> + 'static> {
+        let QueryOverrides {
+            settings,
+            quota_key,
+            projection,
+            decimal_rescale,
+            timing,
+            progress,
+            read_ahead,
+            cancel_on_drop,
+            header,
+        } = overrides;
+
+        // Reject bad identifier parameters before taking a query slot
+        if let Some(params) = &params {
+            params.validate_identifiers(&query)?;
+        }
+
+        // Wait for a query slot, if configured
+        let permit = self.throttle.acquire_query().await?;
+
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
         let connection = self.conn().await?;
@@ -655,10 +940,17 @@ impl<T: ClientFormat> Client<T> {
             .send_operation(
                 Operation::Query {
                     query,
-                    settings: self.settings.clone(),
-                    params: params.map(Into::into),
+                    settings: settings.or_else(|| self.settings.clone()),
+                    params,
                     response: tx,
-                    header: None,
+                    header,
+                    quota_key,
+                    projection,
+                    decimal_rescale,
+                    timing,
+                    progress,
+                    read_ahead,
+                    cancel_on_drop,
                 },
                 qid,
                 true,
@@ -677,7 +969,7 @@ impl<T: ClientFormat> Client<T> {
         #[cfg(feature = "inner_pool")]
         connection.finish(conn_idx, Operation::<T::Data>::weight_query());
 
-        Ok(create_response_stream::<T>(responses, qid, self.client_id))
+        Ok(hold_permit(create_response_stream::<T>(responses, qid, self.client_id), permit))
     }
 
     /// Executes a `ClickHouse` query and discards all returned data.
@@ -721,7 +1013,9 @@ impl<T: ClientFormat> Client<T> {
             db.format = T::FORMAT,
             db.operation = "query",
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn execute(&self, query: impl Into<ParsedQuery>, qid: Option<Qid>) -> Result<()> {
@@ -770,7 +1064,9 @@ impl<T: ClientFormat> Client<T> {
             db.format = T::FORMAT,
             db.operation = "query",
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn execute_params<P: Into<QueryParams>>(
@@ -779,7 +1075,7 @@ impl<T: ClientFormat> Client<T> {
         params: Option<P>,
         qid: Option<Qid>,
     ) -> Result<()> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
+        let (query, qid) = record_query(qid, query.into(), self.client_id, self.redact_queries());
         let stream = self.query_raw(query, params, qid).await?;
         tokio::pin!(stream);
         while let Some(next) = stream.next().await {
@@ -827,7 +1123,9 @@ impl<T: ClientFormat> Client<T> {
             db.format = T::FORMAT,
             db.operation = "query",
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn execute_now(&self, query: impl Into<ParsedQuery>, qid: Option<Qid>) -> Result<()> {
@@ -872,7 +1170,9 @@ impl<T: ClientFormat> Client<T> {
             db.format = T::FORMAT,
             db.operation = "query",
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn execute_now_params<P: Into<QueryParams>>(
@@ -881,7 +1181,7 @@ impl<T: ClientFormat> Client<T> {
         params: Option<P>,
         qid: Option<Qid>,
     ) -> Result<()> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
+        let (query, qid) = record_query(qid, query.into(), self.client_id, self.redact_queries());
         drop(self.query_raw(query, params, qid).await?);
         Ok(())
     }
@@ -992,18 +1292,268 @@ impl<T: ClientFormat> Client<T> {
         self.execute(stmt, qid).await?;
         Ok(())
     }
+
+    /// Deletes rows matching `predicate` from `table`.
+    ///
+    /// With `lightweight` set, a lightweight `DELETE FROM` is issued when the server supports
+    /// it, otherwise (or with `lightweight` unset) the delete falls back to an
+    /// `ALTER TABLE ... DELETE` mutation. The settings needed by the server's version, including
+    /// the experimental flag on servers predating stable lightweight deletes, are added
+    /// automatically. The call waits for the delete to complete on the current replica, see
+    /// [`Client::delete_with_wait`] to configure this.
+    ///
+    /// # Parameters
+    /// - `table`: The table to delete from, optionally qualified with a database.
+    /// - `predicate`: The `WHERE` expression selecting rows to delete. Use `1` to delete all rows.
+    /// - `lightweight`: Whether to prefer a lightweight delete.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Errors
+    /// - Fails if the table name or predicate is empty.
+    /// - Fails if the query execution encounters a `ClickHouse` error.
+    /// - Fails if the connection is interrupted.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// client.delete("my_db.events", "ts < now() - INTERVAL 30 DAY", true, None).await?;
+    /// ```
+    pub async fn delete(
+        &self,
+        table: &str,
+        predicate: &str,
+        lightweight: bool,
+        qid: Option<Qid>,
+    ) -> Result<()> {
+        self.delete_with_wait(table, predicate, lightweight, MutationWait::default(), qid).await
+    }
+
+    /// Deletes rows matching `predicate` from `table`, waiting for completion as configured.
+    ///
+    /// See [`Client::delete`] for how the statement is chosen.
+    ///
+    /// # Errors
+    /// - Fails if the table name or predicate is empty.
+    /// - Fails if the query execution encounters a `ClickHouse` error.
+    /// - Fails if the connection is interrupted.
+    #[instrument(
+        name = "clickhouse.delete",
+        skip_all
+        fields(db.system = "clickhouse", db.operation = "delete", lightweight, ?wait)
+    )]
+    pub async fn delete_with_wait(
+        &self,
+        table: &str,
+        predicate: &str,
+        lightweight: bool,
+        wait: MutationWait,
+        qid: Option<Qid>,
+    ) -> Result<()> {
+        let stmt = delete_statement(table, predicate, lightweight, wait, self.server_version())?;
+        self.execute(stmt, qid).await?;
+        Ok(())
+    }
+
+    /// Runs a server-side `INSERT ... SELECT`, streaming its progress.
+    ///
+    /// Unlike [`Client::insert`], no data is sent by the client: the server reads the rows from
+    /// the `SELECT` and writes them itself. The returned [`InsertSelectResponse`] streams the
+    /// statement's [`Progress`] updates and resolves to an [`InsertSelectSummary`] with the
+    /// total rows read and written once the statement completes. See
+    /// [`Client::insert_select_with_timeout`] to bound the statement's execution time.
+    ///
+    /// # Parameters
+    /// - `query`: The `INSERT ... SELECT` statement to run.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Errors
+    /// - Fails if the statement is not an `INSERT ... SELECT`, ie inserts `VALUES`.
+    /// - Fails if the connection is interrupted.
+    ///
+    /// Errors raised by `ClickHouse` while executing the statement are returned by
+    /// [`InsertSelectResponse::summary`].
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    /// use futures_util::StreamExt;
+    ///
+    /// let mut response =
+    ///     client.insert_select("INSERT INTO archive SELECT * FROM events", None).await?;
+    /// while let Some(progress) = response.next().await {
+    ///     println!("{progress}");
+    /// }
+    /// let summary = response.summary().await?;
+    /// println!("Wrote {} rows in {:?}", summary.written_rows, summary.elapsed);
+    /// ```
+    pub async fn insert_select(
+        &self,
+        query: impl Into<ParsedQuery>,
+        qid: Option<Qid>,
+    ) -> Result<InsertSelectResponse> {
+        self.insert_select_with_timeout(query, None, qid).await
+    }
+
+    /// Runs a server-side `INSERT ... SELECT`, bounding its execution time on the server.
+    ///
+    /// See [`Client::insert_select`]. The `timeout` is applied as the statement's
+    /// `max_execution_time`, rounded up to whole seconds, so the server aborts the statement
+    /// once it is exceeded.
+    ///
+    /// # Errors
+    /// - Fails if the statement is not an `INSERT ... SELECT`, ie inserts `VALUES`.
+    /// - Fails if the connection is interrupted.
+    #[instrument(
+        name = "clickhouse.insert_select",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "insert_select",
+            clickhouse.client.id = self.client_id,
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text,
+            ?timeout
+        )
+    )]
+    pub async fn insert_select_with_timeout(
+        &self,
+        query: impl Into<ParsedQuery>,
+        timeout: Option<Duration>,
+        qid: Option<Qid>,
+    ) -> Result<InsertSelectResponse> {
+        let query: ParsedQuery = query.into();
+        let stmt = insert_select_statement(&query, timeout)?;
+        let (query, qid) = record_query(qid, stmt.into(), self.client_id, self.redact_queries());
+
+        // The totals are accumulated from this query's own progress packets, none are dropped
+        let (tx, rx) = mpsc::channel(32);
+        let totals = Arc::new(parking_lot::Mutex::new(Progress::default()));
+        let query_totals = Arc::clone(&totals);
+        let progress = ProgressHook::new(move |progress| {
+            let mut total = query_totals.lock();
+            *total = *total + *progress;
+            // Progress is informational, drop updates nobody is reading
+            let _ = tx.try_send(*progress).ok();
+        });
+        let overrides = QueryOverrides { progress: Some(progress), ..Default::default() };
+        let started = Instant::now();
+        let stream = self.query_raw_inner(query, None, qid, overrides).await?;
+
+        let task = SpawnedTask::spawn(async move {
+            tokio::pin!(stream);
+            // Progress packets are handled before the end of the stream is forwarded
+            while let Some(result) = stream.next().await {
+                drop(result?);
+            }
+
+            let total = *totals.lock();
+            Ok::<_, Error>(InsertSelectSummary {
+                read_rows:     total.read_rows,
+                read_bytes:    total.read_bytes,
+                written_rows:  total.written_rows.unwrap_or_default(),
+                written_bytes: total.written_bytes.unwrap_or_default(),
+                elapsed:       started.elapsed(),
+            })
+        });
+        Ok(InsertSelectResponse::new(rx, task))
+    }
+
+    /// Executes a `ClickHouse` query, streaming the server's [`Progress`] updates alongside its
+    /// data.
+    ///
+    /// The server sends progress packets while it reads, ie rows and bytes read so far and the
+    /// total rows to read, so dashboards can show how far along a long running query is. Each
+    /// update is yielded as a [`QueryUpdate::Progress`] ahead of the data that follows it, and
+    /// values are deltas since the previous update. Updates are dropped rather than buffered if
+    /// the stream is not polled, data never is. To receive updates through a callback instead,
+    /// see [`QueryOptions::with_progress`](crate::explain::QueryOptions::with_progress).
+    ///
+    /// # Parameters
+    /// - `query`: The SQL query to execute (e.g., `"SELECT * FROM my_table"`).
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Errors
+    /// - Fails if the query is malformed or unsupported by `ClickHouse`.
+    /// - Fails if the connection to `ClickHouse` is interrupted.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    /// use futures_util::StreamExt;
+    ///
+    /// let mut response = client.query_with_progress("SELECT * FROM events", None).await?;
+    /// let mut read = Progress::default();
+    /// while let Some(update) = response.next().await {
+    ///     match update? {
+    ///         QueryUpdate::Progress(progress) => {
+    ///             read = read + progress;
+    ///             println!("{} of {} rows", read.read_rows, read.total_rows_to_read);
+    ///         }
+    ///         QueryUpdate::Data(batch) => println!("{} rows received", batch.num_rows()),
+    ///     }
+    /// }
+    /// ```
+    #[instrument(
+        name = "clickhouse.query_with_progress",
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            db.format = T::FORMAT,
+            clickhouse.client.id = self.client_id,
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
+        )
+    )]
+    pub async fn query_with_progress(
+        &self,
+        query: impl Into<ParsedQuery>,
+        qid: Option<Qid>,
+    ) -> Result<ClickHouseResponse<QueryUpdate<T::Data>>> {
+        let (query, qid) = record_query(qid, query.into(), self.client_id, self.redact_queries());
+        let (tx, rx) = mpsc::channel(32);
+        // Progress is informational, drop updates nobody is reading
+        let progress = ProgressHook::new(move |progress| {
+            let _ = tx.try_send(*progress).ok();
+        });
+        let overrides = QueryOverrides { progress: Some(progress), ..Default::default() };
+        let stream = self.query_raw_inner(query, None, qid, overrides).await?;
+        Ok(ClickHouseResponse::from_stream(WithProgress::new(stream, rx)))
+    }
 }
 
 impl<T: ClientFormat> Client<T> {
     /// Get a reference to the underlying connection.
-    ///
-    /// TODO: Support reconnect.
     #[expect(clippy::unused_async)]
     async fn conn(&self) -> Result<&connection::Connection<T>> {
-        // TODO: Add reconnection logic here if configured
+        // Dropped connections are re-established when sending the next operation, if configured
         Ok(self.connection.as_ref())
     }
 
+    /// Merge consecutive small blocks, if insert coalescing is configured.
+    fn coalesce_blocks(&self, blocks: Vec<T::Data>) -> Result<Vec<T::Data>> {
+        match self.coalescing {
+            Some(coalescing) => T::coalesce(blocks, coalescing),
+            None => Ok(blocks),
+        }
+    }
+
+    /// The max insert block size: the configured one, else the server's, else its default.
+    fn max_insert_block_size(&self) -> usize {
+        self.max_block
+            .or_else(|| self.connection.server_max_insert_block_size())
+            .unwrap_or(CLICKHOUSE_DEFAULT_MAX_INSERT_BLOCK_SIZE)
+    }
+
+    /// Split blocks exceeding the max insert block size.
+    fn split_blocks(&self, blocks: Vec<T::Data>) -> Vec<T::Data> {
+        let max_rows = self.max_insert_block_size();
+        blocks.into_iter().flat_map(|b| T::split(b, max_rows)).collect()
+    }
+
     /// # Feature
     /// Requires the `cloud` feature to be enabled.
     #[cfg(feature = "cloud")]
@@ -1022,13 +1572,38 @@ impl<T: ClientFormat> Client<T> {
         }
     }
 
+    /// Shared implementation of `probe`: pings the server, then runs [`PROBE_QUERY`] through
+    /// `query_now`, which returns the server's time in microseconds and its timezone.
+    async fn probe_with<F, Fut>(&self, query_now: F) -> Result<ProbeResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(i64, String)>>,
+    {
+        let start = Instant::now();
+        self.health_check(true).await?;
+        let rtt = start.elapsed();
+
+        let sent = unix_micros(SystemTime::now());
+        let (server_now, server_timezone) = query_now().await?;
+        let received = unix_micros(SystemTime::now());
+
+        Ok(ProbeResult {
+            rtt,
+            server_version: self.server_version(),
+            server_timezone,
+            clock_skew_micros: estimate_clock_skew(sent, received, server_now),
+        })
+    }
+
     // Helper function to convert a receiver of data into a `ClickHouseResponse`
     fn insert_response(
         &self,
         rx: mpsc::Receiver<Result<T::Data>>,
         qid: Qid,
+        permit: QueryPermit,
     ) -> ClickHouseResponse<()> {
-        ClickHouseResponse::<()>::from_stream(handle_insert_response::<T>(rx, qid, self.client_id))
+        let stream = handle_insert_response::<T>(rx, qid, self.client_id);
+        ClickHouseResponse::<()>::from_stream(hold_permit(stream, permit))
     }
 }
 
@@ -1084,7 +1659,9 @@ impl Client<NativeFormat> {
             db.operation = "insert",
             db.format = NativeFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         ),
         skip_all
     )]
@@ -1095,7 +1672,10 @@ impl Client<NativeFormat> {
         qid: Option<Qid>,
     ) -> Result<ClickHouseResponse<()>> {
         let cid = self.client_id;
-        let (query, qid) = record_query(qid, query.into(), cid);
+        let (query, qid) = record_query(qid, query.into(), cid, self.redact_queries());
+
+        // Wait for a query slot, if configured
+        let permit = self.throttle.acquire_query().await?;
 
         // Create metadata channel
         let (tx, rx) = oneshot::channel();
@@ -1112,6 +1692,13 @@ impl Client<NativeFormat> {
                     params: None,
                     response: tx,
                     header: Some(header_tx),
+                    quota_key: None,
+                    projection: None,
+                    decimal_rescale: None,
+                    timing: None,
+                    progress: None,
+                    read_ahead: None,
+                    cancel_on_drop: false,
                 },
                 qid,
                 false,
@@ -1129,6 +1716,9 @@ impl Client<NativeFormat> {
             .map_err(|_| Error::Protocol(format!("Failed to receive header for query {qid}")))?;
         let data = Block::from_rows(blocks.collect(), header)?;
 
+        // Pace the insert, if configured. The rows are only known once the header arrives
+        self.throttle.acquire_insert(std::slice::from_ref(&data)).await;
+
         let (tx, rx) = oneshot::channel();
         let _ =
             connection.send_operation(Operation::Insert { data, response: tx }, qid, true).await?;
@@ -1140,7 +1730,7 @@ impl Client<NativeFormat> {
         #[cfg(feature = "inner_pool")]
         connection.finish(conn_idx, Operation::<Block>::weight_query());
 
-        Ok(self.insert_response(responses, qid))
+        Ok(self.insert_response(responses, qid, permit))
     }
 
     /// Executes a `ClickHouse` query and streams deserialized rows.
@@ -1236,7 +1826,14 @@ impl Client<NativeFormat> {
     #[instrument(
         name = "clickhouse.query_params",
         skip_all,
-        fields(db.system = "clickhouse", db.operation = "query", db.format = NativeFormat::FORMAT)
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            db.format = NativeFormat::FORMAT,
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
+        )
     )]
     pub async fn query_params<T: Row + Send + 'static>(
         &self,
@@ -1244,7 +1841,7 @@ impl Client<NativeFormat> {
         params: Option<QueryParams>,
         qid: Option<Qid>,
     ) -> Result<ClickHouseResponse<T>> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
+        let (query, qid) = record_query(qid, query.into(), self.client_id, self.redact_queries());
         let raw = self.query_raw(query, params, qid).await?;
         Ok(ClickHouseResponse::new(Box::pin(raw.flat_map(|block| {
             match block {
@@ -1311,7 +1908,9 @@ impl Client<NativeFormat> {
             db.operation = "query",
             db.format = NativeFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn query_one<T: Row + Send + 'static>(
@@ -1370,7 +1969,9 @@ impl Client<NativeFormat> {
             db.operation = "query",
             db.format = NativeFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn query_one_params<T: Row + Send + 'static>(
@@ -1439,6 +2040,32 @@ impl Client<NativeFormat> {
         self.execute(stmt, qid).await?;
         Ok(())
     }
+
+    /// Probes the server, measuring round trip time and estimating clock skew.
+    ///
+    /// The returned [`ProbeResult`] also includes the server version and default timezone, which
+    /// helps diagnose shifted `DateTime` values and choose replicas by latency.
+    ///
+    /// # Errors
+    /// - Fails if the ping or the probe query fails.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let probe = client.probe(None).await?;
+    /// println!("rtt={:?} timezone={}", probe.rtt, probe.server_timezone);
+    /// ```
+    pub async fn probe(&self, qid: Option<Qid>) -> Result<ProbeResult> {
+        self.probe_with(|| async {
+            let mut row = self
+                .query_one::<crate::RawRow>(PROBE_QUERY, qid)
+                .await?
+                .ok_or_else(|| Error::Protocol("Probe query returned no rows".into()))?;
+            Ok((row.try_get(0)?, row.try_get(1)?))
+        })
+        .await
+    }
 }
 
 impl Client<ArrowFormat> {
@@ -1483,7 +2110,13 @@ impl Client<ArrowFormat> {
     /// ```
     #[instrument(
         skip_all,
-        fields(db.system = "clickhouse", db.operation = "query", clickhouse.query.id)
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
+        )
     )]
     pub async fn query(
         &self,
@@ -1529,7 +2162,13 @@ impl Client<ArrowFormat> {
     /// ```
     #[instrument(
         skip_all,
-        fields(db.system = "clickhouse", db.operation = "query", clickhouse.query.id)
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
+        )
     )]
     pub async fn query_params(
         &self,
@@ -1537,8 +2176,152 @@ impl Client<ArrowFormat> {
         params: Option<QueryParams>,
         qid: Option<Qid>,
     ) -> Result<ClickHouseResponse<RecordBatch>> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
-        Ok(ClickHouseResponse::new(Box::pin(self.query_raw(query, params, qid).await?)))
+        let query = query.into();
+        let policy = self.connection.metadata().arrow_options.unknown_type_policy;
+        if policy != UnknownTypePolicy::Error {
+            return self.query_unknown_types(query, params, qid, policy).await;
+        }
+        let (query, qid) = record_query(qid, query, self.client_id, self.redact_queries());
+        let options = self.connection.metadata().arrow_options;
+        let (sender, header) = ResultHeader::channel(options, None, None);
+        let overrides = QueryOverrides { header: Some(sender), ..Default::default() };
+        let stream = self.query_raw_inner(query, params, qid, overrides).await?;
+        Ok(ClickHouseResponse::new(Box::pin(stream)).with_header(header))
+    }
+
+    /// Run a query under an [`UnknownTypePolicy`] other than `Error`, describing its result first
+    /// and rewriting it if any column has an unknown type.
+    async fn query_unknown_types(
+        &self,
+        query: ParsedQuery,
+        params: Option<QueryParams>,
+        qid: Option<Qid>,
+        policy: UnknownTypePolicy,
+    ) -> Result<ClickHouseResponse<RecordBatch>> {
+        let describe = unknown::describe_query(&query);
+        let described = async {
+            let batches = self
+                .query_raw(describe, params.clone(), Qid::new())
+                .await?
+                .try_collect::<Vec<_>>()
+                .await?;
+            unknown::unknown_columns(&batches)
+        };
+        // Queries that can't be described, ie `SHOW` or `EXPLAIN`, are run as is
+        let unknown = described.await.unwrap_or_else(|error| {
+            debug!(?error, { ATT_CID } = self.client_id, "Could not describe query result");
+            Vec::new()
+        });
+        if unknown.is_empty() {
+            let (query, qid) = record_query(qid, query, self.client_id, self.redact_queries());
+            return Ok(ClickHouseResponse::new(Box::pin(
+                self.query_raw(query, params, qid).await?,
+            )));
+        }
+
+        let (legacy, other): (Vec<_>, Vec<_>) =
+            unknown.iter().partition(|column| column.is_legacy_object());
+        if !legacy.is_empty() {
+            let columns = legacy.iter().map(|c| c.name.as_str()).collect::<Vec<_>>();
+            warn!(
+                ?columns,
+                { ATT_CID } = self.client_id,
+                "Object('json') is deprecated, reading the columns as JSON strings"
+            );
+        }
+        if !other.is_empty() {
+            let columns =
+                other.iter().map(|c| format!("{} {}", c.name, c.type_name)).collect::<Vec<_>>();
+            warn!(
+                ?columns,
+                ?policy,
+                { ATT_CID } = self.client_id,
+                "Query result has columns of unknown types"
+            );
+        }
+        let rewritten = ParsedQuery(unknown::rewrite_query(&query, &unknown, policy)?);
+        let (query, qid) = record_query(qid, rewritten, self.client_id, self.redact_queries());
+        let stream = self.query_raw(query, params, qid).await?;
+        if policy == UnknownTypePolicy::Skip && legacy.is_empty() {
+            return Ok(ClickHouseResponse::new(Box::pin(stream)));
+        }
+        let stream = stream
+            .map(move |batch| batch.and_then(|batch| unknown::tag_raw_columns(batch, &unknown)));
+        Ok(ClickHouseResponse::new(Box::pin(stream)))
+    }
+
+    /// Executes a `ClickHouse` query and writes the results to `writer` as newline-delimited
+    /// JSON (JSON Lines), one object per row.
+    ///
+    /// Batches are encoded and written as they arrive, so memory use is bounded by the size of
+    /// a single batch regardless of the size of the result. Values without a native JSON
+    /// representation (dates, decimals, binary) are rendered according to `options`. The
+    /// writer is flushed once the stream completes.
+    ///
+    /// # Parameters
+    /// - `query`: The SQL query to execute (e.g., `"SELECT * FROM my_table"`).
+    /// - `writer`: The destination, ie a file or socket.
+    /// - `options`: How values are rendered, see [`NdjsonOptions`].
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Returns
+    /// A [`Result`] containing the number of rows written.
+    ///
+    /// # Errors
+    /// - Fails if the query fails to execute or if connection issues occur.
+    /// - Fails if a column's type cannot be rendered as JSON.
+    /// - Fails if writing to `writer` fails.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    /// use clickhouse_arrow::arrow::ndjson::{BinaryFormat, NdjsonOptions};
+    ///
+    /// let client = Client::builder()
+    ///     .with_endpoint("localhost:9000")
+    ///     .build_arrow()
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// let file = tokio::fs::File::create("export.ndjson").await.unwrap();
+    /// let writer = tokio::io::BufWriter::new(file);
+    /// let options = NdjsonOptions::new().with_binary(BinaryFormat::Hex);
+    /// let rows = client
+    ///     .query_to_ndjson("SELECT * FROM my_table", writer, options, None)
+    ///     .await
+    ///     .unwrap();
+    /// println!("Exported {rows} rows");
+    /// ```
+    #[instrument(
+        skip_all,
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
+        )
+    )]
+    pub async fn query_to_ndjson<W: AsyncWrite + Unpin>(
+        &self,
+        query: impl Into<ParsedQuery>,
+        mut writer: W,
+        options: NdjsonOptions,
+        qid: Option<Qid>,
+    ) -> Result<u64> {
+        let encoder = NdjsonEncoder::new(options);
+        let mut stream = self.query(query, qid).await?;
+        let mut buffer = Vec::new();
+        let mut rows = 0;
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+            buffer.clear();
+            encoder.encode(&batch, &mut buffer)?;
+            writer.write_all(&buffer).await?;
+            rows += batch.num_rows() as u64;
+        }
+        writer.flush().await?;
+        Ok(rows)
     }
 
     /// Executes a `ClickHouse` query with result limits and streams Arrow [`RecordBatch`] results.
@@ -1589,7 +2372,13 @@ impl Client<ArrowFormat> {
     /// ```
     #[instrument(
         skip_all,
-        fields(db.system = "clickhouse", db.operation = "query", clickhouse.query.id)
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
+        )
     )]
     pub async fn query_with_limits(
         &self,
@@ -1647,7 +2436,13 @@ impl Client<ArrowFormat> {
     /// ```
     #[instrument(
         skip_all,
-        fields(db.system = "clickhouse", db.operation = "query", clickhouse.query.id)
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
+        )
     )]
     pub async fn query_with_limits_params(
         &self,
@@ -1656,8 +2451,12 @@ impl Client<ArrowFormat> {
         limits: QueryLimits,
         qid: Option<Qid>,
     ) -> Result<LimitedResponse<ClickHouseResponse<RecordBatch>>> {
-        let inner = self.query_params(query, params, qid).await?;
-        Ok(LimitedResponse::new(inner, limits))
+        let (query, qid) = record_query(qid, query.into(), self.client_id, self.redact_queries());
+        let timing = Arc::new(QueryTiming::default());
+        let overrides = QueryOverrides { timing: Some(Arc::clone(&timing)), ..Default::default() };
+        let stream = self.query_raw_inner(query, params, qid, overrides).await?;
+        let inner = ClickHouseResponse::new(Box::pin(stream));
+        Ok(LimitedResponse::new(inner, limits).with_timing(timing))
     }
 
     /// Executes a `ClickHouse` query with unified options.
@@ -1667,13 +2466,15 @@ impl Client<ArrowFormat> {
     /// - Result limits (memory, rows, batches)
     /// - EXPLAIN execution (parallel or explain-only)
     /// - Query ID
+    /// - Column projection, skipping unneeded columns without decoding them
+    /// - Exploding `Map` columns into `Struct` columns
     ///
     /// For simpler use cases, consider using [`Client::query`], [`Client::query_params`],
     /// or [`Client::query_with_limits`] instead.
     ///
     /// # Parameters
     /// - `query`: The SQL query to execute.
-    /// - `options`: Configuration for params, limits, explain, and query ID.
+    /// - `options`: Configuration for params, limits, explain, query ID, quota key, and projection.
     ///
     /// # Returns
     /// A [`Result`] containing a [`ClickHouseResponse<RecordBatch>`] that streams
@@ -1724,7 +2525,13 @@ impl Client<ArrowFormat> {
     /// ```
     #[instrument(
         skip_all,
-        fields(db.system = "clickhouse", db.operation = "query", clickhouse.query.id)
+        fields(
+            db.system = "clickhouse",
+            db.operation = "query",
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
+        )
     )]
     pub async fn query_with_options(
         &self,
@@ -1821,13 +2628,69 @@ impl Client<ArrowFormat> {
             });
         }
 
-        // Execute the actual query
-        let (query_str, recorded_qid) = record_query(Some(qid), parsed_query, self.client_id);
-        let stream = self.query_raw(query_str, options.params, recorded_qid).await?;
+        // Execute the actual query, as another user if configured
+        let parsed_query = match options.user_override.as_deref() {
+            Some(user) => execute_as_statement(user, &parsed_query)?.into(),
+            None => parsed_query,
+        };
+        let (query_str, recorded_qid) =
+            record_query(Some(qid), parsed_query, self.client_id, self.redact_queries());
+        let cancel_on_drop = options.has_result_limits() || options.sorted_by.is_some();
+        // Only limited responses report timings, so only record them when limits are set
+        let timing = options.limits.is_some().then(|| Arc::new(QueryTiming::default()));
+        // Exploding maps reshapes the result, so its header doesn't describe the batches
+        let (sender, header) = if options.explode_maps.is_none() {
+            let arrow_options = self.connection.metadata().arrow_options;
+            let projection = options.projection.clone();
+            let rescale = options.decimal_rescale.clone();
+            let (sender, header) = ResultHeader::channel(arrow_options, projection, rescale);
+            (Some(sender), Some(header))
+        } else {
+            (None, None)
+        };
+        let overrides = QueryOverrides {
+            settings:        self.query_settings(options.settings, options.comment.as_deref()),
+            quota_key:       options.quota_key,
+            projection:      options.projection,
+            decimal_rescale: options.decimal_rescale,
+            timing:          timing.clone(),
+            progress:        options.progress,
+            read_ahead:      options.read_ahead,
+            cancel_on_drop,
+            header:          sender,
+        };
+        let stream =
+            self.query_raw_inner(query_str, options.params, recorded_qid, overrides).await?;
+
+        // Validate ordering before any columns are reshaped, failing on the first unordered row
+        let mut ordering = options.sorted_by.map(OrderValidator::new);
+        let stream = stream.map(move |batch| match ordering.as_mut() {
+            Some(ordering) => batch.and_then(|batch| ordering.check(&batch).map(|()| batch)),
+            None => batch,
+        });
+
+        // Explode map columns, tracking keys across the batches of this result
+        let mut exploder = options.explode_maps.map(MapExploder::new);
+        let stream = stream.map(move |batch| match exploder.as_mut() {
+            Some(exploder) => batch.and_then(|batch| exploder.explode(batch)),
+            None => batch,
+        });
+
+        // Fail and cancel the query if it returns more rows or bytes than allowed
+        let stream = ResultGuard::new(
+            stream,
+            options.max_result_rows,
+            options.max_result_bytes,
+            options.partial_results,
+        );
 
         // Wrap in limited response if limits are configured
         let response = if let Some(limits) = options.limits {
-            let limited = LimitedResponse::new(ClickHouseResponse::from_stream(stream), limits);
+            let mut limited =
+                LimitedResponse::new(ClickHouseResponse::from_stream(stream), limits);
+            if let Some(timing) = timing {
+                limited = limited.with_timing(timing);
+            }
             // Note: We lose the explain receiver here since LimitedResponse wraps
             // ClickHouseResponse For now, we'll handle this by not supporting limits +
             // explain together TODO: Consider wrapping LimitedResponse to preserve
@@ -1846,7 +2709,10 @@ impl Client<ArrowFormat> {
             ClickHouseResponse::new(Box::pin(stream))
         };
 
-        Ok(response)
+        Ok(match header {
+            Some(header) => response.with_header(header),
+            None => response,
+        })
     }
 
     /// Extract text from EXPLAIN result batches.
@@ -1916,7 +2782,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         ),
         skip_all
     )]
@@ -1925,7 +2793,8 @@ impl Client<ArrowFormat> {
         query: impl Into<ParsedQuery>,
         qid: Option<Qid>,
     ) -> Result<ClickHouseResponse<Vec<Value>>> {
-        let (query, qid) = record_query(qid, query.into(), self.client_id);
+        let (query, qid) = record_query(qid, query.into(), self.client_id, self.redact_queries());
+        let permit = self.throttle.acquire_query().await?;
         let connection = self.conn().await?;
 
         // Create metadata channel
@@ -1942,6 +2811,13 @@ impl Client<ArrowFormat> {
                     params: None,
                     response: tx,
                     header: Some(header_tx),
+                    quota_key: None,
+                    projection: None,
+                    decimal_rescale: None,
+                    timing: None,
+                    progress: None,
+                    read_ahead: None,
+                    cancel_on_drop: false,
                 },
                 qid,
                 true,
@@ -1971,7 +2847,7 @@ impl Client<ArrowFormat> {
         #[cfg(feature = "inner_pool")]
         connection.finish(conn_idx, Operation::<RecordBatch>::weight_insert_many());
 
-        Ok(ClickHouseResponse::from_stream(response))
+        Ok(ClickHouseResponse::from_stream(hold_permit(response, permit)))
     }
 
     /// Executes a `ClickHouse` query and returns the first column of the first batch.
@@ -2022,7 +2898,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn query_column(
@@ -2077,7 +2955,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn query_column_params(
@@ -2143,7 +3023,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn query_one(
@@ -2197,7 +3079,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn query_one_params(
@@ -2220,6 +3104,347 @@ impl Client<ArrowFormat> {
         }
     }
 
+    /// Inserts a range of rows from a [`RecordBatch`] into `table` without copying data.
+    ///
+    /// The rows in `range` are sliced from `batch` with [`RecordBatch::slice`], which only
+    /// adjusts array offsets, and the serializers honor those offsets so that only the selected
+    /// rows are written. This allows chunking large batches for insert without copying them.
+    ///
+    /// # Parameters
+    /// - `table`: The table to insert into, optionally qualified by database (e.g. `"db.events"`).
+    /// - `batch`: The batch containing the rows to insert.
+    /// - `range`: The range of rows in `batch` to insert.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Returns
+    /// A [`Result`] containing a stream of [`Result<()>`], as returned by [`Client::insert`].
+    ///
+    /// # Errors
+    /// - Returns [`Error::Client`] if `range` is out of bounds for `batch`.
+    /// - Fails for the same reasons as [`Client::insert`].
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let client = Client::builder()
+    ///     .with_endpoint("localhost:9000")
+    ///     .build_arrow()
+    ///     .await?;
+    ///
+    /// // Insert the batch in chunks of 10,000 rows
+    /// for start in (0..batch.num_rows()).step_by(10_000) {
+    ///     let end = (start + 10_000).min(batch.num_rows());
+    ///     client.insert_slice("db.events", &batch, start..end, None).await?.collect::<Vec<_>>().await;
+    /// }
+    /// ```
+    pub async fn insert_slice(
+        &self,
+        table: &str,
+        batch: &RecordBatch,
+        range: std::ops::Range<usize>,
+        qid: Option<Qid>,
+    ) -> Result<impl Stream<Item = Result<()>> + '_> {
+        if range.start > range.end || range.end > batch.num_rows() {
+            return Err(Error::Client(format!(
+                "Insert range {range:?} out of bounds for batch of {} rows",
+                batch.num_rows()
+            )));
+        }
+        let slice = batch.slice(range.start, range.len());
+        self.insert(format!("INSERT INTO {table} FORMAT Native"), slice, qid).await
+    }
+
+    /// Fetches the default declarations of `table`'s columns, caching them on the client.
+    ///
+    /// Returns the columns declared with a `DEFAULT`, `MATERIALIZED`, `ALIAS` or `EPHEMERAL`
+    /// expression, read from `system.columns`. Later calls for the same `table` are served from
+    /// the cache, which is shared by clones of this client, until invalidated with
+    /// [`Client::invalidate_column_defaults`], ie after altering the table.
+    ///
+    /// # Parameters
+    /// - `table`: The table, optionally qualified by database (e.g. `"db.events"`).
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Errors
+    /// - Returns [`Error::UndefinedTables`] if `table` does not exist.
+    /// - Fails if the query encounters a `ClickHouse` error.
+    pub async fn fetch_column_defaults(
+        &self,
+        table: &str,
+        qid: Option<Qid>,
+    ) -> Result<Arc<ColumnDefaults>> {
+        if let Some(defaults) = self.defaults.get(table) {
+            return Ok(defaults);
+        }
+        let defaults = Arc::new(crate::defaults::fetch_column_defaults(self, table, qid).await?);
+        self.defaults.insert(table, Arc::clone(&defaults));
+        Ok(defaults)
+    }
+
+    /// Drops the cached column defaults of `table`, or of all tables if `None`, see
+    /// [`Client::fetch_column_defaults`].
+    pub fn invalidate_column_defaults(&self, table: Option<&str>) {
+        self.defaults.invalidate(table);
+    }
+
+    /// Inserts a [`RecordBatch`] into `table`, taking its column defaults into account.
+    ///
+    /// `MATERIALIZED` and `ALIAS` columns cannot be inserted into, so when `batch` contains any
+    /// they are dropped or rejected according to `policy`, rather than failing on the server.
+    /// The insert names the remaining columns, so table columns missing from `batch` are filled
+    /// in with their `DEFAULT` expressions. Column defaults are cached as in
+    /// [`Client::fetch_column_defaults`].
+    ///
+    /// # Parameters
+    /// - `table`: The table, optionally qualified by database (e.g. `"db.events"`).
+    /// - `batch`: The batch to insert.
+    /// - `policy`: What to do with `MATERIALIZED` and `ALIAS` columns present in `batch`.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Returns
+    /// A [`Result`] containing a stream of [`Result<()>`], as returned by [`Client::insert`].
+    ///
+    /// # Errors
+    /// - Returns [`Error::Client`] naming the columns if `batch` contains `MATERIALIZED` or `ALIAS`
+    ///   columns and `policy` is [`GeneratedColumnPolicy::Error`].
+    /// - Fails if the column defaults cannot be fetched, see [`Client::fetch_column_defaults`].
+    /// - Fails for the same reasons as [`Client::insert`].
+    pub async fn insert_table(
+        &self,
+        table: &str,
+        batch: RecordBatch,
+        policy: GeneratedColumnPolicy,
+        qid: Option<Qid>,
+    ) -> Result<impl Stream<Item = Result<()>> + '_> {
+        let defaults = self.fetch_column_defaults(table, None).await?;
+        let batch = crate::defaults::prepare_insert(table, batch, &defaults, policy)?;
+        let query = crate::defaults::insert_statement(table, &batch)?;
+        self.insert(query, batch, qid).await
+    }
+
+    /// Samples up to `rows` rows from `table` and summarizes each column, for data previews.
+    ///
+    /// Sampling avoids `ORDER BY rand()`, which reads and sorts the whole table. The row count
+    /// and sampling key of `table` are read from `system.tables`, then:
+    /// - Tables with no more than `rows` rows are read with a plain `LIMIT`.
+    /// - Tables with a sampling key are sampled with the `SAMPLE` clause.
+    /// - Other tables are filtered with `rand()`, stopping as soon as `LIMIT` is reached.
+    ///
+    /// The sampling rate is chosen to yield about twice the requested rows, so the returned
+    /// batch contains `rows` rows in most cases, but may contain fewer for sampled tables.
+    ///
+    /// # Parameters
+    /// - `table`: The table to preview, optionally qualified by database (e.g. `"db.events"`).
+    /// - `rows`: The maximum number of rows to return.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Returns
+    /// A [`Result`] containing a [`crate::explore::Preview`] with the sampled rows, a
+    /// [`crate::explore::ColumnSummary`] for each column, and the sampling method used.
+    ///
+    /// # Errors
+    /// - Returns [`Error::UndefinedTables`] if `table` does not exist.
+    /// - Fails if either query execution encounters a `ClickHouse` error.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let client = Client::builder()
+    ///     .with_endpoint("localhost:9000")
+    ///     .build_arrow()
+    ///     .await?;
+    ///
+    /// let preview = client.preview("db.events", 100, None).await?;
+    /// println!("Sampled {} rows via {:?}", preview.batch.num_rows(), preview.method);
+    /// ```
+    pub async fn preview(
+        &self,
+        table: &str,
+        rows: usize,
+        qid: Option<Qid>,
+    ) -> Result<crate::explore::Preview> {
+        crate::explore::preview(self, table, rows, qid).await
+    }
+
+    /// Creates an [`crate::sink::InsertSink`] for `table`, a long-running insert sink that
+    /// watches the table for schema drift.
+    ///
+    /// The sink re-reads the table schema every `options.check_interval`, before inserting the
+    /// next batch. When the table changed, the drift is failed on, accepted, or reconciled by
+    /// adapting batches to the new schema, as decided by the configured
+    /// [`crate::sink::DriftAction`] or [`crate::sink::DriftHook`].
+    ///
+    /// # Parameters
+    /// - `table`: The target table, optionally qualified by database (e.g. `"db.events"`).
+    /// - `options`: The check interval and drift handling.
+    ///
+    /// # Errors
+    /// - Returns [`Error::UndefinedTables`] if `table` does not exist.
+    /// - Fails if reading the table schema encounters a `ClickHouse` error.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    /// use clickhouse_arrow::sink::{DriftAction, InsertSinkOptions};
+    ///
+    /// let options = InsertSinkOptions::default().with_drift_action(DriftAction::Reconcile);
+    /// let mut sink = client.insert_sink("db.events", options).await?;
+    /// sink.insert(batch).await?;
+    /// ```
+    pub async fn insert_sink(
+        &self,
+        table: &str,
+        options: crate::sink::InsertSinkOptions,
+    ) -> Result<crate::sink::InsertSink> {
+        crate::sink::insert_sink(self, table, options).await
+    }
+
+    /// Creates a staging table for `table` and returns a [`crate::staging::StagedInsert`] that
+    /// inserts batches into it, moving them into `table` on commit.
+    ///
+    /// This gives pseudo-transactional batch loads: readers of `table` see either none or all
+    /// of the staged batches. Plain `MergeTree` family tables are committed by attaching the
+    /// staged partitions, other tables with a single `INSERT ... SELECT`, see
+    /// [`crate::staging::StageStrategy`].
+    ///
+    /// # Parameters
+    /// - `table`: The target table, optionally qualified by database (e.g. `"db.events"`).
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Errors
+    /// - Returns [`Error::UndefinedTables`] if `table` does not exist.
+    /// - Fails if creating the staging table encounters a `ClickHouse` error.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let mut stager = client.staged_insert("db.events", None).await?;
+    /// stager.insert(batch_1).await?;
+    /// stager.insert(batch_2).await?;
+    /// let rows = stager.commit().await?;
+    /// ```
+    pub async fn staged_insert(
+        &self,
+        table: &str,
+        qid: Option<Qid>,
+    ) -> Result<crate::staging::StagedInsert> {
+        crate::staging::staged_insert(self, table, qid).await
+    }
+
+    /// Inserts `batch` into a `ReplacingMergeTree` or `CollapsingMergeTree` table as an upsert,
+    /// filling the engine's version and sign columns when the batch does not include them.
+    ///
+    /// See [`crate::upsert`] for the recipe and [`crate::upsert::UpsertOptions`] for how the
+    /// version is chosen and whether the table is optimized afterwards.
+    ///
+    /// # Parameters
+    /// - `table`: The target table, optionally qualified by database (e.g. `"db.users"`).
+    /// - `batch`: The rows to upsert.
+    /// - `options`: The version column, how it is filled, and whether to optimize.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Errors
+    /// - Returns [`Error::UndefinedTables`] if `table` does not exist.
+    /// - Fails if the table's engine does not deduplicate rows, or `options` names a version column
+    ///   other than the engine's.
+    /// - Fails if the insert or optimize encounters a `ClickHouse` error.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::upsert::{UpsertOptions, VersionFill};
+    ///
+    /// let options = UpsertOptions::default().with_version(VersionFill::Increment).with_optimize();
+    /// client.upsert("db.users", batch, options, None).await?;
+    /// ```
+    pub async fn upsert(
+        &self,
+        table: &str,
+        batch: RecordBatch,
+        options: crate::upsert::UpsertOptions,
+        qid: Option<Qid>,
+    ) -> Result<()> {
+        let arrow_options = self.connection.metadata().arrow_options;
+        crate::upsert::upsert(self, table, batch, options, arrow_options, qid).await
+    }
+
+    /// Updates rows of a `CollapsingMergeTree` or `VersionedCollapsingMergeTree` table from their
+    /// `old_batch` state to their `new_batch` state.
+    ///
+    /// A cancel row with sign `-1` is inserted for every row of `old_batch`, and a state row with
+    /// sign `1` for every row of `new_batch`, in a single insert. See
+    /// [`crate::upsert::collapse_rows`] for how the sign and version columns are filled.
+    ///
+    /// # Parameters
+    /// - `table`: The target table, optionally qualified by database (e.g. `"db.balances"`).
+    /// - `old_batch`: The currently stored state of the rows, exactly as inserted.
+    /// - `new_batch`: The new state of the rows.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Errors
+    /// - Returns [`Error::UndefinedTables`] if `table` does not exist.
+    /// - Fails if the table's engine is not a collapsing engine, or the batches' columns differ.
+    /// - Fails if the insert encounters a `ClickHouse` error.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// // CREATE TABLE balances (id UInt64, amount Int64, sign Int8)
+    /// // ENGINE = CollapsingMergeTree(sign) ORDER BY id
+    /// client.collapse_update("balances", &stored, &updated, None).await?;
+    /// ```
+    pub async fn collapse_update(
+        &self,
+        table: &str,
+        old_batch: &RecordBatch,
+        new_batch: &RecordBatch,
+        qid: Option<Qid>,
+    ) -> Result<()> {
+        crate::upsert::collapse_update(self, table, old_batch, new_batch, qid).await
+    }
+
+    /// Computes statistics for `columns` of `table` using a single aggregate query.
+    ///
+    /// For each column, the minimum and maximum non-null values, the null count and fraction,
+    /// and an estimate of the number of distinct values (using `uniq`) are computed over every
+    /// row of the table, along with the table's row count. This is useful for query planners and
+    /// data catalogs. For a cheaper summary of a sample of rows, see [`Client::preview`].
+    ///
+    /// # Parameters
+    /// - `table`: The table to analyze, optionally qualified by database (e.g. `"db.events"`).
+    /// - `columns`: The columns to compute statistics for.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Returns
+    /// A [`Result`] containing a [`crate::explore::TableStats`] with a
+    /// [`crate::explore::ColumnStats`] for each requested column, in order.
+    ///
+    /// # Errors
+    /// - Returns [`Error::Client`] if `columns` is empty.
+    /// - Fails if the query execution encounters a `ClickHouse` error (e.g., unknown column).
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let client = Client::builder()
+    ///     .with_endpoint("localhost:9000")
+    ///     .build_arrow()
+    ///     .await?;
+    ///
+    /// let stats = client.column_stats("db.events", &["user_id", "country"], None).await?;
+    /// println!("{} rows, ~{} users", stats.row_count, stats.columns[0].distinct_estimate);
+    /// ```
+    pub async fn column_stats(
+        &self,
+        table: &str,
+        columns: &[&str],
+        qid: Option<Qid>,
+    ) -> Result<crate::explore::TableStats> {
+        crate::explore::column_stats(self, table, columns, qid).await
+    }
+
     /// Fetches the list of database names (schemas) in `ClickHouse`.
     ///
     /// This method queries `ClickHouse` to retrieve the names of all databases
@@ -2257,7 +3482,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn fetch_schemas(&self, qid: Option<Qid>) -> Result<Vec<String>> {
@@ -2304,7 +3531,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn fetch_all_tables(&self, qid: Option<Qid>) -> Result<HashMap<String, Vec<String>>> {
@@ -2350,7 +3579,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn fetch_tables(
@@ -2408,7 +3639,9 @@ impl Client<ArrowFormat> {
             db.operation = "query",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn fetch_schema(
@@ -2422,6 +3655,106 @@ impl Client<ArrowFormat> {
         crate::arrow::schema::fetch_schema(self, database, tables, qid, options).await
     }
 
+    /// Compares an Arrow schema against a `ClickHouse` table, reporting for each field whether it
+    /// can be inserted as-is, needs a cast, may lose data, or cannot be inserted, and the
+    /// `ClickHouse` type it maps to.
+    ///
+    /// See [`crate::arrow::compat`] for details.
+    ///
+    /// # Errors
+    /// - Fails if the table's columns cannot be fetched or the table does not exist.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// let report = client.compare_schema(None, "events", &batch.schema(), None).await?;
+    /// println!("{report}");
+    /// assert!(report.is_compatible());
+    /// ```
+    pub async fn compare_schema(
+        &self,
+        database: Option<&str>,
+        table: &str,
+        schema: &arrow::datatypes::Schema,
+        qid: Option<Qid>,
+    ) -> Result<CompatibilityReport> {
+        let database = database.unwrap_or(self.connection.database());
+        let columns =
+            crate::arrow::schema::fetch_column_types(self, Some(database), table, qid).await?;
+        if columns.is_empty() {
+            return Err(Error::UndefinedTables {
+                db:     database.to_string(),
+                tables: vec![table.to_string()],
+            });
+        }
+        let options = self.connection.metadata().arrow_options;
+        Ok(compare_schemas(schema, &columns, Some(options)))
+    }
+
+    /// Dry runs the insert of `batch` into `table`, reporting what happens to each column
+    /// without sending any data.
+    ///
+    /// The batch goes through the client's insert masking and block validator, then each column
+    /// is matched to the table's column, has the [`ArrowOptions`] null policy and timestamp
+    /// localization applied, and is serialized to a discarded buffer. Masking audit hooks are not
+    /// called. See [`crate::arrow::conversion`] for details.
+    ///
+    /// # Parameters
+    /// - `table`: The table, optionally qualified by database (e.g. `"db.events"`).
+    /// - `batch`: The batch to check.
+    /// - `qid`: Optional query ID for tracking and debugging.
+    ///
+    /// # Returns
+    /// A [`Result`] containing a [`ConversionReport`] with the conversions applied to each column
+    /// and why columns would fail to insert. Failures are reported, not returned as errors, use
+    /// [`ConversionReport::into_result`] to fail on them.
+    ///
+    /// # Errors
+    /// - Returns [`Error::UndefinedTables`] if `table` does not exist.
+    /// - Fails if the table's columns cannot be fetched.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// let report = client.validate_batch("db.events", &batch, None).await?;
+    /// println!("{report}");
+    /// let _ = report.into_result()?;
+    /// ```
+    pub async fn validate_batch(
+        &self,
+        table: &str,
+        batch: &RecordBatch,
+        qid: Option<Qid>,
+    ) -> Result<ConversionReport> {
+        let (database, name) = crate::explore::split_table(table);
+        let columns =
+            crate::arrow::schema::fetch_column_types(self, database.as_deref(), &name, qid).await?;
+        if columns.is_empty() {
+            let db = database.unwrap_or_else(|| "currentDatabase()".into());
+            return Err(Error::UndefinedTables { db, tables: vec![name] });
+        }
+
+        // Mask as `Client::insert` would, leaving the audit hooks to real inserts
+        let (batch, applied) = match self.masking.as_ref() {
+            Some(masking) => {
+                let database = database.as_deref().unwrap_or(self.connection.database());
+                let database = if database.is_empty() { "default" } else { database };
+                let (batch, audit) =
+                    masking.apply_unaudited(&format!("{database}.{name}"), batch.clone())?;
+                (batch, audit.applied)
+            }
+            None => (batch.clone(), vec![]),
+        };
+
+        let options = self.connection.metadata().arrow_options;
+        let mut report = plan_conversion(&batch, &columns, options);
+        record_masks(&mut report, applied);
+        report.validation = self
+            .validator
+            .as_ref()
+            .and_then(|validator| validator.validate(&batch).err())
+            .map(|error| error.to_string());
+        Ok(report)
+    }
+
     /// Issues a `CREATE TABLE` DDL statement for a table using Arrow schema.
     ///
     /// Creates a table in the specified database (or the client's default database if
@@ -2471,7 +3804,9 @@ impl Client<ArrowFormat> {
             db.operation = "create.table",
             db.format = ArrowFormat::FORMAT,
             clickhouse.client.id = self.client_id,
-            clickhouse.query.id
+            clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            db.query.text
         )
     )]
     pub async fn create_table(
@@ -2494,6 +3829,40 @@ impl Client<ArrowFormat> {
         self.execute(stmt, qid).await?;
         Ok(())
     }
+
+    /// Probes the server, measuring round trip time and estimating clock skew.
+    ///
+    /// The returned [`ProbeResult`] also includes the server version and default timezone, which
+    /// helps diagnose shifted `DateTime` values and choose replicas by latency.
+    ///
+    /// # Errors
+    /// - Fails if the ping or the probe query fails.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let probe = client.probe(None).await?;
+    /// if probe.clock_skew_exceeds(std::time::Duration::from_secs(1)) {
+    ///     eprintln!("server clock is off by {}us", probe.clock_skew_micros);
+    /// }
+    /// ```
+    pub async fn probe(&self, qid: Option<Qid>) -> Result<ProbeResult> {
+        self.probe_with(|| async {
+            let batch = self
+                .query_one(PROBE_QUERY, qid)
+                .await?
+                .filter(|batch| batch.num_rows() > 0 && batch.num_columns() == 2)
+                .ok_or_else(|| Error::Protocol("Probe query returned no rows".into()))?;
+            let now = cast(batch.column(0), &DataType::Int64)?;
+            let timezone = cast(batch.column(1), &DataType::Utf8)?;
+            Ok((
+                now.as_primitive::<Int64Type>().value(0),
+                timezone.as_string::<i32>().value(0).to_string(),
+            ))
+        })
+        .await
+    }
 }
 
 impl<T: ClientFormat> Drop for Client<T> {
@@ -2502,12 +3871,19 @@ impl<T: ClientFormat> Drop for Client<T> {
     }
 }
 
-/// Simple helper to log query id and client id
-fn record_query(qid: Option<Qid>, query: ParsedQuery, cid: u16) -> (String, Qid) {
+/// Simple helper to log query id, query fingerprint, and client id. The raw query is only
+/// logged if `redact` is not set.
+fn record_query(qid: Option<Qid>, query: ParsedQuery, cid: u16, redact: bool) -> (String, Qid) {
     let qid = qid.unwrap_or_default();
-    let _ = Span::current().record(ATT_QID, tracing::field::display(qid));
+    let span = Span::current();
+    let _ = span.record(ATT_QID, tracing::field::display(qid));
     let query = query.0;
-    trace!(query, { ATT_CID } = cid, "Querying clickhouse");
+    let _ = record_query_span(&span, &query, redact);
+    if redact {
+        trace!({ ATT_CID } = cid, "Querying clickhouse");
+    } else {
+        trace!(query, { ATT_CID } = cid, "Querying clickhouse");
+    }
     (query, qid)
 }
 
@@ -2527,7 +3903,7 @@ mod tests {
         let cid = 123;
         let qid = Qid::new();
 
-        let (parsed_query, returned_qid) = record_query(Some(qid), query, cid);
+        let (parsed_query, returned_qid) = record_query(Some(qid), query, cid, false);
 
         assert_eq!(parsed_query, "SELECT 1");
         assert_eq!(returned_qid, qid);
@@ -2538,7 +3914,7 @@ mod tests {
         let query = ParsedQuery("SELECT 2".to_string());
         let cid = 456;
 
-        let (parsed_query, returned_qid) = record_query(None, query, cid);
+        let (parsed_query, returned_qid) = record_query(None, query, cid, true);
 
         assert_eq!(parsed_query, "SELECT 2");
         // Should generate a default Qid
@@ -2554,7 +3930,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_split_record_batch_function() {
-        // Test the split_record_batch function that insert_max_rows uses
+        // Test the split_record_batch function that inserts use to honor max_insert_block_size
         let batch = create_test_record_batch();
         let max_rows = 2;
 
@@ -2588,4 +3964,52 @@ mod tests {
             assert_eq!(batch.num_rows(), 1);
         }
     }
+
+    #[test]
+    fn test_arrow_format_split_insert_blocks() {
+        use crate::formats::sealed::ClientFormatImpl;
+
+        let batch = create_test_record_batch();
+        let blocks = <ArrowFormat as ClientFormatImpl<RecordBatch>>::split(batch.clone(), 2);
+        assert_eq!(blocks.iter().map(RecordBatch::num_rows).collect::<Vec<_>>(), vec![2, 2, 1]);
+
+        // A max of 0 must never drop data
+        let blocks = <ArrowFormat as ClientFormatImpl<RecordBatch>>::split(batch, 0);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].num_rows(), 5);
+    }
+
+    #[test]
+    fn test_arrow_format_coalesce_insert_blocks() {
+        use crate::formats::sealed::ClientFormatImpl;
+
+        let coalesce = |batches: Vec<RecordBatch>, coalescing: InsertCoalescing| {
+            <ArrowFormat as ClientFormatImpl<RecordBatch>>::coalesce(batches, coalescing).unwrap()
+        };
+        let rows = |blocks: &[RecordBatch]| -> Vec<usize> {
+            blocks.iter().map(RecordBatch::num_rows).collect()
+        };
+
+        let batch = create_test_record_batch();
+        let blocks = coalesce(vec![batch.clone(); 7], InsertCoalescing::new(12));
+        assert_eq!(rows(&blocks), vec![10, 10, 10, 5]);
+        let ids = blocks[0].column(0).as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(ids.values().to_vec(), vec![1, 2, 3, 4, 5, 1, 2, 3, 4, 5]);
+
+        // Blocks at the threshold are sent unchanged, a different schema starts a new block
+        let large = crate::arrow::utils::coalesce_record_batches(vec![batch.clone(); 4], 20, None)
+            .unwrap()
+            .remove(0);
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, true)]));
+        let nullable = RecordBatch::try_new(schema, vec![Arc::clone(batch.column(0))]).unwrap();
+        let batches = vec![batch.clone(), large, batch.clone(), nullable, batch.clone()];
+        let blocks = coalesce(batches, InsertCoalescing::new(20));
+        assert_eq!(rows(&blocks), vec![5, 20, 5, 5, 5]);
+
+        // The byte threshold applies as well
+        let bytes = batch.get_array_memory_size();
+        let coalescing = InsertCoalescing::new(1000).with_max_bytes(bytes * 2);
+        let blocks = coalesce(vec![batch; 5], coalescing);
+        assert_eq!(rows(&blocks), vec![10, 10, 5]);
+    }
 }
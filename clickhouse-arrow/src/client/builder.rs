@@ -5,16 +5,26 @@ use std::sync::Arc;
 
 use tracing::error;
 
+#[cfg(feature = "ssh")]
+use super::SshKeyOptions;
+use super::endpoints::Endpoints;
 use super::tcp::Destination;
 use super::{
-    ArrowOptions, Client, ClientFormat, CompressionMethod, ConnectionContext, Extension, Secret,
+    ArrowOptions, Client, ClientFormat, ClientHooks, ClientInfoOptions, CompressionMethod,
+    ConnectionContext, CredentialsProvider, EndpointStrategy, Extension, FrameLimits,
+    InsertCoalescing, InsertRateLimit, QueryQueue, ReconnectPolicy, Secret,
 };
+use crate::masking::InsertMasking;
 #[cfg(feature = "pool")]
-use crate::pool::ConnectionManager;
+use crate::pool::{ClientPool, ConnectionManager};
 use crate::prelude::SettingValue;
 use crate::settings::Settings;
-use crate::telemetry::TraceContext;
-use crate::{ArrowFormat, ClientOptions, Error, NativeFormat, Result};
+use crate::telemetry::{
+    ConnectEvent, ConnectionHooks, DisconnectEvent, ReconnectAttemptEvent, StatementHook,
+    TraceContext,
+};
+use crate::validation::BlockValidator;
+use crate::{ArrowFormat, ClientOptions, Error, LazyArrowFormat, NativeFormat, Result};
 
 /// A builder for configuring and creating a `ClickHouse` client.
 ///
@@ -22,7 +32,8 @@ use crate::{ArrowFormat, ClientOptions, Error, NativeFormat, Result};
 /// custom connection parameters, such as the server address, credentials, TLS,
 /// compression, and session settings. It supports creating either a single
 /// [`Client`] (via [`ClientBuilder::build`]) or a connection pool (via
-/// [`ClientBuilder::build_pool_manager`], with the `pool` feature enabled).
+/// [`ClientBuilder::build_pool`] or [`ClientBuilder::build_pool_manager`], with the `pool` feature
+/// enabled).
 ///
 /// Use this builder for fine-grained control over the client configuration. The
 /// builder ensures that the destination address is verified before establishing a
@@ -47,9 +58,11 @@ use crate::{ArrowFormat, ClientOptions, Error, NativeFormat, Result};
 #[derive(Default, Debug, Clone)]
 pub struct ClientBuilder {
     destination: Option<Destination>,
+    endpoints:   Option<Endpoints>,
     options:     ClientOptions,
     settings:    Option<Settings>,
     context:     Option<ConnectionContext>,
+    hooks:       ClientHooks,
     verified:    bool,
 }
 
@@ -75,9 +88,11 @@ impl ClientBuilder {
     pub fn new() -> Self {
         ClientBuilder {
             destination: None,
+            endpoints:   None,
             options:     ClientOptions::default(),
             settings:    None,
             context:     None,
+            hooks:       ClientHooks::default(),
             verified:    false,
         }
     }
@@ -278,6 +293,44 @@ impl ClientBuilder {
         D: Into<Destination>,
     {
         self.destination = Some(destination.into());
+        self.endpoints = None;
+        self.verified = false;
+        self
+    }
+
+    /// Sets several `ClickHouse` server addresses to fail over between, such as the replicas of
+    /// a cluster.
+    ///
+    /// Whenever the client establishes a connection, including reconnects (see
+    /// [`ClientBuilder::with_reconnect`]) and the connections of a pool, it tries the endpoints
+    /// in the order picked by `strategy` and uses the first one it can connect to. The first
+    /// endpoint is also returned by [`ClientBuilder::destination`]. Endpoints that cannot be
+    /// resolved are skipped, failing only if none resolves. Unless a domain is set with
+    /// [`ClientBuilder::with_domain`], TLS connections verify each endpoint against its own
+    /// host.
+    ///
+    /// # Parameters
+    /// - `endpoints`: The `ClickHouse` server addresses, convertible to [`Destination`].
+    /// - `strategy`: The order the endpoints are tried in, see [`EndpointStrategy`].
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated endpoints.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoints(&["ch-1:9000", "ch-2:9000", "ch-3:9000"], EndpointStrategy::RoundRobin);
+    /// ```
+    #[must_use]
+    pub fn with_endpoints<D>(mut self, endpoints: &[D], strategy: EndpointStrategy) -> Self
+    where
+        D: Into<Destination> + Clone,
+    {
+        let destinations = endpoints.iter().cloned().map(Into::into).collect::<Vec<_>>();
+        self.destination = destinations.first().cloned();
+        self.endpoints = Some(Endpoints::new(destinations, strategy));
         self.verified = false;
         self
     }
@@ -388,10 +441,697 @@ impl ClientBuilder {
     /// environments where IPv6 is unsupported.
     ///
     /// # Parameters
-    /// - `enabled`: If `true`, restricts resolution to IPv4; if `false`, allows both IPv4 and IPv6.
+    /// - `enabled`: If `true`, restricts resolution to IPv4; if `false`, allows both IPv4 and IPv6.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated IPv4 setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_ipv4_only(true);
+    /// ```
+    #[must_use]
+    pub fn with_ipv4_only(mut self, enabled: bool) -> Self {
+        self.options.ipv4_only = enabled;
+        self
+    }
+
+    /// Sets the `ClickHouse` session settings.
+    ///
+    /// This method configures the session settings (e.g., query timeouts, max rows) for
+    /// the client. These settings are applied to all queries executed by the client.
+    /// Use this to customize query behavior beyond the defaults.
+    ///
+    /// # Parameters
+    /// - `settings`: The session settings as an `impl Into<Settings>`.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated settings.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    /// use std::sync::Arc;
+    ///
+    /// let settings = vec![
+    ///     ("select_sequential_consistency", 1)
+    /// ];
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_settings(settings);
+    /// ```
+    #[must_use]
+    pub fn with_settings(mut self, settings: impl Into<Settings>) -> Self {
+        self.settings = Some(settings.into());
+        self
+    }
+
+    /// Set a `ClickHouse` session setting.
+    ///
+    /// This method configures the session settings (e.g., query timeouts, max rows) for
+    /// the client. These settings are applied to all queries executed by the client.
+    /// Use this to customize query behavior beyond the defaults.
+    ///
+    /// # Parameters
+    /// - `key`: The session setting's name.
+    /// - `setting`: The session setting as an `impl Into<SettingValue>`.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated settings.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    /// use std::sync::Arc;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_setting("select_sequential_consistency", 1);
+    /// ```
+    #[must_use]
+    pub fn with_setting(
+        mut self,
+        name: impl Into<String>,
+        setting: impl Into<SettingValue>,
+    ) -> Self {
+        let setting: SettingValue = setting.into();
+        let settings = self.settings.unwrap_or_default().with_setting(name, setting);
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Sets the username for authenticating with `ClickHouse`.
+    ///
+    /// This method configures the username used to authenticate the client with the
+    /// `ClickHouse` server. The default username is typically `"default"`, but this
+    /// can be customized based on the server's configuration.
+    ///
+    /// # Parameters
+    /// - `username`: The username for authentication.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated username.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_username("admin");
+    /// ```
+    #[must_use]
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.options.username = username.into();
+        self
+    }
+
+    /// Sets the password for authenticating with `ClickHouse`.
+    ///
+    /// This method configures the password used to authenticate the client with the
+    /// `ClickHouse` server. The password is stored securely as a [`Secret`]. If no
+    /// password is required, an empty string can be used.
+    ///
+    /// The native protocol always sends the password as entered: users identified with
+    /// `sha256_password`, `double_sha1_password`, or `bcrypt_password` are verified by hashing
+    /// it server-side. Enable TLS ([`ClientBuilder::with_tls`]) to protect it in transit, or use
+    /// SSH key authentication (`ssh` feature) to avoid sending a secret at all.
+    ///
+    /// # Parameters
+    /// - `password`: The password for authentication, convertible to [`Secret`].
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated password.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_username("admin")
+    ///     .with_password("secret");
+    /// ```
+    #[must_use]
+    pub fn with_password<T>(mut self, password: T) -> Self
+    where
+        Secret: From<T>,
+    {
+        self.options.password = Secret::from(password);
+        self
+    }
+
+    /// Authenticates with an SSH private key instead of a password.
+    ///
+    /// The user must be identified `WITH ssh_key BY KEY '...' TYPE '...'` on the server. During
+    /// the handshake the server sends a challenge which is signed with the key, so neither the key
+    /// nor a password leaves the client. Any configured password is ignored.
+    ///
+    /// # Parameters
+    /// - `options`: The private key file and optional passphrase, see [`SshKeyOptions`].
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with SSH key authentication configured.
+    ///
+    /// # Feature
+    /// Requires the `ssh` feature to be enabled.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_username("alice")
+    ///     .with_ssh_key(SshKeyOptions::new("/home/alice/.ssh/id_ed25519").with_passphrase("pw"));
+    /// ```
+    #[cfg(feature = "ssh")]
+    #[must_use]
+    pub fn with_ssh_key(mut self, options: SshKeyOptions) -> Self {
+        self.options.ext.ssh_key = Some(options);
+        self
+    }
+
+    /// Restricts the hosts the client may connect to.
+    ///
+    /// Entries are exact host names, wildcards like `*.example.com` matching any subdomain, IP
+    /// addresses, or CIDR networks like `10.0.0.0/8`. The destination's host and TLS domain must
+    /// match a name or wildcard entry, and if any networks are listed, every address the
+    /// destination resolves to must lie in one of them. This guards against connecting to an
+    /// unexpected server through a misconfigured endpoint or DNS record. Destinations are checked
+    /// during [`ClientBuilder::verify`] and again when connecting.
+    ///
+    /// # Parameters
+    /// - `hosts`: The allowed hosts and networks. An empty list allows every destination.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated allow-list.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("ch-1.prod.example.com:9440")
+    ///     .with_allowed_hosts(["*.prod.example.com", "10.20.0.0/16"]);
+    /// ```
+    #[must_use]
+    pub fn with_allowed_hosts(
+        mut self,
+        hosts: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.options.ext = self.options.ext.with_allowed_hosts(hosts);
+        self
+    }
+
+    /// Pins the public keys TLS servers may present.
+    ///
+    /// Each pin is the hex-encoded SHA-256 hash of a DER-encoded `SubjectPublicKeyInfo`, as printed
+    /// by `openssl x509 -pubkey -noout -in server.crt | openssl pkey -pubin -outform der | openssl
+    /// dgst -sha256`. After the handshake, the public key of the server's certificate must match
+    /// one of the pins, in addition to passing certificate verification. Pin the next key as well
+    /// before rotating certificates.
+    ///
+    /// # Parameters
+    /// - `pins`: The allowed SPKI hashes, optionally colon separated or prefixed with `sha256:`.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated pins.
+    ///
+    /// # Feature
+    /// Requires the `rustls-tls` feature, connecting with pins through `native-tls` fails.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("ch.example.com:9440")
+    ///     .with_tls(true)
+    ///     .with_pinned_spki(["4a2a...e1c9"]);
+    /// ```
+    #[must_use]
+    pub fn with_pinned_spki(mut self, pins: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.options.ext = self.options.ext.with_pinned_spki(pins);
+        self
+    }
+
+    /// Sets the default database for the `ClickHouse` connection.
+    ///
+    /// This method configures the default database used by the client for queries and
+    /// operations. If not set, the `ClickHouse` server defaults to the `"default"`
+    /// database. Specifying a database is optional but recommended when working with a
+    /// specific database to avoid explicit database prefixes in queries.
+    ///
+    /// # Parameters
+    /// - `database`: The name of the default database.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated database.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_database("my_db");
+    /// ```
+    #[must_use]
+    pub fn with_database(mut self, database: impl Into<String>) -> Self {
+        self.options.default_database = database.into();
+        self
+    }
+
+    /// Sets the domain for secure TLS connections to `ClickHouse`.
+    ///
+    /// This method specifies the domain name used for TLS verification when connecting
+    /// to a `ClickHouse` server with TLS enabled (via [`ClientBuilder::with_tls`]). If
+    /// not set, the domain is inferred from the destination during verification. Use
+    /// this to explicitly set the domain for cloud or custom TLS configurations.
+    ///
+    /// # Parameters
+    /// - `domain`: The domain name for TLS verification.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated domain.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_tls(true)
+    ///     .with_domain("clickhouse.example.com");
+    /// ```
+    #[must_use]
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.options.domain = Some(domain.into());
+        self.verified = false;
+        self
+    }
+
+    /// Sets the compression method for data exchange with `ClickHouse`.
+    ///
+    /// This method configures the compression algorithm used for sending and receiving
+    /// data to/from `ClickHouse`. The default is [`CompressionMethod::LZ4`], which
+    /// balances performance and compression ratio. Other options may be available
+    /// depending on the `ClickHouse` server configuration.
+    ///
+    /// # Parameters
+    /// - `compression`: The compression method to use.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated compression setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_compression(CompressionMethod::LZ4);
+    /// ```
+    #[must_use]
+    pub fn with_compression(mut self, compression: CompressionMethod) -> Self {
+        self.options.compression = compression;
+        self
+    }
+
+    /// Sets the Arrow-specific options for `ClickHouse` connections.
+    ///
+    /// This method configures options specific to the Arrow format (used by
+    /// [`Client<ArrowFormat>`]), such as schema mapping or data type conversions. These options
+    /// are applied when the client is built with [`ClientBuilder::build`] for
+    /// [`ArrowFormat`]. Use this to customize Arrow interoperability.
+    ///
+    /// # Parameters
+    /// - `options`: The Arrow-specific options.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated Arrow options.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let arrow_options = ArrowOptions::default();
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_arrow_options(arrow_options);
+    /// ```
+    #[must_use]
+    pub fn with_arrow_options(mut self, options: ArrowOptions) -> Self {
+        self.options.ext.arrow = Some(options);
+        self
+    }
+
+    /// Sets the quota key reported to `ClickHouse` for this client.
+    ///
+    /// The quota key is sent in the handshake addendum and with every query's client info. Use it
+    /// with quotas defined as `KEYED BY client_key` to account usage per tenant or per
+    /// application, rather than per user.
+    ///
+    /// # Parameters
+    /// - `quota_key`: The quota key to report.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated quota key.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_quota_key("tenant-42");
+    /// ```
+    #[must_use]
+    pub fn with_quota_key(mut self, quota_key: impl Into<String>) -> Self {
+        self.options.ext.client_info.quota_key = Some(quota_key.into());
+        self
+    }
+
+    /// Sets a default comment recorded as `log_comment` in `system.query_log` for every query.
+    ///
+    /// Use it to attribute queries to the service and version that issued them. Per-query
+    /// comments set with `QueryOptions::with_comment` are appended to it.
+    ///
+    /// # Parameters
+    /// - `comment`: The comment to record.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the `log_comment` setting configured.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_default_comment(concat!("service=ingest version=", env!("CARGO_PKG_VERSION")));
+    /// ```
+    #[must_use]
+    pub fn with_default_comment(mut self, comment: impl AsRef<str>) -> Self {
+        self.settings = Some(self.settings.unwrap_or_default().with_log_comment(comment.as_ref()));
+        self
+    }
+
+    /// Sets the client name reported to `ClickHouse`.
+    ///
+    /// The name is sent in the hello packet and in the client info of every query, and appears
+    /// as `client_name` in `system.query_log` and `system.processes`. Use it so server-side logs
+    /// attribute queries to the application that issued them. Defaults to `ClickHouseArrow`.
+    ///
+    /// # Parameters
+    /// - `name`: The client name to report.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated client name.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_client_name("ingest-service");
+    /// ```
+    #[must_use]
+    pub fn with_client_name(mut self, name: impl Into<String>) -> Self {
+        self.options.ext.client_info.client_name = Some(name.into());
+        self
+    }
+
+    /// Sets all client info fields reported to `ClickHouse`.
+    ///
+    /// This replaces any quota key or client name set previously. See [`ClientInfoOptions`] for
+    /// the available fields, including client version, OS user, hostname, and distributed depth.
+    ///
+    /// # Parameters
+    /// - `client_info`: The client info overrides to report.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated client info.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_client_info(ClientInfoOptions::default().with_client_version(1, 2, 3));
+    /// ```
+    #[must_use]
+    pub fn with_client_info(mut self, client_info: ClientInfoOptions) -> Self {
+        self.options.ext.client_info = client_info;
+        self
+    }
+
+    /// Sets the maximum number of rows sent to `ClickHouse` in a single insert block.
+    ///
+    /// Batches passed to [`Client::insert`] or [`Client::insert_many`] with more rows than this
+    /// are split into multiple blocks before being sent, avoiding server memory spikes from very
+    /// large blocks. Splitting uses zero-copy slices for Arrow data. When unset, the
+    /// `max_insert_block_size` setting passed via [`ClientBuilder::with_settings`] is used, if
+    /// any, otherwise the server's `max_insert_block_size`.
+    ///
+    /// # Parameters
+    /// - `rows`: The maximum number of rows per insert block.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated insert block size.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_max_insert_block_size(1_048_576);
+    /// ```
+    #[must_use]
+    pub fn with_max_insert_block_size(mut self, rows: usize) -> Self {
+        self.options.ext.max_insert_block_size = Some(rows);
+        self
+    }
+
+    /// Merges small batches passed to [`Client::insert_many`] into larger insert blocks.
+    ///
+    /// Consecutive batches are concatenated until a block reaches the configured row or byte
+    /// threshold, reducing the number of parts created on the server when callers insert many
+    /// tiny batches. See [`InsertCoalescing`] for details.
+    ///
+    /// # Parameters
+    /// - `coalescing`: The row and byte thresholds of coalesced blocks.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated insert coalescing.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_insert_coalescing(InsertCoalescing::new(65_536));
+    /// ```
+    #[must_use]
+    pub fn with_insert_coalescing(mut self, coalescing: InsertCoalescing) -> Self {
+        self.options.ext.insert_coalescing = Some(coalescing);
+        self
+    }
+
+    /// Sets the maximum number of queries and inserts in flight at once.
+    ///
+    /// The limit is shared by the client and its clones. Once reached, further queries wait for
+    /// a running one to complete, including the consumption or drop of its response stream, so a
+    /// shared service can protect `ClickHouse` from bursty callers without an external proxy.
+    /// A limit of 0 is treated as 1.
+    ///
+    /// # Parameters
+    /// - `max`: The maximum number of concurrent queries.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated concurrency limit.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_max_concurrent_queries(8);
+    /// ```
+    #[must_use]
+    pub fn with_max_concurrent_queries(mut self, max: usize) -> Self {
+        self.options.ext.max_concurrent_queries = Some(max);
+        self
+    }
+
+    /// Sets how long queries wait for a slot before they are shed.
+    ///
+    /// Only applies when concurrent queries are capped with
+    /// [`ClientBuilder::with_max_concurrent_queries`]. Queries waiting longer than the queue's
+    /// `max_wait`, or arriving while it is full, fail with [`Error::Overloaded`] instead of
+    /// waiting, see [`QueryQueue`]. Queue depth and shedding are reported by
+    /// [`Client::queue_stats`].
+    ///
+    /// # Parameters
+    /// - `queue`: The deadline and maximum depth of the queue.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated query queue.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use std::time::Duration;
+    ///
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_max_concurrent_queries(8)
+    ///     .with_query_queue(QueryQueue::new(Duration::from_millis(250)));
+    /// ```
+    #[must_use]
+    pub fn with_query_queue(mut self, queue: QueryQueue) -> Self {
+        self.options.ext.query_queue = Some(queue);
+        self
+    }
+
+    /// Sets a rate limit on inserted rows or bytes per second.
+    ///
+    /// The limit is shared by the client and its clones. Inserts exceeding it wait before their
+    /// data is sent, see [`InsertRateLimit`] for how bursts are handled.
+    ///
+    /// # Parameters
+    /// - `limit`: The insert rate limit, in rows or bytes per second.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated insert rate limit.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_insert_rate_limit(InsertRateLimit::RowsPerSecond(100_000));
+    /// ```
+    #[must_use]
+    pub fn with_insert_rate_limit(mut self, limit: InsertRateLimit) -> Self {
+        self.options.ext.insert_rate_limit = Some(limit);
+        self
+    }
+
+    /// Sets how dropped connections are re-established.
+    ///
+    /// When the connection drops, ie after a server restart or network failure, the next query or
+    /// insert first reconnects and repeats the handshake, retrying with exponential backoff as
+    /// configured by `policy`. Session settings are sent with every query and so survive the
+    /// reconnect. Queries in flight when the connection dropped still fail. Without a policy every
+    /// later operation fails until the client is rebuilt.
+    ///
+    /// # Parameters
+    /// - `policy`: The reconnect attempts and backoff between them.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated reconnect policy.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use std::time::Duration;
+    ///
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_reconnect(ReconnectPolicy::new(10).with_multiplier(1.5));
+    /// ```
+    #[must_use]
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.options.ext.reconnect = Some(policy);
+        self
+    }
+
+    /// Restricts the client to statements that read data.
+    ///
+    /// Every statement is classified before it is sent, see [`crate::classify_statement`].
+    /// Inserts, mutations, DDL and other statements changing the server fail with
+    /// [`Error::ReadOnly`] without reaching the server, protecting read-only services from
+    /// accidental writes even when their user has broader rights. `SET` and `USE` only change the
+    /// session and are still allowed. The check is a safeguard, not a replacement for the
+    /// server's `readonly` setting or access rights.
+    ///
+    /// # Parameters
+    /// - `readonly`: If `true`, statements that are not read-only are refused.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated read-only mode.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let client = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_readonly(true)
+    ///     .build_arrow()
+    ///     .await?;
+    /// assert!(client.execute("DROP TABLE events", None).await.is_err());
+    /// ```
+    #[must_use]
+    pub fn with_readonly(mut self, readonly: bool) -> Self {
+        self.options.ext.readonly = readonly;
+        self
+    }
+
+    /// Sets how many decoded blocks are buffered per query ahead of its consumer.
+    ///
+    /// The connection reads and decodes blocks while the consumer processes earlier ones, until
+    /// this many are waiting. A slow consumer then pauses reading, applying backpressure to the
+    /// server instead of buffering the whole result in memory, while a fast consumer keeps the
+    /// buffer from ever filling. Larger values smooth out bursty consumers at the cost of memory,
+    /// roughly `blocks * max_block_size` rows per query. Defaults to 32, a value of 0 is treated
+    /// as 1. Can be overridden per query with [`crate::explain::QueryOptions::with_read_ahead`].
+    ///
+    /// # Parameters
+    /// - `blocks`: The maximum number of blocks buffered per query.
+    ///
+    /// # Returns
+    /// A new [`ClientBuilder`] with the updated read-ahead.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let builder = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_read_ahead(4);
+    /// ```
+    #[must_use]
+    pub fn with_read_ahead(mut self, blocks: usize) -> Self {
+        self.options.ext.read_ahead_blocks = Some(blocks);
+        self
+    }
+
+    /// Sets the size limits of compressed frames sent to and received from the server.
+    ///
+    /// Raise the incoming limits to read results with very large blocks, or lower them to bound
+    /// the memory a single block may claim. Outgoing blocks larger than the frame payload limit
+    /// are split across several frames. See [`FrameLimits`] for the defaults.
+    ///
+    /// # Parameters
+    /// - `limits`: The frame size limits.
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated IPv4 setting.
+    /// A new [`ClientBuilder`] with the updated frame limits.
     ///
     /// # Examples
     /// ```rust,ignore
@@ -399,202 +1139,205 @@ impl ClientBuilder {
     ///
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_ipv4_only(true);
+    ///     .with_frame_limits(FrameLimits::default().with_max_frame_payload(16 << 20));
     /// ```
     #[must_use]
-    pub fn with_ipv4_only(mut self, enabled: bool) -> Self {
-        self.options.ipv4_only = enabled;
+    pub fn with_frame_limits(mut self, limits: FrameLimits) -> Self {
+        self.options.ext.frame_limits = limits;
         self
     }
 
-    /// Sets the `ClickHouse` session settings.
+    /// Sets whether raw SQL is omitted from logs and tracing spans.
     ///
-    /// This method configures the session settings (e.g., query timeouts, max rows) for
-    /// the client. These settings are applied to all queries executed by the client.
-    /// Use this to customize query behavior beyond the defaults.
+    /// Query literals may contain sensitive data. When enabled, queries are only identified in
+    /// logs and spans by their fingerprint, a hash of the query with its literals replaced (see
+    /// [`crate::telemetry::normalize_query`]), which is recorded regardless of this setting.
     ///
     /// # Parameters
-    /// - `settings`: The session settings as an `impl Into<Settings>`.
+    /// - `redact`: Whether to omit raw SQL from logs and spans.
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated settings.
+    /// A new [`ClientBuilder`] with the updated redaction setting.
     ///
     /// # Examples
     /// ```rust,ignore
     /// use clickhouse_arrow::prelude::*;
-    /// use std::sync::Arc;
     ///
-    /// let settings = vec![
-    ///     ("select_sequential_consistency", 1)
-    /// ];
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_settings(settings);
+    ///     .with_redact_queries(true);
     /// ```
     #[must_use]
-    pub fn with_settings(mut self, settings: impl Into<Settings>) -> Self {
-        self.settings = Some(settings.into());
+    pub fn with_redact_queries(mut self, redact: bool) -> Self {
+        self.options.ext.redact_queries = redact;
         self
     }
 
-    /// Set a `ClickHouse` session setting.
+    /// Sets a tracing context for `ClickHouse` connections and queries.
     ///
-    /// This method configures the session settings (e.g., query timeouts, max rows) for
-    /// the client. These settings are applied to all queries executed by the client.
-    /// Use this to customize query behavior beyond the defaults.
+    /// This method configures a [`TraceContext`] to enable distributed tracing for
+    /// client operations, such as connections and queries. The tracing context is
+    /// included in logs and can be used to monitor and debug client behavior across
+    /// distributed systems.
     ///
     /// # Parameters
-    /// - `key`: The session setting's name.
-    /// - `setting`: The session setting as an `impl Into<SettingValue>`.
+    /// - `trace_context`: The tracing context to use.
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated settings.
+    /// A new [`ClientBuilder`] with the updated tracing context.
     ///
     /// # Examples
     /// ```rust,ignore
     /// use clickhouse_arrow::prelude::*;
-    /// use std::sync::Arc;
     ///
+    /// let trace_context = TraceContext::default();
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_setting("select_sequential_consistency", 1);
+    ///     .with_trace_context(trace_context);
     /// ```
     #[must_use]
-    pub fn with_setting(
-        mut self,
-        name: impl Into<String>,
-        setting: impl Into<SettingValue>,
-    ) -> Self {
-        let setting: SettingValue = setting.into();
-        let settings = self.settings.unwrap_or_default().with_setting(name, setting);
-        self.settings = Some(settings);
+    pub fn with_trace_context(mut self, trace_context: TraceContext) -> Self {
+        let mut context = self.context.unwrap_or_default();
+        context.trace = Some(trace_context);
+        self.context = Some(context);
         self
     }
 
-    /// Sets the username for authenticating with `ClickHouse`.
+    /// Sets a block validator to run against every insert before it is serialized.
     ///
-    /// This method configures the username used to authenticate the client with the
-    /// `ClickHouse` server. The default username is typically `"default"`, but this
-    /// can be customized based on the server's configuration.
+    /// Validation happens client-side, so malformed blocks (nulls in key columns, out of order
+    /// timestamps, oversized strings, or any custom check) are rejected before any data is sent
+    /// to `ClickHouse`. Validators apply to [`ArrowFormat`] inserts; other formats ignore them.
     ///
     /// # Parameters
-    /// - `username`: The username for authentication.
+    /// - `validator`: The [`BlockValidator`] to run on each inserted block.
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated username.
+    /// A new [`ClientBuilder`] with the validator configured.
     ///
     /// # Examples
     /// ```rust,ignore
     /// use clickhouse_arrow::prelude::*;
+    /// use clickhouse_arrow::validation::BlockValidator;
     ///
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_username("admin");
+    ///     .with_block_validator(
+    ///         BlockValidator::new().with_non_null_columns(["id"]).with_monotonic_column("ts"),
+    ///     );
     /// ```
     #[must_use]
-    pub fn with_username(mut self, username: impl Into<String>) -> Self {
-        self.options.username = username.into();
+    pub fn with_block_validator(mut self, validator: BlockValidator) -> Self {
+        self.hooks.validator = Some(validator);
         self
     }
 
-    /// Sets the password for authenticating with `ClickHouse`.
+    /// Sets column transforms applied to every insert before it is validated and serialized.
     ///
-    /// This method configures the password used to authenticate the client with the
-    /// `ClickHouse` server. The password is stored securely as a [`Secret`]. If no
-    /// password is required, an empty string can be used.
+    /// Transforms are scoped to the insert's target table, so sensitive columns can be hashed,
+    /// redacted, or nulled client-side and never leave the process unmasked. Each insert block
+    /// that was masked is audited, see [`InsertMasking::with_audit`]. Masking applies to
+    /// [`ArrowFormat`] inserts; other formats ignore it.
     ///
     /// # Parameters
-    /// - `password`: The password for authentication, convertible to [`Secret`].
+    /// - `masking`: The [`InsertMasking`] to apply to each inserted block.
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated password.
+    /// A new [`ClientBuilder`] with the masking configured.
     ///
     /// # Examples
     /// ```rust,ignore
     /// use clickhouse_arrow::prelude::*;
+    /// use clickhouse_arrow::masking::InsertMasking;
     ///
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_username("admin")
-    ///     .with_password("secret");
+    ///     .with_insert_masking(
+    ///         InsertMasking::new().with_redaction("crm.customers", "email", "<redacted>"),
+    ///     );
     /// ```
     #[must_use]
-    pub fn with_password<T>(mut self, password: T) -> Self
-    where
-        Secret: From<T>,
-    {
-        self.options.password = Secret::from(password);
+    pub fn with_insert_masking(mut self, masking: InsertMasking) -> Self {
+        self.hooks.masking = Some(masking);
         self
     }
 
-    /// Sets the default database for the `ClickHouse` connection.
+    /// Sets a callback invoked after each statement the client runs.
     ///
-    /// This method configures the default database used by the client for queries and
-    /// operations. If not set, the `ClickHouse` server defaults to the `"default"`
-    /// database. Specifying a database is optional but recommended when working with a
-    /// specific database to avoid explicit database prefixes in queries.
+    /// The hook receives a [`StatementEvent`] once every query, insert, or execute finishes,
+    /// carrying the statement's fingerprint, duration, rows read and written, and error, if any.
+    /// This enables audit logging and slow query detection without wrapping every call site.
+    /// The raw SQL is included unless queries are redacted, see
+    /// [`ClientBuilder::with_redact_queries`].
+    ///
+    /// The hook runs on the connection's IO task and should return quickly.
     ///
     /// # Parameters
-    /// - `database`: The name of the default database.
+    /// - `hook`: The callback to invoke with each [`StatementEvent`].
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated database.
+    /// A new [`ClientBuilder`] with the statement hook configured.
     ///
     /// # Examples
     /// ```rust,ignore
+    /// use std::time::Duration;
+    ///
     /// use clickhouse_arrow::prelude::*;
     ///
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_database("my_db");
+    ///     .with_statement_hook(|event: &StatementEvent| {
+    ///         if event.duration > Duration::from_secs(1) {
+    ///             warn!(fingerprint = event.fingerprint, ?event.duration, "slow query");
+    ///         }
+    ///     });
     /// ```
     #[must_use]
-    pub fn with_database(mut self, database: impl Into<String>) -> Self {
-        self.options.default_database = database.into();
+    pub fn with_statement_hook(mut self, hook: impl Into<StatementHook>) -> Self {
+        self.hooks.statement_hook = Some(hook.into());
         self
     }
 
-    /// Sets the domain for secure TLS connections to `ClickHouse`.
+    /// Sets a provider of the credentials to connect with.
     ///
-    /// This method specifies the domain name used for TLS verification when connecting
-    /// to a `ClickHouse` server with TLS enabled (via [`ClientBuilder::with_tls`]). If
-    /// not set, the domain is inferred from the destination during verification. Use
-    /// this to explicitly set the domain for cloud or custom TLS configurations.
+    /// The provider is asked for credentials every time a connection is established, including
+    /// reconnects and connections replaced by a pool, so long-running services pick up rotated
+    /// passwords or tokens without a restart. It takes precedence over
+    /// [`ClientBuilder::with_username`] and [`ClientBuilder::with_password`]. See
+    /// [`crate::EnvCredentials`], [`crate::FileCredentials`], and [`crate::CredentialsCallback`].
     ///
     /// # Parameters
-    /// - `domain`: The domain name for TLS verification.
+    /// - `provider`: The source of credentials.
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated domain.
+    /// A new [`ClientBuilder`] with the credentials provider configured.
     ///
     /// # Examples
     /// ```rust,ignore
     /// use clickhouse_arrow::prelude::*;
+    /// use clickhouse_arrow::FileCredentials;
     ///
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_tls(true)
-    ///     .with_domain("clickhouse.example.com");
+    ///     .with_credentials_provider(FileCredentials::new("app", "/run/secrets/clickhouse"));
     /// ```
     #[must_use]
-    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
-        self.options.domain = Some(domain.into());
-        self.verified = false;
+    pub fn with_credentials_provider(mut self, provider: impl CredentialsProvider) -> Self {
+        self.hooks.credentials = Some(Arc::new(provider));
         self
     }
 
-    /// Sets the compression method for data exchange with `ClickHouse`.
+    /// Sets a callback invoked whenever a connection to the server is established.
     ///
-    /// This method configures the compression algorithm used for sending and receiving
-    /// data to/from `ClickHouse`. The default is [`CompressionMethod::LZ4`], which
-    /// balances performance and compression ratio. Other options may be available
-    /// depending on the `ClickHouse` server configuration.
+    /// The hook receives a [`ConnectEvent`] with the endpoint and server version once the
+    /// handshake completed, for the initial connection as well as re-established ones, see
+    /// [`ClientBuilder::with_reconnect`]. The hook should return quickly.
     ///
     /// # Parameters
-    /// - `compression`: The compression method to use.
+    /// - `hook`: The callback to invoke with each [`ConnectEvent`].
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated compression setting.
+    /// A new [`ClientBuilder`] with the connect hook configured.
     ///
     /// # Examples
     /// ```rust,ignore
@@ -602,69 +1345,81 @@ impl ClientBuilder {
     ///
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_compression(CompressionMethod::LZ4);
+    ///     .on_connect(|event: &ConnectEvent| {
+    ///         info!(endpoint = %event.endpoint, version = ?event.server_version, "connected");
+    ///     });
     /// ```
     #[must_use]
-    pub fn with_compression(mut self, compression: CompressionMethod) -> Self {
-        self.options.compression = compression;
-        self
+    pub fn on_connect(self, hook: impl Fn(&ConnectEvent) + Send + Sync + 'static) -> Self {
+        self.with_connection_hooks(|hooks| hooks.on_connect(hook))
     }
 
-    /// Sets the Arrow-specific options for `ClickHouse` connections.
+    /// Sets a callback invoked whenever a connection to the server ends.
     ///
-    /// This method configures options specific to the Arrow format (used by
-    /// [`Client<ArrowFormat>`]), such as schema mapping or data type conversions. These options
-    /// are applied when the client is built with [`ClientBuilder::build`] for
-    /// [`ArrowFormat`]. Use this to customize Arrow interoperability.
+    /// The hook receives a [`DisconnectEvent`] with the endpoint and the
+    /// [`crate::telemetry::DisconnectReason`]: either the client shut the connection down, or it
+    /// failed with an error, ie the server closed it or the network dropped. The hook runs on the
+    /// connection's IO task and should return quickly.
     ///
     /// # Parameters
-    /// - `options`: The Arrow-specific options.
+    /// - `hook`: The callback to invoke with each [`DisconnectEvent`].
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated Arrow options.
+    /// A new [`ClientBuilder`] with the disconnect hook configured.
     ///
     /// # Examples
     /// ```rust,ignore
     /// use clickhouse_arrow::prelude::*;
     ///
-    /// let arrow_options = ArrowOptions::default();
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_arrow_options(arrow_options);
+    ///     .on_disconnect(|event: &DisconnectEvent| {
+    ///         if let DisconnectReason::Error(error) = &event.reason {
+    ///             error!(endpoint = %event.endpoint, error, "connection lost");
+    ///         }
+    ///     });
     /// ```
     #[must_use]
-    pub fn with_arrow_options(mut self, options: ArrowOptions) -> Self {
-        self.options.ext.arrow = Some(options);
-        self
+    pub fn on_disconnect(self, hook: impl Fn(&DisconnectEvent) + Send + Sync + 'static) -> Self {
+        self.with_connection_hooks(|hooks| hooks.on_disconnect(hook))
     }
 
-    /// Sets a tracing context for `ClickHouse` connections and queries.
+    /// Sets a callback invoked after each attempt to re-establish a dropped connection.
     ///
-    /// This method configures a [`TraceContext`] to enable distributed tracing for
-    /// client operations, such as connections and queries. The tracing context is
-    /// included in logs and can be used to monitor and debug client behavior across
-    /// distributed systems.
+    /// The hook receives a [`ReconnectAttemptEvent`] with the attempt number, the error that
+    /// failed it, if any, and the delay before the next attempt. Only called if reconnects are
+    /// enabled with [`ClientBuilder::with_reconnect`]. The hook should return quickly.
     ///
     /// # Parameters
-    /// - `trace_context`: The tracing context to use.
+    /// - `hook`: The callback to invoke with each [`ReconnectAttemptEvent`].
     ///
     /// # Returns
-    /// A new [`ClientBuilder`] with the updated tracing context.
+    /// A new [`ClientBuilder`] with the reconnect attempt hook configured.
     ///
     /// # Examples
     /// ```rust,ignore
     /// use clickhouse_arrow::prelude::*;
     ///
-    /// let trace_context = TraceContext::default();
     /// let builder = ClientBuilder::new()
     ///     .with_endpoint("localhost:9000")
-    ///     .with_trace_context(trace_context);
+    ///     .with_reconnect(ReconnectPolicy::default())
+    ///     .on_reconnect_attempt(|event: &ReconnectAttemptEvent| {
+    ///         if event.retry_in.is_none() && !event.is_success() {
+    ///             error!(client_id = event.client_id, "giving up reconnecting");
+    ///         }
+    ///     });
     /// ```
     #[must_use]
-    pub fn with_trace_context(mut self, trace_context: TraceContext) -> Self {
-        let mut context = self.context.unwrap_or_default();
-        context.trace = Some(trace_context);
-        self.context = Some(context);
+    pub fn on_reconnect_attempt(
+        self,
+        hook: impl Fn(&ReconnectAttemptEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.with_connection_hooks(|hooks| hooks.on_reconnect_attempt(hook))
+    }
+
+    fn with_connection_hooks(mut self, f: impl FnOnce(ConnectionHooks) -> ConnectionHooks) -> Self {
+        let hooks = self.hooks.connection_hooks.take().unwrap_or_default();
+        self.hooks.connection_hooks = Some(f(hooks));
         self
     }
 
@@ -686,6 +1441,10 @@ impl ClientBuilder {
     /// - Fails if the destination cannot be resolved ([`Error::MalformedConnectionInformation`]).
     /// - Fails if TLS is enabled but no domain is provided and cannot be inferred
     ///   ([`Error::MalformedConnectionInformation`]).
+    /// - With [`ClientBuilder::with_endpoints`], fails only if none of the endpoints resolves.
+    /// - Fails if the destination is not in the allowed hosts
+    ///   ([`Error::MalformedConnectionInformation`]), or the allowed hosts or SPKI pins are invalid
+    ///   ([`Error::Configuration`]).
     ///
     /// # Examples
     /// ```rust,ignore
@@ -697,6 +1456,24 @@ impl ClientBuilder {
     /// println!("Destination verified!");
     /// ```
     pub async fn verify(mut self) -> Result<Self> {
+        let _ = super::trust::parse_spki_pins(&self.options.ext.pinned_spki)?;
+        if let Some(endpoints) = self.endpoints.as_ref() {
+            if self.options.use_tls
+                && self.options.domain.is_none()
+                && endpoints.destinations().iter().any(|d| d.domain().is_empty())
+            {
+                return Err(Error::MalformedConnectionInformation(
+                    "Domain required for TLS, couldn't be determined from endpoints".into(),
+                ));
+            }
+            // Endpoints are resolved again on every connection, as any of them may be down
+            let _ = endpoints
+                .resolve(&self.options)
+                .await
+                .inspect_err(|error| error!(?error, "Failed to resolve endpoints"))?;
+            self.verified = true;
+            return Ok(self);
+        }
         let (addrs, domain) = {
             let destination =
                 self.destination.as_ref().ok_or(Error::MissingConnectionInformation)?;
@@ -722,9 +1499,17 @@ impl ClientBuilder {
                 (addrs, self.options.domain)
             }
         };
+        let destination = self.destination.take().ok_or(Error::MissingConnectionInformation)?;
+        super::trust::check_allowed_hosts(
+            &self.options.ext.allowed_hosts,
+            destination.host(),
+            domain.as_deref(),
+            &addrs,
+        )?;
 
         self.options.domain = domain;
-        self.destination = Some(Destination::from(addrs));
+        // Keep the host so the allow-list can be checked again when connecting
+        self.destination = Some(destination.into_resolved(addrs));
         self.verified = true;
 
         Ok(self)
@@ -769,12 +1554,18 @@ impl ClientBuilder {
     /// ```
     pub async fn build<T: ClientFormat>(self) -> Result<Client<T>> {
         let verified_builder = if self.verified { self } else { self.verify().await? };
+        let endpoints = match verified_builder.endpoints {
+            Some(endpoints) => endpoints,
+            // Guaranteed in verify above
+            None => Endpoints::from(verified_builder.destination.unwrap()),
+        };
 
-        Client::connect(
-            verified_builder.destination.unwrap(), // Guaranteed in verify above
+        Client::connect_endpoints(
+            endpoints,
             verified_builder.options,
             verified_builder.settings.map(Arc::new),
             verified_builder.context,
+            verified_builder.hooks,
         )
         .await
     }
@@ -801,6 +1592,20 @@ impl ClientBuilder {
         Self::build::<NativeFormat>(self).await
     }
 
+    /// A helper method to build a [`Client<LazyArrowFormat>`] directly
+    ///
+    /// Queries return [`crate::arrow::lazy::LazyBatch`]es, whose columns are decoded into arrow
+    /// arrays on first access.
+    ///
+    /// # Errors
+    /// - Returns an error if destination verification fails.
+    ///
+    /// # Panics
+    /// - Shouldn't panic, verification guarantees destination.
+    pub async fn build_lazy_arrow(self) -> Result<Client<LazyArrowFormat>> {
+        Self::build::<LazyArrowFormat>(self).await
+    }
+
     /// Build an HTTP client for `ClickHouse` using `ArrowStream` format.
     ///
     /// This creates an [`HttpClient`](crate::http::HttpClient) that uses HTTP transport
@@ -913,6 +1718,49 @@ impl ClientBuilder {
             ConnectionManager::<T>::try_new_with_builder(self).await?.with_check(check_health);
         Ok(manager)
     }
+
+    /// Builds a [`ClientPool`] keeping `size` connections open.
+    ///
+    /// All `size` connections are established up front and replaced as they break or expire, so
+    /// concurrent queries and inserts each check out a warm connection instead of sharing one.
+    /// Connections are returned to the pool once their operation completes. For finer control
+    /// over the pool, ie timeouts or connection lifetimes, use
+    /// [`ConnectionPoolBuilder`](crate::ConnectionPoolBuilder).
+    ///
+    /// # Parameters
+    /// - `size`: The number of connections to keep open, at least 1.
+    ///
+    /// # Returns
+    /// A [`Result`] containing the [`ClientPool<T>`], or an error if verification or the initial
+    /// connections fail.
+    ///
+    /// # Errors
+    /// - Fails if the destination is unset or invalid ([`Error::MissingConnectionInformation`],
+    ///   [`Error::MalformedConnectionInformation`]).
+    /// - Fails if the initial connections cannot be established.
+    ///
+    /// # Feature
+    /// Requires the `pool` feature to be enabled.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::prelude::*;
+    ///
+    /// let pool = ClientBuilder::new()
+    ///     .with_endpoint("localhost:9000")
+    ///     .with_username("default")
+    ///     .build_pool::<ArrowFormat>(8)
+    ///     .await
+    ///     .unwrap();
+    /// let batches = pool.query("SELECT 1", None).await?.collect_result().await?;
+    /// ```
+    #[cfg(feature = "pool")]
+    pub async fn build_pool<T: ClientFormat>(self, size: u32) -> Result<ClientPool<T>> {
+        let size = size.max(1);
+        let manager = self.build_pool_manager::<T>(false).await?;
+        let pool = bb8::Pool::builder().max_size(size).min_idle(size).build(manager).await?;
+        Ok(ClientPool::new(pool))
+    }
 }
 
 impl ClientBuilder {
@@ -1056,8 +1904,10 @@ impl ClientBuilder {
 mod tests {
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
     use std::path::PathBuf;
+    use std::time::Duration;
 
     use super::*;
+    use crate::telemetry::StatementEvent;
 
     fn default_builder() -> ClientBuilder { ClientBuilder::new() }
 
@@ -1151,6 +2001,110 @@ mod tests {
         assert!(!builder.verified());
     }
 
+    #[test]
+    fn test_with_client_info() {
+        let builder = default_builder().with_client_name("my-app").with_quota_key("tenant");
+        let info = &builder.options().ext.client_info;
+        assert_eq!(info.client_name.as_deref(), Some("my-app"));
+        assert_eq!(info.quota_key.as_deref(), Some("tenant"));
+
+        let builder = builder.with_client_info(ClientInfoOptions::default().with_os_user("svc"));
+        let info = &builder.options().ext.client_info;
+        assert_eq!(info.client_name, None);
+        assert_eq!(info.os_user.as_deref(), Some("svc"));
+    }
+
+    #[test]
+    fn test_with_default_comment() {
+        let builder = default_builder()
+            .with_setting("max_threads", 4)
+            .with_default_comment("service=ingest version=1.2.3");
+        let settings = builder.settings().unwrap();
+        assert_eq!(settings.encode_to_strings(), vec![
+            "max_threads = 4",
+            "log_comment = service=ingest version=1.2.3"
+        ]);
+    }
+
+    #[test]
+    fn test_with_max_insert_block_size() {
+        let builder = default_builder();
+        assert_eq!(builder.options().ext.max_insert_block_size, None);
+        let builder = builder.with_max_insert_block_size(1000);
+        assert_eq!(builder.options().ext.max_insert_block_size, Some(1000));
+        assert_eq!(builder.options().ext.insert_coalescing, None);
+        let coalescing = InsertCoalescing::new(10_000).with_max_bytes(1 << 20);
+        let builder = builder.with_insert_coalescing(coalescing);
+        assert_eq!(builder.options().ext.insert_coalescing, Some(coalescing));
+    }
+
+    #[test]
+    fn test_with_read_ahead() {
+        let builder = default_builder();
+        assert_eq!(builder.options().ext.read_ahead_blocks, None);
+        let builder = builder.with_read_ahead(4);
+        assert_eq!(builder.options().ext.read_ahead_blocks, Some(4));
+    }
+
+    #[test]
+    fn test_with_frame_limits() {
+        let builder = ClientBuilder::new();
+        assert_eq!(builder.options().ext.frame_limits, FrameLimits::default());
+        let limits = FrameLimits::default().with_max_frame_payload(1024);
+        let builder = builder.with_frame_limits(limits);
+        assert_eq!(builder.options().ext.frame_limits.max_frame_payload, 1024);
+    }
+
+    #[test]
+    fn test_with_throttling() {
+        let builder = default_builder();
+        assert_eq!(builder.options().ext.max_concurrent_queries, None);
+        assert_eq!(builder.options().ext.insert_rate_limit, None);
+        assert_eq!(builder.options().ext.query_queue, None);
+        let queue = QueryQueue::new(Duration::from_millis(100)).with_max_depth(16);
+        let builder = builder
+            .with_max_concurrent_queries(4)
+            .with_query_queue(queue)
+            .with_insert_rate_limit(InsertRateLimit::BytesPerSecond(1 << 20));
+        assert_eq!(builder.options().ext.max_concurrent_queries, Some(4));
+        assert_eq!(builder.options().ext.query_queue, Some(queue));
+        assert_eq!(
+            builder.options().ext.insert_rate_limit,
+            Some(InsertRateLimit::BytesPerSecond(1 << 20))
+        );
+    }
+
+    #[test]
+    fn test_with_reconnect() {
+        let builder = default_builder();
+        assert_eq!(builder.options().ext.reconnect, None);
+        let policy = ReconnectPolicy::new(3)
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(100))
+            .with_multiplier(3.0);
+        let builder = builder.with_reconnect(policy);
+        assert_eq!(builder.options().ext.reconnect, Some(policy));
+
+        let delays = (1..=5).map(|attempt| policy.backoff(attempt)).collect::<Vec<_>>();
+        assert_eq!(delays, vec![
+            Duration::from_millis(10),
+            Duration::from_millis(30),
+            Duration::from_millis(90),
+            Duration::from_millis(100),
+            Duration::from_millis(100),
+        ]);
+        // Multipliers below 1 never shrink the delay, overflows are capped
+        assert_eq!(policy.with_multiplier(0.5).backoff(4), Duration::from_millis(10));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_with_redact_queries() {
+        let builder = default_builder();
+        assert!(!builder.options().ext.redact_queries);
+        let builder = builder.with_redact_queries(true);
+        assert!(builder.options().ext.redact_queries);
+    }
+
     #[test]
     fn test_with_trace_context() {
         let trace_context = TraceContext::default();
@@ -1158,6 +2112,52 @@ mod tests {
         assert_eq!(builder.context.unwrap().trace, Some(trace_context));
     }
 
+    #[test]
+    fn test_with_credentials_provider() {
+        let builder =
+            default_builder().with_credentials_provider(crate::Credentials::new("a", "b"));
+        assert!(builder.hooks.credentials.is_some());
+    }
+
+    #[test]
+    fn test_connection_hooks() {
+        let hooks = |builder: &ClientBuilder| format!("{:?}", builder.hooks.connection_hooks);
+        let builder = default_builder();
+        assert_eq!(hooks(&builder), "None");
+        let builder = builder
+            .on_connect(|_: &ConnectEvent| {})
+            .on_reconnect_attempt(|_: &ReconnectAttemptEvent| {});
+        assert_eq!(
+            hooks(&builder),
+            "Some(ConnectionHooks { on_connect: true, on_disconnect: false, \
+             on_reconnect_attempt: true })"
+        );
+        let builder = builder.on_disconnect(|_: &DisconnectEvent| {});
+        assert!(hooks(&builder).contains("on_disconnect: true"));
+    }
+
+    #[test]
+    fn test_with_statement_hook() {
+        let builder = default_builder().with_statement_hook(|_: &StatementEvent| {});
+        assert!(builder.hooks.statement_hook.is_some());
+    }
+
+    #[test]
+    fn test_with_block_validator() {
+        let builder = ClientBuilder::new()
+            .with_block_validator(BlockValidator::new().with_non_null_columns(["id"]));
+        let validator = builder.hooks.validator.as_ref().unwrap();
+        assert_eq!(validator.len(), 1);
+    }
+
+    #[test]
+    fn test_with_insert_masking() {
+        let builder = ClientBuilder::new()
+            .with_insert_masking(InsertMasking::new().with_nulls("users", "phone"));
+        let masking = builder.hooks.masking.as_ref().unwrap();
+        assert_eq!(masking.len(), 1);
+    }
+
     #[test]
     fn test_connection_identifier() {
         let builder = default_builder()
@@ -1189,6 +2189,58 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_verify_allowed_hosts() {
+        let builder = default_builder()
+            .with_endpoint("localhost:9000")
+            .with_allowed_hosts(["localhost", "127.0.0.0/8"])
+            .with_ipv4_only(true)
+            .verify()
+            .await
+            .unwrap();
+        assert_eq!(builder.destination().and_then(Destination::host), Some("localhost"));
+
+        let builder = default_builder()
+            .with_endpoint("localhost:9000")
+            .with_allowed_hosts(["*.example.com"])
+            .verify()
+            .await;
+        assert!(matches!(builder, Err(Error::MalformedConnectionInformation(_))));
+
+        let builder = default_builder()
+            .with_endpoint("localhost:9000")
+            .with_pinned_spki(["not a pin"])
+            .verify()
+            .await;
+        assert!(matches!(builder, Err(Error::Configuration(_))));
+    }
+
+    #[tokio::test]
+    async fn test_verify_endpoints() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9000);
+        let builder = default_builder()
+            .with_endpoints(&["invalid.invalid:9000", "127.0.0.1:9000"], EndpointStrategy::Random);
+        assert_eq!(builder.destination(), Some(&Destination::from("invalid.invalid:9000")));
+        let builder = builder.verify().await.unwrap();
+        assert!(builder.verified());
+
+        let builder = default_builder()
+            .with_endpoints(&["invalid.invalid:9000"], EndpointStrategy::FirstAlive)
+            .verify()
+            .await;
+        assert!(builder.is_err());
+
+        // A single destination replaces the endpoints
+        let builder = default_builder()
+            .with_endpoints(&["invalid.invalid:9000"], EndpointStrategy::FirstAlive)
+            .with_socket_addr(addr)
+            .verify()
+            .await
+            .unwrap();
+        assert!(builder.endpoints.is_none());
+        assert_eq!(builder.destination(), Some(&Destination::from(vec![addr])));
+    }
+
     #[tokio::test]
     async fn test_verify_no_connection_information() {
         let builder = default_builder().verify().await;
@@ -2,7 +2,6 @@ use std::io;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-use futures_util::ready;
 use pin_project::pin_project;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
 use tracing::{debug, trace};
@@ -74,15 +73,22 @@ impl<W: ClickHouseWrite> AsyncWrite for ChunkWriter<W> {
     }
 }
 
+/// Maximum size of a single chunk accepted from the server
+const MAX_CHUNK_SIZE: u32 = 100_000_000;
+
+/// Reads the chunked framing used when chunked receive mode is negotiated.
+///
+/// Every packet is split into one or more chunks, each prefixed with its little-endian `u32`
+/// size, and terminated by a zero-sized chunk. The framing is stripped so callers see the same
+/// byte stream as in non-chunked mode.
 #[pin_project]
 pub(crate) struct ChunkReader<R> {
     #[pin]
-    inner:       R,
-    state:       ReaderState,
-    buffer:      Vec<u8>, // Internal buffer for excess chunk data
-    read_buffer: Vec<u8>, // Internal buffer for reading chunk data
-    buffer_pos:  usize,   // Current position in the buffer
-    chunk_size:  u32,     // Remaining bytes in the current chunk
+    inner:      R,
+    state:      ReaderState,
+    header:     [u8; 4], // Chunk header, possibly received across several reads
+    header_pos: usize,   // Bytes of the header received so far
+    chunk_size: u32,     // Remaining bytes in the current chunk
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -93,58 +99,49 @@ enum ReaderState {
 
 impl<R: ClickHouseRead> ChunkReader<R> {
     pub(crate) fn new(inner: R) -> Self {
-        Self {
-            inner,
-            state: ReaderState::Header,
-            buffer: Vec::with_capacity(64 * 1024),
-            // In practice, 1MB chunks is seen often
-            read_buffer: Vec::with_capacity(1024 * 1024),
-            buffer_pos: 0,
-            chunk_size: 0,
-        }
+        Self { inner, state: ReaderState::Header, header: [0; 4], header_pos: 0, chunk_size: 0 }
     }
 }
 
 impl<R: ClickHouseRead> AsyncRead for ChunkReader<R> {
-    #[allow(clippy::too_many_lines)]
     fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
         let mut this = self.project();
+        let start = buf.filled().len();
 
-        // If we have buffered data, serve it to the caller first
-        if *this.buffer_pos < this.buffer.len() {
-            let available = &this.buffer[*this.buffer_pos..];
-            let to_copy = available.len().min(buf.remaining());
-            buf.put_slice(&available[..to_copy]);
-            *this.buffer_pos += to_copy;
-            // Clear buffer if fully consumed to free memory
-            if *this.buffer_pos >= this.buffer.len() {
-                this.buffer.clear();
-                *this.buffer_pos = 0;
-            }
-            return Poll::Ready(Ok(()));
-        }
-
-        loop {
-            #[expect(clippy::cast_possible_truncation)]
+        while buf.remaining() > 0 {
             match this.state {
                 ReaderState::Header => {
-                    // Read the 4-byte chunk size (little-endian u32)
-                    let mut header = [0u8; 4];
-                    let mut header_buf = ReadBuf::new(&mut header);
-                    ready!(this.inner.as_mut().poll_read(cx, &mut header_buf))?;
+                    // The header may arrive split across reads
+                    let mut header_buf = ReadBuf::new(&mut this.header[*this.header_pos..]);
+                    match this.inner.as_mut().poll_read(cx, &mut header_buf) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                        // Hand back what has been read so far instead of losing it
+                        Poll::Pending if buf.filled().len() > start => break,
+                        Poll::Pending => return Poll::Pending,
+                    }
 
-                    if header.len() < 4 {
+                    let read = header_buf.filled().len();
+                    if read == 0 {
+                        // A clean EOF between packets is reported as EOF to the caller
+                        if *this.header_pos == 0 {
+                            break;
+                        }
                         return Poll::Ready(Err(io::Error::new(
                             io::ErrorKind::UnexpectedEof,
                             "Incomplete chunk header",
                         )));
                     }
-
-                    *this.chunk_size = u32::from_le_bytes(header);
+                    *this.header_pos += read;
+                    if *this.header_pos < this.header.len() {
+                        continue;
+                    }
+                    *this.header_pos = 0;
+                    *this.chunk_size = u32::from_le_bytes(*this.header);
 
                     // Terminating sequence, stay in Header and continue
                     if *this.chunk_size == 0 {
@@ -152,10 +149,10 @@ impl<R: ClickHouseRead> AsyncRead for ChunkReader<R> {
                         continue;
                     }
 
-                    if *this.chunk_size > 100_000_000 {
+                    if *this.chunk_size > MAX_CHUNK_SIZE {
                         return Poll::Ready(Err(io::Error::new(
                             io::ErrorKind::InvalidData,
-                            "Chunk size too large",
+                            format!("Chunk size too large: {}", this.chunk_size),
                         )));
                     }
 
@@ -165,51 +162,127 @@ impl<R: ClickHouseRead> AsyncRead for ChunkReader<R> {
                 ReaderState::Data => {
                     // Read up to the remaining chunk size or the caller's buffer size
                     let to_read = buf.remaining().min(*this.chunk_size as usize);
-
-                    // This conditional means that this.chunk_size is 0 buf needs more
-                    if to_read == 0 && buf.remaining() > 0 {
-                        *this.state = ReaderState::Header;
-                        continue;
+                    let progressed = buf.filled().len() > start;
+                    let mut data_buf = ReadBuf::new(buf.initialize_unfilled_to(to_read));
+                    match this.inner.as_mut().poll_read(cx, &mut data_buf) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                        Poll::Pending if progressed => break,
+                        Poll::Pending => return Poll::Pending,
                     }
 
-                    if this.read_buffer.len() < to_read {
-                        this.read_buffer.resize(to_read, 0);
-                    }
-
-                    let mut read_buf = ReadBuf::new(&mut this.read_buffer[..to_read]);
-                    ready!(this.inner.as_mut().poll_read(cx, &mut read_buf))?;
-
-                    let filled = read_buf.filled();
-                    if filled.is_empty() && *this.chunk_size > 0 {
+                    let read = data_buf.filled().len();
+                    if read == 0 {
                         return Poll::Ready(Err(io::Error::new(
                             io::ErrorKind::UnexpectedEof,
                             "Unexpected EOF in chunk data",
                         )));
                     }
+                    buf.advance(read);
 
-                    let filled_len = filled.len();
-                    let remaining = buf.remaining();
-                    let to_copy = filled_len.min(remaining);
-
-                    buf.put_slice(&filled[..to_copy]);
-
-                    // Store excess
-                    if to_copy < filled_len {
-                        this.buffer.clear();
-                        this.buffer.extend_from_slice(&filled[to_copy..]);
+                    #[expect(clippy::cast_possible_truncation)]
+                    {
+                        *this.chunk_size -= read as u32;
                     }
-
-                    *this.chunk_size -= filled_len as u32;
-
                     if *this.chunk_size == 0 {
                         *this.state = ReaderState::Header;
                     }
-
-                    if buf.remaining() == 0 {
-                        return Poll::Ready(Ok(()));
-                    }
                 }
             }
         }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+
+    /// Yields at most `step` bytes per read, returning `Pending` before every read
+    struct Trickle {
+        data:    Vec<u8>,
+        pos:     usize,
+        step:    usize,
+        pending: bool,
+    }
+
+    impl Trickle {
+        fn new(data: Vec<u8>, step: usize) -> Self { Self { data, pos: 0, step, pending: true } }
+    }
+
+    impl AsyncRead for Trickle {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            if self.pending {
+                self.pending = false;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            self.pending = true;
+            let end = self.data.len().min(self.pos + self.step.min(buf.remaining()));
+            buf.put_slice(&self.data[self.pos..end]);
+            self.pos = end;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn frame(packets: &[&[&[u8]]]) -> Vec<u8> {
+        let mut framed = Vec::new();
+        for chunks in packets {
+            for chunk in *chunks {
+                framed.extend_from_slice(&u32::try_from(chunk.len()).unwrap().to_le_bytes());
+                framed.extend_from_slice(chunk);
+            }
+            framed.extend_from_slice(&0u32.to_le_bytes());
+        }
+        framed
+    }
+
+    #[tokio::test]
+    async fn test_chunk_reader_strips_framing() {
+        let framed = frame(&[&[b"hello ", b"chunked"], &[b" world"]]);
+        for step in [1, 3, 5, 64] {
+            let mut reader = ChunkReader::new(Trickle::new(framed.clone(), step));
+            let mut out = Vec::new();
+            let _ = reader.read_to_end(&mut out).await.unwrap();
+            assert_eq!(out, b"hello chunked world", "step {step}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunk_reader_truncated() {
+        // Truncated header
+        let mut framed = frame(&[&[b"abc"]]);
+        framed.extend_from_slice(&[5, 0]);
+        let mut out = Vec::new();
+        let error = ChunkReader::new(Trickle::new(framed, 2)).read_to_end(&mut out).await;
+        assert_eq!(error.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        // Truncated data
+        let mut framed = 10u32.to_le_bytes().to_vec();
+        framed.extend_from_slice(b"abc");
+        let mut out = Vec::new();
+        let error = ChunkReader::new(Trickle::new(framed, 4)).read_to_end(&mut out).await;
+        assert_eq!(error.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_writer_round_trip() {
+        let mut writer = ChunkWriter::new(Vec::new());
+        writer.write_all(b"first").await.unwrap();
+        writer.finish_chunk().await.unwrap();
+        writer.write_all(b"second").await.unwrap();
+        writer.finish_chunk().await.unwrap();
+        assert_eq!(writer.inner, frame(&[&[b"first"], &[b"second"]]));
+
+        let mut out = Vec::new();
+        let _ = ChunkReader::new(writer.inner.as_slice()).read_to_end(&mut out).await.unwrap();
+        assert_eq!(out, b"firstsecond");
     }
 }
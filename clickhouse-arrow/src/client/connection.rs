@@ -1,26 +1,31 @@
 use std::collections::VecDeque;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 #[cfg(feature = "inner_pool")]
 use arc_swap::ArcSwap;
-use parking_lot::Mutex;
+#[cfg(not(feature = "inner_pool"))]
+use parking_lot::RwLock;
 use strum::Display;
 use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{Mutex, broadcast, mpsc};
 use tokio::task::{AbortHandle, JoinSet};
-use tokio_rustls::rustls;
 
+use super::endpoints::{Target, Targets};
 use super::internal::{InternalConn, PendingQuery};
-use super::{ArrowOptions, CompressionMethod, Event};
+use super::{
+    ArrowOptions, CompressionMethod, CredentialsProvider, Event, FrameLimits, ReconnectPolicy,
+};
 use crate::client::chunk::{ChunkReader, ChunkWriter};
+use crate::constants::READ_AHEAD_DEFAULT;
 use crate::flags::{conn_read_buffer_size, conn_write_buffer_size};
 use crate::io::{ClickHouseRead, ClickHouseWrite};
 use crate::native::protocol::{
     ClientHello, DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM, DBMS_TCP_PROTOCOL_VERSION, ServerHello,
 };
 use crate::prelude::*;
+use crate::query::check_read_only;
 use crate::{ClientOptions, Message, Operation};
 
 // Type alias for the JoinSet used to spawn inner connections
@@ -51,19 +56,23 @@ impl From<ConnectionStatus> for u8 {
 /// Client metadata passed around the internal client
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct ClientMetadata {
-    pub(crate) client_id:     u16,
-    pub(crate) compression:   CompressionMethod,
-    pub(crate) arrow_options: ArrowOptions,
+    pub(crate) client_id:      u16,
+    pub(crate) compression:    CompressionMethod,
+    pub(crate) arrow_options:  ArrowOptions,
+    /// Whether raw SQL is omitted from logs and spans
+    pub(crate) redact_queries: bool,
+    /// Decoded blocks buffered per query ahead of its consumer
+    pub(crate) read_ahead:     usize,
+    /// Whether statements that are not read-only are refused
+    pub(crate) readonly:       bool,
+    /// Size limits of compressed frames
+    pub(crate) frame_limits:   FrameLimits,
 }
 
 impl ClientMetadata {
     /// Helper function to disable compression on the metadata.
     pub(crate) fn disable_compression(self) -> Self {
-        Self {
-            client_id:     self.client_id,
-            compression:   CompressionMethod::None,
-            arrow_options: self.arrow_options,
-        }
+        Self { compression: CompressionMethod::None, ..self }
     }
 
     /// Helper function to provide settings for compression
@@ -82,22 +91,51 @@ impl ClientMetadata {
 /// A struct defining the information needed to connect over TCP.
 #[derive(Debug)]
 struct ConnectState<T: Send + Sync + 'static> {
-    status:  Arc<AtomicU8>,
-    channel: mpsc::Sender<Message<T>>,
+    status:                Arc<AtomicU8>,
+    channel:               mpsc::Sender<Message<T>>,
     #[expect(unused)]
-    handle:  AbortHandle,
+    handle:                AbortHandle,
+    /// `(major, minor, patch)` version reported in the server hello
+    server_version:        (u64, u64, u64),
+    /// The server address connected to
+    endpoint:              SocketAddr,
+    /// `max_insert_block_size` from the settings in the server hello, if the server changed it
+    max_insert_block_size: Option<usize>,
+}
+
+impl<T: Send + Sync + 'static> ConnectState<T> {
+    fn status(&self) -> ConnectionStatus { self.status.load(Ordering::Acquire).into() }
 }
 
-// NOTE: ArcSwaps are used to support reconnects in the future.
+/// Callbacks shared by a client's connections.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Hooks {
+    pub(super) statement:  Option<StatementHook>,
+    pub(super) connection: ConnectionHooks,
+}
+
+/// What is needed to re-establish a dropped connection, if configured.
+#[derive(Debug)]
+struct Reconnect {
+    policy:      ReconnectPolicy,
+    events:      Arc<broadcast::Sender<Event>>,
+    credentials: Option<Arc<dyn CredentialsProvider>>,
+    /// Set on shutdown, after which connections stay closed
+    shutdown:    AtomicBool,
+}
+
+// NOTE: States are swapped when a dropped connection is re-established.
 #[derive(Debug)]
 pub(super) struct Connection<T: ClientFormat> {
-    #[expect(unused)]
-    addrs:         Arc<[SocketAddr]>,
+    targets:       Targets,
     options:       Arc<ClientOptions>,
-    io_task:       Arc<Mutex<IoHandle<T::Data>>>,
+    /// Also held while reconnecting, so each dropped connection is re-established once
+    io_task:       Mutex<IoHandle<T::Data>>,
     metadata:      ClientMetadata,
+    hooks:         Hooks,
+    reconnect:     Option<Reconnect>,
     #[cfg(not(feature = "inner_pool"))]
-    state:         Arc<ConnectState<T::Data>>,
+    state:         RwLock<Arc<ConnectState<T::Data>>>,
     /// NOTE: Max connections must remain at 4, unless algorithm changes
     #[cfg(feature = "inner_pool")]
     state:         Vec<ArcSwap<ConnectState<T::Data>>>,
@@ -120,10 +158,12 @@ impl<T: ClientFormat> Connection<T> {
     )]
     pub(crate) async fn connect(
         client_id: u16,
-        addrs: Vec<SocketAddr>,
+        targets: Targets,
         options: ClientOptions,
         events: Arc<broadcast::Sender<Event>>,
         trace_ctx: TraceContext,
+        hooks: Hooks,
+        credentials: Option<Arc<dyn CredentialsProvider>>,
     ) -> Result<Self> {
         let span = Span::current();
         span.in_scope(|| trace!({ {ATT_CID} = client_id }, "connecting stream"));
@@ -137,19 +177,19 @@ impl<T: ClientFormat> Connection<T> {
             client_id,
             compression: options.compression,
             arrow_options: options.ext.arrow.unwrap_or_default(),
+            redact_queries: options.ext.redact_queries,
+            read_ahead: options.ext.read_ahead_blocks.unwrap_or(READ_AHEAD_DEFAULT).max(1),
+            readonly: options.ext.readonly,
+            frame_limits: options.ext.frame_limits,
         };
 
-        // Install rustls provider if using tls
-        if options.use_tls {
-            drop(rustls::crypto::aws_lc_rs::default_provider().install_default());
-        }
-
         // Establish tcp connection, perform handshake, and spawn io task
         let state = Arc::new(
-            Self::connect_inner(&addrs, &mut io_task, Arc::clone(&events), &options, metadata)
-                .await?,
+            Self::connect_any(&targets, &mut io_task, &events, &options, metadata, &hooks).await?,
         );
 
+        #[cfg(not(feature = "inner_pool"))]
+        let state = RwLock::new(state);
         #[cfg(feature = "inner_pool")]
         let mut state = vec![ArcSwap::from(state)];
 
@@ -163,36 +203,90 @@ impl<T: ClientFormat> Connection<T> {
 
         #[cfg(feature = "inner_pool")]
         for _ in 0..inner_pool_size.saturating_sub(1) {
-            let events = Arc::clone(&events);
             state.push(ArcSwap::from(Arc::new(
-                Self::connect_inner(&addrs, &mut io_task, events, &options, metadata).await?,
+                Self::connect_any(&targets, &mut io_task, &events, &options, metadata, &hooks)
+                    .await?,
             )));
         }
 
+        let reconnect = options.ext.reconnect.map(|policy| Reconnect {
+            policy,
+            events,
+            credentials,
+            shutdown: AtomicBool::new(false),
+        });
+
         Ok(Self {
-            addrs: Arc::from(addrs.as_slice()),
-            io_task: Arc::new(Mutex::new(io_task)),
+            targets,
+            io_task: Mutex::new(io_task),
             options: Arc::new(options),
             metadata,
+            hooks,
+            reconnect,
             state,
             #[cfg(feature = "inner_pool")]
             load_balancer: Arc::new(load::AtomicLoad::new(inner_pool_size)),
         })
     }
 
+    /// Connect to the first endpoint available, in the order picked by the endpoint strategy.
+    async fn connect_any(
+        targets: &Targets,
+        io_task: &mut IoHandle<T::Data>,
+        events: &Arc<broadcast::Sender<Event>>,
+        options: &ClientOptions,
+        metadata: ClientMetadata,
+        hooks: &Hooks,
+    ) -> Result<ConnectState<T::Data>> {
+        let mut last_error = None;
+        for target in targets.order() {
+            let events = Arc::clone(events);
+            match Self::connect_inner(target, io_task, events, options, metadata, hooks).await {
+                Ok(state) => return Ok(state),
+                Err(error) if targets.len() > 1 => {
+                    let endpoint = target.endpoint();
+                    warn!(?error, %endpoint, "Failed to connect to endpoint, failing over");
+                    last_error = Some(error);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Err(last_error.unwrap_or(Error::MissingConnectionInformation))
+    }
+
     async fn connect_inner(
-        addrs: &[SocketAddr],
+        target: &Target,
         io_task: &mut IoHandle<T::Data>,
         events: Arc<broadcast::Sender<Event>>,
         options: &ClientOptions,
         metadata: ClientMetadata,
+        hooks: &Hooks,
     ) -> Result<ConnectState<T::Data>> {
+        let (addrs, endpoint) = (target.addrs.as_slice(), target.endpoint());
         if options.use_tls {
-            let tls_stream = super::tcp::connect_tls(addrs, options.domain.as_deref()).await?;
-            Self::establish_connection(tls_stream, io_task, events, options, metadata).await
+            #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+            {
+                let domain = target.domain.as_deref().or(options.domain.as_deref());
+                let pins = &options.ext.pinned_spki;
+                let stream = super::tcp::connect_tls(addrs, domain, pins).await?;
+                Self::establish_connection(
+                    stream, io_task, events, options, metadata, hooks, endpoint,
+                )
+                .await
+            }
+            #[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+            {
+                Err(Error::Configuration(
+                    "TLS requested but no TLS backend enabled, enable `rustls-tls` or `native-tls`"
+                        .into(),
+                ))
+            }
+        } else if !options.ext.pinned_spki.is_empty() {
+            Err(Error::Configuration("SPKI pins require TLS, enable it with `with_tls`".into()))
         } else {
-            let tcp_stream = super::tcp::connect_socket(addrs).await?;
-            Self::establish_connection(tcp_stream, io_task, events, options, metadata).await
+            let stream = super::tcp::connect_socket(addrs).await?;
+            Self::establish_connection(stream, io_task, events, options, metadata, hooks, endpoint)
+                .await
         }
     }
 
@@ -202,6 +296,8 @@ impl<T: ClientFormat> Connection<T> {
         events: Arc<broadcast::Sender<Event>>,
         options: &ClientOptions,
         metadata: ClientMetadata,
+        hooks: &Hooks,
+        endpoint: SocketAddr,
     ) -> Result<ConnectState<T::Data>> {
         let cid = metadata.client_id;
 
@@ -211,6 +307,10 @@ impl<T: ClientFormat> Connection<T> {
 
         // Perform connection handshake
         let server_hello = Arc::new(Self::perform_handshake(&mut stream, cid, options).await?);
+        let server_version = server_hello.version;
+        let max_insert_block_size =
+            server_hello.settings.as_ref().and_then(|s| s.get_usize("max_insert_block_size"));
+        hooks.connection.connected(&ConnectEvent { client_id: cid, endpoint, server_version });
 
         // Create operation channel
         let (operations, op_rx) = mpsc::channel(InternalConn::<T>::CAPACITY);
@@ -218,6 +318,11 @@ impl<T: ClientFormat> Connection<T> {
         // Split stream
         let (reader, writer) = tokio::io::split(stream);
 
+        // Client info is sent with every query
+        let client_info = Arc::new(options.ext.client_info.clone());
+        let hook = hooks.statement.clone();
+        let connection_hooks = hooks.connection.clone();
+
         // Spawn read loop
         let handle = io_task.spawn(
             async move {
@@ -225,7 +330,8 @@ impl<T: ClientFormat> Connection<T> {
                 let chunk_recv = server_hello.supports_chunked_recv();
 
                 // Create and run internal client
-                let mut internal = InternalConn::<T>::new(metadata, events, server_hello);
+                let mut internal =
+                    InternalConn::<T>::new(metadata, events, server_hello, client_info, hook);
 
                 let reader = BufReader::with_capacity(conn_read_buffer_size(), reader);
                 let writer = BufWriter::with_capacity(conn_write_buffer_size(), writer);
@@ -252,6 +358,10 @@ impl<T: ClientFormat> Connection<T> {
                 if let Err(error) = result {
                     error!(?error, "Internal connection lost");
                     internal_status.store(ConnectionStatus::Error.into(), Ordering::Release);
+                    // Shutdowns are reported by the client, which may abort this task first
+                    let reason = DisconnectReason::Error(error.to_string());
+                    let event = DisconnectEvent { client_id: cid, endpoint, reason };
+                    connection_hooks.disconnected(&event);
                 } else {
                     info!("Internal connection closed");
                     internal_status.store(ConnectionStatus::Closed.into(), Ordering::Release);
@@ -269,7 +379,14 @@ impl<T: ClientFormat> Connection<T> {
         );
 
         trace!({ ATT_CID } = cid, "spawned connection loop");
-        Ok(ConnectState { status, channel: operations, handle })
+        Ok(ConnectState {
+            status,
+            channel: operations,
+            handle,
+            server_version,
+            endpoint,
+            max_insert_block_size,
+        })
     }
 
     #[instrument(
@@ -288,6 +405,13 @@ impl<T: ClientFormat> Connection<T> {
         qid: Qid,
         finished: bool,
     ) -> Result<usize> {
+        // Refuse writes before they reach the server
+        if self.metadata.readonly
+            && let Operation::Query { query, .. } = &op
+        {
+            check_read_only(query)?;
+        }
+
         #[cfg(not(feature = "inner_pool"))]
         let conn_idx = 0; // Dummy for non-fast mode
         #[cfg(feature = "inner_pool")]
@@ -307,24 +431,32 @@ impl<T: ClientFormat> Connection<T> {
             finished
         );
 
-        // Get the current state
-        #[cfg(not(feature = "inner_pool"))]
-        let state = &self.state;
-        #[cfg(feature = "inner_pool")]
-        let state = self.state[conn_idx].load();
-
-        // Get the current status
-        #[cfg(not(feature = "inner_pool"))]
-        let status = self.state.status.load(Ordering::Acquire);
-        #[cfg(feature = "inner_pool")]
-        let status = state.status.load(Ordering::Acquire);
+        // Reconnects are configured and the connection is not shut down
+        let reconnect =
+            self.reconnect.as_ref().filter(|reconnect| !reconnect.shutdown.load(Ordering::Acquire));
 
-        // First check if the underlying connection is ok (until re-connects are impelemented)
-        if status > 0 {
-            return Err(Error::Client("No active connection".into()));
+        // First check if the underlying connection is ok, re-establishing it if configured
+        let mut state = self.state(conn_idx);
+        if state.status() != ConnectionStatus::Open {
+            let Some(reconnect) = reconnect else {
+                return Err(Error::Client("No active connection".into()));
+            };
+            state = self.reconnect(conn_idx, reconnect).await?;
         }
 
-        let result = state.channel.send(Message::Operation { qid, op }).instrument(span).await;
+        let message = Message::Operation { qid, op };
+        let result = state.channel.send(message).instrument(span.clone()).await;
+
+        // The connection may also drop between checking its status and sending
+        let result = match (result, reconnect) {
+            (Err(mpsc::error::SendError(message)), Some(reconnect)) => {
+                self.update_status(conn_idx, ConnectionStatus::Closed);
+                let state = self.reconnect(conn_idx, reconnect).await?;
+                state.channel.send(message).instrument(span).await
+            }
+            (result, _) => result,
+        };
+
         if result.is_err() {
             error!({ ATT_QID } = %qid, "failed to send message");
             self.update_status(conn_idx, ConnectionStatus::Closed);
@@ -334,6 +466,84 @@ impl<T: ClientFormat> Connection<T> {
         Ok(conn_idx)
     }
 
+    /// Re-establish the dropped connection `idx`, retrying as configured by the policy.
+    async fn reconnect(
+        &self,
+        idx: usize,
+        reconnect: &Reconnect,
+    ) -> Result<Arc<ConnectState<T::Data>>> {
+        let cid = self.metadata.client_id;
+        let mut io_task = self.io_task.lock().await;
+
+        // Another operation may have reconnected while waiting for the lock
+        let state = self.state(idx);
+        if state.status() == ConnectionStatus::Open {
+            return Ok(state);
+        }
+
+        // Reap the io tasks of dropped connections
+        while io_task.try_join_next().is_some() {}
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            debug!({ ATT_CID } = cid, attempt, "Reconnecting conn {idx}");
+
+            // Fetch fresh credentials, as on the initial connect
+            let result = match reconnect.credentials.as_ref() {
+                Some(provider) => match provider.credentials().await {
+                    Ok(credentials) => {
+                        let mut options = ClientOptions::clone(&self.options);
+                        options.username = credentials.username;
+                        options.password = credentials.password;
+                        self.reconnect_inner(&mut io_task, reconnect, &options).await
+                    }
+                    Err(error) => Err(error),
+                },
+                None => self.reconnect_inner(&mut io_task, reconnect, &self.options).await,
+            };
+
+            let max_attempts = reconnect.policy.max_attempts.max(1);
+            let retry = result.is_err() && attempt < max_attempts;
+            let retry_in = retry.then(|| reconnect.policy.backoff(attempt));
+            self.hooks.connection.reconnect_attempted(&ReconnectAttemptEvent {
+                client_id: cid,
+                endpoint: result.as_ref().ok().map(|state| state.endpoint),
+                attempt,
+                max_attempts,
+                error: result.as_ref().err().map(ToString::to_string),
+                retry_in,
+            });
+
+            match (result, retry_in) {
+                (Ok(state), _) => {
+                    info!({ ATT_CID } = cid, attempt, "Reconnected conn {idx}");
+                    let state = Arc::new(state);
+                    self.replace_state(idx, Arc::clone(&state));
+                    return Ok(state);
+                }
+                (Err(error), Some(delay)) => {
+                    warn!(?error, { ATT_CID } = cid, attempt, ?delay, "Reconnect failed, retrying");
+                    tokio::time::sleep(delay).await;
+                }
+                (Err(error), None) => {
+                    error!(?error, { ATT_CID } = cid, attempt, "Failed to reconnect conn {idx}");
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    async fn reconnect_inner(
+        &self,
+        io_task: &mut IoHandle<T::Data>,
+        reconnect: &Reconnect,
+        options: &ClientOptions,
+    ) -> Result<ConnectState<T::Data>> {
+        let (targets, events, hooks) = (&self.targets, &reconnect.events, &self.hooks);
+        Self::connect_any(targets, io_task, events, options, self.metadata, hooks).await
+    }
+
     #[instrument(
         level = "trace",
         skip_all,
@@ -341,10 +551,16 @@ impl<T: ClientFormat> Connection<T> {
     )]
     pub(crate) async fn shutdown(&self) -> Result<()> {
         trace!({ ATT_CID } = self.metadata.client_id, "Shutting down connections");
+        if let Some(reconnect) = self.reconnect.as_ref() {
+            reconnect.shutdown.store(true, Ordering::Release);
+        }
         #[cfg(not(feature = "inner_pool"))]
         {
-            if self.state.channel.send(Message::Shutdown).await.is_err() {
+            let state = self.state(0);
+            if state.channel.send(Message::Shutdown).await.is_err() {
                 error!("Failed to shutdown connection");
+            } else {
+                self.shut_down(&state);
             }
         }
         #[cfg(feature = "inner_pool")]
@@ -355,16 +571,20 @@ impl<T: ClientFormat> Connection<T> {
                 // Send the message again to shutdown the next internal connection
                 if state.channel.send(Message::Shutdown).await.is_err() {
                     error!("Failed to shutdown connection {i}");
+                } else {
+                    self.shut_down(&state);
                 }
             }
         }
-        self.io_task.lock().abort_all();
+        self.io_task.lock().await.abort_all();
         Ok(())
     }
 
     pub(crate) async fn check_connection(&self, ping: bool) -> Result<()> {
-        // First check that internal channels are ok
-        self.check_channel()?;
+        // First check that internal channels are ok, unless the ping may re-establish them
+        if !ping || self.reconnect.is_none() {
+            self.check_channel()?;
+        }
 
         if !ping {
             return Ok(());
@@ -397,15 +617,38 @@ impl<T: ClientFormat> Connection<T> {
         Ok(())
     }
 
+    /// Report a connection shut down by the client.
+    fn shut_down(&self, state: &ConnectState<T::Data>) {
+        let reason = DisconnectReason::Shutdown;
+        let (client_id, endpoint) = (self.metadata.client_id, state.endpoint);
+        self.hooks.connection.disconnected(&DisconnectEvent { client_id, endpoint, reason });
+    }
+
     fn update_status(&self, idx: usize, status: ConnectionStatus) {
         trace!({ ATT_CID } = self.metadata.client_id, ?status, "Updating status conn {idx}");
+        self.state(idx).status.store(status.into(), Ordering::Release);
+    }
 
+    fn state(&self, idx: usize) -> Arc<ConnectState<T::Data>> {
         #[cfg(not(feature = "inner_pool"))]
-        let state = &self.state;
+        let state = {
+            debug_assert_eq!(idx, 0);
+            Arc::clone(&*self.state.read())
+        };
         #[cfg(feature = "inner_pool")]
-        let state = self.state[idx].load();
+        let state = self.state[idx].load_full();
 
-        state.status.store(status.into(), Ordering::Release);
+        state
+    }
+
+    fn replace_state(&self, idx: usize, state: Arc<ConnectState<T::Data>>) {
+        #[cfg(not(feature = "inner_pool"))]
+        {
+            debug_assert_eq!(idx, 0);
+            *self.state.write() = state;
+        }
+        #[cfg(feature = "inner_pool")]
+        self.state[idx].store(state);
     }
 
     async fn perform_handshake<RW: ClickHouseRead + ClickHouseWrite + Send + 'static>(
@@ -416,10 +659,26 @@ impl<T: ClientFormat> Connection<T> {
         use crate::client::reader::Reader;
         use crate::client::writer::Writer;
 
+        #[cfg(feature = "ssh")]
+        let ssh_signer =
+            options.ext.ssh_key.as_ref().map(super::ssh::SshSigner::load).transpose()?;
+
+        #[cfg(feature = "ssh")]
+        let (username, password) = if ssh_signer.is_some() {
+            // The marker tells the server a challenge exchange follows the hello
+            (format!("{}{}", super::ssh::SSH_KEY_AUTHENTICATION_MARKER, options.username), "")
+        } else {
+            (options.username.clone(), options.password.get())
+        };
+        #[cfg(not(feature = "ssh"))]
+        let (username, password) = (options.username.clone(), options.password.get());
+
         let client_hello = ClientHello {
             default_database: options.default_database.clone(),
-            username:         options.username.clone(),
-            password:         options.password.get().to_string(),
+            username,
+            password: password.to_string(),
+            client_name: options.ext.client_info.client_name.clone(),
+            client_version: options.ext.client_info.client_version,
         };
 
         // Send client hello
@@ -427,6 +686,23 @@ impl<T: ClientFormat> Connection<T> {
             .await
             .inspect_err(|error| error!(?error, { ATT_CID } = client_id, "Failed to send hello"))?;
 
+        // Answer the SSH challenge before the server replies with its hello
+        #[cfg(feature = "ssh")]
+        if let Some(signer) = ssh_signer {
+            Writer::send_ssh_challenge_request(stream).await?;
+            let challenge = Reader::receive_ssh_challenge(stream).await?;
+            let signature = signer.sign(
+                DBMS_TCP_PROTOCOL_VERSION,
+                &options.default_database,
+                &options.username,
+                &challenge,
+            )?;
+            Writer::send_ssh_challenge_response(stream, &signature).await.inspect_err(|error| {
+                error!(?error, { ATT_CID } = client_id, "Failed to answer SSH challenge");
+            })?;
+            debug!({ ATT_CID } = client_id, "Answered SSH challenge");
+        }
+
         // Receive server hello
         let chunked_modes = (options.ext.chunked_send, options.ext.chunked_recv);
         let server_hello =
@@ -435,7 +711,9 @@ impl<T: ClientFormat> Connection<T> {
         trace!({ ATT_CID } = client_id, ?server_hello, "Finished handshake");
 
         if server_hello.revision_version >= DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM {
-            Writer::send_addendum(stream, server_hello.revision_version, &server_hello).await?;
+            let quota_key = options.ext.client_info.quota_key.as_deref().unwrap_or_default();
+            Writer::send_addendum(stream, server_hello.revision_version, &server_hello, quota_key)
+                .await?;
             stream.flush().await.inspect_err(|error| error!(?error, "Error writing addendum"))?;
         }
 
@@ -448,38 +726,31 @@ impl<T: ClientFormat> Connection<T> {
 
     pub(crate) fn database(&self) -> &str { &self.options.default_database }
 
+    pub(crate) fn server_version(&self) -> (u64, u64, u64) {
+        // Every inner connection targets the same server
+        self.state(0).server_version
+    }
+
+    /// The server's `max_insert_block_size`, if it differs from the default.
+    pub(crate) fn server_max_insert_block_size(&self) -> Option<usize> {
+        self.state(0).max_insert_block_size
+    }
+
     #[cfg(feature = "inner_pool")]
     pub(crate) fn finish(&self, conn_idx: usize, weight: u8) {
         self.load_balancer.finish(usize::from(weight), conn_idx);
     }
 
     pub(crate) fn status(&self) -> ConnectionStatus {
-        #[cfg(not(feature = "inner_pool"))]
-        let status = ConnectionStatus::from(self.state.status.load(Ordering::Acquire));
-
         // TODO: Status is strange if we have an internal pool. Figure this out.
         // Just use the first channel for now
-        #[cfg(feature = "inner_pool")]
-        let status = ConnectionStatus::from(self.state[0].load().status.load(Ordering::Acquire));
-
-        status
+        self.state(0).status()
     }
 
     fn check_channel(&self) -> Result<()> {
-        #[cfg(not(feature = "inner_pool"))]
-        {
-            if self.state.channel.is_closed() {
-                self.update_status(0, ConnectionStatus::Closed);
-                Err(Error::ChannelClosed)
-            } else {
-                Ok(())
-            }
-        }
-
         // TODO: Checking channel is strange if we have an internal pool. Figure this out.
         // Just return status of first connection for now
-        #[cfg(feature = "inner_pool")]
-        if self.state[0].load().channel.is_closed() {
+        if self.state(0).channel.is_closed() {
             self.update_status(0, ConnectionStatus::Closed);
             Err(Error::ChannelClosed)
         } else {
@@ -491,7 +762,7 @@ impl<T: ClientFormat> Connection<T> {
 impl<T: ClientFormat> Drop for Connection<T> {
     fn drop(&mut self) {
         trace!({ ATT_CID } = self.metadata.client_id, "Connection dropped");
-        self.io_task.lock().abort_all();
+        self.io_task.get_mut().abort_all();
     }
 }
 
@@ -0,0 +1,221 @@
+//! Credentials fetched at connect time, so passwords and tokens can be rotated without a restart.
+//!
+//! A [`CredentialsProvider`] set with [`super::builder::ClientBuilder::with_credentials_provider`]
+//! is asked for credentials every time a connection is established, including when a pool
+//! replaces a broken connection, and takes precedence over the builder's username and password.
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use futures_util::future::BoxFuture;
+use parking_lot::Mutex;
+
+use crate::prelude::Secret;
+use crate::{Error, Result};
+
+/// Environment variable read by [`EnvCredentials::default`] for the username.
+pub const CREDENTIALS_USER_ENV_VAR: &str = "CLICKHOUSE_USER";
+/// Environment variable read by [`EnvCredentials::default`] for the password.
+pub const CREDENTIALS_PASSWORD_ENV_VAR: &str = "CLICKHOUSE_PASSWORD";
+
+/// A username and password, or token, used to authenticate a connection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: Secret,
+}
+
+impl Credentials {
+    pub fn new(username: impl Into<String>, password: impl Into<Secret>) -> Self {
+        Self { username: username.into(), password: password.into() }
+    }
+}
+
+/// A source of [`Credentials`], asked for fresh credentials whenever a connection is made.
+///
+/// Implemented by [`Credentials`] for static credentials, [`EnvCredentials`],
+/// [`FileCredentials`], and [`CredentialsCallback`] for anything else, such as fetching a token
+/// from a secrets manager.
+pub trait CredentialsProvider: std::fmt::Debug + Send + Sync + 'static {
+    /// Fetch the credentials to authenticate the next connection with.
+    ///
+    /// # Errors
+    /// Errors fail the connection attempt.
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>>;
+}
+
+impl CredentialsProvider for Credentials {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        Box::pin(std::future::ready(Ok(self.clone())))
+    }
+}
+
+/// Credentials read from environment variables on every connect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvCredentials {
+    username_var: String,
+    password_var: String,
+}
+
+impl EnvCredentials {
+    /// Read the username and password from the named environment variables.
+    pub fn new(username_var: impl Into<String>, password_var: impl Into<String>) -> Self {
+        Self { username_var: username_var.into(), password_var: password_var.into() }
+    }
+
+    fn read(&self) -> Result<Credentials> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|error| {
+                Error::Configuration(format!("Credentials variable {name} unavailable: {error}"))
+            })
+        };
+        Ok(Credentials::new(var(&self.username_var)?, var(&self.password_var)?))
+    }
+}
+
+impl Default for EnvCredentials {
+    /// Read [`CREDENTIALS_USER_ENV_VAR`] and [`CREDENTIALS_PASSWORD_ENV_VAR`].
+    fn default() -> Self { Self::new(CREDENTIALS_USER_ENV_VAR, CREDENTIALS_PASSWORD_ENV_VAR) }
+}
+
+impl CredentialsProvider for EnvCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        Box::pin(std::future::ready(self.read()))
+    }
+}
+
+/// A fixed username with a password read from a file, such as a mounted Kubernetes secret.
+///
+/// The file is re-read whenever its modification time changes, so a rotated password is picked
+/// up by the next connection. Trailing newlines are ignored.
+#[derive(Debug)]
+pub struct FileCredentials {
+    username: String,
+    path:     PathBuf,
+    cached:   Mutex<Option<(SystemTime, Secret)>>,
+}
+
+impl FileCredentials {
+    pub fn new(username: impl Into<String>, password_file: impl Into<PathBuf>) -> Self {
+        Self {
+            username: username.into(),
+            path:     password_file.into(),
+            cached:   Mutex::new(None),
+        }
+    }
+
+    fn read(&self) -> Result<Credentials> {
+        let unavailable = |error: std::io::Error| {
+            Error::Configuration(format!(
+                "Credentials file {} unavailable: {error}",
+                self.path.display()
+            ))
+        };
+        let modified =
+            std::fs::metadata(&self.path).and_then(|m| m.modified()).map_err(unavailable)?;
+
+        let mut cached = self.cached.lock();
+        if let Some((_, password)) = cached.as_ref().filter(|(at, _)| *at == modified) {
+            return Ok(Credentials::new(self.username.clone(), password.clone()));
+        }
+        let contents = std::fs::read_to_string(&self.path).map_err(unavailable)?;
+        let password = Secret::new(contents.trim_end_matches(['\r', '\n']));
+        *cached = Some((modified, password.clone()));
+        Ok(Credentials::new(self.username.clone(), password))
+    }
+}
+
+impl CredentialsProvider for FileCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        Box::pin(std::future::ready(self.read()))
+    }
+}
+
+type CredentialsFn = dyn Fn() -> BoxFuture<'static, Result<Credentials>> + Send + Sync;
+
+/// Credentials produced by an async callback, for example fetching a short-lived token.
+#[derive(Clone)]
+pub struct CredentialsCallback(Arc<CredentialsFn>);
+
+impl CredentialsCallback {
+    /// Create a provider from a callback returning a future of the credentials.
+    pub fn new<F, Fut>(callback: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Credentials>> + Send + 'static,
+    {
+        Self(Arc::new(move || Box::pin(callback())))
+    }
+}
+
+impl std::fmt::Debug for CredentialsCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CredentialsCallback")
+    }
+}
+
+impl CredentialsProvider for CredentialsCallback {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> { (self.0)() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::Qid;
+
+    #[tokio::test]
+    async fn test_static_credentials() {
+        let credentials = Credentials::new("alice", "secret");
+        assert_eq!(credentials.credentials().await.unwrap(), credentials);
+    }
+
+    #[tokio::test]
+    async fn test_env_credentials() {
+        let provider = EnvCredentials::new(
+            "CLICKHOUSE_ARROW_TEST_CREDENTIALS_USER",
+            "CLICKHOUSE_ARROW_TEST_CREDENTIALS_PASSWORD",
+        );
+        assert!(matches!(provider.credentials().await, Err(Error::Configuration(_))));
+
+        // SAFETY: The variables are unique to this test
+        unsafe {
+            std::env::set_var("CLICKHOUSE_ARROW_TEST_CREDENTIALS_USER", "bob");
+            std::env::set_var("CLICKHOUSE_ARROW_TEST_CREDENTIALS_PASSWORD", "hunter2");
+        }
+        assert_eq!(provider.credentials().await.unwrap(), Credentials::new("bob", "hunter2"));
+    }
+
+    #[tokio::test]
+    async fn test_file_credentials() {
+        let path = std::env::temp_dir().join(format!("clickhouse-arrow-creds-{}", Qid::new()));
+        let provider = FileCredentials::new("carol", &path);
+        assert!(matches!(provider.credentials().await, Err(Error::Configuration(_))));
+
+        std::fs::write(&path, "first\n").unwrap();
+        assert_eq!(provider.credentials().await.unwrap().password, Secret::new("first"));
+
+        // Rotating the file is picked up once its modification time changes
+        std::fs::write(&path, "second").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(60)).unwrap();
+        assert_eq!(provider.credentials().await.unwrap().password, Secret::new("second"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_credentials_callback() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let provider = CredentialsCallback::new(move || {
+            let call = counter.fetch_add(1, Ordering::Relaxed);
+            async move { Ok(Credentials::new("dave", format!("token-{call}"))) }
+        });
+        assert_eq!(provider.credentials().await.unwrap().password, Secret::new("token-0"));
+        assert_eq!(provider.credentials().await.unwrap().password, Secret::new("token-1"));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+}
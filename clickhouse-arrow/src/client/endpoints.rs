@@ -0,0 +1,226 @@
+//! Connecting to one of several endpoints, such as the replicas of a cluster.
+//!
+//! A client built with [`ClientBuilder::with_endpoints`](super::ClientBuilder::with_endpoints)
+//! tries its endpoints in an order picked by the [`EndpointStrategy`] whenever it establishes a
+//! connection: the initial one, the inner connections of the `inner_pool` feature, the clients
+//! of a pool, and reconnects. An endpoint that cannot be connected to is skipped for the next
+//! one, so connections only fail once every endpoint failed.
+//!
+//! Endpoints that cannot be resolved are skipped as well, and resolved again by the next client
+//! built. The allowed hosts are checked against every endpoint that resolved.
+use std::hash::{BuildHasher, RandomState};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::tcp::Destination;
+use crate::prelude::*;
+use crate::{ClientOptions, Error, Result};
+
+/// The order endpoints are tried in when connecting, see
+/// [`ClientBuilder::with_endpoints`](super::ClientBuilder::with_endpoints).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EndpointStrategy {
+    /// Try the endpoints in the order given, preferring the first one available.
+    #[default]
+    FirstAlive,
+    /// Start each connection at the endpoint after the one the previous connection started at,
+    /// spreading connections evenly. The rotation is shared by the clients of a pool.
+    RoundRobin,
+    /// Start each connection at a random endpoint.
+    Random,
+}
+
+/// The endpoints of a client and their strategy, as configured on the builder.
+#[derive(Debug, Clone)]
+pub(crate) struct Endpoints {
+    destinations: Vec<Destination>,
+    strategy:     EndpointStrategy,
+    /// The round-robin rotation, shared by the clients built from clones of a builder
+    next:         Arc<AtomicUsize>,
+}
+
+impl Endpoints {
+    pub(crate) fn new(destinations: Vec<Destination>, strategy: EndpointStrategy) -> Self {
+        Self { destinations, strategy, next: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    pub(crate) fn destinations(&self) -> &[Destination] { &self.destinations }
+
+    /// Resolve the endpoints, skipping those that cannot be resolved unless none can.
+    ///
+    /// # Errors
+    /// Returns an error if no endpoint resolves, or an endpoint is not in the allowed hosts.
+    pub(crate) async fn resolve(&self, options: &ClientOptions) -> Result<Targets> {
+        let failover = self.destinations.len() > 1;
+        let mut targets = Vec::with_capacity(self.destinations.len());
+        let mut last_error = None;
+        for destination in &self.destinations {
+            let addrs = match destination.resolve(options.ipv4_only).await {
+                Ok(addrs) if !addrs.is_empty() => addrs,
+                Ok(_) => {
+                    let error = "Socket addresses cannot be empty".to_string();
+                    last_error = Some(Error::MalformedConnectionInformation(error));
+                    continue;
+                }
+                Err(error) => {
+                    if failover {
+                        warn!(?error, ?destination, "Failed to resolve endpoint, skipping it");
+                    }
+                    last_error = Some(error);
+                    continue;
+                }
+            };
+
+            // Every endpoint has its own TLS domain unless one was set explicitly
+            let domain = options
+                .domain
+                .is_none()
+                .then(|| destination.domain())
+                .filter(|domain| !domain.is_empty());
+            super::trust::check_allowed_hosts(
+                &options.ext.allowed_hosts,
+                destination.host(),
+                domain.as_deref().or(options.domain.as_deref()),
+                &addrs,
+            )?;
+            targets.push(Target { addrs, domain });
+        }
+
+        if targets.is_empty() {
+            return Err(last_error.unwrap_or(Error::MissingConnectionInformation));
+        }
+        Ok(Targets { targets, strategy: self.strategy, next: Arc::clone(&self.next) })
+    }
+}
+
+impl From<Destination> for Endpoints {
+    fn from(destination: Destination) -> Self {
+        Self::new(vec![destination], EndpointStrategy::default())
+    }
+}
+
+/// An endpoint resolved to its socket addresses.
+#[derive(Debug, Clone)]
+pub(crate) struct Target {
+    /// The non-empty socket addresses of the endpoint
+    pub(crate) addrs:  Vec<SocketAddr>,
+    /// The TLS domain of the endpoint, replacing the domain of the options
+    pub(crate) domain: Option<String>,
+}
+
+impl Target {
+    /// The address connections are made to.
+    pub(crate) fn endpoint(&self) -> SocketAddr { self.addrs[0] }
+}
+
+/// The resolved endpoints of a client, see the [module docs](self).
+#[derive(Debug)]
+pub(crate) struct Targets {
+    targets:  Vec<Target>,
+    strategy: EndpointStrategy,
+    next:     Arc<AtomicUsize>,
+}
+
+impl Targets {
+    pub(crate) fn len(&self) -> usize { self.targets.len() }
+
+    /// The address of the first endpoint configured.
+    pub(crate) fn primary(&self) -> SocketAddr { self.targets[0].endpoint() }
+
+    /// The endpoints in the order the next connection tries them.
+    pub(crate) fn order(&self) -> impl Iterator<Item = &Target> {
+        let len = self.targets.len();
+        let start = match self.strategy {
+            EndpointStrategy::FirstAlive => 0,
+            EndpointStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % len,
+            EndpointStrategy::Random => {
+                let seed = self.next.fetch_add(1, Ordering::Relaxed);
+                let random = RandomState::new().hash_one(seed);
+                usize::try_from(random % len as u64).unwrap_or_default()
+            }
+        };
+        self.targets.iter().cycle().skip(start).take(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::*;
+
+    fn targets(strategy: EndpointStrategy, count: u8) -> Targets {
+        let targets = (1..=count)
+            .map(|i| Target {
+                addrs:  vec![SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, i)), 9000)],
+                domain: None,
+            })
+            .collect();
+        Targets { targets, strategy, next: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    fn order(targets: &Targets) -> Vec<u8> {
+        targets
+            .order()
+            .map(|target| match target.endpoint().ip() {
+                IpAddr::V4(ip) => ip.octets()[3],
+                IpAddr::V6(_) => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_first_alive_order() {
+        let targets = targets(EndpointStrategy::FirstAlive, 3);
+        assert_eq!(order(&targets), vec![1, 2, 3]);
+        assert_eq!(order(&targets), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_round_robin_order() {
+        let targets = targets(EndpointStrategy::RoundRobin, 3);
+        assert_eq!(order(&targets), vec![1, 2, 3]);
+        assert_eq!(order(&targets), vec![2, 3, 1]);
+        assert_eq!(order(&targets), vec![3, 1, 2]);
+        assert_eq!(order(&targets), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_random_order() {
+        let targets = targets(EndpointStrategy::Random, 4);
+        let mut starts = std::collections::HashSet::new();
+        for _ in 0..64 {
+            let order = order(&targets);
+            // Endpoints after the random start follow in order
+            let start = order[0];
+            let expected = (0..4).map(|i| (start - 1 + i) % 4 + 1).collect::<Vec<_>>();
+            assert_eq!(order, expected);
+            let _ = starts.insert(start);
+        }
+        assert!(starts.len() > 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_skips_unresolvable() {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 9000));
+        let unresolvable = Destination::from("invalid.invalid:9000");
+        let endpoints =
+            Endpoints::new(vec![unresolvable.clone(), addr.into()], EndpointStrategy::FirstAlive);
+        let targets = endpoints.resolve(&ClientOptions::default()).await.unwrap();
+        assert_eq!(targets.len(), 1);
+        let target = targets.order().next().unwrap();
+        assert_eq!(target.endpoint(), addr);
+        // A single endpoint still gets its own TLS domain
+        assert_eq!(target.domain.as_deref(), Some("127.0.0.1"));
+
+        let endpoints = Endpoints::from(unresolvable);
+        assert!(endpoints.resolve(&ClientOptions::default()).await.is_err());
+
+        // The allowed hosts apply to every endpoint
+        let mut options = ClientOptions::default();
+        options.ext.allowed_hosts = vec!["10.0.0.0/8".into()];
+        let endpoints = Endpoints::new(vec![addr.into()], EndpointStrategy::RoundRobin);
+        assert!(endpoints.resolve(&options).await.is_err());
+    }
+}
@@ -1,26 +1,31 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU16;
+use std::time::Instant;
 
 use strum::{AsRefStr, IntoStaticStr};
 use tokio::sync::{broadcast, mpsc, oneshot};
 
-use super::Event;
 use super::chunk::ChunkWriter;
 use super::connection::ClientMetadata;
 use super::reader::Reader;
 use super::writer::{Query, Writer};
+use super::{ClientInfoOptions, Event};
 use crate::ClickHouseEvent;
+use crate::arrow::{BatchMetadata, DecimalRescale};
 use crate::errors::*;
-use crate::formats::DeserializerState;
+use crate::formats::{DataSize, DeserializerState};
 use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::limits::QueryTiming;
 use crate::native::block::Block;
 use crate::native::block_info::BlockInfo;
 use crate::native::client_info::ClientInfo;
+use crate::native::profile_events::ProfileEvents;
 use crate::native::protocol::{QueryProcessingStage, ServerData, ServerHello, ServerPacket};
 use crate::prelude::*;
 use crate::query::QueryParams;
 use crate::settings::Settings;
+use crate::telemetry::record_query_span;
 
 type ResponseReceiver<T> = mpsc::Receiver<Result<T>>;
 type ResponseSender<T> = mpsc::Sender<Result<T>>;
@@ -38,11 +43,25 @@ pub(crate) enum Operation<Data: Send + Sync> {
     Ping { response: oneshot::Sender<Result<()>> },
     #[strum(serialize = "Query")]
     Query {
-        query:    String,
-        settings: Option<Arc<Settings>>,
-        params:   Option<QueryParams>,
-        response: oneshot::Sender<Result<ResponseReceiver<Data>>>,
-        header:   Option<oneshot::Sender<Vec<(String, Type)>>>,
+        query:           String,
+        settings:        Option<Arc<Settings>>,
+        params:          Option<QueryParams>,
+        response:        oneshot::Sender<Result<ResponseReceiver<Data>>>,
+        header:          Option<oneshot::Sender<Vec<(String, Type)>>>,
+        /// Overrides the connection's quota key for this query only
+        quota_key:       Option<String>,
+        /// Names of the columns to decode, skipping all others
+        projection:      Option<Arc<[String]>>,
+        /// Decimal columns converted to another precision and scale while decoding
+        decimal_rescale: Option<Arc<[(String, DecimalRescale)]>>,
+        /// Records time to first batch and total stream duration
+        timing:          Option<Arc<QueryTiming>>,
+        /// Called with each progress update of this query
+        progress:        Option<ProgressHook>,
+        /// Overrides the connection's read-ahead for this query only
+        read_ahead:      Option<usize>,
+        /// Cancel the query on the server once its response is dropped
+        cancel_on_drop:  bool,
     },
     #[strum(serialize = "Insert")]
     Insert { data: Data, response: oneshot::Sender<Result<()>> },
@@ -88,19 +107,47 @@ pub(super) enum InsertState<T> {
 
 pub(super) struct ExecutingQuery<T: Send + Sync> {
     qid:             Qid,
+    /// Fingerprint of the query, see [`crate::telemetry::query_fingerprint`]
+    fingerprint:     u64,
+    /// The raw query, retained for the statement hook unless queries are redacted
+    query:           Option<String>,
+    /// When the query was sent
+    started:         Instant,
+    /// Whether no packet has been received for the query yet
+    first_packet:    bool,
+    /// Rows read and written, as reported by progress packets
+    read_rows:       u64,
+    written_rows:    u64,
+    /// Totals of the profile events reported so far
+    profile_events:  ProfileEvents,
     state:           QueryState,
     header:          Option<Vec<(String, Type)>>,
     header_response: Option<oneshot::Sender<Vec<(String, Type)>>>,
     response:        ResponseSender<T>,
+    timing:          Option<Arc<QueryTiming>>,
+    progress:        Option<ProgressHook>,
+    /// Whether to cancel the query once `response` is dropped
+    cancel_on_drop:  bool,
+    /// Whether a cancel packet was sent
+    cancelled:       bool,
+    /// Number of data blocks received so far
+    blocks:          u64,
 }
 
 pub(super) struct PendingQuery<T: Send + Sync> {
-    qid:      Qid,
-    query:    String,
-    settings: Option<Arc<Settings>>,
-    params:   Option<QueryParams>,
-    response: oneshot::Sender<Result<ResponseReceiver<T>>>,
-    header:   Option<oneshot::Sender<Vec<(String, Type)>>>,
+    qid:             Qid,
+    query:           String,
+    settings:        Option<Arc<Settings>>,
+    params:          Option<QueryParams>,
+    response:        oneshot::Sender<Result<ResponseReceiver<T>>>,
+    header:          Option<oneshot::Sender<Vec<(String, Type)>>>,
+    quota_key:       Option<String>,
+    projection:      Option<Arc<[String]>>,
+    decimal_rescale: Option<Arc<[(String, DecimalRescale)]>>,
+    timing:          Option<Arc<QueryTiming>>,
+    progress:        Option<ProgressHook>,
+    read_ahead:      Option<usize>,
+    cancel_on_drop:  bool,
 }
 
 pub(super) struct InternalConn<T: ClientFormat> {
@@ -111,6 +158,8 @@ pub(super) struct InternalConn<T: ClientFormat> {
     events:       Arc<broadcast::Sender<Event>>,
     metadata:     ClientMetadata,
     state:        DeserializerState<T::Deser>,
+    client_info:  Arc<ClientInfoOptions>,
+    hook:         Option<StatementHook>,
 }
 
 impl<T: ClientFormat> InternalConn<T> {
@@ -120,6 +169,8 @@ impl<T: ClientFormat> InternalConn<T> {
         metadata: ClientMetadata,
         events: Arc<broadcast::Sender<Event>>,
         server_hello: Arc<ServerHello>,
+        client_info: Arc<ClientInfoOptions>,
+        hook: Option<StatementHook>,
     ) -> Self {
         // Generate a unique connection id. Since `Connection` supports up to 4 connections in
         // `inner_pool` it's helpful to distinguish.
@@ -134,6 +185,8 @@ impl<T: ClientFormat> InternalConn<T> {
             metadata,
             events,
             state,
+            client_info,
+            hook,
         }
     }
 
@@ -151,8 +204,15 @@ impl<T: ClientFormat> InternalConn<T> {
         mut operations: mpsc::Receiver<Message<T::Data>>,
     ) -> Result<()> {
         loop {
-            match self.run_inner(&mut reader, &mut writer, &mut operations).await? {
-                OperationTask::Shutdown => return Ok(()),
+            let task = self
+                .run_inner(&mut reader, &mut writer, &mut operations)
+                .await
+                .inspect_err(|error| self.abandon_executing(&error.to_string()))?;
+            match task {
+                OperationTask::Shutdown => {
+                    self.abandon_executing("Connection shut down");
+                    return Ok(());
+                }
                 OperationTask::Ping(response) => {
                     let cid = self.cid;
                     let revision = self.server_hello.revision_version;
@@ -179,7 +239,11 @@ impl<T: ClientFormat> InternalConn<T> {
         mut operations: mpsc::Receiver<Message<T::Data>>,
     ) -> Result<()> {
         loop {
-            match self.run_inner(&mut reader, &mut writer, &mut operations).await? {
+            let task = self
+                .run_inner(&mut reader, &mut writer, &mut operations)
+                .await
+                .inspect_err(|error| self.abandon_executing(&error.to_string()))?;
+            match task {
                 OperationTask::Ping(response) => {
                     // Be sure to flush the Ping
                     writer.finish_chunk().await?;
@@ -192,7 +256,10 @@ impl<T: ClientFormat> InternalConn<T> {
                 // Logical chunk boundary, flush
                 OperationTask::Chunk(ChunkBoundary::Flush) => writer.finish_chunk().await?,
                 OperationTask::Chunk(ChunkBoundary::None) => {}
-                OperationTask::Shutdown => return Ok(()),
+                OperationTask::Shutdown => {
+                    self.abandon_executing("Connection shut down");
+                    return Ok(());
+                }
             }
         }
     }
@@ -229,6 +296,20 @@ impl<T: ClientFormat> InternalConn<T> {
             result = self.receive_packet(reader), if self.executing.is_some() => {
                 result.inspect_err(|error| error!(?error, { ATT_CID } = cid, "Fatal error"))?;
 
+                // Cancel the executing query if its response was dropped. The server ends the
+                // query with an exception or end of stream, which is read as usual
+                if let Some(exec) = self.executing.as_mut()
+                    && exec.cancel_on_drop
+                    && !exec.cancelled
+                    && exec.response.is_closed()
+                {
+                    let qid = exec.qid;
+                    debug!({ ATT_CON } = cid, { ATT_QID } = %qid, "Response dropped, cancelling");
+                    Writer::send_cancel(writer).await?;
+                    exec.cancelled = true;
+                    flush = OperationTask::Chunk(ChunkBoundary::Flush);
+                }
+
                 // Queue up next query if any
                 if self.executing.is_none()
                     && let Some(query) = self.pending.pop_front() {
@@ -270,8 +351,35 @@ impl<T: ClientFormat> InternalConn<T> {
                 return Ok(OperationTask::default());
             }
             // Query - NOTE: May be any type of query, ie DDL, DML, Settings, etc.
-            Operation::Query { query, settings, params, response, header } => {
-                let pending = PendingQuery { qid, query, settings, params, response, header };
+            Operation::Query {
+                query,
+                settings,
+                params,
+                response,
+                header,
+                quota_key,
+                projection,
+                decimal_rescale,
+                timing,
+                progress,
+                read_ahead,
+                cancel_on_drop,
+            } => {
+                let pending = PendingQuery {
+                    qid,
+                    query,
+                    settings,
+                    params,
+                    response,
+                    header,
+                    quota_key,
+                    projection,
+                    decimal_rescale,
+                    timing,
+                    progress,
+                    read_ahead,
+                    cancel_on_drop,
+                };
                 if self.pending.is_empty() && self.executing.is_none() {
                     self.send_query(writer, pending).await?;
                     return Ok(OperationTask::Chunk(ChunkBoundary::Flush));
@@ -318,6 +426,8 @@ impl<T: ClientFormat> InternalConn<T> {
         fields(
             clickhouse.connection.id = self.cid,
             clickhouse.query.id,
+            clickhouse.query.fingerprint,
+            clickhouse.query.first_byte_ms,
             clickhouse.packet.id,
             executing.query,
         ),
@@ -332,8 +442,10 @@ impl<T: ClientFormat> InternalConn<T> {
         };
 
         let qid = exec.qid;
-        let _ = Span::current().record("executing.query", tracing::field::display(&exec));
-        let _ = Span::current().record(ATT_QID, tracing::field::display(qid));
+        let span = Span::current();
+        let _ = span.record("executing.query", tracing::field::display(&exec));
+        let _ = span.record(ATT_QID, tracing::field::display(qid));
+        let _ = span.record(ATT_FINGERPRINT, format!("{:016x}", exec.fingerprint));
         trace!({ ATT_CON } = cid, { ATT_QID } = %qid, state = exec.state.as_ref(), "receiving");
 
         // Wait for packet from server
@@ -343,7 +455,15 @@ impl<T: ClientFormat> InternalConn<T> {
             Reader::receive_packet::<T>(reader, revision, self.metadata, &mut self.state).await?
         };
 
-        let _ = Span::current().record(ATT_PID, packet.as_ref());
+        // Time to first byte, measured once per query
+        if exec.first_packet {
+            exec.first_packet = false;
+            let elapsed = exec.started.elapsed().as_millis();
+            let _ = span.record(ATT_FIRST_BYTE, elapsed);
+            debug!({ ATT_CON } = cid, { ATT_QID } = %qid, elapsed_ms = elapsed, "first byte");
+        }
+
+        let _ = span.record(ATT_PID, packet.as_ref());
         debug!({ ATT_CON } = cid, { ATT_QID } = %qid, packet = packet.as_ref(), "packet");
 
         match packet {
@@ -357,13 +477,30 @@ impl<T: ClientFormat> InternalConn<T> {
                 exec.header = Some(header);
             }
             ServerPacket::Data(ServerData { block }) => {
+                if let Some(timing) = exec.timing.as_ref() {
+                    timing.record_first_batch(exec.started.elapsed());
+                }
+                let block = if self.metadata.arrow_options.batch_metadata {
+                    let server = self.server_hello.display_name.clone();
+                    let lineage = BatchMetadata { qid, index: exec.blocks, server };
+                    T::attach_batch_metadata(block, &lineage)
+                } else {
+                    block
+                };
+                exec.blocks += 1;
                 let _ = exec.response.send(Ok(block)).await.ok();
             }
             ServerPacket::ProfileEvents(info) => {
+                exec.profile_events.record(&info);
                 let event = ClickHouseEvent::Profile(info);
                 let _ = self.events.send(Event { event, qid, client_id }).ok();
             }
             ServerPacket::Progress(progress) => {
+                exec.read_rows += progress.read_rows;
+                exec.written_rows += progress.written_rows.unwrap_or_default();
+                if let Some(hook) = exec.progress.as_ref() {
+                    hook.call(qid, &progress);
+                }
                 let event = ClickHouseEvent::Progress(progress);
                 let _ = self.events.send(Event { event, qid, client_id }).ok();
             }
@@ -371,7 +508,8 @@ impl<T: ClientFormat> InternalConn<T> {
                 let error = exception.emit();
                 error!({ ATT_QID } = %exec.qid, { ATT_CON } = cid, "EXCEPTION: {error}");
                 let _ = exec.response.send(Err(error.clone().into())).await.ok();
-                drop(self.executing.take());
+                let finished = self.executing.take();
+                self.emit_statement(finished, Some(error.to_string()));
                 if error.is_fatal() {
                     return Err(error.into());
                 }
@@ -379,7 +517,11 @@ impl<T: ClientFormat> InternalConn<T> {
             }
             ServerPacket::EndOfStream => {
                 debug!({ ATT_CON } = cid, { ATT_QID } = %qid, "END OF STREAM");
-                drop(self.executing.take());
+                if let Some(timing) = exec.timing.as_ref() {
+                    timing.record_total(exec.started.elapsed());
+                }
+                let finished = self.executing.take();
+                self.emit_statement(finished, None);
                 T::finish_deser(&mut self.state);
             }
             ServerPacket::Hello(_) => {
@@ -417,16 +559,84 @@ impl<T: ClientFormat> InternalConn<T> {
         Ok(())
     }
 
+    /// Emit the executing query, if any, to the statement hook as failed with `error`, since it
+    /// will never finish once the connection is torn down.
+    fn abandon_executing(&mut self, error: &str) {
+        let abandoned = self.executing.take();
+        self.emit_statement(abandoned, Some(error.to_string()));
+    }
+
+    /// Emit a finished query to the statement hook, if configured.
+    fn emit_statement(&self, exec: Option<ExecutingQuery<T::Data>>, error: Option<String>) {
+        let (Some(hook), Some(exec)) = (self.hook.as_ref(), exec) else {
+            return;
+        };
+        hook.call(&StatementEvent {
+            qid: exec.qid,
+            client_id: self.metadata.client_id,
+            fingerprint: exec.fingerprint,
+            query: exec.query,
+            duration: exec.started.elapsed(),
+            read_rows: exec.read_rows,
+            written_rows: exec.written_rows,
+            profile_events: exec.profile_events,
+            error,
+        });
+    }
+
     // WRITE
 
-    #[instrument(skip_all, fields(clickhouse.query.id = %query.qid), err)]
+    #[instrument(
+        name = "clickhouse.query.send",
+        skip_all,
+        fields(
+            clickhouse.connection.id = self.cid,
+            clickhouse.query.id = %query.qid,
+            clickhouse.query.fingerprint,
+            db.query.text,
+        ),
+        err
+    )]
     async fn send_query<W: ClickHouseWrite>(
         &mut self,
         writer: &mut W,
         query: PendingQuery<T::Data>,
     ) -> Result<()> {
-        let PendingQuery { qid, query, settings, params, response, header } = query;
-        debug!({ ATT_CON } = self.cid, { ATT_QID } = %qid, query, "sending query");
+        let PendingQuery {
+            qid,
+            query,
+            settings,
+            params,
+            response,
+            header,
+            quota_key,
+            projection,
+            decimal_rescale,
+            timing,
+            progress,
+            read_ahead,
+            cancel_on_drop,
+        } = query;
+        let redact = self.metadata.redact_queries;
+        let fingerprint = record_query_span(&Span::current(), &query, redact);
+        let started = Instant::now();
+        if redact {
+            debug!({ ATT_CON } = self.cid, { ATT_QID } = %qid, "sending query");
+        } else {
+            debug!({ ATT_CON } = self.cid, { ATT_QID } = %qid, query, "sending query");
+        }
+
+        // Only one query executes at a time, so its blocks are decoded with its projection. State
+        // left by an abandoned query, ie a `LowCardinality` global dictionary, is dropped first
+        T::finish_deser(&mut self.state);
+        T::set_projection(&mut self.state, projection);
+        T::set_decimal_rescale(&mut self.state, decimal_rescale);
+        self.state.rows_read = 0;
+
+        let mut info = ClientInfo::from_options(&self.client_info);
+        if let Some(quota_key) = quota_key.as_deref() {
+            info.quota_key = quota_key;
+        }
 
         // Send initial query
         if let Err(error) = Writer::send_query(
@@ -437,7 +647,7 @@ impl<T: ClientFormat> InternalConn<T> {
                 settings,
                 params,
                 stage: QueryProcessingStage::Complete,
-                info: ClientInfo::default(),
+                info,
             },
             self.server_hello.settings.as_ref(),
             self.server_hello.revision_version,
@@ -446,22 +656,49 @@ impl<T: ClientFormat> InternalConn<T> {
         .await
         {
             error!(?error, { ATT_CON } = self.cid, { ATT_QID } = %qid, "Query failed to send");
+            if let Some(hook) = self.hook.as_ref() {
+                hook.call(&StatementEvent {
+                    qid,
+                    client_id: self.metadata.client_id,
+                    fingerprint,
+                    query: (!redact).then_some(query),
+                    duration: started.elapsed(),
+                    read_rows: 0,
+                    written_rows: 0,
+                    profile_events: ProfileEvents::default(),
+                    error: Some(error.to_string()),
+                });
+            }
             drop(response.send(Err(Error::Client(error.to_string()))));
             return Err(error);
         }
 
         trace!({ ATT_CON } = self.cid, { ATT_QID } = %qid, "query sent");
 
-        // Send back the data response channel
-        let (sender, receiver) = mpsc::channel(32);
+        // Send back the data response channel. Its capacity bounds how many blocks are read ahead
+        // of the consumer, once full reading pauses until the consumer catches up
+        let read_ahead = read_ahead.map_or(self.metadata.read_ahead, |blocks| blocks.max(1));
+        let (sender, receiver) = mpsc::channel(read_ahead);
         let _ = response.send(Ok(receiver)).ok();
 
         self.executing = Some(ExecutingQuery {
             qid,
+            fingerprint,
+            query: (self.hook.is_some() && !redact).then_some(query),
+            started,
+            first_packet: true,
+            read_rows: 0,
+            written_rows: 0,
+            profile_events: ProfileEvents::default(),
             state: QueryState::Header,
             header: None,
             header_response: header,
             response: sender,
+            timing,
+            progress,
+            cancel_on_drop,
+            cancelled: false,
+            blocks: 0,
         });
 
         self.send_delimiter(writer, qid).await?;
@@ -499,7 +736,10 @@ impl<T: ClientFormat> InternalConn<T> {
                 if !data.is_empty() {
                     let block_count = data.len();
                     trace!({ ATT_QID } = %qid, blocks = block_count, "Batch insert with deferred flush");
+                    // Column errors report the failing block's position within the insert
+                    let mut offset = 0;
                     for block in data {
+                        let rows = block.data_rows() as u64;
                         Writer::send_data_no_flush::<T>(
                             writer,
                             block,
@@ -508,7 +748,9 @@ impl<T: ClientFormat> InternalConn<T> {
                             revision,
                             self.metadata,
                         )
-                        .await?;
+                        .await
+                        .map_err(|e| e.with_row_offset(offset))?;
+                        offset += rows;
                     }
                 }
                 // Delimiter includes the final flush for all accumulated data
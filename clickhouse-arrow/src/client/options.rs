@@ -1,8 +1,10 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use tracing::warn;
 
 use super::CompressionMethod;
+use crate::constants::CLICKHOUSE_DEFAULT_CHUNK_ROWS;
 use crate::native::protocol::ChunkedProtocolMode;
 use crate::prelude::Secret;
 
@@ -161,22 +163,76 @@ impl ClientOptions {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Extension {
     /// Options specific to (de)serializing arrow data.
-    pub arrow:          Option<ArrowOptions>,
+    pub arrow:                  Option<ArrowOptions>,
     /// Options specific to communicating with `ClickHouse` over their cloud offering.
     #[cfg(feature = "cloud")]
-    pub cloud:          CloudOptions,
+    pub cloud:                  CloudOptions,
     /// Options related to server/client protocol send chunking.
     /// This may be removed, as it may be defaulted.
     #[cfg_attr(feature = "serde", serde(default))]
-    pub chunked_send:   ChunkedProtocolMode,
+    pub chunked_send:           ChunkedProtocolMode,
     /// Options related to server/client protocol recv chunking.
     /// This may be removed, as it may be defaulted
     #[cfg_attr(feature = "serde", serde(default))]
-    pub chunked_recv:   ChunkedProtocolMode,
+    pub chunked_recv:           ChunkedProtocolMode,
     /// Related to `inner_pool`, how many 'inner clients' to spawn. Currently capped at 4.
     #[cfg(feature = "inner_pool")]
     #[cfg_attr(feature = "serde", serde(default))]
-    pub fast_mode_size: Option<u8>,
+    pub fast_mode_size:         Option<u8>,
+    /// Client identification sent to the server, see [`ClientInfoOptions`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub client_info:            ClientInfoOptions,
+    /// Maximum number of rows sent per insert block. Larger batches are split client-side using
+    /// zero-copy slices. Falls back to the `max_insert_block_size` session setting if unset, then
+    /// to the server's value.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_insert_block_size:  Option<usize>,
+    /// Merge small batches passed to a single insert into larger blocks, see
+    /// [`InsertCoalescing`]. Batches are sent as-is if unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub insert_coalescing:      Option<InsertCoalescing>,
+    /// Whether raw SQL is omitted from logs and spans. Query fingerprints are always recorded,
+    /// see [`crate::telemetry::normalize_query`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub redact_queries:         bool,
+    /// Maximum number of queries and inserts in flight at once across the client and its clones.
+    /// Further queries wait for a slot. Unlimited if unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_concurrent_queries: Option<usize>,
+    /// Bounds how long queries wait for a slot when concurrent queries are capped, see
+    /// [`QueryQueue`]. Queries wait indefinitely if unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub query_queue:            Option<QueryQueue>,
+    /// Rate limit applied to inserts across the client and its clones, see [`InsertRateLimit`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub insert_rate_limit:      Option<InsertRateLimit>,
+    /// Maximum number of decoded blocks buffered per query ahead of its consumer. Reading from
+    /// the connection pauses once the buffer is full. Defaults to 32 if unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub read_ahead_blocks:      Option<usize>,
+    /// Authenticate with an SSH private key instead of a password, see [`SshKeyOptions`].
+    #[cfg(feature = "ssh")]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ssh_key:                Option<SshKeyOptions>,
+    /// Hosts, wildcards, IP addresses, and CIDR networks the client may connect to. Destinations
+    /// outside the list are refused. Every destination is allowed if empty.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub allowed_hosts:          Vec<String>,
+    /// Hex-encoded SHA-256 hashes of the server certificate's `SubjectPublicKeyInfo`. TLS
+    /// connections to servers whose key matches none of them are refused. Requires `rustls-tls`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pinned_spki:            Vec<String>,
+    /// Re-establish dropped connections before the next query, see [`ReconnectPolicy`].
+    /// Connections are not re-established if unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reconnect:              Option<ReconnectPolicy>,
+    /// Refuse statements that write data or change the schema before sending them, see
+    /// [`crate::classify_statement`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub readonly:               bool,
+    /// Size limits of compressed frames sent and received, see [`FrameLimits`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub frame_limits:           FrameLimits,
 }
 
 /// Configuration extensions for specialized `ClickHouse` client behavior.
@@ -221,6 +277,398 @@ impl Extension {
         self.fast_mode_size = Some(size);
         self
     }
+
+    #[must_use]
+    pub fn with_client_info(mut self, client_info: ClientInfoOptions) -> Self {
+        self.client_info = client_info;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_insert_block_size(mut self, rows: usize) -> Self {
+        self.max_insert_block_size = Some(rows);
+        self
+    }
+
+    #[must_use]
+    pub fn with_insert_coalescing(mut self, coalescing: InsertCoalescing) -> Self {
+        self.insert_coalescing = Some(coalescing);
+        self
+    }
+
+    #[must_use]
+    pub fn with_redact_queries(mut self, redact: bool) -> Self {
+        self.redact_queries = redact;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_concurrent_queries(mut self, max: usize) -> Self {
+        self.max_concurrent_queries = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub fn with_query_queue(mut self, queue: QueryQueue) -> Self {
+        self.query_queue = Some(queue);
+        self
+    }
+
+    #[must_use]
+    pub fn with_insert_rate_limit(mut self, limit: InsertRateLimit) -> Self {
+        self.insert_rate_limit = Some(limit);
+        self
+    }
+
+    #[must_use]
+    pub fn with_read_ahead_blocks(mut self, blocks: usize) -> Self {
+        self.read_ahead_blocks = Some(blocks);
+        self
+    }
+
+    #[cfg(feature = "ssh")]
+    #[must_use]
+    pub fn with_ssh_key(mut self, options: SshKeyOptions) -> Self {
+        self.ssh_key = Some(options);
+        self
+    }
+
+    #[must_use]
+    pub fn with_allowed_hosts(
+        mut self,
+        hosts: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.allowed_hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    #[must_use]
+    pub fn with_pinned_spki(mut self, pins: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.pinned_spki = pins.into_iter().map(Into::into).collect();
+        self
+    }
+
+    #[must_use]
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    #[must_use]
+    pub fn with_readonly(mut self, readonly: bool) -> Self {
+        self.readonly = readonly;
+        self
+    }
+
+    #[must_use]
+    pub fn with_frame_limits(mut self, limits: FrameLimits) -> Self {
+        self.frame_limits = limits;
+        self
+    }
+}
+
+/// Admission control for queries waiting on
+/// [`max_concurrent_queries`](Extension::max_concurrent_queries).
+///
+/// Queries that find every slot taken join a queue shared by the client and its clones. A query
+/// still queued after `max_wait`, or arriving while `max_depth` queries are already queued, is
+/// shed with [`Error::Overloaded`](crate::Error::Overloaded) instead of waiting. This keeps
+/// latency-sensitive services failing fast under load rather than piling up requests behind a
+/// saturated server.
+///
+/// # Examples
+/// ```rust,ignore
+/// use std::time::Duration;
+///
+/// use clickhouse_arrow::prelude::*;
+///
+/// let builder = ClientBuilder::new()
+///     .with_endpoint("localhost:9000")
+///     .with_max_concurrent_queries(8)
+///     .with_query_queue(QueryQueue::new(Duration::from_millis(250)).with_max_depth(64));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueryQueue {
+    /// Longest time a query waits for a slot before it is shed.
+    pub max_wait:  Duration,
+    /// Maximum number of queries waiting at once. Unbounded if unset.
+    pub max_depth: Option<usize>,
+}
+
+impl QueryQueue {
+    /// Shed queries waiting longer than `max_wait` for a slot.
+    pub fn new(max_wait: Duration) -> Self { Self { max_wait, max_depth: None } }
+
+    #[must_use]
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+}
+
+/// Merging of small batches into larger insert blocks.
+///
+/// Every block of an insert can become a part on the server, so inserting many tiny batches, ie
+/// a few rows each from a message queue, quickly leads to "too many parts" errors. With
+/// coalescing, consecutive batches of an insert are concatenated client-side until a block
+/// reaches `max_rows` rows or, if set, `max_bytes` bytes of in-memory data, before being
+/// serialized. Batches already at the threshold are sent unchanged, and batches with a different
+/// schema than the block being built start a new one.
+///
+/// Coalesced blocks are still split at the max insert block size, if configured.
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let builder = ClientBuilder::new()
+///     .with_endpoint("localhost:9000")
+///     .with_insert_coalescing(InsertCoalescing::new(65_536).with_max_bytes(16 << 20));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InsertCoalescing {
+    /// Rows at which a block is complete.
+    pub max_rows:  usize,
+    /// In-memory bytes at which a block is complete. Only rows are counted if unset.
+    pub max_bytes: Option<usize>,
+}
+
+impl InsertCoalescing {
+    /// Merge batches into blocks of up to `max_rows` rows.
+    pub fn new(max_rows: usize) -> Self { Self { max_rows, max_bytes: None } }
+
+    #[must_use]
+    pub fn with_max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+}
+
+/// A client-side limit on how fast data is inserted.
+///
+/// Inserts are paced with a token bucket holding one second worth of the rate, so short bursts
+/// up to the rate are sent immediately and sustained load is smoothed to the configured average.
+/// A single insert larger than the rate is still sent, delaying the inserts that follow it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InsertRateLimit {
+    /// Maximum rows inserted per second.
+    RowsPerSecond(u64),
+    /// Maximum bytes inserted per second, measured on the in-memory batches.
+    BytesPerSecond(u64),
+}
+
+/// How a client re-establishes a connection that dropped, ie after a server restart or network
+/// failure.
+///
+/// The next query or insert on a dropped connection reconnects first: the TCP (and TLS)
+/// connection is re-established and the handshake repeated, with credentials fetched again from
+/// the client's credentials provider, if any. Failed attempts are retried after a delay growing
+/// exponentially from `initial_backoff` up to `max_backoff`. Once `max_attempts` attempts failed,
+/// the operation fails with the last connection error and the next operation starts over.
+///
+/// Session settings are sent with every query, so they apply to the new connection unchanged.
+/// Queries in flight when the connection dropped fail and are not retried.
+///
+/// # Examples
+/// ```rust,ignore
+/// use std::time::Duration;
+///
+/// use clickhouse_arrow::prelude::*;
+///
+/// let policy = ReconnectPolicy::new(10)
+///     .with_backoff(Duration::from_millis(50), Duration::from_secs(5));
+/// let builder = ClientBuilder::new().with_endpoint("localhost:9000").with_reconnect(policy);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReconnectPolicy {
+    /// Connection attempts made before giving up. At least one attempt is always made.
+    pub max_attempts:    u32,
+    /// Delay after the first failed attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between attempts.
+    pub max_backoff:     Duration,
+    /// Factor the delay grows by after each further failed attempt, at least 1.
+    pub multiplier:      f64,
+}
+
+impl ReconnectPolicy {
+    /// Make up to `max_attempts` attempts, with the default backoff.
+    pub fn new(max_attempts: u32) -> Self { Self { max_attempts, ..Self::default() } }
+
+    #[must_use]
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    #[must_use]
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// The delay after `attempt` failed attempts, starting at 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let secs = self.initial_backoff.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        Duration::try_from_secs_f64(secs)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+}
+
+impl Default for ReconnectPolicy {
+    /// 5 attempts, waiting 100ms after the first failure and doubling up to 10s.
+    fn default() -> Self {
+        Self {
+            max_attempts:    5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff:     Duration::from_secs(10),
+            multiplier:      2.0,
+        }
+    }
+}
+
+/// Size limits of the compressed frames exchanged with `ClickHouse`.
+///
+/// Incoming frames larger than `max_compressed_chunk_size`, or decompressing to more than
+/// `max_decompressed_chunk_size` bytes, are refused with
+/// [`Error::ChunkTooLarge`](crate::Error::ChunkTooLarge) before anything is allocated for them.
+/// Outgoing blocks are split across frames of at most `max_frame_payload` uncompressed bytes,
+/// which the server reassembles. Only applies when compression is enabled.
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let limits = FrameLimits::default().with_max_decompressed_chunk_size(256 << 20);
+/// let builder = ClientBuilder::new().with_endpoint("localhost:9000").with_frame_limits(limits);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FrameLimits {
+    /// Largest frame accepted from the server, including its 9-byte header.
+    pub max_compressed_chunk_size:   u32,
+    /// Largest decompressed chunk accepted from the server.
+    pub max_decompressed_chunk_size: u32,
+    /// Largest payload written to a single outgoing frame, at least 1.
+    pub max_frame_payload:           usize,
+}
+
+impl FrameLimits {
+    #[must_use]
+    pub fn with_max_compressed_chunk_size(mut self, size: u32) -> Self {
+        self.max_compressed_chunk_size = size;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_decompressed_chunk_size(mut self, size: u32) -> Self {
+        self.max_decompressed_chunk_size = size;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_frame_payload(mut self, size: usize) -> Self {
+        self.max_frame_payload = size;
+        self
+    }
+}
+
+impl Default for FrameLimits {
+    /// Accepts frames up to 100MB decompressing to up to 1GB, and splits outgoing blocks into
+    /// frames of 64MiB.
+    fn default() -> Self {
+        Self {
+            max_compressed_chunk_size:   100_000_000,
+            max_decompressed_chunk_size: 1_000_000_000,
+            max_frame_payload:           64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Client identification sent to `ClickHouse` in the handshake and with every query.
+///
+/// These values surface server-side in `system.query_log`, `system.processes`, and quota
+/// accounting, which makes it possible to attribute queries to the application that issued them.
+/// Any field left as `None` uses the library default.
+///
+/// # Fields
+/// - `client_name`: Client name reported in the hello packet and `ClientInfo`.
+/// - `client_version`: `(major, minor, patch)` version reported alongside the client name.
+/// - `quota_key`: Quota key used for keyed quotas (`KEYED BY client_key`).
+/// - `os_user`: OS user reported in `ClientInfo`.
+/// - `client_hostname`: Hostname reported in `ClientInfo`.
+/// - `distributed_depth`: Distributed query depth, only useful when proxying distributed queries.
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let info = ClientInfoOptions::default()
+///     .with_client_name("ingest-service")
+///     .with_quota_key("tenant-42");
+/// let options = ClientOptions::default().extend(|ext| ext.with_client_info(info.clone()));
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientInfoOptions {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub client_name:       Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub client_version:    Option<(u64, u64, u64)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub quota_key:         Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub os_user:           Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub client_hostname:   Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub distributed_depth: Option<u64>,
+}
+
+impl ClientInfoOptions {
+    #[must_use]
+    pub fn with_client_name(mut self, name: impl Into<String>) -> Self {
+        self.client_name = Some(name.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_client_version(mut self, major: u64, minor: u64, patch: u64) -> Self {
+        self.client_version = Some((major, minor, patch));
+        self
+    }
+
+    #[must_use]
+    pub fn with_quota_key(mut self, quota_key: impl Into<String>) -> Self {
+        self.quota_key = Some(quota_key.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_os_user(mut self, os_user: impl Into<String>) -> Self {
+        self.os_user = Some(os_user.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_client_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.client_hostname = Some(hostname.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_distributed_depth(mut self, depth: u64) -> Self {
+        self.distributed_depth = Some(depth);
+        self
+    }
 }
 
 // TODO: Remove - make the properties public!
@@ -247,6 +695,27 @@ impl Extension {
 /// - `nullable_array_default_empty`: If `true`, maps `Nullable(Array(...))` to `Array(...)` with
 ///   `[]` for nulls during inserts and schema creation (if `disable_strict_schema_ddl = true`); if
 ///   `false`, errors on `Nullable(Array(...))` (default).
+/// - `sparse_as_run_end_encoded`: If `true`, columns whose type supports sparse serialization are
+///   returned as Arrow `RunEndEncoded` arrays, without expanding sparse blocks; if `false`, sparse
+///   columns are expanded to dense arrays (default).
+/// - `null_policy`: How nulls in an Arrow array bound for a non-nullable `ClickHouse` column are
+///   handled during inserts. See [`NullPolicy`]. Defaults to [`NullPolicy::Error`].
+/// - `localize_naive_timestamps`: If `true`, Arrow timestamps without a timezone are read as wall
+///   clock times in the target column's timezone during inserts; if `false`, they are read as UTC
+///   (default).
+/// - `unknown_type_policy`: How query result columns with types this crate cannot read are handled.
+///   See [`UnknownTypePolicy`]. Defaults to [`UnknownTypePolicy::Error`].
+/// - `batch_metadata`: If `true`, query results carry their query id, block index, and server in
+///   each `RecordBatch`'s schema metadata; if `false`, they don't (default).
+/// - `large_offsets`: If `true`, `String`, `Binary` and `Array` columns are read as Arrow
+///   `LargeUtf8`/`LargeBinary`/`LargeList` with 64-bit offsets, for columns holding more than 2GB
+///   per batch; if `false`, 32-bit offset types are used (default).
+/// - `block_info_metadata`: If `true`, query results carry the block info the server sent with
+///   each block, ie whether it holds GROUP BY overflow rows, in each `RecordBatch`'s schema
+///   metadata; if `false`, they don't (default).
+/// - `retained_builder_rows`: The number of rows of capacity the Arrow builders of a query keep
+///   between blocks, so long scans decode each block without regrowing them. `0` releases all
+///   capacity after every block. Defaults to one default sized block (65,409 rows).
 ///
 /// # Notes
 /// - During schema creation, options are converted to strict mode (via
@@ -287,8 +756,26 @@ pub struct ArrowOptions {
     pub strict_schema:                bool,
     pub disable_strict_schema_ddl:    bool,
     pub nullable_array_default_empty: bool,
+    pub sparse_as_run_end_encoded:    bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub null_policy:                  NullPolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub localize_naive_timestamps:    bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unknown_type_policy:          UnknownTypePolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub batch_metadata:               bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub large_offsets:                bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub block_info_metadata:          bool,
+    #[cfg_attr(feature = "serde", serde(default = "default_retained_builder_rows"))]
+    pub retained_builder_rows:        usize,
 }
 
+#[cfg(feature = "serde")]
+const fn default_retained_builder_rows() -> usize { CLICKHOUSE_DEFAULT_CHUNK_ROWS }
+
 impl Default for ArrowOptions {
     /// Creates an `ArrowOptions` instance with default values.
     ///
@@ -341,6 +828,14 @@ impl ArrowOptions {
             strict_schema:                false,
             disable_strict_schema_ddl:    false,
             nullable_array_default_empty: true,
+            sparse_as_run_end_encoded:    false,
+            null_policy:                  NullPolicy::Error,
+            localize_naive_timestamps:    false,
+            unknown_type_policy:          UnknownTypePolicy::Error,
+            batch_metadata:               false,
+            large_offsets:                false,
+            block_info_metadata:          false,
+            retained_builder_rows:        CLICKHOUSE_DEFAULT_CHUNK_ROWS,
         }
     }
 
@@ -370,6 +865,14 @@ impl ArrowOptions {
             strict_schema:                true,
             disable_strict_schema_ddl:    false,
             nullable_array_default_empty: false,
+            sparse_as_run_end_encoded:    false,
+            null_policy:                  NullPolicy::Error,
+            localize_naive_timestamps:    false,
+            unknown_type_policy:          UnknownTypePolicy::Error,
+            batch_metadata:               false,
+            large_offsets:                false,
+            block_info_metadata:          false,
+            retained_builder_rows:        CLICKHOUSE_DEFAULT_CHUNK_ROWS,
         }
     }
 
@@ -409,6 +912,14 @@ impl ArrowOptions {
         Self {
             strings_as_strings: self.strings_as_strings,
             use_date32_for_date: self.use_date32_for_date,
+            sparse_as_run_end_encoded: self.sparse_as_run_end_encoded,
+            null_policy: self.null_policy,
+            localize_naive_timestamps: self.localize_naive_timestamps,
+            unknown_type_policy: self.unknown_type_policy,
+            batch_metadata: self.batch_metadata,
+            large_offsets: self.large_offsets,
+            block_info_metadata: self.block_info_metadata,
+            retained_builder_rows: self.retained_builder_rows,
             ..Self::strict()
         }
     }
@@ -560,6 +1071,238 @@ impl ArrowOptions {
         self
     }
 
+    /// Sets whether sparse columns are returned as Arrow `RunEndEncoded` arrays.
+    ///
+    /// `ClickHouse` may send columns with mostly default values using sparse serialization, where
+    /// only the non-default values and their positions are transmitted. By default these are
+    /// expanded into dense arrays, which for ultra-sparse columns can cost far more memory than
+    /// the data received. When this option is enabled (`true`), each sparse column is surfaced as
+    /// a `RunEndEncoded` array (`Int32` run ends) whose runs of defaults are stored once.
+    ///
+    /// Sparse serialization is decided per block by the server, so the option applies to every
+    /// column whose type supports it, ie numbers, dates, decimals, strings and fixed strings.
+    /// Blocks sent dense are run end encoded as well, so a column keeps the same data type across
+    /// the `RecordBatch`es of a query. Use `arrow::compute::cast` to densify if needed.
+    ///
+    /// # Parameters
+    /// - `enabled`: If `true`, sparse columns are returned as `RunEndEncoded`; if `false`, they are
+    ///   expanded to dense arrays.
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::arrow::ArrowOptions;
+    ///
+    /// let arrow_options = ArrowOptions::new()
+    ///     .with_sparse_as_run_end_encoded(true);
+    /// assert!(arrow_options.sparse_as_run_end_encoded);
+    /// ```
+    #[must_use]
+    pub fn with_sparse_as_run_end_encoded(mut self, enabled: bool) -> Self {
+        self.sparse_as_run_end_encoded = enabled;
+        self
+    }
+
+    /// Sets how nulls are handled when inserting into non-nullable `ClickHouse` columns.
+    ///
+    /// An Arrow array may contain nulls while the target column, as reported by the server's
+    /// insert header, is not `Nullable`. Sending such a column as is either fails server-side or
+    /// writes whatever value sits behind the null slot. The policy is applied to each column before
+    /// serialization. `Array` columns are governed by
+    /// [`ArrowOptions::with_nullable_array_default_empty`] instead.
+    ///
+    /// # Parameters
+    /// - `policy`: The [`NullPolicy`] to apply.
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::{ArrowOptions, NullPolicy};
+    ///
+    /// let arrow_options = ArrowOptions::new()
+    ///     .with_null_policy(NullPolicy::Default);
+    /// assert_eq!(arrow_options.null_policy, NullPolicy::Default);
+    /// ```
+    #[must_use]
+    pub fn with_null_policy(mut self, policy: NullPolicy) -> Self {
+        self.null_policy = policy;
+        self
+    }
+
+    /// Sets whether Arrow timestamps without a timezone are localized on insert.
+    ///
+    /// Arrow timestamps carrying a timezone are instants and are sent unchanged, whatever the
+    /// timezone of the target `DateTime`/`DateTime64` column. Timestamps without a timezone are
+    /// ambiguous: by default they are treated as UTC. When enabled, they are instead treated as
+    /// wall clock times in the target column's timezone, as `ClickHouse` does when parsing
+    /// `'2024-03-10 02:30:00'`, and converted to instants using the bundled IANA database.
+    ///
+    /// # Parameters
+    /// - `enabled`: If `true`, naive timestamps are localized to the column's timezone.
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::arrow::ArrowOptions;
+    ///
+    /// let arrow_options = ArrowOptions::new().with_localize_naive_timestamps(true);
+    /// assert!(arrow_options.localize_naive_timestamps);
+    /// ```
+    #[must_use]
+    pub fn with_localize_naive_timestamps(mut self, enabled: bool) -> Self {
+        self.localize_naive_timestamps = enabled;
+        self
+    }
+
+    /// Sets how query result columns with unknown or unsupported types are handled.
+    ///
+    /// By default, a query fails when any of its result columns has a type this crate cannot
+    /// read, ie a type added in a newer `ClickHouse` release. Skipping the column or reading it
+    /// as raw text instead lets exploratory queries over exotic tables still return the other
+    /// columns. Policies other than [`UnknownTypePolicy::Error`] cost an extra `DESCRIBE` round
+    /// trip per Arrow query, so they are best kept for interactive use.
+    ///
+    /// # Parameters
+    /// - `policy`: The [`UnknownTypePolicy`] to apply.
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::{ArrowOptions, UnknownTypePolicy};
+    ///
+    /// let arrow_options = ArrowOptions::new().with_unknown_type_policy(UnknownTypePolicy::Raw);
+    /// assert_eq!(arrow_options.unknown_type_policy, UnknownTypePolicy::Raw);
+    /// ```
+    #[must_use]
+    pub fn with_unknown_type_policy(mut self, policy: UnknownTypePolicy) -> Self {
+        self.unknown_type_policy = policy;
+        self
+    }
+
+    /// Sets whether query results carry batch metadata.
+    ///
+    /// When enabled, the schema metadata of every `RecordBatch` returned by a query holds the
+    /// query id, the index of the batch within the query's result, starting at 0, and the
+    /// server's display name, under [`BATCH_QUERY_ID_KEY`](crate::arrow::BATCH_QUERY_ID_KEY),
+    /// [`BATCH_INDEX_KEY`](crate::arrow::BATCH_INDEX_KEY) and
+    /// [`BATCH_SERVER_KEY`](crate::arrow::BATCH_SERVER_KEY). Downstream exactly-once sinks can use
+    /// them to deduplicate redelivered batches and to trace where each batch came from.
+    ///
+    /// # Parameters
+    /// - `enabled`: If `true`, batch metadata is attached to query results.
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::arrow::{BATCH_INDEX_KEY, BATCH_QUERY_ID_KEY};
+    ///
+    /// let arrow_options = ArrowOptions::new().with_batch_metadata(true);
+    /// // For each batch of a query
+    /// let metadata = batch.schema_ref().metadata();
+    /// let key = (&metadata[BATCH_QUERY_ID_KEY], &metadata[BATCH_INDEX_KEY]);
+    /// ```
+    #[must_use]
+    pub fn with_batch_metadata(mut self, enabled: bool) -> Self {
+        self.batch_metadata = enabled;
+        self
+    }
+
+    /// Sets whether variable length columns are read with 64-bit offsets.
+    ///
+    /// Arrow `Utf8`, `Binary` and `List` arrays index their values with 32-bit offsets, so a single
+    /// column of a batch cannot hold more than 2GB of string bytes or `i32::MAX` list items. When
+    /// enabled, `ClickHouse` `String`, `Binary` and `Array` columns are read as `LargeUtf8`,
+    /// `LargeBinary` and `LargeList` instead. Inserts accept both variants regardless of this
+    /// setting. `LowCardinality` dictionaries keep 32-bit offsets.
+    ///
+    /// # Parameters
+    /// - `enabled`: If `true`, query results use 64-bit offset types.
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::arrow::ArrowOptions;
+    ///
+    /// let arrow_options = ArrowOptions::new().with_strings_as_strings(true).with_large_offsets(true);
+    /// assert!(arrow_options.large_offsets);
+    /// ```
+    #[must_use]
+    pub fn with_large_offsets(mut self, enabled: bool) -> Self {
+        self.large_offsets = enabled;
+        self
+    }
+
+    /// Sets whether query results carry the server's block info.
+    ///
+    /// When enabled, the schema metadata of every `RecordBatch` returned by a query holds the
+    /// block info the server sent with its block, ie whether the block holds the overflow rows of
+    /// a `GROUP BY` with `max_rows_to_group_by`, which can be recovered with
+    /// [`BlockInfo::from_metadata`](crate::native::block_info::BlockInfo::from_metadata). Block
+    /// info is attached to every block, so schemas are the same throughout a query's result.
+    ///
+    /// # Parameters
+    /// - `enabled`: If `true`, block info is attached to query results.
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::native::block_info::BlockInfo;
+    ///
+    /// let arrow_options = ArrowOptions::new().with_block_info_metadata(true);
+    /// // For each batch of a query
+    /// let overflows = BlockInfo::from_metadata(batch.schema_ref().metadata())
+    ///     .is_some_and(|info| info.is_overflows);
+    /// ```
+    #[must_use]
+    pub fn with_block_info_metadata(mut self, enabled: bool) -> Self {
+        self.block_info_metadata = enabled;
+        self
+    }
+
+    /// Sets the capacity, in rows, that query result builders retain between blocks.
+    ///
+    /// Each column of a query is decoded by one Arrow builder, reused for every block of the
+    /// query. Finishing a block hands the builder's buffers to the returned arrays, so by default
+    /// each builder reserves room for the rows of the block it just decoded, up to this limit,
+    /// and a long scan decodes every block into presized buffers instead of regrowing them. The
+    /// scratch buffer shared by all columns is trimmed to the same limit after each block.
+    ///
+    /// Lower the limit to bound the memory held by idle queries with many wide columns, or set
+    /// it to `0` to release all capacity after every block.
+    ///
+    /// # Parameters
+    /// - `rows`: The maximum number of rows of capacity retained per builder.
+    ///
+    /// # Returns
+    /// A new [`ArrowOptions`] with the updated setting.
+    ///
+    /// # Examples
+    /// ```rust,ignore
+    /// use clickhouse_arrow::arrow::ArrowOptions;
+    ///
+    /// let arrow_options = ArrowOptions::new().with_retained_builder_rows(8_192);
+    /// assert_eq!(arrow_options.retained_builder_rows, 8_192);
+    /// ```
+    #[must_use]
+    pub fn with_retained_builder_rows(mut self, rows: usize) -> Self {
+        self.retained_builder_rows = rows;
+        self
+    }
+
     /// Sets an Arrow option by name and value.
     ///
     /// This method updates a specific option identified by `name` to the given boolean
@@ -570,6 +1313,11 @@ impl ArrowOptions {
     /// - `"disable_strict_schema_ddl"`: Disables strict mode for schema creation.
     /// - `"nullable_array_default_empty"`: Maps `Nullable(Array(...))` to `Array(...)` with `[]`
     ///   for nulls.
+    /// - `"sparse_as_run_end_encoded"`: Returns columns that may be sparse as `RunEndEncoded`.
+    /// - `"localize_naive_timestamps"`: Reads timezone-less timestamps in the column's timezone.
+    /// - `"batch_metadata"`: Attaches the query id, block index, and server to query results.
+    /// - `"large_offsets"`: Reads `String`, `Binary` and `Array` columns with 64-bit offsets.
+    /// - `"block_info_metadata"`: Attaches the server's block info to query results.
     ///
     /// If an unrecognized name is provided, a warning is logged, and the options are
     /// returned unchanged. Use this for dynamic configuration or when options are
@@ -600,6 +1348,11 @@ impl ArrowOptions {
             "strict_schema" => self.with_strict_schema(value),
             "disable_strict_schema_ddl" => self.with_disable_strict_schema_ddl(value),
             "nullable_array_default_empty" => self.with_nullable_array_default_empty(value),
+            "sparse_as_run_end_encoded" => self.with_sparse_as_run_end_encoded(value),
+            "localize_naive_timestamps" => self.with_localize_naive_timestamps(value),
+            "batch_metadata" => self.with_batch_metadata(value),
+            "large_offsets" => self.with_large_offsets(value),
+            "block_info_metadata" => self.with_block_info_metadata(value),
             k => {
                 warn!("Unrecognized option for ArrowOptions: {k}");
                 self
@@ -682,6 +1435,48 @@ impl<'a> FromIterator<(&'a str, bool)> for ArrowOptions {
     }
 }
 
+/// Handling of nulls in Arrow arrays inserted into non-nullable `ClickHouse` columns.
+///
+/// Used by [`ArrowOptions::null_policy`]. The policy only applies when the column type comes from
+/// the server's insert header and does not accept nulls, i.e. it is neither `Nullable(...)` nor
+/// `LowCardinality(Nullable(...))`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NullPolicy {
+    /// Fail the insert with an error naming the column and the offending row indices (default).
+    #[default]
+    Error,
+    /// Replace nulls with the type's default value (`0`, empty string, empty array, etc.).
+    Default,
+    /// Send the column as `Nullable`, leaving it to the server to convert nulls to the column's
+    /// `DEFAULT` expression (requires `insert_null_as_default`, enabled by default in
+    /// `ClickHouse`).
+    Nullable,
+}
+
+/// Handling of query result columns whose `ClickHouse` type cannot be read.
+///
+/// Used by [`ArrowOptions::unknown_type_policy`]. Since the native format carries no per-value
+/// lengths, a column of an unknown type cannot be skipped once the server has sent it. Policies
+/// other than [`UnknownTypePolicy::Error`] therefore describe the query's result first and, if
+/// any column has an unknown type, rewrite the query so the server leaves the column out or
+/// sends it as text.
+///
+/// Columns of the deprecated `Object('json')` type are read as JSON strings, in Arrow `Utf8` with
+/// the type name in the field's metadata, under both `Skip` and `Raw`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnknownTypePolicy {
+    /// Fail the query with a type parse error (default).
+    #[default]
+    Error,
+    /// Leave the column out of the results, logging a warning.
+    Skip,
+    /// Return the column's text representation as Arrow `Binary`, with the original type name in
+    /// the field's [`UNKNOWN_TYPE_METADATA_KEY`](crate::UNKNOWN_TYPE_METADATA_KEY) metadata.
+    Raw,
+}
+
 /// Configuration options for connecting to `ClickHouse` cloud instances.
 ///
 /// The `CloudOptions` struct defines settings specific to `ClickHouse` cloud
@@ -717,3 +1512,47 @@ pub struct CloudOptions {
     #[cfg_attr(feature = "serde", serde(default))]
     pub wakeup:  bool,
 }
+
+/// SSH private key used to authenticate users identified `WITH ssh_key`.
+///
+/// The key is read from an OpenSSH private key file when connecting and signs a challenge sent by
+/// the server, so no password is transmitted. Ed25519, ECDSA (P-256, P-384), and RSA keys are
+/// supported. Encrypted keys require a passphrase.
+///
+/// # Feature
+/// Requires the `ssh` feature to be enabled.
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let client = Client::<ArrowFormat>::builder()
+///     .with_endpoint("localhost:9000")
+///     .with_username("alice")
+///     .with_ssh_key(SshKeyOptions::new("/home/alice/.ssh/id_ed25519"))
+///     .build()
+///     .await?;
+/// ```
+#[cfg(feature = "ssh")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SshKeyOptions {
+    /// Path to the OpenSSH private key file.
+    pub path:       PathBuf,
+    /// Passphrase decrypting the key, if it is encrypted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub passphrase: Option<Secret>,
+}
+
+#[cfg(feature = "ssh")]
+impl SshKeyOptions {
+    pub fn new<P: AsRef<std::path::Path>>(path: P) -> Self {
+        Self { path: path.as_ref().into(), passphrase: None }
+    }
+
+    #[must_use]
+    pub fn with_passphrase(mut self, passphrase: impl Into<Secret>) -> Self {
+        self.passphrase = Some(passphrase.into());
+        self
+    }
+}
@@ -0,0 +1,76 @@
+//! Endpoint latency and clock skew probing, see `Client::probe`.
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Query returning the server's current time, in microseconds since the epoch, and its timezone.
+pub(crate) const PROBE_QUERY: &str =
+    "SELECT toUnixTimestamp64Micro(now64(6)) AS now, timezone() AS timezone";
+
+/// The result of probing a `ClickHouse` endpoint with `Client::probe`.
+///
+/// Useful for diagnosing shifted `DateTime` values, which are usually caused by a server
+/// timezone or clock that differs from the client's, and for choosing replicas by latency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeResult {
+    /// Round trip time of a ping to the server.
+    pub rtt:               Duration,
+    /// The `(major, minor, patch)` version the server reported during the handshake.
+    pub server_version:    (u64, u64, u64),
+    /// The server's default timezone, e.g. `UTC` or `Europe/Amsterdam`.
+    pub server_timezone:   String,
+    /// Estimated offset of the server clock from the local clock, in microseconds. Positive when
+    /// the server is ahead. Accurate to roughly half the round trip time of the probe query.
+    pub clock_skew_micros: i64,
+}
+
+impl ProbeResult {
+    /// Whether the estimated clock skew, in either direction, is larger than `threshold`.
+    pub fn clock_skew_exceeds(&self, threshold: Duration) -> bool {
+        u128::from(self.clock_skew_micros.unsigned_abs()) > threshold.as_micros()
+    }
+}
+
+/// Microseconds since the epoch for `time`, saturating for times before the epoch.
+pub(crate) fn unix_micros(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_micros()).unwrap_or(i64::MAX))
+}
+
+/// Estimate the server clock's offset, assuming the server read its clock halfway between the
+/// local `sent` and `received` times.
+pub(crate) fn estimate_clock_skew(sent: i64, received: i64, server: i64) -> i64 {
+    let midpoint = sent + (received - sent) / 2;
+    server - midpoint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_clock_skew() {
+        // Server read its clock exactly at the midpoint
+        assert_eq!(estimate_clock_skew(1_000, 3_000, 2_000), 0);
+        // Server is ahead
+        assert_eq!(estimate_clock_skew(1_000, 3_000, 5_000_000), 4_998_000);
+        // Server is behind
+        assert_eq!(estimate_clock_skew(10_000, 12_000, 1_000), -10_000);
+    }
+
+    #[test]
+    fn test_clock_skew_exceeds() {
+        let result = ProbeResult {
+            rtt:               Duration::from_millis(1),
+            server_version:    (25, 8, 0),
+            server_timezone:   "UTC".into(),
+            clock_skew_micros: -2_500_000,
+        };
+        assert!(result.clock_skew_exceeds(Duration::from_secs(2)));
+        assert!(!result.clock_skew_exceeds(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_unix_micros() {
+        assert_eq!(unix_micros(UNIX_EPOCH + Duration::from_micros(42)), 42);
+        assert_eq!(unix_micros(UNIX_EPOCH - Duration::from_secs(1)), 0);
+    }
+}
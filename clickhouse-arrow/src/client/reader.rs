@@ -3,8 +3,8 @@ use std::str::FromStr;
 use tokio::io::AsyncReadExt;
 
 use super::connection::ClientMetadata;
-use crate::formats::DeserializerState;
 use crate::formats::sealed::ClientFormatImpl;
+use crate::formats::{DataSize, DeserializerState};
 use crate::io::ClickHouseRead;
 use crate::native::block::Block;
 use crate::native::progress::Progress;
@@ -54,6 +54,18 @@ impl<R: ClickHouseRead + 'static> Reader<R> {
         }
     }
 
+    /// Receive the challenge to sign for SSH key authentication
+    #[cfg(feature = "ssh")]
+    pub(super) async fn receive_ssh_challenge(reader: &mut R) -> Result<Vec<u8>> {
+        match ServerPacketId::from_u64(reader.read_var_uint().await?)? {
+            ServerPacketId::SSHChallenge => Ok(reader.read_string().await?),
+            ServerPacketId::Exception => Err(Self::read_exception(reader).await?.emit().into()),
+            packet => Err(Error::Protocol(format!(
+                "Unexpected packet {packet:?}, expected SSH challenge"
+            ))),
+        }
+    }
+
     /// Receive header packet (empty native block)
     pub(super) async fn receive_header<T: ClientFormat>(
         reader: &mut R,
@@ -221,12 +233,14 @@ impl<R: ClickHouseRead + 'static> Reader<R> {
                 let cl_chunked_send = chunked_modes.0;
                 let cl_chunked_recv = chunked_modes.1;
 
+                // What the client sends the server receives, and vice versa
                 (
-                    ChunkedProtocolMode::negotiate(srv_chunked_send, cl_chunked_send, "send")?,
-                    ChunkedProtocolMode::negotiate(srv_chunked_recv, cl_chunked_recv, "recv")?,
+                    ChunkedProtocolMode::negotiate(srv_chunked_recv, cl_chunked_send, "send")?,
+                    ChunkedProtocolMode::negotiate(srv_chunked_send, cl_chunked_recv, "recv")?,
                 )
             } else {
-                (ChunkedProtocolMode::default(), ChunkedProtocolMode::default())
+                // Servers predating chunked packets never frame
+                (ChunkedProtocolMode::NotChunked, ChunkedProtocolMode::NotChunked)
             };
 
         tracing::trace!(
@@ -481,6 +495,12 @@ impl<R: ClickHouseRead + 'static> Reader<R> {
     }
 
     /// Read a data packet from the server and deserialize into [`ClientFormat`]
+    #[instrument(
+        level = "trace",
+        name = "clickhouse.block.decode",
+        skip_all,
+        fields(db.format = T::FORMAT, clickhouse.client.id = metadata.client_id)
+    )]
     async fn read_data<T: ClientFormat>(
         reader: &mut R,
         revision: u64,
@@ -488,13 +508,16 @@ impl<R: ClickHouseRead + 'static> Reader<R> {
         state: &mut DeserializerState<T::Deser>,
     ) -> Result<Option<ServerData<T::Data>>> {
         drop(reader.read_string().await?);
-        let Some(block) =
-            T::read(reader, revision, metadata, state).await.inspect_err(|error| {
+        let Some(block) = T::read(reader, revision, metadata, state)
+            .await
+            .inspect_err(|error| {
                 error!(?error, { ATT_CID } = metadata.client_id, "Data read fail");
-            })?
+            })
+            .map_err(|error| error.with_row_offset(state.rows_read))?
         else {
             return Ok(None);
         };
+        state.rows_read += block.data_rows() as u64;
         Ok(Some(ServerData { block }))
     }
 }
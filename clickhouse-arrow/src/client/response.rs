@@ -1,15 +1,24 @@
 use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
 
-use futures_util::stream::StreamExt;
+use arrow::array::RecordBatch;
+use arrow::compute::concat_batches;
+use arrow::datatypes::{Schema, SchemaRef};
+use futures_util::stream::{self, StreamExt};
 use futures_util::{Stream, TryStreamExt};
 use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{error, trace};
 
 use super::ClientFormat;
+use crate::arrow::ArrowDeserializerState;
+use crate::arrow::rescale::DecimalRescale;
 use crate::explain::ExplainResult;
 use crate::prelude::{ATT_CID, ATT_QID};
-use crate::{Qid, Result};
+use crate::spawn::SpawnedTask;
+use crate::{ArrowOptions, Error, Progress, Qid, Result, Type};
 
 pub(crate) fn create_response_stream<T: ClientFormat>(
     rx: mpsc::Receiver<Result<T::Data>>,
@@ -37,6 +46,50 @@ pub(crate) fn handle_insert_response<T: ClientFormat>(
         })
 }
 
+/// The header block of a query's result, resolving the result's schema once received.
+///
+/// The connection sends the header's columns when it arrives, before any of the result's rows.
+pub(crate) struct ResultHeader {
+    receiver:        oneshot::Receiver<Vec<(String, Type)>>,
+    options:         ArrowOptions,
+    projection:      Option<Arc<[String]>>,
+    decimal_rescale: Option<Arc<[(String, DecimalRescale)]>>,
+    /// The schema, once resolved
+    schema:          Option<SchemaRef>,
+}
+
+impl ResultHeader {
+    /// Create the header of a query decoded with `options`, `projection` and `decimal_rescale`,
+    /// along with the sender the connection sends the header's columns to.
+    pub(crate) fn channel(
+        options: ArrowOptions,
+        projection: Option<Arc<[String]>>,
+        decimal_rescale: Option<Arc<[(String, DecimalRescale)]>>,
+    ) -> (oneshot::Sender<Vec<(String, Type)>>, Self) {
+        let (sender, receiver) = oneshot::channel();
+        (sender, Self { receiver, options, projection, decimal_rescale, schema: None })
+    }
+
+    /// The schema of the result, decoded as the query's blocks are. `None` if the header wasn't
+    /// received yet.
+    fn schema(&mut self) -> Option<Result<SchemaRef>> {
+        if let Some(schema) = self.schema.as_ref() {
+            return Some(Ok(Arc::clone(schema)));
+        }
+        let columns = self.receiver.try_recv().ok()?;
+        let mut state = ArrowDeserializerState {
+            projection: self.projection.clone(),
+            decimal_rescale: self.decimal_rescale.clone(),
+            ..Default::default()
+        };
+        let schema = state.header_schema(&columns, self.options);
+        if let Ok(schema) = schema.as_ref() {
+            self.schema = Some(Arc::clone(schema));
+        }
+        Some(schema)
+    }
+}
+
 /// Response from a `ClickHouse` query.
 ///
 /// This struct wraps a stream of query results and optionally includes
@@ -47,12 +100,16 @@ pub struct ClickHouseResponse<T> {
     stream:           Pin<Box<dyn Stream<Item = Result<T>> + Send + 'static>>,
     /// Receiver for the parallel EXPLAIN result, if configured.
     explain_receiver: Option<oneshot::Receiver<Result<ExplainResult>>>,
+    /// Whether parallel stages must preserve the server's block order.
+    ordered:          bool,
+    /// The header of the result, if tracked.
+    header:           Option<ResultHeader>,
 }
 
 impl<T> ClickHouseResponse<T> {
     /// Create a new response wrapping a stream.
     pub fn new(stream: Pin<Box<dyn Stream<Item = Result<T>> + Send + 'static>>) -> Self {
-        Self { stream, explain_receiver: None }
+        Self { stream, explain_receiver: None, ordered: true, header: None }
     }
 
     /// Create a new response with an explain receiver.
@@ -60,7 +117,7 @@ impl<T> ClickHouseResponse<T> {
         stream: Pin<Box<dyn Stream<Item = Result<T>> + Send + 'static>>,
         explain_receiver: oneshot::Receiver<Result<ExplainResult>>,
     ) -> Self {
-        Self { stream, explain_receiver: Some(explain_receiver) }
+        Self { stream, explain_receiver: Some(explain_receiver), ordered: true, header: None }
     }
 
     /// Create a response from a stream.
@@ -112,11 +169,227 @@ impl<T> ClickHouseResponse<T> {
         let receiver = self.explain_receiver.take()?;
         match receiver.await {
             Ok(result) => Some(result),
-            Err(_) => Some(Err(crate::Error::ChannelClosed)),
+            Err(_) => Some(Err(Error::ChannelClosed)),
+        }
+    }
+
+    /// Allow parallel stages, ie [`ClickHouseResponse::map_parallel`], to yield results as soon
+    /// as they complete instead of in the server's block order.
+    ///
+    /// Responses are ordered by default: a slow block holds back the blocks after it, even if
+    /// they finished first. Consumers that don't depend on row order, ie aggregating or writing
+    /// to an unordered sink, can opt out to keep every worker busy.
+    #[must_use]
+    pub fn unordered(mut self) -> Self {
+        self.ordered = false;
+        self
+    }
+
+    /// Whether parallel stages preserve the server's block order, see
+    /// [`ClickHouseResponse::unordered`].
+    pub fn is_ordered(&self) -> bool { self.ordered }
+
+    /// Wait for the first item of the response, keeping it at the front of the stream.
+    ///
+    /// For consumers that hand the response to a blocking reader, ie
+    /// [`ClickHouseResponse::into_blocking_reader`], and want to wait for the server beforehand.
+    /// Any EXPLAIN result and the result's header are kept. The stream isn't polled again once it
+    /// ended.
+    #[must_use]
+    pub async fn peek_first(mut self) -> Self
+    where
+        T: Send + 'static,
+    {
+        let mut stream = std::mem::replace(&mut self.stream, Box::pin(stream::empty())).peekable();
+        let _ = Pin::new(&mut stream).peek().await;
+        self.stream = Box::pin(stream);
+        self
+    }
+
+    /// Apply `f` to each item on blocking threads, running up to `concurrency` items at once.
+    ///
+    /// Items are sequenced so that results are yielded in the order the server sent its blocks,
+    /// unless the response is [`unordered`](ClickHouseResponse::unordered). Errors from the
+    /// underlying stream are passed through in place. Any EXPLAIN result is kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let rows = client
+    ///     .query("SELECT * FROM events", None)
+    ///     .await?
+    ///     .map_parallel(4, |batch| Ok(expensive_transform(&batch)))
+    ///     .try_collect::<Vec<_>>()
+    ///     .await?;
+    /// ```
+    pub fn map_parallel<U, F>(self, concurrency: usize, f: F) -> ClickHouseResponse<U>
+    where
+        T: Send + 'static,
+        U: Send + 'static,
+        F: Fn(T) -> Result<U> + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let tasks = self.stream.map(move |item| {
+            let f = Arc::clone(&f);
+            async move {
+                let item = item?;
+                SpawnedTask::spawn_blocking(move || f(item))
+                    .join_unwind()
+                    .await
+                    .map_err(|error| Error::Client(format!("Parallel stage failed: {error}")))?
+            }
+        });
+        let concurrency = concurrency.max(1);
+        let stream: Pin<Box<dyn Stream<Item = Result<U>> + Send + 'static>> = if self.ordered {
+            Box::pin(tasks.buffered(concurrency))
+        } else {
+            Box::pin(tasks.buffer_unordered(concurrency))
+        };
+        // Items are no longer batches of the result's schema
+        ClickHouseResponse {
+            stream,
+            explain_receiver: self.explain_receiver,
+            ordered: self.ordered,
+            header: None,
+        }
+    }
+
+    /// Keep `guard` alive until the stream is exhausted or the response is dropped, ie to return
+    /// a pooled connection once its query completes. Any EXPLAIN result is kept.
+    #[cfg_attr(not(any(feature = "pool", test)), expect(unused))]
+    pub(crate) fn hold_until_complete<G>(self, guard: G) -> Self
+    where
+        T: Send + 'static,
+        G: Send + 'static,
+    {
+        let mut guard = Some(guard);
+        let release = stream::poll_fn(move |_| {
+            drop(guard.take());
+            Poll::Ready(None)
+        });
+        ClickHouseResponse {
+            stream:           Box::pin(self.stream.chain(release)),
+            explain_receiver: self.explain_receiver,
+            ordered:          self.ordered,
+            header:           self.header,
         }
     }
 }
 
+impl ClickHouseResponse<RecordBatch> {
+    /// Collect all batches of the response into a [`QueryResult`].
+    ///
+    /// # Errors
+    /// Returns the first error encountered while receiving batches.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let result = client.query("SELECT * FROM events", None).await?.collect_result().await?;
+    /// println!("{} rows", result.row_count());
+    /// let table = result.collect_table()?;
+    /// ```
+    pub async fn collect_result(self) -> Result<QueryResult> {
+        Ok(QueryResult::from(self.try_collect::<Vec<_>>().await?))
+    }
+
+    /// Track the result's header, see [`ClickHouseResponse::header_schema`].
+    pub(crate) fn with_header(mut self, header: ResultHeader) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// The schema of the result as sent in its header block.
+    ///
+    /// The server sends the header before any rows, so the schema is known once the first batch
+    /// was received or the stream ended, including for results without any rows. Returns `None`
+    /// before then, or if the response doesn't track its header, ie for responses built with
+    /// [`ClickHouseResponse::from_stream`].
+    ///
+    /// # Errors
+    /// Returns an error if a column's type cannot be mapped to Arrow.
+    pub fn header_schema(&mut self) -> Option<Result<SchemaRef>> {
+        self.header.as_mut().and_then(ResultHeader::schema)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ClickHouseResponse<RecordBatch> {
+    /// Convert the response into a stream of rows deserialized into `R`.
+    ///
+    /// Each row is bridged through JSON, see [`crate::arrow::typed`] for how column types map
+    /// to Rust types. A batch that fails to deserialize yields a single error in place of its
+    /// rows. Any EXPLAIN result configured for the query is kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// #[derive(serde::Deserialize)]
+    /// struct Event { id: u64, name: String }
+    ///
+    /// let events = client
+    ///     .query("SELECT id, name FROM events", None)
+    ///     .await?
+    ///     .into_typed::<Event>()
+    ///     .try_collect::<Vec<_>>()
+    ///     .await?;
+    /// ```
+    pub fn into_typed<R>(self) -> ClickHouseResponse<R>
+    where
+        R: serde::de::DeserializeOwned + Send + 'static,
+    {
+        self.into_typed_with(crate::arrow::typed::TYPED_ROW_OPTIONS)
+    }
+
+    /// Like [`ClickHouseResponse::into_typed`], rendering values according to `options` before
+    /// deserializing, ie to read decimals as exact strings.
+    pub fn into_typed_with<R>(
+        self,
+        options: crate::arrow::ndjson::NdjsonOptions,
+    ) -> ClickHouseResponse<R>
+    where
+        R: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let stream = self.stream.flat_map(move |batch| {
+            let rows =
+                batch.and_then(|batch| crate::arrow::typed::batch_to_typed::<R>(&batch, options));
+            futures_util::stream::iter(match rows {
+                Ok(rows) => rows.into_iter().map(Ok).collect::<Vec<_>>(),
+                Err(error) => vec![Err(error)],
+            })
+        });
+        ClickHouseResponse {
+            stream:           Box::pin(stream),
+            explain_receiver: self.explain_receiver,
+            ordered:          self.ordered,
+            header:           None,
+        }
+    }
+}
+
+#[cfg(feature = "ffi")]
+impl ClickHouseResponse<RecordBatch> {
+    /// Convert the response into a synchronous [`crate::arrow::ffi::BlockingRecordBatchReader`].
+    ///
+    /// The reader can be exported as an `ArrowArrayStream` for consumption by local engines such
+    /// as `DuckDB` or `DataFusion`. Blocks on `handle` until the first batch arrives, so this must
+    /// be called from outside an async context (e.g. within `tokio::task::spawn_blocking`).
+    /// Results without any rows report the schema of their header block, see
+    /// [`ClickHouseResponse::header_schema`].
+    ///
+    /// # Errors
+    /// Returns an error if the first batch fails to be received.
+    pub fn into_blocking_reader(
+        mut self,
+        handle: tokio::runtime::Handle,
+    ) -> Result<crate::arrow::ffi::BlockingRecordBatchReader> {
+        let mut header = self.header.take();
+        crate::arrow::ffi::BlockingRecordBatchReader::try_new_with_schema(self, handle, move || {
+            header.as_mut().and_then(ResultHeader::schema)
+        })
+    }
+}
+
 impl<T> Stream for ClickHouseResponse<T>
 where
     T: Send + 'static,
@@ -130,3 +403,386 @@ where
         self.project().stream.poll_next(cx)
     }
 }
+
+/// The outcome of a server-side `INSERT ... SELECT`, see [`super::Client::insert_select`].
+///
+/// Totals are summed from the [`Progress`] packets the server sent for the statement. `elapsed`
+/// is measured by the client, from sending the statement to the end of the stream.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InsertSelectSummary {
+    pub read_rows:     u64,
+    pub read_bytes:    u64,
+    pub written_rows:  u64,
+    pub written_bytes: u64,
+    pub elapsed:       Duration,
+}
+
+/// Response from a server-side `INSERT ... SELECT`, see [`super::Client::insert_select`].
+///
+/// Streams the statement's [`Progress`] updates as they arrive, while the statement is driven to
+/// completion in the background. Updates are dropped rather than buffered if the stream is not
+/// polled, but always count towards [`InsertSelectResponse::summary`].
+pub struct InsertSelectResponse {
+    progress: mpsc::Receiver<Progress>,
+    task:     SpawnedTask<Result<InsertSelectSummary>>,
+}
+
+impl InsertSelectResponse {
+    pub(crate) fn new(
+        progress: mpsc::Receiver<Progress>,
+        task: SpawnedTask<Result<InsertSelectSummary>>,
+    ) -> Self {
+        Self { progress, task }
+    }
+
+    /// Wait for the statement to complete, discarding any remaining progress updates.
+    ///
+    /// # Errors
+    /// - Returns the error of the statement, if it failed.
+    /// - Returns `Client` if the background task was cancelled.
+    pub async fn summary(self) -> Result<InsertSelectSummary> {
+        let Self { progress, task } = self;
+        drop(progress);
+        task.await.map_err(|e| Error::Client(format!("INSERT SELECT task failed: {e}")))?
+    }
+}
+
+impl Stream for InsertSelectResponse {
+    type Item = Progress;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.progress.poll_recv(cx)
+    }
+}
+
+/// An item of a query result streamed with progress, see
+/// [`super::Client::query_with_progress`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryUpdate<T> {
+    /// A block of the result.
+    Data(T),
+    /// A progress update from the server. Values are deltas since the previous update.
+    Progress(Progress),
+}
+
+impl<T> QueryUpdate<T> {
+    /// The block of the result, if this is one.
+    pub fn into_data(self) -> Option<T> {
+        match self {
+            Self::Data(data) => Some(data),
+            Self::Progress(_) => None,
+        }
+    }
+}
+
+/// Interleaves the progress updates of a query with its data, updates first.
+///
+/// Progress packets precede the end of the stream, so updates still queued once the data ends
+/// are returned before the stream ends. Updates are dropped by the sender rather than buffered
+/// if the stream is not polled.
+#[pin_project::pin_project]
+pub(crate) struct WithProgress<S> {
+    #[pin]
+    data:     stream::Fuse<S>,
+    progress: mpsc::Receiver<Progress>,
+}
+
+impl<S: Stream> WithProgress<S> {
+    pub(crate) fn new(data: S, progress: mpsc::Receiver<Progress>) -> Self {
+        Self { data: data.fuse(), progress }
+    }
+}
+
+impl<T, S: Stream<Item = Result<T>>> Stream for WithProgress<S> {
+    type Item = Result<QueryUpdate<T>>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if let Poll::Ready(Some(progress)) = this.progress.poll_recv(cx) {
+            return Poll::Ready(Some(Ok(QueryUpdate::Progress(progress))));
+        }
+        match this.data.poll_next(cx) {
+            Poll::Ready(Some(data)) => Poll::Ready(Some(data.map(QueryUpdate::Data))),
+            Poll::Ready(None) => {
+                Poll::Ready(this.progress.try_recv().ok().map(|p| Ok(QueryUpdate::Progress(p))))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The fully collected result of a query, as received from `ClickHouse` in one or more batches.
+///
+/// Provides the "give me everything" path over a [`ClickHouseResponse`]: the schema, total row
+/// count, access to the batches as received, and [`QueryResult::collect_table`] to concatenate
+/// them into a single [`RecordBatch`].
+///
+/// Queries that return no batches report an empty schema.
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    schema:  SchemaRef,
+    batches: Vec<RecordBatch>,
+}
+
+impl QueryResult {
+    /// Create a result from batches sharing `schema`.
+    pub fn new(schema: SchemaRef, batches: Vec<RecordBatch>) -> Self { Self { schema, batches } }
+
+    /// The schema of the result.
+    #[must_use]
+    pub fn schema(&self) -> SchemaRef { Arc::clone(&self.schema) }
+
+    /// The total number of rows across all batches.
+    #[must_use]
+    pub fn row_count(&self) -> usize { self.batches.iter().map(RecordBatch::num_rows).sum() }
+
+    /// Whether the result contains no rows.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.row_count() == 0 }
+
+    /// The batches as received from the server.
+    #[must_use]
+    pub fn batches(&self) -> &[RecordBatch] { &self.batches }
+
+    /// Iterate over the batches as received from the server.
+    pub fn iter(&self) -> std::slice::Iter<'_, RecordBatch> { self.batches.iter() }
+
+    /// Consume the result, returning the batches as received from the server.
+    #[must_use]
+    pub fn into_batches(self) -> Vec<RecordBatch> { self.batches }
+
+    /// Concatenate all batches into a single [`RecordBatch`].
+    ///
+    /// # Errors
+    /// Returns an error if the batches cannot be concatenated.
+    pub fn collect_table(&self) -> Result<RecordBatch> {
+        Ok(concat_batches(&self.schema, &self.batches)?)
+    }
+}
+
+impl From<Vec<RecordBatch>> for QueryResult {
+    fn from(batches: Vec<RecordBatch>) -> Self {
+        let schema = batches.first().map_or_else(|| Arc::new(Schema::empty()), RecordBatch::schema);
+        Self { schema, batches }
+    }
+}
+
+impl IntoIterator for QueryResult {
+    type IntoIter = std::vec::IntoIter<RecordBatch>;
+    type Item = RecordBatch;
+
+    fn into_iter(self) -> Self::IntoIter { self.batches.into_iter() }
+}
+
+impl<'a> IntoIterator for &'a QueryResult {
+    type IntoIter = std::slice::Iter<'a, RecordBatch>;
+    type Item = &'a RecordBatch;
+
+    fn into_iter(self) -> Self::IntoIter { self.batches.iter() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use arrow::array::AsArray;
+    use arrow::datatypes::Int32Type;
+
+    use super::*;
+    use crate::Error;
+    use crate::test_utils::int32_batch;
+
+    #[tokio::test]
+    async fn test_collect_result() {
+        let batches = vec![Ok(int32_batch(vec![1, 2])), Ok(int32_batch(vec![3]))];
+        let result =
+            ClickHouseResponse::from_stream(stream::iter(batches)).collect_result().await.unwrap();
+        assert_eq!(result.schema().field(0).name(), "v");
+        assert_eq!(result.row_count(), 3);
+        assert_eq!(result.batches().len(), 2);
+        assert_eq!(result.iter().map(RecordBatch::num_rows).collect::<Vec<_>>(), vec![2, 1]);
+
+        let table = result.collect_table().unwrap();
+        assert_eq!(table.num_rows(), 3);
+        assert_eq!(table.column(0).as_primitive::<Int32Type>().values(), &[1, 2, 3]);
+        assert_eq!(result.into_iter().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_collect_result_empty() {
+        let result = ClickHouseResponse::<RecordBatch>::from_stream(stream::empty())
+            .collect_result()
+            .await
+            .unwrap();
+        assert!(result.is_empty());
+        assert!(result.schema().fields().is_empty());
+        assert_eq!(result.collect_table().unwrap().num_rows(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_collect_result_error() {
+        let batches = vec![Ok(int32_batch(vec![1])), Err(Error::Protocol("boom".into()))];
+        let result = ClickHouseResponse::from_stream(stream::iter(batches)).collect_result().await;
+        assert!(matches!(result, Err(Error::Protocol(_))));
+    }
+
+    #[tokio::test]
+    async fn test_header_schema() {
+        let projection = Some(Arc::from(["v".to_string()]));
+        let (sender, header) = ResultHeader::channel(ArrowOptions::default(), projection, None);
+        let mut response =
+            ClickHouseResponse::<RecordBatch>::from_stream(stream::empty()).with_header(header);
+        assert!(response.header_schema().is_none());
+
+        let columns = vec![("v".to_string(), Type::Int32), ("w".to_string(), Type::String)];
+        sender.send(columns).unwrap();
+        assert!(response.next().await.is_none());
+        let schema = response.header_schema().unwrap().unwrap();
+        assert_eq!(schema, int32_batch(vec![]).schema());
+        // The schema is kept once resolved
+        assert_eq!(response.header_schema().unwrap().unwrap(), schema);
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn test_blocking_reader_empty_result_schema() {
+        use arrow::array::RecordBatchReader;
+
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let (sender, header) = ResultHeader::channel(ArrowOptions::default(), None, None);
+        sender.send(vec![("v".to_string(), Type::Int32)]).unwrap();
+        let response =
+            ClickHouseResponse::<RecordBatch>::from_stream(stream::empty()).with_header(header);
+        let reader = response.into_blocking_reader(rt.handle().clone()).unwrap();
+        assert_eq!(reader.schema(), int32_batch(vec![]).schema());
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_with_progress() {
+        let progress = |rows| Progress { read_rows: rows, ..Default::default() };
+        let (tx, rx) = mpsc::channel(4);
+        tx.try_send(progress(10)).unwrap();
+        let (data_tx, data_rx) = mpsc::channel(4);
+        let mut updates = WithProgress::new(ReceiverStream::new(data_rx), rx);
+
+        // Updates are returned ahead of data
+        data_tx.try_send(Ok(1)).unwrap();
+        assert_eq!(updates.next().await.unwrap().unwrap(), QueryUpdate::Progress(progress(10)));
+        assert_eq!(updates.next().await.unwrap().unwrap(), QueryUpdate::Data(1));
+
+        // Queued updates are returned before the end of the data, which ends the stream even if
+        // the sender of updates is still alive
+        tx.try_send(progress(5)).unwrap();
+        drop(data_tx);
+        assert_eq!(updates.next().await.unwrap().unwrap(), QueryUpdate::Progress(progress(5)));
+        assert!(updates.next().await.is_none());
+        drop(tx);
+        assert_eq!(QueryUpdate::<i32>::Progress(progress(1)).into_data(), None);
+    }
+
+    #[tokio::test]
+    async fn test_hold_until_complete() {
+        let guard = Arc::new(());
+        let mut response = ClickHouseResponse::from_stream(stream::iter(vec![Ok(1), Ok(2)]))
+            .hold_until_complete(Arc::clone(&guard));
+        assert!(matches!(response.next().await, Some(Ok(1))));
+        assert!(matches!(response.next().await, Some(Ok(2))));
+        assert_eq!(Arc::strong_count(&guard), 2);
+        // Released once the stream ends, before the response is dropped
+        assert!(response.next().await.is_none());
+        assert_eq!(Arc::strong_count(&guard), 1);
+
+        // Or when the response is dropped early
+        let response = ClickHouseResponse::from_stream(stream::iter(vec![Ok(1)]))
+            .hold_until_complete(Arc::clone(&guard));
+        assert_eq!(Arc::strong_count(&guard), 2);
+        drop(response);
+        assert_eq!(Arc::strong_count(&guard), 1);
+    }
+
+    fn slow_identity(delays: Vec<u64>) -> impl Fn(usize) -> Result<usize> {
+        move |index| {
+            std::thread::sleep(Duration::from_millis(delays[index]));
+            Ok(index)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_map_parallel_preserves_block_order() {
+        // Earlier blocks take longer, so they finish last without sequencing
+        let delays = (0..16).map(|i| 16 - i).collect::<Vec<u64>>();
+        let response = ClickHouseResponse::from_stream(stream::iter((0..16).map(Ok)));
+        assert!(response.is_ordered());
+        let results =
+            response.map_parallel(8, slow_identity(delays)).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(results, (0..16).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_map_parallel_unordered() {
+        // The first block only completes once released, after the others are received
+        let (release, released) = oneshot::channel::<()>();
+        let released = parking_lot::Mutex::new(Some(released));
+        let response = ClickHouseResponse::from_stream(stream::iter((0..4).map(Ok))).unordered();
+        assert!(!response.is_ordered());
+        let mut results = response.map_parallel(4, move |index: usize| {
+            let released = if index == 0 { released.lock().take() } else { None };
+            if let Some(released) = released {
+                let _ = released.blocking_recv();
+            }
+            Ok(index)
+        });
+
+        let mut first = Vec::new();
+        for _ in 0..3 {
+            first.push(results.try_next().await.unwrap().unwrap());
+        }
+        first.sort_unstable();
+        assert_eq!(first, vec![1, 2, 3]);
+
+        release.send(()).unwrap();
+        assert_eq!(results.try_next().await.unwrap(), Some(0));
+        assert!(results.try_next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_peek_first() {
+        let (sender, receiver) = oneshot::channel();
+        let polled = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&polled);
+        let items = stream::iter([Ok(1), Ok(2)]).inspect(move |_| {
+            let _ = counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let response = ClickHouseResponse::from_stream_with_explain(items, receiver);
+        let mut response = response.peek_first().await;
+        assert_eq!(polled.load(Ordering::Relaxed), 1);
+
+        // The EXPLAIN receiver is kept, and the first item is yielded first
+        sender.send(Err(Error::ChannelClosed)).unwrap();
+        assert!(response.explain().await.unwrap().is_err());
+        assert_eq!(response.try_collect::<Vec<_>>().await.unwrap(), vec![1, 2]);
+        assert_eq!(polled.load(Ordering::Relaxed), 2);
+
+        let response = ClickHouseResponse::<i32>::from_stream(stream::empty()).peek_first().await;
+        assert!(response.collect::<Vec<_>>().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_map_parallel_passes_errors() {
+        let items = vec![Ok(1), Err(Error::Protocol("boom".into())), Ok(3)];
+        let results = ClickHouseResponse::from_stream(stream::iter(items))
+            .map_parallel(2, |value: i32| Ok(value * 2))
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(results[0], Ok(2)));
+        assert!(matches!(results[1], Err(Error::Protocol(_))));
+        assert!(matches!(results[2], Ok(6)));
+    }
+}
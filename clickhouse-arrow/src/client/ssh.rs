@@ -0,0 +1,119 @@
+//! SSH key authentication for users identified `WITH ssh_key`.
+//!
+//! The client announces SSH authentication by prefixing the username in its hello, requests a
+//! challenge, and answers with the challenge signed by its private key. The signed message binds
+//! the challenge to the protocol revision, database, and user, as the server reconstructs it.
+use ssh_key::PrivateKey;
+use ssh_key::encoding::Encode;
+use ssh_key::signature::Signer;
+
+use super::SshKeyOptions;
+use crate::prelude::*;
+
+/// Username prefix informing the server that the client authenticates with an SSH key
+pub(super) const SSH_KEY_AUTHENTICATION_MARKER: &str = " SSH KEY AUTHENTICATION ";
+
+/// A private key loaded for answering the server's SSH challenge.
+pub(super) struct SshSigner {
+    key: PrivateKey,
+}
+
+impl SshSigner {
+    /// Load and, if necessary, decrypt the configured OpenSSH private key.
+    pub(super) fn load(options: &SshKeyOptions) -> Result<Self> {
+        let path = options.path.display();
+        let key = PrivateKey::read_openssh_file(&options.path).map_err(|error| {
+            Error::Configuration(format!("Failed to read SSH key {path}: {error}"))
+        })?;
+        let key = if key.is_encrypted() {
+            let Some(passphrase) = options.passphrase.as_ref() else {
+                return Err(Error::Configuration(format!(
+                    "SSH key {path} is encrypted but no passphrase was provided"
+                )));
+            };
+            key.decrypt(passphrase.get()).map_err(|error| {
+                Error::Configuration(format!("Failed to decrypt SSH key {path}: {error}"))
+            })?
+        } else {
+            key
+        };
+        Ok(Self { key })
+    }
+
+    /// Sign the server's challenge, returning the SSH signature blob.
+    pub(super) fn sign(
+        &self,
+        revision: u64,
+        database: &str,
+        user: &str,
+        challenge: &[u8],
+    ) -> Result<Vec<u8>> {
+        let message = challenge_message(revision, database, user, challenge);
+        let signature = self
+            .key
+            .try_sign(&message)
+            .map_err(|error| Error::Client(format!("Failed to sign SSH challenge: {error}")))?;
+        signature
+            .encode_vec()
+            .map_err(|error| Error::Client(format!("Failed to encode SSH signature: {error}")))
+    }
+}
+
+/// The message signed in reply to a challenge, matching the server's reconstruction.
+fn challenge_message(revision: u64, database: &str, user: &str, challenge: &[u8]) -> Vec<u8> {
+    let revision = revision.to_string();
+    let mut message =
+        Vec::with_capacity(revision.len() + database.len() + user.len() + challenge.len());
+    message.extend_from_slice(revision.as_bytes());
+    message.extend_from_slice(database.as_bytes());
+    message.extend_from_slice(user.as_bytes());
+    message.extend_from_slice(challenge);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use ssh_key::rand_core::OsRng;
+    use ssh_key::signature::Verifier;
+    use ssh_key::{Algorithm, LineEnding, Signature};
+
+    use super::*;
+
+    #[test]
+    fn test_challenge_message() {
+        assert_eq!(challenge_message(54479, "db", "alice", b"xyz"), b"54479dbalicexyz");
+    }
+
+    #[test]
+    fn test_sign_challenge() {
+        let key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519).unwrap();
+        let dir = std::env::temp_dir().join(format!("ch-arrow-ssh-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("id_ed25519");
+        std::fs::write(&path, key.to_openssh(LineEnding::LF).unwrap().as_bytes()).unwrap();
+
+        let signer = SshSigner::load(&SshKeyOptions::new(&path)).unwrap();
+        let blob = signer.sign(54479, "", "alice", b"challenge").unwrap();
+
+        let signature = Signature::try_from(blob.as_slice()).unwrap();
+        let message = challenge_message(54479, "", "alice", b"challenge");
+        key.public_key().verify(&message, &signature).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_key_requires_passphrase() {
+        let key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519)
+            .unwrap()
+            .encrypt(&mut OsRng, "hunter2")
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("ch-arrow-ssh-enc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("id_ed25519");
+        std::fs::write(&path, key.to_openssh(LineEnding::LF).unwrap().as_bytes()).unwrap();
+
+        assert!(SshSigner::load(&SshKeyOptions::new(&path)).is_err());
+        assert!(SshSigner::load(&SshKeyOptions::new(&path).with_passphrase("hunter2")).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
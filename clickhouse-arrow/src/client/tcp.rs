@@ -1,12 +1,11 @@
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(feature = "rustls-tls")]
 use std::sync::Arc;
+#[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+use std::sync::OnceLock;
 use std::time::Duration;
 
 use tokio::net::TcpStream;
-use tokio_rustls::TlsConnector;
-use tokio_rustls::client::TlsStream;
-use tokio_rustls::rustls::pki_types::ServerName;
-use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
 
 use crate::constants::*;
 use crate::prelude::*;
@@ -24,13 +23,16 @@ enum DestinationInner {
     SocketAddr(SocketAddr),       // Direct SocketAddr (e.g., 127.0.0.1:9000)
     HostPort(String, u16),        // Hostname and port (e.g., "localhost", 9000)
     Endpoint(String),             // String to parse (e.g., "localhost:9000")
+    Resolved(String, Vec<SocketAddr>), // Host and the non-empty socket addrs it resolved to
 }
 
 impl Destination {
     /// Resolve to Vec<SocketAddr> using [`tokio::net::lookup_host`]
     pub(crate) async fn resolve(&self, ipv4_only: bool) -> Result<Vec<SocketAddr>> {
         let addrs: Vec<SocketAddr> = match &self.inner {
-            DestinationInner::SocketAddrs(addrs) => return Ok(addrs.clone()),
+            DestinationInner::SocketAddrs(addrs) | DestinationInner::Resolved(_, addrs) => {
+                return Ok(addrs.clone());
+            }
             DestinationInner::SocketAddr(addr) => return Ok(vec![*addr]),
             DestinationInner::HostPort(host, port) => {
                 tokio::net::lookup_host((host.as_str(), *port)).await.map(Iterator::collect)
@@ -52,7 +54,7 @@ impl Destination {
     // Create a domain from this Destination
     pub(crate) fn domain(&self) -> String {
         match &self.inner {
-            DestinationInner::SocketAddrs(addrs) => {
+            DestinationInner::SocketAddrs(addrs) | DestinationInner::Resolved(_, addrs) => {
                 addrs.iter().next().map(|addr| addr.ip().to_string()).unwrap_or_default()
             }
             DestinationInner::SocketAddr(addr) => addr.ip().to_string(),
@@ -62,18 +64,56 @@ impl Destination {
             }
         }
     }
+
+    /// The host this destination names, if it wasn't given as socket addresses.
+    pub(crate) fn host(&self) -> Option<&str> {
+        match &self.inner {
+            DestinationInner::SocketAddrs(_) | DestinationInner::SocketAddr(_) => None,
+            DestinationInner::HostPort(host, _) | DestinationInner::Resolved(host, _) => {
+                Some(host.trim_start_matches('[').trim_end_matches(']'))
+            }
+            DestinationInner::Endpoint(endpoint) => {
+                if let Some(bracketed) = endpoint.strip_prefix('[') {
+                    bracketed.split(']').next()
+                } else if endpoint.matches(':').count() > 1 {
+                    // Bare IPv6 address without a port
+                    Some(endpoint)
+                } else {
+                    endpoint.split(':').next()
+                }
+            }
+        }
+    }
+
+    /// Pin this destination to the addresses it resolved to, keeping its host.
+    pub(crate) fn into_resolved(self, addrs: Vec<SocketAddr>) -> Self {
+        let inner = match self.host() {
+            Some(host) => DestinationInner::Resolved(host.to_string(), addrs),
+            None => DestinationInner::SocketAddrs(addrs),
+        };
+        Destination { inner }
+    }
 }
 
+/// TLS stream produced by the enabled TLS backend.
+#[cfg(feature = "rustls-tls")]
+pub(super) type TlsStream = tokio_rustls::client::TlsStream<TcpStream>;
+/// TLS stream produced by the enabled TLS backend.
+#[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+pub(super) type TlsStream = tokio_native_tls::TlsStream<TcpStream>;
+
 /// Connects to `ClickHouse`'s native server port over TLS.
+#[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
 pub(super) async fn connect_tls(
     addrs: &[SocketAddr],
     domain: Option<&str>,
-) -> Result<TlsStream<TcpStream>> {
+    pins: &[String],
+) -> Result<TlsStream> {
     let domain: String =
         domain.as_ref().map_or_else(|| addrs[0].ip().to_string(), ToString::to_string);
     debug!(%domain, "Initiating TLS connection");
     let stream = connect_socket(addrs).await?;
-    tls_stream(domain, stream).await
+    tls_stream(domain, stream, pins).await
 }
 
 /// Connects to `ClickHouse`'s native server port and configures common socket options.
@@ -107,26 +147,76 @@ pub(crate) async fn connect_socket(addrs: &[SocketAddr]) -> Result<TcpStream> {
     Ok(TcpStream::from_std(stream)?)
 }
 
-// Helper function to facilitate TLS connection setup
-async fn tls_stream(domain: String, stream: TcpStream) -> Result<TlsStream<TcpStream>> {
-    let root_store = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.into() };
+/// Shared rustls configuration.
+///
+/// The config is built once per process so that its session store is shared by every connection.
+/// Reconnects, inner pool connections, and pooled clients that churn can then resume previous TLS
+/// sessions (tickets or session IDs) instead of performing a full handshake each time.
+#[cfg(feature = "rustls-tls")]
+fn rustls_config() -> Arc<tokio_rustls::rustls::ClientConfig> {
+    use tokio_rustls::rustls::{self, ClientConfig, RootCertStore};
+
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    Arc::clone(CONFIG.get_or_init(|| {
+        // Install the default crypto provider if the application hasn't already
+        drop(rustls::crypto::aws_lc_rs::default_provider().install_default());
+
+        let root_store = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.into() };
+        let mut tls_config =
+            ClientConfig::builder().with_root_certificates(root_store).with_no_client_auth();
+
+        // Enable session resumption, shared across all connections
+        tls_config.resumption =
+            rustls::client::Resumption::in_memory_sessions(TLS_SESSION_CACHE_SIZE);
+
+        Arc::new(tls_config)
+    }))
+}
 
-    let mut tls_config =
-        ClientConfig::builder().with_root_certificates(root_store).with_no_client_auth();
+// Helper function to facilitate TLS connection setup
+#[cfg(feature = "rustls-tls")]
+async fn tls_stream(domain: String, stream: TcpStream, pins: &[String]) -> Result<TlsStream> {
+    use tokio_rustls::TlsConnector;
+    use tokio_rustls::rustls::pki_types::ServerName;
+
+    let connector = TlsConnector::from(rustls_config());
+    let dnsname = ServerName::try_from(domain).map_err(|e| Error::InvalidDnsName(e.to_string()))?;
+    let stream = connector.connect(dnsname, stream).await?;
+    let leaf = stream.get_ref().1.peer_certificates().and_then(<[_]>::first);
+    super::trust::verify_spki_pins(pins, leaf.map(AsRef::as_ref))?;
+    Ok(stream)
+}
 
-    // Enable session resumption by default
-    tls_config.resumption = rustls::client::Resumption::in_memory_sessions(256);
+/// Shared native-tls connector.
+///
+/// Built once per process. Session resumption is left to the platform TLS library.
+#[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+fn native_tls_connector() -> Result<&'static tokio_native_tls::TlsConnector> {
+    static CONNECTOR: OnceLock<tokio_native_tls::TlsConnector> = OnceLock::new();
+    if let Some(connector) = CONNECTOR.get() {
+        return Ok(connector);
+    }
+    let connector = tokio_native_tls::native_tls::TlsConnector::new()
+        .map_err(|e| Error::Configuration(format!("Failed to build TLS connector: {e}")))?;
+    Ok(CONNECTOR.get_or_init(|| connector.into()))
+}
 
-    let connector = TlsConnector::from(Arc::new(tls_config));
-    let dnsname =
-        ServerName::try_from(domain.clone()).map_err(|e| Error::InvalidDnsName(e.to_string()))?;
-    Ok(connector.connect(dnsname, stream).await?)
+// Helper function to facilitate TLS connection setup
+#[cfg(all(feature = "native-tls", not(feature = "rustls-tls")))]
+async fn tls_stream(domain: String, stream: TcpStream, pins: &[String]) -> Result<TlsStream> {
+    if !pins.is_empty() {
+        return Err(Error::Configuration("SPKI pinning requires the `rustls-tls` feature".into()));
+    }
+    native_tls_connector()?
+        .connect(&domain, stream)
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))
 }
 
 impl std::fmt::Display for Destination {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.inner {
-            DestinationInner::SocketAddrs(addrs) => {
+            DestinationInner::SocketAddrs(addrs) | DestinationInner::Resolved(_, addrs) => {
                 write!(f, "{}", addrs.first().map(ToString::to_string).unwrap_or_default())
             }
             DestinationInner::SocketAddr(addr) => write!(f, "{addr}"),
@@ -217,6 +307,22 @@ mod tests {
     // Helper to create Destination variants
     fn socket_addr() -> SocketAddr { SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9000) }
 
+    #[test]
+    fn test_destination_host() {
+        assert_eq!(Destination::from("localhost:9000").host(), Some("localhost"));
+        assert_eq!(Destination::from("[::1]:9000").host(), Some("::1"));
+        assert_eq!(Destination::from("::1").host(), Some("::1"));
+        assert_eq!(Destination::from(("ch.example.com", 9440)).host(), Some("ch.example.com"));
+        assert_eq!(Destination::from(socket_addr()).host(), None);
+
+        let resolved = Destination::from("localhost:9000").into_resolved(vec![socket_addr()]);
+        assert_eq!(resolved.host(), Some("localhost"));
+        assert_eq!(resolved.domain(), "127.0.0.1");
+        assert_eq!(resolved.to_string(), "127.0.0.1:9000");
+        let resolved = Destination::from(socket_addr()).into_resolved(vec![socket_addr()]);
+        assert_eq!(resolved, Destination::from(vec![socket_addr()]));
+    }
+
     #[tokio::test]
     async fn test_resolve_socket_addrs() {
         let addrs = vec![socket_addr()];
@@ -281,6 +387,14 @@ mod tests {
         assert!(result.iter().all(|addr| matches!(addr, SocketAddr::V4(_))));
     }
 
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn test_rustls_config_shared_for_resumption() {
+        let first = rustls_config();
+        let second = rustls_config();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
     #[test]
     fn test_domain_socket_addrs() {
         let addrs = vec![socket_addr()];
@@ -0,0 +1,287 @@
+//! Client-side concurrency caps, query admission, and insert rate limiting.
+//!
+//! See [`super::ClientBuilder::with_max_concurrent_queries`],
+//! [`super::ClientBuilder::with_query_queue`] and [`super::ClientBuilder::with_insert_rate_limit`].
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use futures_util::{Stream, StreamExt};
+use parking_lot::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError};
+
+use super::{InsertRateLimit, QueryQueue};
+use crate::formats::DataSize;
+use crate::{Error, Result};
+
+/// Held for as long as a query, including the stream of its response, is in flight.
+pub(crate) type QueryPermit = Option<OwnedSemaphorePermit>;
+
+/// Point in time metrics of the query slots of a client, see [`super::Client::queue_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    /// The maximum number of concurrent queries.
+    pub limit:     usize,
+    /// Queries and inserts currently holding a slot.
+    pub in_flight: usize,
+    /// Queries currently queued for a slot.
+    pub waiting:   usize,
+    /// Queries given a slot since the client was built.
+    pub admitted:  u64,
+    /// Queries shed with [`Error::Overloaded`] since the client was built.
+    pub shed:      u64,
+    /// Total time queries waited for a slot, whether admitted or shed.
+    pub wait_time: Duration,
+}
+
+/// Shared limits on the queries and inserts of a client and its clones.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Throttle {
+    queries: Option<Arc<QuerySlots>>,
+    inserts: Option<Arc<RateLimiter>>,
+}
+
+impl Throttle {
+    pub(crate) fn new(
+        max_queries: Option<usize>,
+        queue: Option<QueryQueue>,
+        insert_rate: Option<InsertRateLimit>,
+    ) -> Self {
+        Self {
+            queries: max_queries.map(|max| Arc::new(QuerySlots::new(max, queue))),
+            inserts: insert_rate.map(|limit| Arc::new(RateLimiter::new(limit))),
+        }
+    }
+
+    /// Wait for a query slot, if concurrent queries are capped.
+    ///
+    /// # Errors
+    /// Returns [`Error::Overloaded`] if the query queue is full or the wait exceeds its deadline.
+    pub(crate) async fn acquire_query(&self) -> Result<QueryPermit> {
+        let Some(queries) = self.queries.as_ref() else { return Ok(None) };
+        Ok(Some(queries.acquire().await?))
+    }
+
+    /// Metrics of the query slots, if concurrent queries are capped.
+    pub(crate) fn queue_stats(&self) -> Option<QueueStats> {
+        self.queries.as_ref().map(|queries| queries.stats())
+    }
+
+    /// Wait until the insert rate limit allows sending `data`, if inserts are rate limited.
+    pub(crate) async fn acquire_insert<D: DataSize>(&self, data: &[D]) {
+        let Some(inserts) = self.inserts.as_ref() else { return };
+        let amount = data.iter().map(|d| inserts.limit.amount(d)).sum();
+        let wait = inserts.reserve(amount);
+        if !wait.is_zero() {
+            tracing::trace!(?wait, amount, "Insert rate limited");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Keep `permit` until `stream` is dropped.
+pub(crate) fn hold_permit<S: Stream>(
+    stream: S,
+    permit: QueryPermit,
+) -> impl Stream<Item = S::Item> {
+    stream.map(move |item| {
+        let _held = &permit;
+        item
+    })
+}
+
+/// The slots of [`Throttle`] capping concurrent queries, and the queue waiting for them.
+#[derive(Debug)]
+struct QuerySlots {
+    limit:      usize,
+    permits:    Arc<Semaphore>,
+    queue:      Option<QueryQueue>,
+    waiting:    AtomicUsize,
+    admitted:   AtomicU64,
+    shed:       AtomicU64,
+    wait_nanos: AtomicU64,
+}
+
+impl QuerySlots {
+    fn new(limit: usize, queue: Option<QueryQueue>) -> Self {
+        let limit = limit.max(1);
+        Self {
+            limit,
+            permits: Arc::new(Semaphore::new(limit)),
+            queue,
+            waiting: AtomicUsize::new(0),
+            admitted: AtomicU64::new(0),
+            shed: AtomicU64::new(0),
+            wait_nanos: AtomicU64::new(0),
+        }
+    }
+
+    async fn acquire(&self) -> Result<OwnedSemaphorePermit> {
+        let permit = match Arc::clone(&self.permits).try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(TryAcquireError::NoPermits) => self.wait().await?,
+            Err(TryAcquireError::Closed) => return Err(Error::ChannelClosed),
+        };
+        let _ = self.admitted.fetch_add(1, Ordering::Relaxed);
+        Ok(permit)
+    }
+
+    /// Queue for a slot, shedding the query if the queue is full or the deadline passes.
+    async fn wait(&self) -> Result<OwnedSemaphorePermit> {
+        let started = Instant::now();
+        let queued = self.waiting.fetch_add(1, Ordering::Relaxed);
+        let _waiting = WaitGuard(&self.waiting);
+
+        let acquire = Arc::clone(&self.permits).acquire_owned();
+        let permit = match self.queue {
+            Some(queue) if queue.max_depth.is_some_and(|depth| queued >= depth) => None,
+            Some(queue) => tokio::time::timeout(queue.max_wait, acquire).await.ok(),
+            None => Some(acquire.await),
+        };
+        let waited = started.elapsed();
+        let nanos = u64::try_from(waited.as_nanos()).unwrap_or(u64::MAX);
+        let _ = self.wait_nanos.fetch_add(nanos, Ordering::Relaxed);
+        match permit {
+            Some(permit) => permit.map_err(|_| Error::ChannelClosed),
+            None => {
+                let _ = self.shed.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(?waited, queued, "Query shed, no slot available");
+                Err(Error::Overloaded { waited, queued })
+            }
+        }
+    }
+
+    fn stats(&self) -> QueueStats {
+        QueueStats {
+            limit:     self.limit,
+            in_flight: self.limit - self.permits.available_permits(),
+            waiting:   self.waiting.load(Ordering::Relaxed),
+            admitted:  self.admitted.load(Ordering::Relaxed),
+            shed:      self.shed.load(Ordering::Relaxed),
+            wait_time: Duration::from_nanos(self.wait_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+struct WaitGuard<'a>(&'a AtomicUsize);
+
+impl Drop for WaitGuard<'_> {
+    fn drop(&mut self) { let _ = self.0.fetch_sub(1, Ordering::Relaxed); }
+}
+
+/// A token bucket holding up to one second of the configured rate.
+///
+/// Reservations larger than the available tokens put the bucket into debt, delaying later
+/// reservations, so batches larger than the rate are still sent, at the configured average.
+#[derive(Debug)]
+struct RateLimiter {
+    limit:  InsertRateLimit,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(limit: InsertRateLimit) -> Self {
+        Self { limit, bucket: Mutex::new((limit.per_second(), Instant::now())) }
+    }
+
+    /// Take `amount` tokens, returning how long to wait before sending.
+    #[expect(clippy::cast_precision_loss)]
+    fn reserve(&self, amount: u64) -> Duration {
+        let rate = self.limit.per_second();
+        let mut bucket = self.bucket.lock();
+        let (tokens, updated) = &mut *bucket;
+        let now = Instant::now();
+        *tokens = (*tokens + now.duration_since(*updated).as_secs_f64() * rate).min(rate);
+        *updated = now;
+        *tokens -= amount as f64;
+        if *tokens >= 0.0 { Duration::ZERO } else { Duration::from_secs_f64(-*tokens / rate) }
+    }
+}
+
+impl InsertRateLimit {
+    #[expect(clippy::cast_precision_loss)]
+    fn per_second(self) -> f64 {
+        match self {
+            InsertRateLimit::RowsPerSecond(n) | InsertRateLimit::BytesPerSecond(n) => {
+                n.max(1) as f64
+            }
+        }
+    }
+
+    fn amount<D: DataSize>(self, data: &D) -> u64 {
+        match self {
+            InsertRateLimit::RowsPerSecond(_) => data.data_rows() as u64,
+            InsertRateLimit::BytesPerSecond(_) => data.data_size() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_reserve() {
+        let limiter = RateLimiter::new(InsertRateLimit::RowsPerSecond(100));
+        // A full second of tokens is available up front
+        assert_eq!(limiter.reserve(100), Duration::ZERO);
+        // Then reservations wait for the bucket to refill
+        let wait = limiter.reserve(50);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+        let wait = limiter.reserve(100);
+        assert!(wait > Duration::from_millis(1400) && wait <= Duration::from_millis(1500));
+    }
+
+    #[tokio::test]
+    async fn test_throttle_query_permits() {
+        let throttle = Throttle::new(Some(1), None, None);
+        let permit = throttle.acquire_query().await.unwrap();
+        assert!(permit.is_some());
+
+        // The second query waits until the first permit is released
+        let throttle_clone = throttle.clone();
+        let waiting =
+            tokio::time::timeout(Duration::from_millis(50), throttle_clone.acquire_query());
+        assert!(waiting.await.is_err());
+        drop(permit);
+        assert!(throttle.acquire_query().await.unwrap().is_some());
+
+        // Unlimited throttles never hand out permits
+        assert!(Throttle::default().acquire_query().await.unwrap().is_none());
+        assert_eq!(Throttle::default().queue_stats(), None);
+    }
+
+    #[tokio::test]
+    async fn test_query_queue_sheds() {
+        let queue = QueryQueue::new(Duration::from_millis(100)).with_max_depth(1);
+        let throttle = Throttle::new(Some(1), Some(queue), None);
+        let permit = throttle.acquire_query().await.unwrap();
+
+        // Queued queries are shed once the deadline passes
+        let error = throttle.acquire_query().await.unwrap_err();
+        let Error::Overloaded { waited, queued } = error else {
+            panic!("unexpected error {error}")
+        };
+        assert!(waited >= Duration::from_millis(100));
+        assert_eq!(queued, 0);
+
+        // Queries arriving at a full queue are shed without waiting
+        let waiter = tokio::spawn({
+            let throttle = throttle.clone();
+            async move { throttle.acquire_query().await.map(|permit| permit.is_some()) }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(throttle.queue_stats().unwrap().waiting, 1);
+        let error = throttle.acquire_query().await.unwrap_err();
+        assert!(matches!(error, Error::Overloaded { queued: 1, .. }));
+
+        // A slot freed before the deadline admits the queued query
+        drop(permit);
+        assert!(waiter.await.unwrap().unwrap());
+
+        let stats = throttle.queue_stats().unwrap();
+        assert_eq!((stats.limit, stats.in_flight, stats.waiting), (1, 0, 0));
+        assert_eq!((stats.admitted, stats.shed), (2, 2));
+        assert!(stats.wait_time >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,359 @@
+//! Host allow-lists and TLS public key pinning.
+//!
+//! Both are opt-in via [`super::builder::ClientBuilder::with_allowed_hosts`] and
+//! [`super::builder::ClientBuilder::with_pinned_spki`]. The allow-list is checked whenever a
+//! destination is resolved, so a misconfigured endpoint or DNS record pointing somewhere
+//! unexpected fails before a socket is opened. Pins are checked after the TLS handshake, in
+//! addition to the usual certificate verification.
+use std::net::{IpAddr, SocketAddr};
+
+use crate::{Error, Result};
+
+/// A single parsed allow-list entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AllowedHost {
+    /// An exact host name, lowercased and without a trailing dot
+    Name(String),
+    /// Any subdomain of the suffix, from an entry like `*.example.com`
+    Wildcard(String),
+    /// An IP address or CIDR network
+    Network(IpAddr, u8),
+}
+
+impl AllowedHost {
+    fn parse(entry: &str) -> Result<Self> {
+        let invalid = || Error::Configuration(format!("Invalid allowed host entry: {entry:?}"));
+        let entry = entry.trim();
+        if let Some((ip, prefix)) = entry.split_once('/') {
+            let ip: IpAddr = ip.parse().map_err(|_| invalid())?;
+            let prefix: u8 = prefix.parse().map_err(|_| invalid())?;
+            let max = if ip.is_ipv4() { 32 } else { 128 };
+            return if prefix <= max { Ok(Self::Network(ip, prefix)) } else { Err(invalid()) };
+        }
+        if let Ok(ip) = entry.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            return Ok(Self::Network(ip, if ip.is_ipv4() { 32 } else { 128 }));
+        }
+        let name = normalize_host(entry);
+        if let Some(suffix) = name.strip_prefix("*.") {
+            return if suffix.is_empty() || suffix.contains('*') {
+                Err(invalid())
+            } else {
+                Ok(Self::Wildcard(format!(".{suffix}")))
+            };
+        }
+        if name.is_empty() || name.contains(['*', '/', ' ']) {
+            return Err(invalid());
+        }
+        Ok(Self::Name(name))
+    }
+
+    fn matches_name(&self, host: &str) -> bool {
+        match self {
+            Self::Name(name) => name == host,
+            Self::Wildcard(suffix) => host.ends_with(suffix.as_str()),
+            Self::Network(..) => false,
+        }
+    }
+
+    fn matches_ip(&self, ip: IpAddr) -> bool {
+        let Self::Network(network, prefix) = self else {
+            return false;
+        };
+        // Compare IPv4-mapped IPv6 addresses as IPv4
+        match (*network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(*prefix)).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(*prefix)).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn normalize_host(host: &str) -> String { host.trim_end_matches('.').to_ascii_lowercase() }
+
+/// Check a destination against an allow-list of hosts.
+///
+/// Entries are exact host names, wildcards like `*.example.com` matching any subdomain, IP
+/// addresses, or CIDR networks like `10.0.0.0/8`. An empty allow-list allows every destination.
+/// Otherwise:
+/// - A named `host`, and the TLS `domain` if set, must match a name or wildcard entry, unless the
+///   allow-list only holds networks.
+/// - If the allow-list holds any networks, every resolved address must be in one of them.
+/// - If it holds none, addresses are only trusted through an allowed host name, so destinations
+///   given as socket addresses are refused.
+///
+/// # Errors
+/// Returns [`Error::Configuration`] if an entry can't be parsed, and
+/// [`Error::MalformedConnectionInformation`] if the destination isn't allowed.
+pub(crate) fn check_allowed_hosts(
+    allowed: &[String],
+    host: Option<&str>,
+    domain: Option<&str>,
+    addrs: &[SocketAddr],
+) -> Result<()> {
+    if allowed.is_empty() {
+        return Ok(());
+    }
+    let allowed =
+        allowed.iter().map(|entry| AllowedHost::parse(entry)).collect::<Result<Vec<_>>>()?;
+    let has_names = allowed.iter().any(|entry| !matches!(entry, AllowedHost::Network(..)));
+    let has_networks = allowed.iter().any(|entry| matches!(entry, AllowedHost::Network(..)));
+
+    let refuse = |what: String| {
+        Err(Error::MalformedConnectionInformation(format!("{what} is not in the allowed hosts")))
+    };
+
+    // IP literals are checked as addresses below
+    let mut name_allowed = false;
+    for name in [host, domain].into_iter().flatten() {
+        let ip = name.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>();
+        if ip.is_ok() || !has_names {
+            continue;
+        }
+        if !allowed.iter().any(|entry| entry.matches_name(&normalize_host(name))) {
+            return refuse(format!("Host {name}"));
+        }
+        name_allowed = true;
+    }
+
+    for addr in addrs {
+        let allowed = if has_networks {
+            allowed.iter().any(|entry| entry.matches_ip(addr.ip()))
+        } else {
+            name_allowed
+        };
+        if !allowed {
+            return refuse(format!("Address {}", addr.ip()));
+        }
+    }
+    Ok(())
+}
+
+/// Parse SPKI pins, the hex-encoded SHA-256 hashes of DER-encoded `SubjectPublicKeyInfo`s.
+///
+/// Pins may be separated by colons, as printed by `openssl dgst -c`, and may be prefixed with
+/// `sha256:`.
+///
+/// # Errors
+/// Returns [`Error::Configuration`] if a pin isn't a valid SHA-256 hash.
+pub(crate) fn parse_spki_pins(pins: &[String]) -> Result<Vec<[u8; 32]>> {
+    pins.iter()
+        .map(|pin| {
+            let invalid = || Error::Configuration(format!("Invalid SPKI pin: {pin:?}"));
+            let hex = pin.trim();
+            let hex = hex.strip_prefix("sha256:").unwrap_or(hex).replace(':', "");
+            if hex.len() != 64 || !hex.is_ascii() {
+                return Err(invalid());
+            }
+            let mut hash = [0_u8; 32];
+            for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+                let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+                *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+            }
+            Ok(hash)
+        })
+        .collect()
+}
+
+/// Check the server's leaf certificate against the configured SPKI pins.
+///
+/// # Errors
+/// Returns [`Error::Configuration`] if a pin is invalid, and [`Error::Network`] if the server
+/// presented no certificate or one whose public key matches none of the pins.
+#[cfg(feature = "rustls-tls")]
+pub(crate) fn verify_spki_pins(pins: &[String], certificate: Option<&[u8]>) -> Result<()> {
+    if pins.is_empty() {
+        return Ok(());
+    }
+    let pins = parse_spki_pins(pins)?;
+    let certificate = certificate
+        .ok_or_else(|| Error::Network("Server presented no certificate to pin".into()))?;
+    let spki = subject_public_key_info(certificate)
+        .ok_or_else(|| Error::Network("Server certificate could not be parsed".into()))?;
+    let hash = sha256(spki);
+    if pins.contains(&hash) {
+        Ok(())
+    } else {
+        Err(Error::Network("Server certificate public key matches no pinned SPKI hash".into()))
+    }
+}
+
+/// SHA-256 via the hash provider of the TLS backend, to avoid a separate dependency.
+#[cfg(feature = "rustls-tls")]
+fn sha256(data: &[u8]) -> [u8; 32] {
+    use tokio_rustls::rustls::crypto::aws_lc_rs::cipher_suite::TLS13_AES_128_GCM_SHA256;
+
+    let mut hash = [0_u8; 32];
+    if let Some(suite) = TLS13_AES_128_GCM_SHA256.tls13() {
+        hash.copy_from_slice(suite.common.hash_provider.hash(data).as_ref());
+    }
+    hash
+}
+
+/// Extract the DER-encoded `SubjectPublicKeyInfo` from a DER-encoded X.509 certificate.
+///
+/// Only the structure leading up to the key is walked, its validity is left to the TLS backend.
+#[cfg(feature = "rustls-tls")]
+fn subject_public_key_info(certificate: &[u8]) -> Option<&[u8]> {
+    const SEQUENCE: u8 = 0x30;
+    const VERSION: u8 = 0xa0;
+
+    let (tag, certificate, _) = der_element(certificate)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    let (tag, tbs, _) = der_element(certificate)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    let mut rest = tbs;
+    if rest.first() == Some(&VERSION) {
+        rest = der_element(rest)?.2;
+    }
+    // Skip the serial number, signature algorithm, issuer, validity, and subject
+    for _ in 0..5 {
+        rest = der_element(rest)?.2;
+    }
+    let (tag, _, after) = der_element(rest)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    Some(&rest[..rest.len() - after.len()])
+}
+
+/// Split a DER element into its tag, its content, and the input following it.
+#[cfg(feature = "rustls-tls")]
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (usize::from(first), rest)
+    } else {
+        let bytes = usize::from(first & 0x7f);
+        if bytes == 0 || bytes > 4 || rest.len() < bytes {
+            return None;
+        }
+        let (len, rest) = rest.split_at(bytes);
+        (len.iter().fold(0_usize, |len, &byte| (len << 8) | usize::from(byte)), rest)
+    };
+    (rest.len() >= len).then(|| (tag, &rest[..len], &rest[len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(ip: &str) -> SocketAddr { SocketAddr::new(ip.parse().unwrap(), 9000) }
+
+    fn allowed(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_allowed_hosts_empty_allows_all() {
+        assert!(check_allowed_hosts(&[], Some("evil.com"), None, &[addr("1.2.3.4")]).is_ok());
+    }
+
+    #[test]
+    fn test_allowed_hosts_names() {
+        let list = allowed(&["ch.example.com", "*.prod.example.com"]);
+        let addrs = [addr("10.0.0.1")];
+        assert!(check_allowed_hosts(&list, Some("CH.example.com."), None, &addrs).is_ok());
+        assert!(check_allowed_hosts(&list, Some("a.b.prod.example.com"), None, &addrs).is_ok());
+        assert!(check_allowed_hosts(&list, Some("prod.example.com"), None, &addrs).is_err());
+        assert!(check_allowed_hosts(&list, Some("other.com"), None, &addrs).is_err());
+        // The TLS domain must be allowed as well
+        let domain = Some("other.com");
+        assert!(check_allowed_hosts(&list, Some("ch.example.com"), domain, &addrs).is_err());
+        // Without networks, bare addresses can't be trusted
+        assert!(check_allowed_hosts(&list, None, None, &addrs).is_err());
+        assert!(check_allowed_hosts(&list, Some("10.0.0.1"), None, &addrs).is_err());
+    }
+
+    #[test]
+    fn test_allowed_hosts_networks() {
+        let list = allowed(&["10.0.0.0/8", "fd00::/8", "192.168.1.5"]);
+        let host = Some("anything.internal");
+        assert!(check_allowed_hosts(&list, host, None, &[addr("10.1.2.3")]).is_ok());
+        assert!(check_allowed_hosts(&list, None, None, &[addr("192.168.1.5")]).is_ok());
+        assert!(check_allowed_hosts(&list, None, None, &[addr("fd12::1")]).is_ok());
+        assert!(check_allowed_hosts(&list, None, None, &[addr("::ffff:10.0.0.1")]).is_ok());
+        // DNS pointing outside the allowed networks is refused
+        let addrs = [addr("10.0.0.1"), addr("8.8.8.8")];
+        assert!(check_allowed_hosts(&list, host, None, &addrs).is_err());
+        assert!(check_allowed_hosts(&list, None, None, &[addr("192.168.1.6")]).is_err());
+    }
+
+    #[test]
+    fn test_allowed_hosts_names_and_networks() {
+        let list = allowed(&["ch.example.com", "10.0.0.0/24"]);
+        let host = Some("ch.example.com");
+        assert!(check_allowed_hosts(&list, host, None, &[addr("10.0.0.9")]).is_ok());
+        assert!(check_allowed_hosts(&list, host, None, &[addr("10.0.1.9")]).is_err());
+        assert!(check_allowed_hosts(&list, Some("x.com"), None, &[addr("10.0.0.9")]).is_err());
+    }
+
+    #[test]
+    fn test_allowed_hosts_invalid_entries() {
+        for entry in ["", "*.", "a.*.com", "10.0.0.0/33", "not an ip/8", "a/b"] {
+            let result = check_allowed_hosts(&allowed(&[entry]), None, None, &[]);
+            assert!(matches!(result, Err(Error::Configuration(_))), "{entry:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_spki_pins() {
+        let hex = "ab".repeat(32);
+        let colons = vec!["AB"; 32].join(":");
+        let pins = parse_spki_pins(&[hex.clone(), format!("sha256:{colons}")]).unwrap();
+        assert_eq!(pins, vec![[0xab; 32], [0xab; 32]]);
+        assert!(parse_spki_pins(&["ab".repeat(31)]).is_err());
+        assert!(parse_spki_pins(&["zz".repeat(32)]).is_err());
+        assert!(parse_spki_pins(&["é".repeat(32)]).is_err());
+    }
+
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn test_subject_public_key_info() {
+        // Certificate { tbs { [0] version, serial, sig alg, issuer, validity, subject, spki } }
+        let spki = [0x30, 0x03, 0x02, 0x01, 0x07];
+        let mut tbs = vec![0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01, 0x01];
+        for _ in 0..4 {
+            tbs.extend([0x30, 0x00]);
+        }
+        tbs.extend(spki);
+        let mut certificate = vec![0x30, 0x81, u8::try_from(tbs.len() + 2).unwrap()];
+        certificate.extend([0x30, u8::try_from(tbs.len()).unwrap()]);
+        certificate.extend(&tbs);
+        assert_eq!(subject_public_key_info(&certificate), Some(&spki[..]));
+
+        // Truncated input
+        assert_eq!(subject_public_key_info(&certificate[..certificate.len() - 1]), None);
+        assert_eq!(subject_public_key_info(&[0x30, 0x84, 0xff]), None);
+    }
+
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn test_verify_spki_pins() {
+        let spki = [0x30, 0x03, 0x02, 0x01, 0x07];
+        let mut tbs = vec![0x02, 0x01, 0x01];
+        for _ in 0..4 {
+            tbs.extend([0x30, 0x00]);
+        }
+        tbs.extend(spki);
+        let mut certificate = vec![0x30, u8::try_from(tbs.len() + 2).unwrap()];
+        certificate.extend([0x30, u8::try_from(tbs.len()).unwrap()]);
+        certificate.extend(&tbs);
+
+        let pin = sha256(&spki).iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+        assert!(verify_spki_pins(&[], None).is_ok());
+        assert!(verify_spki_pins(std::slice::from_ref(&pin), Some(&certificate)).is_ok());
+        assert!(verify_spki_pins(&["00".repeat(32)], Some(&certificate)).is_err());
+        assert!(verify_spki_pins(&[pin], None).is_err());
+    }
+}
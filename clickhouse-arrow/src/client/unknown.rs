@@ -0,0 +1,241 @@
+//! Handling of query result columns with unknown types.
+//!
+//! See [`super::UnknownTypePolicy`]. The query's result is described up front and, if any column
+//! has a type that cannot be parsed, the query is wrapped so that the server leaves the column out
+//! or sends its text representation instead.
+//!
+//! Columns of the deprecated `Object('json')` type are the exception: the server sends their
+//! values as tuples whose type changes from block to block, which the client cannot read, but it
+//! can render them with `toJSONString`. Under either policy they are returned as JSON strings in
+//! Arrow `Utf8`, so that data can still be migrated off tables using the type.
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::array::{AsArray, RecordBatch};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema};
+
+use super::UnknownTypePolicy;
+use crate::query::quote_identifier;
+use crate::{Error, Result, Type};
+
+/// Field metadata key carrying the original `ClickHouse` type name of a column returned under
+/// [`UnknownTypePolicy::Raw`].
+pub const UNKNOWN_TYPE_METADATA_KEY: &str = "clickhouse.type";
+
+/// A result column whose type could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnknownColumn {
+    pub(crate) name:      String,
+    pub(crate) type_name: String,
+}
+
+impl UnknownColumn {
+    /// Whether the column has the deprecated `Object('json')` type, read as JSON strings.
+    pub(crate) fn is_legacy_object(&self) -> bool {
+        let type_name = self.type_name.replace(' ', "").to_ascii_lowercase();
+        matches!(type_name.as_str(), "object('json')" | "object(nullable('json'))")
+    }
+}
+
+/// Strip trailing semicolons and whitespace so the query can be used as a subquery.
+fn subquery(query: &str) -> &str { query.trim_end_matches(|c: char| c == ';' || c.is_whitespace()) }
+
+/// The query describing the result columns of `query`.
+pub(crate) fn describe_query(query: &str) -> String { format!("DESCRIBE ({})", subquery(query)) }
+
+/// Collect the columns listed by a `DESCRIBE` whose types cannot be parsed.
+///
+/// # Errors
+/// Returns an error if the batches are missing the `name` or `type` columns.
+pub(crate) fn unknown_columns(batches: &[RecordBatch]) -> Result<Vec<UnknownColumn>> {
+    let mut unknown = Vec::new();
+    for batch in batches {
+        let column = |name: &str| {
+            let array = batch.column_by_name(name).ok_or_else(|| {
+                Error::Protocol(format!("DESCRIBE result is missing the '{name}' column"))
+            })?;
+            Ok::<_, Error>(cast(array, &DataType::Utf8)?)
+        };
+        let (names, types) = (column("name")?, column("type")?);
+        let (names, types) = (names.as_string::<i32>(), types.as_string::<i32>());
+        for row in 0..batch.num_rows() {
+            let type_name = types.value(row);
+            if Type::from_str(type_name).is_err() {
+                unknown.push(UnknownColumn {
+                    name:      names.value(row).to_string(),
+                    type_name: type_name.to_string(),
+                });
+            }
+        }
+    }
+    Ok(unknown)
+}
+
+/// Wrap `query` so the server leaves out, or sends as text, the `unknown` columns.
+///
+/// Legacy `Object('json')` columns are sent as JSON strings under either policy.
+///
+/// # Errors
+/// Returns an error if a column name cannot be quoted as an identifier.
+pub(crate) fn rewrite_query(
+    query: &str,
+    unknown: &[UnknownColumn],
+    policy: UnknownTypePolicy,
+) -> Result<String> {
+    if policy == UnknownTypePolicy::Error {
+        return Ok(query.to_string());
+    }
+    let mut skipped = Vec::new();
+    let mut replaced = Vec::new();
+    for column in unknown {
+        let name = quote_identifier(&column.name)?;
+        if column.is_legacy_object() {
+            replaced.push(format!("toJSONString({name}) AS {name}"));
+        } else if policy == UnknownTypePolicy::Skip {
+            skipped.push(name);
+        } else {
+            replaced.push(format!("toString({name}) AS {name}"));
+        }
+    }
+    let mut clauses = Vec::with_capacity(2);
+    if !skipped.is_empty() {
+        clauses.push(format!("EXCEPT ({})", skipped.join(", ")));
+    }
+    if !replaced.is_empty() {
+        clauses.push(format!("REPLACE ({})", replaced.join(", ")));
+    }
+    Ok(format!("SELECT * {} FROM ({})", clauses.join(" "), subquery(query)))
+}
+
+/// Convert the `unknown` columns of a batch, sent as text, to `Binary` and tag them with their
+/// original type name.
+///
+/// Legacy `Object('json')` columns are converted to `Utf8` instead, and columns left out of the
+/// batch are ignored.
+///
+/// # Errors
+/// Returns an error if a column cannot be cast to `Binary` or `Utf8`.
+pub(crate) fn tag_raw_columns(
+    batch: RecordBatch,
+    unknown: &[UnknownColumn],
+) -> Result<RecordBatch> {
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(batch.num_columns());
+    for (field, array) in schema.fields().iter().zip(batch.columns()) {
+        let Some(column) = unknown.iter().find(|column| &column.name == field.name()) else {
+            fields.push(Arc::clone(field));
+            columns.push(Arc::clone(array));
+            continue;
+        };
+        let data_type =
+            if column.is_legacy_object() { DataType::Utf8 } else { DataType::Binary };
+        let array = cast(array, &data_type)?;
+        let metadata =
+            HashMap::from([(UNKNOWN_TYPE_METADATA_KEY.to_string(), column.type_name.clone())]);
+        fields.push(Arc::new(
+            Field::new(field.name(), data_type, field.is_nullable()).with_metadata(metadata),
+        ));
+        columns.push(array);
+    }
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{BinaryArray, StringArray};
+
+    use super::*;
+
+    fn unknown(name: &str, type_name: &str) -> UnknownColumn {
+        UnknownColumn { name: name.into(), type_name: type_name.into() }
+    }
+
+    #[test]
+    fn test_unknown_columns() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Binary, false),
+            Field::new("type", DataType::Binary, false),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(BinaryArray::from(vec![b"id".as_ref(), b"shape"])),
+            Arc::new(BinaryArray::from(vec![b"UInt64".as_ref(), b"FancyGeometry(3)"])),
+        ])
+        .unwrap();
+        assert_eq!(unknown_columns(&[batch]).unwrap(), vec![unknown("shape", "FancyGeometry(3)")]);
+    }
+
+    #[test]
+    fn test_rewrite_query() {
+        let columns = [unknown("a", "X"), unknown("b`c", "Y")];
+        let query = "SELECT * FROM t;\n";
+        assert_eq!(describe_query(query), "DESCRIBE (SELECT * FROM t)");
+        assert_eq!(rewrite_query(query, &columns, UnknownTypePolicy::Error).unwrap(), query);
+        assert_eq!(
+            rewrite_query(query, &columns, UnknownTypePolicy::Skip).unwrap(),
+            "SELECT * EXCEPT (`a`, `b\\`c`) FROM (SELECT * FROM t)"
+        );
+        assert_eq!(
+            rewrite_query(query, &columns[..1], UnknownTypePolicy::Raw).unwrap(),
+            "SELECT * REPLACE (toString(`a`) AS `a`) FROM (SELECT * FROM t)"
+        );
+
+        // Legacy `Object('json')` columns are read as JSON under either policy
+        let columns = [unknown("a", "X"), unknown("o", "Object('json')")];
+        assert_eq!(
+            rewrite_query(query, &columns, UnknownTypePolicy::Skip).unwrap(),
+            "SELECT * EXCEPT (`a`) REPLACE (toJSONString(`o`) AS `o`) FROM (SELECT * FROM t)"
+        );
+        assert_eq!(
+            rewrite_query(query, &columns[1..], UnknownTypePolicy::Raw).unwrap(),
+            "SELECT * REPLACE (toJSONString(`o`) AS `o`) FROM (SELECT * FROM t)"
+        );
+    }
+
+    #[test]
+    fn test_is_legacy_object() {
+        assert!(unknown("o", "Object('json')").is_legacy_object());
+        assert!(unknown("o", "Object(Nullable('json'))").is_legacy_object());
+        assert!(unknown("o", "Object( 'JSON' )").is_legacy_object());
+        assert!(!unknown("o", "Object('other')").is_legacy_object());
+        assert!(!unknown("o", "FancyGeometry(3)").is_legacy_object());
+    }
+
+    #[test]
+    fn test_tag_raw_columns() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("shape", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(StringArray::from(vec!["1"])),
+            Arc::new(StringArray::from(vec!["(1,2,3)"])),
+        ])
+        .unwrap();
+        let tagged = tag_raw_columns(batch, &[unknown("shape", "FancyGeometry(3)")]).unwrap();
+        let schema = tagged.schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+        assert!(schema.field(0).metadata().is_empty());
+        assert_eq!(schema.field(1).data_type(), &DataType::Binary);
+        assert_eq!(
+            schema.field(1).metadata().get(UNKNOWN_TYPE_METADATA_KEY).map(String::as_str),
+            Some("FancyGeometry(3)")
+        );
+        assert_eq!(tagged.column(1).as_binary::<i32>().value(0), b"(1,2,3)");
+
+        // Legacy `Object('json')` columns stay strings, columns skipped by the query are ignored
+        let schema = Arc::new(Schema::new(vec![Field::new("o", DataType::Binary, false)]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(BinaryArray::from(vec![
+            b"{\"a\":1}".as_ref(),
+        ]))])
+        .unwrap();
+        let columns = [unknown("o", "Object('json')"), unknown("shape", "FancyGeometry(3)")];
+        let tagged = tag_raw_columns(batch, &columns).unwrap();
+        assert_eq!(tagged.num_columns(), 1);
+        assert_eq!(tagged.schema().field(0).data_type(), &DataType::Utf8);
+        assert_eq!(tagged.column(0).as_string::<i32>().value(0), "{\"a\":1}");
+    }
+}
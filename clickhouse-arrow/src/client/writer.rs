@@ -39,10 +39,21 @@ pub(crate) struct Writer<W: ClickHouseWrite> {
 
 impl<W: ClickHouseWrite> Writer<W> {
     pub(super) async fn send_hello(writer: &mut W, params: ClientHello) -> Result<()> {
+        let (major, minor, _) = params.client_version.unwrap_or((
+            crate::constants::VERSION_MAJOR,
+            crate::constants::VERSION_MINOR,
+            crate::constants::VERSION_PATCH,
+        ));
         writer.write_var_uint(ClientPacketId::Hello as u64).await?;
-        writer.write_string(format!("ClickHouseArrow Rust {}", env!("CARGO_PKG_VERSION"))).await?;
-        writer.write_var_uint(crate::constants::VERSION_MAJOR).await?;
-        writer.write_var_uint(crate::constants::VERSION_MINOR).await?;
+        if let Some(name) = params.client_name {
+            writer.write_string(name).await?;
+        } else {
+            writer
+                .write_string(format!("ClickHouseArrow Rust {}", env!("CARGO_PKG_VERSION")))
+                .await?;
+        }
+        writer.write_var_uint(major).await?;
+        writer.write_var_uint(minor).await?;
         writer.write_var_uint(DBMS_TCP_PROTOCOL_VERSION).await?;
         writer.write_string(params.default_database).await?;
         writer.write_string(params.username).await?;
@@ -51,6 +62,26 @@ impl<W: ClickHouseWrite> Writer<W> {
         Ok(())
     }
 
+    /// Request a challenge to sign for SSH key authentication.
+    #[cfg(feature = "ssh")]
+    pub(super) async fn send_ssh_challenge_request(writer: &mut W) -> Result<()> {
+        writer.write_var_uint(ClientPacketId::SSHChallengeRequest as u64).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Reply to the SSH challenge with its signature.
+    #[cfg(feature = "ssh")]
+    pub(super) async fn send_ssh_challenge_response(
+        writer: &mut W,
+        signature: &[u8],
+    ) -> Result<()> {
+        writer.write_var_uint(ClientPacketId::SSHChallengeResponse as u64).await?;
+        writer.write_string(signature).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
     pub(super) async fn send_query(
         writer: &mut W,
         params: Query<'_>,
@@ -102,7 +133,7 @@ impl<W: ClickHouseWrite> Writer<W> {
             if let Some(query_params) = params.params {
                 // Encode query parameters directly (not as Settings)
                 tracing::debug!("Sending {} query parameters", query_params.len());
-                query_params.encode(writer, revision).await?;
+                query_params.encode(writer, params.query, revision).await?;
             }
             writer.write_string("").await?; // end of params
         }
@@ -112,7 +143,8 @@ impl<W: ClickHouseWrite> Writer<W> {
             .instrument(trace_span!(
                 "flush_query",
                 { ATT_QID } = %params.qid,
-                { attribute::DB_QUERY_TEXT } = params.query,
+                // Raw SQL is omitted when queries are redacted
+                { attribute::DB_QUERY_TEXT } = (!metadata.redact_queries).then_some(params.query),
             ))
             .await?;
 
@@ -129,7 +161,13 @@ impl<W: ClickHouseWrite> Writer<W> {
     ) -> Result<()> {
         writer.write_var_uint(ClientPacketId::Data as u64).await?;
         writer.write_string("").await?; // Table name
-        T::write(writer, data, qid, header, revision, metadata).await?;
+        T::write(writer, data, qid, header, revision, metadata)
+            .instrument(trace_span!(
+                "clickhouse.block.serialize",
+                db.format = T::FORMAT,
+                { ATT_QID } = %qid
+            ))
+            .await?;
         writer
             .flush()
             .instrument(trace_span!("flush_data", { ATT_QID } = %qid))
@@ -158,7 +196,13 @@ impl<W: ClickHouseWrite> Writer<W> {
     ) -> Result<()> {
         writer.write_var_uint(ClientPacketId::Data as u64).await?;
         writer.write_string("").await?; // Table name
-        T::write(writer, data, qid, header, revision, metadata).await?;
+        T::write(writer, data, qid, header, revision, metadata)
+            .instrument(trace_span!(
+                "clickhouse.block.serialize",
+                db.format = T::FORMAT,
+                { ATT_QID } = %qid
+            ))
+            .await?;
         // No flush - caller is responsible for flushing after batch complete
         Ok(())
     }
@@ -167,9 +211,10 @@ impl<W: ClickHouseWrite> Writer<W> {
         writer: &mut W,
         revision: u64,
         server_hello: &ServerHello,
+        quota_key: &str,
     ) -> Result<()> {
         if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_QUOTA_KEY {
-            writer.write_string("").await?;
+            writer.write_string(quota_key).await?;
         }
 
         // Send chunked protocol negotiation results
@@ -196,8 +241,6 @@ impl<W: ClickHouseWrite> Writer<W> {
         Ok(())
     }
 
-    // NOTE: Not used currently
-    #[expect(unused)]
     pub(super) async fn send_cancel(writer: &mut W) -> Result<()> {
         writer.write_var_uint(ClientPacketId::Cancel as u64).await?;
         writer.flush().instrument(trace_span!("flush_cancel")).await?;
@@ -0,0 +1,339 @@
+//! Client-driven fan-out of queries across the shards of a cluster.
+//!
+//! A [`ClusterClient`] holds one [`ArrowClient`] per shard and sends a query to each of them
+//! directly, without going through a `Distributed` table. Results are streamed from every shard
+//! concurrently, either as they arrive or merged by their `ORDER BY` keys.
+use std::pin::Pin;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, RecordBatch};
+use arrow::compute::{SortOptions, interleave};
+use arrow::datatypes::SchemaRef;
+use arrow::row::{RowConverter, Rows, SortField};
+use futures_util::future::try_join_all;
+use futures_util::{Stream, TryStreamExt, stream};
+
+use crate::prelude::*;
+use crate::query::ParsedQuery;
+use crate::{ArrowClient, ClickHouseResponse, ClientBuilder, Error, Result};
+
+/// Placeholder replaced with the 1-based shard number in queries sent by [`ClusterClient`].
+pub const SHARD_PLACEHOLDER: &str = "{shard}";
+
+/// The default number of rows per batch emitted by an ordered sharded query.
+pub const DEFAULT_MERGE_BATCH_SIZE: usize = 8192;
+
+type BatchStream = Pin<Box<dyn Stream<Item = Result<RecordBatch>> + Send + 'static>>;
+
+/// A set of clients, one per shard, for querying every shard of a cluster from the client.
+///
+/// Useful when no `Distributed` table exists over the shard-local tables, or to combine results
+/// in ways a `Distributed` table cannot, e.g. with [`crate::arrow::merge::AggregateMerger`].
+///
+/// # Example
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+/// use arrow::compute::SortOptions;
+///
+/// let cluster = ClusterClient::connect(
+///     Client::builder().with_username("default"),
+///     ["shard-1:9000", "shard-2:9000"],
+/// )
+/// .await?;
+/// let query = "SELECT * FROM events_local ORDER BY ts";
+/// let ordered = cluster.query_sharded_ordered(query, &[("ts", SortOptions::default())]).await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct ClusterClient {
+    shards:     Vec<ArrowClient>,
+    batch_size: usize,
+}
+
+impl ClusterClient {
+    /// Create a cluster client from one client per shard, in shard order.
+    ///
+    /// # Errors
+    /// Returns an error if `shards` is empty.
+    pub fn new(shards: Vec<ArrowClient>) -> Result<Self> {
+        if shards.is_empty() {
+            return Err(Error::Configuration(
+                "A cluster client requires at least one shard".into(),
+            ));
+        }
+        Ok(Self { shards, batch_size: DEFAULT_MERGE_BATCH_SIZE })
+    }
+
+    /// Connect to each of the `endpoints`, one per shard, using `builder` for everything else.
+    ///
+    /// # Errors
+    /// Returns an error if `endpoints` is empty or any connection fails.
+    pub async fn connect<I, E>(builder: ClientBuilder, endpoints: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<String>,
+    {
+        let clients = endpoints
+            .into_iter()
+            .map(|endpoint| builder.clone().with_endpoint(endpoint).build_arrow());
+        Self::new(try_join_all(clients).await?)
+    }
+
+    /// Set the number of rows per batch emitted by [`ClusterClient::query_sharded_ordered`].
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// The clients for each shard, in shard order.
+    pub fn shards(&self) -> &[ArrowClient] { &self.shards }
+
+    /// Send `query` to every shard and stream the results as they arrive from any shard.
+    ///
+    /// Each occurrence of [`SHARD_PLACEHOLDER`] in the query is replaced with the 1-based shard
+    /// number. The queries are dispatched concurrently and all must be accepted before results are
+    /// streamed.
+    ///
+    /// # Errors
+    /// Returns an error if any shard fails to start the query. Errors while streaming are
+    /// yielded by the returned stream.
+    pub async fn query_sharded(
+        &self,
+        query: impl Into<ParsedQuery>,
+    ) -> Result<ClickHouseResponse<RecordBatch>> {
+        let responses = self.dispatch(query.into()).await?;
+        Ok(ClickHouseResponse::new(Box::pin(stream::select_all(responses))))
+    }
+
+    /// Send `query` to every shard and merge the results, each sorted by `order_by`, into a
+    /// single stream sorted by `order_by`.
+    ///
+    /// `order_by` names the result columns and sort options matching the query's `ORDER BY`
+    /// clause. Shards whose results are not sorted accordingly produce an unsorted stream.
+    ///
+    /// # Errors
+    /// Returns an error if `order_by` is empty or any shard fails to start the query. Errors
+    /// while streaming, including missing sort columns, are yielded by the returned stream.
+    pub async fn query_sharded_ordered(
+        &self,
+        query: impl Into<ParsedQuery>,
+        order_by: &[(&str, SortOptions)],
+    ) -> Result<ClickHouseResponse<RecordBatch>> {
+        if order_by.is_empty() {
+            return Err(Error::Client("An ordered sharded query requires sort columns".into()));
+        }
+        let responses = self.dispatch(query.into()).await?;
+        let order_by =
+            order_by.iter().map(|(name, options)| ((*name).to_string(), *options)).collect();
+        let merge = KWayMerge::new(responses, order_by, self.batch_size);
+        let stream = stream::try_unfold(merge, |mut merge| async move {
+            Ok(merge.next_batch().await?.map(|batch| (batch, merge)))
+        });
+        Ok(ClickHouseResponse::new(Box::pin(stream)))
+    }
+
+    async fn dispatch(&self, query: ParsedQuery) -> Result<Vec<BatchStream>> {
+        let query = query.0;
+        let responses = self.shards.iter().enumerate().map(|(i, client)| {
+            let query = query.replace(SHARD_PLACEHOLDER, &(i + 1).to_string());
+            async move {
+                let response = client.query(query, None).await?;
+                debug!(shard = i + 1, "sharded query dispatched");
+                Ok::<_, Error>(Box::pin(response) as BatchStream)
+            }
+        });
+        try_join_all(responses).await
+    }
+}
+
+/// The current batch of one shard's stream during a k-way merge.
+struct Cursor {
+    batch:  RecordBatch,
+    rows:   Rows,
+    offset: usize,
+    /// Index of `batch` in the sources of the batch being built.
+    source: usize,
+}
+
+/// Merges shard streams, each sorted by `order_by`, into sorted batches.
+struct KWayMerge {
+    streams:    Vec<BatchStream>,
+    cursors:    Vec<Option<Cursor>>,
+    order_by:   Vec<(String, SortOptions)>,
+    converter:  Option<RowConverter>,
+    schema:     Option<SchemaRef>,
+    batch_size: usize,
+    started:    bool,
+}
+
+impl KWayMerge {
+    fn new(streams: Vec<BatchStream>, order_by: Vec<(String, SortOptions)>, size: usize) -> Self {
+        let cursors = streams.iter().map(|_| None).collect();
+        Self {
+            streams,
+            cursors,
+            order_by,
+            converter: None,
+            schema: None,
+            batch_size: size,
+            started: false,
+        }
+    }
+
+    /// Convert the sort columns of `batch` into comparable rows.
+    fn rows(&mut self, batch: &RecordBatch) -> Result<Rows> {
+        let columns = self
+            .order_by
+            .iter()
+            .map(|(name, _)| {
+                batch.column_by_name(name).map(Arc::clone).ok_or_else(|| {
+                    Error::Client(format!("Sort column '{name}' not found in sharded results"))
+                })
+            })
+            .collect::<Result<Vec<ArrayRef>>>()?;
+        if self.converter.is_none() {
+            let fields = columns
+                .iter()
+                .zip(&self.order_by)
+                .map(|(column, (_, options))| {
+                    SortField::new_with_options(column.data_type().clone(), *options)
+                })
+                .collect();
+            self.converter = Some(RowConverter::new(fields)?);
+            self.schema = Some(batch.schema());
+        }
+        let converter = self.converter.as_ref().expect("converter initialized above");
+        Ok(converter.convert_columns(&columns)?)
+    }
+
+    /// Load the next non-empty batch of shard `i`, or mark the shard exhausted.
+    async fn advance(&mut self, i: usize, source: usize) -> Result<()> {
+        self.cursors[i] = None;
+        while let Some(batch) = self.streams[i].try_next().await? {
+            if batch.num_rows() == 0 {
+                continue;
+            }
+            let rows = self.rows(&batch)?;
+            self.cursors[i] = Some(Cursor { batch, rows, offset: 0, source });
+            break;
+        }
+        Ok(())
+    }
+
+    async fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        if !self.started {
+            self.started = true;
+            for i in 0..self.streams.len() {
+                self.advance(i, 0).await?;
+            }
+        }
+
+        // Every live cursor's batch is a source of the output batch
+        let mut sources = Vec::new();
+        for cursor in self.cursors.iter_mut().flatten() {
+            cursor.source = sources.len();
+            sources.push(cursor.batch.clone());
+        }
+        if sources.is_empty() {
+            return Ok(None);
+        }
+
+        let mut indices = Vec::with_capacity(self.batch_size);
+        while indices.len() < self.batch_size {
+            let Some(next) = self
+                .cursors
+                .iter()
+                .enumerate()
+                .filter_map(|(i, cursor)| cursor.as_ref().map(|c| (i, c.rows.row(c.offset))))
+                .min_by(|(_, a), (_, b)| a.cmp(b))
+                .map(|(i, _)| i)
+            else {
+                break;
+            };
+            let cursor = self.cursors[next].as_mut().expect("cursor selected above");
+            indices.push((cursor.source, cursor.offset));
+            cursor.offset += 1;
+            if cursor.offset == cursor.batch.num_rows() {
+                self.advance(next, sources.len()).await?;
+                if let Some(cursor) = &self.cursors[next] {
+                    sources.push(cursor.batch.clone());
+                }
+            }
+        }
+
+        let schema = self.schema.clone().unwrap_or_else(|| sources[0].schema());
+        let columns = (0..schema.fields().len())
+            .map(|c| {
+                let arrays = sources.iter().map(|b| b.column(c).as_ref()).collect::<Vec<_>>();
+                interleave(&arrays, &indices)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(RecordBatch::try_new(schema, columns)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{AsArray, Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+
+    use super::*;
+
+    fn batch(ids: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("label", DataType::Utf8, false),
+        ]));
+        let labels = ids.iter().map(|id| format!("row-{id}")).collect::<Vec<_>>();
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(labels)),
+        ])
+        .unwrap()
+    }
+
+    fn shard(batches: Vec<RecordBatch>) -> BatchStream {
+        Box::pin(stream::iter(batches.into_iter().map(Ok)))
+    }
+
+    async fn merge(streams: Vec<BatchStream>, options: SortOptions, size: usize) -> Vec<i64> {
+        let mut merge = KWayMerge::new(streams, vec![("id".into(), options)], size);
+        let mut ids = Vec::new();
+        while let Some(batch) = merge.next_batch().await.unwrap() {
+            assert!(batch.num_rows() <= size);
+            let column = batch.column(0).as_primitive::<Int64Type>();
+            let labels = batch.column(1).as_string::<i32>();
+            for (id, label) in column.values().iter().zip(labels.iter().flatten()) {
+                assert_eq!(label, format!("row-{id}"));
+                ids.push(*id);
+            }
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_kway_merge() {
+        let streams = vec![
+            shard(vec![batch(vec![1, 4]), batch(vec![]), batch(vec![7, 9])]),
+            shard(vec![batch(vec![2, 3, 8])]),
+            shard(vec![]),
+            shard(vec![batch(vec![5]), batch(vec![6, 10])]),
+        ];
+        assert_eq!(merge(streams, SortOptions::default(), 3).await, (1..=10).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_kway_merge_descending() {
+        let streams =
+            vec![shard(vec![batch(vec![9, 5, 1])]), shard(vec![batch(vec![8, 2]), batch(vec![0])])];
+        let options = SortOptions { descending: true, nulls_first: false };
+        assert_eq!(merge(streams, options, 100).await, vec![9, 8, 5, 2, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_kway_merge_missing_column() {
+        let streams = vec![shard(vec![batch(vec![1])])];
+        let mut merge = KWayMerge::new(streams, vec![("nope".into(), SortOptions::default())], 8);
+        assert!(matches!(merge.next_batch().await, Err(Error::Client(_))));
+    }
+}
@@ -2,218 +2,376 @@
 //!
 //! LZ4 and ZSTD support w/ ClickHouse's custom frame format:
 //! - 16 bytes: CityHash128 checksum
-//! - 1 byte: compression method (0x82=LZ4, 0x90=ZSTD)
+//! - 1 byte: compression method (0x02=None, 0x82=LZ4, 0x90=ZSTD)
 //! - 4 bytes: compressed size (incl. 9-byte header)
 //! - 4 bytes: decompressed size
 //! - N bytes: payload
 //!
 //! Checksum covers method+sizes+payload. Matches clickhouse-rs and official C++ client.
-use std::future::Future;
+//! `CompressionMethod::None` frames carry the payload as-is, still checksummed.
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::task::{Context, Poll, ready};
 
-use futures_util::FutureExt;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, ReadBuf};
 
+use crate::FrameLimits;
 use crate::io::{ClickHouseRead, ClickHouseWrite};
 use crate::native::protocol::CompressionMethod;
 use crate::{Error, Result};
 
-/// Compress and write in ClickHouse chunk format.
-#[expect(clippy::cast_possible_truncation)]
+/// Size of the method byte and the two sizes preceding a frame's payload.
+const FRAME_HEADER_SIZE: usize = 9;
+
+/// Compress and write in ClickHouse chunk format, with the default [`FrameLimits`].
 #[cfg_attr(not(test), expect(unused))]
 pub(crate) async fn compress_data<W: ClickHouseWrite>(
     writer: &mut W,
     raw: Vec<u8>,
     compression: CompressionMethod,
 ) -> Result<()> {
-    let decompressed_size = raw.len();
-    let mut out = match compression {
-        // ZSTD with default compression level (1)
-        CompressionMethod::ZSTD => zstd::bulk::compress(&raw, 1)
-            .map_err(|e| Error::SerializeError(format!("ZSTD compress error: {e}")))?,
-        // LZ4
-        CompressionMethod::LZ4 => lz4_flex::compress(&raw),
-        // None
-        CompressionMethod::None => return Ok(()),
-    };
-
-    let mut new_out = Vec::with_capacity(out.len() + 13);
-    new_out.push(compression.byte());
-    new_out.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
-    new_out.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
-    new_out.append(&mut out);
-
-    let hash = cityhash_rs::cityhash_102_128(&new_out[..]);
-    writer.write_u64_le((hash >> 64) as u64).await?;
-    writer.write_u64_le(hash as u64).await?;
-    writer.write_all(&new_out[..]).await?;
-
-    Ok(())
+    write_frames(writer, &raw, compression, FrameLimits::default().max_frame_payload).await
 }
 
-#[expect(clippy::cast_possible_truncation)]
 pub(crate) async fn compress_data_sync<W: ClickHouseWrite>(
     writer: &mut W,
     raw: bytes::Bytes,
     compression: CompressionMethod,
+    limits: FrameLimits,
 ) -> Result<()> {
-    let decompressed_size = raw.len();
-    let mut out = match compression {
-        // ZSTD with default compression level (1)
-        CompressionMethod::ZSTD => zstd::bulk::compress(&raw, 1)
-            .map_err(|e| Error::SerializeError(format!("ZSTD compress error: {e}")))?,
-        // LZ4
-        CompressionMethod::LZ4 => lz4_flex::compress(&raw),
-        // None
-        CompressionMethod::None => return Ok(()),
-    };
-
-    let mut new_out = Vec::with_capacity(out.len() + 13);
-    new_out.push(compression.byte());
-    new_out.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
-    new_out.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
-    new_out.append(&mut out);
-
-    let hash = cityhash_rs::cityhash_102_128(&new_out[..]);
-    writer.write_u64_le((hash >> 64) as u64).await?;
-    writer.write_u64_le(hash as u64).await?;
-    writer.write_all(&new_out[..]).await?;
-
-    Ok(())
+    write_frames(writer, &raw, compression, limits.max_frame_payload).await
 }
 
 /// Compress from pooled buffer – reduces malloc churn for high-throughput inserts.
-#[expect(clippy::cast_possible_truncation)]
 pub(crate) async fn compress_data_pooled<W: ClickHouseWrite>(
     writer: &mut W,
     raw: crate::simd::PooledBuffer,
     compression: CompressionMethod,
+    limits: FrameLimits,
 ) -> Result<()> {
-    let decompressed_size = raw.len();
-    let mut out = match compression {
-        // ZSTD with default compression level (1)
-        CompressionMethod::ZSTD => zstd::bulk::compress(&raw, 1)
-            .map_err(|e| Error::SerializeError(format!("ZSTD compress error: {e}")))?,
-        // LZ4
-        CompressionMethod::LZ4 => lz4_flex::compress(&raw),
-        // None
-        CompressionMethod::None => return Ok(()),
-    };
+    write_frames(writer, &raw, compression, limits.max_frame_payload).await
+}
+
+/// Write `raw` as frames of at most `max_payload` bytes each, so blocks aren't limited by the
+/// frame's u32 sizes. The server reassembles them. Each frame is written as soon as it is
+/// built, so only one is held in memory at a time.
+async fn write_frames<W: ClickHouseWrite>(
+    writer: &mut W,
+    raw: &[u8],
+    compression: CompressionMethod,
+    max_payload: usize,
+) -> Result<()> {
+    let max_payload = max_payload.max(1);
+    let mut start = 0;
+    // An empty block is still written as a single, empty frame
+    loop {
+        let end = raw.len().min(start + max_payload);
+        write_frame(writer, &encode_frame(&raw[start..end], compression)?).await?;
+        start = end;
+        if start >= raw.len() {
+            return Ok(());
+        }
+    }
+}
 
-    // Drop the input buffer early to return it to the pool
-    drop(raw);
+/// Build a frame, without its checksum, holding `raw` compressed with `compression`.
+///
+/// The payload is compressed directly into the frame, so LZ4 and uncompressed frames are built
+/// without intermediate copies.
+fn encode_frame(raw: &[u8], compression: CompressionMethod) -> Result<Vec<u8>> {
+    let mut frame = match compression {
+        CompressionMethod::None => Vec::with_capacity(FRAME_HEADER_SIZE + raw.len()),
+        CompressionMethod::LZ4 => Vec::with_capacity(
+            FRAME_HEADER_SIZE + lz4_flex::block::get_maximum_output_size(raw.len()),
+        ),
+        // ZSTD's output size is unknown until compressed
+        CompressionMethod::ZSTD => Vec::new(),
+    };
+    frame.push(compression.byte());
+    // Sizes, filled in once the payload is written
+    frame.extend_from_slice(&[0; 8]);
+    match compression {
+        // ZSTD with default compression level (1)
+        CompressionMethod::ZSTD => frame.extend_from_slice(
+            &zstd::bulk::compress(raw, 1)
+                .map_err(|e| Error::SerializeError(format!("ZSTD compress error: {e}")))?,
+        ),
+        CompressionMethod::LZ4 => {
+            let max_size = lz4_flex::block::get_maximum_output_size(raw.len());
+            frame.resize(FRAME_HEADER_SIZE + max_size, 0);
+            let written = lz4_flex::block::compress_into(raw, &mut frame[FRAME_HEADER_SIZE..])
+                .map_err(|e| Error::SerializeError(format!("LZ4 compress error: {e}")))?;
+            frame.truncate(FRAME_HEADER_SIZE + written);
+        }
+        CompressionMethod::None => frame.extend_from_slice(raw),
+    }
 
-    let mut new_out = Vec::with_capacity(out.len() + 13);
-    new_out.push(compression.byte());
-    new_out.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
-    new_out.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
-    new_out.append(&mut out);
+    let frame_size = |size: usize| {
+        u32::try_from(size).map_err(|_| {
+            Error::SerializeError(format!("Chunk of {size} bytes exceeds the frame size limit"))
+        })
+    };
+    let compressed_size = frame_size(frame.len())?;
+    let decompressed_size = frame_size(raw.len())?;
+    frame[1..5].copy_from_slice(&compressed_size.to_le_bytes());
+    frame[5..9].copy_from_slice(&decompressed_size.to_le_bytes());
+    Ok(frame)
+}
 
-    let hash = cityhash_rs::cityhash_102_128(&new_out[..]);
+/// Write a frame preceded by its checksum.
+#[expect(clippy::cast_possible_truncation)]
+async fn write_frame<W: ClickHouseWrite>(writer: &mut W, frame: &[u8]) -> Result<()> {
+    let hash = cityhash_rs::cityhash_102_128(frame);
     writer.write_u64_le((hash >> 64) as u64).await?;
     writer.write_u64_le(hash as u64).await?;
-    writer.write_all(&new_out[..]).await?;
-
+    writer.write_all(frame).await?;
     Ok(())
 }
 
-/// Read and decompress a single chunk. Validates CityHash128 checksum.
+/// Read and decompress a single chunk, with the default [`FrameLimits`]. Validates CityHash128
+/// checksum.
 pub(crate) async fn decompress_data_async(
     reader: &mut impl ClickHouseRead,
     compression: CompressionMethod,
 ) -> Result<Vec<u8>> {
-    // Read checksum (16 bytes)
-    let checksum_high = reader
-        .read_u64_le()
-        .await
-        .map_err(|e| Error::Protocol(format!("Failed to read checksum high: {e}")))?;
-    let checksum_low = reader
-        .read_u64_le()
-        .await
-        .map_err(|e| Error::Protocol(format!("Failed to read checksum low: {e}")))?;
-    let checksum = (u128::from(checksum_high) << 64) | u128::from(checksum_low);
-
-    // Read compression header (9 bytes)
-    let type_byte = reader
-        .read_u8()
-        .await
-        .map_err(|e| Error::Protocol(format!("Failed to read compression type: {e}")))?;
-    if type_byte != compression.byte() {
-        return Err(Error::Protocol(format!(
-            "Unexpected compression algorithm for {compression}: {type_byte:02x}"
-        )));
-    }
-
-    let compressed_size = reader
-        .read_u32_le()
-        .await
-        .map_err(|e| Error::Protocol(format!("Failed to read compressed size: {e}")))?;
-    let decompressed_size = reader
-        .read_u32_le()
+    let mut header_bytes = [0u8; CHUNK_HEADER_SIZE];
+    let _ = reader
+        .read_exact(&mut header_bytes)
         .await
-        .map_err(|e| Error::Protocol(format!("Failed to read decompressed size: {e}")))?;
-
-    // Sanity checks
-    if compressed_size > 100_000_000 || decompressed_size > 1_000_000_000 {
-        return Err(Error::Protocol("Chunk size too large".to_string()));
-    }
+        .map_err(|e| Error::Protocol(format!("Failed to read chunk header: {e}")))?;
+    let header = ChunkHeader::parse(&header_bytes, compression, FrameLimits::default())?;
 
     // Build the complete compressed block for checksum validation
-    let mut compressed = vec![0u8; compressed_size as usize];
+    let mut frame = vec![0u8; header.compressed_size];
+    frame[..FRAME_HEADER_SIZE].copy_from_slice(&header_bytes[CHECKSUM_SIZE..]);
     let _ = reader
-        .read_exact(&mut compressed[9..])
+        .read_exact(&mut frame[FRAME_HEADER_SIZE..])
         .await
         .map_err(|e| Error::Protocol(format!("Failed to read compressed payload: {e}")))?;
-    compressed[0] = type_byte;
-    compressed[1..5].copy_from_slice(&compressed_size.to_le_bytes());
-    compressed[5..9].copy_from_slice(&decompressed_size.to_le_bytes());
-
-    // Validate checksum
-    let calc_checksum = cityhash_rs::cityhash_102_128(&compressed);
-    if calc_checksum != checksum {
-        return Err(Error::Protocol(format!(
-            "Checksum mismatch: expected {checksum:032x}, got {calc_checksum:032x}"
-        )));
+
+    let mut decompressed = Vec::new();
+    header.decompress(&frame, compression, &mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Size of the CityHash128 checksum preceding each frame.
+const CHECKSUM_SIZE: usize = 16;
+/// Size of the checksum and frame header preceding each payload.
+const CHUNK_HEADER_SIZE: usize = CHECKSUM_SIZE + FRAME_HEADER_SIZE;
+
+/// The checksum and sizes of a chunk, read from its header.
+#[derive(Debug, Clone, Copy, Default)]
+struct ChunkHeader {
+    checksum:          u128,
+    /// Size of the frame, ie the method byte, sizes, and payload.
+    compressed_size:   usize,
+    decompressed_size: usize,
+}
+
+impl ChunkHeader {
+    /// Parse the header of a chunk compressed with `compression`, checking its sizes against
+    /// `limits`.
+    fn parse(
+        header: &[u8; CHUNK_HEADER_SIZE],
+        compression: CompressionMethod,
+        limits: FrameLimits,
+    ) -> Result<Self> {
+        let mut checksum = [0u8; CHECKSUM_SIZE];
+        checksum.copy_from_slice(&header[..CHECKSUM_SIZE]);
+        // Written as two little-endian u64s, high half first
+        checksum.rotate_left(8);
+        let checksum = u128::from_le_bytes(checksum);
+
+        let type_byte = header[CHECKSUM_SIZE];
+        if type_byte != compression.byte() {
+            return Err(Error::Protocol(format!(
+                "Unexpected compression algorithm for {compression}: {type_byte:02x}"
+            )));
+        }
+
+        let size_at = |offset: usize| {
+            let mut size = [0u8; 4];
+            size.copy_from_slice(&header[offset..offset + 4]);
+            u32::from_le_bytes(size)
+        };
+        let compressed_size = size_at(CHECKSUM_SIZE + 1);
+        let decompressed_size = size_at(CHECKSUM_SIZE + 5);
+
+        // Sanity checks
+        if compressed_size > limits.max_compressed_chunk_size {
+            return Err(Error::ChunkTooLarge {
+                kind:  "Compressed",
+                size:  compressed_size.into(),
+                limit: limits.max_compressed_chunk_size.into(),
+            });
+        }
+        if decompressed_size > limits.max_decompressed_chunk_size {
+            return Err(Error::ChunkTooLarge {
+                kind:  "Decompressed",
+                size:  decompressed_size.into(),
+                limit: limits.max_decompressed_chunk_size.into(),
+            });
+        }
+        if (compressed_size as usize) < FRAME_HEADER_SIZE {
+            return Err(Error::Protocol(format!(
+                "Chunk size {compressed_size} is below header size"
+            )));
+        }
+
+        Ok(Self {
+            checksum,
+            compressed_size: compressed_size as usize,
+            decompressed_size: decompressed_size as usize,
+        })
     }
 
-    // Decompress based on compression method
-    match compression {
-        CompressionMethod::LZ4 => {
-            lz4_flex::decompress(&compressed[9..], decompressed_size as usize)
-                .map_err(|e| Error::DeserializeError(format!("LZ4 decompress error: {e}")))
+    /// Validate the checksum of `frame`, then decompress its payload into `out`, replacing its
+    /// contents. `out`'s allocation is reused when large enough.
+    fn decompress(
+        &self,
+        frame: &[u8],
+        compression: CompressionMethod,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        let calc_checksum = cityhash_rs::cityhash_102_128(frame);
+        if calc_checksum != self.checksum {
+            return Err(Error::Protocol(format!(
+                "Checksum mismatch: expected {:032x}, got {calc_checksum:032x}",
+                self.checksum
+            )));
         }
-        CompressionMethod::ZSTD => {
-            zstd::bulk::decompress(&compressed[9..], decompressed_size as usize)
-                .map_err(|e| Error::DeserializeError(format!("ZSTD decompress error: {e}")))
+
+        let payload = &frame[FRAME_HEADER_SIZE..];
+        out.clear();
+        match compression {
+            CompressionMethod::LZ4 => {
+                out.resize(self.decompressed_size, 0);
+                let written = lz4_flex::block::decompress_into(payload, out)
+                    .map_err(|e| Error::DeserializeError(format!("LZ4 decompress error: {e}")))?;
+                out.truncate(written);
+            }
+            CompressionMethod::ZSTD => {
+                out.reserve(self.decompressed_size);
+                let _ = zstd::bulk::decompress_to_buffer(payload, out)
+                    .map_err(|e| Error::DeserializeError(format!("ZSTD decompress error: {e}")))?;
+            }
+            CompressionMethod::None => out.extend_from_slice(payload),
         }
-        CompressionMethod::None => {
-            Err(Error::DeserializeError("Attempted to decompress uncompressed data".into()))
+
+        if out.len() != self.decompressed_size {
+            return Err(Error::Protocol(format!(
+                "Chunk decompressed to {} bytes, expected {}",
+                out.len(),
+                self.decompressed_size
+            )));
         }
+        Ok(())
     }
 }
 
-type BlockReadingFuture<'a, R> =
-    Pin<Box<dyn Future<Output = Result<(Vec<u8>, &'a mut R)>> + Send + Sync + 'a>>;
+/// Where a [`DecompressionReader`] is in the current chunk.
+#[derive(Debug, Clone, Copy)]
+enum ChunkState {
+    /// Reading the checksum and frame header, with `filled` bytes read.
+    Header { filled: usize },
+    /// Reading the payload into the frame buffer, with `filled` bytes of the frame read.
+    Payload { filled: usize },
+    /// Serving decompressed bytes, starting at `position`.
+    Serving { position: usize },
+    /// The inner reader ended between chunks.
+    Done,
+}
 
 /// Async reader that decompresses ClickHouse blocks on-the-fly.
+///
+/// A state machine reading chunks straight from the inner reader. The frame and decompressed
+/// buffers are reused across chunks, so reading a result of many small blocks doesn't allocate
+/// per chunk. Each state records how far it got, so a cancelled read resumes where it left off.
 pub(crate) struct DecompressionReader<'a, R: ClickHouseRead + 'static> {
-    mode:                 CompressionMethod,
-    inner:                Option<&'a mut R>,
-    decompressed:         Vec<u8>,
-    position:             usize,
-    block_reading_future: Option<BlockReadingFuture<'a, R>>,
+    mode:         CompressionMethod,
+    limits:       FrameLimits,
+    inner:        &'a mut R,
+    state:        ChunkState,
+    header_bytes: [u8; CHUNK_HEADER_SIZE],
+    /// Header of the chunk being read.
+    header:       ChunkHeader,
+    frame:        Vec<u8>,
+    decompressed: Vec<u8>,
 }
 
 impl<'a, R: ClickHouseRead> DecompressionReader<'a, R> {
     /// Create decompressor. Reads first chunk immediately.
-    pub(crate) async fn new(mode: CompressionMethod, inner: &'a mut R) -> Result<Self> {
+    pub(crate) async fn new(
+        mode: CompressionMethod,
+        limits: FrameLimits,
+        inner: &'a mut R,
+    ) -> Result<Self> {
+        let mut reader = Self {
+            mode,
+            limits,
+            inner,
+            state: ChunkState::Header { filled: 0 },
+            header_bytes: [0; CHUNK_HEADER_SIZE],
+            header: ChunkHeader::default(),
+            frame: Vec::new(),
+            decompressed: Vec::new(),
+        };
         // Decompress intial block
-        let decompressed = decompress_data_async(inner, mode).await.inspect_err(|error| {
+        std::future::poll_fn(|cx| reader.poll_chunk(cx)).await.inspect_err(|error| {
             tracing::error!(?error, "Error decompressing data");
         })?;
+        Ok(reader)
+    }
 
-        Ok(Self { mode, inner: Some(inner), decompressed, position: 0, block_reading_future: None })
+    /// Advance through the current chunk until decompressed bytes are available to serve, or
+    /// the inner reader ends between chunks.
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            match self.state {
+                ChunkState::Serving { position } if position < self.decompressed.len() => {
+                    return Poll::Ready(Ok(()));
+                }
+                ChunkState::Serving { .. } => self.state = ChunkState::Header { filled: 0 },
+                ChunkState::Done => return Poll::Ready(Ok(())),
+                ChunkState::Header { filled } => {
+                    let mut buf = ReadBuf::new(&mut self.header_bytes[filled..]);
+                    ready!(Pin::new(&mut *self.inner).poll_read(cx, &mut buf))?;
+                    let read = buf.filled().len();
+                    let filled = filled + read;
+                    self.state = if read == 0 && filled == 0 {
+                        ChunkState::Done
+                    } else if read == 0 {
+                        return Poll::Ready(Err(Error::Protocol(
+                            "Failed to read chunk header: unexpected end of stream".into(),
+                        )));
+                    } else if filled < CHUNK_HEADER_SIZE {
+                        ChunkState::Header { filled }
+                    } else {
+                        self.header =
+                            ChunkHeader::parse(&self.header_bytes, self.mode, self.limits)?;
+                        self.frame.clear();
+                        self.frame.resize(self.header.compressed_size, 0);
+                        self.frame[..FRAME_HEADER_SIZE]
+                            .copy_from_slice(&self.header_bytes[CHECKSUM_SIZE..]);
+                        ChunkState::Payload { filled: FRAME_HEADER_SIZE }
+                    };
+                }
+                ChunkState::Payload { filled } if filled == self.header.compressed_size => {
+                    self.header.decompress(&self.frame, self.mode, &mut self.decompressed)?;
+                    self.state = ChunkState::Serving { position: 0 };
+                }
+                ChunkState::Payload { filled } => {
+                    let mut buf = ReadBuf::new(&mut self.frame[filled..]);
+                    ready!(Pin::new(&mut *self.inner).poll_read(cx, &mut buf))?;
+                    let read = buf.filled().len();
+                    if read == 0 {
+                        return Poll::Ready(Err(Error::Protocol(
+                            "Failed to read compressed payload: unexpected end of stream".into(),
+                        )));
+                    }
+                    self.state = ChunkState::Payload { filled: filled + read };
+                }
+            }
+        }
     }
 }
 
@@ -227,50 +385,15 @@ impl<R: ClickHouseRead> AsyncRead for DecompressionReader<'_, R> {
             return Poll::Ready(Ok(()));
         }
 
-        // Check if we have a pending operation to complete first
-        if let Some(block_reading_future) = self.block_reading_future.as_mut() {
-            match block_reading_future.poll_unpin(cx) {
-                Poll::Pending => return Poll::Pending,
-                Poll::Ready(Ok((value, inner))) => {
-                    drop(self.block_reading_future.take());
-                    self.decompressed = value;
-                    self.position = 0;
-                    self.inner = Some(inner);
-                    // Fall through to serve data or potentially read more
-                }
-                Poll::Ready(Err(e)) => {
-                    drop(self.block_reading_future.take());
-                    return Poll::Ready(Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        e,
-                    )));
-                }
-            }
-        }
-
-        // If we have available data, serve what we can
-        let available = self.decompressed.len() - self.position;
-        if available > 0 {
-            let to_serve = available.min(buf.remaining());
-            buf.put_slice(&self.decompressed[self.position..self.position + to_serve]);
-            self.position += to_serve;
-            return Poll::Ready(Ok(()));
-        }
+        ready!(self.poll_chunk(cx))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
 
-        // We have no data available in our buffer
-        // Try to read the next chunk if we still have an inner reader
-        if let Some(inner) = self.inner.take() {
-            let mode = self.mode;
-            self.block_reading_future = Some(Box::pin(async move {
-                let value = decompress_data_async(inner, mode).await?;
-                Ok((value, inner))
-            }));
-            // Immediately try to poll the future we just created
-            return self.poll_read(cx, buf);
+        // Serve what we can, an empty read signals the end of the inner reader
+        if let ChunkState::Serving { position } = self.state {
+            let to_serve = (self.decompressed.len() - position).min(buf.remaining());
+            buf.put_slice(&self.decompressed[position..position + to_serve]);
+            self.state = ChunkState::Serving { position: position + to_serve };
         }
-
-        // No inner reader left AND no data in buffer - this is true EOF
-        // Only return EOF if we've actually exhausted all data sources
         Poll::Ready(Ok(()))
     }
 }
@@ -321,12 +444,22 @@ mod tests {
         let mut buffer = Vec::new();
 
         compress_data(&mut buffer, data.clone(), CompressionMethod::None).await.unwrap();
-        assert!(buffer.is_empty());
+        // 16 checksum + 9 header + the payload as-is
+        assert_eq!(buffer.len(), 25 + data.len());
+        assert_eq!(buffer[16], 0x02);
+        assert_eq!(&buffer[25..], &data[..]);
 
-        // For None compression, the data should be in the same chunk format
+        let mut reader = Cursor::new(buffer.clone());
+        let decompressed =
+            decompress_data_async(&mut reader, CompressionMethod::None).await.unwrap();
+        assert_eq!(decompressed, data);
+
+        // The checksum is verified for uncompressed frames too
+        let last = buffer.len() - 1;
+        buffer[last] ^= 0xff;
         let mut reader = Cursor::new(buffer);
-        let decompressed = decompress_data_async(&mut reader, CompressionMethod::None).await;
-        assert!(decompressed.is_err());
+        let result = decompress_data_async(&mut reader, CompressionMethod::None).await;
+        assert!(matches!(result, Err(Error::Protocol(_))));
     }
 
     #[tokio::test]
@@ -371,7 +504,9 @@ mod tests {
         // Create decompression reader
         let mut reader = Cursor::new(buffer);
         let mut decompression_reader =
-            DecompressionReader::new(CompressionMethod::LZ4, &mut reader).await.unwrap();
+            DecompressionReader::new(CompressionMethod::LZ4, FrameLimits::default(), &mut reader)
+                .await
+                .unwrap();
 
         // Read exactly the amount of data we expect (like real ClickHouse usage)
         let mut result = vec![0u8; expected_len];
@@ -380,11 +515,131 @@ mod tests {
         assert_eq!(result, data);
     }
 
+    /// Reads one byte at a time, returning `Pending` before every byte.
+    struct Trickle {
+        inner:   Cursor<Vec<u8>>,
+        pending: bool,
+    }
+
+    impl AsyncRead for Trickle {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let mut byte = [0u8; 1];
+            let mut one = ReadBuf::new(&mut byte);
+            ready!(Pin::new(&mut self.inner).poll_read(cx, &mut one))?;
+            buf.put_slice(one.filled());
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompression_reader_many_chunks() {
+        for compression in
+            [CompressionMethod::None, CompressionMethod::LZ4, CompressionMethod::ZSTD]
+        {
+            let chunks = (0..50_u8).map(|i| vec![i; usize::from(i) * 3 + 1]).collect::<Vec<_>>();
+            let mut buffer = Vec::new();
+            for chunk in &chunks {
+                compress_data(&mut buffer, chunk.clone(), compression).await.unwrap();
+            }
+
+            // Partial reads and pending polls resume mid-header and mid-payload
+            let mut reader = Trickle { inner: Cursor::new(buffer), pending: false };
+            let mut decompression_reader =
+                DecompressionReader::new(compression, FrameLimits::default(), &mut reader)
+                    .await
+                    .unwrap();
+            let mut result = Vec::new();
+            let _ = decompression_reader.read_to_end(&mut result).await.unwrap();
+            assert_eq!(result, chunks.concat(), "Chunked read failed for {compression:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompression_reader_truncated() {
+        let mut buffer = Vec::new();
+        compress_data(&mut buffer, b"first chunk".to_vec(), CompressionMethod::LZ4).await.unwrap();
+        compress_data(&mut buffer, b"second chunk".to_vec(), CompressionMethod::LZ4)
+            .await
+            .unwrap();
+        buffer.truncate(buffer.len() - 3);
+
+        let mut reader = Cursor::new(buffer);
+        let mut decompression_reader =
+            DecompressionReader::new(CompressionMethod::LZ4, FrameLimits::default(), &mut reader)
+                .await
+                .unwrap();
+        let mut result = Vec::new();
+        let error = decompression_reader.read_to_end(&mut result).await.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(result, b"first chunk");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_block_split_across_frames() {
+        let data = (0..1000_u32).flat_map(u32::to_le_bytes).collect::<Vec<_>>();
+        for compression in
+            [CompressionMethod::None, CompressionMethod::LZ4, CompressionMethod::ZSTD]
+        {
+            let mut buffer = Vec::new();
+            write_frames(&mut buffer, &data, compression, 1024).await.unwrap();
+
+            // One frame per 1024 bytes of payload
+            let mut reader = Cursor::new(buffer.clone());
+            let mut payloads = Vec::new();
+            while reader.position() < buffer.len() as u64 {
+                payloads.push(decompress_data_async(&mut reader, compression).await.unwrap());
+            }
+            assert_eq!(payloads.iter().map(Vec::len).collect::<Vec<_>>(), [1024; 4]);
+
+            let mut reader = Cursor::new(buffer);
+            let mut decompression_reader =
+                DecompressionReader::new(compression, FrameLimits::default(), &mut reader)
+                    .await
+                    .unwrap();
+            let mut result = vec![0u8; data.len()];
+            let _ = decompression_reader.read_exact(&mut result).await.unwrap();
+            assert_eq!(result, data, "Split block failed for {compression:?}");
+        }
+    }
+
+    #[test]
+    fn test_chunk_too_large() {
+        let mut header = [0u8; CHUNK_HEADER_SIZE];
+        header[CHECKSUM_SIZE] = CompressionMethod::LZ4.byte();
+        header[CHECKSUM_SIZE + 1..CHECKSUM_SIZE + 5].copy_from_slice(&64_u32.to_le_bytes());
+        header[CHECKSUM_SIZE + 5..].copy_from_slice(&u32::MAX.to_le_bytes());
+        let limits = FrameLimits::default();
+        let error = ChunkHeader::parse(&header, CompressionMethod::LZ4, limits).unwrap_err();
+        assert!(matches!(error, Error::ChunkTooLarge { kind: "Decompressed", .. }));
+        assert!(error.to_string().contains("max_compress_block_size"));
+
+        // Limits are configurable
+        header[CHECKSUM_SIZE + 5..].copy_from_slice(&128_u32.to_le_bytes());
+        assert!(ChunkHeader::parse(&header, CompressionMethod::LZ4, limits).is_ok());
+        let limits = limits.with_max_decompressed_chunk_size(100);
+        let error = ChunkHeader::parse(&header, CompressionMethod::LZ4, limits).unwrap_err();
+        assert!(matches!(error, Error::ChunkTooLarge { kind: "Decompressed", limit: 100, .. }));
+        let limits = limits.with_max_compressed_chunk_size(32);
+        let error = ChunkHeader::parse(&header, CompressionMethod::LZ4, limits).unwrap_err();
+        assert!(matches!(error, Error::ChunkTooLarge { kind: "Compressed", .. }));
+    }
+
     #[tokio::test]
     async fn test_round_trip_compression() {
         let original_data = b"This is a longer piece of test data that should compress well with both LZ4 and ZSTD algorithms".to_vec();
 
-        for compression in [CompressionMethod::LZ4, CompressionMethod::ZSTD] {
+        for compression in
+            [CompressionMethod::None, CompressionMethod::LZ4, CompressionMethod::ZSTD]
+        {
             // Compress
             let mut compressed_buffer = Vec::new();
             compress_data(&mut compressed_buffer, original_data.clone(), compression)
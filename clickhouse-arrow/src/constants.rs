@@ -15,10 +15,16 @@ pub(super) const TCP_CONNECT_TIMEOUT: u64 = 30;
 pub(super) const TCP_KEEP_ALIVE_SECS: u64 = 60;
 pub(super) const TCP_KEEP_ALIVE_INTERVAL: u64 = 10;
 pub(super) const TCP_KEEP_ALIVE_RETRIES: u32 = 6;
+// Number of TLS sessions kept for resumption, shared across connections
+#[cfg(feature = "rustls-tls")]
+pub(super) const TLS_SESSION_CACHE_SIZE: usize = 256;
 
 // Maximum number of progress and profile statuses to keep in memory. New statuses evict old ones.
 pub(super) const EVENTS_CAPACITY: usize = 8;
 
+// Number of decoded blocks buffered ahead of a query's consumer before reading pauses
+pub(crate) const READ_AHEAD_DEFAULT: usize = 32;
+
 // Debugs & ENV Settings
 pub const DEBUG_ARROW_ENV_VAR: &str = "CLICKHOUSE_NATIVE_DEBUG_ARROW";
 pub const CONN_READ_BUFFER_ENV_VAR: &str = "CONNECTION_READ_BUFFER_SIZE";
@@ -26,6 +32,8 @@ pub const CONN_WRITE_BUFFER_ENV_VAR: &str = "CONNECTION_WRITE_BUFFER_SIZE";
 
 // ClickHouse default sizes
 pub(crate) const CLICKHOUSE_DEFAULT_CHUNK_ROWS: usize = 65_409;
+// Server default of `max_insert_block_size`
+pub(crate) const CLICKHOUSE_DEFAULT_MAX_INSERT_BLOCK_SIZE: usize = 1_048_449;
 // pub(crate) const CLICKHOUSE_DEFAULT_CHUNK_BYTES: usize = 523_272; // For reference
 
 // ChunkWriter/ChunkReader buffer sizes (1MB default to match typical chunk sizes)
@@ -0,0 +1,320 @@
+//! Column default metadata and DEFAULT-aware inserts.
+//!
+//! `ClickHouse` columns may be declared with a `DEFAULT`, `MATERIALIZED`, `ALIAS` or `EPHEMERAL`
+//! expression. `MATERIALIZED` and `ALIAS` columns are computed by the server and cannot be
+//! inserted into, so a batch read back with `SELECT *` or built from a table's full schema fails
+//! on insert with a server-side error that does not name the offending column.
+//!
+//! [`ArrowClient::fetch_column_defaults`] reads these declarations from `system.columns` and
+//! caches them per table on the client. [`ArrowClient::insert_table`] uses them to drop (or
+//! reject, see [`GeneratedColumnPolicy`]) computed columns before the batch is sent, and names
+//! the remaining columns in the insert so omitted `DEFAULT` columns are filled in by the server.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use clickhouse_arrow::defaults::GeneratedColumnPolicy;
+//! use clickhouse_arrow::prelude::*;
+//!
+//! // `batch` may contain the table's MATERIALIZED columns, they are dropped before sending
+//! let stream = client.insert_table("db.events", batch, GeneratedColumnPolicy::Omit, None).await?;
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{AsArray, RecordBatch};
+use arrow::compute::cast;
+use arrow::datatypes::DataType;
+use parking_lot::Mutex;
+
+use crate::explore::{split_table, system_table_filter};
+use crate::prelude::*;
+use crate::query::quote_identifier;
+
+/// How a column's default value is declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefaultKind {
+    /// `DEFAULT expr`, used when the column is omitted from an insert.
+    Default,
+    /// `MATERIALIZED expr`, always computed by the server, cannot be inserted into.
+    Materialized,
+    /// `ALIAS expr`, computed on read and never stored, cannot be inserted into.
+    Alias,
+    /// `EPHEMERAL [expr]`, may be inserted into but is never stored.
+    Ephemeral,
+}
+
+impl DefaultKind {
+    /// Parse the `default_kind` column of `system.columns`, which is empty for plain columns.
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "DEFAULT" => Some(DefaultKind::Default),
+            "MATERIALIZED" => Some(DefaultKind::Materialized),
+            "ALIAS" => Some(DefaultKind::Alias),
+            "EPHEMERAL" => Some(DefaultKind::Ephemeral),
+            _ => None,
+        }
+    }
+
+    /// Whether a column of this kind can be inserted into.
+    pub fn is_insertable(self) -> bool {
+        matches!(self, DefaultKind::Default | DefaultKind::Ephemeral)
+    }
+}
+
+/// A column declared with a default expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDefault {
+    pub name:       String,
+    pub kind:       DefaultKind,
+    /// The default expression, empty for `EPHEMERAL` columns without one.
+    pub expression: String,
+}
+
+/// The columns of a table declared with a default expression, see
+/// [`ArrowClient::fetch_column_defaults`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnDefaults {
+    columns: Vec<ColumnDefault>,
+}
+
+impl ColumnDefaults {
+    /// Create column defaults from the given columns.
+    pub fn new(columns: Vec<ColumnDefault>) -> Self { Self { columns } }
+
+    /// The columns declared with a default expression, in table order.
+    pub fn columns(&self) -> &[ColumnDefault] { &self.columns }
+
+    /// The default declaration of the named column, if any.
+    pub fn get(&self, name: &str) -> Option<&ColumnDefault> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Whether the named column can be inserted into. Columns without a default declaration,
+    /// including ones unknown to the table, are assumed insertable.
+    pub fn is_insertable(&self, name: &str) -> bool {
+        self.get(name).is_none_or(|c| c.kind.is_insertable())
+    }
+
+    /// The `MATERIALIZED` and `ALIAS` columns, which cannot be inserted into.
+    pub fn generated(&self) -> impl Iterator<Item = &ColumnDefault> {
+        self.columns.iter().filter(|c| !c.kind.is_insertable())
+    }
+}
+
+/// What [`ArrowClient::insert_table`] does with `MATERIALIZED` and `ALIAS` columns present in
+/// the batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GeneratedColumnPolicy {
+    /// Drop them from the batch before it is sent.
+    #[default]
+    Omit,
+    /// Fail before anything is sent, naming the columns.
+    Error,
+}
+
+/// Per-client cache of [`ColumnDefaults`], keyed by table name as passed by the caller.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ColumnDefaultsCache(Arc<Mutex<HashMap<String, Arc<ColumnDefaults>>>>);
+
+impl ColumnDefaultsCache {
+    pub(crate) fn get(&self, table: &str) -> Option<Arc<ColumnDefaults>> {
+        self.0.lock().get(table).cloned()
+    }
+
+    pub(crate) fn insert(&self, table: &str, defaults: Arc<ColumnDefaults>) {
+        let _ = self.0.lock().insert(table.to_string(), defaults);
+    }
+
+    pub(crate) fn invalidate(&self, table: Option<&str>) {
+        let mut cache = self.0.lock();
+        match table {
+            Some(table) => drop(cache.remove(table)),
+            None => cache.clear(),
+        }
+    }
+}
+
+/// Read the default declarations of `table`'s columns, see
+/// [`ArrowClient::fetch_column_defaults`].
+pub(crate) async fn fetch_column_defaults(
+    client: &ArrowClient,
+    table: &str,
+    qid: Option<Qid>,
+) -> Result<ColumnDefaults> {
+    let (database, name) = split_table(table);
+    let (filter, params) = system_table_filter(database.as_deref(), "table", &name);
+    let batch = client
+        .query_params(
+            format!(
+                "SELECT name, default_kind, default_expression FROM system.columns WHERE {filter} \
+                 ORDER BY position"
+            ),
+            Some(params),
+            qid,
+        )
+        .await?
+        .collect_result()
+        .await?
+        .collect_table()?;
+    if batch.num_rows() == 0 {
+        let db = database.unwrap_or_else(|| "currentDatabase()".into());
+        return Err(Error::UndefinedTables { db, tables: vec![name] });
+    }
+
+    let names = cast(batch.column(0), &DataType::Utf8)?;
+    let kinds = cast(batch.column(1), &DataType::Utf8)?;
+    let expressions = cast(batch.column(2), &DataType::Utf8)?;
+    let (names, kinds, expressions) =
+        (names.as_string::<i32>(), kinds.as_string::<i32>(), expressions.as_string::<i32>());
+    let columns = (0..batch.num_rows())
+        .filter_map(|i| {
+            let kind = DefaultKind::parse(kinds.value(i))?;
+            let (name, expression) = (names.value(i).to_string(), expressions.value(i).to_string());
+            Some(ColumnDefault { name, kind, expression })
+        })
+        .collect();
+    Ok(ColumnDefaults::new(columns))
+}
+
+/// Apply `policy` to the generated columns of `batch`, returning the batch to insert.
+pub(crate) fn prepare_insert(
+    table: &str,
+    batch: RecordBatch,
+    defaults: &ColumnDefaults,
+    policy: GeneratedColumnPolicy,
+) -> Result<RecordBatch> {
+    let schema = batch.schema();
+    let (keep, generated): (Vec<_>, Vec<_>) = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .partition(|(_, field)| defaults.is_insertable(field.name()));
+    if generated.is_empty() {
+        return Ok(batch);
+    }
+
+    let generated = generated.iter().map(|(_, f)| f.name().as_str()).collect::<Vec<_>>();
+    match policy {
+        GeneratedColumnPolicy::Omit => {
+            debug!(table, ?generated, "Omitting generated columns from insert");
+            let indices = keep.into_iter().map(|(i, _)| i).collect::<Vec<_>>();
+            Ok(batch.project(&indices)?)
+        }
+        GeneratedColumnPolicy::Error => Err(Error::Client(format!(
+            "Cannot insert into MATERIALIZED or ALIAS columns of {table}: {}",
+            generated.join(", ")
+        ))),
+    }
+}
+
+/// The insert statement for `batch`, naming its columns so omitted columns get their defaults.
+pub(crate) fn insert_statement(table: &str, batch: &RecordBatch) -> Result<String> {
+    Ok(format!("INSERT INTO {table} ({}) FORMAT Native", column_list(batch)?))
+}
+
+/// The quoted, comma separated column names of `batch`.
+pub(crate) fn column_list(batch: &RecordBatch) -> Result<String> {
+    let columns = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| quote_identifier(f.name()))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(columns.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{Field, Schema};
+
+    use super::*;
+
+    fn defaults() -> ColumnDefaults {
+        ColumnDefaults::new(vec![
+            ColumnDefault {
+                name:       "created".into(),
+                kind:       DefaultKind::Default,
+                expression: "now()".into(),
+            },
+            ColumnDefault {
+                name:       "day".into(),
+                kind:       DefaultKind::Materialized,
+                expression: "toDate(created)".into(),
+            },
+            ColumnDefault {
+                name:       "id_str".into(),
+                kind:       DefaultKind::Alias,
+                expression: "toString(id)".into(),
+            },
+        ])
+    }
+
+    fn batch(columns: &[&str]) -> RecordBatch {
+        let fields = columns.iter().map(|c| Field::new(*c, DataType::Int32, false));
+        let arrays = columns.iter().map(|_| Arc::new(Int32Array::from(vec![1, 2])) as _);
+        RecordBatch::try_new(Arc::new(Schema::new(fields.collect::<Vec<_>>())), arrays.collect())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_default_kind() {
+        assert_eq!(DefaultKind::parse(""), None);
+        assert_eq!(DefaultKind::parse("MATERIALIZED"), Some(DefaultKind::Materialized));
+        assert!(DefaultKind::Ephemeral.is_insertable());
+        assert!(!DefaultKind::Alias.is_insertable());
+
+        let defaults = defaults();
+        assert!(defaults.is_insertable("id"));
+        assert!(defaults.is_insertable("created"));
+        assert!(!defaults.is_insertable("day"));
+        assert_eq!(defaults.generated().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec![
+            "day", "id_str"
+        ]);
+    }
+
+    #[test]
+    fn test_prepare_insert() {
+        let defaults = defaults();
+        let prepared = prepare_insert(
+            "events",
+            batch(&["id", "day", "created", "id_str"]),
+            &defaults,
+            GeneratedColumnPolicy::Omit,
+        )
+        .unwrap();
+        let expected = "INSERT INTO events (`id`, `created`) FORMAT Native";
+        assert_eq!(insert_statement("events", &prepared).unwrap(), expected);
+
+        let error = prepare_insert(
+            "events",
+            batch(&["id", "day", "id_str"]),
+            &defaults,
+            GeneratedColumnPolicy::Error,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("day, id_str"));
+
+        // Batches without generated columns pass through untouched
+        let prepared =
+            prepare_insert("events", batch(&["id"]), &defaults, GeneratedColumnPolicy::Error)
+                .unwrap();
+        assert_eq!(prepared.num_columns(), 1);
+    }
+
+    #[test]
+    fn test_column_defaults_cache() {
+        let cache = ColumnDefaultsCache::default();
+        assert!(cache.get("events").is_none());
+        cache.insert("events", Arc::new(defaults()));
+        cache.insert("other", Arc::new(ColumnDefaults::default()));
+        assert_eq!(cache.get("events").unwrap().columns().len(), 3);
+        cache.invalidate(Some("events"));
+        assert!(cache.get("events").is_none());
+        assert!(cache.get("other").is_some());
+        cache.invalidate(None);
+        assert!(cache.get("other").is_none());
+    }
+}
@@ -31,6 +31,11 @@ pub enum Error {
     DuplicateField(&'static str),
     #[error("protocol error: {0}")]
     Protocol(String),
+    #[error(
+        "{kind} chunk of {size} bytes exceeds the limit of {limit} bytes, lower the \
+         `max_compress_block_size` or `max_block_size` settings to receive smaller chunks"
+    )]
+    ChunkTooLarge { kind: &'static str, size: u64, limit: u64 },
     #[error("Internal channel closed")]
     InternalChannelError,
     #[error("connection timeout: {0}")]
@@ -45,6 +50,13 @@ pub enum Error {
     SerializeError(String),
     #[error("deserialize error for column {0}: {1}")]
     DeserializeErrorWithColumn(&'static str, String),
+    #[error("{}: {source}", column_position(column, *index, *row_offset))]
+    ColumnContext {
+        column:     String,
+        index:      usize,
+        row_offset: Option<u64>,
+        source:     Box<Error>,
+    },
     #[error("connection startup error")]
     StartupError,
     #[error("Exception({0:?})")]
@@ -77,10 +89,22 @@ pub enum Error {
     UndefinedTables { db: String, tables: Vec<String> },
     #[error("Schema configuration is not valid: {0}")]
     SchemaConfig(String),
+    #[error("Schema drift detected: {0}")]
+    SchemaDrift(Box<crate::sink::SchemaDrift>),
+    #[error("Query stopped: {0}")]
+    ResultLimitExceeded(Box<crate::limits::ResultLimitExceeded>),
+    #[error("Query result out of order: {0}")]
+    UnorderedResult(Box<crate::arrow::ordering::UnorderedResult>),
+    #[error("Batch does not match the table schema: {0}")]
+    SchemaMismatch(Box<crate::arrow::conversion::SchemaMismatch>),
     #[error("DDL Statement malformed: {0}")]
     DDLMalformed(String),
     #[error("Insufficient scope for ddl queries: {0}")]
     InsufficientDDLScope(String),
+    #[error("Read-only client refused {kind} statement starting with {keyword}")]
+    ReadOnly { kind: crate::StatementKind, keyword: String },
+    #[error("Query shed after waiting {waited:?} for a free slot, {queued} queries queued ahead")]
+    Overloaded { waited: std::time::Duration, queued: usize },
     #[error("Client error: {0}")]
     Client(String),
 
@@ -111,6 +135,10 @@ pub enum Error {
     ArrowTypeMismatch { expected: String, provided: String },
     #[error("Unsupported arrow type: {0}")]
     ArrowUnsupportedType(String),
+    #[error("Block validation failed ({validator}): {message}")]
+    BlockValidation { validator: String, message: String },
+    #[error("Invalid sparse offsets at position {position} of {num_rows} rows: {reason}")]
+    SparseOffsets { position: u64, num_rows: usize, reason: &'static str },
 
     // DFE Fork: Unimplemented feature
     #[error("Unimplemented: {0}")]
@@ -130,6 +158,82 @@ impl Error {
             x => x,
         }
     }
+
+    /// Attach the name and position of the column being serialized or deserialized.
+    ///
+    /// Only errors caused by a column's data are wrapped, connection and protocol errors are
+    /// returned as is. The innermost column wins, so nested calls keep the original context.
+    #[must_use]
+    pub fn with_column(self, name: impl Into<String>, index: usize) -> Self {
+        if !self.is_data_error() {
+            return self;
+        }
+        Error::ColumnContext {
+            column: name.into(),
+            index,
+            row_offset: None,
+            source: Box::new(self),
+        }
+    }
+
+    /// Offset the row position of a [`Error::ColumnContext`] by `offset`, ie the position of the
+    /// failing block within a larger batch or result. Other errors are returned as is.
+    #[must_use]
+    pub fn with_row_offset(self, offset: u64) -> Self {
+        match self {
+            Error::ColumnContext { column, index, row_offset, source } => Error::ColumnContext {
+                column,
+                index,
+                row_offset: Some(row_offset.unwrap_or_default() + offset),
+                source,
+            },
+            x => x,
+        }
+    }
+
+    /// The name and index of the column that caused the error, if known.
+    pub fn column(&self) -> Option<(&str, usize)> {
+        match self {
+            Error::ColumnContext { column, index, .. } => Some((column, *index)),
+            _ => None,
+        }
+    }
+
+    /// The error without any column context attached by [`Error::with_column`].
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::ColumnContext { source, .. } => source.root_cause(),
+            x => x,
+        }
+    }
+
+    fn is_data_error(&self) -> bool {
+        matches!(
+            self,
+            Error::TypeParseError(_)
+                | Error::DeserializeError(_)
+                | Error::SerializeError(_)
+                | Error::UnexpectedType(_)
+                | Error::TypeConversion(_)
+                | Error::Utf8(_)
+                | Error::FromUtf8(_)
+                | Error::DateTime(_)
+                | Error::Arrow(_)
+                | Error::ArrowSerialize(_)
+                | Error::ArrowDeserialize(_)
+                | Error::ArrowTypeMismatch { .. }
+                | Error::ArrowUnsupportedType(_)
+                | Error::SparseOffsets { .. }
+        )
+    }
+}
+
+/// Describe a column position, ie `column 3 'price' (rows from 8192)`.
+fn column_position(column: &str, index: usize, row_offset: Option<u64>) -> String {
+    match row_offset {
+        Some(offset) => format!("column {index} '{column}' (rows from {offset})"),
+        None => format!("column {index} '{column}'"),
+    }
 }
 
 /// Implement `serde::ser::Error` to enable custom serialization in query parameters.
@@ -175,6 +279,24 @@ mod tests {
         assert!(matches!(err_with_col, Error::DeserializeErrorWithColumn("my_column", _)));
     }
 
+    #[test]
+    fn test_error_with_column() {
+        let err = Error::ArrowDeserialize("bad value".into()).with_column("price", 3);
+        assert_eq!(err.column(), Some(("price", 3)));
+        assert_eq!(err.to_string(), "column 3 'price': arrow deserialize error: bad value");
+        assert!(matches!(err.root_cause(), Error::ArrowDeserialize(_)));
+
+        // The innermost context is kept and row offsets accumulate
+        let err = err.with_column("outer", 0).with_row_offset(8192).with_row_offset(100);
+        assert_eq!(err.column(), Some(("price", 3)));
+        assert!(err.to_string().starts_with("column 3 'price' (rows from 8292):"));
+
+        // Connection errors are not data errors
+        let err = Error::ConnectionGone("closed").with_column("price", 3).with_row_offset(1);
+        assert!(matches!(err, Error::ConnectionGone(_)));
+        assert_eq!(err.column(), None);
+    }
+
     #[test]
     fn test_non_exhaustive_pattern() {
         // This test verifies that the #[non_exhaustive] attribute works correctly.
@@ -28,11 +28,17 @@
 //! ```
 
 use std::fmt;
+use std::sync::Arc;
 
+use arrow::compute::SortOptions;
 use arrow::record_batch::RecordBatch;
 
+use crate::arrow::explode::{MapExplodeOptions, MapExploder};
+use crate::arrow::rescale::DecimalRescale;
 use crate::limits::QueryLimits;
 use crate::query::{Qid, QueryParams};
+use crate::settings::{SettingValue, Settings};
+use crate::telemetry::ProgressHook;
 
 /// Type of EXPLAIN operation to run.
 ///
@@ -401,6 +407,10 @@ impl ExplainEstimateRow {
 /// - Result limits (memory, rows, batches)
 /// - EXPLAIN execution
 /// - Query ID
+/// - Quota key
+/// - Column projection
+/// - Exploding `Map` columns into `Struct` columns
+/// - Rescaling decimal columns
 ///
 /// # Example
 ///
@@ -422,13 +432,40 @@ impl ExplainEstimateRow {
 #[derive(Debug, Clone, Default)]
 pub struct QueryOptions {
     /// Query parameters for parameterized queries.
-    pub params:  Option<QueryParams>,
+    pub params:           Option<QueryParams>,
     /// Result limits (memory, rows, batches).
-    pub limits:  Option<QueryLimits>,
+    pub limits:           Option<QueryLimits>,
     /// EXPLAIN configuration.
-    pub explain: Option<ExplainOptions>,
+    pub explain:          Option<ExplainOptions>,
     /// Query ID for tracking and debugging.
-    pub qid:     Option<Qid>,
+    pub qid:              Option<Qid>,
+    /// Quota key for this query, overriding the connection's quota key.
+    pub quota_key:        Option<String>,
+    /// Names of the columns to decode, see [`QueryOptions::project`].
+    pub projection:       Option<Arc<[String]>>,
+    /// Explode `Map` columns into `Struct` columns, see [`QueryOptions::with_map_explode`].
+    pub explode_maps:     Option<MapExplodeOptions>,
+    /// Decimal columns converted while decoding, see [`QueryOptions::rescale_decimal`].
+    pub decimal_rescale:  Option<Arc<[(String, DecimalRescale)]>>,
+    /// User to execute the query as, see [`QueryOptions::with_user_override`].
+    pub user_override:    Option<String>,
+    /// Comment attributing the query in `system.query_log`, see [`QueryOptions::with_comment`].
+    pub comment:          Option<String>,
+    /// Decoded blocks buffered ahead of the consumer, see [`QueryOptions::with_read_ahead`].
+    pub read_ahead:       Option<usize>,
+    /// Settings for this query, see [`QueryOptions::with_settings`].
+    pub settings:         Option<Settings>,
+    /// Rows after which the query fails, see [`QueryOptions::with_max_result_rows`].
+    pub max_result_rows:  Option<u64>,
+    /// Bytes after which the query fails, see [`QueryOptions::with_max_result_bytes`].
+    pub max_result_bytes: Option<usize>,
+    /// Attach the results received so far to a result limit error, see
+    /// [`QueryOptions::with_partial_results`].
+    pub partial_results:  bool,
+    /// Sort keys the results are validated against, see [`QueryOptions::assert_sorted_by`].
+    pub sorted_by:        Option<Arc<[(String, SortOptions)]>>,
+    /// Called with the query's progress updates, see [`QueryOptions::with_progress`].
+    pub progress:         Option<ProgressHook>,
 }
 
 impl QueryOptions {
@@ -464,6 +501,203 @@ impl QueryOptions {
         self
     }
 
+    /// Set the quota key for this query.
+    ///
+    /// Overrides the connection-level quota key (see `ClientBuilder::with_quota_key`) so that
+    /// multi-tenant services can attribute server resource usage to individual end customers.
+    #[must_use]
+    pub fn with_quota_key(mut self, quota_key: impl Into<String>) -> Self {
+        self.quota_key = Some(quota_key.into());
+        self
+    }
+
+    /// Only decode the named columns of each returned block.
+    ///
+    /// Other columns are skipped over in the encoded data rather than decoded and dropped, which
+    /// saves time and memory when a query returns columns the caller does not need, e.g. from a
+    /// `SELECT *` or a view. Returned batches contain the projected columns in the order the
+    /// server sends them. Names without a matching column are ignored.
+    ///
+    /// Columns of types that must be decoded to find their end, such as `LowCardinality`, are
+    /// still decoded but then dropped. Prefer selecting only the needed columns in the query
+    /// itself when possible, as that also avoids reading and sending them.
+    #[must_use]
+    pub fn project<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.projection = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Explode `Map` columns of the result into `Struct` columns with one field per key.
+    ///
+    /// For downstream tools that cannot handle `MapArray`. Keys are collected across the batches
+    /// of the result, so later batches may have more fields than earlier ones. See
+    /// [`MapExploder`] for details.
+    #[must_use]
+    pub fn with_map_explode(mut self, options: MapExplodeOptions) -> Self {
+        self.explode_maps = Some(options);
+        self
+    }
+
+    /// Convert the decimal column `column` to the precision and scale of `rescale` while decoding.
+    ///
+    /// For consumers that need a narrower or coarser decimal than the server returns, ie
+    /// `Decimal128(18, 4)` from a `Decimal(38, 10)` aggregate, without a separate Arrow compute
+    /// pass over the results. Reducing the scale rounds half away from zero, and values that do
+    /// not fit the target precision are handled per [`DecimalRescale::overflow`]. Call once per
+    /// column, a later call for the same column replaces the earlier one.
+    ///
+    /// The query fails if the column is not a decimal. Names without a matching column are
+    /// ignored.
+    #[must_use]
+    pub fn rescale_decimal(mut self, column: impl Into<String>, rescale: DecimalRescale) -> Self {
+        let column = column.into();
+        let mut rescales = self.decimal_rescale.as_deref().unwrap_or_default().to_vec();
+        rescales.retain(|(name, _)| *name != column);
+        rescales.push((column, rescale));
+        self.decimal_rescale = Some(rescales.into());
+        self
+    }
+
+    /// Fail the query if its results are not sorted by `column` with `options`.
+    ///
+    /// For consumers relying on `ORDER BY`, ie when merging or paging through `Distributed`
+    /// tables, where unordered results would otherwise go unnoticed. Each call adds a sort key
+    /// after the earlier ones, a later call for the same column replaces its options. Rows are
+    /// compared across batches as they arrive, and the first row sorting before its predecessor
+    /// fails the stream with [`Error::UnorderedResult`](crate::Error::UnorderedResult), stopping
+    /// the query. `ClickHouse` places nulls last unless `NULLS FIRST` is given, so
+    /// `SortOptions::nulls_first` should usually be `false`.
+    ///
+    /// The query fails if a key column is missing from the results. See
+    /// [`ordering`](crate::arrow::ordering) for details.
+    #[must_use]
+    pub fn assert_sorted_by(mut self, column: impl Into<String>, options: SortOptions) -> Self {
+        let column = column.into();
+        let mut keys = self.sorted_by.as_deref().unwrap_or_default().to_vec();
+        if let Some(key) = keys.iter_mut().find(|(name, _)| *name == column) {
+            key.1 = options;
+        } else {
+            keys.push((column, options));
+        }
+        self.sorted_by = Some(keys.into());
+        self
+    }
+
+    /// Call `hook` with each [`Progress`](crate::Progress) update the server sends for the query.
+    ///
+    /// For dashboards and CLIs showing how far along a long running query is, ie from the rows
+    /// read so far against `total_rows_to_read`. Updates are deltas, sum them for running totals.
+    /// The hook runs on the connection's IO task, so it should return quickly, ie by forwarding
+    /// to a channel. See [`Client::query_with_progress`](crate::Client::query_with_progress) to
+    /// receive updates in the result stream instead.
+    #[must_use]
+    pub fn with_progress(mut self, hook: impl Into<ProgressHook>) -> Self {
+        self.progress = Some(hook.into());
+        self
+    }
+
+    /// Execute the query as another user, through `EXECUTE AS <user> <query>`.
+    ///
+    /// The query is checked against the target user's grants, default roles, row policies and
+    /// quotas rather than the connected user's, so a privileged connection can verify what
+    /// tenants of a multi-tenant application see, ie in row policy tests. The connected user
+    /// needs the `IMPERSONATE` grant on the target user, and the server must have
+    /// `access_control_improvements.allow_impersonate_user` enabled.
+    ///
+    /// Only the query itself is impersonated, an EXPLAIN configured alongside runs as the
+    /// connected user.
+    #[must_use]
+    pub fn with_user_override(mut self, user: impl Into<String>) -> Self {
+        self.user_override = Some(user.into());
+        self
+    }
+
+    /// Attach a comment to the query, recorded as `log_comment` in `system.query_log`.
+    ///
+    /// Use it to attribute queries to the job, request, or feature that issued them. The comment
+    /// is appended to any default comment configured with `ClientBuilder::with_default_comment`,
+    /// separated by `"; "`. It is sent as a setting, leaving the query text unchanged.
+    #[must_use]
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Buffer up to `blocks` decoded blocks ahead of the consumer, overriding the client's
+    /// read-ahead (see `ClientBuilder::with_read_ahead`) for this query.
+    ///
+    /// Lower values bound memory for large results consumed slowly, higher values keep a fast
+    /// consumer from waiting on the connection. A value of 0 is treated as 1.
+    #[must_use]
+    pub fn with_read_ahead(mut self, blocks: usize) -> Self {
+        self.read_ahead = Some(blocks);
+        self
+    }
+
+    /// Send `settings` with this query, on top of the client's settings.
+    ///
+    /// Settings given here take precedence over client settings of the same name, and replace
+    /// any settings set by earlier calls.
+    #[must_use]
+    pub fn with_settings(mut self, settings: impl Into<Settings>) -> Self {
+        self.settings = Some(settings.into());
+        self
+    }
+
+    /// Send a single setting with this query, on top of the client's settings.
+    #[must_use]
+    pub fn with_setting<S>(mut self, name: impl Into<String>, setting: S) -> Self
+    where
+        SettingValue: From<S>,
+    {
+        self.settings = Some(self.settings.unwrap_or_default().with_setting(name, setting));
+        self
+    }
+
+    /// Fail the query once it returns more than `rows` rows.
+    ///
+    /// Unlike a [`QueryLimits`] row limit, which quietly truncates the result, exceeding this limit
+    /// cancels the query on the server and ends the stream with
+    /// [`Error::ResultLimitExceeded`](crate::Error::ResultLimitExceeded). Use it to protect
+    /// interactive applications from accidentally unbounded queries, e.g. a `SELECT *` on a
+    /// billion row table. Batches within the limit are yielded as they arrive.
+    #[must_use]
+    pub fn with_max_result_rows(mut self, rows: u64) -> Self {
+        self.max_result_rows = Some(rows);
+        self
+    }
+
+    /// Fail the query once its batches take up more than `bytes` of memory, see
+    /// [`QueryOptions::with_max_result_rows`].
+    ///
+    /// Memory is measured with `RecordBatch::get_array_memory_size`.
+    #[must_use]
+    pub fn with_max_result_bytes(mut self, bytes: usize) -> Self {
+        self.max_result_bytes = Some(bytes);
+        self
+    }
+
+    /// Attach the results that fit within the result limits to the
+    /// [`Error::ResultLimitExceeded`](crate::Error::ResultLimitExceeded) returned when they are
+    /// exceeded.
+    ///
+    /// The received batches are kept until the query completes, which the limits bound.
+    #[must_use]
+    pub fn with_partial_results(mut self) -> Self {
+        self.partial_results = true;
+        self
+    }
+
+    /// Whether a result row or byte limit is configured.
+    #[must_use]
+    pub fn has_result_limits(&self) -> bool {
+        self.max_result_rows.is_some() || self.max_result_bytes.is_some()
+    }
+
     /// Check if any options are set.
     #[must_use]
     pub fn has_options(&self) -> bool {
@@ -471,6 +705,16 @@ impl QueryOptions {
             || self.limits.is_some()
             || self.explain.is_some()
             || self.qid.is_some()
+            || self.quota_key.is_some()
+            || self.projection.is_some()
+            || self.explode_maps.is_some()
+            || self.decimal_rescale.is_some()
+            || self.user_override.is_some()
+            || self.comment.is_some()
+            || self.read_ahead.is_some()
+            || self.settings.is_some()
+            || self.sorted_by.is_some()
+            || self.has_result_limits()
     }
 
     /// Check if explain is configured.
@@ -562,6 +806,84 @@ mod tests {
         assert!(explain_only.is_explain_only());
     }
 
+    #[test]
+    fn test_query_options_quota_key() {
+        assert!(!QueryOptions::new().has_options());
+
+        let opts = QueryOptions::new().with_quota_key("customer-1");
+        assert!(opts.has_options());
+        assert_eq!(opts.quota_key.as_deref(), Some("customer-1"));
+    }
+
+    #[test]
+    fn test_query_options_user_override() {
+        let opts = QueryOptions::new().with_user_override("tenant_a");
+        assert!(opts.has_options());
+        assert_eq!(opts.user_override.as_deref(), Some("tenant_a"));
+    }
+
+    #[test]
+    fn test_query_options_comment() {
+        let opts = QueryOptions::new().with_comment("job=nightly-export");
+        assert!(opts.has_options());
+        assert_eq!(opts.comment.as_deref(), Some("job=nightly-export"));
+    }
+
+    #[test]
+    fn test_query_options_read_ahead() {
+        let opts = QueryOptions::new().with_read_ahead(4);
+        assert!(opts.has_options());
+        assert_eq!(opts.read_ahead, Some(4));
+    }
+
+    #[test]
+    fn test_query_options_settings() {
+        let opts = QueryOptions::new()
+            .with_settings(vec![("max_threads", 4)])
+            .with_setting("max_threads", 8)
+            .with_setting("readonly", 1);
+        assert!(opts.has_options());
+        let settings = opts.settings.unwrap();
+        assert_eq!(settings.encode_to_strings(), vec!["max_threads = 8", "readonly = 1"]);
+    }
+
+    #[test]
+    fn test_query_options_project() {
+        let opts = QueryOptions::new().project(["a", "b"]);
+        assert!(opts.has_options());
+        assert_eq!(opts.projection.as_deref(), Some(&["a".to_string(), "b".to_string()][..]));
+    }
+
+    #[test]
+    fn test_query_options_rescale_decimal() {
+        use crate::arrow::rescale::DecimalOverflow;
+
+        let price = DecimalRescale::new(18, 4).with_overflow(DecimalOverflow::Saturate);
+        let opts = QueryOptions::new()
+            .rescale_decimal("price", DecimalRescale::new(9, 2))
+            .rescale_decimal("total", DecimalRescale::new(38, 2))
+            .rescale_decimal("price", price);
+        assert!(opts.has_options());
+        let rescales = opts.decimal_rescale.unwrap();
+        assert_eq!(
+            &rescales[..],
+            &[("total".to_string(), DecimalRescale::new(38, 2)), ("price".to_string(), price)]
+        );
+    }
+
+    #[test]
+    fn test_query_options_assert_sorted_by() {
+        let asc = SortOptions { descending: false, nulls_first: false };
+        let desc = SortOptions { descending: true, nulls_first: false };
+        let opts = QueryOptions::new()
+            .assert_sorted_by("day", asc)
+            .assert_sorted_by("id", asc)
+            .assert_sorted_by("day", desc);
+        assert!(opts.has_options());
+        let keys = opts.sorted_by.unwrap();
+        assert_eq!(&keys[..], &[("day".to_string(), desc), ("id".to_string(), asc)]);
+    }
+
     #[test]
     fn test_explain_result_display() {
         let text = ExplainResult::Text("Expression\n  ReadFromStorage".to_string());
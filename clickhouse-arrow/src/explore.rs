@@ -0,0 +1,508 @@
+//! Data exploration helpers for building previews of tables.
+//!
+//! [`ArrowClient::preview`] returns a sample of rows from a table along with a summary of each
+//! column, as needed by data-exploration UIs. Sampling avoids `ORDER BY rand()`, which requires
+//! reading and sorting the whole table:
+//! - Tables with no more rows than requested are read with a plain `LIMIT`.
+//! - Tables with a sampling key use the `SAMPLE` clause, which skips granules server-side.
+//! - Other tables are filtered with `rand()` at the sampling rate, which streams granules in
+//!   parallel and stops reading as soon as `LIMIT` is reached.
+//!
+//! [`ArrowClient::column_stats`] computes statistics over every row of a table instead, using a
+//! single aggregate query, for query planners and data catalogs.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use clickhouse_arrow::prelude::*;
+//!
+//! let preview = client.preview("analytics.events", 100, None).await?;
+//! arrow::util::pretty::print_batches(&[preview.batch.clone()])?;
+//! for column in &preview.columns {
+//!     println!("{}: {} nulls, {} distinct", column.name, column.null_count, column.distinct_count);
+//! }
+//!
+//! let stats = client.column_stats("analytics.events", &["user_id", "country"], None).await?;
+//! for column in &stats.columns {
+//!     println!("{}: ~{} distinct, {:.1}% null", column.name, column.distinct_estimate,
+//!         column.null_fraction * 100.0);
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch};
+use arrow::compute::{SortOptions, cast, sort_to_indices};
+use arrow::datatypes::{DataType, UInt64Type};
+use arrow::util::display::array_value_to_string;
+
+use crate::prelude::*;
+use crate::query::quote_identifier;
+
+/// Factor by which the sampling rate exceeds the requested fraction of rows, so that the sample
+/// usually contains at least the requested number of rows.
+const OVERSAMPLE: f64 = 2.0;
+
+/// How the rows of a [`Preview`] were selected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleMethod {
+    /// The table has no more rows than requested, or its size is unknown, so rows were read
+    /// with a plain `LIMIT`.
+    Limit,
+    /// Rows were sampled with the `SAMPLE` clause using the table's sampling key.
+    SampleClause(f64),
+    /// Rows were filtered with `rand()` at the given sampling rate.
+    RandomFilter(f64),
+}
+
+/// Summary of a single column of a [`Preview`], computed from the sampled rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSummary {
+    /// Column name.
+    pub name:           String,
+    /// Arrow data type of the column.
+    pub data_type:      DataType,
+    /// Number of null values in the sample.
+    pub null_count:     usize,
+    /// Number of distinct non-null values in the sample.
+    pub distinct_count: usize,
+    /// Smallest non-null value in the sample, if the type is orderable.
+    pub min:            Option<String>,
+    /// Largest non-null value in the sample, if the type is orderable.
+    pub max:            Option<String>,
+}
+
+/// A sample of rows from a table, see [`ArrowClient::preview`].
+#[derive(Debug, Clone)]
+pub struct Preview {
+    /// The sampled rows.
+    pub batch:      RecordBatch,
+    /// A summary of each column of the sample.
+    pub columns:    Vec<ColumnSummary>,
+    /// Total rows in the table as reported by `system.tables`, if known.
+    pub total_rows: Option<u64>,
+    /// How the rows were selected.
+    pub method:     SampleMethod,
+}
+
+/// Statistics of a single column over every row of a table, see [`ArrowClient::column_stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    /// Column name.
+    pub name:              String,
+    /// Smallest non-null value, formatted by `ClickHouse`, or `None` if every value is null.
+    pub min:               Option<String>,
+    /// Largest non-null value, formatted by `ClickHouse`, or `None` if every value is null.
+    pub max:               Option<String>,
+    /// Number of null values.
+    pub null_count:        u64,
+    /// Fraction of values that are null, between 0 and 1. Zero for empty tables.
+    pub null_fraction:     f64,
+    /// Approximate number of distinct non-null values, as computed by `uniq`.
+    pub distinct_estimate: u64,
+}
+
+/// Statistics of a set of columns of a table, see [`ArrowClient::column_stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStats {
+    /// Number of rows in the table.
+    pub row_count: u64,
+    /// Statistics for each requested column, in the order requested.
+    pub columns:   Vec<ColumnStats>,
+}
+
+impl TableStats {
+    /// Returns the statistics of the column named `name`, if requested.
+    pub fn column(&self, name: &str) -> Option<&ColumnStats> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+}
+
+/// Number of aggregates computed per column by [`column_stats_query`].
+const STATS_PER_COLUMN: usize = 4;
+
+/// Build the single aggregate query computing [`TableStats`] for `columns` of `table`.
+///
+/// The first column is the row count, followed by [`STATS_PER_COLUMN`] columns per requested
+/// column: min, max, null count, and distinct estimate. `OrNull` combinators ensure min and max
+/// are null rather than the type's default when every value is null.
+fn column_stats_query(table: &str, columns: &[&str]) -> Result<String> {
+    let aggregates = columns
+        .iter()
+        .map(|column| {
+            let column = quote_identifier(column)?;
+            Ok(format!(
+                "toString(minOrNull({column})), toString(maxOrNull({column})), \
+                 countIf(isNull({column})), uniq({column})"
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .join(", ");
+    Ok(format!("SELECT count(), {aggregates} FROM {table}"))
+}
+
+/// Read the single row returned by [`column_stats_query`] into [`TableStats`].
+fn read_column_stats(columns: &[&str], batch: &RecordBatch) -> Result<TableStats> {
+    let expected = 1 + columns.len() * STATS_PER_COLUMN;
+    if batch.num_rows() != 1 || batch.num_columns() != expected {
+        return Err(Error::Protocol(format!(
+            "Expected 1 row of {expected} columns for column statistics, got {} rows of {} columns",
+            batch.num_rows(),
+            batch.num_columns()
+        )));
+    }
+
+    let read_u64 = |i: usize| -> Result<u64> {
+        let array = cast(batch.column(i), &DataType::UInt64)?;
+        Ok(array.as_primitive::<UInt64Type>().value(0))
+    };
+    let read_string = |i: usize| -> Result<Option<String>> {
+        let array = cast(batch.column(i), &DataType::Utf8)?;
+        let array = array.as_string::<i32>();
+        Ok(array.is_valid(0).then(|| array.value(0).to_string()))
+    };
+
+    let row_count = read_u64(0)?;
+    let columns = columns
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let offset = 1 + i * STATS_PER_COLUMN;
+            let null_count = read_u64(offset + 2)?;
+            #[expect(clippy::cast_precision_loss)]
+            let null_fraction =
+                if row_count == 0 { 0.0 } else { null_count as f64 / row_count as f64 };
+            Ok(ColumnStats {
+                name: (*name).to_string(),
+                min: read_string(offset)?,
+                max: read_string(offset + 1)?,
+                null_count,
+                null_fraction,
+                distinct_estimate: read_u64(offset + 3)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(TableStats { row_count, columns })
+}
+
+/// Compute statistics for `columns` of `table`, see [`ArrowClient::column_stats`].
+pub(crate) async fn column_stats(
+    client: &ArrowClient,
+    table: &str,
+    columns: &[&str],
+    qid: Option<Qid>,
+) -> Result<TableStats> {
+    if columns.is_empty() {
+        return Err(Error::Client("Column statistics require at least one column".into()));
+    }
+    let batch = client
+        .query(column_stats_query(table, columns)?, qid)
+        .await?
+        .collect_result()
+        .await?
+        .collect_table()?;
+    read_column_stats(columns, &batch)
+}
+
+/// Split a possibly qualified table name into its database, if any, and table name.
+pub(crate) fn split_table(table: &str) -> (Option<String>, String) {
+    let unquote = |s: &str| s.trim_matches(['`', '"']).to_string();
+    match table.split_once('.') {
+        Some((db, name)) => (Some(unquote(db)), unquote(name)),
+        None => (None, unquote(table)),
+    }
+}
+
+/// A filter on the `database` and `name_column` columns of a `system.*` table, matching `name`
+/// in `database`, or the current database if `None`.
+///
+/// The names are bound as query parameters rather than interpolated, so they need no escaping.
+pub(crate) fn system_table_filter(
+    database: Option<&str>,
+    name_column: &str,
+    name: &str,
+) -> (String, QueryParams) {
+    let mut params = vec![("system_table_name", name)];
+    let database_expr = match database {
+        Some(database) => {
+            params.push(("system_table_database", database));
+            "{system_table_database:String}"
+        }
+        None => "currentDatabase()",
+    };
+    let filter =
+        format!("database = {database_expr} AND {name_column} = {{system_table_name:String}}");
+    (filter, QueryParams::from(params))
+}
+
+/// Choose how to sample `rows` from a table with `total_rows` rows.
+fn sample_method(rows: usize, total_rows: Option<u64>, has_sampling_key: bool) -> SampleMethod {
+    match total_rows {
+        #[expect(clippy::cast_precision_loss)]
+        Some(total) if total > rows as u64 => {
+            let rate = (OVERSAMPLE * rows as f64 / total as f64).min(1.0);
+            if has_sampling_key {
+                SampleMethod::SampleClause(rate)
+            } else {
+                SampleMethod::RandomFilter(rate)
+            }
+        }
+        _ => SampleMethod::Limit,
+    }
+}
+
+/// Build the query selecting `rows` rows from `table` using `method`.
+fn sample_query(table: &str, rows: usize, method: SampleMethod) -> String {
+    match method {
+        SampleMethod::Limit => format!("SELECT * FROM {table} LIMIT {rows}"),
+        SampleMethod::SampleClause(rate) => {
+            format!("SELECT * FROM {table} SAMPLE {rate} LIMIT {rows}")
+        }
+        SampleMethod::RandomFilter(rate) => {
+            // `rand()` is uniform over UInt32
+            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let threshold = (rate * f64::from(u32::MAX)) as u64;
+            format!("SELECT * FROM {table} WHERE rand() <= {threshold} LIMIT {rows}")
+        }
+    }
+}
+
+/// Summarize a column of sampled rows.
+fn summarize(name: &str, array: &ArrayRef) -> ColumnSummary {
+    let valid = (0..array.len()).filter(|&i| array.is_valid(i));
+    let distinct =
+        valid.filter_map(|i| array_value_to_string(array, i).ok()).collect::<HashSet<_>>().len();
+
+    // Nulls last in both directions, so the first index is a non-null value if one exists
+    let extreme = |descending| {
+        let options = SortOptions { descending, nulls_first: false };
+        let indices = sort_to_indices(array, Some(options), Some(1)).ok()?;
+        let index = usize::try_from(indices.values().first().copied()?).ok()?;
+        array.is_valid(index).then(|| array_value_to_string(array, index).ok()).flatten()
+    };
+
+    ColumnSummary {
+        name:           name.to_string(),
+        data_type:      array.data_type().clone(),
+        null_count:     array.null_count(),
+        distinct_count: distinct,
+        min:            extreme(false),
+        max:            extreme(true),
+    }
+}
+
+/// Summarize each column of a batch.
+pub(crate) fn summarize_batch(batch: &RecordBatch) -> Vec<ColumnSummary> {
+    batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, array)| summarize(field.name(), array))
+        .collect()
+}
+
+/// Sample up to `rows` rows from `table`, see [`ArrowClient::preview`].
+pub(crate) async fn preview(
+    client: &ArrowClient,
+    table: &str,
+    rows: usize,
+    qid: Option<Qid>,
+) -> Result<Preview> {
+    let (database, name) = split_table(table);
+    let (filter, params) = system_table_filter(database.as_deref(), "name", &name);
+    let info = client
+        .query_params(
+            format!("SELECT total_rows, sampling_key FROM system.tables WHERE {filter}"),
+            Some(params),
+            qid,
+        )
+        .await?
+        .collect_result()
+        .await?
+        .collect_table()?;
+    if info.num_rows() == 0 {
+        let db = database.unwrap_or_else(|| "currentDatabase()".into());
+        return Err(Error::UndefinedTables { db, tables: vec![name] });
+    }
+
+    let total_rows = cast(info.column(0), &DataType::UInt64)?;
+    let total_rows = total_rows.as_primitive::<UInt64Type>();
+    let total_rows = total_rows.is_valid(0).then(|| total_rows.value(0));
+    let sampling_key = cast(info.column(1), &DataType::Utf8)?;
+    let has_sampling_key = !sampling_key.as_string::<i32>().value(0).is_empty();
+
+    let method = sample_method(rows, total_rows, has_sampling_key);
+    let result =
+        client.query(sample_query(table, rows, method), None).await?.collect_result().await?;
+    let batch = result.collect_table()?;
+    let columns = summarize_batch(&batch);
+    Ok(Preview { batch, columns, total_rows, method })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int32Array, StringArray, UInt64Array};
+    use arrow::datatypes::{Field, Schema};
+
+    use super::*;
+
+    #[test]
+    fn test_split_table() {
+        assert_eq!(split_table("db.events"), (Some("db".into()), "events".into()));
+        assert_eq!(split_table("`db`.`events`"), (Some("db".into()), "events".into()));
+        assert_eq!(split_table("events"), (None, "events".into()));
+    }
+
+    #[test]
+    fn test_system_table_filter() {
+        let (filter, params) = system_table_filter(Some("it's"), "table", "events");
+        assert_eq!(
+            filter,
+            "database = {system_table_database:String} AND table = {system_table_name:String}"
+        );
+        assert_eq!(
+            params,
+            QueryParams::from(vec![
+                ("system_table_name", "events"),
+                ("system_table_database", "it's")
+            ])
+        );
+        let (filter, _) = system_table_filter(None, "name", "events");
+        assert_eq!(filter, "database = currentDatabase() AND name = {system_table_name:String}");
+    }
+
+    #[test]
+    fn test_sample_method() {
+        assert_eq!(sample_method(100, None, true), SampleMethod::Limit);
+        assert_eq!(sample_method(100, Some(50), true), SampleMethod::Limit);
+        assert_eq!(sample_method(100, Some(1000), true), SampleMethod::SampleClause(0.2));
+        assert_eq!(sample_method(100, Some(1000), false), SampleMethod::RandomFilter(0.2));
+        assert_eq!(sample_method(100, Some(150), false), SampleMethod::RandomFilter(1.0));
+    }
+
+    #[test]
+    fn test_sample_query() {
+        assert_eq!(sample_query("t", 10, SampleMethod::Limit), "SELECT * FROM t LIMIT 10");
+        assert_eq!(
+            sample_query("t", 10, SampleMethod::SampleClause(0.5)),
+            "SELECT * FROM t SAMPLE 0.5 LIMIT 10"
+        );
+        assert_eq!(
+            sample_query("t", 10, SampleMethod::RandomFilter(0.5)),
+            "SELECT * FROM t WHERE rand() <= 2147483647 LIMIT 10"
+        );
+        assert!(!sample_query("t", 10, SampleMethod::RandomFilter(0.5)).contains("ORDER BY"));
+    }
+
+    #[test]
+    fn test_column_stats_query() {
+        assert_eq!(
+            column_stats_query("db.t", &["id", "a`b"]).unwrap(),
+            "SELECT count(), toString(minOrNull(`id`)), toString(maxOrNull(`id`)), \
+             countIf(isNull(`id`)), uniq(`id`), toString(minOrNull(`a\\`b`)), \
+             toString(maxOrNull(`a\\`b`)), countIf(isNull(`a\\`b`)), uniq(`a\\`b`) FROM db.t"
+        );
+    }
+
+    #[test]
+    fn test_read_column_stats() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("count()", DataType::UInt64, false),
+            Field::new("min", DataType::Utf8, true),
+            Field::new("max", DataType::Utf8, true),
+            Field::new("nulls", DataType::UInt64, false),
+            Field::new("uniq", DataType::UInt64, false),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(UInt64Array::from(vec![8])),
+            Arc::new(StringArray::from(vec![Some("1")])),
+            Arc::new(StringArray::from(vec![Some("9")])),
+            Arc::new(UInt64Array::from(vec![2])),
+            Arc::new(UInt64Array::from(vec![5])),
+        ])
+        .unwrap();
+
+        let stats = read_column_stats(&["id"], &batch).unwrap();
+        assert_eq!(stats.row_count, 8);
+        assert_eq!(
+            stats.column("id"),
+            Some(&ColumnStats {
+                name:              "id".into(),
+                min:               Some("1".into()),
+                max:               Some("9".into()),
+                null_count:        2,
+                null_fraction:     0.25,
+                distinct_estimate: 5,
+            })
+        );
+        assert!(stats.column("missing").is_none());
+
+        // Column count must match the requested columns
+        assert!(read_column_stats(&["id", "name"], &batch).is_err());
+    }
+
+    #[test]
+    fn test_read_column_stats_empty_table() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("count()", DataType::UInt64, false),
+            Field::new("min", DataType::Utf8, true),
+            Field::new("max", DataType::Utf8, true),
+            Field::new("nulls", DataType::UInt64, false),
+            Field::new("uniq", DataType::UInt64, false),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(UInt64Array::from(vec![0])),
+            Arc::new(StringArray::from(vec![None::<&str>])),
+            Arc::new(StringArray::from(vec![None::<&str>])),
+            Arc::new(UInt64Array::from(vec![0])),
+            Arc::new(UInt64Array::from(vec![0])),
+        ])
+        .unwrap();
+
+        let stats = read_column_stats(&["id"], &batch).unwrap();
+        assert_eq!(stats.columns[0].min, None);
+        assert_eq!(stats.columns[0].max, None);
+        assert!(stats.columns[0].null_fraction.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_summarize_batch() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(Int32Array::from(vec![Some(3), None, Some(1), Some(3)])),
+            Arc::new(StringArray::from(vec![None, None, Some("b"), Some("a")])),
+        ])
+        .unwrap();
+
+        let columns = summarize_batch(&batch);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0], ColumnSummary {
+            name:           "id".into(),
+            data_type:      DataType::Int32,
+            null_count:     1,
+            distinct_count: 2,
+            min:            Some("1".into()),
+            max:            Some("3".into()),
+        });
+        assert_eq!(columns[1].null_count, 2);
+        assert_eq!(columns[1].distinct_count, 2);
+        assert_eq!(columns[1].min.as_deref(), Some("a"));
+        assert_eq!(columns[1].max.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_summarize_all_nulls() {
+        let array = Arc::new(Int32Array::from(vec![None, None])) as ArrayRef;
+        let summary = summarize("empty", &array);
+        assert_eq!(summary.null_count, 2);
+        assert_eq!(summary.distinct_count, 0);
+        assert_eq!(summary.min, None);
+        assert_eq!(summary.max, None);
+    }
+}
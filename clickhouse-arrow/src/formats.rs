@@ -1,19 +1,25 @@
 mod arrow;
+mod lazy;
 mod native;
 pub(crate) mod protocol_data;
 
 // Re-exports
 pub use arrow::ArrowFormat;
+pub use lazy::LazyArrowFormat;
 pub use native::NativeFormat;
 
 use crate::ArrowOptions;
 
 /// Trait for estimating the in-memory size of data.
 ///
-/// This is used by the load balancer to skip load balancing overhead for small inserts.
+/// This is used by the load balancer to skip load balancing overhead for small inserts, and by
+/// the insert rate limiter.
 pub(crate) trait DataSize {
     /// Returns the estimated size of the data in bytes.
     fn data_size(&self) -> usize;
+
+    /// Returns the number of rows in the data.
+    fn data_rows(&self) -> usize;
 }
 
 /// Threshold for "small" inserts that skip load balancing (1MB).
@@ -34,12 +40,17 @@ pub trait ClientFormat: sealed::ClientFormatImpl<Self::Data> + Send + Sync + 'st
 }
 
 pub(crate) mod sealed {
+    use std::sync::Arc;
+
     use super::{DeserializerState, SerializerState};
-    use crate::Type;
+    use crate::arrow::{BatchMetadata, DecimalRescale};
     use crate::client::connection::ClientMetadata;
     use crate::errors::Result;
     use crate::io::{ClickHouseRead, ClickHouseWrite};
+    use crate::masking::InsertMasking;
     use crate::query::Qid;
+    use crate::validation::BlockValidator;
+    use crate::{InsertCoalescing, Type};
 
     pub(crate) trait ClientFormatImpl<T>: std::fmt::Debug
     where
@@ -54,6 +65,42 @@ pub(crate) mod sealed {
 
         fn finish_deser(_state: &mut DeserializerState<Self::Deser>) {}
 
+        /// Restrict deserialization of the next query's blocks to the named columns. Formats that
+        /// cannot project decode every column.
+        fn set_projection(
+            _state: &mut DeserializerState<Self::Deser>,
+            _projection: Option<Arc<[String]>>,
+        ) {
+        }
+
+        /// Convert the named decimal columns of the next query's blocks while decoding. Formats
+        /// that cannot rescale decode decimals unchanged.
+        fn set_decimal_rescale(
+            _state: &mut DeserializerState<Self::Deser>,
+            _rescale: Option<Arc<[(String, DecimalRescale)]>>,
+        ) {
+        }
+
+        /// Run client-side block validation prior to serialization. Formats that cannot be
+        /// validated accept all data.
+        fn validate(_data: &T, _validator: &BlockValidator) -> Result<()> { Ok(()) }
+
+        /// Split a block into blocks of at most `max_rows` rows prior to insert. Formats that
+        /// cannot be split send the block unchanged.
+        fn split(data: T, _max_rows: usize) -> Vec<T> { vec![data] }
+
+        /// Merge consecutive small blocks of an insert into larger ones prior to insert. Formats
+        /// that cannot be merged send the blocks unchanged.
+        fn coalesce(data: Vec<T>, _coalescing: InsertCoalescing) -> Result<Vec<T>> { Ok(data) }
+
+        /// Apply the insert masking transforms configured for `table`. Formats that cannot be
+        /// masked send the block unchanged.
+        fn mask(data: T, _masking: &InsertMasking, _table: &str) -> Result<T> { Ok(data) }
+
+        /// Attach lineage metadata to a deserialized block. Formats without schema metadata
+        /// return the block unchanged.
+        fn attach_batch_metadata(data: T, _metadata: &BatchMetadata) -> T { data }
+
         fn write<'a, W: ClickHouseWrite>(
             writer: &'a mut W,
             data: T,
@@ -77,6 +124,8 @@ pub(crate) mod sealed {
 pub(crate) struct DeserializerState<T: Default = ()> {
     pub(crate) options:      Option<ArrowOptions>,
     pub(crate) deserializer: T,
+    /// Rows decoded so far for the current query, locating errors within the result
+    pub(crate) rows_read:    u64,
 }
 
 impl<T: Default> DeserializerState<T> {
@@ -1,20 +1,27 @@
+use std::sync::Arc;
+
 use arrow::array::RecordBatch;
 use arrow::datatypes::SchemaRef;
 
 use super::protocol_data::{EmptyBlock, ProtocolData};
 use super::{DataSize, DeserializerState};
 use crate::Type;
-use crate::arrow::ArrowDeserializerState;
+use crate::arrow::{ArrowDeserializerState, BatchMetadata, DecimalRescale};
 use crate::compression::{DecompressionReader, compress_data_pooled};
 use crate::connection::ClientMetadata;
 use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::masking::InsertMasking;
 use crate::native::protocol::CompressionMethod;
 use crate::prelude::*;
 use crate::simd::PooledBuffer;
+use crate::validation::BlockValidator;
 
 impl DataSize for RecordBatch {
     #[inline]
     fn data_size(&self) -> usize { self.get_array_memory_size() }
+
+    #[inline]
+    fn data_rows(&self) -> usize { self.num_rows() }
 }
 
 /// Marker trait for Arrow format.
@@ -38,6 +45,51 @@ impl super::sealed::ClientFormatImpl<RecordBatch> for ArrowFormat {
     fn finish_deser(state: &mut DeserializerState<Self::Deser>) {
         state.deserializer().builders.clear();
         state.deserializer().buffer.clear();
+        state.deserializer().headers.clear();
+    }
+
+    fn set_projection(
+        state: &mut DeserializerState<Self::Deser>,
+        projection: Option<Arc<[String]>>,
+    ) {
+        state.deserializer().projection = projection;
+    }
+
+    fn set_decimal_rescale(
+        state: &mut DeserializerState<Self::Deser>,
+        rescale: Option<Arc<[(String, DecimalRescale)]>>,
+    ) {
+        state.deserializer().decimal_rescale = rescale;
+    }
+
+    fn validate(batch: &RecordBatch, validator: &BlockValidator) -> Result<()> {
+        validator.validate(batch)
+    }
+
+    fn mask(batch: RecordBatch, masking: &InsertMasking, table: &str) -> Result<RecordBatch> {
+        masking.apply(table, batch).map(|(batch, _)| batch)
+    }
+
+    fn attach_batch_metadata(batch: RecordBatch, metadata: &BatchMetadata) -> RecordBatch {
+        metadata.attach(batch)
+    }
+
+    fn split(batch: RecordBatch, max_rows: usize) -> Vec<RecordBatch> {
+        if max_rows == 0 {
+            return vec![batch];
+        }
+        crate::arrow::utils::split_record_batch(batch, max_rows)
+    }
+
+    fn coalesce(
+        batches: Vec<RecordBatch>,
+        coalescing: InsertCoalescing,
+    ) -> Result<Vec<RecordBatch>> {
+        crate::arrow::utils::coalesce_record_batches(
+            batches,
+            coalescing.max_rows,
+            coalescing.max_bytes,
+        )
     }
 
     /// Writes a `RecordBatch` to the `ClickHouse` protocol.
@@ -71,7 +123,7 @@ impl super::sealed::ClientFormatImpl<RecordBatch> for ArrowFormat {
             batch
                 .write(raw.buffer_mut(), revision, header, metadata.arrow_options)
                 .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "serialize"))?;
-            compress_data_pooled(writer, raw, metadata.compression)
+            compress_data_pooled(writer, raw, metadata.compression, metadata.frame_limits)
                 .await
                 .inspect_err(|error| error!(?error, { ATT_QID } = %qid, "compressing"))?;
         }
@@ -89,7 +141,9 @@ impl super::sealed::ClientFormatImpl<RecordBatch> for ArrowFormat {
         if let CompressionMethod::None = metadata.compression {
             RecordBatch::read_async(reader, revision, arrow_options, state).await
         } else {
-            let mut decompressor = DecompressionReader::new(metadata.compression, reader).await?;
+            let mut decompressor =
+                DecompressionReader::new(metadata.compression, metadata.frame_limits, reader)
+                    .await?;
             RecordBatch::read_async(&mut decompressor, revision, arrow_options, state).await
         }
         .inspect_err(|error| error!(?error, "deserializing arrow record batch"))
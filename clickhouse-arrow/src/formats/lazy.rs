@@ -0,0 +1,66 @@
+use arrow::datatypes::SchemaRef;
+
+use super::DeserializerState;
+use super::arrow::ArrowFormat;
+use super::sealed::ClientFormatImpl;
+use crate::Type;
+use crate::arrow::lazy::LazyBatch;
+use crate::compression::DecompressionReader;
+use crate::connection::ClientMetadata;
+use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::native::protocol::CompressionMethod;
+use crate::prelude::*;
+use crate::validation::BlockValidator;
+
+/// Marker trait for lazily decoded Arrow format.
+///
+/// Read native `ClickHouse` blocks into [`LazyBatch`]es, whose columns are decoded into arrow
+/// arrays on first access, and write [`LazyBatch`]es by decoding them into arrow `RecordBatch`es.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyArrowFormat {}
+
+impl ClientFormat for LazyArrowFormat {
+    type Data = LazyBatch;
+
+    const FORMAT: &'static str = "LazyArrow";
+}
+
+impl ClientFormatImpl<LazyBatch> for LazyArrowFormat {
+    type Deser = ();
+    type Schema = SchemaRef;
+    type Ser = ();
+
+    fn validate(batch: &LazyBatch, validator: &BlockValidator) -> Result<()> {
+        validator.validate(&batch.to_record_batch()?)
+    }
+
+    async fn write<W: ClickHouseWrite>(
+        writer: &mut W,
+        batch: LazyBatch,
+        qid: Qid,
+        header: Option<&[(String, Type)]>,
+        revision: u64,
+        metadata: ClientMetadata,
+    ) -> Result<()> {
+        let batch = batch.to_record_batch()?;
+        ArrowFormat::write(writer, batch, qid, header, revision, metadata).await
+    }
+
+    async fn read<R: ClickHouseRead + 'static>(
+        reader: &mut R,
+        revision: u64,
+        metadata: ClientMetadata,
+        _state: &mut DeserializerState,
+    ) -> Result<Option<LazyBatch>> {
+        let arrow_options = metadata.arrow_options;
+        if let CompressionMethod::None = metadata.compression {
+            LazyBatch::read_async(reader, revision, arrow_options).await
+        } else {
+            let mut decompressor =
+                DecompressionReader::new(metadata.compression, metadata.frame_limits, reader)
+                    .await?;
+            LazyBatch::read_async(&mut decompressor, revision, arrow_options).await
+        }
+        .inspect_err(|error| error!(?error, "deserializing lazy arrow record batch"))
+    }
+}
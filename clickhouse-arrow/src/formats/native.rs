@@ -13,6 +13,10 @@ use crate::prelude::*;
 impl DataSize for Block {
     #[inline]
     fn data_size(&self) -> usize { self.estimate_size() }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[inline]
+    fn data_rows(&self) -> usize { self.rows as usize }
 }
 
 /// Marker for Native format.
@@ -42,7 +46,9 @@ impl super::sealed::ClientFormatImpl<Block> for NativeFormat {
         Ok(if let CompressionMethod::None = metadata.compression {
             Block::read_async(reader, revision, (), state).await?.into_option()
         } else {
-            let mut decompressor = DecompressionReader::new(metadata.compression, reader).await?;
+            let mut decompressor =
+                DecompressionReader::new(metadata.compression, metadata.frame_limits, reader)
+                    .await?;
             Block::read_async(&mut decompressor, revision, (), state).await?.into_option()
         })
     }
@@ -68,7 +74,8 @@ impl super::sealed::ClientFormatImpl<Block> for NativeFormat {
             data.write(&mut buffer, revision, header, ())
                 .inspect_err(|error| error!(?error, {ATT_QID} = %qid, "(block:compressed)"))?;
 
-            compress_data_sync(writer, buffer.freeze(), metadata.compression)
+            let (compression, limits) = (metadata.compression, metadata.frame_limits);
+            compress_data_sync(writer, buffer.freeze(), compression, limits)
                 .instrument(trace_span!("compress_block"))
                 .await
                 .inspect_err(|error| error!(?error, {ATT_QID} = %qid, "compressing"))
@@ -0,0 +1,98 @@
+//! ## Fuzzing entry points
+//!
+//! Thin wrappers over the internal deserializers, exposed for the `cargo-fuzz` targets in
+//! `clickhouse-arrow/fuzz`. Every function accepts arbitrary bytes and must return an error, never
+//! panic, on malformed input. Only available with the `fuzzing` feature and not part of the
+//! stable API.
+//!
+//! ```bash
+//! cd clickhouse-arrow
+//! cargo +nightly fuzz run arrow_block
+//! ```
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int64Array, RecordBatch};
+use bytes::Bytes;
+
+use crate::arrow::ch_to_arrow_type;
+use crate::formats::DeserializerState;
+use crate::formats::protocol_data::ProtocolData;
+use crate::native::block::Block;
+use crate::native::protocol::DBMS_TCP_PROTOCOL_VERSION;
+use crate::native::sparse::{expand_sparse_array, read_sparse_offsets_sync};
+use crate::{ArrowOptions, Result, Type};
+
+/// Deserialize a native protocol data block into an Arrow [`RecordBatch`].
+///
+/// # Errors
+/// Returns an error if the block is malformed.
+pub fn arrow_block(data: &[u8]) -> Result<RecordBatch> {
+    let mut reader = Bytes::copy_from_slice(data);
+    let mut state = DeserializerState::default();
+    RecordBatch::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, ArrowOptions::default(), &mut state)
+}
+
+/// Deserialize a native protocol data block into a native [`Block`].
+///
+/// # Errors
+/// Returns an error if the block is malformed.
+pub fn native_block(data: &[u8]) -> Result<()> {
+    let mut reader = Bytes::copy_from_slice(data);
+    let mut state = DeserializerState::default();
+    let _ = Block::read(&mut reader, DBMS_TCP_PROTOCOL_VERSION, (), &mut state)?;
+    Ok(())
+}
+
+/// Parse a `ClickHouse` type name and map it to its Arrow type.
+///
+/// # Errors
+/// Returns an error if the type name is invalid or has no Arrow mapping.
+pub fn type_name(data: &[u8]) -> Result<()> {
+    let type_ = Type::from_str(&String::from_utf8_lossy(data))?;
+    let _ = ch_to_arrow_type(&type_, Some(ArrowOptions::default()))?;
+    Ok(())
+}
+
+/// Read sparse offsets and expand a matching sparse column.
+///
+/// The first two bytes select the number of rows, the remainder is the offsets stream.
+///
+/// # Errors
+/// Returns an error if the offsets are malformed.
+pub fn sparse_offsets(data: &[u8]) -> Result<ArrayRef> {
+    let Some((rows, rest)) = data.split_first_chunk::<2>() else {
+        return Err(crate::Error::Protocol("not enough data".into()));
+    };
+    let num_rows = usize::from(u16::from_le_bytes(*rows));
+    let mut reader = Bytes::copy_from_slice(rest);
+    let offsets = read_sparse_offsets_sync(&mut reader, num_rows)?;
+    #[expect(clippy::cast_possible_wrap)]
+    let values: ArrayRef =
+        Arc::new(Int64Array::from_iter_values(offsets.iter().map(|&o| o as i64)));
+    expand_sparse_array(&values, &offsets, num_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzz_entry_points_reject_garbage() {
+        let inputs: [&[u8]; 5] = [&[], &[0xff; 16], &[1, 0, 2, 0, 0], &[0x80; 32], b"Array("];
+        for input in inputs {
+            let _ = arrow_block(input);
+            let _ = native_block(input);
+            let _ = type_name(input);
+            let _ = sparse_offsets(input);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_sparse_offsets_valid() {
+        // 8 rows, values at positions 2 and 4, 3 trailing defaults ending the granule
+        let data = [8, 0, 2, 1, 131, 128, 128, 128, 128, 128, 128, 128, 64];
+        let expanded = sparse_offsets(&data).unwrap();
+        assert_eq!(expanded.len(), 8);
+    }
+}
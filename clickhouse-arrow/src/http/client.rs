@@ -4,24 +4,42 @@
 //! your network team insists on HTTP-only egress. Native protocol is faster
 //! and more CPU-efficient at both ends.
 
+use std::sync::Arc;
+
 use arrow::array::RecordBatch;
 use bytes::Bytes;
+use futures_util::{Stream, TryStreamExt};
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
 use tracing::{Instrument, debug, instrument, trace_span};
 
 use super::arrow_stream::{deserialize_batches, serialize_batch};
 use super::config::HttpOptions;
-use crate::Error;
 use crate::errors::Result;
+use crate::query::{escape_text, reads_param_escaped};
+use crate::{Error, ParamValue, QueryParams};
 
 /// HTTP client using ClickHouse's ArrowStream format.
 ///
 /// Alternative to native TCP when you need HTTP (proxies, load balancers, etc).
 /// Simpler but slightly higher latency than native protocol.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct HttpClient {
-    client:  reqwest::Client,
-    options: HttpOptions,
+    client:     reqwest::Client,
+    options:    HttpOptions,
+    middleware: Vec<Arc<RequestMiddleware>>,
+}
+
+/// Hook invoked on every outgoing request just before it is sent.
+pub type RequestMiddleware = dyn Fn(&mut reqwest::Request) + Send + Sync;
+
+impl std::fmt::Debug for HttpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpClient")
+            .field("client", &self.client)
+            .field("options", &self.options)
+            .field("middleware", &self.middleware.len())
+            .finish()
+    }
 }
 
 impl HttpClient {
@@ -37,7 +55,48 @@ impl HttpClient {
             .build()
             .map_err(|e| Error::Configuration(format!("Failed to build HTTP client: {e}")))?;
 
-        Ok(Self { client, options })
+        Ok(Self { client, options, middleware: Vec::new() })
+    }
+
+    /// Add a hook that can mutate every request before it is sent.
+    ///
+    /// Use this to inject signatures (e.g. AWS `SigV4`), custom auth headers, or tracing headers
+    /// required by a reverse proxy. Hooks run in the order they were added, after the default
+    /// `ClickHouse` headers and body are set.
+    ///
+    /// ```rust,ignore
+    /// let client = HttpClient::new(options)?.with_request_middleware(|req| {
+    ///     let _ = req.headers_mut().insert("X-Proxy-Auth", HeaderValue::from_static("token"));
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_request_middleware<F>(mut self, middleware: F) -> Self
+    where
+        F: Fn(&mut reqwest::Request) + Send + Sync + 'static,
+    {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Build the request, run middleware, and send it.
+    async fn send(&self, builder: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let request = self.prepare(builder)?;
+        self.client
+            .execute(request)
+            .instrument(trace_span!("http_request"))
+            .await
+            .map_err(|e| Error::Network(e.to_string()))
+    }
+
+    /// Build the request and apply middleware in registration order.
+    fn prepare(&self, builder: reqwest::RequestBuilder) -> Result<reqwest::Request> {
+        let mut request = builder
+            .build()
+            .map_err(|e| Error::Configuration(format!("Failed to build HTTP request: {e}")))?;
+        for middleware in &self.middleware {
+            middleware(&mut request);
+        }
+        Ok(request)
     }
 
     /// Build default headers for requests.
@@ -86,18 +145,87 @@ impl HttpClient {
 
         debug!(url = %url, "Executing HTTP query");
 
-        let response = self
-            .client
-            .get(url)
-            .headers(headers)
-            .send()
-            .instrument(trace_span!("http_request"))
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+        let request = self.client.get(url).headers(headers);
+        let response = self.send(request).await?;
+
+        self.handle_response(response).await
+    }
+
+    /// Execute SELECT query with `{name:Type}` parameters, returns Arrow RecordBatches.
+    ///
+    /// Parameters are bound by the server, including `{name:Identifier}` parameters for table and
+    /// column names, so values never have to be formatted into the SQL. Identifier parameters are
+    /// validated before the request is sent, see [`QueryParams::validate_identifiers`].
+    ///
+    /// ```rust,ignore
+    /// let params = vec![("table", ParamValue::from("events")), ("id", ParamValue::from(1))];
+    /// let batches = client
+    ///     .query_params("SELECT * FROM {table:Identifier} WHERE id = {id:UInt64}", params)
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if an identifier parameter is invalid, the request fails, or the server
+    /// responds with a non-success status.
+    #[instrument(skip(self, params), fields(sql = %sql))]
+    pub async fn query_params(
+        &self,
+        sql: &str,
+        params: impl Into<QueryParams>,
+    ) -> Result<Vec<RecordBatch>> {
+        let params = params.into();
+        params.validate_identifiers(sql)?;
+
+        let mut url = self.build_query_url(sql, "ArrowStream");
+        append_params(&mut url, sql, &params);
+        let headers = self.default_headers();
+
+        debug!(url = %url, "Executing HTTP query with parameters");
+
+        let request = self.client.get(url).headers(headers);
+        let response = self.send(request).await?;
 
         self.handle_response(response).await
     }
 
+    /// Execute SELECT query with an arbitrary output `FORMAT`, streaming the raw response body.
+    ///
+    /// Escape hatch for formats this crate doesn't decode (`Pretty`, `CSV`, `JSONCompact`, ...),
+    /// useful for display or handing off to other tools. Chunks are yielded as they arrive.
+    ///
+    /// Only available over HTTP: the native protocol always returns `Native` blocks regardless of
+    /// the `FORMAT` clause.
+    ///
+    /// # Errors
+    /// Returns an error if the format name is invalid, the request fails, or the server responds
+    /// with a non-success status.
+    #[instrument(skip(self), fields(sql = %sql, format = %format))]
+    pub async fn query_raw_format(
+        &self,
+        sql: &str,
+        format: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static> {
+        validate_format_name(format)?;
+
+        let url = self.build_query_url(sql, format);
+        let headers = self.default_headers();
+
+        debug!(url = %url, "Executing HTTP raw format query");
+
+        let request = self.client.get(url).headers(headers);
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Server(format!("HTTP {status}: {body}")));
+        }
+
+        Ok(response
+            .bytes_stream()
+            .map_err(|e| Error::Network(format!("Failed to read response body: {e}"))))
+    }
+
     /// Execute DDL or non-returning query (CREATE, DROP, ALTER, etc).
     #[instrument(skip(self), fields(sql = %sql))]
     pub async fn execute(&self, sql: &str) -> Result<()> {
@@ -108,14 +236,39 @@ impl HttpClient {
 
         debug!(url = %url, "Executing HTTP DDL");
 
-        let response = self
-            .client
-            .post(url)
-            .headers(headers)
-            .send()
-            .instrument(trace_span!("http_request"))
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+        let request = self.client.post(url).headers(headers);
+        let response = self.send(request).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Server(format!("HTTP {status}: {body}")));
+        }
+
+        Ok(())
+    }
+
+    /// Execute DDL or non-returning query with `{name:Type}` parameters, see
+    /// [`HttpClient::query_params`].
+    ///
+    /// # Errors
+    /// Returns an error if an identifier parameter is invalid, the request fails, or the server
+    /// responds with a non-success status.
+    #[instrument(skip(self, params), fields(sql = %sql))]
+    pub async fn execute_params(&self, sql: &str, params: impl Into<QueryParams>) -> Result<()> {
+        let params = params.into();
+        params.validate_identifiers(sql)?;
+
+        let mut url = self.options.url.clone();
+        let _ = url.query_pairs_mut().append_pair("query", sql);
+        append_params(&mut url, sql, &params);
+
+        let headers = self.default_headers();
+
+        debug!(url = %url, "Executing HTTP DDL with parameters");
+
+        let request = self.client.post(url).headers(headers);
+        let response = self.send(request).await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -140,15 +293,8 @@ impl HttpClient {
 
         debug!(url = %url, body_size = body.len(), "Executing HTTP insert");
 
-        let response = self
-            .client
-            .post(url)
-            .headers(headers)
-            .body(body)
-            .send()
-            .instrument(trace_span!("http_request"))
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+        let request = self.client.post(url).headers(headers).body(body);
+        let response = self.send(request).await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -177,15 +323,8 @@ impl HttpClient {
 
         debug!(url = %url, body_size = body.len(), "Executing HTTP batch insert");
 
-        let response = self
-            .client
-            .post(url)
-            .headers(headers)
-            .body(body)
-            .send()
-            .instrument(trace_span!("http_request"))
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
+        let request = self.client.post(url).headers(headers).body(body);
+        let response = self.send(request).await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -215,6 +354,29 @@ impl HttpClient {
     }
 }
 
+/// Add `params` of `sql` to `url` as `param_<name>` pairs.
+///
+/// Over HTTP, parameter values are sent unquoted, so strings are only escaped if their placeholder
+/// is read in the escaped text format, as over the native protocol.
+fn append_params(url: &mut url::Url, sql: &str, params: &QueryParams) {
+    let mut pairs = url.query_pairs_mut();
+    for (name, value) in &params.0 {
+        let value = match value {
+            ParamValue::String(value) if reads_param_escaped(sql, name) => escape_text(value),
+            value => value.to_string(),
+        };
+        let _ = pairs.append_pair(&format!("param_{name}"), &value);
+    }
+}
+
+/// Format names are interpolated into the query, so only allow identifier characters.
+fn validate_format_name(format: &str) -> Result<()> {
+    if format.is_empty() || !format.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(Error::Client(format!("Invalid output format: {format:?}")));
+    }
+    Ok(())
+}
+
 /// Serialize multiple batches to `ArrowStream` format.
 fn serialize_batches(batches: &[RecordBatch]) -> Result<Bytes> {
     use arrow::ipc::writer::StreamWriter;
@@ -242,3 +404,78 @@ fn serialize_batches(batches: &[RecordBatch]) -> Result<Bytes> {
 
     Ok(Bytes::from(buffer))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_middleware() {
+        let client = HttpClient::new(HttpOptions::default())
+            .unwrap()
+            .with_request_middleware(|req| {
+                drop(req.headers_mut().insert("X-First", HeaderValue::from_static("1")));
+            })
+            .with_request_middleware(|req| {
+                // Runs after the first hook and sees its changes
+                let seen = req.headers().contains_key("X-First");
+                let value = HeaderValue::from_static(if seen { "after" } else { "before" });
+                drop(req.headers_mut().insert("X-Second", value));
+            });
+
+        let builder =
+            client.client.get(client.options.url.clone()).headers(client.default_headers());
+        let request = client.prepare(builder).unwrap();
+        assert_eq!(request.headers().get("X-First").unwrap(), "1");
+        assert_eq!(request.headers().get("X-Second").unwrap(), "after");
+        assert!(format!("{client:?}").contains("middleware: 2"));
+    }
+
+    #[test]
+    fn test_append_params() {
+        let mut url = url::Url::parse("http://localhost:8123/").unwrap();
+        let params = QueryParams::from(vec![
+            ("table", ParamValue::from("my-table")),
+            ("name", ParamValue::from("it's\ta")),
+            ("id", ParamValue::from(42)),
+        ]);
+        append_params(&mut url, "SELECT {id:UInt64} FROM {table:Identifier}", &params);
+        let pairs = url.query_pairs().into_owned().collect::<Vec<_>>();
+        assert_eq!(pairs, vec![
+            ("param_table".to_string(), "my-table".to_string()),
+            ("param_name".to_string(), "it's\\ta".to_string()),
+            ("param_id".to_string(), "42".to_string()),
+        ]);
+
+        // Array literals carry their own escapes, identifiers are substituted as is
+        let mut url = url::Url::parse("http://localhost:8123/").unwrap();
+        let params = QueryParams::from(vec![
+            ("tags", ParamValue::from("['it\\'s', 'a\\\\b']")),
+            ("table", ParamValue::from("a\\b")),
+        ]);
+        append_params(&mut url, "SELECT {tags:Array(String)} FROM {table:Identifier}", &params);
+        let pairs = url.query_pairs().into_owned().collect::<Vec<_>>();
+        assert_eq!(pairs, vec![
+            ("param_tags".to_string(), "['it\\'s', 'a\\\\b']".to_string()),
+            ("param_table".to_string(), "a\\b".to_string()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_query_params_rejects_invalid_identifiers() {
+        let client = HttpClient::new(HttpOptions::default()).unwrap();
+        let params = vec![("table", "events\0")];
+        let result = client.query_params("SELECT * FROM {table:Identifier}", params).await;
+        assert!(matches!(result, Err(Error::Client(_))));
+    }
+
+    #[test]
+    fn test_validate_format_name() {
+        assert!(validate_format_name("CSV").is_ok());
+        assert!(validate_format_name("JSONCompactEachRow").is_ok());
+        assert!(validate_format_name("Pretty_Space").is_ok());
+        assert!(validate_format_name("").is_err());
+        assert!(validate_format_name("CSV; DROP TABLE t").is_err());
+        assert!(validate_format_name("CSV SETTINGS x=1").is_err());
+    }
+}
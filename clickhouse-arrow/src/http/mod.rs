@@ -26,5 +26,6 @@ mod client;
 mod config;
 pub mod escape;
 
-pub use client::HttpClient;
+pub use client::{HttpClient, RequestMiddleware};
 pub use config::{DEFAULT_TIMEOUT_SECS, HttpOptions};
+pub use reqwest::Request;
@@ -153,10 +153,11 @@
 //! - **Behavior**: `ClickHouse` does not make the same distinction between `Utf8`, `Utf8View`, or
 //!   `LargeUtf8`. All of these are mapped to either `Type::Binary` (the default, see above) or
 //!   `Type::String`
-//! - **Option**: None
-//! - **Default**: Unsupported
-//! - **Impact**: When deserializing from `ClickHouse`, manual modification will be necessary to use
-//!   these data types.
+//! - **Option**: `large_offsets` (default: `false`) reads `String`, `Binary` and `Array` as
+//!   `LargeUtf8`/`LargeBinary`/`LargeList`.
+//! - **Default**: Disabled (`false`).
+//! - **Impact**: When deserializing from `ClickHouse`, `View` types need manual conversion, and
+//!   `Large` types are only produced when `large_offsets` is enabled.
 //!
 //! #### `Utf8` -> `Binary`
 //! - **Behavior**: By default, `Type::String`/`DataType::Utf8` will be represented as Binary.
@@ -174,7 +175,10 @@
 //!
 //! #### `LowCardinality(Nullable(...))` vs `Nullable(LowCardinality(...))`
 //! - **Behavior**: Like arrays mentioned above, `ClickHouse` does not allow nullable low
-//!   cardinality. The default behavior is to push down the nullability.
+//!   cardinality. The default behavior is to push down the nullability. Column types provided
+//!   directly, e.g. through `Row` definitions or schema conversions, are rewritten to
+//!   `LowCardinality(Nullable(...))` when generating DDL, and both forms are read as a nullable
+//!   Arrow `Dictionary`.
 //! - **Option**: `low_cardinality_nullable_error` (default: `false`).
 //! - **Default**: Disabled (`false`).
 //! - **Impact**: Enables flexible insertion but may cause schema mismatches if nulls are present.
@@ -213,30 +217,47 @@ compile_error!("Features `jemalloc` and `mimalloc` are mutually exclusive. Enabl
 
 pub mod arrow;
 mod client;
+pub mod cluster;
 mod compression;
 mod constants;
+pub mod defaults;
 mod errors;
 pub mod explain;
+pub mod explore;
 mod flags;
 mod formats;
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzz;
 #[cfg(feature = "http")]
 pub mod http;
 mod io;
 #[cfg(all(target_os = "linux", feature = "io-uring"))]
 pub mod io_uring;
 pub mod limits;
+pub mod maintenance;
+pub mod masking;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod native;
 #[cfg(feature = "pool")]
 mod pool;
 pub mod prelude;
 mod query;
 mod schema;
+#[cfg(feature = "server")]
+pub mod server;
 mod settings;
 pub mod simd;
+pub mod sink;
 pub mod spawn;
+pub mod spool;
+pub mod staging;
 pub mod telemetry;
-#[cfg(any(feature = "test-utils", feature = "tmpfs-size"))]
+#[cfg(any(test, feature = "test-utils", feature = "tmpfs-size"))]
 pub mod test_utils;
+pub mod upsert;
+pub mod validation;
 
 #[cfg(feature = "derive")]
 /// Derive macro for the [Row] trait.
@@ -269,12 +290,14 @@ pub mod test_utils;
 /// - For serialization, the ordering of fields in the struct declaration must match the order in the `INSERT` statement, respectively in the table declaration. See issue [#34](https://github.com/Protryon/clickhouse_arrow/issues/34).
 pub use clickhouse_arrow_derive::Row;
 pub use client::*;
+pub use cluster::ClusterClient;
 /// Set this environment to enable additional debugs around arrow (de)serialization.
 pub use constants::{CONN_READ_BUFFER_ENV_VAR, CONN_WRITE_BUFFER_ENV_VAR, DEBUG_ARROW_ENV_VAR};
 pub use errors::*;
-pub use formats::{ArrowFormat, ClientFormat, NativeFormat};
+pub use formats::{ArrowFormat, ClientFormat, LazyArrowFormat, NativeFormat};
 /// Contains useful top-level traits to interface with [`crate::prelude::NativeFormat`]
 pub use native::convert::*;
+pub use native::profile_events::ProfileEvents;
 pub use native::progress::Progress;
 pub use native::protocol::{ChunkedProtocolMode, ProfileEvent};
 /// Represents the types that `ClickHouse` supports internally.
@@ -284,8 +307,11 @@ pub use native::values::*;
 pub use native::{CompressionMethod, ServerError, Severity};
 #[cfg(feature = "pool")]
 pub use pool::*;
-pub use query::{ParamValue, ParsedQuery, Qid, QueryParams};
-pub use schema::CreateOptions;
+pub use query::{
+    ParamValue, ParsedQuery, Qid, QueryParams, StatementKind, classify_statement, quote_identifier,
+    validate_identifier,
+};
+pub use schema::{CreateOptions, MutationWait};
 pub use settings::{Setting, SettingValue, Settings};
 
 mod aliases {
@@ -7,15 +7,24 @@
 //!
 //! When limits are exceeded, results are truncated and a status indicator
 //! is provided to inform the caller that the results were cropped.
+//!
+//! Alternatively, `QueryOptions::with_max_result_rows` and `QueryOptions::with_max_result_bytes`
+//! treat an oversized result as an error: the query is cancelled on the server and the stream
+//! ends with [`Error::ResultLimitExceeded`](crate::Error::ResultLimitExceeded).
+//!
+//! Query timings (time to first batch and total stream duration) are measured by the
+//! connection and reported alongside the limit statistics.
 
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
 
 use arrow::record_batch::RecordBatch;
 use futures_util::Stream;
 use pin_project::pin_project;
 
-use crate::Result;
+use crate::{Error, Result};
 
 /// Reason why query results were truncated.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -42,15 +51,23 @@ impl std::fmt::Display for TruncationReason {
 #[derive(Debug, Clone, Copy, Default)]
 pub struct QueryStats {
     /// Total rows returned (after truncation).
-    pub rows_returned:     u64,
+    pub rows_returned:       u64,
     /// Total batches returned (after truncation).
-    pub batches_returned:  u64,
+    pub batches_returned:    u64,
     /// Total memory consumed by returned batches (bytes).
-    pub memory_bytes:      usize,
+    pub memory_bytes:        usize,
     /// Whether results were truncated.
-    pub truncated:         bool,
+    pub truncated:           bool,
     /// Reason for truncation, if any.
-    pub truncation_reason: Option<TruncationReason>,
+    pub truncation_reason:   Option<TruncationReason>,
+    /// Time from sending the query to decoding the first data block with rows.
+    ///
+    /// `None` until the first block arrives, or if the query returned no rows.
+    pub time_to_first_batch: Option<Duration>,
+    /// Time from sending the query to the server's end of stream.
+    ///
+    /// `None` while streaming, or if the stream was truncated before it completed.
+    pub stream_duration:     Option<Duration>,
 }
 
 impl QueryStats {
@@ -69,13 +86,38 @@ impl QueryStats {
         } else {
             String::new()
         };
+        let timing = match (self.time_to_first_batch, self.stream_duration) {
+            (Some(first), Some(total)) => format!(", first batch {first:?}, total {total:?}"),
+            (Some(first), None) => format!(", first batch {first:?}"),
+            (None, Some(total)) => format!(", total {total:?}"),
+            (None, None) => String::new(),
+        };
         format!(
-            "{} rows, {} batches, {} bytes{}",
-            self.rows_returned, self.batches_returned, self.memory_bytes, truncation
+            "{} rows, {} batches, {} bytes{timing}{truncation}",
+            self.rows_returned, self.batches_returned, self.memory_bytes
         )
     }
 }
 
+/// Timings of a single query, recorded by the connection as packets are decoded.
+///
+/// Both durations are measured from the moment the query is serialized and sent.
+#[derive(Debug, Default)]
+pub(crate) struct QueryTiming {
+    first_batch: OnceLock<Duration>,
+    total:       OnceLock<Duration>,
+}
+
+impl QueryTiming {
+    /// Record the time to the first decoded data block. Only the first call is kept.
+    pub(crate) fn record_first_batch(&self, elapsed: Duration) {
+        let _ = self.first_batch.set(elapsed);
+    }
+
+    /// Record the time to the end of the stream. Only the first call is kept.
+    pub(crate) fn record_total(&self, elapsed: Duration) { let _ = self.total.set(elapsed); }
+}
+
 /// Configuration for query result limits.
 ///
 /// All limits are optional. When a limit is reached, the stream stops
@@ -144,16 +186,20 @@ struct LimitState {
     total_memory:      usize,
     truncated:         bool,
     truncation_reason: Option<TruncationReason>,
+    timing:            Option<Arc<QueryTiming>>,
 }
 
 impl LimitState {
     fn to_stats(&self) -> QueryStats {
+        let timing = self.timing.as_deref();
         QueryStats {
-            rows_returned:     self.total_rows,
-            batches_returned:  self.total_batches,
-            memory_bytes:      self.total_memory,
-            truncated:         self.truncated,
-            truncation_reason: self.truncation_reason,
+            rows_returned:       self.total_rows,
+            batches_returned:    self.total_batches,
+            memory_bytes:        self.total_memory,
+            truncated:           self.truncated,
+            truncation_reason:   self.truncation_reason,
+            time_to_first_batch: timing.and_then(|t| t.first_batch.get().copied()),
+            stream_duration:     timing.and_then(|t| t.total.get().copied()),
         }
     }
 }
@@ -183,6 +229,12 @@ where
 
     /// Get the current statistics (can be called during or after streaming).
     pub fn stats(&self) -> QueryStats { self.state.to_stats() }
+
+    /// Attach the timings recorded by the connection executing the query.
+    pub(crate) fn with_timing(mut self, timing: Arc<QueryTiming>) -> Self {
+        self.state.timing = Some(timing);
+        self
+    }
 }
 
 impl<S> Stream for LimitedStream<S>
@@ -250,6 +302,108 @@ where
     }
 }
 
+/// Details of a query stopped by its result guard, see `QueryOptions::with_max_result_rows`.
+#[derive(Debug, Clone)]
+pub struct ResultLimitExceeded {
+    /// The limit that was exceeded, [`TruncationReason::RowLimit`] or
+    /// [`TruncationReason::MemoryLimit`].
+    pub reason:  TruncationReason,
+    /// Rows received when the query was stopped, including the batch that exceeded the limit.
+    pub rows:    u64,
+    /// Memory of the batches received when the query was stopped, in bytes.
+    pub bytes:   usize,
+    /// The results that fit within the limits, if requested with
+    /// `QueryOptions::with_partial_results`. Includes batches already yielded by the stream, so
+    /// collecting callers don't lose them, and the rows of the last batch that fit a row limit.
+    pub partial: Option<Vec<RecordBatch>>,
+}
+
+impl std::fmt::Display for ResultLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} after {} rows ({} bytes)", self.reason, self.rows, self.bytes)
+    }
+}
+
+/// A stream wrapper that fails, rather than truncates, a result exceeding a row or memory limit.
+///
+/// Once a limit is exceeded the inner stream is dropped, which cancels the query on the server
+/// when the connection was asked to, and a single [`Error::ResultLimitExceeded`] is yielded.
+#[pin_project]
+pub(crate) struct ResultGuard<S> {
+    #[pin]
+    inner:     Option<S>,
+    max_rows:  Option<u64>,
+    max_bytes: Option<usize>,
+    rows:      u64,
+    bytes:     usize,
+    partial:   Option<Vec<RecordBatch>>,
+}
+
+impl<S> ResultGuard<S> {
+    pub(crate) fn new(
+        inner: S,
+        max_rows: Option<u64>,
+        max_bytes: Option<usize>,
+        keep_partial: bool,
+    ) -> Self {
+        Self {
+            inner: Some(inner),
+            max_rows,
+            max_bytes,
+            rows: 0,
+            bytes: 0,
+            partial: keep_partial.then(Vec::new),
+        }
+    }
+}
+
+impl<S> Stream for ResultGuard<S>
+where
+    S: Stream<Item = Result<RecordBatch>>,
+{
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let Some(inner) = this.inner.as_mut().as_pin_mut() else {
+            return Poll::Ready(None);
+        };
+        let batch = match ready!(inner.poll_next(cx)) {
+            Some(Ok(batch)) => batch,
+            other => return Poll::Ready(other),
+        };
+
+        let previous_rows = *this.rows;
+        *this.rows += batch.num_rows() as u64;
+        *this.bytes += batch.get_array_memory_size();
+        let reason = if this.max_rows.is_some_and(|max| *this.rows > max) {
+            TruncationReason::RowLimit
+        } else if this.max_bytes.is_some_and(|max| *this.bytes > max) {
+            TruncationReason::MemoryLimit
+        } else {
+            if let Some(partial) = this.partial.as_mut() {
+                partial.push(batch.clone());
+            }
+            return Poll::Ready(Some(Ok(batch)));
+        };
+
+        // Stop reading, dropping the response cancels the query
+        this.inner.set(None);
+        let mut partial = this.partial.take();
+        if let (Some(partial), TruncationReason::RowLimit, Some(max)) =
+            (partial.as_mut(), reason, *this.max_rows)
+        {
+            let fits = usize::try_from(max - previous_rows).unwrap_or(usize::MAX);
+            if fits > 0 {
+                partial.push(batch.slice(0, fits));
+            }
+        }
+        let exceeded =
+            ResultLimitExceeded { reason, rows: *this.rows, bytes: *this.bytes, partial };
+        Poll::Ready(Some(Err(Error::ResultLimitExceeded(Box::new(exceeded)))))
+    }
+}
+
 /// Response wrapper that includes both the stream and access to stats.
 #[pin_project]
 pub struct LimitedResponse<S> {
@@ -266,6 +420,11 @@ where
         Self { stream: LimitedStream::new(inner, limits) }
     }
 
+    /// Attach the timings recorded by the connection executing the query.
+    pub(crate) fn with_timing(self, timing: Arc<QueryTiming>) -> Self {
+        Self { stream: self.stream.with_timing(timing) }
+    }
+
     /// Get the current statistics.
     ///
     /// This can be called at any time, including during streaming.
@@ -410,11 +569,12 @@ mod tests {
     #[tokio::test]
     async fn test_stats_summary() {
         let stats = QueryStats {
-            rows_returned:     1000,
-            batches_returned:  5,
-            memory_bytes:      8192,
-            truncated:         true,
+            rows_returned: 1000,
+            batches_returned: 5,
+            memory_bytes: 8192,
+            truncated: true,
             truncation_reason: Some(TruncationReason::RowLimit),
+            ..Default::default()
         };
 
         let summary = stats.summary();
@@ -425,6 +585,28 @@ mod tests {
         assert!(summary.contains("row limit exceeded"));
     }
 
+    #[tokio::test]
+    async fn test_stats_timing() {
+        let batches = vec![Ok(create_test_batch(10)), Ok(create_test_batch(10))];
+        let timing = Arc::new(QueryTiming::default());
+        let mut limited =
+            LimitedResponse::new(futures_util::stream::iter(batches), QueryLimits::none())
+                .with_timing(Arc::clone(&timing));
+
+        assert_eq!(limited.stats().time_to_first_batch, None);
+        assert_eq!(limited.stats().stream_duration, None);
+
+        timing.record_first_batch(Duration::from_millis(5));
+        timing.record_first_batch(Duration::from_millis(50));
+        while limited.next().await.is_some() {}
+        timing.record_total(Duration::from_millis(20));
+
+        let stats = limited.stats();
+        assert_eq!(stats.time_to_first_batch, Some(Duration::from_millis(5)));
+        assert_eq!(stats.stream_duration, Some(Duration::from_millis(20)));
+        assert!(stats.summary().contains("first batch 5ms, total 20ms"));
+    }
+
     #[tokio::test]
     async fn test_query_limits_builder() {
         let limits =
@@ -439,6 +621,50 @@ mod tests {
         assert!(!no_limits.has_limits());
     }
 
+    #[tokio::test]
+    async fn test_result_guard_rows() {
+        let batches = (0..5).map(|_| Ok(create_test_batch(100))).collect::<Vec<_>>();
+        let stream = futures_util::stream::iter(batches);
+        let mut guard = ResultGuard::new(stream, Some(250), None, true);
+
+        assert_eq!(guard.next().await.unwrap().unwrap().num_rows(), 100);
+        assert_eq!(guard.next().await.unwrap().unwrap().num_rows(), 100);
+        let Err(Error::ResultLimitExceeded(exceeded)) = guard.next().await.unwrap() else {
+            panic!("Expected the row limit to be exceeded");
+        };
+        assert!(guard.next().await.is_none());
+
+        assert_eq!(exceeded.reason, TruncationReason::RowLimit);
+        assert_eq!(exceeded.rows, 300);
+        let partial = exceeded.partial.unwrap();
+        assert_eq!(partial.iter().map(RecordBatch::num_rows).collect::<Vec<_>>(), vec![
+            100, 100, 50
+        ]);
+        assert!(exceeded.to_string().contains("row limit exceeded after 300 rows"));
+    }
+
+    #[tokio::test]
+    async fn test_result_guard_bytes() {
+        let batch_memory = create_test_batch(100).get_array_memory_size();
+        let batches = (0..3).map(|_| Ok(create_test_batch(100))).collect::<Vec<_>>();
+        let stream = futures_util::stream::iter(batches);
+        let results =
+            ResultGuard::new(stream, None, Some(batch_memory), false).collect::<Vec<_>>().await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        let Err(Error::ResultLimitExceeded(exceeded)) = &results[1] else {
+            panic!("Expected the memory limit to be exceeded");
+        };
+        assert_eq!(exceeded.reason, TruncationReason::MemoryLimit);
+        assert!(exceeded.partial.is_none());
+
+        // Without limits every batch passes through
+        let batches = (0..3).map(|_| Ok(create_test_batch(100))).collect::<Vec<_>>();
+        let stream = futures_util::stream::iter(batches);
+        assert_eq!(ResultGuard::new(stream, None, None, false).count().await, 3);
+    }
+
     // ========================================================================
     // Edge case tests
     // ========================================================================
@@ -0,0 +1,352 @@
+//! Background table maintenance.
+//!
+//! Ingestion services usually end up running `OPTIMIZE`, deduplication, or TTL statements on a
+//! timer, ie to merge the small parts left by frequent inserts or to enforce a TTL added after
+//! the data was written. A [`MaintenanceScheduler`] runs these statements in the background:
+//! each [`MaintenanceJob`] runs a [`MaintenanceTask`] on a table every `interval`.
+//!
+//! - Each wait is extended by a random delay of up to the job's `jitter`, so that many service
+//!   instances, or many jobs started together, do not all hit the server at once.
+//! - Runs never overlap: a job waits for its previous run to finish, and a run is skipped while
+//!   another job is still running on the same table.
+//! - Runs are logged, and reported to an optional hook as a [`MaintenanceRun`].
+//!
+//! Dropping the [`MaintenanceHandle`] returned by [`MaintenanceScheduler::start`] aborts all
+//! jobs, [`MaintenanceHandle::shutdown`] lets running statements finish first.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use std::time::Duration;
+//!
+//! use clickhouse_arrow::maintenance::{MaintenanceJob, MaintenanceScheduler, MaintenanceTask};
+//!
+//! let hour = Duration::from_secs(3600);
+//! let handle = MaintenanceScheduler::new(client.clone())
+//!     .with_job(MaintenanceJob::new("db.events", MaintenanceTask::OptimizeFinal, hour))
+//!     .with_job(
+//!         MaintenanceJob::new("db.sessions", MaintenanceTask::MaterializeTtl, 24 * hour)
+//!             .with_jitter(Duration::from_secs(600)),
+//!     )
+//!     .with_hook(|run| info!(table = run.table, outcome = ?run.outcome, "maintenance"))
+//!     .start();
+//!
+//! // On shutdown, let running statements finish
+//! handle.shutdown().await;
+//! ```
+use std::collections::HashSet;
+use std::hash::{BuildHasher, RandomState};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::sync::watch;
+
+use crate::formats::ClientFormat;
+use crate::prelude::*;
+use crate::spawn::SpawnedTask;
+
+/// Placeholder replaced by the table name in [`MaintenanceTask::Statement`].
+pub const TABLE_PLACEHOLDER: &str = "{table}";
+
+/// A maintenance statement run on a table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MaintenanceTask {
+    /// `OPTIMIZE TABLE`, scheduling a merge of the table's parts.
+    Optimize,
+    /// `OPTIMIZE TABLE ... FINAL`, merging each partition into a single part.
+    OptimizeFinal,
+    /// `OPTIMIZE TABLE ... FINAL DEDUPLICATE`, also removing fully duplicated rows.
+    Deduplicate,
+    /// `ALTER TABLE ... MATERIALIZE TTL`, applying the table's TTL rules to existing parts.
+    MaterializeTtl,
+    /// Any statement, with [`TABLE_PLACEHOLDER`] replaced by the table name.
+    Statement(String),
+}
+
+impl MaintenanceTask {
+    /// The statement run on `table`.
+    pub fn statement(&self, table: &str) -> String {
+        match self {
+            MaintenanceTask::Optimize => format!("OPTIMIZE TABLE {table}"),
+            MaintenanceTask::OptimizeFinal => format!("OPTIMIZE TABLE {table} FINAL"),
+            MaintenanceTask::Deduplicate => format!("OPTIMIZE TABLE {table} FINAL DEDUPLICATE"),
+            MaintenanceTask::MaterializeTtl => format!("ALTER TABLE {table} MATERIALIZE TTL"),
+            MaintenanceTask::Statement(statement) => statement.replace(TABLE_PLACEHOLDER, table),
+        }
+    }
+}
+
+/// A [`MaintenanceTask`] run on a table at an interval.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceJob {
+    /// The table, optionally qualified by database (e.g. `"db.events"`).
+    pub table:         String,
+    /// The statement to run.
+    pub task:          MaintenanceTask,
+    /// The time between the end of a run and the start of the next, before jitter.
+    pub interval:      Duration,
+    /// The maximum random delay added to each wait.
+    pub jitter:        Duration,
+    /// The time before the first run, before jitter. Defaults to `interval`.
+    pub initial_delay: Duration,
+}
+
+impl MaintenanceJob {
+    /// Run `task` on `table` every `interval`, without jitter.
+    pub fn new(table: impl Into<String>, task: MaintenanceTask, interval: Duration) -> Self {
+        Self {
+            table: table.into(),
+            task,
+            interval,
+            jitter: Duration::ZERO,
+            initial_delay: interval,
+        }
+    }
+
+    /// Add a random delay of up to `jitter` to each wait.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the time before the first run, ie `Duration::ZERO` to run as soon as started.
+    #[must_use]
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// The statement this job runs.
+    pub fn statement(&self) -> String { self.task.statement(&self.table) }
+}
+
+/// How a [`MaintenanceRun`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaintenanceOutcome {
+    /// The statement succeeded.
+    Completed,
+    /// The statement failed with this error. The job keeps running on schedule.
+    Failed(String),
+    /// The run was skipped as another job was running on the same table.
+    Skipped,
+}
+
+/// A single run of a [`MaintenanceJob`], reported to the scheduler's hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceRun {
+    /// The job's table.
+    pub table:     String,
+    /// The statement run.
+    pub statement: String,
+    /// How the run ended.
+    pub outcome:   MaintenanceOutcome,
+    /// How long the statement took, zero if skipped.
+    pub elapsed:   Duration,
+}
+
+/// Signature of a maintenance run hook.
+pub type MaintenanceHookFn = dyn Fn(&MaintenanceRun) + Send + Sync;
+
+/// Runs [`MaintenanceJob`]s in the background. See the [module documentation](self).
+pub struct MaintenanceScheduler<T: ClientFormat> {
+    client: Arc<Client<T>>,
+    jobs:   Vec<MaintenanceJob>,
+    hook:   Option<Arc<MaintenanceHookFn>>,
+}
+
+impl<T: ClientFormat> std::fmt::Debug for MaintenanceScheduler<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MaintenanceScheduler")
+            .field("jobs", &self.jobs)
+            .field("hook", &self.hook.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: ClientFormat> MaintenanceScheduler<T> {
+    /// Create a scheduler without jobs, running statements with `client`.
+    pub fn new(client: Client<T>) -> Self {
+        Self { client: Arc::new(client), jobs: vec![], hook: None }
+    }
+
+    /// Add a job.
+    #[must_use]
+    pub fn with_job(mut self, job: MaintenanceJob) -> Self {
+        self.jobs.push(job);
+        self
+    }
+
+    /// Set a callback invoked after every run, ie to record metrics or alert on failures.
+    #[must_use]
+    pub fn with_hook(mut self, hook: impl Fn(&MaintenanceRun) + Send + Sync + 'static) -> Self {
+        self.hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// The configured jobs.
+    pub fn jobs(&self) -> &[MaintenanceJob] { &self.jobs }
+
+    /// Start running the jobs in the background.
+    ///
+    /// Must be called within a tokio runtime.
+    pub fn start(self) -> MaintenanceHandle {
+        let (shutdown, signal) = watch::channel(false);
+        let running = Arc::new(Mutex::new(HashSet::new()));
+        let tasks = self
+            .jobs
+            .into_iter()
+            .map(|job| {
+                let runner = JobRunner {
+                    client: Arc::clone(&self.client),
+                    job,
+                    hook: self.hook.clone(),
+                    running: Arc::clone(&running),
+                };
+                SpawnedTask::spawn(runner.run(signal.clone()))
+            })
+            .collect();
+        MaintenanceHandle { tasks, shutdown }
+    }
+}
+
+/// Running maintenance jobs, aborted when dropped.
+#[derive(Debug)]
+pub struct MaintenanceHandle {
+    tasks:    Vec<SpawnedTask<()>>,
+    shutdown: watch::Sender<bool>,
+}
+
+impl MaintenanceHandle {
+    /// The number of running jobs.
+    pub fn jobs(&self) -> usize { self.tasks.len() }
+
+    /// Stop all jobs, waiting for running statements to finish.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(true);
+        for task in self.tasks {
+            if let Err(error) = task.join().await {
+                warn!(?error, "Maintenance job failed to stop cleanly");
+            }
+        }
+    }
+}
+
+/// The state of one job's background task.
+struct JobRunner<T: ClientFormat> {
+    client:  Arc<Client<T>>,
+    job:     MaintenanceJob,
+    hook:    Option<Arc<MaintenanceHookFn>>,
+    /// Tables with a statement in flight, shared by all jobs of a scheduler.
+    running: Arc<Mutex<HashSet<String>>>,
+}
+
+impl<T: ClientFormat> JobRunner<T> {
+    async fn run(self, mut shutdown: watch::Receiver<bool>) {
+        let mut delay = self.job.initial_delay;
+        loop {
+            let wait = delay + jitter(self.job.jitter);
+            tokio::select! {
+                () = tokio::time::sleep(wait) => {}
+                _ = shutdown.wait_for(|stop| *stop) => return,
+            }
+            let run = self.run_once().await;
+            let (table, statement) = (&run.table, &run.statement);
+            match &run.outcome {
+                MaintenanceOutcome::Completed => {
+                    debug!(table, statement, elapsed = ?run.elapsed, "Maintenance run completed");
+                }
+                MaintenanceOutcome::Failed(error) => {
+                    warn!(table, statement, error, "Maintenance run failed");
+                }
+                MaintenanceOutcome::Skipped => {
+                    debug!(table, statement, "Maintenance run skipped, table busy");
+                }
+            }
+            if let Some(hook) = self.hook.as_ref() {
+                hook(&run);
+            }
+            delay = self.job.interval;
+        }
+    }
+
+    async fn run_once(&self) -> MaintenanceRun {
+        let statement = self.job.statement();
+        let mut run = MaintenanceRun {
+            table: self.job.table.clone(),
+            statement,
+            outcome: MaintenanceOutcome::Skipped,
+            elapsed: Duration::ZERO,
+        };
+        if !self.running.lock().insert(self.job.table.clone()) {
+            return run;
+        }
+        let started = Instant::now();
+        let result = self.client.execute(run.statement.as_str(), None).await;
+        let _ = self.running.lock().remove(&self.job.table);
+        run.elapsed = started.elapsed();
+        run.outcome = match result {
+            Ok(()) => MaintenanceOutcome::Completed,
+            Err(error) => MaintenanceOutcome::Failed(error.to_string()),
+        };
+        run
+    }
+}
+
+/// A random delay of up to `max`.
+fn jitter(max: Duration) -> Duration {
+    let max_nanos = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    if max_nanos == 0 {
+        return Duration::ZERO;
+    }
+    // `RandomState` is randomly keyed, plenty for spreading runs out
+    let random = RandomState::new().hash_one(Instant::now());
+    Duration::from_nanos(random % max_nanos.saturating_add(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_statements() {
+        let statements = [
+            MaintenanceTask::Optimize,
+            MaintenanceTask::OptimizeFinal,
+            MaintenanceTask::Deduplicate,
+            MaintenanceTask::MaterializeTtl,
+            MaintenanceTask::Statement("ALTER TABLE {table} DELETE WHERE ts < now() - 60".into()),
+        ]
+        .map(|task| task.statement("db.events"));
+        assert_eq!(statements, [
+            "OPTIMIZE TABLE db.events",
+            "OPTIMIZE TABLE db.events FINAL",
+            "OPTIMIZE TABLE db.events FINAL DEDUPLICATE",
+            "ALTER TABLE db.events MATERIALIZE TTL",
+            "ALTER TABLE db.events DELETE WHERE ts < now() - 60",
+        ]);
+    }
+
+    #[test]
+    fn test_job_defaults() {
+        let hour = Duration::from_secs(3600);
+        let job = MaintenanceJob::new("events", MaintenanceTask::Optimize, hour);
+        assert_eq!(job.initial_delay, hour);
+        assert_eq!(job.jitter, Duration::ZERO);
+        let job = job.with_initial_delay(Duration::ZERO).with_jitter(Duration::from_secs(60));
+        assert_eq!(job.initial_delay, Duration::ZERO);
+        assert_eq!(job.jitter, Duration::from_secs(60));
+        assert_eq!(job.statement(), "OPTIMIZE TABLE events");
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+        let max = Duration::from_millis(50);
+        assert!((0..100).map(|_| jitter(max)).all(|delay| delay <= max));
+        // Not stuck on a single value
+        let delays = (0..100).map(|_| jitter(max)).collect::<HashSet<_>>();
+        assert!(delays.len() > 1);
+    }
+}
@@ -0,0 +1,464 @@
+//! Masking of sensitive columns before inserts leave the process.
+//!
+//! [`InsertMasking`] holds an ordered set of named column transforms, each scoped to a table.
+//! Before an insert block is validated and serialized, every transform configured for the insert's
+//! target table replaces its column, so personal data can be hashed or redacted client-side and
+//! never reaches the network in the clear. Each insert that applied transforms produces a
+//! [`MaskingAudit`], logged and passed to the optional audit hook.
+//!
+//! A few transforms are built in:
+//! - [`InsertMasking::with_redaction`]: replace every non-null string with a constant.
+//! - [`InsertMasking::with_nulls`]: replace every value with null. The column must be `Nullable`.
+//! - [`InsertMasking::with_hash`]: replace every non-null string or binary value with the output of
+//!   a hash function, such as a keyed HMAC, keeping values joinable without revealing them.
+//!
+//! Custom transforms are plain closures (`Fn(&ArrayRef) -> Result<ArrayRef>`), which must return
+//! an array of the same length.
+//!
+//! Masking is configured on the client via [`crate::ClientBuilder::with_insert_masking`] and
+//! applies to [`crate::ArrowFormat`] inserts whose target table can be read from the
+//! `INSERT INTO` statement. Unqualified tables are qualified with the client's default database.
+//!
+//! # Examples
+//! ```rust,ignore
+//! use clickhouse_arrow::prelude::*;
+//! use clickhouse_arrow::masking::InsertMasking;
+//!
+//! let masking = InsertMasking::new()
+//!     .with_redaction("analytics.users", "email", "<redacted>")
+//!     .with_nulls("analytics.users", "phone")
+//!     .with_hash("*", "ip", |value| hmac_hex(&key, value))
+//!     .with_audit(|audit| info!(table = audit.table, masks = ?audit.applied, "masked insert"));
+//!
+//! let client = Client::builder()
+//!     .with_endpoint("localhost:9000")
+//!     .with_insert_masking(masking)
+//!     .build_arrow()
+//!     .await?;
+//! ```
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, BinaryBuilder, StringArray, new_null_array};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+
+use crate::{Error, Result};
+
+/// Signature of a column masking transform.
+pub type MaskFn = dyn Fn(&ArrayRef) -> Result<ArrayRef> + Send + Sync;
+
+/// Signature of a masking audit hook.
+pub type MaskingAuditFn = dyn Fn(&MaskingAudit) + Send + Sync;
+
+/// Table pattern matching every table.
+pub const ANY_TABLE: &str = "*";
+
+/// A transform applied to a column of an insert.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMask {
+    /// The masked column
+    pub column:    String,
+    /// The name of the transform
+    pub transform: String,
+    /// The number of non-null values the transform replaced
+    pub values:    usize,
+}
+
+/// The transforms applied to a single insert block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaskingAudit {
+    /// The insert's target table, qualified with its database
+    pub table:   String,
+    /// The transforms applied, in order
+    pub applied: Vec<AppliedMask>,
+}
+
+#[derive(Clone)]
+struct MaskRule {
+    table:     Arc<str>,
+    column:    Arc<str>,
+    transform: Arc<str>,
+    mask:      Arc<MaskFn>,
+}
+
+/// An ordered collection of named column transforms, scoped to tables.
+///
+/// Tables are matched by name, `"db.table"` matching that table only, `"table"` matching the table
+/// in any database, and [`ANY_TABLE`] matching every table. Transforms for columns missing from a
+/// block are skipped. Cloning is cheap, the underlying callbacks are shared.
+#[derive(Clone, Default)]
+pub struct InsertMasking {
+    rules: Vec<MaskRule>,
+    audit: Option<Arc<MaskingAuditFn>>,
+}
+
+impl std::fmt::Debug for InsertMasking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rules = self
+            .rules
+            .iter()
+            .map(|r| format!("{}.{}: {}", r.table, r.column, r.transform))
+            .collect::<Vec<_>>();
+        f.debug_struct("InsertMasking")
+            .field("rules", &rules)
+            .field("audit", &self.audit.is_some())
+            .finish()
+    }
+}
+
+impl InsertMasking {
+    /// Create an empty set of transforms.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns true if no transforms are configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.rules.is_empty() }
+
+    /// Returns the number of configured transforms.
+    #[must_use]
+    pub fn len(&self) -> usize { self.rules.len() }
+
+    /// Add a custom transform of `column` in `table`. The name is recorded in audits and errors.
+    #[must_use]
+    pub fn with_mask<F>(
+        mut self,
+        table: impl Into<Arc<str>>,
+        column: impl Into<Arc<str>>,
+        transform: impl Into<Arc<str>>,
+        mask: F,
+    ) -> Self
+    where
+        F: Fn(&ArrayRef) -> Result<ArrayRef> + Send + Sync + 'static,
+    {
+        self.rules.push(MaskRule {
+            table:     table.into(),
+            column:    column.into(),
+            transform: transform.into(),
+            mask:      Arc::new(mask),
+        });
+        self
+    }
+
+    /// Replace every non-null value of a string column with `replacement`.
+    #[must_use]
+    pub fn with_redaction(
+        self,
+        table: impl Into<Arc<str>>,
+        column: impl Into<Arc<str>>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        let replacement = replacement.into();
+        self.with_mask(table, column, "redact", move |array| redact(array, &replacement))
+    }
+
+    /// Replace every value of a column with null. The target column must be `Nullable`.
+    #[must_use]
+    pub fn with_nulls(self, table: impl Into<Arc<str>>, column: impl Into<Arc<str>>) -> Self {
+        self.with_mask(table, column, "null", |array| {
+            Ok(new_null_array(array.data_type(), array.len()))
+        })
+    }
+
+    /// Replace every non-null value of a string or binary column with `hash(value)`.
+    ///
+    /// Use a keyed hash, such as an HMAC with a secret key, so low-entropy values like emails
+    /// can't be recovered by hashing candidates.
+    #[must_use]
+    pub fn with_hash<F>(
+        self,
+        table: impl Into<Arc<str>>,
+        column: impl Into<Arc<str>>,
+        hash: F,
+    ) -> Self
+    where
+        F: Fn(&[u8]) -> String + Send + Sync + 'static,
+    {
+        self.with_mask(table, column, "hash", move |array| hash_values(array, &hash))
+    }
+
+    /// Set a hook called with the audit of every insert block that applied transforms.
+    #[must_use]
+    pub fn with_audit(mut self, audit: impl Fn(&MaskingAudit) + Send + Sync + 'static) -> Self {
+        self.audit = Some(Arc::new(audit));
+        self
+    }
+
+    /// Apply the transforms configured for `table` to `batch`.
+    ///
+    /// `table` is the insert's target, qualified with its database. Masked columns with nulls are
+    /// marked nullable in the returned batch's schema.
+    ///
+    /// # Errors
+    /// Returns [`Error::BlockValidation`] naming the failing transform.
+    pub fn apply(&self, table: &str, batch: RecordBatch) -> Result<(RecordBatch, MaskingAudit)> {
+        let (batch, audit) = self.apply_unaudited(table, batch)?;
+        if !audit.applied.is_empty() {
+            tracing::debug!(table, applied = ?audit.applied, "Masked insert columns");
+            if let Some(hook) = self.audit.as_ref() {
+                hook(&audit);
+            }
+        }
+        Ok((batch, audit))
+    }
+
+    /// Apply the transforms configured for `table` without calling the audit hook, for dry runs
+    /// of inserts that are never sent.
+    pub(crate) fn apply_unaudited(
+        &self,
+        table: &str,
+        batch: RecordBatch,
+    ) -> Result<(RecordBatch, MaskingAudit)> {
+        let mut audit = MaskingAudit { table: table.to_string(), applied: vec![] };
+        let rules = self.rules.iter().filter(|rule| table_matches(&rule.table, table));
+        let mut rules = rules.peekable();
+        if rules.peek().is_none() {
+            return Ok((batch, audit));
+        }
+        let schema = batch.schema();
+        let mut columns = batch.columns().to_vec();
+        let mut fields = schema.fields().iter().map(|f| f.as_ref().clone()).collect::<Vec<_>>();
+
+        for rule in rules {
+            let Some(index) = fields.iter().position(|f| f.name() == rule.column.as_ref()) else {
+                continue;
+            };
+            let failed = |message: String| Error::BlockValidation {
+                validator: format!("mask {} of {}", rule.transform, rule.column),
+                message,
+            };
+            let original = &columns[index];
+            let masked = (rule.mask)(original).map_err(|e| failed(e.to_string()))?;
+            if masked.len() != original.len() {
+                return Err(failed(format!(
+                    "returned {} values for {} rows",
+                    masked.len(),
+                    original.len()
+                )));
+            }
+            let values = original.len() - original.null_count();
+            let field = &fields[index];
+            let nullable = field.is_nullable() || masked.null_count() > 0;
+            fields[index] = Field::new(field.name(), masked.data_type().clone(), nullable)
+                .with_metadata(field.metadata().clone());
+            columns[index] = masked;
+            audit.applied.push(AppliedMask {
+                column: rule.column.to_string(),
+                transform: rule.transform.to_string(),
+                values,
+            });
+        }
+
+        let schema = Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()));
+        let batch = RecordBatch::try_new(schema, columns)?;
+        Ok((batch, audit))
+    }
+}
+
+fn table_matches(pattern: &str, table: &str) -> bool {
+    pattern == ANY_TABLE
+        || pattern == table
+        || (!pattern.contains('.') && table.rsplit_once('.').is_some_and(|(_, t)| t == pattern))
+}
+
+fn redact(array: &ArrayRef, replacement: &str) -> Result<ArrayRef> {
+    if !matches!(
+        array.data_type(),
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View | DataType::Dictionary(..)
+    ) {
+        return Err(Error::ArrowUnsupportedType(format!(
+            "Only string columns can be redacted, found {}",
+            array.data_type()
+        )));
+    }
+    let redacted = (0..array.len())
+        .map(|row| array.is_valid(row).then_some(replacement))
+        .collect::<StringArray>();
+    Ok(cast(&redacted, array.data_type())?)
+}
+
+fn hash_values(array: &ArrayRef, hash: &dyn Fn(&[u8]) -> String) -> Result<ArrayRef> {
+    let data_type = array.data_type();
+    let binary = match data_type {
+        DataType::Binary => Arc::clone(array),
+        DataType::Utf8
+        | DataType::LargeUtf8
+        | DataType::Utf8View
+        | DataType::LargeBinary
+        | DataType::BinaryView
+        | DataType::Dictionary(..) => cast(array, &DataType::Binary)?,
+        dt => {
+            return Err(Error::ArrowUnsupportedType(format!(
+                "Only string and binary columns can be hashed, found {dt}"
+            )));
+        }
+    };
+    let mut builder = BinaryBuilder::with_capacity(binary.len(), binary.len() * 32);
+    for value in binary.as_binary::<i32>() {
+        builder.append_option(value.map(hash));
+    }
+    // Hashes are valid UTF-8, so string columns keep their type
+    Ok(cast(&builder.finish(), data_type)?)
+}
+
+/// Extract the target table of an `INSERT INTO` statement, as an optional database and a table.
+///
+/// Returns `None` for statements that aren't inserts into a named table, such as inserts into
+/// table functions.
+pub(crate) fn insert_table(query: &str) -> Option<(Option<String>, String)> {
+    let mut rest = query.trim_start();
+    for keyword in ["INSERT", "INTO"] {
+        rest = strip_keyword(rest, keyword)?;
+    }
+    if let Some(after) = strip_keyword(rest, "TABLE") {
+        rest = after;
+    }
+    if strip_keyword(rest, "FUNCTION").is_some() {
+        return None;
+    }
+    let (first, rest) = identifier(rest)?;
+    match rest.strip_prefix('.') {
+        Some(rest) => Some((Some(first), identifier(rest)?.0)),
+        None => Some((None, first)),
+    }
+}
+
+fn strip_keyword<'a>(input: &'a str, keyword: &str) -> Option<&'a str> {
+    let head = input.get(..keyword.len())?;
+    let rest = &input[keyword.len()..];
+    (head.eq_ignore_ascii_case(keyword) && rest.starts_with(char::is_whitespace))
+        .then(|| rest.trim_start())
+}
+
+/// Read a bare or quoted identifier, returning it unquoted along with the remaining input.
+fn identifier(input: &str) -> Option<(String, &str)> {
+    let quote = input.chars().next().filter(|c| *c == '`' || *c == '"');
+    if let Some(quote) = quote {
+        let mut name = String::new();
+        let mut chars = input[1..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => name.push(chars.next()?.1),
+                c if c == quote => return Some((name, &input[i + 2..])),
+                c => name.push(c),
+            }
+        }
+        return None;
+    }
+    let end = input.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(input.len());
+    (end > 0).then(|| (input[..end].to_string(), &input[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{BinaryArray, Int64Array, LargeStringArray};
+
+    use super::*;
+
+    fn test_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("email", DataType::Utf8, false),
+            Field::new("phone", DataType::LargeUtf8, true),
+        ]));
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(StringArray::from(vec!["a@x.com", "b@x.com"])),
+            Arc::new(LargeStringArray::from(vec![Some("555"), None])),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_insert_table() {
+        let parse = insert_table;
+        assert_eq!(parse("INSERT INTO t VALUES"), Some((None, "t".into())));
+        assert_eq!(
+            parse("  insert into db.t FORMAT Native"),
+            Some((Some("db".into()), "t".into()))
+        );
+        assert_eq!(
+            parse("INSERT INTO TABLE `my db`.\"t-1\"(a, b) VALUES"),
+            Some((Some("my db".into()), "t-1".into()))
+        );
+        assert_eq!(parse("INSERT INTO `a\\`b` VALUES"), Some((None, "a`b".into())));
+        assert_eq!(parse("INSERT INTO FUNCTION remote('h', db.t) VALUES"), None);
+        assert_eq!(parse("SELECT 1"), None);
+        assert_eq!(parse("INSERT INTO `unterminated"), None);
+    }
+
+    #[test]
+    fn test_table_matches() {
+        assert!(table_matches("*", "db.t"));
+        assert!(table_matches("db.t", "db.t"));
+        assert!(table_matches("t", "db.t"));
+        assert!(!table_matches("other.t", "db.t"));
+        assert!(!table_matches("u", "db.t"));
+    }
+
+    #[test]
+    fn test_builtin_masks() {
+        let audits = Arc::new(parking_lot::Mutex::new(vec![]));
+        let recorded = Arc::clone(&audits);
+        let masking = InsertMasking::new()
+            .with_redaction("users", "email", "<redacted>")
+            .with_nulls("db.users", "phone")
+            .with_hash("other", "email", |v| format!("{}", v.len()))
+            .with_audit(move |audit| recorded.lock().push(audit.clone()));
+        assert_eq!(masking.len(), 3);
+        assert!(format!("{masking:?}").contains("users.email: redact"));
+
+        let (batch, audit) = masking.apply("db.users", test_batch()).unwrap();
+        let email = batch.column_by_name("email").unwrap().as_string::<i32>();
+        assert_eq!(email.iter().collect::<Vec<_>>(), vec![Some("<redacted>"); 2]);
+        let phone = batch.column_by_name("phone").unwrap();
+        assert_eq!(phone.data_type(), &DataType::LargeUtf8);
+        assert_eq!(phone.null_count(), 2);
+        assert_eq!(audit.applied, vec![
+            AppliedMask { column: "email".into(), transform: "redact".into(), values: 2 },
+            AppliedMask { column: "phone".into(), transform: "null".into(), values: 1 },
+        ]);
+        assert_eq!(audits.lock().as_slice(), &[audit]);
+
+        // No rules for the table, the batch passes through
+        let (batch, audit) = masking.apply("db.events", test_batch()).unwrap();
+        assert_eq!(batch, test_batch());
+        assert!(audit.applied.is_empty());
+        assert_eq!(audits.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_hash_mask() {
+        let masking = InsertMasking::new()
+            .with_hash("*", "email", |v| format!("h{}", v.len()))
+            .with_hash("*", "phone", |v| format!("h{}", v.len()));
+        let (batch, _) = masking.apply("db.t", test_batch()).unwrap();
+        let email = batch.column_by_name("email").unwrap().as_string::<i32>();
+        assert_eq!(email.iter().collect::<Vec<_>>(), vec![Some("h7"), Some("h7")]);
+        let phone = batch.column_by_name("phone").unwrap().as_string::<i64>();
+        assert_eq!(phone.iter().collect::<Vec<_>>(), vec![Some("h3"), None]);
+
+        let binary: ArrayRef = Arc::new(BinaryArray::from(vec![&b"abc"[..]]));
+        let hashed = hash_values(&binary, &|v| format!("{}", v.len())).unwrap();
+        assert_eq!(hashed.as_binary::<i32>().value(0), b"3");
+    }
+
+    #[test]
+    fn test_mask_errors() {
+        let masking = InsertMasking::new().with_redaction("*", "id", "x");
+        let error = masking.apply("db.t", test_batch()).unwrap_err();
+        assert!(matches!(error, Error::BlockValidation { .. }));
+        assert!(error.to_string().contains("mask redact of id"));
+
+        let masking =
+            InsertMasking::new().with_mask("*", "id", "truncate", |array| Ok(array.slice(0, 1)));
+        let error = masking.apply("db.t", test_batch()).unwrap_err();
+        assert!(error.to_string().contains("returned 1 values for 2 rows"));
+
+        // Nulls in a non-nullable column mark it nullable
+        let masking = InsertMasking::new().with_nulls("*", "id");
+        let (batch, _) = masking.apply("db.t", test_batch()).unwrap();
+        assert!(batch.schema_ref().field(0).is_nullable());
+    }
+}
@@ -0,0 +1,607 @@
+//! ## Prometheus metrics for clients.
+//!
+//! [`ClientMetrics`] collects statement counts, latencies, rows read and written, insert
+//! throughput, connection pool state, query queues, and [`BUFFER_POOL`] usage, rendering them in
+//! the Prometheus text exposition format. [`MetricsServer`] serves the rendered metrics on a
+//! `/metrics` endpoint so services embedding this crate can be scraped without wiring up an HTTP
+//! framework.
+//!
+//! ```rust,ignore
+//! use clickhouse_arrow::metrics::{ClientMetrics, MetricsServer};
+//! use clickhouse_arrow::prelude::*;
+//!
+//! let metrics = ClientMetrics::new();
+//! let client = ClientBuilder::new()
+//!     .with_endpoint("localhost:9000")
+//!     .with_statement_hook(metrics.statement_hook())
+//!     .build_arrow()
+//!     .await?;
+//!
+//! // Scrape http://127.0.0.1:9464/metrics
+//! let server = MetricsServer::bind("127.0.0.1:9464", metrics.clone()).await?;
+//! ```
+//!
+//! Statement metrics are fed by a [`StatementHook`], so every client sharing the hook reports
+//! into the same registry. Insert bytes are only known to the caller, record them with
+//! [`ClientMetrics::record_insert`].
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use arrow::record_batch::RecordBatch;
+use parking_lot::Mutex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::{QueueStats, Result};
+use crate::simd::BUFFER_POOL;
+use crate::spawn::SpawnedTask;
+use crate::telemetry::{StatementEvent, StatementHook};
+
+/// Upper bounds, in seconds, of the statement latency histogram buckets.
+pub const LATENCY_BUCKETS: [f64; 12] =
+    [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Maximum size of an HTTP request head accepted by [`MetricsServer`].
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// How long [`MetricsServer`] waits for a scraper to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Point in time state of a connection pool registered with [`ClientMetrics`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolSnapshot {
+    /// Connections currently held by the pool, idle or checked out.
+    pub connections: u32,
+    /// Connections idle in the pool.
+    pub idle:        u32,
+    /// Per-lane metrics, for priority pools.
+    #[cfg(feature = "pool")]
+    pub lanes:       Vec<(crate::Priority, crate::LaneStats)>,
+}
+
+type PoolSource = Arc<dyn Fn() -> PoolSnapshot + Send + Sync>;
+
+type QueueSource = Arc<dyn Fn() -> Option<QueueStats> + Send + Sync>;
+
+/// A cumulative latency histogram with fixed [`LATENCY_BUCKETS`].
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets:    [AtomicU64; LATENCY_BUCKETS.len()],
+    count:      AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            let _ = self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        let _ = self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+        let _ = self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[expect(clippy::cast_precision_loss)]
+    fn render(&self, out: &mut String, name: &str) {
+        let mut cumulative = 0;
+        for (bound, bucket) in LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {count}");
+        let _ = writeln!(out, "{name}_sum {sum}");
+        let _ = writeln!(out, "{name}_count {count}");
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    statements:     AtomicU64,
+    errors:         AtomicU64,
+    latency:        Histogram,
+    read_rows:      AtomicU64,
+    written_rows:   AtomicU64,
+    insert_batches: AtomicU64,
+    insert_rows:    AtomicU64,
+    insert_bytes:   AtomicU64,
+    pools:          Mutex<Vec<(String, PoolSource)>>,
+    queues:         Mutex<Vec<(String, QueueSource)>>,
+}
+
+/// A registry of client metrics, rendered in the Prometheus text exposition format.
+///
+/// Cloning is cheap and clones share the same counters.
+#[derive(Clone, Default)]
+pub struct ClientMetrics(Arc<Registry>);
+
+impl ClientMetrics {
+    /// Create an empty registry.
+    pub fn new() -> Self { Self::default() }
+
+    /// A [`StatementHook`] recording statement counts, errors, latencies, and rows into this
+    /// registry. Pass it to
+    /// [`ClientBuilder::with_statement_hook`](crate::ClientBuilder::with_statement_hook).
+    pub fn statement_hook(&self) -> StatementHook {
+        let metrics = self.clone();
+        StatementHook::new(move |event| metrics.record_statement(event))
+    }
+
+    /// Record a completed statement. Called by [`Self::statement_hook`].
+    pub fn record_statement(&self, event: &StatementEvent) {
+        let registry = &self.0;
+        let _ = registry.statements.fetch_add(1, Ordering::Relaxed);
+        if event.is_error() {
+            let _ = registry.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        registry.latency.observe(event.duration);
+        let _ = registry.read_rows.fetch_add(event.read_rows, Ordering::Relaxed);
+        let _ = registry.written_rows.fetch_add(event.written_rows, Ordering::Relaxed);
+    }
+
+    /// Record a batch sent by an insert, counting its rows and Arrow memory size.
+    pub fn record_insert(&self, batch: &RecordBatch) {
+        let registry = &self.0;
+        let _ = registry.insert_batches.fetch_add(1, Ordering::Relaxed);
+        let _ = registry.insert_rows.fetch_add(batch.num_rows() as u64, Ordering::Relaxed);
+        let bytes = batch.get_array_memory_size() as u64;
+        let _ = registry.insert_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Register a custom pool source, reported under the `pool` label `name`.
+    ///
+    /// The source is called on every scrape, so it should be cheap.
+    pub fn register_pool_source(
+        &self,
+        name: impl Into<String>,
+        source: impl Fn() -> PoolSnapshot + Send + Sync + 'static,
+    ) {
+        self.0.pools.lock().push((name.into(), Arc::new(source)));
+    }
+
+    /// Report the state of a [`ConnectionPool`](crate::ConnectionPool) under the `pool` label
+    /// `name`.
+    #[cfg(feature = "pool")]
+    pub fn register_pool<T: crate::ClientFormat>(
+        &self,
+        name: impl Into<String>,
+        pool: crate::ConnectionPool<T>,
+    ) {
+        self.register_pool_source(name, move || {
+            let state = pool.state();
+            PoolSnapshot {
+                connections: state.connections,
+                idle:        state.idle_connections,
+                lanes:       Vec::new(),
+            }
+        });
+    }
+
+    /// Report the state of a [`PriorityPool`](crate::PriorityPool), including per-lane metrics,
+    /// under the `pool` label `name`.
+    #[cfg(feature = "pool")]
+    pub fn register_priority_pool<T: crate::ClientFormat>(
+        &self,
+        name: impl Into<String>,
+        pool: crate::PriorityPool<T>,
+    ) {
+        use crate::Priority;
+
+        self.register_pool_source(name, move || {
+            let state = pool.pool().state();
+            PoolSnapshot {
+                connections: state.connections,
+                idle:        state.idle_connections,
+                lanes:       [Priority::Interactive, Priority::Background]
+                    .into_iter()
+                    .map(|priority| (priority, pool.stats(priority)))
+                    .collect(),
+            }
+        });
+    }
+
+    /// Register a custom query queue source, reported under the `client` label `name`. Nothing
+    /// is reported while the source returns `None`.
+    ///
+    /// The source is called on every scrape, so it should be cheap.
+    pub fn register_queue_source(
+        &self,
+        name: impl Into<String>,
+        source: impl Fn() -> Option<QueueStats> + Send + Sync + 'static,
+    ) {
+        self.0.queues.lock().push((name.into(), Arc::new(source)));
+    }
+
+    /// Report the query queue of a client, see
+    /// [`ClientBuilder::with_query_queue`](crate::ClientBuilder::with_query_queue), under the
+    /// `client` label `name`. Nothing is reported unless the client caps concurrent queries.
+    pub fn register_queue<T: crate::ClientFormat>(
+        &self,
+        name: impl Into<String>,
+        client: &crate::Client<T>,
+    ) {
+        self.register_queue_source(name, client.queue_source());
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let registry = &self.0;
+        let mut out = String::with_capacity(4096);
+
+        let counters = [
+            ("clickhouse_statements_total", "Statements completed", &registry.statements),
+            ("clickhouse_statement_errors_total", "Statements that failed", &registry.errors),
+            ("clickhouse_rows_read_total", "Rows read by the server", &registry.read_rows),
+            ("clickhouse_rows_written_total", "Rows written by the server", &registry.written_rows),
+            (
+                "clickhouse_insert_batches_total",
+                "Batches sent by inserts",
+                &registry.insert_batches,
+            ),
+            ("clickhouse_insert_rows_total", "Rows sent by inserts", &registry.insert_rows),
+            (
+                "clickhouse_insert_bytes_total",
+                "Arrow bytes sent by inserts",
+                &registry.insert_bytes,
+            ),
+        ];
+        for (name, help, value) in counters {
+            header(&mut out, name, help, "counter");
+            let _ = writeln!(out, "{name} {}", value.load(Ordering::Relaxed));
+        }
+
+        let name = "clickhouse_statement_duration_seconds";
+        header(&mut out, name, "Statement latency", "histogram");
+        registry.latency.render(&mut out, name);
+
+        self.render_pools(&mut out);
+        self.render_queues(&mut out);
+
+        let stats = BUFFER_POOL.stats();
+        let name = "clickhouse_buffer_pool_buffers";
+        header(&mut out, name, "Buffers held by the global buffer pool", "gauge");
+        for (tier, count) in [
+            ("tiny", stats.tiny_count),
+            ("small", stats.small_count),
+            ("medium", stats.medium_count),
+            ("large", stats.large_count),
+            ("xlarge", stats.xlarge_count),
+        ] {
+            let _ = writeln!(out, "{name}{{tier=\"{tier}\"}} {count}");
+        }
+
+        out
+    }
+
+    fn render_pools(&self, out: &mut String) {
+        let pools = self.0.pools.lock().clone();
+        if pools.is_empty() {
+            return;
+        }
+        let snapshots =
+            pools.iter().map(|(name, source)| (escape_label(name), source())).collect::<Vec<_>>();
+
+        let name = "clickhouse_pool_connections";
+        header(out, name, "Connections held by the pool", "gauge");
+        for (pool, snapshot) in &snapshots {
+            let _ = writeln!(out, "{name}{{pool=\"{pool}\"}} {}", snapshot.connections);
+        }
+        let name = "clickhouse_pool_idle_connections";
+        header(out, name, "Idle connections in the pool", "gauge");
+        for (pool, snapshot) in &snapshots {
+            let _ = writeln!(out, "{name}{{pool=\"{pool}\"}} {}", snapshot.idle);
+        }
+
+        #[cfg(feature = "pool")]
+        render_lanes(out, &snapshots);
+    }
+
+    fn render_queues(&self, out: &mut String) {
+        let queues = self.0.queues.lock().clone();
+        let stats = queues
+            .iter()
+            .filter_map(|(name, source)| Some((escape_label(name), source()?)))
+            .collect::<Vec<_>>();
+        if stats.is_empty() {
+            return;
+        }
+
+        let gauges: [(&str, &str, fn(&QueueStats) -> usize); 3] = [
+            ("clickhouse_query_queue_limit", "Maximum concurrent queries", |s| s.limit),
+            ("clickhouse_query_queue_in_flight", "Queries holding a slot", |s| s.in_flight),
+            ("clickhouse_query_queue_waiting", "Queries queued for a slot", |s| s.waiting),
+        ];
+        for (name, help, value) in gauges {
+            header(out, name, help, "gauge");
+            for (client, stats) in &stats {
+                let _ = writeln!(out, "{name}{{client=\"{client}\"}} {}", value(stats));
+            }
+        }
+
+        let counters: [(&str, &str, fn(&QueueStats) -> u64); 2] = [
+            ("clickhouse_query_queue_admitted_total", "Queries given a slot", |s| s.admitted),
+            ("clickhouse_query_queue_shed_total", "Queries shed by the queue", |s| s.shed),
+        ];
+        for (name, help, value) in counters {
+            header(out, name, help, "counter");
+            for (client, stats) in &stats {
+                let _ = writeln!(out, "{name}{{client=\"{client}\"}} {}", value(stats));
+            }
+        }
+        let name = "clickhouse_query_queue_wait_seconds_total";
+        header(out, name, "Time queries waited for a slot", "counter");
+        for (client, stats) in &stats {
+            let seconds = stats.wait_time.as_secs_f64();
+            let _ = writeln!(out, "{name}{{client=\"{client}\"}} {seconds}");
+        }
+    }
+}
+
+impl std::fmt::Debug for ClientMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientMetrics")
+            .field("statements", &self.0.statements.load(Ordering::Relaxed))
+            .field("pools", &self.0.pools.lock().len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "pool")]
+fn render_lanes(out: &mut String, snapshots: &[(String, PoolSnapshot)]) {
+    if snapshots.iter().all(|(_, snapshot)| snapshot.lanes.is_empty()) {
+        return;
+    }
+    let lanes = snapshots
+        .iter()
+        .flat_map(|(pool, snapshot)| {
+            snapshot.lanes.iter().map(move |(priority, stats)| {
+                let lane = match priority {
+                    crate::Priority::Interactive => "interactive",
+                    crate::Priority::Background => "background",
+                };
+                (format!("pool=\"{pool}\",lane=\"{lane}\""), stats)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let gauges: [(&str, &str, fn(&crate::LaneStats) -> String); 3] = [
+        ("clickhouse_pool_lane_limit", "Concurrency limit of the lane", |s| s.limit.to_string()),
+        ("clickhouse_pool_lane_in_flight", "Connections checked out through the lane", |s| {
+            s.in_flight.to_string()
+        }),
+        ("clickhouse_pool_lane_waiting", "Callers waiting for the lane", |s| s.waiting.to_string()),
+    ];
+    for (name, help, value) in gauges {
+        header(out, name, help, "gauge");
+        for (labels, stats) in &lanes {
+            let _ = writeln!(out, "{name}{{{labels}}} {}", value(stats));
+        }
+    }
+
+    let name = "clickhouse_pool_lane_acquired_total";
+    header(out, name, "Connections checked out through the lane", "counter");
+    for (labels, stats) in &lanes {
+        let _ = writeln!(out, "{name}{{{labels}}} {}", stats.acquired);
+    }
+    let name = "clickhouse_pool_lane_wait_seconds_total";
+    header(out, name, "Time callers waited for the lane and a connection", "counter");
+    for (labels, stats) in &lanes {
+        let _ = writeln!(out, "{name}{{{labels}}} {}", stats.wait_time.as_secs_f64());
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Escape a label value per the Prometheus text format.
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// A minimal HTTP server exposing [`ClientMetrics`] on `GET /metrics`.
+///
+/// Scrapes are served one at a time on a background task, which is stopped when the server is
+/// dropped. Any other path responds `404 Not Found`.
+#[derive(Debug)]
+pub struct MetricsServer {
+    local_addr: SocketAddr,
+    _task:      SpawnedTask<()>,
+}
+
+impl MetricsServer {
+    /// Bind to `addr` and start serving `metrics`. Bind to port 0 to pick a free port, see
+    /// [`Self::local_addr`].
+    ///
+    /// # Errors
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind(addr: impl ToSocketAddrs, metrics: ClientMetrics) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let task = SpawnedTask::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(error) => {
+                        tracing::warn!(?error, "Metrics server failed to accept connection");
+                        continue;
+                    }
+                };
+                if let Err(error) = serve(stream, &metrics).await {
+                    tracing::debug!(?error, %peer, "Metrics scrape failed");
+                }
+            }
+        });
+        tracing::debug!(%local_addr, "Metrics server listening");
+        Ok(Self { local_addr, _task: task })
+    }
+
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr { self.local_addr }
+}
+
+async fn serve(mut stream: TcpStream, metrics: &ClientMetrics) -> std::io::Result<()> {
+    let mut head = Vec::with_capacity(1024);
+    let mut buf = [0_u8; 1024];
+    let read_head = async {
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut buf).await?;
+            if read == 0 || head.len() + read > MAX_REQUEST_HEAD {
+                return Err(std::io::ErrorKind::InvalidData.into());
+            }
+            head.extend_from_slice(&buf[..read]);
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    tokio::time::timeout(REQUEST_TIMEOUT, read_head)
+        .await
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+
+    let request_line = head.split(|byte| *byte == b'\r').next().unwrap_or_default();
+    let mut parts = request_line.split(|byte| *byte == b' ');
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let path = path.split(|byte| *byte == b'?').next().unwrap_or_default();
+
+    let (status, body) = match (method, path) {
+        (b"GET", b"/metrics") => ("200 OK", metrics.render()),
+        (b"GET", _) => ("404 Not Found", "Not Found\n".to_string()),
+        _ => ("405 Method Not Allowed", "Method Not Allowed\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4; \
+         charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+    use crate::query::Qid;
+
+    fn event(duration: Duration, error: Option<&str>) -> StatementEvent {
+        StatementEvent {
+            qid: Qid::new(),
+            client_id: 0,
+            fingerprint: 0,
+            query: None,
+            duration,
+            read_rows: 10,
+            written_rows: 5,
+            profile_events: crate::ProfileEvents::default(),
+            error: error.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn test_render_statements() {
+        let metrics = ClientMetrics::new();
+        let hook = metrics.statement_hook();
+        hook.call(&event(Duration::from_millis(3), None));
+        hook.call(&event(Duration::from_secs(20), Some("boom")));
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("# TYPE clickhouse_statements_total counter\n"));
+        assert!(rendered.contains("clickhouse_statements_total 2\n"));
+        assert!(rendered.contains("clickhouse_statement_errors_total 1\n"));
+        assert!(rendered.contains("clickhouse_rows_read_total 20\n"));
+        assert!(rendered.contains("clickhouse_rows_written_total 10\n"));
+        assert!(
+            rendered.contains("clickhouse_statement_duration_seconds_bucket{le=\"0.001\"} 0\n")
+        );
+        assert!(
+            rendered.contains("clickhouse_statement_duration_seconds_bucket{le=\"0.005\"} 1\n")
+        );
+        assert!(rendered.contains("clickhouse_statement_duration_seconds_bucket{le=\"10\"} 1\n"));
+        assert!(rendered.contains("clickhouse_statement_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(rendered.contains("clickhouse_statement_duration_seconds_count 2\n"));
+        assert!(rendered.contains("clickhouse_buffer_pool_buffers{tier=\"tiny\"}"));
+        assert!(!rendered.contains("clickhouse_pool_connections"));
+    }
+
+    #[test]
+    fn test_render_inserts_and_pools() {
+        let metrics = ClientMetrics::new();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(Int32Array::from(vec![1, 2, 3]))]).unwrap();
+        metrics.record_insert(&batch);
+        metrics.register_pool_source("a\"b", || PoolSnapshot {
+            connections: 4,
+            idle: 1,
+            ..Default::default()
+        });
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("clickhouse_insert_batches_total 1\n"));
+        assert!(rendered.contains("clickhouse_insert_rows_total 3\n"));
+        assert!(rendered.contains("clickhouse_pool_connections{pool=\"a\\\"b\"} 4\n"));
+        assert!(rendered.contains("clickhouse_pool_idle_connections{pool=\"a\\\"b\"} 1\n"));
+    }
+
+    #[test]
+    fn test_render_queues() {
+        let metrics = ClientMetrics::new();
+        metrics.register_queue_source("api", || {
+            Some(QueueStats {
+                limit:     8,
+                in_flight: 8,
+                waiting:   3,
+                admitted:  120,
+                shed:      4,
+                wait_time: Duration::from_millis(1500),
+            })
+        });
+        metrics.register_queue_source("uncapped", || None);
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("# TYPE clickhouse_query_queue_waiting gauge\n"));
+        assert!(rendered.contains("clickhouse_query_queue_limit{client=\"api\"} 8\n"));
+        assert!(rendered.contains("clickhouse_query_queue_in_flight{client=\"api\"} 8\n"));
+        assert!(rendered.contains("clickhouse_query_queue_waiting{client=\"api\"} 3\n"));
+        assert!(rendered.contains("clickhouse_query_queue_admitted_total{client=\"api\"} 120\n"));
+        assert!(rendered.contains("clickhouse_query_queue_shed_total{client=\"api\"} 4\n"));
+        assert!(
+            rendered.contains("clickhouse_query_queue_wait_seconds_total{client=\"api\"} 1.5\n")
+        );
+        assert!(!rendered.contains("uncapped"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server() {
+        let metrics = ClientMetrics::new();
+        metrics.record_statement(&event(Duration::from_millis(1), None));
+        let server = MetricsServer::bind("127.0.0.1:0", metrics).await.unwrap();
+
+        let request = async |request: &str| {
+            let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            let _ = stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        let response = request("GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("clickhouse_statements_total 1\n"));
+
+        let response = request("GET /other HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        let response = request("POST /metrics HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+    }
+}
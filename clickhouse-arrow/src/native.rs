@@ -4,6 +4,7 @@ pub mod block_info;
 pub(crate) mod client_info;
 pub mod convert;
 pub mod error_codes;
+pub mod profile_events;
 pub mod progress;
 pub(crate) mod protocol;
 pub(crate) mod sparse;
@@ -4,7 +4,8 @@ use indexmap::IndexMap;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use super::block_info::BlockInfo;
-use super::protocol::DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION;
+use super::protocol::{DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION, MAX_STRING_SIZE};
+use super::sparse::{SerializationKinds, read_serialization_kinds, read_serialization_kinds_sync};
 use crate::deserialize::ClickHouseNativeDeserializer;
 use crate::formats::protocol_data::ProtocolData;
 use crate::formats::{DeserializerState, SerializerState};
@@ -178,7 +179,7 @@ impl ProtocolData<Self, ()> for Block {
         writer.write_var_uint(columns as u64).await?;
         writer.write_var_uint(self.rows).await?;
 
-        for (name, type_) in self.column_types {
+        for (i, (name, type_)) in self.column_types.into_iter().enumerate() {
             let mut values = Vec::with_capacity(rows);
             values.extend(self.column_data.drain(..rows));
 
@@ -201,7 +202,10 @@ impl ProtocolData<Self, ()> for Block {
 
                 let mut state = SerializerState::default();
                 type_.serialize_prefix_async(writer, &mut state).await?;
-                type_.serialize_column(values, writer, &mut state).await?;
+                type_
+                    .serialize_column(values, writer, &mut state)
+                    .await
+                    .map_err(|e| e.with_column(&name, i))?;
             }
         }
         Ok(())
@@ -226,7 +230,7 @@ impl ProtocolData<Self, ()> for Block {
         writer.put_var_uint(columns as u64)?;
         writer.put_var_uint(self.rows)?;
 
-        for (name, type_) in self.column_types {
+        for (i, (name, type_)) in self.column_types.into_iter().enumerate() {
             let mut values = Vec::with_capacity(rows);
             values.extend(self.column_data.drain(..rows));
 
@@ -249,7 +253,9 @@ impl ProtocolData<Self, ()> for Block {
 
                 let mut state = SerializerState::default();
                 type_.serialize_prefix(writer, &mut state);
-                type_.serialize_column_sync(values, writer, &mut state)?;
+                type_
+                    .serialize_column_sync(values, writer, &mut state)
+                    .map_err(|e| e.with_column(&name, i))?;
             }
         }
         Ok(())
@@ -286,39 +292,30 @@ impl ProtocolData<Self, ()> for Block {
                 .await
                 .inspect_err(|e| error!("reading column type (name {name}): {e}"))?;
 
-            // Check for sparse/custom serialization
-            // Protocol: if has_custom=1, next byte is KindStackBinarySerializationType
+            let type_ = Type::from_str(&type_name)
+                .inspect_err(|error| {
+                    error!(?error, "Type deserialize failed: name={name}, type={type_name}");
+                })
+                .map_err(|e| e.with_column(&name, i))?;
+
+            // Check for sparse/custom serialization, including the kinds of nested tuple elements
             // See: https://github.com/ClickHouse/ClickHouse/blob/master/src/DataTypes/Serializations/SerializationInfo.cpp
-            let serialization_kind =
-                if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                    let has_custom = reader.read_u8().await? != 0;
-                    if has_custom {
-                        // KindStackBinarySerializationType enum:
-                        // 0 = DEFAULT, 1 = SPARSE, 2 = DETACHED, 3 = DETACHED_OVER_SPARSE,
-                        // 4 = REPLICATED, 5 = COMBINATION
-                        reader.read_u8().await?
-                    } else {
-                        0 // DEFAULT
-                    }
-                } else {
-                    0 // DEFAULT (no custom serialization support)
-                };
-
-            if serialization_kind != 0 {
+            let kinds = if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
+                read_serialization_kinds(reader, &type_).await?
+            } else {
+                SerializationKinds::default() // No custom serialization support
+            };
+
+            if kinds.is_custom() {
                 // Sparse or other custom serialization not supported for native format
                 return Err(Error::Unimplemented(format!(
-                    "Custom serialization kind {serialization_kind} not yet supported for column \
-                     '{name}' (type: {type_name}). Workaround: Set \
-                     `ratio_of_defaults_for_sparse_serialization = 1.0` in your ClickHouse server \
-                     settings to disable sparse serialization, or use ArrowFormat instead of \
-                     NativeFormat."
+                    "Custom serialization not yet supported for column '{name}' (type: \
+                     {type_name}). Workaround: Set `ratio_of_defaults_for_sparse_serialization = \
+                     1.0` in your ClickHouse server settings to disable sparse serialization, or \
+                     use ArrowFormat instead of NativeFormat."
                 )));
             }
 
-            let type_ = Type::from_str(&type_name).inspect_err(|error| {
-                error!(?error, "Type deserialize failed: name={name}, type={type_name}");
-            })?;
-
             let mut row_data = if rows > 0 {
                 type_.deserialize_prefix_async(reader, state).await?;
 
@@ -326,7 +323,8 @@ impl ProtocolData<Self, ()> for Block {
                 type_
                     .deserialize_column(reader, rows as usize, state)
                     .await
-                    .inspect_err(|e| error!("deserialize (name {name}): {e}"))?
+                    .inspect_err(|e| error!("deserialize (name {name}): {e}"))
+                    .map_err(|e| e.with_column(&name, i))?
             } else {
                 vec![]
             };
@@ -350,11 +348,17 @@ impl ProtocolData<Self, ()> for Block {
         let columns = reader.try_get_var_uint()? as usize;
         let rows = reader.try_get_var_uint()?;
 
+        if columns > MAX_STRING_SIZE {
+            return Err(Error::Protocol(format!("block too large: {columns} columns")));
+        }
+
+        // Each column occupies at least one byte, so never preallocate past the input
+        let capacity = columns.min(reader.remaining());
         let mut block = Block {
             info,
             rows,
-            column_types: Vec::with_capacity(columns),
-            column_data: Vec::with_capacity(columns),
+            column_types: Vec::with_capacity(capacity),
+            column_data: Vec::with_capacity(capacity),
         };
 
         for i in 0..columns {
@@ -372,45 +376,37 @@ impl ProtocolData<Self, ()> for Block {
                     .to_vec(),
             )?;
 
-            // Check for sparse/custom serialization
-            // Protocol: if has_custom=1, next byte is KindStackBinarySerializationType
+            let type_ = Type::from_str(&type_name)
+                .inspect_err(|error| {
+                    error!(?error, "Type deserialize failed: name={name}, type={type_name}");
+                })
+                .map_err(|e| e.with_column(&name, i))?;
+
+            // Check for sparse/custom serialization, including the kinds of nested tuple elements
             // See: https://github.com/ClickHouse/ClickHouse/blob/master/src/DataTypes/Serializations/SerializationInfo.cpp
-            let serialization_kind =
-                if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
-                    let has_custom = reader.try_get_u8()? != 0;
-                    if has_custom {
-                        // KindStackBinarySerializationType enum:
-                        // 0 = DEFAULT, 1 = SPARSE, 2 = DETACHED, 3 = DETACHED_OVER_SPARSE,
-                        // 4 = REPLICATED, 5 = COMBINATION
-                        reader.try_get_u8()?
-                    } else {
-                        0 // DEFAULT
-                    }
-                } else {
-                    0 // DEFAULT (no custom serialization support)
-                };
-
-            if serialization_kind != 0 {
+            let kinds = if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CUSTOM_SERIALIZATION {
+                read_serialization_kinds_sync(reader, &type_)?
+            } else {
+                SerializationKinds::default() // No custom serialization support
+            };
+
+            if kinds.is_custom() {
                 // Sparse or other custom serialization not supported for native format
                 return Err(Error::Unimplemented(format!(
-                    "Custom serialization kind {serialization_kind} not yet supported for column \
-                     '{name}' (type: {type_name}). Workaround: Set \
-                     `ratio_of_defaults_for_sparse_serialization = 1.0` in your ClickHouse server \
-                     settings to disable sparse serialization, or use ArrowFormat instead of \
-                     NativeFormat."
+                    "Custom serialization not yet supported for column '{name}' (type: \
+                     {type_name}). Workaround: Set `ratio_of_defaults_for_sparse_serialization = \
+                     1.0` in your ClickHouse server settings to disable sparse serialization, or \
+                     use ArrowFormat instead of NativeFormat."
                 )));
             }
 
-            let type_ = Type::from_str(&type_name).inspect_err(|error| {
-                error!(?error, "Type deserialize failed: name={name}, type={type_name}");
-            })?;
-
             #[allow(clippy::cast_possible_truncation)]
             let mut row_data = if rows > 0 {
                 type_.deserialize_prefix(reader)?;
                 type_
                     .deserialize_column_sync(reader, rows as usize, state)
-                    .inspect_err(|e| error!("deserialize (name {name}): {e}"))?
+                    .inspect_err(|e| error!("deserialize (name {name}): {e}"))
+                    .map_err(|e| e.with_column(&name, i))?
             } else {
                 vec![]
             };
@@ -1,12 +1,39 @@
+use std::collections::HashMap;
+
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::io::{ClickHouseBytesRead, ClickHouseBytesWrite, ClickHouseRead, ClickHouseWrite};
 use crate::{Error, Result};
 
+/// Schema metadata key carrying [`BlockInfo::is_overflows`] on Arrow query results.
+pub const BLOCK_INFO_IS_OVERFLOWS_KEY: &str = "clickhouse.block_info.is_overflows";
+/// Schema metadata key carrying [`BlockInfo::bucket_num`] on Arrow query results.
+pub const BLOCK_INFO_BUCKET_NUM_KEY: &str = "clickhouse.block_info.bucket_num";
+
 /// Metadata about a block
-#[derive(Debug, Clone, Copy)]
+///
+/// For `NativeFormat` this is available on every [`crate::native::block::Block`]. For
+/// `ArrowFormat`, block info is attached to the schema metadata of every `RecordBatch` if
+/// [`ArrowOptions::block_info_metadata`](crate::ArrowOptions::block_info_metadata) is enabled, and
+/// can be recovered with [`BlockInfo::from_metadata`].
+///
+/// # Examples
+/// ```rust,ignore
+/// use clickhouse_arrow::native::block_info::BlockInfo;
+///
+/// // GROUP BY ... WITH TOTALS with `group_by_overflow_mode = 'any'`
+/// while let Some(batch) = stream.next().await.transpose()? {
+///     if BlockInfo::from_metadata(batch.schema_ref().metadata()).is_some_and(|i| i.is_overflows) {
+///         // Rows aggregated past `max_rows_to_group_by`
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BlockInfo {
+    /// Whether the block contains rows that overflowed `max_rows_to_group_by` when
+    /// `group_by_overflow_mode = 'any'`.
     pub is_overflows: bool,
+    /// Bucket number for two-level aggregation, or `-1` if not applicable.
     pub bucket_num:   i32,
 }
 
@@ -15,6 +42,27 @@ impl Default for BlockInfo {
 }
 
 impl BlockInfo {
+    /// Whether this block info carries no information beyond the defaults.
+    pub fn is_default(&self) -> bool { *self == Self::default() }
+
+    /// Convert to schema metadata entries, as attached to Arrow query results.
+    pub fn to_metadata(&self) -> HashMap<String, String> {
+        HashMap::from([
+            (BLOCK_INFO_IS_OVERFLOWS_KEY.to_string(), self.is_overflows.to_string()),
+            (BLOCK_INFO_BUCKET_NUM_KEY.to_string(), self.bucket_num.to_string()),
+        ])
+    }
+
+    /// Recover block info from schema metadata, if present.
+    ///
+    /// Returns `None` if the metadata carries no block info, i.e. the schema was not read from
+    /// `ClickHouse` with block info metadata enabled.
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Option<Self> {
+        let is_overflows = metadata.get(BLOCK_INFO_IS_OVERFLOWS_KEY)?.parse().ok()?;
+        let bucket_num = metadata.get(BLOCK_INFO_BUCKET_NUM_KEY)?.parse().ok()?;
+        Some(Self { is_overflows, bucket_num })
+    }
+
     pub(crate) async fn read_async<R: ClickHouseRead>(reader: &mut R) -> Result<Self> {
         let mut new = Self::default();
         loop {
@@ -38,8 +86,8 @@ impl BlockInfo {
     }
 
     pub(crate) async fn write_async<W: ClickHouseWrite>(&self, writer: &mut W) -> Result<()> {
-        writer.write_var_uint(1).await?; // Block info version
-        writer.write_u8(if self.is_overflows { 1 } else { 2 }).await?; // Is overflows
+        writer.write_var_uint(1).await?; // Is overflows
+        writer.write_u8(u8::from(self.is_overflows)).await?;
         writer.write_var_uint(2).await?; // Bucket num
         writer.write_i32_le(self.bucket_num).await?; // Bucket num
         writer.write_var_uint(0).await?; // End field
@@ -70,11 +118,50 @@ impl BlockInfo {
     }
 
     pub(crate) fn write<W: ClickHouseBytesWrite>(self, writer: &mut W) -> Result<()> {
-        writer.put_var_uint(1)?; // Block info version
-        writer.put_u8(if self.is_overflows { 1 } else { 2 }); // Is overflows
+        writer.put_var_uint(1)?; // Is overflows
+        writer.put_u8(u8::from(self.is_overflows));
         writer.put_var_uint(2)?; // Bucket num
         writer.put_i32_le(self.bucket_num); // Bucket num
         writer.put_var_uint(0)?; // End field
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_block_info_round_trip() {
+        for info in
+            [BlockInfo::default(), BlockInfo { is_overflows: true, bucket_num: 7 }, BlockInfo {
+                is_overflows: false,
+                bucket_num:   3,
+            }]
+        {
+            let mut buffer = Vec::new();
+            info.write(&mut buffer).unwrap();
+            let read = BlockInfo::read(&mut Cursor::new(buffer)).unwrap();
+            assert_eq!(read, info);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_info_round_trip_async() {
+        let info = BlockInfo { is_overflows: false, bucket_num: 12 };
+        let mut buffer = Vec::new();
+        info.write_async(&mut buffer).await.unwrap();
+        let read = BlockInfo::read_async(&mut Cursor::new(buffer)).await.unwrap();
+        assert_eq!(read, info);
+    }
+
+    #[test]
+    fn test_block_info_metadata() {
+        let info = BlockInfo { is_overflows: true, bucket_num: -1 };
+        assert!(!info.is_default());
+        assert_eq!(BlockInfo::from_metadata(&info.to_metadata()), Some(info));
+        assert_eq!(BlockInfo::from_metadata(&HashMap::new()), None);
+    }
+}
@@ -4,6 +4,7 @@ use uuid::Uuid;
 use super::protocol::{
     DBMS_MIN_REVISION_WITH_JWT_IN_INTERSERVER, DBMS_MIN_REVISION_WITH_QUERY_AND_LINE_NUMBERS,
 };
+use crate::ClientInfoOptions;
 use crate::io::ClickHouseWrite;
 use crate::native::protocol::{
     DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH,
@@ -82,7 +83,33 @@ impl Default for ClientInfo<'_> {
     }
 }
 
-impl ClientInfo<'_> {
+impl<'a> ClientInfo<'a> {
+    /// Build client info, applying any user provided overrides to the defaults.
+    pub(crate) fn from_options(options: &'a ClientInfoOptions) -> Self {
+        let mut info = ClientInfo::default();
+        if let Some(name) = options.client_name.as_deref() {
+            info.client_name = name;
+        }
+        if let Some((major, minor, patch)) = options.client_version {
+            info.client_version_major = major;
+            info.client_version_minor = minor;
+            info.client_version_patch = patch;
+        }
+        if let Some(quota_key) = options.quota_key.as_deref() {
+            info.quota_key = quota_key;
+        }
+        if let Some(os_user) = options.os_user.as_deref() {
+            info.os_user = os_user;
+        }
+        if let Some(hostname) = options.client_hostname.as_deref() {
+            info.client_hostname = hostname;
+        }
+        if let Some(depth) = options.distributed_depth {
+            info.distributed_depth = depth;
+        }
+        info
+    }
+
     pub(crate) async fn write<W: ClickHouseWrite>(&self, to: &mut W, revision: u64) -> Result<()> {
         to.write_u8(self.kind as u8).await?;
         if self.kind == QueryKind::NoQuery {
@@ -147,3 +174,51 @@ impl ClientInfo<'_> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_info_defaults() {
+        let options = ClientInfoOptions::default();
+        let info = ClientInfo::from_options(&options);
+        assert_eq!(info.client_name, "ClickHouseArrow");
+        assert_eq!(info.quota_key, "");
+        assert_eq!(info.distributed_depth, 1);
+        assert_eq!(info.client_version_major, crate::constants::VERSION_MAJOR);
+    }
+
+    #[test]
+    fn test_client_info_overrides() {
+        let options = ClientInfoOptions::default()
+            .with_client_name("my-app")
+            .with_client_version(2, 3, 4)
+            .with_quota_key("tenant")
+            .with_os_user("svc")
+            .with_client_hostname("host-1")
+            .with_distributed_depth(0);
+        let info = ClientInfo::from_options(&options);
+        assert_eq!(info.client_name, "my-app");
+        assert_eq!(
+            (info.client_version_major, info.client_version_minor, info.client_version_patch),
+            (2, 3, 4)
+        );
+        assert_eq!(info.quota_key, "tenant");
+        assert_eq!(info.os_user, "svc");
+        assert_eq!(info.client_hostname, "host-1");
+        assert_eq!(info.distributed_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn test_client_info_writes_quota_key() {
+        let options = ClientInfoOptions::default().with_quota_key("tenant-key");
+        let mut buffer = Vec::new();
+        ClientInfo::from_options(&options)
+            .write(&mut buffer, DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO)
+            .await
+            .unwrap();
+        let needle = b"tenant-key";
+        assert!(buffer.windows(needle.len()).any(|w| w == needle));
+    }
+}
@@ -0,0 +1,149 @@
+//! Totals of the profile events `ClickHouse` reports while running a query.
+//!
+//! The server sends `ProfileEvents` packets periodically and once more before the end of a
+//! query, each holding the counters incremented since the previous packet along with gauges such
+//! as memory usage. [`ProfileEvents`] folds them into per-query totals, delivered with the
+//! statement's [`StatementEvent`](crate::telemetry::StatementEvent) once it completes.
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::protocol::ProfileEvent;
+
+/// Thread id of the rows holding the totals of all threads running the query on a host.
+const THREAD_GROUP_ID: u64 = 0;
+
+/// Type code of gauges, the other events are counters incremented since the previous packet.
+const GAUGE: i8 = 2;
+
+/// The profile events of a query, ie `SelectedRows` or `NetworkSendBytes`.
+///
+/// Counters are summed over the packets and hosts of the query, gauges such as
+/// `MemoryTrackerPeakUsage` keep the highest value reported. See `system.events` for the meaning
+/// of each event. Commonly used events have typed accessors, returning zero if the server did
+/// not report them, all others are available through [`ProfileEvents::get`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileEvents {
+    events: BTreeMap<String, i64>,
+}
+
+impl ProfileEvents {
+    /// Add the rows of a `ProfileEvents` packet.
+    ///
+    /// Packets holding the totals of a thread group also hold the rows of its threads, which are
+    /// skipped so they aren't counted twice.
+    pub(crate) fn record(&mut self, events: &[ProfileEvent]) {
+        let grouped = events.iter().any(|event| event.thread_id == THREAD_GROUP_ID);
+        for event in events.iter().filter(|event| !grouped || event.thread_id == THREAD_GROUP_ID) {
+            let value = self.events.entry(event.name.clone()).or_default();
+            if event.type_code == GAUGE {
+                *value = (*value).max(event.value);
+            } else {
+                *value += event.value;
+            }
+        }
+    }
+
+    /// The value of the event `name`, if the server reported it.
+    pub fn get(&self, name: &str) -> Option<i64> { self.events.get(name).copied() }
+
+    /// All reported events and their values, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.events.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Whether no events were reported, ie by servers too old to send them.
+    pub fn is_empty(&self) -> bool { self.events.is_empty() }
+
+    /// Rows selected for reading from `MergeTree` tables, after index analysis.
+    pub fn selected_rows(&self) -> u64 { self.counter("SelectedRows") }
+
+    /// Uncompressed bytes selected for reading from `MergeTree` tables.
+    pub fn selected_bytes(&self) -> u64 { self.counter("SelectedBytes") }
+
+    /// Data parts selected for reading from `MergeTree` tables.
+    pub fn selected_parts(&self) -> u64 { self.counter("SelectedParts") }
+
+    /// Marks (index granules) selected for reading from `MergeTree` tables.
+    pub fn selected_marks(&self) -> u64 { self.counter("SelectedMarks") }
+
+    /// Compressed bytes read from disk or the page cache.
+    pub fn read_compressed_bytes(&self) -> u64 { self.counter("ReadCompressedBytes") }
+
+    /// Rows inserted into tables.
+    pub fn inserted_rows(&self) -> u64 { self.counter("InsertedRows") }
+
+    /// Uncompressed bytes inserted into tables.
+    pub fn inserted_bytes(&self) -> u64 { self.counter("InsertedBytes") }
+
+    /// Bytes sent over the network, including between the hosts of a distributed query.
+    pub fn network_send_bytes(&self) -> u64 { self.counter("NetworkSendBytes") }
+
+    /// Bytes received over the network, including between the hosts of a distributed query.
+    pub fn network_receive_bytes(&self) -> u64 { self.counter("NetworkReceiveBytes") }
+
+    /// Wall clock time spent by the threads of the query, summed over threads.
+    pub fn real_time(&self) -> Duration { self.micros("RealTimeMicroseconds") }
+
+    /// CPU time spent in user space by the threads of the query.
+    pub fn user_time(&self) -> Duration { self.micros("UserTimeMicroseconds") }
+
+    /// CPU time spent in the kernel by the threads of the query.
+    pub fn system_time(&self) -> Duration { self.micros("SystemTimeMicroseconds") }
+
+    /// Highest memory usage of the query, in bytes.
+    pub fn peak_memory_usage(&self) -> u64 { self.counter("MemoryTrackerPeakUsage") }
+
+    fn counter(&self, name: &str) -> u64 {
+        self.get(name).and_then(|value| u64::try_from(value).ok()).unwrap_or_default()
+    }
+
+    fn micros(&self, name: &str) -> Duration { Duration::from_micros(self.counter(name)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(thread_id: u64, type_code: i8, name: &str, value: i64) -> ProfileEvent {
+        ProfileEvent { thread_id, type_code, name: name.into(), value, ..Default::default() }
+    }
+
+    #[test]
+    fn test_record_thread_group_totals() {
+        let mut events = ProfileEvents::default();
+        assert!(events.is_empty());
+        events.record(&[
+            event(0, 1, "SelectedRows", 100),
+            event(42, 1, "SelectedRows", 60),
+            event(43, 1, "SelectedRows", 40),
+            event(0, 2, "MemoryTrackerPeakUsage", 4096),
+        ]);
+        events.record(&[
+            event(0, 1, "SelectedRows", 50),
+            event(0, 1, "NetworkSendBytes", 512),
+            event(0, 2, "MemoryTrackerPeakUsage", 1024),
+        ]);
+        assert_eq!(events.selected_rows(), 150);
+        assert_eq!(events.network_send_bytes(), 512);
+        // Gauges keep the highest value
+        assert_eq!(events.peak_memory_usage(), 4096);
+        assert_eq!(events.selected_bytes(), 0);
+        assert_eq!(events.get("SelectedBytes"), None);
+        assert_eq!(events.iter().map(|(name, _)| name).collect::<Vec<_>>(), vec![
+            "MemoryTrackerPeakUsage",
+            "NetworkSendBytes",
+            "SelectedRows"
+        ]);
+    }
+
+    #[test]
+    fn test_record_per_thread() {
+        // Without a thread group row, every thread's increments count
+        let mut events = ProfileEvents::default();
+        events.record(&[
+            event(7, 1, "UserTimeMicroseconds", 1500),
+            event(8, 1, "UserTimeMicroseconds", 500),
+        ]);
+        assert_eq!(events.user_time(), Duration::from_millis(2));
+    }
+}
@@ -108,6 +108,8 @@ pub(crate) struct ClientHello {
     pub(crate) default_database: String,
     pub(crate) username:         String,
     pub(crate) password:         String,
+    pub(crate) client_name:      Option<String>,
+    pub(crate) client_version:   Option<(u64, u64, u64)>,
 }
 
 /// `ServerPacketId` is the packet id read from `ClickHouse`.
@@ -199,7 +201,6 @@ pub(crate) enum ServerPacket<T = Block> {
 pub(crate) struct ServerHello {
     #[expect(unused)]
     pub(crate) server_name:      String,
-    #[expect(unused)]
     pub(crate) version:          (u64, u64, u64),
     pub(crate) revision_version: u64,
     #[expect(unused)]
@@ -323,14 +324,23 @@ impl LogData {
 }
 
 /// Emitted by `ClickHouse` during operations.
+///
+/// Each `ProfileEvents` packet holds one row per event and thread, see
+/// [`ProfileEvents`](crate::ProfileEvents) for the totals of a query.
 #[derive(Debug, Clone, Default)]
 pub struct ProfileEvent {
-    pub(crate) host_name:    String,
-    pub(crate) current_time: String,
-    pub(crate) thread_id:    u64,
-    pub(crate) type_code:    i8,
-    pub(crate) name:         String,
-    pub(crate) value:        i64,
+    /// The host running the thread
+    pub host_name:    String,
+    /// When the server collected the event
+    pub current_time: String,
+    /// The thread the event was recorded on, `0` for the totals of the query's threads
+    pub thread_id:    u64,
+    /// `1` for counters incremented since the previous packet, `2` for gauges
+    pub type_code:    i8,
+    /// The name of the event, ie `SelectedRows`
+    pub name:         String,
+    /// The value of the event
+    pub value:        i64,
 }
 
 impl ProfileEvent {
@@ -504,3 +514,53 @@ impl AsRef<str> for CompressionMethod {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunked_negotiation() {
+        use ChunkedProtocolMode::{Chunked, ChunkedOptional, NotChunked, NotChunkedOptional};
+
+        // Optional on either side defers to the other
+        assert_eq!(
+            ChunkedProtocolMode::negotiate(ChunkedOptional, NotChunked, "send").unwrap(),
+            NotChunked
+        );
+        assert_eq!(
+            ChunkedProtocolMode::negotiate(NotChunkedOptional, Chunked, "send").unwrap(),
+            Chunked
+        );
+        assert_eq!(
+            ChunkedProtocolMode::negotiate(Chunked, ChunkedOptional, "recv").unwrap(),
+            Chunked
+        );
+        assert_eq!(
+            ChunkedProtocolMode::negotiate(NotChunked, ChunkedOptional, "recv").unwrap(),
+            NotChunked
+        );
+        // Both optional, the client preference wins
+        assert_eq!(
+            ChunkedProtocolMode::negotiate(NotChunkedOptional, ChunkedOptional, "recv").unwrap(),
+            Chunked
+        );
+        // Both strict must agree
+        assert_eq!(ChunkedProtocolMode::negotiate(Chunked, Chunked, "send").unwrap(), Chunked);
+        let error = ChunkedProtocolMode::negotiate(NotChunked, Chunked, "send").unwrap_err();
+        assert!(error.to_string().contains("send set to chunked, server requires notchunked"));
+    }
+
+    #[test]
+    fn test_chunked_mode_round_trip() {
+        for mode in [
+            ChunkedProtocolMode::Chunked,
+            ChunkedProtocolMode::ChunkedOptional,
+            ChunkedProtocolMode::NotChunked,
+            ChunkedProtocolMode::NotChunkedOptional,
+        ] {
+            assert_eq!(ChunkedProtocolMode::from_str(mode.as_ref()).unwrap(), mode);
+        }
+        assert!(ChunkedProtocolMode::from_str("sometimes").is_err());
+    }
+}
@@ -13,144 +13,243 @@ use std::sync::Arc;
 
 use arrow::array::*;
 use arrow::datatypes::*;
+use tokio::io::AsyncReadExt;
 
-use crate::Result;
 use crate::io::ClickHouseRead;
+use crate::{ArrowOptions, Error, Result, Type};
 
 /// End-of-granule marker (bit 62). When set, this is the final VarUInt in the offsets stream.
 pub(crate) const END_OF_GRANULE_FLAG: u64 = 1 << 62;
 
-/// State for sparse deserialisation across multiple reads.
-#[derive(Debug, Default, Clone)]
-pub(crate) struct SparseDeserializeState {
-    /// Trailing defaults from previous read that haven't been consumed yet.
-    pub num_trailing_defaults:    u64,
-    /// Non-default value pending after the trailing defaults.
-    pub has_value_after_defaults: bool,
-}
+/// `KindStackBinarySerializationType` value for sparse serialization.
+const SPARSE_KIND: u8 = 1;
+/// `KindStackBinarySerializationType` value for a variable-length kind stack.
+const COMBINATION_KIND: u8 = 5;
+/// Upper bound on the length of a combination kind stack.
+const MAX_KIND_STACK: u64 = 64;
 
-/// Read sparse offsets from stream. Returns positions of non-default values.
+/// Serialization kinds of a column, mirroring `SerializationInfo` in `ClickHouse`.
 ///
-/// Must loop until END_OF_GRANULE_FLAG – can't stop early even if we have enough
-/// rows, or the stream will be misaligned for the next column.
-#[allow(clippy::cast_possible_truncation)] // row counts fit in usize
-pub(crate) async fn read_sparse_offsets<R: ClickHouseRead>(
-    reader: &mut R,
-    num_rows: usize,
-    state: &mut SparseDeserializeState,
-) -> Result<Vec<usize>> {
-    let mut offsets = Vec::new();
-    let mut current_position: u64 = 0;
+/// Only `Tuple` carries kinds for its elements (`SerializationInfoTuple`), each of which may be
+/// sparse independently of the tuple itself. All other containers (`Array`, `Map`, `Nullable`,
+/// `LowCardinality`) serialize their nested data with the default kind.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct SerializationKinds {
+    /// Whether this column (or tuple element) uses sparse serialization.
+    pub sparse:   bool,
+    /// Whether this column (or tuple element) uses any non-default serialization.
+    pub custom:   bool,
+    /// Kinds of each element, in order, when the column is a `Tuple`.
+    pub elements: Vec<SerializationKinds>,
+}
 
-    // Handle any state carried over from previous read
-    if state.num_trailing_defaults > 0 {
-        current_position += state.num_trailing_defaults;
-        state.num_trailing_defaults = 0;
+impl SerializationKinds {
+    /// Whether any element nested within this column uses sparse serialization.
+    pub(crate) fn has_nested_sparse(&self) -> bool {
+        self.elements.iter().any(|e| e.sparse || e.has_nested_sparse())
     }
-    if state.has_value_after_defaults {
-        if (current_position as usize) < num_rows {
-            offsets.push(current_position as usize);
-        }
-        current_position += 1;
-        state.has_value_after_defaults = false;
+
+    /// Whether this column or any nested element uses a non-default serialization.
+    pub(crate) fn is_custom(&self) -> bool {
+        self.custom || self.elements.iter().any(SerializationKinds::is_custom)
     }
 
-    // Read offset groups until we hit END_OF_GRANULE
-    // The format is: [group_size, group_size, ..., group_size | END_OF_GRANULE_FLAG]
-    // Each group_size represents the count of defaults before a non-default value
-    // The final group has END_OF_GRANULE_FLAG set and represents trailing defaults
-    loop {
-        let group_size = reader.read_var_uint().await?;
+    /// Build the kinds tree from kinds read in pre-order, matching the order `ClickHouse` writes
+    /// them in.
+    fn from_preorder(type_: &Type, kinds: &mut impl Iterator<Item = (bool, bool)>) -> Self {
+        let (sparse, custom) = kinds.next().unwrap_or_default();
+        let elements = match type_ {
+            Type::Tuple(inner) => inner.iter().map(|t| Self::from_preorder(t, kinds)).collect(),
+            _ => Vec::new(),
+        };
+        Self { sparse, custom, elements }
+    }
+}
 
-        // Check if this is the end of granule
-        let is_end_of_granule = (group_size & END_OF_GRANULE_FLAG) != 0;
-        let actual_group_size = group_size & !END_OF_GRANULE_FLAG;
+/// Number of kinds `ClickHouse` writes for `type_`: one for the column and one per tuple element,
+/// recursively.
+fn kind_count(type_: &Type) -> usize {
+    match type_ {
+        Type::Tuple(inner) => 1 + inner.iter().map(kind_count).sum::<usize>(),
+        _ => 1,
+    }
+}
 
-        // Move past the default values
-        current_position += actual_group_size;
+/// Read the serialization kinds of a column, including nested tuple elements.
+///
+/// Protocol: a `has_custom` byte, followed (if set) by one `KindStackBinarySerializationType`
+/// per kind in pre-order. See `SerializationInfo.cpp` and `SerializationInfoTuple.cpp` in
+/// `ClickHouse`.
+pub(crate) async fn read_serialization_kinds<R: ClickHouseRead>(
+    reader: &mut R,
+    type_: &Type,
+) -> Result<SerializationKinds> {
+    if reader.read_u8().await? == 0 {
+        return Ok(SerializationKinds::default());
+    }
 
-        if is_end_of_granule {
-            // Store trailing defaults for potential next read
-            if current_position > num_rows as u64 {
-                state.num_trailing_defaults = current_position - num_rows as u64;
+    let count = kind_count(type_);
+    let mut kinds = Vec::with_capacity(count);
+    for _ in 0..count {
+        // KindStackBinarySerializationType enum:
+        // 0 = DEFAULT, 1 = SPARSE, 2 = DETACHED, 3 = DETACHED_OVER_SPARSE,
+        // 4 = REPLICATED, 5 = COMBINATION
+        let kind = reader.read_u8().await?;
+        let sparse = if kind == COMBINATION_KIND {
+            // COMBINATION: VarUInt count, then count x UInt8 kinds
+            let stack = reader.read_var_uint().await?;
+            if stack > MAX_KIND_STACK {
+                return Err(Error::DeserializeError(format!(
+                    "Serialization kind stack of {stack} exceeds {MAX_KIND_STACK}"
+                )));
             }
-            break;
-        }
-
-        // There's a non-default value at current_position
-        if (current_position as usize) < num_rows {
-            offsets.push(current_position as usize);
-            current_position += 1;
+            let mut sparse = false;
+            for _ in 0..stack {
+                sparse |= reader.read_u8().await? == SPARSE_KIND;
+            }
+            sparse
         } else {
-            // Non-default value is beyond our window, save state
-            state.has_value_after_defaults = true;
-            // We still need to read remaining VarUInts until END_OF_GRANULE
-            // For now, just continue looping - the END_OF_GRANULE will terminate
-        }
+            kind == SPARSE_KIND
+        };
+        kinds.push((sparse, kind != 0));
     }
-
-    Ok(offsets)
+    Ok(SerializationKinds::from_preorder(type_, &mut kinds.into_iter()))
 }
 
-/// Sync version of read_sparse_offsets for bytes::Buf readers.
-#[allow(dead_code)]
-#[allow(clippy::cast_possible_truncation)] // row counts fit in usize
-pub(crate) fn read_sparse_offsets_sync<R: crate::io::ClickHouseBytesRead>(
+/// Sync version of [`read_serialization_kinds`] for bytes::Buf readers.
+pub(crate) fn read_serialization_kinds_sync<R: crate::io::ClickHouseBytesRead>(
     reader: &mut R,
-    num_rows: usize,
-    state: &mut SparseDeserializeState,
-) -> Result<Vec<usize>> {
-    let mut offsets = Vec::new();
-    let mut current_position: u64 = 0;
-
-    // Handle any state carried over from previous read
-    if state.num_trailing_defaults > 0 {
-        current_position += state.num_trailing_defaults;
-        state.num_trailing_defaults = 0;
+    type_: &Type,
+) -> Result<SerializationKinds> {
+    if reader.try_get_u8()? == 0 {
+        return Ok(SerializationKinds::default());
     }
-    if state.has_value_after_defaults {
-        if (current_position as usize) < num_rows {
-            offsets.push(current_position as usize);
-        }
-        current_position += 1;
-        state.has_value_after_defaults = false;
+
+    let count = kind_count(type_);
+    let mut kinds = Vec::with_capacity(count);
+    for _ in 0..count {
+        let kind = reader.try_get_u8()?;
+        let sparse = if kind == COMBINATION_KIND {
+            let stack = reader.try_get_var_uint()?;
+            if stack > MAX_KIND_STACK {
+                return Err(Error::DeserializeError(format!(
+                    "Serialization kind stack of {stack} exceeds {MAX_KIND_STACK}"
+                )));
+            }
+            let mut sparse = false;
+            for _ in 0..stack {
+                sparse |= reader.try_get_u8()? == SPARSE_KIND;
+            }
+            sparse
+        } else {
+            kind == SPARSE_KIND
+        };
+        kinds.push((sparse, kind != 0));
     }
+    Ok(SerializationKinds::from_preorder(type_, &mut kinds.into_iter()))
+}
+
+/// Accumulates non-default positions while enforcing limits derived from `num_rows`.
+///
+/// Each block serializes its sparse columns from scratch, so the offsets of a column are read in
+/// one go and never continue into the next read. Group sizes come straight from the wire, so
+/// every step is checked: neither defaults nor values may fall beyond `num_rows`. This bounds
+/// both the offsets `Vec` (at most `num_rows` entries) and the number of groups consumed before
+/// `END_OF_GRANULE_FLAG` (at most `num_rows + 1`).
+struct SparseOffsets {
+    offsets:  Vec<usize>,
+    position: u64,
+    num_rows: usize,
+}
 
-    // Read offset groups until we hit END_OF_GRANULE
-    // The format is: [group_size, group_size, ..., group_size | END_OF_GRANULE_FLAG]
-    // Each group_size represents the count of defaults before a non-default value
-    // The final group has END_OF_GRANULE_FLAG set and represents trailing defaults
-    loop {
-        let group_size = reader.try_get_var_uint()?;
+#[allow(clippy::cast_possible_truncation)] // positions are checked against num_rows
+impl SparseOffsets {
+    fn new(num_rows: usize) -> Self { Self { offsets: Vec::new(), position: 0, num_rows } }
 
+    fn error(&self, reason: &'static str) -> Error {
+        Error::SparseOffsets { position: self.position, num_rows: self.num_rows, reason }
+    }
+
+    /// Apply a single group size. Returns `true` once the end of the granule is reached.
+    ///
+    /// The format is: [group_size, group_size, ..., group_size | END_OF_GRANULE_FLAG]
+    /// Each group_size represents the count of defaults before a non-default value
+    /// The final group has END_OF_GRANULE_FLAG set and represents trailing defaults
+    fn push_group(&mut self, group_size: u64) -> Result<bool> {
         // Check if this is the end of granule
         let is_end_of_granule = (group_size & END_OF_GRANULE_FLAG) != 0;
         let actual_group_size = group_size & !END_OF_GRANULE_FLAG;
 
         // Move past the default values
-        current_position += actual_group_size;
+        let num_rows = self.num_rows as u64;
+        self.position = self
+            .position
+            .checked_add(actual_group_size)
+            .filter(|&position| position <= num_rows)
+            .ok_or_else(|| self.error("defaults beyond block rows"))?;
 
         if is_end_of_granule {
-            // Store trailing defaults for potential next read
-            if current_position > num_rows as u64 {
-                state.num_trailing_defaults = current_position - num_rows as u64;
-            }
-            break;
+            return Ok(true);
         }
 
-        // There's a non-default value at current_position
-        if (current_position as usize) < num_rows {
-            offsets.push(current_position as usize);
-            current_position += 1;
-        } else {
-            // Non-default value is beyond our window, save state
-            state.has_value_after_defaults = true;
-            // We still need to read remaining VarUInts until END_OF_GRANULE
-            // For now, just continue looping - the END_OF_GRANULE will terminate
+        // There's a non-default value at the current position
+        if self.position == num_rows {
+            return Err(self.error("non-default value beyond block rows"));
         }
+        self.offsets.push(self.position as usize);
+        self.position += 1;
+        Ok(false)
     }
+}
+
+/// Read sparse offsets from stream. Returns positions of non-default values.
+///
+/// Must loop until END_OF_GRANULE_FLAG – can't stop early even if we have enough
+/// rows, or the stream will be misaligned for the next column.
+///
+/// # Errors
+/// Returns [`Error::SparseOffsets`] if the group sizes are inconsistent with `num_rows`.
+pub(crate) async fn read_sparse_offsets<R: ClickHouseRead>(
+    reader: &mut R,
+    num_rows: usize,
+) -> Result<Vec<usize>> {
+    let mut offsets = SparseOffsets::new(num_rows);
+    while !offsets.push_group(reader.read_var_uint().await?)? {}
+    Ok(offsets.offsets)
+}
 
-    Ok(offsets)
+/// Sync version of read_sparse_offsets for bytes::Buf readers.
+pub(crate) fn read_sparse_offsets_sync<R: crate::io::ClickHouseBytesRead>(
+    reader: &mut R,
+    num_rows: usize,
+) -> Result<Vec<usize>> {
+    let mut offsets = SparseOffsets::new(num_rows);
+    while !offsets.push_group(reader.try_get_var_uint()?)? {}
+    Ok(offsets.offsets)
+}
+
+/// Ensure offsets line up with the sparse values and fall within `total_rows`.
+fn validate_sparse_offsets(
+    sparse_array: &ArrayRef,
+    offsets: &[usize],
+    total_rows: usize,
+) -> Result<()> {
+    if sparse_array.len() != offsets.len() {
+        return Err(Error::DeserializeError(format!(
+            "Sparse array length {} does not match offsets length {}",
+            sparse_array.len(),
+            offsets.len()
+        )));
+    }
+    // Offsets come from the wire, ensure they are strictly increasing and within bounds so that
+    // every sparse value is placed exactly once.
+    if offsets.windows(2).any(|w| w[0] >= w[1]) || offsets.last().is_some_and(|&o| o >= total_rows)
+    {
+        return Err(Error::DeserializeError(format!(
+            "Invalid sparse offsets for {total_rows} rows"
+        )));
+    }
+    Ok(())
 }
 
 /// Expand sparse array to full size, filling non-offset positions with defaults.
@@ -159,52 +258,52 @@ pub(crate) fn expand_sparse_array(
     offsets: &[usize],
     total_rows: usize,
 ) -> Result<ArrayRef> {
-    assert_eq!(sparse_array.len(), offsets.len(), "Sparse array length must match offsets length");
+    validate_sparse_offsets(sparse_array, offsets, total_rows)?;
 
     let data_type = sparse_array.data_type();
 
     // Handle each data type
     let result: ArrayRef = match data_type {
-        DataType::Int8 => expand_primitive::<Int8Type>(sparse_array, offsets, total_rows),
-        DataType::Int16 => expand_primitive::<Int16Type>(sparse_array, offsets, total_rows),
-        DataType::Int32 => expand_primitive::<Int32Type>(sparse_array, offsets, total_rows),
-        DataType::Int64 => expand_primitive::<Int64Type>(sparse_array, offsets, total_rows),
-        DataType::UInt8 => expand_primitive::<UInt8Type>(sparse_array, offsets, total_rows),
-        DataType::UInt16 => expand_primitive::<UInt16Type>(sparse_array, offsets, total_rows),
-        DataType::UInt32 => expand_primitive::<UInt32Type>(sparse_array, offsets, total_rows),
-        DataType::UInt64 => expand_primitive::<UInt64Type>(sparse_array, offsets, total_rows),
-        DataType::Float32 => expand_primitive::<Float32Type>(sparse_array, offsets, total_rows),
-        DataType::Float64 => expand_primitive::<Float64Type>(sparse_array, offsets, total_rows),
-        DataType::Date32 => expand_primitive::<Date32Type>(sparse_array, offsets, total_rows),
-        DataType::Date64 => expand_primitive::<Date64Type>(sparse_array, offsets, total_rows),
+        DataType::Int8 => expand_primitive::<Int8Type>(sparse_array, offsets, total_rows)?,
+        DataType::Int16 => expand_primitive::<Int16Type>(sparse_array, offsets, total_rows)?,
+        DataType::Int32 => expand_primitive::<Int32Type>(sparse_array, offsets, total_rows)?,
+        DataType::Int64 => expand_primitive::<Int64Type>(sparse_array, offsets, total_rows)?,
+        DataType::UInt8 => expand_primitive::<UInt8Type>(sparse_array, offsets, total_rows)?,
+        DataType::UInt16 => expand_primitive::<UInt16Type>(sparse_array, offsets, total_rows)?,
+        DataType::UInt32 => expand_primitive::<UInt32Type>(sparse_array, offsets, total_rows)?,
+        DataType::UInt64 => expand_primitive::<UInt64Type>(sparse_array, offsets, total_rows)?,
+        DataType::Float32 => expand_primitive::<Float32Type>(sparse_array, offsets, total_rows)?,
+        DataType::Float64 => expand_primitive::<Float64Type>(sparse_array, offsets, total_rows)?,
+        DataType::Date32 => expand_primitive::<Date32Type>(sparse_array, offsets, total_rows)?,
+        DataType::Date64 => expand_primitive::<Date64Type>(sparse_array, offsets, total_rows)?,
         DataType::Timestamp(TimeUnit::Second, _) => {
-            expand_primitive::<TimestampSecondType>(sparse_array, offsets, total_rows)
+            expand_primitive::<TimestampSecondType>(sparse_array, offsets, total_rows)?
         }
         DataType::Timestamp(TimeUnit::Millisecond, _) => {
-            expand_primitive::<TimestampMillisecondType>(sparse_array, offsets, total_rows)
+            expand_primitive::<TimestampMillisecondType>(sparse_array, offsets, total_rows)?
         }
         DataType::Timestamp(TimeUnit::Microsecond, _) => {
-            expand_primitive::<TimestampMicrosecondType>(sparse_array, offsets, total_rows)
+            expand_primitive::<TimestampMicrosecondType>(sparse_array, offsets, total_rows)?
         }
         DataType::Timestamp(TimeUnit::Nanosecond, _) => {
-            expand_primitive::<TimestampNanosecondType>(sparse_array, offsets, total_rows)
+            expand_primitive::<TimestampNanosecondType>(sparse_array, offsets, total_rows)?
         }
         DataType::Decimal128(_, _) => {
-            expand_primitive::<Decimal128Type>(sparse_array, offsets, total_rows)
+            expand_primitive::<Decimal128Type>(sparse_array, offsets, total_rows)?
         }
         DataType::Decimal256(_, _) => {
-            expand_primitive::<Decimal256Type>(sparse_array, offsets, total_rows)
+            expand_primitive::<Decimal256Type>(sparse_array, offsets, total_rows)?
         }
-        DataType::Utf8 => expand_string::<i32>(sparse_array, offsets, total_rows),
-        DataType::LargeUtf8 => expand_string::<i64>(sparse_array, offsets, total_rows),
-        DataType::Binary => expand_binary::<i32>(sparse_array, offsets, total_rows),
-        DataType::LargeBinary => expand_binary::<i64>(sparse_array, offsets, total_rows),
-        DataType::Boolean => expand_boolean(sparse_array, offsets, total_rows),
+        DataType::Utf8 => expand_string::<i32>(sparse_array, offsets, total_rows)?,
+        DataType::LargeUtf8 => expand_string::<i64>(sparse_array, offsets, total_rows)?,
+        DataType::Binary => expand_binary::<i32>(sparse_array, offsets, total_rows)?,
+        DataType::LargeBinary => expand_binary::<i64>(sparse_array, offsets, total_rows)?,
+        DataType::Boolean => expand_boolean(sparse_array, offsets, total_rows)?,
         DataType::FixedSizeBinary(size) => {
-            expand_fixed_size_binary(sparse_array, offsets, total_rows, *size)
+            expand_fixed_size_binary(sparse_array, offsets, total_rows, *size)?
         }
         _ => {
-            return Err(crate::Error::Unimplemented(format!(
+            return Err(Error::Unimplemented(format!(
                 "Sparse expansion not implemented for type: {data_type:?}"
             )));
         }
@@ -213,15 +312,125 @@ pub(crate) fn expand_sparse_array(
     Ok(result)
 }
 
+/// Encode a sparse array as a [`RunArray`] without expanding it to `total_rows`.
+///
+/// Each run of defaults is stored as a single default value, so memory is proportional to the
+/// number of non-default values rather than the number of rows.
+pub(crate) fn encode_sparse_array(
+    sparse_array: &ArrayRef,
+    offsets: &[usize],
+    total_rows: usize,
+) -> Result<ArrayRef> {
+    validate_sparse_offsets(sparse_array, offsets, total_rows)?;
+
+    // A single default value of the same type
+    let default = expand_sparse_array(&sparse_array.slice(0, 0), &[], 1)?;
+
+    // Index 0 of `values` is the default, sparse value `i` is at index `i + 1`
+    let mut run_ends = Vec::with_capacity(offsets.len() * 2 + 1);
+    let mut indices = Vec::with_capacity(offsets.len() * 2 + 1);
+    let mut position = 0;
+    for (i, &offset) in offsets.iter().enumerate() {
+        if offset > position {
+            run_ends.push(run_end(offset)?);
+            indices.push(0_u32);
+        }
+        run_ends.push(run_end(offset + 1)?);
+        indices.push(u32::try_from(i + 1).map_err(|_| {
+            Error::DeserializeError("Too many sparse values for run end encoding".into())
+        })?);
+        position = offset + 1;
+    }
+    if total_rows > position {
+        run_ends.push(run_end(total_rows)?);
+        indices.push(0);
+    }
+
+    let values = arrow::compute::concat(&[default.as_ref(), sparse_array.as_ref()])?;
+    let values = arrow::compute::take(&values, &UInt32Array::from(indices), None)?;
+    Ok(Arc::new(RunArray::<Int32Type>::try_new(&Int32Array::from(run_ends), &values)?))
+}
+
+/// Run end encode a dense column if `sparse_as_run_end_encoded` applies to its type.
+///
+/// Whether a block is sent sparse is decided by the server, so the option applies to every column
+/// whose type could be sparse. A column then has the same `RunEndEncoded` type in every block,
+/// including blocks sent dense or without rows. Other arrays are returned unchanged.
+pub(crate) fn encode_dense_array(array: ArrayRef, options: ArrowOptions) -> Result<ArrayRef> {
+    if !options.sparse_as_run_end_encoded || !is_run_end_encodable(array.data_type()) {
+        return Ok(array);
+    }
+
+    // A run ends at every row that differs from the next one, and at the last row. Each run's
+    // value is taken from its first row.
+    let len = array.len();
+    let mut run_ends = Vec::new();
+    let mut starts = Vec::new();
+    if len > 0 {
+        starts.push(0_u32);
+        let changed = arrow::compute::kernels::cmp::distinct(
+            &array.slice(0, len - 1),
+            &array.slice(1, len - 1),
+        )?;
+        for row in changed.values().set_indices() {
+            let end = run_end(row + 1)?;
+            run_ends.push(end);
+            starts.push(end.unsigned_abs());
+        }
+        run_ends.push(run_end(len)?);
+    }
+
+    let values = arrow::compute::take(&array, &UInt32Array::from(starts), None)?;
+    Ok(Arc::new(RunArray::<Int32Type>::try_new(&Int32Array::from(run_ends), &values)?))
+}
+
+/// Whether arrays of `data_type` can be sparse, and so are run end encoded with
+/// `sparse_as_run_end_encoded`. Mirrors the types supported by [`expand_sparse_array`].
+pub(crate) fn is_run_end_encodable(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::Date32
+            | DataType::Date64
+            | DataType::Timestamp(_, _)
+            | DataType::Decimal128(_, _)
+            | DataType::Decimal256(_, _)
+            | DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Binary
+            | DataType::LargeBinary
+            | DataType::Boolean
+            | DataType::FixedSizeBinary(_)
+    )
+}
+
+/// `row` as an `Int32` run end.
+fn run_end(row: usize) -> Result<i32> {
+    i32::try_from(row).map_err(|_| {
+        Error::DeserializeError(format!("Sparse run end {row} overflows Int32 run ends"))
+    })
+}
+
 fn expand_primitive<T: ArrowPrimitiveType>(
     sparse_array: &ArrayRef,
     offsets: &[usize],
     total_rows: usize,
-) -> ArrayRef
+) -> Result<ArrayRef>
 where
     T::Native: Default,
 {
-    let sparse = sparse_array.as_primitive::<T>();
+    let sparse = sparse_array
+        .as_primitive_opt::<T>()
+        .ok_or_else(|| Error::DeserializeError("Expected primitive array for sparse".into()))?;
     let mut builder = PrimitiveBuilder::<T>::with_capacity(total_rows);
 
     let mut offset_idx = 0;
@@ -239,15 +448,18 @@ where
         }
     }
 
-    Arc::new(builder.finish())
+    Ok(Arc::new(builder.finish()))
 }
 
 fn expand_string<O: OffsetSizeTrait>(
     sparse_array: &ArrayRef,
     offsets: &[usize],
     total_rows: usize,
-) -> ArrayRef {
-    let sparse = sparse_array.as_any().downcast_ref::<GenericStringArray<O>>().unwrap();
+) -> Result<ArrayRef> {
+    let sparse = sparse_array
+        .as_any()
+        .downcast_ref::<GenericStringArray<O>>()
+        .ok_or_else(|| Error::DeserializeError("Expected string array for sparse".into()))?;
     let mut builder =
         GenericStringBuilder::<O>::with_capacity(total_rows, sparse.value_data().len());
 
@@ -266,15 +478,18 @@ fn expand_string<O: OffsetSizeTrait>(
         }
     }
 
-    Arc::new(builder.finish())
+    Ok(Arc::new(builder.finish()))
 }
 
 fn expand_binary<O: OffsetSizeTrait>(
     sparse_array: &ArrayRef,
     offsets: &[usize],
     total_rows: usize,
-) -> ArrayRef {
-    let sparse = sparse_array.as_any().downcast_ref::<GenericBinaryArray<O>>().unwrap();
+) -> Result<ArrayRef> {
+    let sparse = sparse_array
+        .as_any()
+        .downcast_ref::<GenericBinaryArray<O>>()
+        .ok_or_else(|| Error::DeserializeError("Expected binary array for sparse".into()))?;
     let mut builder =
         GenericBinaryBuilder::<O>::with_capacity(total_rows, sparse.value_data().len());
 
@@ -293,11 +508,17 @@ fn expand_binary<O: OffsetSizeTrait>(
         }
     }
 
-    Arc::new(builder.finish())
+    Ok(Arc::new(builder.finish()))
 }
 
-fn expand_boolean(sparse_array: &ArrayRef, offsets: &[usize], total_rows: usize) -> ArrayRef {
-    let sparse = sparse_array.as_boolean();
+fn expand_boolean(
+    sparse_array: &ArrayRef,
+    offsets: &[usize],
+    total_rows: usize,
+) -> Result<ArrayRef> {
+    let sparse = sparse_array
+        .as_boolean_opt()
+        .ok_or_else(|| Error::DeserializeError("Expected boolean array for sparse".into()))?;
     let mut builder = BooleanBuilder::with_capacity(total_rows);
 
     let mut offset_idx = 0;
@@ -315,7 +536,7 @@ fn expand_boolean(sparse_array: &ArrayRef, offsets: &[usize], total_rows: usize)
         }
     }
 
-    Arc::new(builder.finish())
+    Ok(Arc::new(builder.finish()))
 }
 
 #[allow(clippy::cast_sign_loss)] // size is always positive from Arrow schema
@@ -324,8 +545,10 @@ fn expand_fixed_size_binary(
     offsets: &[usize],
     total_rows: usize,
     size: i32,
-) -> ArrayRef {
-    let sparse = sparse_array.as_fixed_size_binary();
+) -> Result<ArrayRef> {
+    let sparse = sparse_array.as_fixed_size_binary_opt().ok_or_else(|| {
+        Error::DeserializeError("Expected fixed size binary array for sparse".into())
+    })?;
     let mut builder = FixedSizeBinaryBuilder::with_capacity(total_rows, size);
     let default_value = vec![0u8; size as usize];
 
@@ -335,16 +558,16 @@ fn expand_fixed_size_binary(
             if sparse.is_null(offset_idx) {
                 builder.append_null();
             } else {
-                builder.append_value(sparse.value(offset_idx)).unwrap();
+                builder.append_value(sparse.value(offset_idx))?;
             }
             offset_idx += 1;
         } else {
             // Default is zeros
-            builder.append_value(&default_value).unwrap();
+            builder.append_value(&default_value)?;
         }
     }
 
-    Arc::new(builder.finish())
+    Ok(Arc::new(builder.finish()))
 }
 
 #[cfg(test)]
@@ -379,8 +602,7 @@ mod tests {
         data.extend(encode_var_uint(3 | END_OF_GRANULE_FLAG)); // 3 trailing defaults
 
         let mut bytes = Bytes::from(data);
-        let mut state = SparseDeserializeState::default();
-        let offsets = read_sparse_offsets_sync(&mut bytes, 8, &mut state).unwrap();
+        let offsets = read_sparse_offsets_sync(&mut bytes, 8).unwrap();
 
         assert_eq!(offsets, vec![2, 4]);
     }
@@ -394,8 +616,7 @@ mod tests {
         data.extend(encode_var_uint(4 | END_OF_GRANULE_FLAG));
 
         let mut bytes = Bytes::from(data);
-        let mut state = SparseDeserializeState::default();
-        let offsets = read_sparse_offsets_sync(&mut bytes, 4, &mut state).unwrap();
+        let offsets = read_sparse_offsets_sync(&mut bytes, 4).unwrap();
 
         assert!(offsets.is_empty());
     }
@@ -415,8 +636,7 @@ mod tests {
         data.extend(encode_var_uint(END_OF_GRANULE_FLAG)); // 0 trailing defaults
 
         let mut bytes = Bytes::from(data);
-        let mut state = SparseDeserializeState::default();
-        let offsets = read_sparse_offsets_sync(&mut bytes, 3, &mut state).unwrap();
+        let offsets = read_sparse_offsets_sync(&mut bytes, 3).unwrap();
 
         assert_eq!(offsets, vec![0, 1, 2]);
     }
@@ -432,8 +652,7 @@ mod tests {
         data.extend(encode_var_uint(END_OF_GRANULE_FLAG)); // 0 trailing defaults
 
         let mut bytes = Bytes::from(data);
-        let mut state = SparseDeserializeState::default();
-        let offsets = read_sparse_offsets_sync(&mut bytes, 4, &mut state).unwrap();
+        let offsets = read_sparse_offsets_sync(&mut bytes, 4).unwrap();
 
         assert_eq!(offsets, vec![0, 3]);
     }
@@ -512,4 +731,199 @@ mod tests {
             assert_eq!(expanded_i32.value(i), 0);
         }
     }
+
+    #[test]
+    fn test_expand_sparse_length_mismatch() {
+        let sparse_array: ArrayRef = Arc::new(Int32Array::from(vec![1, 2]));
+        let result = expand_sparse_array(&sparse_array, &[0], 5);
+        assert!(matches!(result, Err(Error::DeserializeError(_))));
+    }
+
+    #[test]
+    fn test_expand_sparse_invalid_offsets() {
+        let sparse_array: ArrayRef = Arc::new(Int32Array::from(vec![1, 2]));
+        // Out of range
+        assert!(expand_sparse_array(&sparse_array, &[1, 5], 5).is_err());
+        // Not strictly increasing
+        assert!(expand_sparse_array(&sparse_array, &[3, 3], 5).is_err());
+        assert!(expand_sparse_array(&sparse_array, &[3, 1], 5).is_err());
+    }
+
+    #[test]
+    fn test_read_sparse_offsets_rejects_defaults_beyond_rows() {
+        let mut bytes = Bytes::from(encode_var_uint(5 | END_OF_GRANULE_FLAG));
+        let result = read_sparse_offsets_sync(&mut bytes, 4);
+        assert!(matches!(
+            result,
+            Err(Error::SparseOffsets { reason: "defaults beyond block rows", .. })
+        ));
+
+        // As are huge group sizes
+        let mut data = encode_var_uint(0);
+        data.extend(encode_var_uint((END_OF_GRANULE_FLAG - 1) | END_OF_GRANULE_FLAG));
+        let mut bytes = Bytes::from(data);
+        assert!(read_sparse_offsets_sync(&mut bytes, 4).is_err());
+    }
+
+    #[test]
+    fn test_read_sparse_offsets_rejects_values_beyond_rows() {
+        // Endless stream of values past the end of the block
+        let mut data = Vec::new();
+        data.extend(encode_var_uint(10));
+        data.extend(std::iter::repeat_n(0, 1024));
+        data.extend(encode_var_uint(END_OF_GRANULE_FLAG));
+
+        let mut bytes = Bytes::from(data);
+        let result = read_sparse_offsets_sync(&mut bytes, 4);
+        assert!(matches!(result, Err(Error::SparseOffsets { num_rows: 4, .. })));
+
+        // A value right after the last row
+        let mut data = vec![0; 4];
+        data.extend(encode_var_uint(0));
+        data.extend(encode_var_uint(END_OF_GRANULE_FLAG));
+        let mut bytes = Bytes::from(data);
+        let result = read_sparse_offsets_sync(&mut bytes, 4);
+        assert!(matches!(
+            result,
+            Err(Error::SparseOffsets { reason: "non-default value beyond block rows", .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_sparse_offsets_independent_reads() {
+        // Two sparse columns of 4 rows back to back, [0, v, 0, 0] and [v, 0, 0, v]
+        let mut data = encode_var_uint(1);
+        data.extend(encode_var_uint(2 | END_OF_GRANULE_FLAG));
+        data.extend(encode_var_uint(0));
+        data.extend(encode_var_uint(2));
+        data.extend(encode_var_uint(END_OF_GRANULE_FLAG));
+
+        let mut bytes = Bytes::from(data);
+        assert_eq!(read_sparse_offsets_sync(&mut bytes, 4).unwrap(), vec![1]);
+        assert_eq!(read_sparse_offsets_sync(&mut bytes, 4).unwrap(), vec![0, 3]);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_read_sparse_offsets_truncated() {
+        let mut bytes = Bytes::from(encode_var_uint(1));
+        assert!(read_sparse_offsets_sync(&mut bytes, 4).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_sparse_offsets_async_rejects_values_beyond_rows() {
+        let mut data = encode_var_uint(8);
+        data.extend(encode_var_uint(0));
+        data.extend(encode_var_uint(END_OF_GRANULE_FLAG));
+
+        let mut reader = std::io::Cursor::new(data);
+        let result = read_sparse_offsets(&mut reader, 4).await;
+        assert!(matches!(result, Err(Error::SparseOffsets { .. })));
+    }
+
+    #[test]
+    fn test_encode_sparse_run_end_encoded() {
+        // [0, 0, 0, 5, 6, 0, 0, 0]
+        let sparse_array: ArrayRef = Arc::new(Int32Array::from(vec![5, 6]));
+        let encoded = encode_sparse_array(&sparse_array, &[3, 4], 8).unwrap();
+        let run_array = encoded.as_run::<Int32Type>();
+
+        assert_eq!(run_array.len(), 8);
+        assert_eq!(run_array.run_ends().values(), &[3, 4, 5, 8]);
+        assert_eq!(run_array.values().as_primitive::<Int32Type>().values(), &[0, 5, 6, 0]);
+    }
+
+    #[test]
+    fn test_encode_sparse_string_edges() {
+        // Values at the first and last rows, no leading or trailing default runs
+        let sparse_array: ArrayRef = Arc::new(StringArray::from(vec![Some("a"), None]));
+        let encoded = encode_sparse_array(&sparse_array, &[0, 3], 4).unwrap();
+        let run_array = encoded.as_run::<Int32Type>();
+
+        assert_eq!(run_array.run_ends().values(), &[1, 3, 4]);
+        let values = run_array.values().as_string::<i32>();
+        assert_eq!(values.value(0), "a");
+        assert_eq!(values.value(1), "");
+        assert!(values.is_null(2));
+
+        // Offsets are validated the same way as expansion
+        assert!(encode_sparse_array(&sparse_array, &[0, 4], 4).is_err());
+    }
+
+    #[test]
+    fn test_encode_dense_run_end_encoded() {
+        let options = ArrowOptions::default().with_sparse_as_run_end_encoded(true);
+        let array: ArrayRef =
+            Arc::new(StringArray::from(vec![Some("a"), Some("a"), None, None, Some("b")]));
+        let encoded = encode_dense_array(Arc::clone(&array), options).unwrap();
+        let run_array = encoded.as_run::<Int32Type>();
+
+        assert_eq!(run_array.len(), 5);
+        assert_eq!(run_array.run_ends().values(), &[2, 4, 5]);
+        let values = run_array.values().as_string::<i32>();
+        assert_eq!(values.value(0), "a");
+        assert!(values.is_null(1));
+        assert_eq!(values.value(2), "b");
+
+        // Same type as a sparse column, even without rows
+        let sparse = encode_sparse_array(&array.slice(0, 1), &[1], 3).unwrap();
+        let empty = encode_dense_array(array.slice(0, 0), options).unwrap();
+        assert_eq!(empty.len(), 0);
+        assert_eq!(empty.data_type(), sparse.data_type());
+        assert_eq!(encoded.data_type(), sparse.data_type());
+
+        // Unchanged when disabled or for types that are never sparse
+        let dense = encode_dense_array(Arc::clone(&array), ArrowOptions::default()).unwrap();
+        assert_eq!(dense.data_type(), &DataType::Utf8);
+        let list: ArrayRef = Arc::new(ListArray::new_null(
+            Arc::new(Field::new_list_field(DataType::Int32, true)),
+            2,
+        ));
+        let list = encode_dense_array(list, options).unwrap();
+        assert!(matches!(list.data_type(), DataType::List(_)));
+    }
+
+    #[test]
+    fn test_read_serialization_kinds_default() {
+        let mut bytes = Bytes::from(vec![0_u8]);
+        let kinds = read_serialization_kinds_sync(&mut bytes, &Type::Int64).unwrap();
+        assert_eq!(kinds, SerializationKinds::default());
+        assert!(!kinds.is_custom());
+    }
+
+    #[test]
+    fn test_read_serialization_kinds_nested_tuple() {
+        // Tuple(Int64, Tuple(Int32, String)) -> 5 kinds in pre-order
+        let type_ = Type::Tuple(vec![Type::Int64, Type::Tuple(vec![Type::Int32, Type::String])]);
+        let mut bytes = Bytes::from(vec![1, 0, 1, 0, 0, 5, 2, 2, 1]);
+        let kinds = read_serialization_kinds_sync(&mut bytes, &type_).unwrap();
+
+        assert!(!kinds.sparse);
+        assert!(kinds.has_nested_sparse());
+        assert!(kinds.elements[0].sparse);
+        assert!(!kinds.elements[1].sparse);
+        assert!(!kinds.elements[1].elements[0].sparse);
+        // COMBINATION containing SPARSE
+        assert!(kinds.elements[1].elements[1].sparse);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_read_serialization_kinds_rejects_large_stack() {
+        let mut data = vec![1, 5];
+        data.extend(encode_var_uint(u64::MAX));
+        let mut bytes = Bytes::from(data);
+        assert!(read_serialization_kinds_sync(&mut bytes, &Type::Int64).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_serialization_kinds_async_matches_sync() {
+        let type_ = Type::Tuple(vec![Type::Int64, Type::String]);
+        let data = vec![1, 0, 1, 0];
+        let mut reader = std::io::Cursor::new(data.clone());
+        let async_kinds = read_serialization_kinds(&mut reader, &type_).await.unwrap();
+        let sync_kinds = read_serialization_kinds_sync(&mut Bytes::from(data), &type_).unwrap();
+        assert_eq!(async_kinds, sync_kinds);
+        assert!(async_kinds.elements[0].sparse);
+    }
 }
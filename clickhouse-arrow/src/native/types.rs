@@ -218,6 +218,38 @@ impl Type {
         }
     }
 
+    /// Rewrite `Nullable(LowCardinality(T))`, which `ClickHouse` rejects, into the server-legal
+    /// `LowCardinality(Nullable(T))`, recursing into container types.
+    ///
+    /// Both forms describe the same data on the wire, so this is applied to user-provided types
+    /// before they are written into DDL.
+    #[must_use]
+    pub fn normalize_low_cardinality(self) -> Type {
+        match self {
+            Type::Nullable(inner) => match *inner {
+                Type::LowCardinality(inner) => Type::LowCardinality(Box::new(
+                    inner.normalize_low_cardinality().into_nullable(),
+                )),
+                inner => Type::Nullable(Box::new(inner.normalize_low_cardinality())),
+            },
+            Type::LowCardinality(inner) => {
+                Type::LowCardinality(Box::new(inner.normalize_low_cardinality()))
+            }
+            Type::Array(inner) => Type::Array(Box::new(inner.normalize_low_cardinality())),
+            Type::Map(key, value) => Type::Map(
+                Box::new(key.normalize_low_cardinality()),
+                Box::new(value.normalize_low_cardinality()),
+            ),
+            Type::Tuple(inner) => {
+                Type::Tuple(inner.into_iter().map(Type::normalize_low_cardinality).collect())
+            }
+            Type::Nested(fields) => Type::Nested(
+                fields.into_iter().map(|(n, t)| (n, t.normalize_low_cardinality())).collect(),
+            ),
+            t => t,
+        }
+    }
+
     pub fn default_value(&self) -> Value {
         match self {
             Type::Int8 => Value::Int8(0),
@@ -554,6 +554,13 @@ impl FromStr for Type {
                         .collect::<Result<_, _>>()?;
                     Type::Nested(fields)
                 }
+                // Deprecated `Object('json')`, sent as tuples whose type varies between blocks
+                "Object" => {
+                    return Err(Error::TypeParseError(format!(
+                        "deprecated type '{s}' cannot be read natively, use \
+                         `UnknownTypePolicy::Skip` or `Raw` to read it as JSON strings"
+                    )));
+                }
                 id => {
                     return Err(Error::TypeParseError(format!(
                         "invalid type with arguments: '{ident}' (ident = {id})"
@@ -955,6 +962,7 @@ mod tests {
         );
         assert_eq!(Type::from_str("JSON").unwrap(), Type::Object);
         assert_eq!(Type::from_str("Object").unwrap(), Type::Object);
+        assert!(Type::from_str("Object('json')").is_err()); // Legacy, read as JSON strings
 
         assert!(Type::from_str("LowCardinality()").is_err()); // Missing arg
         assert!(Type::from_str("Array(Int32, String)").is_err()); // Too many args
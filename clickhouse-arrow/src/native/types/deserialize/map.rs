@@ -55,13 +55,24 @@ impl Deserializer for MapDeserializer {
             offsets.push(reader.read_u64_le().await?);
         }
 
-        #[expect(clippy::cast_possible_truncation)]
-        let total_length = *offsets.last().unwrap() as usize;
+        if offsets.windows(2).any(|w| w[0] > w[1]) {
+            return Err(Error::DeserializeError("Map offsets must be non-decreasing".to_string()));
+        }
+        let total_length = offsets
+            .last()
+            .and_then(|&total| usize::try_from(total).ok())
+            .filter(|&total| total <= MAX_STRING_SIZE)
+            .ok_or_else(|| Error::DeserializeError("Map offsets out of range".to_string()))?;
 
         let keys = key.deserialize_column(reader, total_length, state).await?;
-        assert_eq!(keys.len(), total_length);
         let values = value.deserialize_column(reader, total_length, state).await?;
-        assert_eq!(values.len(), total_length);
+        if keys.len() != total_length || values.len() != total_length {
+            return Err(Error::DeserializeError(format!(
+                "Map expected {total_length} entries, got {} keys and {} values",
+                keys.len(),
+                values.len()
+            )));
+        }
 
         let mut keys = keys.into_iter();
         let mut values = values.into_iter();
@@ -71,8 +82,11 @@ impl Deserializer for MapDeserializer {
             let mut key_out = vec![];
             let mut value_out = vec![];
             while last_offset < offset {
-                key_out.push(keys.next().unwrap());
-                value_out.push(values.next().unwrap());
+                let (Some(key), Some(value)) = (keys.next(), values.next()) else {
+                    return Err(Error::DeserializeError("Map entries exhausted".to_string()));
+                };
+                key_out.push(key);
+                value_out.push(value);
                 last_offset += 1;
             }
             out.push(Value::Map(key_out, value_out));
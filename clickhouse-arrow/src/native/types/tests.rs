@@ -1,5 +1,6 @@
 use std::io::Cursor;
 use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
 
 use chrono_tz::Tz;
 use uuid::Uuid;
@@ -759,6 +760,39 @@ fn test_type_methods() {
     assert_eq!(Type::String.into_nullable(), t);
 }
 
+#[test]
+fn test_normalize_low_cardinality() {
+    let lc_null_string = Type::LowCardinality(Box::new(Type::Nullable(Box::new(Type::String))));
+    let null_lc_string = Type::from_str("Nullable(LowCardinality(String))").unwrap();
+    assert_eq!(null_lc_string.clone().normalize_low_cardinality(), lc_null_string);
+    assert_eq!(
+        null_lc_string.normalize_low_cardinality().to_string(),
+        "LowCardinality(Nullable(String))"
+    );
+    // Already legal types are left untouched
+    assert_eq!(lc_null_string.clone().normalize_low_cardinality(), lc_null_string);
+    assert_eq!(
+        Type::Nullable(Box::new(Type::Int32)).normalize_low_cardinality(),
+        Type::Nullable(Box::new(Type::Int32))
+    );
+    // Nested occurrences are rewritten too
+    let nested = Type::from_str(
+        "Tuple(Map(String, Array(Nullable(LowCardinality(String)))), \
+         Nullable(LowCardinality(String)))",
+    )
+    .unwrap();
+    assert_eq!(
+        nested.normalize_low_cardinality(),
+        Type::Tuple(vec![
+            Type::Map(
+                Box::new(Type::String),
+                Box::new(Type::Array(Box::new(lc_null_string.clone())))
+            ),
+            lc_null_string,
+        ])
+    );
+}
+
 #[test]
 fn test_type_validate() {
     assert!(Type::Decimal32(100).validate().is_err());
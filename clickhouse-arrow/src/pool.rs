@@ -1,12 +1,20 @@
 use std::num::NonZeroU64;
-use std::time::Duration;
-
-use bb8::ManageConnection;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use arrow::record_batch::RecordBatch;
+use bb8::{ManageConnection, PooledConnection, RunError};
+use futures_util::StreamExt;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::time::timeout;
 
 use crate::prelude::*;
 use crate::settings::Settings;
-use crate::{Client, ClientBuilder, ClientOptions, ConnectionStatus, Destination, Error, Result};
+use crate::{
+    ClickHouseResponse, Client, ClientBuilder, ClientOptions, ConnectionStatus, Destination, Error,
+    Result,
+};
 
 /// Alias for `ConnectionPoolBuilder<NativeFormat>`
 pub type NativeConnectionPoolBuilder = ConnectionPoolBuilder<NativeFormat>;
@@ -24,6 +32,10 @@ pub type NativePoolBuilder = bb8::Builder<ConnectionManager<NativeFormat>>;
 pub type ArrowPoolBuilder = bb8::Builder<ConnectionManager<ArrowFormat>>;
 /// Alias for [`bb8::Pool<ConnectionManager<T>>`]
 pub type ConnectionPool<T> = bb8::Pool<ConnectionManager<T>>;
+/// Alias for `ClientPool<NativeFormat>`
+pub type NativeClientPool = ClientPool<NativeFormat>;
+/// Alias for `ClientPool<ArrowFormat>`
+pub type ArrowClientPool = ClientPool<ArrowFormat>;
 
 /// Helper to construct a bb8 connection pool
 pub struct ConnectionPoolBuilder<T: ClientFormat> {
@@ -102,6 +114,24 @@ impl<T: ClientFormat> ConnectionPoolBuilder<T> {
             .with_check(self.check_health);
         self.pool.build(manager).await
     }
+
+    /// Builds a connection pool with priority lanes, see [`PriorityPool`].
+    ///
+    /// # Errors
+    /// Returns an error if the connection manager build fails or the pool build fails, ie
+    /// `Destination` fails to verify.
+    pub async fn build_priority(self, limits: PriorityLimits) -> Result<PriorityPool<T>> {
+        Ok(PriorityPool::new(self.build().await?, limits))
+    }
+
+    /// Builds a [`ClientPool`], checking out a connection per query or insert.
+    ///
+    /// # Errors
+    /// Returns an error if the connection manager build fails or the pool build fails, ie
+    /// `Destination` fails to verify.
+    pub async fn build_client_pool(self) -> Result<ClientPool<T>> {
+        Ok(ClientPool::new(self.build().await?))
+    }
 }
 
 /// `ConnectionManager` is the underlying manager that `bb8::Pool` uses to manage connections.
@@ -240,6 +270,318 @@ impl<T: ClientFormat> ManageConnection for ConnectionManager<T> {
     }
 }
 
+/// A pool of warm connections, checking one out for each query or insert.
+///
+/// Sharing one [`Client`] across tasks serializes their queries on a single connection. A
+/// `ClientPool` gives each operation its own pooled connection and returns it to the pool once
+/// the operation completes: inserts and executes when they return, queries when their response
+/// is exhausted or dropped. Connections are validated on check out and replaced when broken.
+///
+/// Build one with [`ClientBuilder::build_pool`], which keeps the given number of connections
+/// open, or wrap a pool configured through [`ConnectionPoolBuilder`] with [`ClientPool::new`].
+/// Use [`ClientPool::get`] to run several statements on the same connection.
+///
+/// # Example
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let pool = ClientBuilder::new()
+///     .with_endpoint("localhost:9000")
+///     .build_pool::<ArrowFormat>(8)
+///     .await?;
+///
+/// // Each task checks out its own connection for the duration of its query
+/// let batches = pool.query("SELECT 1", None).await?.collect_result().await?;
+/// pool.insert("INSERT INTO events FORMAT Native", batch, None).await?;
+/// ```
+#[derive(Clone)]
+pub struct ClientPool<T: ClientFormat> {
+    pool: ConnectionPool<T>,
+}
+
+impl<T: ClientFormat> ClientPool<T> {
+    /// Wrap an existing pool.
+    pub fn new(pool: ConnectionPool<T>) -> Self { Self { pool } }
+
+    /// The underlying pool.
+    pub fn pool(&self) -> &ConnectionPool<T> { &self.pool }
+
+    /// Connections currently open and idle in the pool.
+    pub fn state(&self) -> bb8::State { self.pool.state() }
+
+    /// Check out a connection, returned to the pool when dropped.
+    ///
+    /// # Errors
+    /// Returns an error if the pool fails to provide a connection or times out.
+    pub async fn get(&self) -> Result<PooledConnection<'static, ConnectionManager<T>>> {
+        self.pool.get_owned().await.map_err(checkout_error)
+    }
+
+    /// Execute `query` on a pooled connection, discarding any returned data. See
+    /// [`Client::execute`].
+    ///
+    /// # Errors
+    /// Returns an error if no connection can be checked out or the query fails.
+    pub async fn execute(&self, query: impl Into<ParsedQuery>, qid: Option<Qid>) -> Result<()> {
+        self.get().await?.execute(query, qid).await
+    }
+
+    /// Insert `block` on a pooled connection, waiting for the insert to complete. See
+    /// [`Client::insert`].
+    ///
+    /// # Errors
+    /// Returns an error if no connection can be checked out or the insert fails.
+    pub async fn insert(
+        &self,
+        query: impl Into<ParsedQuery>,
+        block: T::Data,
+        qid: Option<Qid>,
+    ) -> Result<()> {
+        let conn = self.get().await?;
+        let stream = conn.insert(query, block, qid).await?;
+        tokio::pin!(stream);
+        while let Some(result) = stream.next().await {
+            result?;
+        }
+        Ok(())
+    }
+}
+
+impl ClientPool<ArrowFormat> {
+    /// Run `query` on a pooled connection, held until the response is exhausted or dropped. See
+    /// [`Client::query`](crate::ArrowClient::query).
+    ///
+    /// # Errors
+    /// Returns an error if no connection can be checked out or the query fails to start.
+    pub async fn query(
+        &self,
+        query: impl Into<ParsedQuery>,
+        qid: Option<Qid>,
+    ) -> Result<ClickHouseResponse<RecordBatch>> {
+        let conn = self.get().await?;
+        let response = conn.query(query, qid).await?;
+        Ok(response.hold_until_complete(conn))
+    }
+
+    /// Run `query` with `options` on a pooled connection, held until the response is exhausted
+    /// or dropped. See [`Client::query_with_options`](crate::ArrowClient::query_with_options).
+    ///
+    /// # Errors
+    /// Returns an error if no connection can be checked out or the query fails to start.
+    pub async fn query_with_options(
+        &self,
+        query: impl Into<ParsedQuery>,
+        options: QueryOptions,
+    ) -> Result<ClickHouseResponse<RecordBatch>> {
+        let conn = self.get().await?;
+        let response = conn.query_with_options(query, options).await?;
+        Ok(response.hold_until_complete(conn))
+    }
+}
+
+fn checkout_error(error: RunError<Error>) -> Error {
+    match error {
+        RunError::User(error) => error,
+        RunError::TimedOut => {
+            Error::ConnectionTimeout("Timed out waiting for a pooled connection".into())
+        }
+    }
+}
+
+/// Priority class of work checked out of a [`PriorityPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Latency sensitive work, ie dashboard queries.
+    Interactive,
+    /// Throughput oriented work, ie bulk inserts and backfills.
+    Background,
+}
+
+/// Per-class concurrency limits of a [`PriorityPool`].
+///
+/// Keeping `background` below the pool's `max_size` reserves the remaining connections for
+/// interactive work, so interactive queries never wait behind bulk traffic for a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityLimits {
+    /// Maximum connections checked out for [`Priority::Interactive`] work at once.
+    pub interactive: usize,
+    /// Maximum connections checked out for [`Priority::Background`] work at once.
+    pub background:  usize,
+}
+
+impl PriorityLimits {
+    /// Create limits from the maximum connections checked out per class. Limits of 0 are
+    /// treated as 1.
+    pub fn new(interactive: usize, background: usize) -> Self { Self { interactive, background } }
+
+    /// Limits for a pool of `max_size` connections, reserving `reserved` of them for interactive
+    /// work.
+    pub fn reserve_interactive(max_size: u32, reserved: u32) -> Self {
+        let background = max_size.saturating_sub(reserved).max(1);
+        Self { interactive: max_size as usize, background: background as usize }
+    }
+}
+
+/// Point in time metrics of a [`PriorityPool`] lane.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LaneStats {
+    /// The lane's concurrency limit.
+    pub limit:     usize,
+    /// Connections currently checked out through the lane.
+    pub in_flight: usize,
+    /// Callers currently waiting for the lane or for a connection.
+    pub waiting:   usize,
+    /// Connections checked out through the lane since the pool was created.
+    pub acquired:  u64,
+    /// Total time callers waited for the lane and a connection.
+    pub wait_time: Duration,
+    /// Longest time a caller waited for the lane and a connection.
+    pub max_wait:  Duration,
+}
+
+/// A connection pool with separate concurrency limits for interactive and background work.
+///
+/// Each [`Priority`] class has its own lane, capping how many connections it may hold at once.
+/// Limiting background work below the pool size keeps connections free for interactive queries,
+/// so dashboards aren't starved behind bulk inserts sharing the pool. Per-lane metrics are
+/// available through [`PriorityPool::stats`].
+///
+/// # Example
+/// ```rust,ignore
+/// use clickhouse_arrow::prelude::*;
+///
+/// let pool = ArrowConnectionPoolBuilder::new("localhost:9000")
+///     .configure_pool(|pool| pool.max_size(16))
+///     .build_priority(PriorityLimits::reserve_interactive(16, 4))
+///     .await?;
+///
+/// let client = pool.get(Priority::Interactive).await?;
+/// let batches = client.query("SELECT 1", None).await?.collect::<Vec<_>>().await;
+/// ```
+#[derive(Clone)]
+pub struct PriorityPool<T: ClientFormat> {
+    pool:        ConnectionPool<T>,
+    interactive: Arc<Lane>,
+    background:  Arc<Lane>,
+}
+
+impl<T: ClientFormat> PriorityPool<T> {
+    /// Wrap an existing pool with priority lanes.
+    pub fn new(pool: ConnectionPool<T>, limits: PriorityLimits) -> Self {
+        Self {
+            pool,
+            interactive: Arc::new(Lane::new(limits.interactive)),
+            background: Arc::new(Lane::new(limits.background)),
+        }
+    }
+
+    /// The underlying pool, bypassing the lanes.
+    pub fn pool(&self) -> &ConnectionPool<T> { &self.pool }
+
+    /// Check out a connection for work of the given priority, waiting for a free slot in its
+    /// lane and then for a pooled connection.
+    ///
+    /// # Errors
+    /// Returns an error if the pool fails to provide a connection or times out.
+    pub async fn get(&self, priority: Priority) -> Result<PriorityConnection<T>> {
+        let lane = self.lane(priority);
+        let started = Instant::now();
+        let _waiting = lane.wait();
+        let permit = lane.acquire().await?;
+        let conn = self.pool.get_owned().await.map_err(checkout_error)?;
+        lane.record(started.elapsed());
+        trace!(?priority, wait = ?started.elapsed(), "Checked out pooled connection");
+        Ok(PriorityConnection { conn, _permit: permit })
+    }
+
+    /// Metrics of the lane for the given priority.
+    pub fn stats(&self, priority: Priority) -> LaneStats { self.lane(priority).stats() }
+
+    fn lane(&self, priority: Priority) -> &Lane {
+        match priority {
+            Priority::Interactive => &self.interactive,
+            Priority::Background => &self.background,
+        }
+    }
+}
+
+/// A pooled [`Client`] checked out of a [`PriorityPool`], returned to the pool and releasing
+/// its lane slot when dropped.
+pub struct PriorityConnection<T: ClientFormat> {
+    conn:    PooledConnection<'static, ConnectionManager<T>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<T: ClientFormat> std::ops::Deref for PriorityConnection<T> {
+    type Target = Client<T>;
+
+    fn deref(&self) -> &Self::Target { &self.conn }
+}
+
+impl<T: ClientFormat> std::ops::DerefMut for PriorityConnection<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.conn }
+}
+
+/// A [`PriorityPool`] lane, limiting concurrency and tracking metrics for one priority class.
+#[derive(Debug)]
+struct Lane {
+    limit:          usize,
+    permits:        Arc<Semaphore>,
+    waiting:        AtomicUsize,
+    acquired:       AtomicU64,
+    wait_nanos:     AtomicU64,
+    max_wait_nanos: AtomicU64,
+}
+
+impl Lane {
+    fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            limit,
+            permits: Arc::new(Semaphore::new(limit)),
+            waiting: AtomicUsize::new(0),
+            acquired: AtomicU64::new(0),
+            wait_nanos: AtomicU64::new(0),
+            max_wait_nanos: AtomicU64::new(0),
+        }
+    }
+
+    async fn acquire(&self) -> Result<OwnedSemaphorePermit> {
+        Arc::clone(&self.permits).acquire_owned().await.map_err(|_| Error::ChannelClosed)
+    }
+
+    /// Count the caller as waiting until the returned guard is dropped.
+    fn wait(&self) -> WaitGuard<'_> {
+        let _ = self.waiting.fetch_add(1, Ordering::Relaxed);
+        WaitGuard(&self.waiting)
+    }
+
+    #[expect(clippy::cast_possible_truncation)]
+    fn record(&self, wait: Duration) {
+        let nanos = wait.as_nanos() as u64;
+        let _ = self.acquired.fetch_add(1, Ordering::Relaxed);
+        let _ = self.wait_nanos.fetch_add(nanos, Ordering::Relaxed);
+        let _ = self.max_wait_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> LaneStats {
+        LaneStats {
+            limit:     self.limit,
+            in_flight: self.limit - self.permits.available_permits(),
+            waiting:   self.waiting.load(Ordering::Relaxed),
+            acquired:  self.acquired.load(Ordering::Relaxed),
+            wait_time: Duration::from_nanos(self.wait_nanos.load(Ordering::Relaxed)),
+            max_wait:  Duration::from_nanos(self.max_wait_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+struct WaitGuard<'a>(&'a AtomicUsize);
+
+impl Drop for WaitGuard<'_> {
+    fn drop(&mut self) { let _ = self.0.fetch_sub(1, Ordering::Relaxed); }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ExponentialBackoff {
     current_interval: Duration,
@@ -280,3 +622,40 @@ impl ExponentialBackoff {
 impl Default for ExponentialBackoff {
     fn default() -> Self { Self::new() }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_limits() {
+        let limits = PriorityLimits::reserve_interactive(16, 4);
+        assert_eq!(limits, PriorityLimits::new(16, 12));
+        // Background work always gets at least one connection
+        assert_eq!(PriorityLimits::reserve_interactive(4, 8).background, 1);
+    }
+
+    #[tokio::test]
+    async fn test_lane_limits_and_stats() {
+        let lane = Lane::new(2);
+        let first = lane.acquire().await.unwrap();
+        let _second = lane.acquire().await.unwrap();
+        lane.record(Duration::from_millis(5));
+        lane.record(Duration::from_millis(1));
+
+        // The lane is full, further callers wait
+        let waiting = lane.wait();
+        assert!(tokio::time::timeout(Duration::from_millis(20), lane.acquire()).await.is_err());
+        let stats = lane.stats();
+        assert_eq!(stats.in_flight, 2);
+        assert_eq!(stats.waiting, 1);
+        assert_eq!(stats.acquired, 2);
+        assert_eq!(stats.wait_time, Duration::from_millis(6));
+        assert_eq!(stats.max_wait, Duration::from_millis(5));
+
+        drop((waiting, first));
+        let _third = lane.acquire().await.unwrap();
+        assert_eq!(lane.stats().waiting, 0);
+        assert_eq!(Lane::new(0).stats().limit, 1);
+    }
+}
@@ -1,21 +1,30 @@
 //! ## Convenience exports for working with the library.
 pub use tracing::{Instrument, Span, debug, error, info, instrument, trace, trace_span, warn};
 
+pub use crate::arrow::lazy::LazyBatch;
 pub use crate::arrow::types::SchemaConversions;
 pub use crate::errors::*;
 pub use crate::explain::{
     ExplainEstimateRow, ExplainFormat, ExplainMode, ExplainOperation, ExplainOptions,
     ExplainResult, QueryOptions,
 };
-pub use crate::formats::{ArrowFormat, ClientFormat, NativeFormat};
-pub use crate::limits::{LimitedResponse, QueryLimits, QueryStats, TruncationReason};
+pub use crate::explore::{ColumnStats, ColumnSummary, Preview, SampleMethod, TableStats};
+pub use crate::formats::{ArrowFormat, ClientFormat, LazyArrowFormat, NativeFormat};
+pub use crate::limits::{
+    LimitedResponse, QueryLimits, QueryStats, ResultLimitExceeded, TruncationReason,
+};
 pub use crate::native::protocol::*;
 pub use crate::native::values::*;
 pub use crate::query::{ParamValue, ParsedQuery, Qid, QueryParams};
 pub use crate::schema::*;
 pub use crate::settings::*;
 pub use crate::telemetry::*;
-pub use crate::{ArrowClient, Client, ClientBuilder, CompressionMethod, NativeClient, Row, Type};
+pub use crate::validation::BlockValidator;
+pub use crate::{
+    ArrowClient, Client, ClientBuilder, ClientInfoOptions, CompressionMethod, EndpointStrategy,
+    FrameLimits, InsertCoalescing, InsertRateLimit, LazyArrowClient, NativeClient, QueryQueue,
+    QueryResult, QueryUpdate, ReconnectPolicy, Row, Type,
+};
 
 // TODO: Encrypt
 /// Newtype to protect secrets from being logged
@@ -2,10 +2,10 @@ use std::fmt;
 
 use uuid::Uuid;
 
-use crate::Result;
 use crate::io::ClickHouseWrite;
 use crate::prelude::SettingValue;
 use crate::settings::SETTING_FLAG_CUSTOM;
+use crate::{Error, Result};
 
 /// An internal representation of a query id, meant to reduce costs when tracing, passing around,
 /// and converting to strings.
@@ -98,6 +98,35 @@ impl QueryParams {
     /// Returns the number of query parameters.
     pub(crate) fn len(&self) -> usize { self.0.len() }
 
+    /// Checks the parameters bound to `{name:Identifier}` placeholders in `query`.
+    ///
+    /// `ClickHouse` substitutes identifier parameters as a single, unparsed name, so the value
+    /// `"db.events"` names a table called `db.events` rather than the table `events` in `db`. Use
+    /// separate placeholders, e.g. `{db:Identifier}.{table:Identifier}`, for qualified names.
+    ///
+    /// # Errors
+    /// Returns an error if an identifier parameter is missing, is not a string, or fails
+    /// [`validate_identifier`].
+    pub fn validate_identifiers(&self, query: &str) -> Result<()> {
+        for name in identifier_placeholders(query) {
+            let value = self.0.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+            match value {
+                Some(SettingValue::String(value)) => validate_identifier(value).map_err(|e| {
+                    Error::Client(format!("Invalid identifier parameter '{name}': {e}"))
+                })?,
+                Some(value) => {
+                    return Err(Error::Client(format!(
+                        "Identifier parameter '{name}' must be a string, found {value}"
+                    )));
+                }
+                None => {
+                    return Err(Error::Client(format!("Missing identifier parameter '{name}'")));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Encodes query parameters to the `ClickHouse` native protocol.
     ///
     /// Parameters are encoded using the Settings wire format with custom flag:
@@ -110,6 +139,10 @@ impl QueryParams {
     /// - Numbers: raw numeric string (e.g., "42", "3.14")
     /// - Booleans: "true" or "false"
     ///
+    /// The server reads string values in the escaped text format, so their special characters
+    /// are escaped first, unless the placeholder in `query` takes them as is, see
+    /// [`reads_escaped`].
+    ///
     /// See: <https://github.com/ClickHouse/ClickHouse/blob/master/src/Core/Field.cpp#L312>
     ///
     /// # Errors
@@ -117,6 +150,7 @@ impl QueryParams {
     pub(crate) async fn encode<W: ClickHouseWrite>(
         &self,
         writer: &mut W,
+        query: &str,
         _revision: u64,
     ) -> Result<()> {
         // Encode each parameter using Settings wire format
@@ -125,13 +159,201 @@ impl QueryParams {
             writer.write_var_uint(SETTING_FLAG_CUSTOM).await?;
 
             // Encode value as field dump
-            let field_dump = encode_field_dump(value);
+            let field_dump = encode_field_dump(value, reads_param_escaped(query, key));
             writer.write_string(&field_dump).await?;
         }
         Ok(())
     }
 }
 
+/// The names and types of the `{name:Type}` placeholders in `query`.
+fn placeholders(query: &str) -> impl Iterator<Item = (&str, &str)> {
+    query.split('{').skip(1).filter_map(|rest| {
+        let (placeholder, _) = rest.split_once('}')?;
+        let (name, type_) = placeholder.split_once(':')?;
+        Some((name.trim(), type_.trim()))
+    })
+}
+
+/// Names of the parameters used as `{name:Identifier}` placeholders in `query`.
+fn identifier_placeholders(query: &str) -> impl Iterator<Item = &str> {
+    placeholders(query).filter_map(|(name, type_)| (type_ == "Identifier").then_some(name))
+}
+
+/// Whether the server reads values of placeholders of `type_` in the escaped text format.
+///
+/// Identifiers are substituted as is, and arrays, maps and tuples are parsed as literals whose
+/// quoted elements carry their own escapes.
+fn reads_escaped(type_: &str) -> bool {
+    type_ != "Identifier" && !["Array(", "Map(", "Tuple("].iter().any(|c| type_.starts_with(c))
+}
+
+/// Whether the server reads the value of parameter `name` in the escaped text format, by the
+/// type of its placeholder in `query`, see [`reads_escaped`].
+pub(crate) fn reads_param_escaped(query: &str, name: &str) -> bool {
+    placeholders(query).find(|(n, _)| *n == name).is_none_or(|(_, type_)| reads_escaped(type_))
+}
+
+/// Escapes `value` for the escaped text format, ie `\` -> `\\`, tab -> `\t`, newline -> `\n`.
+pub(crate) fn escape_text(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+/// Checks that `name` can be used as a `ClickHouse` identifier.
+///
+/// Any name can be represented once quoted, so only names that are empty, longer than 1024 bytes,
+/// or contain control characters, which are almost certainly the result of a bug or an injection
+/// attempt, are rejected.
+///
+/// # Errors
+/// Returns [`Error::Client`] if the name is rejected.
+pub fn validate_identifier(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::Client("Identifier is empty".into()));
+    }
+    if name.len() > MAX_IDENTIFIER_LEN {
+        return Err(Error::Client(format!("Identifier is longer than {MAX_IDENTIFIER_LEN} bytes")));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(Error::Client(format!("Identifier {name:?} contains control characters")));
+    }
+    Ok(())
+}
+
+/// Validates `name` and quotes it with backticks for use in a query built by hand, e.g. DDL where
+/// query parameters are not supported.
+///
+/// # Errors
+/// Returns an error if the name fails [`validate_identifier`].
+pub fn quote_identifier(name: &str) -> Result<String> {
+    validate_identifier(name)?;
+    Ok(format!("`{}`", name.replace('\\', "\\\\").replace('`', "\\`")))
+}
+
+/// Longest identifier accepted by [`validate_identifier`].
+const MAX_IDENTIFIER_LEN: usize = 1024;
+
+/// The kind of a statement, as classified by [`classify_statement`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum StatementKind {
+    /// Reads data or metadata, ie `SELECT`, `SHOW`, `DESCRIBE`, `EXPLAIN` or `EXISTS`.
+    Read,
+    /// Changes the state of the session only, ie `SET` or `USE`.
+    Session,
+    /// Writes data or changes the state of the server, ie `INSERT`, `DELETE`, `OPTIMIZE` or
+    /// `KILL`. Statements that cannot be classified are considered writes.
+    Write,
+    /// Changes the schema or access rights, ie `CREATE`, `ALTER`, `DROP`, `TRUNCATE` or `GRANT`.
+    #[strum(serialize = "DDL")]
+    Ddl,
+}
+
+impl StatementKind {
+    /// Whether read-only clients run statements of this kind, see
+    /// [`ClientBuilder::with_readonly`](crate::ClientBuilder::with_readonly).
+    pub fn is_read_only(self) -> bool { matches!(self, Self::Read | Self::Session) }
+}
+
+/// Classifies `query` by its leading keyword, ignoring comments and parentheses.
+///
+/// A query holding several statements separated by `;` is classified by the first statement
+/// that is not read-only, so a write cannot hide behind a `SELECT`. Statements executed as another
+/// user, ie ``EXECUTE AS `tenant` SELECT 1``, are classified by the statement executed.
+///
+/// # Example
+/// ```
+/// use clickhouse_arrow::{StatementKind, classify_statement};
+///
+/// assert_eq!(classify_statement("-- report\n(SELECT 1)"), StatementKind::Read);
+/// assert_eq!(classify_statement("alter table t delete where 1"), StatementKind::Ddl);
+/// ```
+pub fn classify_statement(query: &str) -> StatementKind { classify(query).0 }
+
+/// Refuses `query` unless it is read-only, see [`classify_statement`].
+///
+/// # Errors
+/// Returns [`Error::ReadOnly`] naming the leading keyword of the refused statement.
+pub(crate) fn check_read_only(query: &str) -> Result<()> {
+    match classify(query) {
+        (kind, _) if kind.is_read_only() => Ok(()),
+        (kind, keyword) => Err(Error::ReadOnly { kind, keyword }),
+    }
+}
+
+/// The kind of `query` and the keyword it was classified by.
+fn classify(query: &str) -> (StatementKind, String) {
+    let normalized = crate::telemetry::normalize_query(query);
+    let mut statements = normalized.split(';').filter_map(|statement| {
+        let executed = strip_execute_as(statement);
+        let keyword = executed
+            .unwrap_or(statement)
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .find(|word| !word.is_empty())
+            .map(str::to_ascii_uppercase);
+        match keyword {
+            Some(keyword) => Some((keyword_kind(&keyword), keyword)),
+            // `EXECUTE AS` without a statement switches the session's user
+            None => executed.map(|_| (StatementKind::Session, "EXECUTE".into())),
+        }
+    });
+    let first = statements.next().unwrap_or((StatementKind::Read, String::new()));
+    if first.0.is_read_only() {
+        statements.find(|(kind, _)| !kind.is_read_only()).unwrap_or(first)
+    } else {
+        first
+    }
+}
+
+/// The statement following an ``EXECUTE AS <user>`` prefix of `statement`, if it has one.
+fn strip_execute_as(statement: &str) -> Option<&str> {
+    let rest = strip_keyword(statement.trim_start(), "EXECUTE")?;
+    let rest = strip_keyword(rest.trim_start(), "AS")?.trim_start();
+    let end = if let Some(quoted) = rest.strip_prefix('`') {
+        // Skip escaped and doubled backticks to find the closing one
+        let mut chars = quoted.char_indices().peekable();
+        let mut end = quoted.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    let _ = chars.next();
+                }
+                '`' if chars.peek().is_some_and(|&(_, c)| c == '`') => {
+                    let _ = chars.next();
+                }
+                '`' => {
+                    end = i + 1;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        end + 1
+    } else {
+        rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len())
+    };
+    Some(&rest[end..])
+}
+
+/// `text` following the case-insensitive `keyword`, if it starts with it.
+fn strip_keyword<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = text.get(keyword.len()..)?;
+    let word_ends = !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_');
+    (text[..keyword.len()].eq_ignore_ascii_case(keyword) && word_ends).then_some(rest)
+}
+
+fn keyword_kind(keyword: &str) -> StatementKind {
+    match keyword {
+        "SELECT" | "WITH" | "SHOW" | "DESCRIBE" | "DESC" | "EXPLAIN" | "EXISTS" | "CHECK" => {
+            StatementKind::Read
+        }
+        "SET" | "USE" => StatementKind::Session,
+        "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "RENAME" | "EXCHANGE" | "ATTACH" | "DETACH"
+        | "UNDROP" | "GRANT" | "REVOKE" => StatementKind::Ddl,
+        _ => StatementKind::Write,
+    }
+}
+
 /// Encodes a `SettingValue` as a `ClickHouse` field dump string for query parameters.
 ///
 /// **IMPORTANT**: `ClickHouse's` native protocol only supports **string** parameters!
@@ -143,22 +365,32 @@ impl QueryParams {
 ///
 /// Field dump format for parameters:
 /// - All values are encoded as quoted strings: `'value'`
-/// - Single quotes within strings are escaped: `'` -> `\'`
+/// - If `escaped`, strings are first escaped for the escaped text format the server parses them
+///   in: `\` -> `\\`, tab -> `\t`, newline -> `\n`
+/// - Backslashes and single quotes within strings are escaped: `\` -> `\\`, `'` -> `\'`
+///
+/// The server unquotes the dump before parsing the value as its placeholder type, so unescaped
+/// values reach the server unchanged, ie quoted elements of array values keep their own escapes.
 ///
 /// # Examples
 /// ```rust,ignore
-/// encode_field_dump(&SettingValue::String("hello"))    // "'hello'"
-/// encode_field_dump(&SettingValue::String("it's"))     // "'it\\'s'"
-/// encode_field_dump(&SettingValue::Int(42))             // "'42'"
-/// encode_field_dump(&SettingValue::Float(3.14))         // "'3.14'"
-/// encode_field_dump(&SettingValue::Bool(true))          // "'true'"
+/// encode_field_dump(&SettingValue::String("hello"), true)       // "'hello'"
+/// encode_field_dump(&SettingValue::String("it's"), true)        // "'it\\'s'"
+/// encode_field_dump(&SettingValue::String("a\\b"), true)        // "'a\\\\\\\\b'"
+/// encode_field_dump(&SettingValue::String("['it\\'s']"), false) // "'[\\'it\\\\\\'s\\']'"
+/// encode_field_dump(&SettingValue::Int(42), true)                // "'42'"
+/// encode_field_dump(&SettingValue::Float(3.14), true)            // "'3.14'"
+/// encode_field_dump(&SettingValue::Bool(true), true)             // "'true'"
 /// ```
 ///
 /// See: <https://github.com/ClickHouse/ClickHouse/blob/master/src/Server/TCPHandler.cpp>
-fn encode_field_dump(value: &SettingValue) -> String {
+fn encode_field_dump(value: &SettingValue, escaped: bool) -> String {
     // All parameter values must be strings for toNameToNameMap() to work
     match value {
-        SettingValue::String(s) => format!("'{}'", s.replace('\'', "\\'")),
+        SettingValue::String(s) => {
+            let s = if escaped { escape_text(s) } else { s.clone() };
+            format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+        }
         SettingValue::Int(i) => format!("'{i}'"),
         SettingValue::Float(f) => format!("'{f}'"),
         SettingValue::Bool(b) => format!("'{b}'"),
@@ -194,3 +426,122 @@ impl From<&str> for ParsedQuery {
 impl From<&String> for ParsedQuery {
     fn from(q: &String) -> ParsedQuery { ParsedQuery(q.trim().to_string()) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_field_dump() {
+        assert_eq!(encode_field_dump(&ParamValue::from("hello"), true), "'hello'");
+        assert_eq!(encode_field_dump(&ParamValue::from("it's"), true), r"'it\'s'");
+        // Escaped for the text format, then for the dump
+        assert_eq!(encode_field_dump(&ParamValue::from(r"a\b"), true), r"'a\\\\b'");
+        assert_eq!(encode_field_dump(&ParamValue::from("a\tb\n"), true), r"'a\\tb\\n'");
+        assert_eq!(encode_field_dump(&ParamValue::from(r"a\b"), false), r"'a\\b'");
+        assert_eq!(encode_field_dump(&ParamValue::from(42), true), "'42'");
+        assert_eq!(encode_field_dump(&ParamValue::from(true), true), "'true'");
+        // Quoted array elements keep their escapes once the dump is unquoted
+        let array = ParamValue::from(vec!["it's"]);
+        assert_eq!(encode_field_dump(&array, false), r"'[\'it\\\'s\']'");
+    }
+
+    #[test]
+    fn test_reads_escaped() {
+        let query =
+            "SELECT {s:String}, {n: Nullable(String)}, {a:Array(String)} FROM {t:Identifier}";
+        let types = placeholders(query).collect::<Vec<_>>();
+        assert_eq!(types, vec![
+            ("s", "String"),
+            ("n", "Nullable(String)"),
+            ("a", "Array(String)"),
+            ("t", "Identifier")
+        ]);
+        let escaped = types.iter().map(|(_, type_)| reads_escaped(type_)).collect::<Vec<_>>();
+        assert_eq!(escaped, vec![true, true, false, false]);
+    }
+
+    #[test]
+    fn test_identifier_placeholders() {
+        let query = "SELECT {col: Identifier}, {id:UInt64} FROM {db:Identifier}.{table:Identifier}";
+        assert_eq!(identifier_placeholders(query).collect::<Vec<_>>(), vec!["col", "db", "table"]);
+        assert_eq!(identifier_placeholders("SELECT '{'").count(), 0);
+    }
+
+    #[test]
+    fn test_validate_identifiers() {
+        let query = "SELECT * FROM {db:Identifier}.{table:Identifier} WHERE id = {id:UInt64}";
+        let params = QueryParams::from(vec![
+            ("db", ParamValue::from("default")),
+            ("table", ParamValue::from("my-table")),
+            ("id", ParamValue::from(1)),
+        ]);
+        assert!(params.validate_identifiers(query).is_ok());
+
+        let missing = QueryParams::from(vec![("db", "default")]);
+        assert!(missing.validate_identifiers(query).is_err());
+        let numeric =
+            QueryParams::from(vec![("db", ParamValue::from("a")), ("table", ParamValue::from(1))]);
+        assert!(numeric.validate_identifiers(query).is_err());
+        let control = QueryParams::from(vec![("db", "a"), ("table", "t\n; DROP TABLE t")]);
+        assert!(control.validate_identifiers(query).is_err());
+    }
+
+    #[test]
+    fn test_classify_statement() {
+        assert_eq!(classify_statement("SELECT 1"), StatementKind::Read);
+        assert_eq!(classify_statement(" with x AS (SELECT 1) SELECT x"), StatementKind::Read);
+        assert_eq!(classify_statement("/* INSERT */ (SELECT 'DROP')"), StatementKind::Read);
+        assert_eq!(classify_statement("EXPLAIN DELETE FROM t WHERE 1"), StatementKind::Read);
+        assert_eq!(classify_statement("SET max_threads = 1"), StatementKind::Session);
+        assert_eq!(classify_statement("INSERT INTO t VALUES (1)"), StatementKind::Write);
+        assert_eq!(classify_statement("OPTIMIZE TABLE t FINAL"), StatementKind::Write);
+        assert_eq!(classify_statement("SYSTEM DROP DNS CACHE"), StatementKind::Write);
+        assert_eq!(classify_statement("Drop table t"), StatementKind::Ddl);
+        assert_eq!(classify_statement("TRUNCATE t"), StatementKind::Ddl);
+
+        // Writes following a read are found, semicolons in literals are ignored
+        assert_eq!(classify_statement("SELECT 1; DROP TABLE t"), StatementKind::Ddl);
+        assert_eq!(classify_statement("SELECT ';DROP TABLE t';"), StatementKind::Read);
+        assert_eq!(classify_statement(""), StatementKind::Read);
+
+        // Statements executed as another user are classified by the statement executed
+        assert_eq!(classify_statement("EXECUTE AS `a b` SELECT 1"), StatementKind::Read);
+        assert_eq!(classify_statement("execute as alice DROP TABLE t"), StatementKind::Ddl);
+        assert_eq!(classify_statement("EXECUTE AS `x\\` DROP` SELECT 1"), StatementKind::Read);
+        assert_eq!(classify_statement("EXECUTE AS `select` INSERT INTO t"), StatementKind::Write);
+        assert_eq!(classify_statement("EXECUTE AS alice"), StatementKind::Session);
+        assert_eq!(classify_statement("EXECUTED 1"), StatementKind::Write);
+
+        assert!(StatementKind::Session.is_read_only());
+        assert!(!StatementKind::Write.is_read_only());
+        assert_eq!(StatementKind::Ddl.to_string(), "DDL");
+    }
+
+    #[test]
+    fn test_check_read_only() {
+        assert!(check_read_only("SHOW TABLES").is_ok());
+        let error = check_read_only("SELECT 1; insert into t select 2").unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::ReadOnly { kind: StatementKind::Write, keyword } if keyword == "INSERT"
+        ));
+        let message = "Read-only client refused write statement starting with INSERT";
+        assert_eq!(error.to_string(), message);
+
+        // Read-only clients run reads as another user, but not writes
+        let select = crate::schema::execute_as_statement("tenant", "SELECT 1").unwrap();
+        assert!(check_read_only(&select).is_ok());
+        let drop = crate::schema::execute_as_statement("tenant", "DROP TABLE t").unwrap();
+        assert!(check_read_only(&drop).is_err());
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("events").unwrap(), "`events`");
+        assert_eq!(quote_identifier("my`table").unwrap(), "`my\\`table`");
+        assert_eq!(quote_identifier("a\\b").unwrap(), "`a\\\\b`");
+        assert!(quote_identifier("").is_err());
+        assert!(quote_identifier(&"x".repeat(2000)).is_err());
+    }
+}
@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 use std::fmt::Write as _;
 use std::sync::Arc;
+use std::time::Duration;
 
 use arrow::datatypes::SchemaRef;
-use tracing::error;
+use tracing::{debug, error};
 
 use super::settings::{SettingValue, Settings};
 use crate::arrow::types::{SchemaConversions, schema_conversion};
+use crate::query::quote_identifier;
 use crate::{ArrowOptions, ColumnDefinition, Error, Result, Row, Type};
 
 /// Non-exhaustive list of `ClickHouse` engines. Helps prevent typos when configuring the engine.
@@ -451,6 +453,192 @@ pub(crate) fn drop_db_statement(database: &str, sync: bool) -> Result<String> {
     Ok(ddl)
 }
 
+/// How long a delete waits for its mutation to complete before returning.
+///
+/// Maps onto `mutations_sync`, or `lightweight_deletes_sync` for lightweight deletes on servers
+/// that support it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MutationWait {
+    /// Return once the mutation is scheduled.
+    Async,
+    /// Wait for the mutation to complete on the current replica.
+    #[default]
+    Local,
+    /// Wait for the mutation to complete on all replicas.
+    AllReplicas,
+}
+
+impl MutationWait {
+    fn setting_value(self) -> u8 {
+        match self {
+            MutationWait::Async => 0,
+            MutationWait::Local => 1,
+            MutationWait::AllReplicas => 2,
+        }
+    }
+}
+
+/// First server version supporting `DELETE FROM` behind
+/// `allow_experimental_lightweight_delete`.
+const LIGHTWEIGHT_DELETE_EXPERIMENTAL: (u64, u64) = (22, 8);
+/// First server version supporting `DELETE FROM` without any setting.
+const LIGHTWEIGHT_DELETE_STABLE: (u64, u64) = (23, 3);
+/// First server version waiting on lightweight deletes via `lightweight_deletes_sync`.
+const LIGHTWEIGHT_DELETES_SYNC: (u64, u64) = (24, 4);
+
+/// Generates a `ClickHouse` delete statement for the given server version.
+///
+/// Lightweight deletes (`DELETE FROM`) are used when requested and supported by the server,
+/// otherwise the statement falls back to a mutation (`ALTER TABLE ... DELETE`).
+///
+/// # Arguments
+/// - `table`: The (optionally database qualified) table name.
+/// - `predicate`: The `WHERE` expression selecting rows to delete.
+/// - `lightweight`: Whether to prefer a lightweight delete.
+/// - `wait`: How long the statement waits for the mutation to complete.
+/// - `server_version`: The `(major, minor, patch)` version of the server.
+///
+/// # Errors
+/// - Returns `DDLMalformed` if the table name or predicate is empty.
+///
+/// # Example
+/// ```rust,ignore
+/// let sql = delete_statement("t", "id = 1", true, MutationWait::Local, (24, 8, 0)).unwrap();
+/// assert_eq!(sql, "DELETE FROM t WHERE id = 1 SETTINGS lightweight_deletes_sync = 1");
+/// ```
+pub(crate) fn delete_statement(
+    table: &str,
+    predicate: &str,
+    lightweight: bool,
+    wait: MutationWait,
+    server_version: (u64, u64, u64),
+) -> Result<String> {
+    if table.is_empty() {
+        return Err(Error::DDLMalformed("Table name cannot be empty".into()));
+    }
+
+    // An empty predicate would delete every row, require it to be spelled out
+    let predicate = predicate.trim();
+    if predicate.is_empty() {
+        return Err(Error::DDLMalformed("Delete predicate cannot be empty".into()));
+    }
+
+    let version = (server_version.0, server_version.1);
+    let sync = wait.setting_value();
+    Ok(if lightweight && version >= LIGHTWEIGHT_DELETES_SYNC {
+        format!("DELETE FROM {table} WHERE {predicate} SETTINGS lightweight_deletes_sync = {sync}")
+    } else if lightweight && version >= LIGHTWEIGHT_DELETE_STABLE {
+        format!("DELETE FROM {table} WHERE {predicate} SETTINGS mutations_sync = {sync}")
+    } else if lightweight && version >= LIGHTWEIGHT_DELETE_EXPERIMENTAL {
+        format!(
+            "DELETE FROM {table} WHERE {predicate} SETTINGS allow_experimental_lightweight_delete \
+             = 1, mutations_sync = {sync}"
+        )
+    } else {
+        if lightweight {
+            debug!(?server_version, "Lightweight delete unsupported, falling back to mutation");
+        }
+        format!("ALTER TABLE {table} DELETE WHERE {predicate} SETTINGS mutations_sync = {sync}")
+    })
+}
+
+/// Validates a server-side `INSERT ... SELECT` statement, optionally bounding its execution time.
+///
+/// The statement must start with `INSERT` and source its rows from a `SELECT` (optionally
+/// preceded by a `WITH` clause), rather than from client data (`VALUES`, `FORMAT` or `FROM
+/// INFILE`). Keywords inside quotes or backticks are ignored. A `timeout` is applied as
+/// `max_execution_time`, rounded up to whole seconds, extending the trailing `SETTINGS` clause of
+/// the `SELECT` if there is one. Subqueries in parentheses are not inspected.
+///
+/// # Errors
+/// - Returns `DDLMalformed` if the statement is not an `INSERT ... SELECT`.
+///
+/// # Example
+/// ```rust,ignore
+/// let timeout = Some(Duration::from_secs(30));
+/// let sql = insert_select_statement("INSERT INTO t SELECT * FROM s", timeout);
+/// assert_eq!(sql.unwrap(), "INSERT INTO t SELECT * FROM s SETTINGS max_execution_time = 30");
+/// ```
+pub(crate) fn insert_select_statement(query: &str, timeout: Option<Duration>) -> Result<String> {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    let keywords = unquoted_keywords(query);
+    if keywords.first().is_none_or(|k| k != "INSERT") {
+        return Err(Error::DDLMalformed(format!("Expected an INSERT statement: {query}")));
+    }
+    let source = keywords
+        .iter()
+        .position(|k| matches!(k.as_str(), "SELECT" | "WITH" | "VALUES" | "FORMAT" | "INFILE"));
+    let Some(select) = source.filter(|&i| matches!(keywords[i].as_str(), "SELECT" | "WITH")) else {
+        return Err(Error::DDLMalformed(format!(
+            "Expected an INSERT ... SELECT statement, use insert for client data: {query}"
+        )));
+    };
+
+    let Some(timeout) = timeout else {
+        return Ok(query.to_string());
+    };
+    let seconds = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+    let has_settings = keywords[select..].iter().any(|k| k == "SETTINGS");
+    Ok(if has_settings {
+        format!("{query}, max_execution_time = {seconds}")
+    } else {
+        format!("{query} SETTINGS max_execution_time = {seconds}")
+    })
+}
+
+/// Wraps `query` to execute as `user`, ie ``EXECUTE AS `tenant` SELECT ...``.
+///
+/// Read-only clients classify the wrapped statement by `query`, see
+/// [`classify_statement`](crate::classify_statement).
+///
+/// # Errors
+/// - Returns `DDLMalformed` if the user name is empty.
+/// - Returns an error if the user name cannot be quoted as an identifier.
+pub(crate) fn execute_as_statement(user: &str, query: &str) -> Result<String> {
+    let user = user.trim();
+    if user.is_empty() {
+        return Err(Error::DDLMalformed("User to execute as cannot be empty".into()));
+    }
+    Ok(format!("EXECUTE AS {} {query}", quote_identifier(user)?))
+}
+
+/// Uppercased words of `query` outside of parentheses, skipping quoted strings and identifiers.
+fn unquoted_keywords(query: &str) -> Vec<String> {
+    let mut keywords = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut depth = 0_usize;
+    let mut chars = query.chars();
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            if c == '\\' {
+                let _ = chars.next();
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        if c.is_ascii_alphanumeric() || c == '_' {
+            word.push(c.to_ascii_uppercase());
+            continue;
+        }
+        if !word.is_empty() && depth == 0 {
+            keywords.push(std::mem::take(&mut word));
+        }
+        word.clear();
+        match c {
+            '\'' | '"' | '`' => quote = Some(c),
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    if !word.is_empty() && depth == 0 {
+        keywords.push(word);
+    }
+    keywords
+}
+
 /// Generates a `ClickHouse` `CREATE TABLE` statement from an Arrow schema and table options.
 ///
 /// # Arguments
@@ -568,6 +756,8 @@ pub(crate) fn create_table_statement<T: ColumnDefine>(
 
     let total = definitions.len();
     for (i, (name, type_, default_value)) in definitions.into_iter().enumerate() {
+        // Servers reject `Nullable(LowCardinality(T))`, which user-provided types may contain
+        let type_ = type_.normalize_low_cardinality();
         let _ = write!(sql, "  {name} {type_}");
         if let Some(d) = options
             .defaults
@@ -877,6 +1067,98 @@ mod tests {
         assert!(matches!(result, Err(Error::DDLMalformed(_))));
     }
 
+    #[test]
+    fn test_insert_select_statement() {
+        let sql = insert_select_statement("INSERT INTO t SELECT * FROM s;", None).unwrap();
+        compare_sql(sql, "INSERT INTO t SELECT * FROM s");
+
+        let timeout = Some(Duration::from_millis(1500));
+        let sql = insert_select_statement("insert into t (a) select a from s", timeout).unwrap();
+        compare_sql(sql, "insert into t (a) select a from s SETTINGS max_execution_time = 2");
+
+        let sql = insert_select_statement(
+            "INSERT INTO t SETTINGS async_insert = 0 WITH x AS (SELECT 1) SELECT * FROM x \
+             SETTINGS max_threads = 4",
+            Some(Duration::from_secs(10)),
+        )
+        .unwrap();
+        compare_sql(
+            sql,
+            "INSERT INTO t SETTINGS async_insert = 0 WITH x AS (SELECT 1) SELECT * FROM x \
+             SETTINGS max_threads = 4, max_execution_time = 10",
+        );
+
+        // Keywords in identifiers, strings and subqueries are ignored
+        let sql = insert_select_statement(
+            "INSERT INTO `values` SELECT 'VALUES' FROM (SELECT 1 SETTINGS max_threads = 1)",
+            Some(Duration::from_secs(1)),
+        )
+        .unwrap();
+        compare_sql(
+            sql,
+            "INSERT INTO `values` SELECT 'VALUES' FROM (SELECT 1 SETTINGS max_threads = 1) \
+             SETTINGS max_execution_time = 1",
+        );
+
+        for query in [
+            "SELECT 1",
+            "INSERT INTO t VALUES (1)",
+            "INSERT INTO t FORMAT Native",
+            "INSERT INTO t FROM INFILE 'data.csv' FORMAT CSV",
+            "INSERT INTO t",
+        ] {
+            let result = insert_select_statement(query, None);
+            assert!(matches!(result, Err(Error::DDLMalformed(_))), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_execute_as_statement() {
+        let sql = execute_as_statement("tenant_a", "SELECT * FROM t").unwrap();
+        compare_sql(sql, "EXECUTE AS `tenant_a` SELECT * FROM t");
+
+        let sql = execute_as_statement("a`b", "SELECT 1").unwrap();
+        compare_sql(sql, "EXECUTE AS `a\\`b` SELECT 1");
+
+        let sql = execute_as_statement("a\\", "SELECT 1").unwrap();
+        compare_sql(sql, "EXECUTE AS `a\\\\` SELECT 1");
+
+        let sql = execute_as_statement("a\\`b", "SELECT 1").unwrap();
+        compare_sql(sql, "EXECUTE AS `a\\\\\\`b` SELECT 1");
+
+        let result = execute_as_statement(" ", "SELECT 1");
+        assert!(matches!(result, Err(Error::DDLMalformed(_))));
+    }
+
+    #[test]
+    fn test_delete_statement() {
+        let sql =
+            delete_statement("db.t", "id = 1", true, MutationWait::Local, (24, 8, 1)).unwrap();
+        compare_sql(sql, "DELETE FROM db.t WHERE id = 1 SETTINGS lightweight_deletes_sync = 1");
+
+        let sql = delete_statement("t", "id = 1", true, MutationWait::Async, (23, 8, 0)).unwrap();
+        compare_sql(sql, "DELETE FROM t WHERE id = 1 SETTINGS mutations_sync = 0");
+
+        let sql =
+            delete_statement("t", "id = 1", true, MutationWait::AllReplicas, (22, 8, 0)).unwrap();
+        compare_sql(
+            sql,
+            "DELETE FROM t WHERE id = 1 SETTINGS allow_experimental_lightweight_delete = 1, \
+             mutations_sync = 2",
+        );
+
+        // Falls back to a mutation when unsupported or not requested
+        let sql = delete_statement("t", "id = 1", true, MutationWait::Local, (22, 3, 0)).unwrap();
+        compare_sql(sql, "ALTER TABLE t DELETE WHERE id = 1 SETTINGS mutations_sync = 1");
+        let sql = delete_statement("t", "id = 1", false, MutationWait::Local, (24, 8, 0)).unwrap();
+        compare_sql(sql, "ALTER TABLE t DELETE WHERE id = 1 SETTINGS mutations_sync = 1");
+
+        let result = delete_statement("", "id = 1", true, MutationWait::Local, (24, 8, 0));
+        assert!(matches!(result, Err(Error::DDLMalformed(_))));
+        let result = delete_statement("t", "  ", true, MutationWait::Local, (24, 8, 0));
+        assert!(matches!(result, Err(Error::DDLMalformed(_))));
+    }
+
     #[test]
     fn test_create_table_statement() {
         let schema = Arc::new(Schema::new(vec![
@@ -954,6 +1236,33 @@ mod tests {
         assert!(sql.contains("ORDER BY (id)"));
     }
 
+    #[test]
+    fn test_create_table_normalizes_nullable_low_cardinality() {
+        struct Columns;
+        impl ColumnDefine for Columns {
+            type DefaultValue = String;
+
+            fn definitions() -> Option<Vec<ColumnDefinition<String>>> {
+                let null_lc_string =
+                    Type::Nullable(Box::new(Type::LowCardinality(Box::new(Type::String))));
+                Some(vec![
+                    ("id".into(), Type::Int32, None),
+                    ("name".into(), null_lc_string.clone(), None),
+                    ("tags".into(), Type::Array(Box::new(null_lc_string)), None),
+                ])
+            }
+        }
+
+        let options = CreateOptions::new("MergeTree").with_order_by(&["id".to_string()]);
+        let sql = create_table_statement::<Columns>(None, "my_table", None, &options).unwrap();
+        compare_sql(
+            sql,
+            "CREATE TABLE IF NOT EXISTS `my_table` (\n  id Int32,\n  name \
+             LowCardinality(Nullable(String)),\n  tags \
+             Array(LowCardinality(Nullable(String)))\n)\nENGINE = MergeTree\nORDER BY (id)",
+        );
+    }
+
     #[test]
     fn test_create_table_with_enum8() {
         let schema = Arc::new(Schema::new(vec![
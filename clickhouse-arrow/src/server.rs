@@ -0,0 +1,781 @@
+//! ## `ClickHouse` native protocol server primitives
+//!
+//! Accept connections from `ClickHouse` native-protocol clients (`clickhouse-client`, drivers, or
+//! this crate's [`crate::Client`]) and answer them from a [`ServerHandler`]. The handler decides
+//! how queries are answered and where inserted data goes, so the primitives can front a
+//! `ClickHouse`-compatible ingestion service, a proxy, or a test double.
+//!
+//! The server performs the handshake, decodes queries and insert blocks into Arrow
+//! `RecordBatch`es with this crate's codecs, and encodes responses. It does not parse SQL: every
+//! statement starting with `INSERT` is treated as an insert, anything else is passed to
+//! [`ServerHandler::query`].
+//!
+//! Requires the `server` feature.
+//!
+//! # Examples
+//! ```rust,ignore
+//! use clickhouse_arrow::prelude::*;
+//! use clickhouse_arrow::server::{QueryContext, QueryResponse, Server, ServerHandler};
+//!
+//! struct Ingest;
+//!
+//! impl ServerHandler for Ingest {
+//!     async fn query(&self, ctx: &QueryContext) -> Result<QueryResponse> {
+//!         let message = format!("{} is not supported", ctx.query);
+//!         Ok(QueryResponse::Exception { code: 48, message })
+//!     }
+//!
+//!     async fn insert(&self, ctx: &QueryContext, batch: RecordBatch) -> Result<()> {
+//!         println!("{}: {} rows", ctx.query, batch.num_rows());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let server = Server::builder().bind("127.0.0.1:9000", Ingest).await?;
+//! println!("Listening on {}", server.local_addr());
+//! ```
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::task::JoinHandle;
+
+use crate::arrow::ArrowDeserializerState;
+use crate::client::connection::ClientMetadata;
+use crate::constants::READ_AHEAD_DEFAULT;
+use crate::formats::DeserializerState;
+use crate::formats::sealed::ClientFormatImpl;
+use crate::io::{ClickHouseRead, ClickHouseWrite};
+use crate::native::protocol::{
+    ClientPacketId, DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_CHUNKED_PACKETS,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_INTERSERVER_EXTERNALLY_GRANTED_ROLES,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_PARALLEL_REPLICAS, DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_PASSWORD_COMPLEXITY_RULES,
+    DBMS_MIN_PROTOCOL_VERSION_WITH_QUERY_START_TIME, DBMS_MIN_PROTOCOL_VERSION_WITH_QUOTA_KEY,
+    DBMS_MIN_REVISION_WITH_CLIENT_INFO, DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET,
+    DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET_V2, DBMS_MIN_REVISION_WITH_JWT_IN_INTERSERVER,
+    DBMS_MIN_REVISION_WITH_OPENTELEMETRY, DBMS_MIN_REVISION_WITH_QUERY_AND_LINE_NUMBERS,
+    DBMS_MIN_REVISION_WITH_QUERY_PLAN_SERIALIZATION,
+    DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO, DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME,
+    DBMS_MIN_REVISION_WITH_SERVER_SETTINGS, DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE,
+    DBMS_MIN_REVISION_WITH_VERSION_PATCH,
+    DBMS_MIN_REVISION_WITH_VERSIONED_CLUSTER_FUNCTION_PROTOCOL,
+    DBMS_MIN_REVISION_WITH_VERSIONED_PARALLEL_REPLICAS_PROTOCOL,
+    DBMS_PARALLEL_REPLICAS_PROTOCOL_VERSION, DBMS_TCP_PROTOCOL_VERSION, ServerPacketId,
+};
+use crate::prelude::*;
+use crate::{ArrowOptions, FrameLimits};
+
+/// Error code sent when [`ServerHandler::authenticate`] rejects a client.
+pub const AUTHENTICATION_FAILED: i32 = 516;
+
+/// Error code sent when a handler returns an error.
+pub const UNKNOWN_EXCEPTION: i32 = 1002;
+
+const DEFAULT_SERVER_NAME: &str = "ClickHouseArrow";
+const DEFAULT_VERSION: (u64, u64, u64) = (25, 1, 0);
+const DEFAULT_TIMEZONE: &str = "UTC";
+
+/// The hello sent by a client when it connects.
+#[derive(Debug, Clone)]
+pub struct ClientHello {
+    pub client_name: String,
+    pub version:     (u64, u64),
+    pub revision:    u64,
+    pub database:    String,
+    pub user:        String,
+    pub password:    Secret,
+}
+
+/// A statement received from a client, with the connection it arrived on.
+#[derive(Debug, Clone)]
+pub struct QueryContext {
+    pub query_id: String,
+    pub query:    String,
+    pub settings: Settings,
+    pub database: String,
+    pub user:     String,
+    pub peer:     SocketAddr,
+}
+
+impl QueryContext {
+    /// Whether the statement is an insert, i.e. the client will send data blocks.
+    pub fn is_insert(&self) -> bool {
+        self.query.trim_start().get(..6).is_some_and(|s| s.eq_ignore_ascii_case("insert"))
+    }
+}
+
+/// The reply to a query.
+#[derive(Debug, Clone)]
+pub enum QueryResponse {
+    /// Complete without data, as DDL does.
+    Empty,
+    /// Reply with a header derived from the first batch's schema, then every batch.
+    Batches(Vec<RecordBatch>),
+    /// Reply with a server exception.
+    Exception { code: i32, message: String },
+}
+
+/// Callbacks deciding how a [`Server`] answers its clients.
+///
+/// Handlers are shared by every connection. Errors returned from a callback are sent to the
+/// client as an exception with code [`UNKNOWN_EXCEPTION`], the connection stays open.
+pub trait ServerHandler: Send + Sync + 'static {
+    /// Accept or reject a client after its hello. All clients are accepted by default.
+    ///
+    /// # Errors
+    /// Return an error to reject the client with [`AUTHENTICATION_FAILED`].
+    fn authenticate(&self, _hello: &ClientHello) -> Result<()> { Ok(()) }
+
+    /// Answer a statement that is not an insert.
+    fn query(&self, ctx: &QueryContext) -> impl Future<Output = Result<QueryResponse>> + Send;
+
+    /// Called when an insert starts, before the client sends data.
+    ///
+    /// Returning a schema sends it to the client as the insert header, which clients use to
+    /// choose the `ClickHouse` type of each column. Without one, clients infer types from their
+    /// data. Returns `None` by default.
+    fn begin_insert(
+        &self,
+        _ctx: &QueryContext,
+    ) -> impl Future<Output = Result<Option<SchemaRef>>> + Send {
+        async { Ok(None) }
+    }
+
+    /// Receive a block of inserted data.
+    fn insert(
+        &self,
+        ctx: &QueryContext,
+        batch: RecordBatch,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Called after the last block of an insert, before the client is acknowledged.
+    fn end_insert(&self, _ctx: &QueryContext) -> impl Future<Output = Result<()>> + Send {
+        async { Ok(()) }
+    }
+}
+
+/// Identity and codec options announced to clients.
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    pub name:          String,
+    pub version:       (u64, u64, u64),
+    pub timezone:      String,
+    pub arrow_options: ArrowOptions,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            name:          DEFAULT_SERVER_NAME.to_string(),
+            version:       DEFAULT_VERSION,
+            timezone:      DEFAULT_TIMEZONE.to_string(),
+            arrow_options: ArrowOptions::default(),
+        }
+    }
+}
+
+/// Builder for [`Server`].
+#[derive(Debug, Default, Clone)]
+pub struct ServerBuilder {
+    options: ServerOptions,
+}
+
+impl ServerBuilder {
+    /// Set the server name sent in the handshake.
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.options.name = name.into();
+        self
+    }
+
+    /// Set the server version (major, minor, patch) sent in the handshake.
+    #[must_use]
+    pub fn with_version(mut self, major: u64, minor: u64, patch: u64) -> Self {
+        self.options.version = (major, minor, patch);
+        self
+    }
+
+    /// Set the server timezone sent in the handshake.
+    #[must_use]
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.options.timezone = timezone.into();
+        self
+    }
+
+    /// Set the Arrow options used to decode inserts and encode query responses.
+    #[must_use]
+    pub fn with_arrow_options(mut self, options: ArrowOptions) -> Self {
+        self.options.arrow_options = options;
+        self
+    }
+
+    /// Bind to `addr` and serve every connection with `handler`.
+    ///
+    /// # Errors
+    /// Returns an error if the listener cannot be bound.
+    pub async fn bind<A: ToSocketAddrs, H: ServerHandler>(
+        self,
+        addr: A,
+        handler: H,
+    ) -> Result<Server> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve(listener, handler)
+    }
+
+    /// Serve every connection accepted by `listener` with `handler`.
+    ///
+    /// # Errors
+    /// Returns an error if the listener's address cannot be read.
+    pub fn serve<H: ServerHandler>(self, listener: TcpListener, handler: H) -> Result<Server> {
+        let addr = listener.local_addr()?;
+        let handler = Arc::new(handler);
+        let options = Arc::new(self.options);
+
+        let handle = tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(error) => {
+                        error!(?error, "Server failed to accept connection");
+                        break;
+                    }
+                };
+                trace!(%peer, "Server accepted connection");
+                let handler = Arc::clone(&handler);
+                let options = Arc::clone(&options);
+                drop(tokio::spawn(async move {
+                    if let Err(error) = serve_tcp(stream, peer, handler.as_ref(), &options).await {
+                        error!(?error, %peer, "Server connection failed");
+                    }
+                }));
+            }
+        });
+
+        debug!(%addr, "Server listening");
+        Ok(Server { addr, handle })
+    }
+}
+
+/// A running server. The listener is stopped when dropped.
+#[derive(Debug)]
+pub struct Server {
+    addr:   SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl Server {
+    /// Create a builder for configuring the server
+    pub fn builder() -> ServerBuilder { ServerBuilder::default() }
+
+    /// The address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr { self.addr }
+
+    /// Stop accepting connections. Connections already accepted are served until they close.
+    pub fn shutdown(&self) { self.handle.abort(); }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) { self.handle.abort(); }
+}
+
+/// Serve a TCP connection accepted from `peer` until it disconnects.
+async fn serve_tcp<H: ServerHandler>(
+    stream: TcpStream,
+    peer: SocketAddr,
+    handler: &H,
+    options: &ServerOptions,
+) -> Result<()> {
+    stream.set_nodelay(true)?;
+    serve_connection(stream, peer, handler, options).await
+}
+
+/// Serve a single client connection from `peer` until it disconnects.
+///
+/// Use this to plug the protocol into an existing accept loop, e.g. behind a TLS terminator, in
+/// which case `stream` is the decrypted stream and `peer` the address of the client.
+///
+/// # Errors
+/// Returns an error if the client violates the protocol or the connection fails.
+pub async fn serve_connection<S, H>(
+    stream: S,
+    peer: SocketAddr,
+    handler: &H,
+    options: &ServerOptions,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    H: ServerHandler,
+{
+    let (reader, writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+    let mut writer = BufWriter::new(writer);
+
+    let hello = read_hello(&mut reader).await?;
+    let revision = hello.revision.min(DBMS_TCP_PROTOCOL_VERSION);
+    trace!(client_name = hello.client_name, revision, "Server received hello");
+
+    if let Err(error) = handler.authenticate(&hello) {
+        write_exception(&mut writer, AUTHENTICATION_FAILED, &error.to_string()).await?;
+        return Ok(());
+    }
+
+    write_hello(&mut writer, revision, options).await?;
+    read_addendum(&mut reader, revision).await?;
+
+    let connection = ServerConnection { hello, peer, revision, options };
+    loop {
+        let packet = match reader.read_var_uint().await {
+            Ok(packet) => packet,
+            // Client hung up
+            Err(Error::Io(error)) if error.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+
+        match packet {
+            p if p == ClientPacketId::Ping as u64 => {
+                writer.write_var_uint(ServerPacketId::Pong as u64).await?;
+                writer.flush().await?;
+            }
+            p if p == ClientPacketId::Query as u64 => {
+                connection.handle_query(&mut reader, &mut writer, handler).await?;
+            }
+            p => return Err(Error::Protocol(format!("Server: unsupported packet {p}"))),
+        }
+    }
+}
+
+/// Per-connection state shared by every query on it.
+struct ServerConnection<'a> {
+    hello:    ClientHello,
+    peer:     SocketAddr,
+    revision: u64,
+    options:  &'a ServerOptions,
+}
+
+impl ServerConnection<'_> {
+    async fn handle_query<R, W, H>(&self, reader: &mut R, writer: &mut W, handler: &H) -> Result<()>
+    where
+        R: ClickHouseRead + 'static,
+        W: ClickHouseWrite,
+        H: ServerHandler,
+    {
+        let revision = self.revision;
+        let query_id = reader.read_utf8_string().await?;
+        if revision >= DBMS_MIN_REVISION_WITH_CLIENT_INFO {
+            skip_client_info(reader, revision).await?;
+        }
+        let settings = Settings::decode(reader).await?;
+        if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_INTERSERVER_EXTERNALLY_GRANTED_ROLES {
+            let _roles = reader.read_string().await?;
+        }
+        if revision >= DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET {
+            let _secret = reader.read_string().await?;
+        }
+        let _stage = reader.read_var_uint().await?;
+        let compressed = reader.read_u8().await? != 0;
+        let query = reader.read_utf8_string().await?;
+        if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS {
+            let _params = Settings::decode(reader).await?;
+        }
+        trace!(query_id, query, compressed, "Server received query");
+
+        // Blocks travel compressed in both directions once the client asks for it
+        let compression = if !compressed {
+            CompressionMethod::None
+        } else if settings.encode_to_key_value_strings().iter().any(|(key, value)| {
+            key == "network_compression_method" && value.eq_ignore_ascii_case("zstd")
+        }) {
+            CompressionMethod::ZSTD
+        } else {
+            CompressionMethod::LZ4
+        };
+        let metadata = ClientMetadata {
+            client_id: 0,
+            compression,
+            arrow_options: self.options.arrow_options,
+            redact_queries: false,
+            read_ahead: READ_AHEAD_DEFAULT,
+            readonly: false,
+            frame_limits: FrameLimits::default(),
+        };
+        let ctx = QueryContext {
+            query_id,
+            query,
+            settings,
+            database: self.hello.database.clone(),
+            user: self.hello.user.clone(),
+            peer: self.peer,
+        };
+
+        // Initial delimiter following the query
+        let mut state = DeserializerState::default().with_arrow_options(metadata.arrow_options);
+        expect_packet(reader, ClientPacketId::Data).await?;
+        let _ = read_data(reader, revision, metadata, &mut state).await?;
+
+        let result = if ctx.is_insert() {
+            self.receive_insert(reader, writer, handler, &ctx, metadata, &mut state).await
+        } else {
+            match handler.query(&ctx).await {
+                Ok(QueryResponse::Empty) => Ok(()),
+                Ok(QueryResponse::Batches(batches)) => {
+                    let qid = Qid::default();
+                    if let Some(first) = batches.first() {
+                        let header = RecordBatch::new_empty(first.schema());
+                        write_batch(writer, header, qid, revision, metadata).await?;
+                    }
+                    for batch in batches {
+                        write_batch(writer, batch, qid, revision, metadata).await?;
+                    }
+                    Ok(())
+                }
+                Ok(QueryResponse::Exception { code, message }) => {
+                    return write_exception(writer, code, &message).await;
+                }
+                Err(error) => Err(error),
+            }
+        };
+
+        if let Err(error) = result {
+            // Protocol errors leave the stream in an unknown state
+            if matches!(error, Error::Protocol(_) | Error::Io(_)) {
+                return Err(error);
+            }
+            return write_exception(writer, UNKNOWN_EXCEPTION, &error.to_string()).await;
+        }
+
+        writer.write_var_uint(ServerPacketId::EndOfStream as u64).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Announce the insert header, then pass every block to the handler until the closing
+    /// delimiter.
+    async fn receive_insert<R, W, H>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        handler: &H,
+        ctx: &QueryContext,
+        metadata: ClientMetadata,
+        state: &mut DeserializerState<ArrowDeserializerState>,
+    ) -> Result<()>
+    where
+        R: ClickHouseRead + 'static,
+        W: ClickHouseWrite,
+        H: ServerHandler,
+    {
+        let revision = self.revision;
+        if let Some(schema) = handler.begin_insert(ctx).await? {
+            let header = RecordBatch::new_empty(schema);
+            write_batch(writer, header, Qid::default(), revision, metadata).await?;
+            writer.flush().await?;
+        }
+
+        // Blocks must be drained even after a handler error to keep the stream in sync
+        let mut result = Ok(());
+        loop {
+            expect_packet(reader, ClientPacketId::Data).await?;
+            let Some(batch) = read_data(reader, revision, metadata, state).await? else { break };
+            if result.is_ok() {
+                result = handler.insert(ctx, batch).await;
+            }
+        }
+        result?;
+        handler.end_insert(ctx).await
+    }
+}
+
+/// Read the client hello.
+async fn read_hello<R: ClickHouseRead>(reader: &mut R) -> Result<ClientHello> {
+    let packet = reader.read_var_uint().await?;
+    if packet != ClientPacketId::Hello as u64 {
+        return Err(Error::Protocol(format!("Server: expected hello, got {packet}")));
+    }
+    let client_name = reader.read_utf8_string().await?;
+    let major = reader.read_var_uint().await?;
+    let minor = reader.read_var_uint().await?;
+    let revision = reader.read_var_uint().await?;
+    let database = reader.read_utf8_string().await?;
+    let user = reader.read_utf8_string().await?;
+    let password = Secret::new(reader.read_utf8_string().await?);
+    Ok(ClientHello { client_name, version: (major, minor), revision, database, user, password })
+}
+
+/// Reply to the client hello.
+async fn write_hello<W: ClickHouseWrite>(
+    writer: &mut W,
+    revision: u64,
+    options: &ServerOptions,
+) -> Result<()> {
+    let (major, minor, patch) = options.version;
+    writer.write_var_uint(ServerPacketId::Hello as u64).await?;
+    writer.write_string(&options.name).await?;
+    writer.write_var_uint(major).await?;
+    writer.write_var_uint(minor).await?;
+    writer.write_var_uint(DBMS_TCP_PROTOCOL_VERSION).await?;
+    if revision >= DBMS_MIN_REVISION_WITH_VERSIONED_PARALLEL_REPLICAS_PROTOCOL {
+        writer.write_var_uint(DBMS_PARALLEL_REPLICAS_PROTOCOL_VERSION).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE {
+        writer.write_string(&options.timezone).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME {
+        writer.write_string(&options.name).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_VERSION_PATCH {
+        writer.write_var_uint(patch).await?;
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CHUNKED_PACKETS {
+        writer.write_string(ChunkedProtocolMode::NotChunked.as_ref()).await?;
+        writer.write_string(ChunkedProtocolMode::NotChunked.as_ref()).await?;
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_PASSWORD_COMPLEXITY_RULES {
+        writer.write_var_uint(0).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET_V2 {
+        writer.write_u64_le(0).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_SERVER_SETTINGS {
+        writer.write_string("").await?; // end of settings
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_QUERY_PLAN_SERIALIZATION {
+        writer.write_var_uint(0).await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_VERSIONED_CLUSTER_FUNCTION_PROTOCOL {
+        writer.write_var_uint(0).await?;
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read the addendum sent by the client after the server hello.
+async fn read_addendum<R: ClickHouseRead>(reader: &mut R, revision: u64) -> Result<()> {
+    if revision < DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM {
+        return Ok(());
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_QUOTA_KEY {
+        let _quota_key = reader.read_string().await?;
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_CHUNKED_PACKETS {
+        // The server announces strict `notchunked`, conforming clients never choose chunked
+        for direction in ["send", "recv"] {
+            let mode = reader.read_utf8_string().await?;
+            if mode != ChunkedProtocolMode::NotChunked.as_ref() {
+                return Err(Error::Protocol(format!(
+                    "Server: chunked protocol not supported, client {direction} set to {mode}"
+                )));
+            }
+        }
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_VERSIONED_PARALLEL_REPLICAS_PROTOCOL {
+        let _ = reader.read_var_uint().await?;
+    }
+    Ok(())
+}
+
+/// Skip over the client info sent with every query.
+async fn skip_client_info<R: ClickHouseRead>(reader: &mut R, revision: u64) -> Result<()> {
+    // Query kind, `NoQuery` carries nothing else
+    if reader.read_u8().await? == 0 {
+        return Ok(());
+    }
+    let _initial_user = reader.read_string().await?;
+    let _initial_query_id = reader.read_string().await?;
+    let _initial_address = reader.read_string().await?;
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_QUERY_START_TIME {
+        let _ = reader.read_u64_le().await?;
+    }
+    let _interface = reader.read_u8().await?;
+    let _os_user = reader.read_string().await?;
+    let _client_hostname = reader.read_string().await?;
+    let _client_name = reader.read_string().await?;
+    let _ = reader.read_var_uint().await?;
+    let _ = reader.read_var_uint().await?;
+    let _ = reader.read_var_uint().await?;
+    if revision >= DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO {
+        let _quota_key = reader.read_string().await?;
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH {
+        let _ = reader.read_var_uint().await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_VERSION_PATCH {
+        let _ = reader.read_var_uint().await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_OPENTELEMETRY && reader.read_u8().await? == 1 {
+        let mut trace_id = [0u8; 16];
+        let _ = reader.read_exact(&mut trace_id).await?;
+        let _span_id = reader.read_u64().await?;
+        let _tracestate = reader.read_string().await?;
+        let _trace_flags = reader.read_u8().await?;
+    }
+    if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_PARALLEL_REPLICAS {
+        for _ in 0..3 {
+            let _ = reader.read_var_uint().await?;
+        }
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_QUERY_AND_LINE_NUMBERS {
+        let _ = reader.read_var_uint().await?;
+        let _ = reader.read_var_uint().await?;
+    }
+    if revision >= DBMS_MIN_REVISION_WITH_JWT_IN_INTERSERVER {
+        let _jwt = reader.read_u8().await?;
+    }
+    Ok(())
+}
+
+async fn expect_packet<R: ClickHouseRead>(reader: &mut R, expected: ClientPacketId) -> Result<()> {
+    let packet = reader.read_var_uint().await?;
+    if packet != expected as u64 {
+        return Err(Error::Protocol(format!("Server: expected packet {expected:?}, got {packet}")));
+    }
+    Ok(())
+}
+
+/// Read a client data packet (after its id), returning `None` for the empty delimiter block.
+async fn read_data<R: ClickHouseRead + 'static>(
+    reader: &mut R,
+    revision: u64,
+    metadata: ClientMetadata,
+    state: &mut DeserializerState<ArrowDeserializerState>,
+) -> Result<Option<RecordBatch>> {
+    let _table = reader.read_string().await?;
+    let batch =
+        <ArrowFormat as ClientFormatImpl<RecordBatch>>::read(reader, revision, metadata, state)
+            .await?;
+    <ArrowFormat as ClientFormatImpl<RecordBatch>>::finish_deser(state);
+    Ok(batch)
+}
+
+async fn write_batch<W: ClickHouseWrite>(
+    writer: &mut W,
+    batch: RecordBatch,
+    qid: Qid,
+    revision: u64,
+    metadata: ClientMetadata,
+) -> Result<()> {
+    writer.write_var_uint(ServerPacketId::Data as u64).await?;
+    writer.write_string("").await?; // Table name
+    <ArrowFormat as ClientFormatImpl<RecordBatch>>::write(
+        writer, batch, qid, None, revision, metadata,
+    )
+    .await
+}
+
+async fn write_exception<W: ClickHouseWrite>(
+    writer: &mut W,
+    code: i32,
+    message: &str,
+) -> Result<()> {
+    writer.write_var_uint(ServerPacketId::Exception as u64).await?;
+    writer.write_i32_le(code).await?;
+    writer.write_string("DB::Exception").await?;
+    writer.write_string(message).await?;
+    writer.write_string("").await?; // stack trace
+    writer.write_u8(0).await?; // has nested
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use arrow::array::{AsArray, Int64Array};
+    use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Ingest {
+        rows:     AtomicU64,
+        finished: AtomicU64,
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]))
+    }
+
+    impl ServerHandler for Ingest {
+        fn authenticate(&self, hello: &ClientHello) -> Result<()> {
+            if hello.user == "intruder" {
+                return Err(Error::Client("unknown user".into()));
+            }
+            Ok(())
+        }
+
+        async fn query(&self, ctx: &QueryContext) -> Result<QueryResponse> {
+            if ctx.query.contains("rows") {
+                let rows = self.rows.load(Ordering::Relaxed).cast_signed();
+                let batch =
+                    RecordBatch::try_new(schema(), vec![Arc::new(Int64Array::from(vec![rows]))])?;
+                return Ok(QueryResponse::Batches(vec![batch]));
+            }
+            Ok(QueryResponse::Empty)
+        }
+
+        async fn begin_insert(&self, _ctx: &QueryContext) -> Result<Option<SchemaRef>> {
+            Ok(Some(schema()))
+        }
+
+        async fn insert(&self, _ctx: &QueryContext, batch: RecordBatch) -> Result<()> {
+            let _ = self.rows.fetch_add(batch.num_rows() as u64, Ordering::Relaxed);
+            Ok(())
+        }
+
+        async fn end_insert(&self, _ctx: &QueryContext) -> Result<()> {
+            let _ = self.finished.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_insert_and_query() {
+        let server = Server::builder().bind("127.0.0.1:0", Ingest::default()).await.unwrap();
+        let client = Client::<ArrowFormat>::builder()
+            .with_endpoint(server.local_addr().to_string())
+            .with_username("default")
+            .with_ipv4_only(true)
+            .build::<ArrowFormat>()
+            .await
+            .unwrap();
+
+        let batch = RecordBatch::try_new(schema(), vec![Arc::new(Int64Array::from(vec![1, 2, 3]))])
+            .unwrap();
+        let mut stream = client.insert("INSERT INTO t FORMAT Native", batch, None).await.unwrap();
+        while let Some(result) = stream.next().await {
+            result.unwrap();
+        }
+
+        let batches = client
+            .query("SELECT rows", None)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(batches[0].column(0).as_primitive::<Int64Type>().value(0), 3);
+    }
+
+    #[tokio::test]
+    async fn test_server_rejects_client() {
+        let server = Server::builder().bind("127.0.0.1:0", Ingest::default()).await.unwrap();
+        let result = Client::<ArrowFormat>::builder()
+            .with_endpoint(server.local_addr().to_string())
+            .with_username("intruder")
+            .with_ipv4_only(true)
+            .build::<ArrowFormat>()
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -56,6 +56,9 @@ use crate::{Error, Result};
 const SETTING_FLAG_IMPORTANT: u64 = 0x01;
 pub(crate) const SETTING_FLAG_CUSTOM: u64 = 0x02;
 
+/// Setting recorded as `log_comment` in `system.query_log`.
+pub(crate) const LOG_COMMENT: &str = "log_comment";
+
 /// Supported value types for `ClickHouse` query settings.
 ///
 /// This enum represents the possible data types for a [`Setting`]'s value, including
@@ -579,6 +582,41 @@ impl Settings {
 
     /// Internal helper to find a specific settings
     pub(crate) fn get(&self, key: &str) -> Option<&Setting> { self.0.iter().find(|s| s.key == key) }
+
+    /// Internal helper to read an unsigned integer setting, accepting numeric strings
+    pub(crate) fn get_usize(&self, key: &str) -> Option<usize> {
+        match &self.get(key)?.value {
+            SettingValue::Int(i) => usize::try_from(*i).ok(),
+            SettingValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Internal helper to apply `other` on top of these settings, replacing settings of the same
+    /// name
+    #[must_use]
+    pub(crate) fn merge(mut self, other: Settings) -> Self {
+        for setting in other.0 {
+            match self.0.iter_mut().find(|s| s.key == setting.key) {
+                Some(current) => *current = setting,
+                None => self.0.push(setting),
+            }
+        }
+        self
+    }
+
+    /// Internal helper to append a comment to the `log_comment` setting, separated from any
+    /// existing comment by `"; "`
+    #[must_use]
+    pub(crate) fn with_log_comment(self, comment: &str) -> Self {
+        let log_comment = match self.get(LOG_COMMENT).map(|s| &s.value) {
+            Some(SettingValue::String(current)) if !current.is_empty() => {
+                format!("{current}; {comment}")
+            }
+            _ => comment.to_string(),
+        };
+        self.with_setting(LOG_COMMENT, log_comment)
+    }
 }
 
 impl<T, K, S> From<T> for Settings
@@ -1470,4 +1508,44 @@ mod tests {
             SettingValue::String("['\\'quoted\\'','normal']".to_string())
         );
     }
+
+    #[test]
+    fn test_settings_with_log_comment() {
+        let settings = Settings::default().with_log_comment("nightly-export");
+        assert_eq!(settings.encode_to_strings(), vec!["log_comment = nightly-export"]);
+
+        let settings = Settings::default()
+            .with_setting("max_threads", 8_i64)
+            .with_log_comment("service=ingest")
+            .with_log_comment("job=42");
+        assert_eq!(settings.encode_to_strings(), vec![
+            "max_threads = 8",
+            "log_comment = service=ingest; job=42"
+        ]);
+    }
+
+    #[test]
+    fn test_settings_merge() {
+        let client = Settings::from([("max_threads", 8_i64), ("readonly", 0_i64)]);
+        let query = Settings::from([("max_threads", 4_i64), ("max_block_size", 1024_i64)]);
+        assert_eq!(client.merge(query).encode_to_strings(), vec![
+            "max_threads = 4",
+            "readonly = 0",
+            "max_block_size = 1024"
+        ]);
+    }
+
+    #[test]
+    fn test_settings_get_usize() {
+        let settings = Settings::default()
+            .with_setting("max_insert_block_size", 1000_i64)
+            .with_setting("as_string", "2048")
+            .with_setting("negative", -1_i64)
+            .with_setting("flag", true);
+        assert_eq!(settings.get_usize("max_insert_block_size"), Some(1000));
+        assert_eq!(settings.get_usize("as_string"), Some(2048));
+        assert_eq!(settings.get_usize("negative"), None);
+        assert_eq!(settings.get_usize("flag"), None);
+        assert_eq!(settings.get_usize("missing"), None);
+    }
 }
@@ -0,0 +1,410 @@
+//! Long-running insert sinks with schema drift detection.
+//!
+//! [`ArrowClient::insert_sink`] returns an [`InsertSink`] that inserts batches into a table and
+//! periodically re-reads the table's schema. When the table changes under the sink, ie a column
+//! is added, dropped, or retyped by a migration, the change is reported as a [`SchemaDrift`]
+//! before the next batch is sent, rather than surfacing as an opaque server error part way
+//! through an insert.
+//!
+//! What happens on drift is decided by the sink's [`DriftAction`], or by a [`DriftHook`] that
+//! inspects the drift and picks the action:
+//! - [`DriftAction::Fail`] returns [`Error::SchemaDrift`] from the insert.
+//! - [`DriftAction::Continue`] accepts the new schema and sends batches unchanged.
+//! - [`DriftAction::Reconcile`] adapts batches to the new schema: columns dropped from the table
+//!   are dropped from batches, retyped columns are cast, and added columns are left to their
+//!   defaults.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use std::time::Duration;
+//!
+//! use clickhouse_arrow::prelude::*;
+//! use clickhouse_arrow::sink::{DriftAction, InsertSinkOptions, SchemaDrift};
+//!
+//! let options = InsertSinkOptions::default()
+//!     .with_check_interval(Duration::from_secs(30))
+//!     .with_drift_hook(|drift: &SchemaDrift| {
+//!         warn!(%drift, "Events table changed");
+//!         DriftAction::Reconcile
+//!     });
+//! let mut sink = client.insert_sink("analytics.events", options).await?;
+//! while let Some(batch) = batches.recv().await {
+//!     sink.insert(batch).await?;
+//! }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use arrow::array::RecordBatch;
+use arrow::compute::{CastOptions, cast_with_options};
+use arrow::datatypes::{Schema, SchemaRef};
+use futures_util::StreamExt;
+
+use crate::defaults::insert_statement;
+use crate::explore::split_table;
+use crate::prelude::*;
+use crate::spool::Spool;
+
+/// How often an [`InsertSink`] re-reads the table schema by default.
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A change in a table's schema detected by an [`InsertSink`].
+#[derive(Debug, Clone)]
+pub struct SchemaDrift {
+    pub table:    String,
+    /// The schema the sink was writing with.
+    pub previous: SchemaRef,
+    /// The table's current schema.
+    pub current:  SchemaRef,
+}
+
+impl SchemaDrift {
+    /// Columns added to the table.
+    pub fn added(&self) -> Vec<&str> {
+        let fields = self.current.fields().iter();
+        fields
+            .filter(|f| self.previous.field_with_name(f.name()).is_err())
+            .map(|f| f.name().as_str())
+            .collect()
+    }
+
+    /// Columns dropped from the table.
+    pub fn removed(&self) -> Vec<&str> {
+        let fields = self.previous.fields().iter();
+        fields
+            .filter(|f| self.current.field_with_name(f.name()).is_err())
+            .map(|f| f.name().as_str())
+            .collect()
+    }
+
+    /// Columns whose type or nullability changed.
+    pub fn changed(&self) -> Vec<&str> {
+        let fields = self.current.fields().iter();
+        fields
+            .filter(|f| self.previous.field_with_name(f.name()).is_ok_and(|p| p != f.as_ref()))
+            .map(|f| f.name().as_str())
+            .collect()
+    }
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "schema of {} changed", self.table)?;
+        for (kind, columns) in
+            [("added", self.added()), ("removed", self.removed()), ("changed", self.changed())]
+        {
+            if !columns.is_empty() {
+                write!(f, ", {kind}: {}", columns.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What an [`InsertSink`] does when it detects [`SchemaDrift`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DriftAction {
+    /// Fail the insert with [`Error::SchemaDrift`]. The sink keeps the previous schema, so the
+    /// drift is checked for and reported again on the next insert.
+    #[default]
+    Fail,
+    /// Accept the new schema and keep sending batches unchanged.
+    Continue,
+    /// Accept the new schema and adapt batches to it.
+    Reconcile,
+}
+
+/// A callback deciding what an [`InsertSink`] does on [`SchemaDrift`].
+#[derive(Clone)]
+pub struct DriftHook(Arc<dyn Fn(&SchemaDrift) -> DriftAction + Send + Sync>);
+
+impl DriftHook {
+    /// Create a hook from a callback.
+    pub fn new(hook: impl Fn(&SchemaDrift) -> DriftAction + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Invoke the hook, failing the insert if it panics.
+    fn call(&self, drift: &SchemaDrift) -> DriftAction {
+        let hook = std::panic::AssertUnwindSafe(|| (self.0)(drift));
+        std::panic::catch_unwind(hook).unwrap_or_else(|_| {
+            error!(table = %drift.table, "Schema drift hook panicked");
+            DriftAction::Fail
+        })
+    }
+}
+
+impl fmt::Debug for DriftHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "DriftHook") }
+}
+
+impl<F: Fn(&SchemaDrift) -> DriftAction + Send + Sync + 'static> From<F> for DriftHook {
+    fn from(hook: F) -> Self { Self::new(hook) }
+}
+
+/// Configuration of an [`InsertSink`].
+#[derive(Debug, Clone)]
+pub struct InsertSinkOptions {
+    /// How often the table schema is re-read, checked before each insert.
+    pub check_interval: Duration,
+    /// What to do on drift when no hook is configured.
+    pub action:         DriftAction,
+    /// Decides what to do on drift, overriding `action`.
+    pub hook:           Option<DriftHook>,
+    /// Spools batches to disk before inserting them, see [`InsertSinkOptions::with_spool`].
+    pub spool:          Option<Arc<Spool>>,
+}
+
+impl Default for InsertSinkOptions {
+    fn default() -> Self {
+        Self {
+            check_interval: DEFAULT_CHECK_INTERVAL,
+            action:         DriftAction::default(),
+            hook:           None,
+            spool:          None,
+        }
+    }
+}
+
+impl InsertSinkOptions {
+    /// Set how often the table schema is re-read.
+    #[must_use]
+    pub fn with_check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    /// Set what to do on drift.
+    #[must_use]
+    pub fn with_drift_action(mut self, action: DriftAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Set a callback deciding what to do on drift, ie to alert before reconciling.
+    #[must_use]
+    pub fn with_drift_hook(mut self, hook: impl Into<DriftHook>) -> Self {
+        self.hook = Some(hook.into());
+        self
+    }
+
+    /// Spool batches to disk before inserting them, for at-least-once delivery.
+    ///
+    /// Each batch is durably appended to `spool` and then inserted with its deduplication token,
+    /// see [`crate::spool`]. Batches left in the spool, ie by a crash, are replayed when the
+    /// sink is created and with each insert.
+    #[must_use]
+    pub fn with_spool(mut self, spool: Arc<Spool>) -> Self {
+        self.spool = Some(spool);
+        self
+    }
+}
+
+/// Inserts batches into a table, watching the table for schema drift. See the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct InsertSink {
+    client:     ArrowClient,
+    table:      String,
+    options:    InsertSinkOptions,
+    schema:     SchemaRef,
+    checked_at: Instant,
+    reconcile:  bool,
+    rows:       u64,
+}
+
+impl InsertSink {
+    /// The target table.
+    pub fn table(&self) -> &str { &self.table }
+
+    /// The table schema as of the last check.
+    pub fn schema(&self) -> &SchemaRef { &self.schema }
+
+    /// The number of rows inserted so far, including rows replayed from the spool.
+    pub fn rows(&self) -> u64 { self.rows }
+
+    /// Insert a batch, first re-reading the table schema if the check interval has elapsed.
+    ///
+    /// # Errors
+    /// - Returns [`Error::SchemaDrift`] if the table changed and the drift action is
+    ///   [`DriftAction::Fail`].
+    /// - Fails if the batch cannot be reconciled with the table schema.
+    /// - Fails if the insert encounters a `ClickHouse` error.
+    pub async fn insert(&mut self, batch: RecordBatch) -> Result<()> {
+        if self.checked_at.elapsed() >= self.options.check_interval {
+            let _ = self.check_schema().await?;
+        }
+        let batch = if self.reconcile { reconcile(&self.schema, &batch)? } else { batch };
+        if let Some(spool) = self.options.spool.as_ref() {
+            self.rows += spool.insert(&self.client, &self.table, batch).await?;
+            return Ok(());
+        }
+        let rows = batch.num_rows() as u64;
+        let query = insert_statement(&self.table, &batch)?;
+        let stream = self.client.insert(query, batch, None).await?;
+        tokio::pin!(stream);
+        while let Some(result) = stream.next().await {
+            result?;
+        }
+        self.rows += rows;
+        Ok(())
+    }
+
+    /// Re-read the table schema now, applying the drift action if it changed.
+    ///
+    /// Returns the drift that was accepted, if any.
+    ///
+    /// # Errors
+    /// - Returns [`Error::SchemaDrift`] if the table changed and the drift action is
+    ///   [`DriftAction::Fail`].
+    /// - Fails if the schema cannot be read.
+    pub async fn check_schema(&mut self) -> Result<Option<SchemaDrift>> {
+        let current = fetch_table_schema(&self.client, &self.table).await?;
+        if current.fields() == self.schema.fields() {
+            self.checked_at = Instant::now();
+            return Ok(None);
+        }
+
+        let drift =
+            SchemaDrift { table: self.table.clone(), previous: Arc::clone(&self.schema), current };
+        let action = self.options.hook.as_ref().map_or(self.options.action, |h| h.call(&drift));
+        debug!(%drift, ?action, "Detected schema drift");
+        match action {
+            DriftAction::Fail => return Err(Error::SchemaDrift(Box::new(drift))),
+            DriftAction::Continue => {}
+            DriftAction::Reconcile => self.reconcile = true,
+        }
+        self.schema = Arc::clone(&drift.current);
+        self.checked_at = Instant::now();
+        Ok(Some(drift))
+    }
+}
+
+/// Adapt `batch` to `schema`: columns missing from the schema are dropped, columns of another
+/// type are cast, and schema columns missing from the batch are left out of the insert. Values
+/// that can't be cast fail the insert, rather than being inserted as nulls.
+fn reconcile(schema: &Schema, batch: &RecordBatch) -> Result<RecordBatch> {
+    let options = CastOptions { safe: false, ..Default::default() };
+    let (fields, columns): (Vec<_>, Vec<_>) = batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .filter_map(|(field, column)| {
+            let target = schema.field_with_name(field.name()).ok()?;
+            Some(if target.data_type() == column.data_type() {
+                Ok((Arc::clone(field), Arc::clone(column)))
+            } else {
+                cast_with_options(column, target.data_type(), &options)
+                    .map(|column| (Arc::new(target.clone()), column))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+    Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?)
+}
+
+async fn fetch_table_schema(client: &ArrowClient, table: &str) -> Result<SchemaRef> {
+    let (database, name) = split_table(table);
+    let mut schemas = client.fetch_schema(database.as_deref(), &[name.as_str()], None).await?;
+    schemas.remove(&name).ok_or_else(|| {
+        let db = database.unwrap_or_else(|| "currentDatabase()".into());
+        Error::UndefinedTables { db, tables: vec![name] }
+    })
+}
+
+/// Create an insert sink for `table`, see [`ArrowClient::insert_sink`].
+pub(crate) async fn insert_sink(
+    client: &ArrowClient,
+    table: &str,
+    options: InsertSinkOptions,
+) -> Result<InsertSink> {
+    let schema = fetch_table_schema(client, table).await?;
+    let rows = match options.spool.as_ref() {
+        Some(spool) => spool.replay(client).await?,
+        None => 0,
+    };
+    Ok(InsertSink {
+        client: client.clone(),
+        table: table.to_string(),
+        options,
+        schema,
+        checked_at: Instant::now(),
+        reconcile: false,
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field};
+
+    use super::*;
+
+    fn schema(fields: &[(&str, DataType)]) -> SchemaRef {
+        let fields = fields.iter().map(|(n, t)| Field::new(*n, t.clone(), false));
+        Arc::new(Schema::new(fields.collect::<Vec<_>>()))
+    }
+
+    #[test]
+    fn test_schema_drift() {
+        let drift = SchemaDrift {
+            table:    "events".into(),
+            previous: schema(&[("id", DataType::Int32), ("old", DataType::Utf8)]),
+            current:  schema(&[("id", DataType::Int64), ("new", DataType::Utf8)]),
+        };
+        assert_eq!(drift.added(), vec!["new"]);
+        assert_eq!(drift.removed(), vec!["old"]);
+        assert_eq!(drift.changed(), vec!["id"]);
+        assert_eq!(
+            drift.to_string(),
+            "schema of events changed, added: new, removed: old, changed: id"
+        );
+    }
+
+    #[test]
+    fn test_drift_hook() {
+        let drift = SchemaDrift {
+            table:    "events".into(),
+            previous: schema(&[]),
+            current:  schema(&[("id", DataType::Int32)]),
+        };
+        let hook = DriftHook::from(|drift: &SchemaDrift| {
+            if drift.removed().is_empty() { DriftAction::Reconcile } else { DriftAction::Fail }
+        });
+        assert_eq!(hook.call(&drift), DriftAction::Reconcile);
+
+        let panicking = DriftHook::new(|_| panic!("hook failure"));
+        assert_eq!(panicking.call(&drift), DriftAction::Fail);
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let target = schema(&[("id", DataType::Int64), ("added", DataType::Utf8)]);
+        let batch = RecordBatch::try_new(
+            schema(&[("id", DataType::Int32), ("dropped", DataType::Utf8)]),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+        )
+        .unwrap();
+        let reconciled = reconcile(&target, &batch).unwrap();
+        assert_eq!(reconciled.schema(), schema(&[("id", DataType::Int64)]));
+        assert_eq!(reconciled.num_rows(), 2);
+
+        // Values that can't be cast are an error, not nulls
+        let target = schema(&[("id", DataType::Int64)]);
+        let batch = RecordBatch::try_new(
+            schema(&[("id", DataType::Utf8)]),
+            vec![Arc::new(StringArray::from(vec!["1", "x"]))],
+        )
+        .unwrap();
+        assert!(reconcile(&target, &batch).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! A write-ahead local spool for at-least-once ingestion.
+//!
+//! A [`Spool`] is a directory of batches waiting to be inserted. Each batch is appended to the
+//! spool as an Arrow IPC file, synced to disk, and only removed once `ClickHouse` acknowledged
+//! its insert. Batches left behind by a crash or a failed insert are replayed when the spool is
+//! next replayed, ie on restart, so every appended batch is inserted at least once.
+//!
+//! Each batch is assigned a deduplication token when appended, and is always inserted with it as
+//! `insert_deduplication_token`. A batch replayed after its insert succeeded but before it was
+//! removed from the spool is then dropped by the server instead of being inserted twice. This
+//! requires a `MergeTree` family table with deduplication enabled, which is the default for
+//! replicated tables and needs `non_replicated_deduplication_window` for others.
+//!
+//! A spool can be used directly, or by an [`crate::sink::InsertSink`] through
+//! [`crate::sink::InsertSinkOptions::with_spool`], which replays it when the sink is created.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use clickhouse_arrow::prelude::*;
+//! use clickhouse_arrow::spool::Spool;
+//!
+//! let spool = Spool::open("/var/lib/ingest/spool").await?;
+//! // Insert anything left over from a previous run
+//! let replayed = spool.replay(&client).await?;
+//! // Spool and insert a new batch
+//! spool.insert(&client, "db.events", batch).await?;
+//! ```
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use arrow::array::RecordBatch;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use futures_util::StreamExt;
+use uuid::Uuid;
+
+use crate::defaults::column_list;
+use crate::prelude::*;
+use crate::spawn::SpawnedTask;
+
+/// Extension of spooled batch files.
+const ENTRY_EXTENSION: &str = "arrows";
+/// Extension of batch files being written, removed when the spool is opened.
+const TEMP_EXTENSION: &str = "tmp";
+/// Schema metadata key holding a spooled batch's target table.
+const TABLE_KEY: &str = "clickhouse_arrow.spool.table";
+/// Schema metadata key holding a spooled batch's deduplication token.
+const TOKEN_KEY: &str = "clickhouse_arrow.spool.token";
+
+/// A disk-backed queue of batches waiting to be inserted. See the [module documentation](self).
+#[derive(Debug)]
+pub struct Spool {
+    dir:    PathBuf,
+    next:   AtomicU64,
+    /// Serializes replays, so concurrent replays don't insert the same entries.
+    replay: tokio::sync::Mutex<()>,
+}
+
+/// A batch read back from the spool.
+struct SpoolEntry {
+    path:  PathBuf,
+    table: String,
+    token: String,
+    batch: RecordBatch,
+}
+
+impl Spool {
+    /// Open the spool in `dir`, creating the directory if needed.
+    ///
+    /// Entries left behind by a previous process are kept, and inserted by the next
+    /// [`Spool::replay`]. Partially written entries, from a crash during an append, are removed.
+    ///
+    /// # Errors
+    /// - Fails if the directory cannot be created or read.
+    pub async fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let next = blocking({
+            let dir = dir.clone();
+            move || {
+                fs::create_dir_all(&dir)?;
+                let mut next = 0;
+                for path in entry_paths(&dir, TEMP_EXTENSION)? {
+                    fs::remove_file(path)?;
+                }
+                for path in entry_paths(&dir, ENTRY_EXTENSION)? {
+                    next = next.max(entry_seq(&path).map_or(0, |seq| seq + 1));
+                }
+                Ok(next)
+            }
+        })
+        .await?;
+        debug!(dir = %dir.display(), next, "Opened spool");
+        Ok(Self { dir, next: AtomicU64::new(next), replay: tokio::sync::Mutex::new(()) })
+    }
+
+    /// The spool directory.
+    pub fn dir(&self) -> &Path { &self.dir }
+
+    /// The number of batches waiting to be inserted.
+    ///
+    /// # Errors
+    /// - Fails if the spool directory cannot be read.
+    pub async fn pending(&self) -> Result<usize> {
+        let dir = self.dir.clone();
+        blocking(move || Ok(entry_paths(&dir, ENTRY_EXTENSION)?.len())).await
+    }
+
+    /// Append a batch for `table` to the spool, returning once it is durably stored.
+    ///
+    /// The batch is inserted by the next [`Spool::replay`].
+    ///
+    /// # Errors
+    /// - Fails if the batch cannot be written or synced to disk.
+    pub async fn append(&self, table: &str, batch: RecordBatch) -> Result<()> {
+        let seq = self.next.fetch_add(1, Ordering::SeqCst);
+        let path = self.dir.join(format!("{seq:020}.{ENTRY_EXTENSION}"));
+        let metadata = HashMap::from([
+            (TABLE_KEY.to_string(), table.to_string()),
+            (TOKEN_KEY.to_string(), Uuid::new_v4().to_string()),
+        ]);
+        let schema = batch.schema().as_ref().clone().with_metadata(metadata);
+        let batch = batch.with_schema(Arc::new(schema))?;
+        blocking(move || write_entry(&path, &batch)).await?;
+        trace!(table, seq, "Spooled batch");
+        Ok(())
+    }
+
+    /// Insert all spooled batches, in the order they were appended, removing each from the spool
+    /// once its insert is acknowledged. Returns the number of rows inserted.
+    ///
+    /// Stops at the first failed insert, leaving it and later batches in the spool.
+    ///
+    /// # Errors
+    /// - Fails if a spooled batch cannot be read or removed.
+    /// - Fails if an insert encounters a `ClickHouse` error.
+    pub async fn replay(&self, client: &ArrowClient) -> Result<u64> {
+        let _guard = self.replay.lock().await;
+        let dir = self.dir.clone();
+        let paths = blocking(move || entry_paths(&dir, ENTRY_EXTENSION)).await?;
+        let mut rows = 0;
+        for path in paths {
+            let entry = blocking(move || read_entry(path)).await?;
+            rows += entry.batch.num_rows() as u64;
+            let query = format!(
+                "INSERT INTO {} ({}) SETTINGS insert_deduplication_token = '{}' FORMAT Native",
+                entry.table,
+                column_list(&entry.batch)?,
+                entry.token
+            );
+            let stream = client.insert(query, entry.batch, None).await?;
+            tokio::pin!(stream);
+            while let Some(result) = stream.next().await {
+                result?;
+            }
+            let path = entry.path;
+            blocking(move || Ok(fs::remove_file(path)?)).await?;
+        }
+        Ok(rows)
+    }
+
+    /// Append a batch for `table` and replay the spool, inserting it along with any batches
+    /// left from earlier failures. Returns the number of rows inserted.
+    ///
+    /// # Errors
+    /// - Fails if appending or replaying fails, see [`Spool::append`] and [`Spool::replay`]. A
+    ///   batch appended before a failed replay stays in the spool.
+    pub async fn insert(
+        &self,
+        client: &ArrowClient,
+        table: &str,
+        batch: RecordBatch,
+    ) -> Result<u64> {
+        self.append(table, batch).await?;
+        self.replay(client).await
+    }
+}
+
+/// Run blocking file system work off the runtime.
+async fn blocking<R: Send + 'static>(
+    work: impl FnOnce() -> Result<R> + Send + 'static,
+) -> Result<R> {
+    SpawnedTask::spawn_blocking(work).join_unwind().await.map_err(std::io::Error::other)?
+}
+
+/// The paths in `dir` with `extension`, sorted by sequence number.
+fn entry_paths(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .filter(|path| path.as_ref().is_ok_and(|p| p.extension().is_some_and(|e| e == extension)))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+fn entry_seq(path: &Path) -> Option<u64> { path.file_stem()?.to_str()?.parse().ok() }
+
+/// Write an entry to a temporary file, then sync and rename it into place, so a crash never
+/// leaves a partially written entry to replay.
+fn write_entry(path: &Path, batch: &RecordBatch) -> Result<()> {
+    let temp = path.with_extension(TEMP_EXTENSION);
+    let file = File::create(&temp)?;
+    let mut writer = StreamWriter::try_new(BufWriter::new(file), &batch.schema())?;
+    writer.write(batch)?;
+    writer.finish()?;
+    let file = writer.into_inner()?.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+    fs::rename(&temp, path)?;
+    // Persist the rename itself
+    #[cfg(unix)]
+    File::open(path.parent().unwrap_or(Path::new(".")))?.sync_all()?;
+    Ok(())
+}
+
+fn read_entry(path: PathBuf) -> Result<SpoolEntry> {
+    let reader = StreamReader::try_new(BufReader::new(File::open(&path)?), None)?;
+    let schema = reader.schema();
+    let metadata = |key: &str| {
+        schema.metadata().get(key).cloned().ok_or_else(|| {
+            Error::Client(format!("Spooled batch {} is missing {key}", path.display()))
+        })
+    };
+    let (table, token) = (metadata(TABLE_KEY)?, metadata(TOKEN_KEY)?);
+    let batches = reader.collect::<Result<Vec<_>, _>>()?;
+    let batch = arrow::compute::concat_batches(&schema, &batches)?;
+    Ok(SpoolEntry { path, table, token, batch })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::int32_batch;
+
+    #[tokio::test]
+    async fn test_spool_append_and_reopen() {
+        let dir = std::env::temp_dir().join(format!("spool_{}", Uuid::new_v4().simple()));
+        let spool = Spool::open(&dir).await.unwrap();
+        spool.append("db.events", int32_batch(vec![1, 2])).await.unwrap();
+        spool.append("db.events", int32_batch(vec![3])).await.unwrap();
+        assert_eq!(spool.pending().await.unwrap(), 2);
+
+        // A partially written entry from a crash is discarded on open
+        fs::write(dir.join(format!("{:020}.{TEMP_EXTENSION}", 2)), b"partial").unwrap();
+        drop(spool);
+        let spool = Spool::open(&dir).await.unwrap();
+        assert_eq!(spool.pending().await.unwrap(), 2);
+        assert_eq!(spool.next.load(Ordering::SeqCst), 2);
+
+        let paths = entry_paths(&dir, ENTRY_EXTENSION).unwrap();
+        let entries = paths.into_iter().map(|p| read_entry(p).unwrap()).collect::<Vec<_>>();
+        assert_eq!(entries[0].table, "db.events");
+        assert_eq!(entries[0].batch.num_rows(), 2);
+        assert_eq!(entries[1].batch.num_rows(), 1);
+        assert_ne!(entries[0].token, entries[1].token);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
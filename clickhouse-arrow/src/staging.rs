@@ -0,0 +1,242 @@
+//! Pseudo-transactional batch loads through staging tables.
+//!
+//! [`ArrowClient::staged_insert`] creates a staging table with the structure of the target table
+//! and returns a [`StagedInsert`]. Batches inserted through the stager land in the staging table
+//! only, and are moved into the target on [`StagedInsert::commit`] or discarded on
+//! [`StagedInsert::rollback`]. Readers of the target never observe rows that are still staged or
+//! were rolled back.
+//!
+//! How rows are moved, and so how they become visible, depends on the target's engine, see
+//! [`StageStrategy`]:
+//! - Plain `MergeTree` family tables are staged in a copy of the table, and each staged partition
+//!   is attached with `ALTER TABLE ... ATTACH PARTITION ... FROM`, which copies no data. Attaching
+//!   is atomic per partition only: while a commit spanning several partitions is in progress,
+//!   readers may observe the partitions attached so far.
+//! - Other tables (replicated, shared, or non-`MergeTree`) are staged in a `MergeTree` table with
+//!   the same columns, and copied with a single `INSERT ... SELECT`.
+//!
+//! The staging table is dropped once committed or rolled back. A stager dropped without either
+//! leaves its staging table behind, see [`StagedInsert::staging_table`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use clickhouse_arrow::prelude::*;
+//!
+//! let mut stager = client.staged_insert("analytics.events", None).await?;
+//! for batch in batches {
+//!     if let Err(error) = stager.insert(batch).await {
+//!         stager.rollback().await?;
+//!         return Err(error);
+//!     }
+//! }
+//! let rows = stager.commit().await?;
+//! ```
+
+use arrow::array::{AsArray, RecordBatch};
+use arrow::compute::cast;
+use arrow::datatypes::DataType;
+use futures_util::StreamExt;
+use uuid::Uuid;
+
+use crate::explore::{split_table, system_table_filter};
+use crate::prelude::*;
+use crate::query::quote_identifier;
+
+/// How a [`StagedInsert`] moves its rows into the target table on commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StageStrategy {
+    /// Attach each staged partition with `ALTER TABLE ... ATTACH PARTITION ... FROM`.
+    AttachPartitions,
+    /// Copy the staged rows with `INSERT ... SELECT`.
+    InsertSelect,
+}
+
+impl StageStrategy {
+    /// Partitions can only be attached between tables of the same structure and storage, which
+    /// rules out replicated and shared tables, whose copies would share the same replication path.
+    fn for_engine(engine: &str) -> Self {
+        let replicated = engine.starts_with("Replicated") || engine.starts_with("Shared");
+        if engine.ends_with("MergeTree") && !replicated {
+            StageStrategy::AttachPartitions
+        } else {
+            StageStrategy::InsertSelect
+        }
+    }
+}
+
+/// Inserts batches into a staging table, moving them into the target table on commit. See the
+/// [module documentation](self).
+#[derive(Debug)]
+#[must_use = "staged rows are only moved into the target table on commit"]
+pub struct StagedInsert {
+    client:   ArrowClient,
+    table:    String,
+    database: Option<String>,
+    staging:  String,
+    strategy: StageStrategy,
+    rows:     u64,
+}
+
+impl StagedInsert {
+    /// The target table.
+    pub fn table(&self) -> &str { &self.table }
+
+    /// The staging table, ie to clean it up if the stager was dropped without committing.
+    pub fn staging_table(&self) -> String { self.qualified_staging() }
+
+    /// How rows are moved into the target table on commit.
+    pub fn strategy(&self) -> StageStrategy { self.strategy }
+
+    /// The number of rows staged so far.
+    pub fn rows(&self) -> u64 { self.rows }
+
+    /// Insert a batch into the staging table.
+    ///
+    /// # Errors
+    /// - Fails if the batch does not match the target table's structure.
+    /// - Fails if the insert encounters a `ClickHouse` error.
+    pub async fn insert(&mut self, batch: RecordBatch) -> Result<()> {
+        let rows = batch.num_rows() as u64;
+        let query = format!("INSERT INTO {} FORMAT Native", self.qualified_staging()?);
+        let stream = self.client.insert(query, batch, None).await?;
+        tokio::pin!(stream);
+        while let Some(result) = stream.next().await {
+            result?;
+        }
+        self.rows += rows;
+        Ok(())
+    }
+
+    /// Move the staged rows into the target table and drop the staging table, returning the
+    /// number of rows committed.
+    ///
+    /// With [`StageStrategy::AttachPartitions`] each partition is attached atomically, so a
+    /// failure part way through leaves the remaining partitions in the staging table. The stager
+    /// is kept on failure, so the staging table can be found with
+    /// [`StagedInsert::staging_table`] to recover them, or dropped with
+    /// [`StagedInsert::rollback`].
+    ///
+    /// # Errors
+    /// - Fails if moving the rows or dropping the staging table encounters a `ClickHouse` error.
+    pub async fn commit(&mut self) -> Result<u64> {
+        let staging = self.qualified_staging()?;
+        match self.strategy {
+            StageStrategy::AttachPartitions => {
+                for partition in self.staged_partitions().await? {
+                    let query = format!(
+                        "ALTER TABLE {} ATTACH PARTITION ID '{partition}' FROM {staging}",
+                        self.table
+                    );
+                    self.client.execute(query, None).await?;
+                }
+            }
+            StageStrategy::InsertSelect => {
+                let query = format!("INSERT INTO {} SELECT * FROM {staging}", self.table);
+                self.client.execute(query, None).await?;
+            }
+        }
+        debug!(table = %self.table, staging, rows = self.rows, "Committed staged insert");
+        self.drop_staging().await?;
+        Ok(self.rows)
+    }
+
+    /// Discard the staged rows and drop the staging table.
+    ///
+    /// # Errors
+    /// - Fails if dropping the staging table encounters a `ClickHouse` error.
+    pub async fn rollback(self) -> Result<()> {
+        debug!(table = %self.table, rows = self.rows, "Rolling back staged insert");
+        self.drop_staging().await
+    }
+
+    async fn staged_partitions(&self) -> Result<Vec<String>> {
+        let (filter, params) =
+            system_table_filter(self.database.as_deref(), "table", &self.staging);
+        let batch = self
+            .client
+            .query_params(
+                format!("SELECT DISTINCT partition_id FROM system.parts WHERE active AND {filter}"),
+                Some(params),
+                None,
+            )
+            .await?
+            .collect_result()
+            .await?
+            .collect_table()?;
+        let partitions = cast(batch.column(0), &DataType::Utf8)?;
+        Ok(partitions.as_string::<i32>().iter().flatten().map(ToString::to_string).collect())
+    }
+
+    async fn drop_staging(&self) -> Result<()> {
+        let query = format!("DROP TABLE IF EXISTS {} SYNC", self.qualified_staging()?);
+        self.client.execute(query, None).await
+    }
+
+    fn qualified_staging(&self) -> Result<String> {
+        let staging = quote_identifier(&self.staging)?;
+        Ok(match &self.database {
+            Some(db) => format!("{}.{staging}", quote_identifier(db)?),
+            None => staging,
+        })
+    }
+}
+
+/// Create a staging table for `table`, see [`ArrowClient::staged_insert`].
+pub(crate) async fn staged_insert(
+    client: &ArrowClient,
+    table: &str,
+    qid: Option<Qid>,
+) -> Result<StagedInsert> {
+    let (database, name) = split_table(table);
+    let (filter, params) = system_table_filter(database.as_deref(), "name", &name);
+    let info = client
+        .query_params(format!("SELECT engine FROM system.tables WHERE {filter}"), Some(params), qid)
+        .await?
+        .collect_result()
+        .await?
+        .collect_table()?;
+    if info.num_rows() == 0 {
+        let db = database.unwrap_or_else(|| "currentDatabase()".into());
+        return Err(Error::UndefinedTables { db, tables: vec![name] });
+    }
+    let engine = cast(info.column(0), &DataType::Utf8)?;
+    let strategy = StageStrategy::for_engine(engine.as_string::<i32>().value(0));
+
+    let staging = format!("{name}_staging_{}", Uuid::new_v4().simple());
+    let stager = StagedInsert {
+        client: client.clone(),
+        table: table.to_string(),
+        database,
+        staging,
+        strategy,
+        rows: 0,
+    };
+    let query = match strategy {
+        StageStrategy::AttachPartitions => {
+            format!("CREATE TABLE {} AS {table}", stager.qualified_staging()?)
+        }
+        StageStrategy::InsertSelect => format!(
+            "CREATE TABLE {} AS {table} ENGINE = MergeTree ORDER BY tuple()",
+            stager.qualified_staging()?
+        ),
+    };
+    client.execute(query, None).await?;
+    debug!(table, staging = %stager.staging, ?strategy, "Created staging table");
+    Ok(stager)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_strategy_for_engine() {
+        for engine in ["MergeTree", "ReplacingMergeTree", "AggregatingMergeTree"] {
+            assert_eq!(StageStrategy::for_engine(engine), StageStrategy::AttachPartitions);
+        }
+        for engine in ["ReplicatedMergeTree", "SharedMergeTree", "Memory", "Distributed", "Log"] {
+            assert_eq!(StageStrategy::for_engine(engine), StageStrategy::InsertSelect);
+        }
+    }
+}
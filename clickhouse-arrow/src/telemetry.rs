@@ -11,11 +11,23 @@
 //!     .init();
 //! // Use clickhouse_arrow
 //! ```
+//!
+//! Query spans carry a `clickhouse.query.fingerprint` field, a hash of the query with its
+//! literals replaced by placeholders (see [`normalize_query`]). Queries that differ only in their
+//! literals share a fingerprint, allowing traces to be grouped without the raw SQL. Raw SQL is
+//! also logged unless disabled with
+//! [`ClientBuilder::with_redact_queries`](crate::ClientBuilder::with_redact_queries), since
+//! literals may contain sensitive data.
+use std::net::SocketAddr;
 use std::num::NonZeroU64;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub use opentelemetry_semantic_conventions::*;
 use tracing::Span;
 
+use crate::query::Qid;
+
 /// Commonly used attribute names
 pub const ATT_CID: &str = "clickhouse.client.id";
 pub const ATT_CON: &str = "clickhouse.connection.id";
@@ -26,6 +38,8 @@ pub const ATT_PID: &str = "clickhouse.packet.id";
 pub const ATT_MSGTYPE: &str = "clickhouse.message.type";
 pub const ATT_FIELD_NAME: &str = "clickhouse.field.name";
 pub const ATT_FIELD_TYPE: &str = "clickhouse.field.type";
+pub const ATT_FINGERPRINT: &str = "clickhouse.query.fingerprint";
+pub const ATT_FIRST_BYTE: &str = "clickhouse.query.first_byte_ms";
 
 /// A helper to link spans to various actions, namely connection. Sometimes, clients are spawned on
 /// separate tasks. This provides a simple way to link traces if a link is preferred in some
@@ -51,3 +65,457 @@ impl From<NonZeroU64> for TraceContext {
 impl From<Option<NonZeroU64>> for TraceContext {
     fn from(id: Option<NonZeroU64>) -> Self { Self(id) }
 }
+
+/// A statement completed by a client, emitted to a [`StatementHook`].
+///
+/// Events are emitted after each query, insert, or execute finishes, successfully or not, making
+/// them suitable for audit logging and slow query detection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementEvent {
+    /// The query id of the statement
+    pub qid:            Qid,
+    /// The id of the client that ran the statement
+    pub client_id:      u16,
+    /// Fingerprint of the statement's SQL, see [`query_fingerprint`]
+    pub fingerprint:    u64,
+    /// The raw SQL, unless queries are redacted, see
+    /// [`ClientBuilder::with_redact_queries`](crate::ClientBuilder::with_redact_queries)
+    pub query:          Option<String>,
+    /// Time from sending the statement until the server finished or failed it
+    pub duration:       Duration,
+    /// Rows read by the server, as reported by progress packets
+    pub read_rows:      u64,
+    /// Rows written by the server, as reported by progress packets
+    pub written_rows:   u64,
+    /// Totals of the profile events the server reported, ie `SelectedRows`
+    pub profile_events: crate::ProfileEvents,
+    /// The error that failed the statement, if any
+    pub error:          Option<String>,
+}
+
+impl StatementEvent {
+    /// Whether the statement failed.
+    pub fn is_error(&self) -> bool { self.error.is_some() }
+}
+
+/// A callback invoked with a [`StatementEvent`] after each statement a client runs.
+///
+/// Hooks are called on the connection's IO task, so they should return quickly, for example by
+/// logging or forwarding the event to a channel. Panics in a hook are caught and logged.
+#[derive(Clone)]
+pub struct StatementHook(Arc<dyn Fn(&StatementEvent) + Send + Sync>);
+
+impl StatementHook {
+    /// Create a hook from a callback.
+    pub fn new(hook: impl Fn(&StatementEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Invoke the hook, catching and logging panics so they don't take down the connection.
+    pub(crate) fn call(&self, event: &StatementEvent) {
+        let hook = std::panic::AssertUnwindSafe(|| (self.0)(event));
+        if std::panic::catch_unwind(hook).is_err() {
+            tracing::error!({ ATT_QID } = %event.qid, "Statement hook panicked");
+        }
+    }
+}
+
+impl std::fmt::Debug for StatementHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StatementHook")
+    }
+}
+
+impl<F: Fn(&StatementEvent) + Send + Sync + 'static> From<F> for StatementHook {
+    fn from(hook: F) -> Self { Self::new(hook) }
+}
+
+/// A callback invoked with each [`Progress`](crate::Progress) update the server sends for a
+/// query, see [`QueryOptions::with_progress`](crate::explain::QueryOptions::with_progress).
+///
+/// Progress values are deltas since the previous update, sum them for running totals. Hooks are
+/// called on the connection's IO task, so they should return quickly. Panics in a hook are caught
+/// and logged.
+#[derive(Clone)]
+pub struct ProgressHook(Arc<dyn Fn(&crate::Progress) + Send + Sync>);
+
+impl ProgressHook {
+    /// Create a hook from a callback.
+    pub fn new(hook: impl Fn(&crate::Progress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Invoke the hook, catching and logging panics so they don't take down the connection.
+    pub(crate) fn call(&self, qid: Qid, progress: &crate::Progress) {
+        let hook = std::panic::AssertUnwindSafe(|| (self.0)(progress));
+        if std::panic::catch_unwind(hook).is_err() {
+            tracing::error!({ ATT_QID } = %qid, "Progress hook panicked");
+        }
+    }
+}
+
+impl std::fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ProgressHook")
+    }
+}
+
+impl<F: Fn(&crate::Progress) + Send + Sync + 'static> From<F> for ProgressHook {
+    fn from(hook: F) -> Self { Self::new(hook) }
+}
+
+/// A connection to the server was established, emitted to [`ConnectionHooks::on_connect`].
+///
+/// Emitted for the initial connection, every inner connection of the `inner_pool` feature, and
+/// every re-established connection once its handshake completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectEvent {
+    /// The id of the client owning the connection
+    pub client_id:      u16,
+    /// The server address connected to
+    pub endpoint:       SocketAddr,
+    /// `(major, minor, patch)` version reported by the server
+    pub server_version: (u64, u64, u64),
+}
+
+/// Why a connection ended, see [`DisconnectEvent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// The client shut the connection down.
+    Shutdown,
+    /// The connection failed, ie the server closed it or the network dropped. Holds the error.
+    Error(String),
+}
+
+/// A connection to the server ended, emitted to [`ConnectionHooks::on_disconnect`].
+///
+/// Not emitted for connections dropped along with their client without a shutdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisconnectEvent {
+    /// The id of the client owning the connection
+    pub client_id: u16,
+    /// The server address of the connection
+    pub endpoint:  SocketAddr,
+    /// Why the connection ended
+    pub reason:    DisconnectReason,
+}
+
+/// An attempt to re-establish a dropped connection finished, emitted to
+/// [`ConnectionHooks::on_reconnect_attempt`]. See [`crate::ReconnectPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectAttemptEvent {
+    /// The id of the client owning the connection
+    pub client_id:    u16,
+    /// The server address reconnected to, `None` if the attempt failed
+    pub endpoint:     Option<SocketAddr>,
+    /// The attempt, starting at 1
+    pub attempt:      u32,
+    /// Attempts made before giving up
+    pub max_attempts: u32,
+    /// The error that failed the attempt, if any
+    pub error:        Option<String>,
+    /// Delay before the next attempt, `None` if the attempt succeeded or was the last
+    pub retry_in:     Option<Duration>,
+}
+
+impl ReconnectAttemptEvent {
+    /// Whether the attempt re-established the connection.
+    pub fn is_success(&self) -> bool { self.error.is_none() }
+}
+
+type Hook<E> = Arc<dyn Fn(&E) + Send + Sync>;
+
+/// Callbacks invoked as a client's connections are established, lost, and re-established.
+///
+/// Allows logging connection churn and alerting on it without wrapping the client. Hooks are
+/// called on the connection's IO task or the operation reconnecting it, so they should return
+/// quickly, for example by logging or forwarding the event to a channel. Panics in a hook are
+/// caught and logged.
+///
+/// # Example
+/// ```rust,ignore
+/// use clickhouse_arrow::telemetry::{ConnectionHooks, DisconnectReason};
+///
+/// let hooks = ConnectionHooks::new()
+///     .on_connect(|event| println!("connected to {}", event.endpoint))
+///     .on_disconnect(|event| {
+///         if let DisconnectReason::Error(error) = &event.reason {
+///             eprintln!("lost {}: {error}", event.endpoint);
+///         }
+///     });
+/// ```
+#[derive(Clone, Default)]
+pub struct ConnectionHooks {
+    on_connect:           Option<Hook<ConnectEvent>>,
+    on_disconnect:        Option<Hook<DisconnectEvent>>,
+    on_reconnect_attempt: Option<Hook<ReconnectAttemptEvent>>,
+}
+
+impl ConnectionHooks {
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the callback invoked whenever a connection is established.
+    #[must_use]
+    pub fn on_connect(mut self, hook: impl Fn(&ConnectEvent) + Send + Sync + 'static) -> Self {
+        self.on_connect = Some(Arc::new(hook));
+        self
+    }
+
+    /// Set the callback invoked whenever a connection ends.
+    #[must_use]
+    pub fn on_disconnect(
+        mut self,
+        hook: impl Fn(&DisconnectEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_disconnect = Some(Arc::new(hook));
+        self
+    }
+
+    /// Set the callback invoked after each attempt to re-establish a dropped connection.
+    #[must_use]
+    pub fn on_reconnect_attempt(
+        mut self,
+        hook: impl Fn(&ReconnectAttemptEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_reconnect_attempt = Some(Arc::new(hook));
+        self
+    }
+
+    pub(crate) fn connected(&self, event: &ConnectEvent) {
+        call_hook(self.on_connect.as_ref(), event, "connect");
+    }
+
+    pub(crate) fn disconnected(&self, event: &DisconnectEvent) {
+        call_hook(self.on_disconnect.as_ref(), event, "disconnect");
+    }
+
+    pub(crate) fn reconnect_attempted(&self, event: &ReconnectAttemptEvent) {
+        call_hook(self.on_reconnect_attempt.as_ref(), event, "reconnect attempt");
+    }
+}
+
+impl std::fmt::Debug for ConnectionHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectionHooks")
+            .field("on_connect", &self.on_connect.is_some())
+            .field("on_disconnect", &self.on_disconnect.is_some())
+            .field("on_reconnect_attempt", &self.on_reconnect_attempt.is_some())
+            .finish()
+    }
+}
+
+/// Invoke a connection hook, if set, catching and logging panics.
+fn call_hook<E>(hook: Option<&Hook<E>>, event: &E, name: &str) {
+    let Some(hook) = hook else { return };
+    if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| hook(event))).is_err() {
+        tracing::error!("Connection {name} hook panicked");
+    }
+}
+
+/// Normalizes a query by replacing its literals with `?` placeholders.
+///
+/// String and numeric literals are replaced, lists of placeholders such as those of an `IN`
+/// clause are collapsed into a single placeholder, comments are removed, and whitespace is
+/// collapsed. Identifiers, including quoted identifiers, are retained.
+///
+/// # Example
+/// ```
+/// use clickhouse_arrow::telemetry::normalize_query;
+///
+/// let query = "SELECT * FROM t WHERE name = 'alice' AND id IN (1, 2,  3)";
+/// assert_eq!(normalize_query(query), "SELECT * FROM t WHERE name = ? AND id IN (?)");
+/// ```
+pub fn normalize_query(query: &str) -> String {
+    let chars = query.chars().collect::<Vec<_>>();
+    // Tokens paired with whether whitespace preceded them
+    let mut tokens: Vec<(String, bool)> = Vec::new();
+    let mut space = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let token = match c {
+            c if c.is_whitespace() => {
+                space = true;
+                i += 1;
+                continue;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                space = true;
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i = (i + 2).min(chars.len());
+                space = true;
+                continue;
+            }
+            '\'' | '`' | '"' => {
+                i += 1;
+                while i < chars.len() {
+                    match chars[i] {
+                        '\\' => i += 2,
+                        q if q == c && chars.get(i + 1) == Some(&c) => i += 2,
+                        q if q == c => break,
+                        _ => i += 1,
+                    }
+                }
+                i = (i + 1).min(chars.len());
+                if c == '\'' { "?".to_string() } else { chars[start..i].iter().collect() }
+            }
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+                "?".to_string()
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                chars[start..i].iter().collect()
+            }
+            c => {
+                i += 1;
+                c.to_string()
+            }
+        };
+
+        // Collapse placeholder lists, ie `(?, ?, ?)` into `(?)`
+        let len = tokens.len();
+        if token == "?" && len >= 2 && tokens[len - 1].0 == "," && tokens[len - 2].0 == "?" {
+            let _ = tokens.pop();
+        } else {
+            tokens.push((token, space));
+        }
+        space = false;
+    }
+
+    let mut normalized = String::with_capacity(query.len());
+    for (token, space) in tokens {
+        if space && !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.push_str(&token);
+    }
+    normalized
+}
+
+/// Returns a stable 64-bit fingerprint of a query's normalized form, see [`normalize_query`].
+///
+/// Queries that differ only in their literals, comments, or whitespace share a fingerprint.
+pub fn query_fingerprint(query: &str) -> u64 {
+    // FNV-1a, stable across platforms and releases unlike `DefaultHasher`
+    normalize_query(query).bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Records a query's fingerprint, and the raw query unless `redact` is set, on `span`.
+///
+/// Returns the fingerprint.
+pub(crate) fn record_query_span(span: &Span, query: &str, redact: bool) -> u64 {
+    let fingerprint = query_fingerprint(query);
+    let _ = span.record(ATT_FINGERPRINT, format!("{fingerprint:016x}"));
+    if !redact {
+        let _ = span.record(attribute::DB_QUERY_TEXT, query);
+    }
+    fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_query_literals() {
+        assert_eq!(
+            normalize_query("SELECT * FROM t WHERE a = 'it''s' AND b = 'x\\'y' AND c = 1.5e3"),
+            "SELECT * FROM t WHERE a = ? AND b = ? AND c = ?"
+        );
+        assert_eq!(
+            normalize_query("INSERT INTO t VALUES (1, 'a'), (2, 'b')"),
+            "INSERT INTO t VALUES (?), (?)"
+        );
+    }
+
+    #[test]
+    fn test_normalize_query_identifiers_and_comments() {
+        assert_eq!(
+            normalize_query("SELECT `col 1`, \"x'y\", t1.c2\n  -- comment\nFROM /* c */ db.t1"),
+            "SELECT `col 1`, \"x'y\", t1.c2 FROM db.t1"
+        );
+        assert_eq!(normalize_query("SELECT toUInt8(1)"), "SELECT toUInt8(?)");
+    }
+
+    #[test]
+    fn test_statement_hook_catches_panics() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let hook = StatementHook::new(move |event| {
+            let _ = counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            assert!(!event.is_error(), "failed statement");
+        });
+        let mut event = StatementEvent {
+            qid:            Qid::new(),
+            client_id:      0,
+            fingerprint:    query_fingerprint("SELECT 1"),
+            query:          None,
+            duration:       Duration::from_millis(5),
+            read_rows:      1,
+            written_rows:   0,
+            profile_events: crate::ProfileEvents::default(),
+            error:          None,
+        };
+        hook.call(&event);
+        event.error = Some("boom".into());
+        hook.call(&event);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_connection_hooks() {
+        let reasons = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&reasons);
+        let hooks = ConnectionHooks::new()
+            .on_connect(|_| panic!("connect hook"))
+            .on_disconnect(move |event| seen.lock().push(event.reason.clone()));
+        let endpoint = SocketAddr::from(([127, 0, 0, 1], 9000));
+
+        // Panics are caught, and unset hooks are skipped
+        hooks.connected(&ConnectEvent { client_id: 1, endpoint, server_version: (25, 8, 1) });
+        hooks.reconnect_attempted(&ReconnectAttemptEvent {
+            client_id: 1,
+            endpoint: Some(endpoint),
+            attempt: 1,
+            max_attempts: 3,
+            error: None,
+            retry_in: None,
+        });
+        let error = DisconnectReason::Error("connection reset".into());
+        hooks.disconnected(&DisconnectEvent { client_id: 1, endpoint, reason: error.clone() });
+        let shutdown = DisconnectReason::Shutdown;
+        hooks.disconnected(&DisconnectEvent { client_id: 1, endpoint, reason: shutdown.clone() });
+        assert_eq!(*reasons.lock(), vec![error, shutdown]);
+        assert_eq!(
+            format!("{hooks:?}"),
+            "ConnectionHooks { on_connect: true, on_disconnect: true, on_reconnect_attempt: false }"
+        );
+    }
+
+    #[test]
+    fn test_query_fingerprint() {
+        let a = query_fingerprint("SELECT * FROM t WHERE id IN (1, 2, 3)");
+        let b = query_fingerprint("SELECT *  FROM t\nWHERE id IN (42)");
+        let c = query_fingerprint("SELECT * FROM u WHERE id IN (42)");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
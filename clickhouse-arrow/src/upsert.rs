@@ -0,0 +1,555 @@
+//! Upserts into `ReplacingMergeTree` and `CollapsingMergeTree` tables.
+//!
+//! `ClickHouse` has no `UPDATE ... ON CONFLICT`, and the usual recipe for upserts is to insert new
+//! versions of rows into a table whose engine deduplicates them on merge. [`ArrowClient::upsert`]
+//! packages that recipe:
+//! - The target's engine is checked to be a `Replacing`, `Collapsing`, or `VersionedCollapsing`
+//!   `MergeTree`, including their replicated and shared variants.
+//! - The version column, if the engine has one and the batch does not, is filled according to
+//!   [`VersionFill`], and the sign column of collapsing engines is filled with `1`.
+//! - The batch is inserted, and optionally `OPTIMIZE TABLE ... FINAL` is run to deduplicate
+//!   immediately rather than on the next merge.
+//!
+//! Collapsing engines can also be updated in place of an upsert by cancelling the stored state of
+//! rows and inserting their new state. [`ArrowClient::collapse_update`] generates the cancel rows
+//! with [`collapse_rows`], so they don't have to be constructed by hand.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use clickhouse_arrow::prelude::*;
+//! use clickhouse_arrow::upsert::{UpsertOptions, VersionFill};
+//!
+//! // CREATE TABLE users (id UInt64, name String, updated DateTime64(3))
+//! // ENGINE = ReplacingMergeTree(updated) ORDER BY id
+//! let options = UpsertOptions::default().with_version(VersionFill::Now);
+//! client.upsert("users", batch, options, None).await?;
+//! ```
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use arrow::array::{
+    ArrayRef, AsArray, Int8Array, Int64Array, RecordBatch, TimestampMicrosecondArray,
+};
+use arrow::compute::kernels::numeric::add;
+use arrow::compute::{cast, concat_batches};
+use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+use futures_util::StreamExt;
+
+use crate::ArrowOptions;
+use crate::arrow::types::ch_to_arrow_type;
+use crate::explore::{split_table, system_table_filter};
+use crate::prelude::*;
+use crate::query::quote_identifier;
+
+/// How the version column is filled for batches that do not include it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VersionFill {
+    /// The current time. `DateTime` columns get the time itself, integer columns the number of
+    /// microseconds since the Unix epoch.
+    #[default]
+    Now,
+    /// One more than the largest version in the table, read with `SELECT max(...)` before the
+    /// insert. Concurrent upserts may pick the same version.
+    Increment,
+}
+
+/// Options for [`ArrowClient::upsert`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UpsertOptions {
+    /// The version column, which defaults to the one declared by the table's engine.
+    pub version_column: Option<String>,
+    /// How the version column is filled when missing from the batch.
+    pub version:        VersionFill,
+    /// Run `OPTIMIZE TABLE ... FINAL` after the insert.
+    pub optimize:       bool,
+}
+
+impl UpsertOptions {
+    /// Set the version column, which must match the one declared by the table's engine.
+    #[must_use]
+    pub fn with_version_column(mut self, column: impl Into<String>) -> Self {
+        self.version_column = Some(column.into());
+        self
+    }
+
+    /// Set how the version column is filled when missing from the batch.
+    #[must_use]
+    pub fn with_version(mut self, version: VersionFill) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Run `OPTIMIZE TABLE ... FINAL` after the insert, so duplicates are removed immediately.
+    ///
+    /// This rewrites the table's parts and is expensive on large tables.
+    #[must_use]
+    pub fn with_optimize(mut self) -> Self {
+        self.optimize = true;
+        self
+    }
+}
+
+/// The deduplicating `MergeTree` engines that support upserts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpsertEngine {
+    /// `ReplacingMergeTree([version[, is_deleted]])`
+    Replacing,
+    /// `CollapsingMergeTree(sign)`
+    Collapsing,
+    /// `VersionedCollapsingMergeTree(sign, version)`
+    VersionedCollapsing,
+}
+
+/// The engine of an upsert target and the columns its parameters name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EngineColumns {
+    pub(crate) engine:  UpsertEngine,
+    pub(crate) version: Option<String>,
+    pub(crate) sign:    Option<String>,
+}
+
+/// Parse `engine` and `engine_full` from `system.tables`.
+///
+/// # Errors
+/// Returns an error if the engine does not support upserts.
+pub(crate) fn parse_engine(engine: &str, engine_full: &str) -> Result<EngineColumns> {
+    let base = engine.trim_start_matches("Replicated").trim_start_matches("Shared");
+    let kind = match base {
+        "ReplacingMergeTree" => UpsertEngine::Replacing,
+        "CollapsingMergeTree" => UpsertEngine::Collapsing,
+        "VersionedCollapsingMergeTree" => UpsertEngine::VersionedCollapsing,
+        _ => {
+            return Err(Error::Client(format!(
+                "Upserts require a Replacing or Collapsing MergeTree table, found {engine}"
+            )));
+        }
+    };
+
+    // Replicated engines take quoted path and replica arguments first
+    let columns = engine_arguments(engine, engine_full)
+        .into_iter()
+        .filter(|arg| !arg.starts_with('\''))
+        .map(|arg| arg.trim_matches('`').to_string())
+        .collect::<Vec<_>>();
+    let (version, sign) = match kind {
+        UpsertEngine::Replacing => (columns.first().cloned(), None),
+        UpsertEngine::Collapsing => (None, columns.first().cloned()),
+        UpsertEngine::VersionedCollapsing => (columns.get(1).cloned(), columns.first().cloned()),
+    };
+    Ok(EngineColumns { engine: kind, version, sign })
+}
+
+/// The top level arguments of `engine` in an `engine_full` definition.
+fn engine_arguments<'a>(engine: &str, engine_full: &'a str) -> Vec<&'a str> {
+    let Some(definition) = engine_full.strip_prefix(engine).and_then(|d| d.strip_prefix('('))
+    else {
+        return Vec::new();
+    };
+    let mut args = Vec::new();
+    let (mut depth, mut quoted, mut arg_start) = (0_usize, false, 0);
+    for (i, c) in definition.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted && depth == 0 => {
+                args.push(definition[arg_start..i].trim());
+                break;
+            }
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                args.push(definition[arg_start..i].trim());
+                arg_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.retain(|arg| !arg.is_empty());
+    args
+}
+
+/// Read the engine of `table` from `system.tables`.
+async fn fetch_engine(
+    client: &ArrowClient,
+    table: &str,
+    qid: Option<Qid>,
+) -> Result<EngineColumns> {
+    let (database, name) = split_table(table);
+    let (filter, params) = system_table_filter(database.as_deref(), "name", &name);
+    let info = client
+        .query_params(
+            format!("SELECT engine, engine_full FROM system.tables WHERE {filter}"),
+            Some(params),
+            qid,
+        )
+        .await?
+        .collect_result()
+        .await?
+        .collect_table()?;
+    if info.num_rows() == 0 {
+        let db = database.unwrap_or_else(|| "currentDatabase()".into());
+        return Err(Error::UndefinedTables { db, tables: vec![name] });
+    }
+    let engine = cast(info.column(0), &DataType::Utf8)?;
+    let engine_full = cast(info.column(1), &DataType::Utf8)?;
+    parse_engine(engine.as_string::<i32>().value(0), engine_full.as_string::<i32>().value(0))
+}
+
+async fn insert_batch(client: &ArrowClient, table: &str, batch: RecordBatch) -> Result<()> {
+    let query = crate::defaults::insert_statement(table, &batch)?;
+    let stream = client.insert(query, batch, None).await?;
+    tokio::pin!(stream);
+    while let Some(result) = stream.next().await {
+        result?;
+    }
+    Ok(())
+}
+
+/// Fill the version and sign columns missing from `batch`, see [`ArrowClient::upsert`].
+pub(crate) async fn upsert(
+    client: &ArrowClient,
+    table: &str,
+    batch: RecordBatch,
+    options: UpsertOptions,
+    arrow_options: ArrowOptions,
+    qid: Option<Qid>,
+) -> Result<()> {
+    let (database, name) = split_table(table);
+    let columns = fetch_engine(client, table, qid).await?;
+
+    let version = match (options.version_column, columns.version) {
+        (Some(requested), Some(declared)) if requested != declared => {
+            return Err(Error::Client(format!(
+                "Version column '{requested}' does not match '{declared}' declared by {table}"
+            )));
+        }
+        (requested, declared) => requested.or(declared),
+    };
+
+    let missing = |column: &Option<String>| {
+        column.as_ref().filter(|c| batch.schema().field_with_name(c).is_err()).cloned()
+    };
+    let (missing_version, missing_sign) = (missing(&version), missing(&columns.sign));
+    let mut batch = batch;
+    if missing_version.is_some() || missing_sign.is_some() {
+        let types =
+            crate::arrow::schema::fetch_column_types(client, database.as_deref(), &name, None)
+                .await?;
+        let arrow_type = |column: &str| {
+            let type_ =
+                types.iter().find(|(name, _)| name == column).map(|(_, type_)| type_).ok_or_else(
+                    || Error::Client(format!("Column '{column}' not found in {table}")),
+                )?;
+            Ok::<_, Error>(ch_to_arrow_type(type_, Some(arrow_options))?.0)
+        };
+        if let Some(column) = missing_version {
+            let value = match options.version {
+                VersionFill::Now => unix_micros(),
+                VersionFill::Increment => max_version(client, table, &column).await? + 1,
+            };
+            let array = version_array(value, batch.num_rows(), &arrow_type(&column)?)?;
+            batch = append_column(&batch, &column, array)?;
+        }
+        if let Some(column) = missing_sign {
+            let array = Arc::new(Int8Array::from(vec![1; batch.num_rows()])) as ArrayRef;
+            batch = append_column(&batch, &column, cast(&array, &arrow_type(&column)?)?)?;
+        }
+    }
+
+    let rows = batch.num_rows();
+    insert_batch(client, table, batch).await?;
+    debug!(table, rows, ?version, engine = ?columns.engine, "Upserted batch");
+
+    if options.optimize {
+        client.execute(format!("OPTIMIZE TABLE {table} FINAL"), None).await?;
+    }
+    Ok(())
+}
+
+/// Insert the cancel and state rows updating `old` to `new`, see [`ArrowClient::collapse_update`].
+pub(crate) async fn collapse_update(
+    client: &ArrowClient,
+    table: &str,
+    old: &RecordBatch,
+    new: &RecordBatch,
+    qid: Option<Qid>,
+) -> Result<()> {
+    let columns = fetch_engine(client, table, qid).await?;
+    let Some(sign) = columns.sign.as_deref() else {
+        return Err(Error::Client(format!(
+            "Collapsing updates require a CollapsingMergeTree or VersionedCollapsingMergeTree \
+             table, {table} is a {:?} table",
+            columns.engine
+        )));
+    };
+    let batch = collapse_rows(old, new, sign, columns.version.as_deref())?;
+    let rows = batch.num_rows();
+    insert_batch(client, table, batch).await?;
+    debug!(table, rows, engine = ?columns.engine, "Inserted collapsing update");
+    Ok(())
+}
+
+/// Build the rows that update the `old` state of some rows to their `new` state in a
+/// `CollapsingMergeTree` or `VersionedCollapsingMergeTree` table.
+///
+/// The result holds a cancel row, with `sign_column` set to `-1`, for each row of `old`, followed
+/// by a state row, with `sign_column` set to `1`, for each row of `new`. Any existing sign column
+/// in either batch is replaced. `new` must have the same columns as `old`, in any order.
+///
+/// For `VersionedCollapsingMergeTree` tables, pass the `version_column`. `old` must include it, as
+/// cancel rows only collapse rows of the same version. If `new` doesn't include it, the rows of
+/// `new` must correspond to the rows of `old` and get the old version plus one.
+///
+/// # Errors
+/// Returns an error if the batches' columns differ or a required version is missing.
+pub fn collapse_rows(
+    old: &RecordBatch,
+    new: &RecordBatch,
+    sign_column: &str,
+    version_column: Option<&str>,
+) -> Result<RecordBatch> {
+    let old = without_column(old, sign_column)?;
+    let mut new = without_column(new, sign_column)?;
+
+    if let Some(version) = version_column {
+        let old_version = old.column_by_name(version).ok_or_else(|| {
+            Error::Client(format!("Cancel rows must include the version column '{version}'"))
+        })?;
+        if new.schema().field_with_name(version).is_err() {
+            if old.num_rows() != new.num_rows() {
+                return Err(Error::Client(format!(
+                    "New rows without the version column '{version}' must correspond to the old \
+                     rows, found {} old and {} new rows",
+                    old.num_rows(),
+                    new.num_rows()
+                )));
+            }
+            let next = add(&cast(old_version, &DataType::Int64)?, &Int64Array::new_scalar(1))?;
+            new = append_column(&new, version, cast(&next, old_version.data_type())?)?;
+        }
+    }
+
+    // Line up the columns of `new` with those of `old`
+    let old_schema = old.schema();
+    if old_schema.fields().len() != new.num_columns() {
+        return Err(Error::ArrowTypeMismatch {
+            expected: format!("{old_schema:?}"),
+            provided: format!("{:?}", new.schema()),
+        });
+    }
+    let new_columns = old_schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let column = new.column_by_name(field.name()).ok_or_else(|| {
+                Error::Client(format!("New rows are missing the column '{}'", field.name()))
+            })?;
+            Ok(cast(column, field.data_type())?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let new = RecordBatch::try_new(Arc::clone(&old_schema), new_columns)?;
+
+    let signs = |sign: i8, rows: usize| Arc::new(Int8Array::from(vec![sign; rows])) as ArrayRef;
+    let old = append_column(&old, sign_column, signs(-1, old.num_rows()))?;
+    let new = append_column(&new, sign_column, signs(1, new.num_rows()))?;
+    Ok(concat_batches(&old.schema(), [&old, &new])?)
+}
+
+/// `batch` without the column `name`, if it has one.
+fn without_column(batch: &RecordBatch, name: &str) -> Result<RecordBatch> {
+    match batch.schema().index_of(name) {
+        Ok(index) => {
+            let indices = (0..batch.num_columns()).filter(|i| *i != index).collect::<Vec<_>>();
+            Ok(batch.project(&indices)?)
+        }
+        Err(_) => Ok(batch.clone()),
+    }
+}
+
+fn unix_micros() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_micros()).unwrap_or(i64::MAX))
+}
+
+/// The largest version in `table`, in the column's raw integer representation.
+async fn max_version(client: &ArrowClient, table: &str, column: &str) -> Result<i64> {
+    let batch = client
+        .query(format!("SELECT max({}) FROM {table}", quote_identifier(column)?), None)
+        .await?
+        .collect_result()
+        .await?
+        .collect_table()?;
+    if batch.num_rows() == 0 {
+        return Ok(0);
+    }
+    let max = cast(batch.column(0), &DataType::Int64)?;
+    let max = max.as_primitive::<Int64Type>();
+    Ok(if max.is_null(0) { 0 } else { max.value(0) })
+}
+
+/// An array of `rows` copies of `value`, cast to the version column's type.
+///
+/// Timestamps are built from `value` as microseconds, so [`VersionFill::Now`] maps to the current
+/// time at any precision. Values that do not fit the column's type are an error.
+fn version_array(value: i64, rows: usize, data_type: &DataType) -> Result<ArrayRef> {
+    let array: ArrayRef = match data_type {
+        DataType::Timestamp(_, tz) => Arc::new(
+            TimestampMicrosecondArray::from(vec![value; rows]).with_timezone_opt(tz.clone()),
+        ),
+        _ => Arc::new(Int64Array::from(vec![value; rows])),
+    };
+    let array = cast(&array, data_type)?;
+    if array.null_count() > 0 {
+        return Err(Error::TypeConversion(format!("Version {value} does not fit {data_type}")));
+    }
+    Ok(array)
+}
+
+fn append_column(batch: &RecordBatch, name: &str, array: ArrayRef) -> Result<RecordBatch> {
+    let schema = batch.schema();
+    let mut fields = schema.fields().iter().cloned().collect::<Vec<_>>();
+    fields.push(Arc::new(Field::new(name, array.data_type().clone(), false)));
+    let mut columns = batch.columns().to_vec();
+    columns.push(array);
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{StringArray, UInt32Array, UInt64Array};
+    use arrow::datatypes::{Int8Type, TimeUnit, UInt32Type, UInt64Type};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_engine() {
+        let columns = parse_engine(
+            "ReplacingMergeTree",
+            "ReplacingMergeTree(updated, is_deleted) ORDER BY id SETTINGS index_granularity = 8192",
+        )
+        .unwrap();
+        assert_eq!(columns, EngineColumns {
+            engine:  UpsertEngine::Replacing,
+            version: Some("updated".into()),
+            sign:    None,
+        });
+
+        let columns = parse_engine(
+            "ReplicatedVersionedCollapsingMergeTree",
+            "ReplicatedVersionedCollapsingMergeTree('/clickhouse/tables/{shard}/t', '{replica}', \
+             `sign`, ver) ORDER BY id",
+        )
+        .unwrap();
+        assert_eq!(columns.engine, UpsertEngine::VersionedCollapsing);
+        assert_eq!(columns.sign.as_deref(), Some("sign"));
+        assert_eq!(columns.version.as_deref(), Some("ver"));
+
+        let columns =
+            parse_engine("ReplacingMergeTree", "ReplacingMergeTree ORDER BY (id, name)").unwrap();
+        assert_eq!(columns.version, None);
+
+        let columns =
+            parse_engine("SharedCollapsingMergeTree", "SharedCollapsingMergeTree(s) ORDER BY id")
+                .unwrap();
+        assert_eq!(columns.sign.as_deref(), Some("s"));
+
+        assert!(matches!(
+            parse_engine("MergeTree", "MergeTree ORDER BY id"),
+            Err(Error::Client(_))
+        ));
+    }
+
+    #[test]
+    fn test_version_array() {
+        let array = version_array(5, 2, &DataType::UInt64).unwrap();
+        assert_eq!(array.as_primitive::<UInt64Type>().values().to_vec(), vec![5, 5]);
+
+        let data_type = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+        let array = version_array(1_500_000, 1, &data_type).unwrap();
+        assert_eq!(array.data_type(), &data_type);
+        assert_eq!(
+            array.as_primitive::<arrow::datatypes::TimestampMillisecondType>().value(0),
+            1_500
+        );
+
+        assert!(matches!(
+            version_array(i64::MAX, 1, &DataType::UInt32),
+            Err(Error::TypeConversion(_))
+        ));
+    }
+
+    fn users(ids: Vec<u64>, names: Vec<&str>, versions: Option<Vec<u32>>) -> RecordBatch {
+        let mut fields = vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new("name", DataType::Utf8, false),
+        ];
+        let mut columns: Vec<ArrayRef> =
+            vec![Arc::new(UInt64Array::from(ids)), Arc::new(StringArray::from(names))];
+        if let Some(versions) = versions {
+            fields.push(Field::new("ver", DataType::UInt32, false));
+            columns.push(Arc::new(UInt32Array::from(versions)));
+        }
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+    }
+
+    #[test]
+    fn test_collapse_rows() {
+        let old = users(vec![1, 2], vec!["a", "b"], None);
+        let new = users(vec![1], vec!["c"], None);
+        let rows = collapse_rows(&old, &new, "sign", None).unwrap();
+        assert_eq!(rows.num_rows(), 3);
+        assert_eq!(rows.schema().field(2).name(), "sign");
+        assert_eq!(rows.column(2).as_primitive::<Int8Type>().values().to_vec(), vec![-1, -1, 1]);
+        assert_eq!(rows.column(1).as_string::<i32>().value(2), "c");
+
+        // Existing sign columns are replaced
+        let again = collapse_rows(&rows, &new, "sign", None).unwrap();
+        assert_eq!(again.num_columns(), 3);
+        assert_eq!(again.column(2).as_primitive::<Int8Type>().values().to_vec(), vec![
+            -1, -1, -1, 1
+        ]);
+
+        // Columns must match, in any order
+        let reordered = new.project(&[1, 0]).unwrap();
+        assert!(collapse_rows(&old, &reordered, "sign", None).is_ok());
+        let narrow = new.project(&[0]).unwrap();
+        assert!(matches!(
+            collapse_rows(&old, &narrow, "sign", None),
+            Err(Error::ArrowTypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_collapse_rows_versioned() {
+        let old = users(vec![1, 2], vec!["a", "b"], Some(vec![3, 7]));
+        let new = users(vec![1, 2], vec!["c", "d"], None);
+        let rows = collapse_rows(&old, &new, "sign", Some("ver")).unwrap();
+        assert_eq!(rows.column(2).as_primitive::<UInt32Type>().values().to_vec(), vec![3, 7, 4, 8]);
+        assert_eq!(rows.column(3).as_primitive::<Int8Type>().values().to_vec(), vec![-1, -1, 1, 1]);
+
+        // Explicit new versions are kept
+        let explicit = users(vec![1], vec!["c"], Some(vec![10]));
+        let rows = collapse_rows(&old, &explicit, "sign", Some("ver")).unwrap();
+        assert_eq!(rows.column(2).as_primitive::<UInt32Type>().value(2), 10);
+
+        // Old rows must carry their version, and implied versions need matching rows
+        let unversioned = users(vec![1], vec!["a"], None);
+        assert!(collapse_rows(&unversioned, &new, "sign", Some("ver")).is_err());
+        assert!(
+            collapse_rows(&old, &users(vec![1], vec!["c"], None), "sign", Some("ver")).is_err()
+        );
+    }
+
+    #[test]
+    fn test_append_column() {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![1, 2]))]).unwrap();
+        let batch = append_column(&batch, "sign", Arc::new(Int8Array::from(vec![1, 1]))).unwrap();
+        assert_eq!(batch.num_columns(), 2);
+        assert_eq!(batch.schema().field(1).name(), "sign");
+    }
+}
@@ -0,0 +1,390 @@
+//! Client-side validation of insert blocks.
+//!
+//! Validators run against each `RecordBatch` before it is serialized and sent to `ClickHouse`.
+//! Rejecting bad data here is far cheaper than letting the server parse a block only to fail
+//! part way through an insert.
+//!
+//! A [`BlockValidator`] is an ordered set of named checks. Custom checks are plain closures
+//! (`Fn(&RecordBatch) -> Result<()>`), and a few common checks are built in:
+//! - [`BlockValidator::with_non_null_columns`]: primary key (or any required) columns contain no
+//!   nulls.
+//! - [`BlockValidator::with_monotonic_column`]: a timestamp (or integer) column never decreases.
+//! - [`BlockValidator::with_max_string_length`]: string values do not exceed a byte length.
+//!
+//! Validators are configured on the client via [`crate::ClientBuilder::with_block_validator`] and
+//! apply to [`crate::ArrowFormat`] inserts.
+//!
+//! # Examples
+//! ```rust,ignore
+//! use clickhouse_arrow::prelude::*;
+//! use clickhouse_arrow::validation::BlockValidator;
+//!
+//! let validator = BlockValidator::new()
+//!     .with_non_null_columns(["id"])
+//!     .with_monotonic_column("ts")
+//!     .with_max_string_length(["name"], 256)
+//!     .with_validator("non_empty", |batch| {
+//!         if batch.num_rows() == 0 {
+//!             return Err(Error::Client("empty batch".into()));
+//!         }
+//!         Ok(())
+//!     });
+//!
+//! let client = Client::builder()
+//!     .with_endpoint("localhost:9000")
+//!     .with_block_validator(validator)
+//!     .build_arrow()
+//!     .await?;
+//! ```
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, ArrowPrimitiveType, AsArray, RecordBatch};
+use arrow::datatypes::*;
+
+use crate::{Error, Result};
+
+/// Signature of a block validation callback.
+pub type ValidatorFn = dyn Fn(&RecordBatch) -> Result<()> + Send + Sync;
+
+/// An ordered collection of named block validators.
+///
+/// Cloning is cheap, the underlying callbacks are shared.
+#[derive(Clone, Default)]
+pub struct BlockValidator {
+    validators: Vec<(Arc<str>, Arc<ValidatorFn>)>,
+}
+
+impl std::fmt::Debug for BlockValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockValidator")
+            .field("validators", &self.validators.iter().map(|(n, _)| n).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl BlockValidator {
+    /// Create an empty validator.
+    #[must_use]
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns true if no validators are configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.validators.is_empty() }
+
+    /// Returns the number of configured validators.
+    #[must_use]
+    pub fn len(&self) -> usize { self.validators.len() }
+
+    /// Add a custom validator. The name is included in any error it returns.
+    #[must_use]
+    pub fn with_validator<F>(mut self, name: impl Into<Arc<str>>, validator: F) -> Self
+    where
+        F: Fn(&RecordBatch) -> Result<()> + Send + Sync + 'static,
+    {
+        self.validators.push((name.into(), Arc::new(validator)));
+        self
+    }
+
+    /// Require that the provided columns exist and contain no nulls.
+    ///
+    /// Useful for primary key / sorting key columns, which `ClickHouse` does not allow to be null.
+    #[must_use]
+    pub fn with_non_null_columns<I, S>(self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let columns = columns.into_iter().map(Into::into).collect::<Vec<_>>();
+        self.with_validator("non_null_columns", move |batch| {
+            columns.iter().try_for_each(|name| validate_non_null(batch, name))
+        })
+    }
+
+    /// Require that the provided column is non-decreasing within each block.
+    ///
+    /// Supports timestamp, date, time, and integer columns. Null values are skipped.
+    #[must_use]
+    pub fn with_monotonic_column(self, column: impl Into<String>) -> Self {
+        let column = column.into();
+        self.with_validator("monotonic_column", move |batch| validate_monotonic(batch, &column))
+    }
+
+    /// Require that string and binary values do not exceed `max_len` bytes.
+    ///
+    /// If `columns` is empty, every string or binary column in the block is checked.
+    #[must_use]
+    pub fn with_max_string_length<I, S>(self, columns: I, max_len: usize) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let columns = columns.into_iter().map(Into::into).collect::<Vec<_>>();
+        self.with_validator("max_string_length", move |batch| {
+            if columns.is_empty() {
+                batch
+                    .schema_ref()
+                    .fields()
+                    .iter()
+                    .zip(batch.columns())
+                    .filter(|(_, array)| is_string_like(array.data_type()))
+                    .try_for_each(|(field, array)| {
+                        check_string_length(field.name(), array, max_len)
+                    })
+            } else {
+                columns.iter().try_for_each(|name| {
+                    check_string_length(name, column_by_name(batch, name)?, max_len)
+                })
+            }
+        })
+    }
+
+    /// Run every validator against the batch, returning the first failure.
+    ///
+    /// # Errors
+    /// Returns [`Error::BlockValidation`] naming the failing validator.
+    pub fn validate(&self, batch: &RecordBatch) -> Result<()> {
+        for (name, validator) in &self.validators {
+            validator(batch).map_err(|error| match error {
+                e @ Error::BlockValidation { .. } => e,
+                e => {
+                    Error::BlockValidation { validator: name.to_string(), message: e.to_string() }
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate that a column exists and contains no nulls.
+///
+/// # Errors
+/// Returns an error if the column is missing or contains nulls.
+pub fn validate_non_null(batch: &RecordBatch, column: &str) -> Result<()> {
+    let array = column_by_name(batch, column)?;
+    let nulls = array.null_count();
+    if nulls > 0 {
+        return Err(Error::BlockValidation {
+            validator: "non_null_columns".into(),
+            message:   format!("column {column} contains {nulls} null value(s)"),
+        });
+    }
+    Ok(())
+}
+
+/// Validate that a column is non-decreasing. Nulls are skipped.
+///
+/// # Errors
+/// Returns an error if the column is missing, unsupported, or decreases at any row.
+pub fn validate_monotonic(batch: &RecordBatch, column: &str) -> Result<()> {
+    let array = column_by_name(batch, column)?;
+    let violation = match array.data_type() {
+        DataType::Int8 => first_decrease::<Int8Type>(array),
+        DataType::Int16 => first_decrease::<Int16Type>(array),
+        DataType::Int32 => first_decrease::<Int32Type>(array),
+        DataType::Int64 => first_decrease::<Int64Type>(array),
+        DataType::UInt8 => first_decrease::<UInt8Type>(array),
+        DataType::UInt16 => first_decrease::<UInt16Type>(array),
+        DataType::UInt32 => first_decrease::<UInt32Type>(array),
+        DataType::UInt64 => first_decrease::<UInt64Type>(array),
+        DataType::Date32 => first_decrease::<Date32Type>(array),
+        DataType::Date64 => first_decrease::<Date64Type>(array),
+        DataType::Time32(TimeUnit::Second) => first_decrease::<Time32SecondType>(array),
+        DataType::Time32(TimeUnit::Millisecond) => first_decrease::<Time32MillisecondType>(array),
+        DataType::Time64(TimeUnit::Microsecond) => first_decrease::<Time64MicrosecondType>(array),
+        DataType::Time64(TimeUnit::Nanosecond) => first_decrease::<Time64NanosecondType>(array),
+        DataType::Timestamp(TimeUnit::Second, _) => first_decrease::<TimestampSecondType>(array),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            first_decrease::<TimestampMillisecondType>(array)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            first_decrease::<TimestampMicrosecondType>(array)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            first_decrease::<TimestampNanosecondType>(array)
+        }
+        dt => {
+            return Err(Error::BlockValidation {
+                validator: "monotonic_column".into(),
+                message:   format!("column {column} has unsupported type {dt}"),
+            });
+        }
+    };
+
+    if let Some(row) = violation {
+        return Err(Error::BlockValidation {
+            validator: "monotonic_column".into(),
+            message:   format!("column {column} decreases at row {row}"),
+        });
+    }
+    Ok(())
+}
+
+/// Validate that string or binary values in a column do not exceed `max_len` bytes.
+///
+/// # Errors
+/// Returns an error if the column is missing, is not a string type, or a value is too long.
+pub fn validate_max_string_length(batch: &RecordBatch, column: &str, max_len: usize) -> Result<()> {
+    check_string_length(column, column_by_name(batch, column)?, max_len)
+}
+
+fn column_by_name<'a>(batch: &'a RecordBatch, column: &str) -> Result<&'a ArrayRef> {
+    batch.column_by_name(column).ok_or_else(|| Error::BlockValidation {
+        validator: "column_lookup".into(),
+        message:   format!("column {column} not found in block"),
+    })
+}
+
+fn is_string_like(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Utf8View
+            | DataType::Binary
+            | DataType::LargeBinary
+            | DataType::BinaryView
+    )
+}
+
+/// Returns the first row index whose value is less than the previous non-null value.
+fn first_decrease<T: ArrowPrimitiveType>(array: &ArrayRef) -> Option<usize> {
+    let array = array.as_primitive::<T>();
+    let mut previous: Option<T::Native> = None;
+    for (row, value) in array.iter().enumerate() {
+        let Some(value) = value else { continue };
+        if previous.is_some_and(|p| value < p) {
+            return Some(row);
+        }
+        previous = Some(value);
+    }
+    None
+}
+
+fn check_string_length(column: &str, array: &ArrayRef, max_len: usize) -> Result<()> {
+    // Returns the first row exceeding the limit along with its length
+    let first_over = |lengths: &mut dyn Iterator<Item = Option<usize>>| {
+        lengths.enumerate().find_map(|(row, len)| len.filter(|l| *l > max_len).map(|l| (row, l)))
+    };
+
+    let violation = match array.data_type() {
+        DataType::Utf8 => first_over(&mut array.as_string::<i32>().iter().map(|v| v.map(str::len))),
+        DataType::LargeUtf8 => {
+            first_over(&mut array.as_string::<i64>().iter().map(|v| v.map(str::len)))
+        }
+        DataType::Utf8View => {
+            first_over(&mut array.as_string_view().iter().map(|v| v.map(str::len)))
+        }
+        DataType::Binary => {
+            first_over(&mut array.as_binary::<i32>().iter().map(|v| v.map(<[u8]>::len)))
+        }
+        DataType::LargeBinary => {
+            first_over(&mut array.as_binary::<i64>().iter().map(|v| v.map(<[u8]>::len)))
+        }
+        DataType::BinaryView => {
+            first_over(&mut array.as_binary_view().iter().map(|v| v.map(<[u8]>::len)))
+        }
+        dt => {
+            return Err(Error::BlockValidation {
+                validator: "max_string_length".into(),
+                message:   format!("column {column} has non-string type {dt}"),
+            });
+        }
+    };
+
+    if let Some((row, len)) = violation {
+        return Err(Error::BlockValidation {
+            validator: "max_string_length".into(),
+            message:   format!(
+                "column {column} value at row {row} is {len} bytes, exceeds maximum {max_len}"
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, StringArray, TimestampMillisecondArray};
+    use arrow::datatypes::{Field, Schema};
+
+    use super::*;
+
+    fn test_batch(ids: Vec<Option<i64>>, ts: Vec<i64>, names: Vec<&str>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, None), false),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(TimestampMillisecondArray::from(ts)),
+            Arc::new(StringArray::from(names)),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_empty_validator_passes() {
+        let batch = test_batch(vec![None], vec![1], vec!["a"]);
+        let validator = BlockValidator::new();
+        assert!(validator.is_empty());
+        assert!(validator.validate(&batch).is_ok());
+    }
+
+    #[test]
+    fn test_non_null_columns() {
+        let validator = BlockValidator::new().with_non_null_columns(["id"]);
+        let good = test_batch(vec![Some(1), Some(2)], vec![1, 2], vec!["a", "b"]);
+        assert!(validator.validate(&good).is_ok());
+
+        let bad = test_batch(vec![Some(1), None], vec![1, 2], vec!["a", "b"]);
+        let err = validator.validate(&bad).unwrap_err();
+        assert!(matches!(err, Error::BlockValidation { .. }));
+        assert!(err.to_string().contains("null"));
+
+        let missing = BlockValidator::new().with_non_null_columns(["nope"]);
+        assert!(missing.validate(&good).is_err());
+    }
+
+    #[test]
+    fn test_monotonic_column() {
+        let validator = BlockValidator::new().with_monotonic_column("ts");
+        let good = test_batch(vec![Some(1), Some(2), Some(3)], vec![1, 1, 5], vec!["a", "b", "c"]);
+        assert!(validator.validate(&good).is_ok());
+
+        let bad = test_batch(vec![Some(1), Some(2), Some(3)], vec![1, 5, 4], vec!["a", "b", "c"]);
+        let err = validator.validate(&bad).unwrap_err();
+        assert!(err.to_string().contains("row 2"));
+
+        let unsupported = BlockValidator::new().with_monotonic_column("name");
+        assert!(unsupported.validate(&good).is_err());
+    }
+
+    #[test]
+    fn test_max_string_length() {
+        let batch = test_batch(vec![Some(1), Some(2)], vec![1, 2], vec!["ab", "abcdef"]);
+        assert!(BlockValidator::new().with_max_string_length(["name"], 6).validate(&batch).is_ok());
+
+        let err =
+            BlockValidator::new().with_max_string_length(["name"], 5).validate(&batch).unwrap_err();
+        assert!(err.to_string().contains("row 1"));
+
+        // Empty column list checks every string column
+        let none: [&str; 0] = [];
+        assert!(BlockValidator::new().with_max_string_length(none, 3).validate(&batch).is_err());
+
+        // Non-string column named explicitly is an error
+        assert!(BlockValidator::new().with_max_string_length(["id"], 3).validate(&batch).is_err());
+    }
+
+    #[test]
+    fn test_custom_validator_named_in_error() {
+        let batch = test_batch(vec![Some(1)], vec![1], vec!["a"]);
+        let validator = BlockValidator::new()
+            .with_validator("always_fails", |_| Err(Error::Client("nope".into())));
+        assert_eq!(validator.len(), 1);
+        let err = validator.validate(&batch).unwrap_err();
+        assert!(err.to_string().contains("always_fails"));
+        assert!(format!("{validator:?}").contains("always_fails"));
+    }
+}
@@ -2,3 +2,4 @@
 
 pub const DISABLE_CLEANUP_ENV: &str = "DISABLE_CLEANUP";
 pub const DISABLE_CLEANUP_ON_ERROR_ENV: &str = "DISABLE_CLEANUP_ON_ERROR";
+pub const UPDATE_GOLDENS_ENV: &str = "UPDATE_GOLDENS";
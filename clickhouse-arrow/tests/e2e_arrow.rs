@@ -77,3 +77,203 @@ e2e_test!(
 // Test named tuple field parsing (issue #85)
 #[cfg(feature = "test-utils")]
 e2e_test!(e2e_arrow_named_tuple, tests::arrow::test_named_tuple_schema, TRACING_DIRECTIVES, None);
+
+// Test inserting a zero-copy slice of a batch
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_insert_slice, tests::arrow::test_insert_slice, TRACING_DIRECTIVES, None);
+
+// Test previewing tables with sampling and column summaries
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_preview, tests::arrow::test_preview, TRACING_DIRECTIVES, None);
+
+// Test computing column statistics with a single aggregate query
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_column_stats, tests::arrow::test_column_stats, TRACING_DIRECTIVES, None);
+
+// Test skipping columns outside a query's projection
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_query_projection,
+    tests::arrow::test_query_projection,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test rescaling decimal columns while decoding
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_query_decimal_rescale,
+    tests::arrow::test_query_decimal_rescale,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test inserting and querying 64-bit offset strings, binaries, and lists
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_large_offsets, tests::arrow::test_large_offsets, TRACING_DIRECTIVES, None);
+
+// Test inserting and querying maps with integer, UUID, and date keys
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_map_key_types, tests::arrow::test_map_key_types, TRACING_DIRECTIVES, None);
+
+// Test decoding columns of lazily decoded batches on access
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_lazy_query, tests::arrow::test_lazy_query, TRACING_DIRECTIVES, None);
+
+// Test emitting statement events to a statement hook
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_statement_hook, tests::arrow::test_statement_hook, TRACING_DIRECTIVES, None);
+
+// Test round tripping randomly generated schemas and data
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_generated_round_trip,
+    tests::arrow::test_generated_round_trip,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test lightweight and mutation deletes
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_delete, tests::arrow::test_delete, TRACING_DIRECTIVES, None);
+
+// Test duplicated result column names are preserved and mapped
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_duplicate_column_names,
+    tests::arrow::test_duplicate_column_names,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test null handling policies for non-nullable insert targets
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_null_policy, tests::arrow::test_null_policy, TRACING_DIRECTIVES, None);
+
+// Test server-side INSERT ... SELECT with progress and summary
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_insert_select, tests::arrow::test_insert_select, TRACING_DIRECTIVES, None);
+
+// Test staging batches in a staging table and committing or rolling them back
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_staged_insert, tests::arrow::test_staged_insert, TRACING_DIRECTIVES, None);
+
+// Test inserting around MATERIALIZED, ALIAS and DEFAULT columns
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_insert_table_defaults,
+    tests::arrow::test_insert_table_defaults,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test detecting and reconciling schema drift under an insert sink
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_insert_sink_drift,
+    tests::arrow::test_insert_sink_drift,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test replaying spooled batches through an insert sink
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_spool_replay, tests::arrow::test_spool_replay, TRACING_DIRECTIVES, None);
+
+// Test checking out pooled connections per query and insert
+#[cfg(all(feature = "test-utils", feature = "pool"))]
+e2e_test!(e2e_arrow_client_pool, tests::arrow::test_client_pool, TRACING_DIRECTIVES, None);
+
+// Test re-establishing a dropped connection before the next query, reporting it to hooks
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_reconnect, tests::arrow::test_reconnect, TRACING_DIRECTIVES, None);
+
+// Test failing over to the next endpoint when one cannot be connected to
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_endpoint_failover,
+    tests::arrow::test_endpoint_failover,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test refusing writes and DDL from a read-only client before they reach the server
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_readonly, tests::arrow::test_readonly, TRACING_DIRECTIVES, None);
+
+// Test shedding queries that wait too long for a slot
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_query_queue, tests::arrow::test_query_queue, TRACING_DIRECTIVES, None);
+
+// Test receiving progress updates alongside query results and through a callback
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_query_progress, tests::arrow::test_query_progress, TRACING_DIRECTIVES, None);
+
+// Test merging small batches into larger blocks on insert
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_insert_coalescing,
+    tests::arrow::test_insert_coalescing,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test sending per-query settings through query options
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_query_options_settings,
+    tests::arrow::test_query_options_settings,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test probing latency, timezone, and clock skew
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_probe, tests::arrow::test_probe, TRACING_DIRECTIVES, None);
+
+// Test failing and cancelling a query that exceeds its result limits
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_query_result_limits,
+    tests::arrow::test_query_result_limits,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test validating the ordering of query results
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_query_assert_sorted_by,
+    tests::arrow::test_query_assert_sorted_by,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test attaching query id and block index metadata to result batches
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_batch_metadata, tests::arrow::test_batch_metadata, TRACING_DIRECTIVES, None);
+
+// Test masking sensitive columns before insert
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_insert_masking, tests::arrow::test_insert_masking, TRACING_DIRECTIVES, None);
+
+// Test dry running inserts with a conversion report
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_validate_batch, tests::arrow::test_validate_batch, TRACING_DIRECTIVES, None);
+
+// Test running OPTIMIZE statements with the background maintenance scheduler
+#[cfg(feature = "test-utils")]
+e2e_test!(
+    e2e_arrow_maintenance_scheduler,
+    tests::arrow::test_maintenance_scheduler,
+    TRACING_DIRECTIVES,
+    None
+);
+
+// Test upserting into a ReplacingMergeTree table
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_upsert, tests::arrow::test_upsert, TRACING_DIRECTIVES, None);
+
+// Test updating a collapsing table with cancel and state rows
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_arrow_collapse_update, tests::arrow::test_collapse_update, TRACING_DIRECTIVES, None);
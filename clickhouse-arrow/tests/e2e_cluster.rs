@@ -0,0 +1,16 @@
+#![allow(unused_crate_dependencies)]
+
+pub mod common;
+pub mod tests;
+
+const TRACING_DIRECTIVES: &[(&str, &str)] =
+    &[("testcontainers", "debug"), ("clickhouse_arrow", "debug")];
+
+// Test ON CLUSTER DDL, insert_quorum, and Distributed reads on a 2x2 cluster
+#[cfg(feature = "test-utils")]
+e2e_cluster_test!(
+    e2e_cluster_on_cluster_replication,
+    tests::cluster::test_on_cluster_replication,
+    TRACING_DIRECTIVES,
+    None
+);
@@ -0,0 +1,10 @@
+#![allow(unused_crate_dependencies)]
+
+pub mod common;
+pub mod tests;
+
+const TRACING_DIRECTIVES: &[(&str, &str)] =
+    &[("testcontainers", "debug"), ("clickhouse_arrow", "info")];
+
+// Snapshot the Arrow schema and values of every supported type against golden files
+e2e_test!(e2e_type_goldens, tests::golden::test_type_goldens, TRACING_DIRECTIVES, None);
@@ -6,12 +6,14 @@ use std::sync::Arc;
 
 use arrow::array::{
     ArrayRef, BooleanArray, Date32Array, Float64Array, Int32Array, Int64Array, RecordBatch,
-    StringArray, TimestampMillisecondArray,
+    StringArray, TimestampMillisecondArray, UInt8Array,
 };
 use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use clickhouse_arrow::ParamValue;
 use clickhouse_arrow::http::{HttpClient, HttpOptions};
 use clickhouse_arrow::prelude::ClientBuilder;
 use clickhouse_arrow::test_utils::ClickHouseContainer;
+use futures_util::TryStreamExt;
 
 pub mod common;
 pub mod tests;
@@ -51,6 +53,14 @@ e2e_test!(e2e_http_types, test_http_types, TRACING_DIRECTIVES, None);
 #[cfg(feature = "test-utils")]
 e2e_test!(e2e_http_errors, test_http_errors, TRACING_DIRECTIVES, None);
 
+// HTTP raw format test
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_http_raw_format, test_http_raw_format, TRACING_DIRECTIVES, None);
+
+// HTTP query parameters test
+#[cfg(feature = "test-utils")]
+e2e_test!(e2e_http_params, test_http_params, TRACING_DIRECTIVES, None);
+
 /// Create a test schema
 fn test_schema() -> Arc<Schema> {
     Arc::new(Schema::new(vec![
@@ -470,3 +480,66 @@ pub async fn test_http_errors(ch: Arc<ClickHouseContainer>) {
 
     eprintln!("HTTP error handling test passed");
 }
+
+/// Test raw format queries
+///
+/// # Panics
+/// Panics if assertions fail
+pub async fn test_http_raw_format(ch: Arc<ClickHouseContainer>) {
+    let client = create_http_client(&ch);
+
+    let body = client
+        .query_raw_format("SELECT number, toString(number) AS s FROM numbers(3)", "CSV")
+        .await
+        .expect("Raw format query should succeed")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Read response body")
+        .concat();
+    assert_eq!(String::from_utf8(body).unwrap(), "0,\"0\"\n1,\"1\"\n2,\"2\"\n");
+
+    let body = client
+        .query_raw_format("SELECT 1 AS value", "JSONCompactEachRow")
+        .await
+        .expect("Raw format query should succeed")
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Read response body")
+        .concat();
+    assert_eq!(String::from_utf8(body).unwrap().trim(), "[1]");
+
+    // Invalid format names are rejected client-side
+    assert!(client.query_raw_format("SELECT 1", "CSV; DROP TABLE x").await.is_err());
+
+    eprintln!("HTTP raw format test passed");
+}
+
+/// Test query parameters are escaped for their placeholder types
+///
+/// # Panics
+/// Panics if assertions fail
+pub async fn test_http_params(ch: Arc<ClickHouseContainer>) {
+    let client = create_http_client(&ch);
+
+    // Scalar strings are escaped, array literals carry their own escapes
+    let params = vec![
+        ("name", ParamValue::from("O'Brien \\ Sons\tLtd")),
+        ("tags", ParamValue::from(r"['it\'s', 'a\\b']")),
+    ];
+    let batches = client
+        .query_params(
+            r"SELECT toUInt8({name:String} = 'O\'Brien \\ Sons\tLtd') AS name_ok,
+                toUInt8({tags:Array(String)} = ['it\'s', 'a\\b']) AS tags_ok",
+            params,
+        )
+        .await
+        .expect("Query with parameters should succeed");
+
+    let batch = &batches[0];
+    for column in batch.columns() {
+        let values = column.as_any().downcast_ref::<UInt8Array>().expect("UInt8 column");
+        assert_eq!(values.value(0), 1);
+    }
+
+    eprintln!("HTTP params test passed");
+}
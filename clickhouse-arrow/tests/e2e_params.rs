@@ -25,3 +25,11 @@ e2e_test!(e2e_params_array_int32, tests::params::test_params_array_int32, TRACIN
 
 // Test parameter functionality - mixed types
 e2e_test!(e2e_params_mixed_types, tests::params::test_params_mixed_types, TRACING_DIRECTIVES, None);
+
+// Test parameter functionality - strings with quotes and backslashes
+e2e_test!(
+    e2e_params_quoted_strings,
+    tests::params::test_params_quoted_strings,
+    TRACING_DIRECTIVES,
+    None
+);
@@ -889,3 +889,95 @@ async fn test_sparse_large_scale() {
 
     println!("Large-scale sparse test passed!");
 }
+
+/// Sparse elements nested within `Tuple` columns – kinds are sent per element.
+#[tokio::test]
+async fn test_sparse_nested_tuple_elements() {
+    init_tracing(None);
+
+    let ch = get_or_create_container(None).await;
+    let client = Client::<ArrowFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_arrow_options(ArrowOptions::default().with_strings_as_strings(true))
+        .build()
+        .await
+        .expect("Failed to create client");
+
+    client
+        .execute("DROP TABLE IF EXISTS sparse_tuple_test", None)
+        .await
+        .expect("Failed to drop table");
+
+    // Force sparse serialization for any element with defaults
+    client
+        .execute(
+            r#"
+            CREATE TABLE sparse_tuple_test (
+                id UInt64,
+                t Tuple(a Int64, b String),
+                nested Tuple(x Tuple(y Int32, z String), w UInt8)
+            ) ENGINE = MergeTree()
+            ORDER BY id
+            SETTINGS ratio_of_defaults_for_sparse_serialization = 0.5
+            "#,
+            None,
+        )
+        .await
+        .expect("Failed to create table");
+
+    // `a` and `y` are 95% defaults, `b`, `z` and `w` are dense
+    client
+        .execute(
+            "INSERT INTO sparse_tuple_test SELECT number, (if(number % 20 = 0, number, 0), \
+             toString(number)), ((if(number % 20 = 0, toInt32(number), 0), toString(number)), \
+             toUInt8(number % 7 + 1)) FROM numbers(10000)",
+            None,
+        )
+        .await
+        .expect("Failed to insert data");
+
+    client
+        .execute("OPTIMIZE TABLE sparse_tuple_test FINAL", None)
+        .await
+        .expect("Failed to optimize table");
+
+    let mut stream = Client::<ArrowFormat>::query(
+        &client,
+        "SELECT id, t, nested FROM sparse_tuple_test ORDER BY id",
+        None,
+    )
+    .await
+    .expect("Failed to query");
+
+    let mut total_rows = 0;
+    while let Some(result) = stream.next().await {
+        let batch = result.expect("Failed to get batch");
+        let ids = batch.column(0).as_primitive::<arrow::datatypes::UInt64Type>();
+        let t = batch.column(1).as_struct();
+        let a = t.column(0).as_primitive::<arrow::datatypes::Int64Type>();
+        let b = t.column(1).as_string::<i32>();
+        let nested = batch.column(2).as_struct();
+        let x = nested.column(0).as_struct();
+        let y = x.column(0).as_primitive::<arrow::datatypes::Int32Type>();
+        let z = x.column(1).as_string::<i32>();
+        let w = nested.column(1).as_primitive::<arrow::datatypes::UInt8Type>();
+
+        for i in 0..batch.num_rows() {
+            let id = ids.value(i);
+            let expected = if id % 20 == 0 { id } else { 0 };
+            assert_eq!(a.value(i) as u64, expected, "t.a mismatch at id {id}");
+            assert_eq!(y.value(i) as u64, expected, "nested.x.y mismatch at id {id}");
+            assert_eq!(b.value(i), id.to_string(), "t.b mismatch at id {id}");
+            assert_eq!(z.value(i), id.to_string(), "nested.x.z mismatch at id {id}");
+            assert_eq!(w.value(i) as u64, id % 7 + 1, "nested.w mismatch at id {id}");
+        }
+        total_rows += batch.num_rows();
+    }
+
+    assert_eq!(total_rows, 10000, "Expected 10000 rows");
+
+    // Cleanup
+    client.execute("DROP TABLE IF EXISTS sparse_tuple_test", None).await.unwrap();
+}
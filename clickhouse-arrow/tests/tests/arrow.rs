@@ -3,11 +3,23 @@ use std::sync::Arc;
 
 use arrow::array::*;
 use arrow::datatypes::*;
+use clickhouse_arrow::arrow::{BatchMetadata, DecimalOverflow, DecimalRescale};
+use clickhouse_arrow::arrow::columns::{ColumnNames, ColumnsByName};
+use clickhouse_arrow::arrow::conversion::{ConversionAction, SuggestedFix};
+use clickhouse_arrow::defaults::{DefaultKind, GeneratedColumnPolicy};
+use clickhouse_arrow::maintenance::{
+    MaintenanceJob, MaintenanceOutcome, MaintenanceScheduler, MaintenanceTask,
+};
+use clickhouse_arrow::masking::{InsertMasking, MaskingAudit};
 use clickhouse_arrow::prelude::*;
+use clickhouse_arrow::sink::{DriftAction, InsertSinkOptions};
+use clickhouse_arrow::spool::Spool;
 use clickhouse_arrow::test_utils::ClickHouseContainer;
+use clickhouse_arrow::test_utils::generator::{SchemaGenerator, assert_round_trip};
+use clickhouse_arrow::upsert::{UpsertOptions, VersionFill};
 use clickhouse_arrow::{
-    ArrowOptions, CompressionMethod, ConnectionStatus, CreateOptions, Result as ClickHouseResult,
-    Type,
+    ArrowOptions, CompressionMethod, ConnectionStatus, CreateOptions, NullPolicy, Progress,
+    Result as ClickHouseResult, Type,
 };
 use futures_util::StreamExt;
 use tracing::debug;
@@ -702,3 +714,1972 @@ pub async fn test_nullable_array_serialization(ch: Arc<ClickHouseContainer>) {
 
     client.shutdown().await.unwrap();
 }
+
+/// # Panics
+pub async fn test_insert_slice(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt32, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new(
+            "tags",
+            DataType::List(Arc::new(Field::new("item", DataType::Utf8, false))),
+            false,
+        ),
+    ]));
+
+    let mut tags = ListBuilder::new(StringBuilder::new()).with_field(Arc::new(Field::new(
+        "item",
+        DataType::Utf8,
+        false,
+    )));
+    for i in 0..6 {
+        for j in 0..=i {
+            tags.values().append_value(format!("tag{i}_{j}"));
+        }
+        tags.append(true);
+    }
+    let batch = RecordBatch::try_new(Arc::clone(&schema), vec![
+        Arc::new(UInt32Array::from_iter_values(0..6)),
+        Arc::new(StringArray::from_iter_values((0..6).map(|i| format!("name{i}")))),
+        Arc::new(tags.finish()),
+    ])
+    .expect("Failed to create RecordBatch");
+
+    let table_name = format!("test_insert_slice_{}", Qid::new());
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table_name} (id UInt32, name String, tags Array(String)) ENGINE = \
+                 Memory"
+            ),
+            None,
+        )
+        .await
+        .expect("Failed to create table");
+
+    // Insert the middle rows only, exercising non-zero offsets in every serializer
+    let query_id = Qid::new();
+    header(query_id, format!("Inserting slice into {table_name}"));
+    let _ = client
+        .insert_slice(&table_name, &batch, 2..5, Some(query_id))
+        .await
+        .expect("Insert slice failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to insert slice");
+
+    // Out of bounds ranges are rejected before anything is sent
+    assert!(client.insert_slice(&table_name, &batch, 4..7, None).await.is_err());
+
+    let queried = client
+        .query(format!("SELECT id, name, tags FROM {table_name} ORDER BY id"), None)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to query data")
+        .collect_table()
+        .expect("Failed to concat batches");
+
+    let expected = batch.slice(2, 3);
+    assert_eq!(queried.num_rows(), 3);
+    assert_eq!(queried.column(0).as_ref(), expected.column(0).as_ref());
+    let names = queried.column(1).as_string::<i32>();
+    let tags = queried.column(2).as_list::<i32>();
+    for row in 0..3 {
+        let id = row + 2;
+        assert_eq!(names.value(row), format!("name{id}"));
+        let row_tags = tags.value(row);
+        let row_tags = row_tags.as_string::<i32>();
+        assert_eq!(row_tags.len(), id + 1);
+        assert_eq!(row_tags.value(0), format!("tag{id}_0"));
+    }
+
+    client
+        .execute(format!("DROP TABLE {table_name}"), None)
+        .await
+        .expect("Failed to drop table");
+    client.shutdown().await.unwrap();
+}
+
+pub async fn test_preview(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let table_name = format!("test_preview_{}", Qid::new());
+    let sampled_name = format!("test_preview_sampled_{}", Qid::new());
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table_name} (id UInt64, name Nullable(String)) ENGINE = MergeTree \
+                 ORDER BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Failed to create table");
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {sampled_name} (id UInt64) ENGINE = MergeTree ORDER BY \
+                 intHash32(id) SAMPLE BY intHash32(id)"
+            ),
+            None,
+        )
+        .await
+        .expect("Failed to create sampled table");
+    for table in [&table_name, &sampled_name] {
+        let columns = if table == &table_name {
+            "number, if(number % 2 = 0, NULL, toString(number))"
+        } else {
+            "number"
+        };
+        client
+            .execute(format!("INSERT INTO {table} SELECT {columns} FROM numbers(10000)"), None)
+            .await
+            .expect("Failed to insert rows");
+    }
+
+    // Small previews of large tables are sampled rather than sorted
+    let query_id = Qid::new();
+    header(query_id, format!("Previewing {table_name}"));
+    let preview = client.preview(&table_name, 10, Some(query_id)).await.expect("Preview failed");
+    assert_eq!(preview.total_rows, Some(10000));
+    assert!(matches!(preview.method, SampleMethod::RandomFilter(_)));
+    assert!(preview.batch.num_rows() <= 10);
+    assert_eq!(preview.columns.len(), 2);
+    assert_eq!(preview.columns[0].name, "id");
+    assert_eq!(preview.columns[0].null_count, 0);
+
+    let preview = client.preview(&sampled_name, 10, None).await.expect("Preview failed");
+    assert!(matches!(preview.method, SampleMethod::SampleClause(_)));
+    assert!(preview.batch.num_rows() <= 10);
+
+    // Previews covering the whole table read it with a plain limit
+    let preview = client.preview(&table_name, 20000, None).await.expect("Preview failed");
+    assert_eq!(preview.method, SampleMethod::Limit);
+    assert_eq!(preview.batch.num_rows(), 10000);
+    assert_eq!(preview.columns[1].null_count, 5000);
+    assert_eq!(preview.columns[0].min.as_deref(), Some("0"));
+    assert_eq!(preview.columns[0].max.as_deref(), Some("9999"));
+
+    assert!(matches!(
+        client.preview("test_preview_missing", 10, None).await,
+        Err(Error::UndefinedTables { .. })
+    ));
+
+    for table in [&table_name, &sampled_name] {
+        client.execute(format!("DROP TABLE {table}"), None).await.expect("Failed to drop table");
+    }
+    client.shutdown().await.unwrap();
+}
+
+pub async fn test_column_stats(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let table_name = format!("test_column_stats_{}", Qid::new());
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table_name} (id UInt64, name Nullable(String)) ENGINE = MergeTree \
+                 ORDER BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Failed to create table");
+
+    // Empty tables report no min or max rather than default values
+    let stats =
+        client.column_stats(&table_name, &["id", "name"], None).await.expect("Stats failed");
+    assert_eq!(stats.row_count, 0);
+    assert_eq!(stats.columns[0].min, None);
+    assert_eq!(stats.columns[1].distinct_estimate, 0);
+
+    client
+        .execute(
+            format!(
+                "INSERT INTO {table_name} SELECT number, if(number % 4 = 0, NULL, toString(number \
+                 % 10)) FROM numbers(1000)"
+            ),
+            None,
+        )
+        .await
+        .expect("Failed to insert rows");
+
+    let query_id = Qid::new();
+    header(query_id, format!("Computing column stats for {table_name}"));
+    let stats = client
+        .column_stats(&table_name, &["id", "name"], Some(query_id))
+        .await
+        .expect("Stats failed");
+    assert_eq!(stats.row_count, 1000);
+
+    let id = stats.column("id").expect("Missing id stats");
+    assert_eq!(id.min.as_deref(), Some("0"));
+    assert_eq!(id.max.as_deref(), Some("999"));
+    assert_eq!(id.null_count, 0);
+
+    let name = stats.column("name").expect("Missing name stats");
+    assert_eq!(name.min.as_deref(), Some("0"));
+    assert_eq!(name.max.as_deref(), Some("9"));
+    assert_eq!(name.null_count, 250);
+    assert!((name.null_fraction - 0.25).abs() < f64::EPSILON);
+    assert_eq!(name.distinct_estimate, 10);
+
+    assert!(client.column_stats(&table_name, &[], None).await.is_err());
+    assert!(client.column_stats(&table_name, &["missing"], None).await.is_err());
+
+    client
+        .execute(format!("DROP TABLE {table_name}"), None)
+        .await
+        .expect("Failed to drop table");
+    client.shutdown().await.unwrap();
+}
+
+pub async fn test_query_projection(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    header(query_id, "Querying with a projection");
+    let options = QueryOptions::new().project(["id", "tags"]).with_qid(query_id);
+    let batch = client
+        .query_with_options(
+            "SELECT number AS id, toString(number) AS name, toLowCardinality(toString(number % \
+             3)) AS category, range(number % 4) AS tags FROM numbers(100000)",
+            options,
+        )
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results")
+        .collect_table()
+        .expect("Failed to concat batches");
+
+    let schema = batch.schema();
+    let names = schema.fields().iter().map(|f| f.name().as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["id", "tags"]);
+    assert_eq!(batch.num_rows(), 100_000);
+    let tags = batch.column(1).as_list::<i32>();
+    assert_eq!(tags.value(7).len(), 3);
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_query_decimal_rescale(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    header(query_id, "Querying with decimal rescaling");
+    let options = QueryOptions::new()
+        .rescale_decimal("total", DecimalRescale::new(18, 4))
+        .rescale_decimal("big", DecimalRescale::new(9, 2).with_overflow(DecimalOverflow::Saturate))
+        .with_qid(query_id);
+    let batch = client
+        .query_with_options(
+            "SELECT toDecimal128(number, 10) / 3 AS total, toDecimal128(1e12, 10) AS big FROM \
+             numbers(4) ORDER BY number",
+            options,
+        )
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results")
+        .collect_table()
+        .expect("Failed to concat batches");
+
+    let schema = batch.schema();
+    assert_eq!(schema.field(0).data_type(), &DataType::Decimal128(18, 4));
+    assert_eq!(schema.field(1).data_type(), &DataType::Decimal128(9, 2));
+    let totals = batch.column(0).as_primitive::<Decimal128Type>().values().to_vec();
+    assert_eq!(totals, vec![0, 3_333, 6_667, 10_000]);
+    let big = batch.column(1).as_primitive::<Decimal128Type>();
+    assert!(big.values().iter().all(|v| *v == 999_999_999));
+
+    // Rescaling a column that is not a decimal fails the query
+    let options = QueryOptions::new().rescale_decimal("n", DecimalRescale::new(18, 4));
+    let result = client
+        .query_with_options("SELECT number AS n FROM numbers(1)", options)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await;
+    assert!(result.is_err());
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_large_offsets(ch: Arc<ClickHouseContainer>) {
+    let client = Client::<ArrowFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .with_arrow_options(
+            ArrowOptions::default().with_strings_as_strings(true).with_large_offsets(true),
+        )
+        .build_arrow()
+        .await
+        .expect("Failed to build client");
+
+    let item = Arc::new(Field::new("item", DataType::LargeUtf8, false));
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt32, false),
+        Field::new("name", DataType::LargeUtf8, true),
+        Field::new("data", DataType::LargeBinary, false),
+        Field::new("tags", DataType::LargeList(Arc::clone(&item)), false),
+    ]));
+    let mut tags = LargeListBuilder::new(LargeStringBuilder::new()).with_field(item);
+    for i in 0..4 {
+        for j in 0..i {
+            tags.values().append_value(format!("tag{i}_{j}"));
+        }
+        tags.append(true);
+    }
+    let batch = RecordBatch::try_new(Arc::clone(&schema), vec![
+        Arc::new(UInt32Array::from_iter_values(0..4)),
+        Arc::new(LargeStringArray::from(vec![Some("a"), None, Some("c"), Some("d")])),
+        Arc::new(LargeBinaryArray::from_iter_values((0..4).map(|i| format!("bytes{i}")))),
+        Arc::new(tags.finish()),
+    ])
+    .expect("Failed to create RecordBatch");
+
+    let table_name = format!("test_large_offsets_{}", Qid::new());
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table_name} (id UInt32, name Nullable(String), data String, tags \
+                 Array(String)) ENGINE = Memory"
+            ),
+            None,
+        )
+        .await
+        .expect("Failed to create table");
+
+    let query_id = Qid::new();
+    header(query_id, format!("Inserting 64-bit offset arrays into {table_name}"));
+    let _ = client
+        .insert(&table_name, batch.clone(), Some(query_id))
+        .await
+        .expect("Insert failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to insert");
+
+    // Read back as 64-bit offset types, `String` columns as `LargeUtf8`
+    let queried = client
+        .query(format!("SELECT id, name, data, tags FROM {table_name} ORDER BY id"), None)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to query data")
+        .collect_table()
+        .expect("Failed to concat batches");
+    assert_eq!(queried.num_rows(), 4);
+    assert_eq!(queried.schema().field(2).data_type(), &DataType::LargeUtf8);
+    for i in [0, 1, 3] {
+        assert_eq!(queried.schema().field(i), schema.field(i));
+        assert_eq!(queried.column(i), batch.column(i));
+    }
+    let data = queried.column(2).as_string::<i64>();
+    assert_eq!(data.value(3), "bytes3");
+
+    client
+        .execute(format!("DROP TABLE {table_name}"), None)
+        .await
+        .expect("Failed to drop table");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_map_key_types(ch: Arc<ClickHouseContainer>) {
+    let client = Client::<ArrowFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .with_arrow_options(ArrowOptions::default().with_strings_as_strings(true))
+        .build_arrow()
+        .await
+        .expect("Failed to build client");
+
+    let uuids = ["550e8400-e29b-41d4-a716-446655440000", "6ba7b810-9dad-11d1-80b4-00c04fd430c8"];
+
+    // Keys typed as produced, ie Int64 from a Python dict and UUIDs as strings
+    let mut by_id = MapBuilder::new(None, Int64Builder::new(), StringBuilder::new());
+    let mut by_uuid = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+    let mut by_day = MapBuilder::new(None, Date32Builder::new(), Int32Builder::new());
+    for i in 0..2_i32 {
+        by_id.keys().append_value(i64::from(i));
+        by_id.values().append_value(format!("id{i}"));
+        by_id.keys().append_value(i64::from(i) + 300);
+        by_id.values().append_value(format!("id{}", i + 300));
+        by_id.append(true).unwrap();
+        by_uuid.keys().append_value(uuids[i as usize]);
+        by_uuid.values().append_value(i);
+        by_uuid.append(true).unwrap();
+        by_day.keys().append_value(19_000 + i);
+        by_day.values().append_value(i);
+        by_day.append(true).unwrap();
+    }
+    let batch = RecordBatch::try_from_iter([
+        ("id", Arc::new(UInt32Array::from_iter_values(0..2)) as ArrayRef),
+        ("by_id", Arc::new(by_id.finish())),
+        ("by_uuid", Arc::new(by_uuid.finish())),
+        ("by_day", Arc::new(by_day.finish())),
+    ])
+    .expect("Failed to create RecordBatch");
+
+    let table_name = format!("test_map_key_types_{}", Qid::new());
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table_name} (id UInt32, by_id Map(UInt16, String), by_uuid \
+                 Map(UUID, Int32), by_day Map(Date, Int32)) ENGINE = Memory"
+            ),
+            None,
+        )
+        .await
+        .expect("Failed to create table");
+
+    let query_id = Qid::new();
+    header(query_id, format!("Inserting maps with integer, UUID, and date keys into {table_name}"));
+    let _ = client
+        .insert(&table_name, batch, Some(query_id))
+        .await
+        .expect("Insert failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to insert");
+
+    // The server sees the same keys, including the UUIDs in their canonical form
+    let queried = client
+        .query(
+            format!(
+                "SELECT by_id, by_uuid, by_day, arrayMap(k -> toString(k), mapKeys(by_uuid)), \
+                 arrayMap(k -> toString(k), mapKeys(by_day)) FROM {table_name} ORDER BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to query data")
+        .collect_table()
+        .expect("Failed to concat batches");
+    assert_eq!(queried.num_rows(), 2);
+
+    let by_id = queried.column(0).as_map();
+    assert_eq!(by_id.keys().as_ref(), &UInt16Array::from(vec![0, 300, 1, 301]) as &dyn Array);
+    let by_uuid = queried.column(1).as_map();
+    let uuid_bytes = FixedSizeBinaryArray::try_from_iter(
+        uuids.iter().map(|u| uuid::Uuid::parse_str(u).unwrap().into_bytes()),
+    )
+    .unwrap();
+    assert_eq!(by_uuid.keys().as_ref(), &uuid_bytes as &dyn Array);
+    let by_day = queried.column(2).as_map();
+    assert_eq!(by_day.keys().as_ref(), &Date32Array::from(vec![19_000, 19_001]) as &dyn Array);
+
+    let uuid_strings = queried.column(3).as_list::<i32>();
+    assert_eq!(uuid_strings.values().as_string::<i32>(), &StringArray::from(uuids.to_vec()));
+    let day_strings = queried.column(4).as_list::<i32>();
+    assert_eq!(
+        day_strings.values().as_string::<i32>(),
+        &StringArray::from(vec!["2022-01-08", "2022-01-09"])
+    );
+
+    client
+        .execute(format!("DROP TABLE {table_name}"), None)
+        .await
+        .expect("Failed to drop table");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_lazy_query(ch: Arc<ClickHouseContainer>) {
+    let client = Client::<ArrowFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .with_arrow_options(ArrowOptions::default().with_strings_as_strings(true))
+        .build_lazy_arrow()
+        .await
+        .expect("Failed to build lazy client");
+
+    let query_id = Qid::new();
+    header(query_id, "Querying lazily decoded batches");
+    let query = "SELECT number AS id, toString(number) AS name, toLowCardinality(toString(number \
+                 % 3)) AS category FROM numbers(10000)"
+        .to_string();
+    let batches = client
+        .query_raw(query, None::<QueryParams>, query_id)
+        .await
+        .expect("Query failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to collect batches");
+
+    let mut rows = 0;
+    for batch in &batches {
+        assert_eq!(batch.num_columns(), 3);
+        assert!(!batch.is_decoded(1));
+        let ids = batch.column_by_name("id").expect("Failed to decode id");
+        let ids = ids.as_primitive::<UInt64Type>();
+        assert_eq!(ids.value(0), rows as u64);
+        assert!(!batch.is_decoded(1));
+        rows += batch.num_rows();
+    }
+    assert_eq!(rows, 10000);
+
+    let names = batches[0].to_record_batch().expect("Failed to decode batch");
+    assert_eq!(names.column(1).as_string::<i32>().value(1), "1");
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_statement_hook(ch: Arc<ClickHouseContainer>) {
+    let events = Arc::new(std::sync::Mutex::new(Vec::<StatementEvent>::new()));
+    let collected = Arc::clone(&events);
+    let (client, _) = bootstrap_with_options(
+        ch.as_ref(),
+        None,
+        Some(move |builder: ClientBuilder| {
+            let collected = Arc::clone(&collected);
+            builder.with_statement_hook(move |event: &StatementEvent| {
+                collected.lock().unwrap().push(event.clone());
+            })
+        }),
+    )
+    .await;
+    // Bootstrapping may run statements of its own
+    events.lock().unwrap().clear();
+
+    let query_id = Qid::new();
+    header(query_id, "Running statements with a statement hook");
+    let _ = client
+        .query_with_options(
+            "SELECT number FROM numbers(1000)",
+            QueryOptions::new().with_qid(query_id),
+        )
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+
+    let error_qid = Qid::new();
+    let result = client.execute("SELECT * FROM missing_table_for_hook", Some(error_qid)).await;
+    assert!(result.is_err());
+
+    let events = events.lock().unwrap().clone();
+    let query = events.iter().find(|e| e.qid == query_id).expect("Query event missing");
+    assert!(!query.is_error());
+    assert_eq!(query.fingerprint, query_fingerprint("SELECT number FROM numbers(1)"));
+    assert_eq!(query.query.as_deref(), Some("SELECT number FROM numbers(1000)"));
+    assert_eq!(query.read_rows, 1000);
+    // Profile events are reported before the end of every query
+    assert!(!query.profile_events.is_empty());
+
+    let failed = events.iter().find(|e| e.qid == error_qid).expect("Error event missing");
+    assert!(failed.is_error());
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_generated_round_trip(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    for seed in 0..20 {
+        let query_id = Qid::new();
+        header(query_id, format!("Round tripping generated schema, seed {seed}"));
+        let mut generator = SchemaGenerator::new(seed).with_max_depth(3);
+        let schema = generator.generate_schema().expect("Failed to generate schema");
+        let batch = generator.generate_batch(&schema, 100).expect("Failed to generate batch");
+        assert_round_trip(&client, &schema, &batch).await;
+    }
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_delete(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    let table = format!("{db}.test_delete");
+    header(query_id, "Deleting rows with lightweight deletes and mutations");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+    client
+        .execute(
+            format!("CREATE TABLE {table} (id UInt64, name String) ENGINE = MergeTree ORDER BY id"),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+    client
+        .execute(
+            format!("INSERT INTO {table} SELECT number, toString(number) FROM numbers(100)"),
+            None,
+        )
+        .await
+        .expect("Insert failed");
+
+    let count = async |client: &ArrowClient| {
+        let batches = client
+            .query(format!("SELECT count() FROM {table}"), None)
+            .await
+            .expect("Count failed")
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<ClickHouseResult<Vec<_>>>()
+            .expect("Failed to collect count");
+        batches[0].column(0).as_primitive::<UInt64Type>().value(0)
+    };
+
+    // Lightweight delete, falling back to a mutation on older servers
+    client.delete(&table, "id < 10", true, None).await.expect("Lightweight delete failed");
+    assert_eq!(count(&client).await, 90);
+
+    // Mutation, waiting for completion
+    client
+        .delete_with_wait(&table, "id >= 90", false, MutationWait::Local, None)
+        .await
+        .expect("Mutation delete failed");
+    assert_eq!(count(&client).await, 80);
+
+    // An empty predicate is rejected rather than deleting every row
+    assert!(client.delete(&table, "", true, None).await.is_err());
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_duplicate_column_names(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    header(query_id, "Querying duplicated and expression-derived column names");
+    let batches = client
+        .query("SELECT 1, 1, 1 + 1, number AS n FROM numbers(1)", Some(query_id))
+        .await
+        .expect("Query failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to collect batches");
+
+    // Names are kept exactly as sent by the server
+    let batch = &batches[0];
+    let fields = batch.schema_ref().fields().iter().map(|f| f.name().clone()).collect::<Vec<_>>();
+    assert_eq!(fields, ["1", "1", "plus(1, 1)", "n"]);
+
+    let names = ColumnNames::from_batch(batch);
+    assert!(names.has_duplicates());
+    assert_eq!(names.duplicates().collect::<Vec<_>>(), vec![("1", &[0, 1][..])]);
+    assert_eq!(batch.columns_by_name("1").len(), 2);
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_null_policy(ch: Arc<ClickHouseContainer>) {
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    let table = format!("{db}.test_null_policy");
+    header(query_id, "Inserting nulls into non-nullable columns");
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt64, false),
+        Field::new("value", DataType::Int32, true),
+    ]));
+    let batch = RecordBatch::try_new(Arc::clone(&schema), vec![
+        Arc::new(UInt64Array::from(vec![1, 2, 3])),
+        Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])),
+    ])
+    .unwrap();
+
+    let client_with = async |policy: NullPolicy| {
+        let (client, _) = bootstrap_with_options(
+            ch.as_ref(),
+            None,
+            Some(move |builder: ClientBuilder| {
+                builder.with_arrow_options(
+                    ArrowOptions::default().with_strings_as_strings(true).with_null_policy(policy),
+                )
+            }),
+        )
+        .await;
+        client
+    };
+
+    let insert = async |client: &ArrowClient| -> ClickHouseResult<()> {
+        let query = format!("INSERT INTO {table} FORMAT Native");
+        let _ = client
+            .insert(&query, batch.clone(), None)
+            .await?
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<ClickHouseResult<Vec<_>>>()?;
+        Ok(())
+    };
+
+    let select_values = async |client: &ArrowClient| {
+        let batches = client
+            .query(format!("SELECT value FROM {table} ORDER BY id"), None)
+            .await
+            .expect("Select failed")
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<ClickHouseResult<Vec<_>>>()
+            .expect("Failed to collect values");
+        batches
+            .iter()
+            .flat_map(|b| b.column(0).as_primitive::<Int32Type>().values().to_vec())
+            .collect::<Vec<_>>()
+    };
+
+    let client = client_with(NullPolicy::Error).await;
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table} (id UInt64, value Int32 DEFAULT 7) ENGINE = MergeTree ORDER \
+                 BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+
+    // Rejected client-side, naming the offending row
+    let error = insert(&client).await.expect_err("Expected nulls to be rejected");
+    assert!(error.to_string().contains("at rows [1]"), "{error}");
+    client.shutdown().await.unwrap();
+
+    // Nulls written as the type's default
+    let client = client_with(NullPolicy::Default).await;
+    insert(&client).await.expect("Insert with default policy failed");
+    assert_eq!(select_values(&client).await, vec![1, 0, 3]);
+    client.execute(format!("TRUNCATE TABLE {table}"), None).await.expect("Truncate failed");
+    client.shutdown().await.unwrap();
+
+    // Nulls sent as Nullable are converted to the column's DEFAULT by the server
+    let client = client_with(NullPolicy::Nullable).await;
+    insert(&client).await.expect("Insert with nullable policy failed");
+    assert_eq!(select_values(&client).await, vec![1, 7, 3]);
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_insert_select(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    let table = format!("{db}.test_insert_select");
+    header(query_id, "Running a server-side INSERT ... SELECT");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+    client
+        .execute(format!("CREATE TABLE {table} (id UInt64) ENGINE = MergeTree ORDER BY id"), None)
+        .await
+        .expect("Create table failed");
+
+    let mut response = client
+        .insert_select_with_timeout(
+            format!("INSERT INTO {table} SELECT number FROM numbers(100000)"),
+            Some(std::time::Duration::from_secs(60)),
+            None,
+        )
+        .await
+        .expect("Insert select failed");
+    let mut updates = 0;
+    while response.next().await.is_some() {
+        updates += 1;
+    }
+    debug!(updates, "Received progress updates");
+    let summary = response.summary().await.expect("Insert select summary failed");
+    assert_eq!(summary.written_rows, 100_000);
+
+    // Data-bearing inserts are rejected
+    let result = client.insert_select(format!("INSERT INTO {table} VALUES (1)"), None).await;
+    assert!(result.is_err());
+
+    // Server errors surface from the summary
+    let response = client
+        .insert_select(format!("INSERT INTO {table} SELECT throwIf(1) FROM numbers(1)"), None)
+        .await
+        .expect("Insert select failed");
+    assert!(response.summary().await.is_err());
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_staged_insert(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Staging batches and committing them atomically");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let count = async |client: &ArrowClient, table: &str| {
+        let batches = client
+            .query(format!("SELECT count() FROM {table}"), None)
+            .await
+            .expect("Count failed")
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<ClickHouseResult<Vec<_>>>()
+            .expect("Failed to collect count");
+        batches[0].column(0).as_primitive::<UInt64Type>().value(0)
+    };
+    let batch = |ids: Vec<u64>| {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(UInt64Array::from(ids))]).unwrap()
+    };
+
+    for engine in ["MergeTree PARTITION BY id % 3 ORDER BY id", "Memory"] {
+        let table = format!("{db}.staged_{}", engine.split_whitespace().next().unwrap());
+        client
+            .execute(format!("CREATE TABLE {table} (id UInt64) ENGINE = {engine}"), None)
+            .await
+            .expect("Create table failed");
+
+        // Staged rows are invisible until committed
+        let mut stager = client.staged_insert(&table, None).await.expect("Stage failed");
+        stager.insert(batch(vec![1, 2, 3])).await.expect("Staged insert failed");
+        stager.insert(batch(vec![4, 5])).await.expect("Staged insert failed");
+        assert_eq!(stager.rows(), 5);
+        assert_eq!(count(&client, &table).await, 0);
+        assert_eq!(stager.commit().await.expect("Commit failed"), 5);
+        assert_eq!(count(&client, &table).await, 5);
+
+        // Rolled back rows never reach the target
+        let mut stager = client.staged_insert(&table, None).await.expect("Stage failed");
+        stager.insert(batch(vec![6])).await.expect("Staged insert failed");
+        let staging = stager.staging_table();
+        stager.rollback().await.expect("Rollback failed");
+        assert_eq!(count(&client, &table).await, 5);
+        assert!(client.execute(format!("SELECT * FROM {staging}"), None).await.is_err());
+    }
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_insert_table_defaults(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Inserting around column defaults");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let table = format!("{db}.defaults");
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table} (id UInt64, label String DEFAULT 'none', doubled UInt64 \
+                 MATERIALIZED id * 2, id_str String ALIAS toString(id)) ENGINE = MergeTree ORDER \
+                 BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+
+    let defaults = client.fetch_column_defaults(&table, None).await.expect("Fetch failed");
+    assert_eq!(defaults.columns().len(), 3);
+    assert_eq!(defaults.get("label").unwrap().kind, DefaultKind::Default);
+    assert_eq!(defaults.generated().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec![
+        "doubled", "id_str"
+    ]);
+
+    // A batch shaped like the full table, minus the DEFAULT column
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt64, false),
+        Field::new("doubled", DataType::UInt64, false),
+        Field::new("id_str", DataType::Utf8, false),
+    ]));
+    let batch = RecordBatch::try_new(schema, vec![
+        Arc::new(UInt64Array::from(vec![1, 2])),
+        Arc::new(UInt64Array::from(vec![0, 0])),
+        Arc::new(StringArray::from(vec!["x", "y"])),
+    ])
+    .unwrap();
+
+    let error = client
+        .insert_table(&table, batch.clone(), GeneratedColumnPolicy::Error, None)
+        .await
+        .map(|_| ())
+        .expect_err("Generated columns should be rejected");
+    assert!(error.to_string().contains("doubled, id_str"));
+
+    client
+        .insert_table(&table, batch, GeneratedColumnPolicy::Omit, None)
+        .await
+        .expect("Insert failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Insert failed");
+
+    let batches = client
+        .query(format!("SELECT label, doubled FROM {table} ORDER BY id"), None)
+        .await
+        .expect("Query failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to collect rows");
+    let labels = arrow::compute::cast(batches[0].column(0), &DataType::Utf8).unwrap();
+    assert_eq!(labels.as_string::<i32>().value(0), "none");
+    assert_eq!(batches[0].column(1).as_primitive::<UInt64Type>().values(), &[2, 4]);
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_insert_sink_drift(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Detecting schema drift under an insert sink");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let table = format!("{db}.drift");
+    client
+        .execute(
+            format!("CREATE TABLE {table} (id UInt64, note String) ENGINE = MergeTree ORDER BY id"),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt64, false),
+        Field::new("note", DataType::Utf8, false),
+    ]));
+    let batch = |ids: Vec<u64>| {
+        let notes =
+            StringArray::from(ids.iter().map(|id| format!("note {id}")).collect::<Vec<_>>());
+        RecordBatch::try_new(Arc::clone(&schema), vec![
+            Arc::new(UInt64Array::from(ids)),
+            Arc::new(notes),
+        ])
+        .unwrap()
+    };
+
+    let check_always = InsertSinkOptions::default().with_check_interval(std::time::Duration::ZERO);
+    let mut failing =
+        client.insert_sink(&table, check_always.clone()).await.expect("Create sink failed");
+    let mut reconciling = client
+        .insert_sink(&table, check_always.with_drift_action(DriftAction::Reconcile))
+        .await
+        .expect("Create sink failed");
+    failing.insert(batch(vec![1])).await.expect("Insert failed");
+
+    client
+        .execute(format!("ALTER TABLE {table} DROP COLUMN note"), None)
+        .await
+        .expect("Alter table failed");
+
+    let error = failing.insert(batch(vec![2])).await.expect_err("Drift should fail the insert");
+    assert!(matches!(&error, Error::SchemaDrift(drift) if drift.removed() == ["note"]));
+
+    reconciling.insert(batch(vec![3, 4])).await.expect("Reconciled insert failed");
+    assert_eq!(reconciling.rows(), 2);
+    assert_eq!(reconciling.schema().fields().len(), 1);
+
+    let batches = client
+        .query(format!("SELECT count() FROM {table}"), None)
+        .await
+        .expect("Count failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to collect count");
+    assert_eq!(batches[0].column(0).as_primitive::<UInt64Type>().value(0), 3);
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_spool_replay(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Replaying spooled batches");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let table = format!("{db}.spooled");
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table} (id UInt64) ENGINE = MergeTree ORDER BY id SETTINGS \
+                 non_replicated_deduplication_window = 100"
+            ),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+
+    let batch = |ids: Vec<u64>| {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(UInt64Array::from(ids))]).unwrap()
+    };
+
+    // Batches spooled before a "crash" are replayed by the next process
+    let dir = std::env::temp_dir().join(format!("spool_{query_id}"));
+    let spool = Spool::open(&dir).await.expect("Open spool failed");
+    spool.append(&table, batch(vec![1, 2, 3])).await.expect("Append failed");
+    spool.append(&table, batch(vec![4])).await.expect("Append failed");
+    drop(spool);
+
+    let spool = Arc::new(Spool::open(&dir).await.expect("Reopen spool failed"));
+    assert_eq!(spool.pending().await.unwrap(), 2);
+    let options = InsertSinkOptions::default().with_spool(Arc::clone(&spool));
+    let mut sink = client.insert_sink(&table, options).await.expect("Create sink failed");
+    assert_eq!(sink.rows(), 4);
+    sink.insert(batch(vec![5, 6])).await.expect("Spooled insert failed");
+    assert_eq!(sink.rows(), 6);
+    assert_eq!(spool.pending().await.unwrap(), 0);
+
+    let batches = client
+        .query(format!("SELECT count() FROM {table}"), None)
+        .await
+        .expect("Count failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to collect count");
+    assert_eq!(batches[0].column(0).as_primitive::<UInt64Type>().value(0), 6);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+#[cfg(feature = "pool")]
+pub async fn test_client_pool(ch: Arc<ClickHouseContainer>) {
+    let pool = Client::<ArrowFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .build_pool::<ArrowFormat>(4)
+        .await
+        .expect("Building pool");
+    assert_eq!(pool.state().connections, 4);
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Running concurrent inserts and queries through a client pool");
+    pool.execute(format!("CREATE DATABASE {db}"), None).await.expect("Create database failed");
+    let table = format!("{db}.pooled");
+    pool.execute(format!("CREATE TABLE {table} (id UInt64) ENGINE = MergeTree ORDER BY id"), None)
+        .await
+        .expect("Create table failed");
+
+    // Each insert checks out its own connection
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+    let inserts = (0..8_u64).map(|i| {
+        let ids = UInt64Array::from((i * 10..i * 10 + 10).collect::<Vec<_>>());
+        let batch = RecordBatch::try_new(Arc::clone(&schema), vec![Arc::new(ids)]).unwrap();
+        pool.insert(format!("INSERT INTO {table} FORMAT Native"), batch, None)
+    });
+    for result in futures_util::future::join_all(inserts).await {
+        result.expect("Pooled insert failed");
+    }
+
+    // Queries hold their connection until the response is exhausted
+    let response = pool.query(format!("SELECT count() FROM {table}"), None).await.unwrap();
+    assert_eq!(pool.state().idle_connections, 3);
+    let batches = response.collect_result().await.expect("Failed to collect count");
+    assert_eq!(batches.batches()[0].column(0).as_primitive::<UInt64Type>().value(0), 80);
+    assert_eq!(pool.state().idle_connections, 4);
+
+    pool.execute(format!("DROP DATABASE {db}"), None).await.expect("Drop database failed");
+}
+
+/// # Panics
+pub async fn test_reconnect(ch: Arc<ClickHouseContainer>) {
+    use std::time::Duration;
+
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinSet;
+
+    // Proxy connections to the server, so they can be dropped mid-session
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("Binding proxy");
+    let proxy_addr = listener.local_addr().unwrap();
+    let upstream = ch.get_native_url().to_string();
+    let sessions = Arc::new(parking_lot::Mutex::new(JoinSet::new()));
+    let accepted = Arc::clone(&sessions);
+    let proxy = tokio::spawn(async move {
+        while let Ok((mut inbound, _)) = listener.accept().await {
+            let mut outbound = TcpStream::connect(&upstream).await.expect("Connecting upstream");
+            drop(accepted.lock().spawn(async move {
+                let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+            }));
+        }
+    });
+
+    // Record connection churn through the connection hooks
+    let connects = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let disconnects = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let attempts = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let (connected, disconnected, attempted) =
+        (Arc::clone(&connects), Arc::clone(&disconnects), Arc::clone(&attempts));
+
+    let policy =
+        ReconnectPolicy::new(3).with_backoff(Duration::from_millis(10), Duration::from_millis(50));
+    let client = Client::<ArrowFormat>::builder()
+        .with_endpoint(proxy_addr.to_string())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_settings(vec![("max_block_size", 1234_i64)])
+        .with_reconnect(policy)
+        .on_connect(move |event: &ConnectEvent| {
+            assert_eq!(event.endpoint, proxy_addr);
+            let _ = connected.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        })
+        .on_disconnect(move |event: &DisconnectEvent| {
+            disconnected.lock().push(event.reason.clone());
+        })
+        .on_reconnect_attempt(move |event: &ReconnectAttemptEvent| {
+            attempted.lock().push(event.clone());
+        })
+        .build()
+        .await
+        .expect("Building client");
+    let connections = connects.load(std::sync::atomic::Ordering::Relaxed);
+
+    let query_id = Qid::new();
+    header(query_id, "Reconnecting after the connection dropped");
+    let query = "SELECT toUInt64(getSetting('max_block_size')) AS block";
+    let block_size =
+        |result: QueryResult| result.batches()[0].column(0).as_primitive::<UInt64Type>().value(0);
+    let result = client.query(query, Some(query_id)).await.unwrap().collect_result().await;
+    assert_eq!(block_size(result.expect("Query failed")), 1234);
+
+    // Drop the connections. Queries fail until reaching a connection found broken before, which
+    // is re-established with the session settings still applying
+    sessions.lock().abort_all();
+    let mut reconnected = None;
+    for _ in 0..=connections * 2 {
+        let Ok(response) = client.query(query, None).await else { continue };
+        if let Ok(result) = response.collect_result().await {
+            reconnected = Some(result);
+            break;
+        }
+    }
+    assert_eq!(block_size(reconnected.expect("Query after reconnect failed")), 1234);
+
+    // Each dropped connection used again was re-established on the first attempt
+    let reconnects = connects.load(std::sync::atomic::Ordering::Relaxed) - connections;
+    assert!(reconnects > 0);
+    assert_eq!(attempts.lock().len(), reconnects);
+    assert!(attempts.lock().iter().all(|event| event.attempt == 1 && event.is_success()));
+    assert!(disconnects.lock().iter().all(|r| matches!(r, DisconnectReason::Error(_))));
+
+    client.shutdown().await.unwrap();
+    assert!(disconnects.lock().contains(&DisconnectReason::Shutdown));
+    proxy.abort();
+}
+
+/// # Panics
+pub async fn test_endpoint_failover(ch: Arc<ClickHouseContainer>) {
+    // A port nothing listens on once the listener is dropped
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Binding listener");
+    let down = listener.local_addr().unwrap().to_string();
+    drop(listener);
+    let up = ch.get_native_url().to_string();
+
+    for strategy in [EndpointStrategy::FirstAlive, EndpointStrategy::RoundRobin] {
+        let query_id = Qid::new();
+        header(query_id, format!("Failing over to the next endpoint with {strategy:?}"));
+        let client = Client::<ArrowFormat>::builder()
+            .with_endpoints(&[down.as_str(), up.as_str(), "invalid.invalid:9000"], strategy)
+            .with_username(&ch.user)
+            .with_password(&ch.password)
+            .build()
+            .await
+            .expect("Building client");
+        let batches = client
+            .query("SELECT 1 AS one", Some(query_id))
+            .await
+            .expect("Query failed")
+            .collect_result()
+            .await
+            .expect("Failed to collect results");
+        assert_eq!(batches.batches()[0].num_rows(), 1);
+        client.shutdown().await.unwrap();
+    }
+
+    // Connecting fails once every endpoint failed
+    let result = Client::<ArrowFormat>::builder()
+        .with_endpoints(&[down.as_str()], EndpointStrategy::Random)
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .build()
+        .await;
+    assert!(result.is_err());
+}
+
+/// # Panics
+pub async fn test_readonly(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+    let readonly = Client::<ArrowFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_readonly(true)
+        .build()
+        .await
+        .expect("Building client");
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Refusing writes from a read-only client");
+    client.execute(format!("CREATE DATABASE {db}"), None).await.expect("Create database failed");
+    let table = format!("{db}.readonly");
+    client
+        .execute(format!("CREATE TABLE {table} (id UInt64) ENGINE = MergeTree ORDER BY id"), None)
+        .await
+        .expect("Create table failed");
+
+    // Writes and DDL are refused before reaching the server
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+    let batch =
+        RecordBatch::try_new(schema, vec![Arc::new(UInt64Array::from(vec![1_u64, 2]))]).unwrap();
+    let result = readonly.insert(format!("INSERT INTO {table} FORMAT Native"), batch, None).await;
+    assert!(matches!(result.err(), Some(clickhouse_arrow::Error::ReadOnly { .. })));
+    for statement in [
+        format!("INSERT INTO {table} VALUES (3)"),
+        format!("/* cleanup */ TRUNCATE TABLE {table}"),
+        format!("SELECT 1; DROP TABLE {table}"),
+    ] {
+        let result = readonly.execute(statement, Some(query_id)).await;
+        assert!(matches!(result, Err(clickhouse_arrow::Error::ReadOnly { .. })));
+    }
+
+    // Reads and session statements still run
+    readonly.execute("SET max_threads = 2", None).await.expect("Session statement failed");
+    let batches = readonly
+        .query(format!("SELECT count() FROM {table}"), Some(query_id))
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+    assert_eq!(batches.batches()[0].column(0).as_primitive::<UInt64Type>().value(0), 0);
+
+    readonly.shutdown().await.unwrap();
+    client.execute(format!("DROP DATABASE {db}"), None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_query_queue(ch: Arc<ClickHouseContainer>) {
+    let client = Client::<ArrowFormat>::builder()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_max_concurrent_queries(1)
+        .with_query_queue(QueryQueue::new(std::time::Duration::from_millis(100)))
+        .build()
+        .await
+        .expect("Building client");
+
+    let query_id = Qid::new();
+    header(query_id, "Shedding queries queued past their deadline");
+
+    // Hold the only slot with a slow query
+    let slow = tokio::spawn({
+        let client = client.clone();
+        async move { client.execute("SELECT sleep(1)", None).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let stats = client.queue_stats().expect("Queries are capped");
+    assert_eq!((stats.limit, stats.in_flight), (1, 1));
+
+    let result = client.execute("SELECT 1", Some(query_id)).await;
+    assert!(matches!(result, Err(clickhouse_arrow::Error::Overloaded { queued: 0, .. })));
+    assert_eq!(client.queue_stats().unwrap().shed, 1);
+
+    // Queries are admitted again once the slot frees up
+    slow.await.unwrap().expect("Slow query failed");
+    client.execute("SELECT 1", Some(query_id)).await.expect("Query after shedding failed");
+    let stats = client.queue_stats().unwrap();
+    assert_eq!((stats.in_flight, stats.waiting, stats.admitted, stats.shed), (0, 0, 2, 1));
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_query_progress(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+    let query = "SELECT number FROM system.numbers_mt LIMIT 2000000";
+
+    let query_id = Qid::new();
+    header(query_id, "Streaming progress alongside query results");
+    let mut response =
+        client.query_with_progress(query, Some(query_id)).await.expect("Query failed");
+    let (mut progress, mut rows, mut updates) = (Progress::default(), 0, 0);
+    while let Some(update) = response.next().await {
+        match update.expect("Failed to receive update") {
+            QueryUpdate::Progress(delta) => {
+                progress = progress + delta;
+                updates += 1;
+            }
+            QueryUpdate::Data(batch) => rows += batch.num_rows(),
+        }
+    }
+    assert_eq!(rows, 2_000_000);
+    assert!(updates > 0);
+    assert!(progress.read_rows >= 2_000_000);
+
+    // The same updates are available through a callback
+    let read_rows = Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let options = QueryOptions::new().with_progress({
+        let read_rows = Arc::clone(&read_rows);
+        move |progress: &Progress| {
+            let _ = read_rows.fetch_add(progress.read_rows, std::sync::atomic::Ordering::Relaxed);
+        }
+    });
+    let result = client
+        .query_with_options(query, options)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+    assert_eq!(result.row_count(), 2_000_000);
+    assert!(read_rows.load(std::sync::atomic::Ordering::Relaxed) >= 2_000_000);
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_query_options_settings(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    header(query_id, "Querying with per-query settings");
+    let options = QueryOptions::new()
+        .with_qid(query_id)
+        .with_settings(vec![("max_threads", 3_i64)])
+        .with_setting("max_block_size", 1234_i64);
+    let batches = client
+        .query_with_options(
+            "SELECT getSetting('max_threads') AS threads, getSetting('max_block_size') AS block",
+            options,
+        )
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+    let batch = &batches[0];
+    let threads = arrow::compute::cast(batch.column(0), &DataType::UInt64).unwrap();
+    let block = arrow::compute::cast(batch.column(1), &DataType::UInt64).unwrap();
+    assert_eq!(threads.as_primitive::<UInt64Type>().value(0), 3);
+    assert_eq!(block.as_primitive::<UInt64Type>().value(0), 1234);
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_query_result_limits(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    header(query_id, "Stopping a query that exceeds its result limits");
+    let options = QueryOptions::new()
+        .with_qid(query_id)
+        .with_setting("max_block_size", 1000_i64)
+        .with_max_result_rows(2500)
+        .with_partial_results();
+    let result = client
+        .query_with_options("SELECT number FROM system.numbers", options)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await;
+    let Err(clickhouse_arrow::Error::ResultLimitExceeded(exceeded)) = result else {
+        panic!("Expected the result limit to be exceeded, got {result:?}");
+    };
+    assert_eq!(exceeded.reason, TruncationReason::RowLimit);
+    let partial = exceeded.partial.expect("Partial results requested");
+    assert_eq!(partial.iter().map(RecordBatch::num_rows).sum::<usize>(), 2500);
+
+    // The unbounded query was cancelled, so the connection is free for the next query
+    let batches = client
+        .query("SELECT 1", None)
+        .await
+        .expect("Query after cancel failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+    assert_eq!(batches[0].num_rows(), 1);
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_query_assert_sorted_by(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+    let ascending = arrow::compute::SortOptions { descending: false, nulls_first: false };
+    let descending = arrow::compute::SortOptions { descending: true, nulls_first: false };
+
+    let query_id = Qid::new();
+    header(query_id, "Validating ordered results across blocks");
+    let options = QueryOptions::new()
+        .with_qid(query_id)
+        .with_setting("max_block_size", 100_i64)
+        .assert_sorted_by("bucket", ascending)
+        .assert_sorted_by("number", descending);
+    let batches = client
+        .query_with_options(
+            "SELECT number, number % 3 AS bucket FROM numbers(1000) ORDER BY bucket, number DESC",
+            options,
+        )
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Ordered results failed validation");
+    assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), 1000);
+
+    let query_id = Qid::new();
+    header(query_id, "Failing a query with unordered results");
+    let options = QueryOptions::new()
+        .with_qid(query_id)
+        .with_setting("max_block_size", 100_i64)
+        .assert_sorted_by("number", descending);
+    let result = client
+        .query_with_options("SELECT number FROM numbers(1000) ORDER BY number", options)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await;
+    let Err(clickhouse_arrow::Error::UnorderedResult(unordered)) = result else {
+        panic!("Expected unordered results, got {result:?}");
+    };
+    assert_eq!((unordered.row, unordered.batch, unordered.batch_row), (1, 0, 1));
+    assert_eq!(unordered.previous, vec!["0".to_string()]);
+    assert_eq!(unordered.current, vec!["1".to_string()]);
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_batch_metadata(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap_with_options(
+        ch.as_ref(),
+        None,
+        Some(|builder: ClientBuilder| {
+            builder.with_arrow_options(
+                ArrowOptions::default().with_strings_as_strings(true).with_batch_metadata(true),
+            )
+        }),
+    )
+    .await;
+
+    let query_id = Qid::new();
+    header(query_id, "Attaching lineage metadata to query result batches");
+    let options = QueryOptions::new().with_qid(query_id).with_setting("max_block_size", 100_i64);
+    let batches = client
+        .query_with_options("SELECT number FROM system.numbers LIMIT 350", options)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+    assert!(batches.len() > 1);
+
+    for (index, batch) in batches.iter().enumerate() {
+        let lineage = BatchMetadata::from_batch(batch).expect("Batch metadata missing");
+        assert_eq!(lineage.qid, query_id);
+        assert_eq!(lineage.index, index as u64);
+    }
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_insert_masking(ch: Arc<ClickHouseContainer>) {
+    let audits = Arc::new(parking_lot::Mutex::new(Vec::<MaskingAudit>::new()));
+    let recorded = Arc::clone(&audits);
+    let masking = InsertMasking::new()
+        .with_redaction("customers", "email", "<redacted>")
+        .with_nulls("customers", "phone")
+        .with_audit(move |audit| recorded.lock().push(audit.clone()));
+    let (client, _) = bootstrap_with_options(
+        ch.as_ref(),
+        None,
+        Some(move |builder: ClientBuilder| builder.with_insert_masking(masking.clone())),
+    )
+    .await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Masking sensitive columns before insert");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let table = format!("{db}.customers");
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table} (id UInt64, email String, phone Nullable(String)) ENGINE = \
+                 MergeTree ORDER BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt64, false),
+        Field::new("email", DataType::Utf8, false),
+        Field::new("phone", DataType::Utf8, true),
+    ]));
+    let batch = RecordBatch::try_new(schema, vec![
+        Arc::new(UInt64Array::from(vec![1, 2])),
+        Arc::new(StringArray::from(vec!["a@example.com", "b@example.com"])),
+        Arc::new(StringArray::from(vec![Some("555-0100"), None])),
+    ])
+    .unwrap();
+    client
+        .insert(format!("INSERT INTO {table} FORMAT Native"), batch, None)
+        .await
+        .expect("Insert failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Insert failed");
+
+    let batches = client
+        .query(format!("SELECT email, phone FROM {table} ORDER BY id"), None)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+    let email = batches[0].column(0).as_string::<i32>();
+    assert_eq!(email.iter().collect::<Vec<_>>(), vec![Some("<redacted>"); 2]);
+    assert_eq!(batches[0].column(1).null_count(), 2);
+
+    let audits = audits.lock();
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0].table, table);
+    assert_eq!(audits[0].applied.len(), 2);
+    drop(audits);
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_validate_batch(ch: Arc<ClickHouseContainer>) {
+    let audits = Arc::new(parking_lot::Mutex::new(Vec::<MaskingAudit>::new()));
+    let recorded = Arc::clone(&audits);
+    let masking = InsertMasking::new()
+        .with_redaction("accounts", "email", "<redacted>")
+        .with_audit(move |audit| recorded.lock().push(audit.clone()));
+    let (client, _) = bootstrap_with_options(
+        ch.as_ref(),
+        None,
+        Some(move |builder: ClientBuilder| builder.with_insert_masking(masking.clone())),
+    )
+    .await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Dry running inserts with validate_batch");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let table = format!("{db}.accounts");
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table} (id UInt64, email String, score Int32, note Nullable(String)) \
+                 ENGINE = MergeTree ORDER BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+
+    // `id` has the wrong integer type, `score` has nulls, and `extra` is not in the table
+    let batch = RecordBatch::try_from_iter([
+        ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+        ("email", Arc::new(StringArray::from(vec!["a@example.com", "b@example.com"]))),
+        ("score", Arc::new(Int32Array::from(vec![Some(1), None]))),
+        ("extra", Arc::new(Int32Array::from(vec![1, 2]))),
+    ])
+    .unwrap();
+    let report = client.validate_batch(&table, &batch, None).await.expect("Validation failed");
+    debug!("Conversion report:\n{report}");
+    assert!(!report.is_ok());
+    let failed = report.failed().map(|c| c.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(failed, vec!["id", "score", "extra"]);
+    assert_eq!(report.columns[1].actions, vec![ConversionAction::Mask("redact".into())]);
+    assert_eq!(report.table_only, vec!["note".to_string()]);
+
+    // The error lists a fix for each failing column
+    let Err(Error::SchemaMismatch(mismatch)) = report.into_result() else {
+        panic!("Expected a schema mismatch");
+    };
+    let fixes = mismatch.columns.iter().map(|c| c.fix.clone()).collect::<Vec<_>>();
+    assert_eq!(fixes, vec![
+        SuggestedFix::Cast(DataType::UInt64),
+        SuggestedFix::Manual,
+        SuggestedFix::Drop
+    ]);
+
+    // A batch matching the table passes, and nothing is inserted or audited
+    let batch = RecordBatch::try_from_iter([
+        ("id", Arc::new(UInt64Array::from(vec![1, 2])) as ArrayRef),
+        ("email", Arc::new(StringArray::from(vec!["a@example.com", "b@example.com"]))),
+        ("score", Arc::new(Int32Array::from(vec![1, 2]))),
+    ])
+    .unwrap();
+    let report = client.validate_batch(&table, &batch, None).await.expect("Validation failed");
+    assert!(report.is_ok(), "{report}");
+    assert!(audits.lock().is_empty());
+    let rows = client
+        .query(format!("SELECT count() FROM {table}"), None)
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+    assert_eq!(rows[0].column(0).as_primitive::<UInt64Type>().value(0), 0);
+
+    let missing = client.validate_batch(&format!("{db}.missing"), &batch, None).await;
+    assert!(matches!(missing, Err(Error::UndefinedTables { .. })));
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_maintenance_scheduler(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Running background maintenance jobs");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let table = format!("{db}.parts");
+    client
+        .execute(format!("CREATE TABLE {table} (id UInt64) ENGINE = MergeTree ORDER BY id"), None)
+        .await
+        .expect("Create table failed");
+    // One part per insert
+    for i in 0..3 {
+        client
+            .execute(format!("INSERT INTO {table} VALUES ({i})"), None)
+            .await
+            .expect("Insert failed");
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let hour = std::time::Duration::from_secs(3600);
+    let handle = MaintenanceScheduler::new(client.clone())
+        .with_job(
+            MaintenanceJob::new(&table, MaintenanceTask::OptimizeFinal, hour)
+                .with_initial_delay(std::time::Duration::ZERO)
+                .with_jitter(std::time::Duration::from_millis(50)),
+        )
+        .with_job(
+            MaintenanceJob::new(format!("{db}.missing"), MaintenanceTask::Optimize, hour)
+                .with_initial_delay(std::time::Duration::ZERO),
+        )
+        .with_hook(move |run| {
+            let _ = tx.send(run.clone());
+        })
+        .start();
+    assert_eq!(handle.jobs(), 2);
+
+    let mut runs = Vec::new();
+    while runs.len() < 2 {
+        let run = tokio::time::timeout(std::time::Duration::from_secs(30), rx.recv())
+            .await
+            .expect("Maintenance run timed out")
+            .expect("Scheduler stopped");
+        runs.push(run);
+    }
+    runs.sort_by(|a, b| a.table.cmp(&b.table));
+    assert!(matches!(runs[0].outcome, MaintenanceOutcome::Failed(_)));
+    assert_eq!(runs[1].statement, format!("OPTIMIZE TABLE {table} FINAL"));
+    assert_eq!(runs[1].outcome, MaintenanceOutcome::Completed);
+    handle.shutdown().await;
+
+    let parts = client
+        .query(
+            format!(
+                "SELECT count() FROM system.parts WHERE database = '{db}' AND table = 'parts' AND \
+                 active"
+            ),
+            None,
+        )
+        .await
+        .expect("Query failed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results");
+    assert_eq!(parts[0].column(0).as_primitive::<UInt64Type>().value(0), 1);
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_probe(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    header(Qid::new(), "Probing server latency and clock skew");
+    let probe = client.probe(None).await.expect("Probe failed");
+    assert!(!probe.server_timezone.is_empty());
+    assert_eq!(probe.server_version, client.server_version());
+    // The container shares the host clock
+    assert!(!probe.clock_skew_exceeds(std::time::Duration::from_secs(5)));
+
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_upsert(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Upserting into a ReplacingMergeTree table");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let table = format!("{db}.users");
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table} (id UInt64, name String, version UInt64) ENGINE = \
+                 ReplacingMergeTree(version) ORDER BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+
+    let batch = |ids: Vec<u64>, names: Vec<&str>| {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        RecordBatch::try_new(schema, vec![
+            Arc::new(UInt64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ])
+        .unwrap()
+    };
+
+    let options = UpsertOptions::default().with_version(VersionFill::Increment);
+    client
+        .upsert(&table, batch(vec![1, 2], vec!["a", "b"]), options.clone(), None)
+        .await
+        .expect("First upsert failed");
+    client
+        .upsert(&table, batch(vec![2], vec!["c"]), options.with_optimize(), None)
+        .await
+        .expect("Second upsert failed");
+
+    let batches = client
+        .query(format!("SELECT id, name, version FROM {table} FINAL ORDER BY id"), None)
+        .await
+        .expect("Query failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to collect results");
+    let batch = &batches[0];
+    assert_eq!(batch.num_rows(), 2);
+    let names = arrow::compute::cast(batch.column(1), &DataType::Utf8).unwrap();
+    assert_eq!(names.as_string::<i32>().value(1), "c");
+    assert_eq!(batch.column(2).as_primitive::<UInt64Type>().values().to_vec(), vec![1, 2]);
+
+    // Tables that don't deduplicate are rejected
+    client
+        .execute(
+            format!("CREATE TABLE {db}.plain (id UInt64) ENGINE = MergeTree ORDER BY id"),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+    let plain = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)])),
+        vec![Arc::new(UInt64Array::from(vec![1]))],
+    )
+    .unwrap();
+    assert!(
+        client
+            .upsert(&format!("{db}.plain"), plain, UpsertOptions::default(), None)
+            .await
+            .is_err()
+    );
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_collapse_update(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Updating a VersionedCollapsingMergeTree table");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let table = format!("{db}.balances");
+    client
+        .execute(
+            format!(
+                "CREATE TABLE {table} (id UInt64, amount Int64, version UInt32, sign Int8) ENGINE \
+                 = VersionedCollapsingMergeTree(sign, version) ORDER BY id"
+            ),
+            None,
+        )
+        .await
+        .expect("Create table failed");
+
+    let batch = |ids: Vec<u64>, amounts: Vec<i64>, versions: Option<Vec<u32>>| {
+        let mut fields = vec![
+            Field::new("id", DataType::UInt64, false),
+            Field::new("amount", DataType::Int64, false),
+        ];
+        let mut columns: Vec<ArrayRef> =
+            vec![Arc::new(UInt64Array::from(ids)), Arc::new(Int64Array::from(amounts))];
+        if let Some(versions) = versions {
+            fields.push(Field::new("version", DataType::UInt32, false));
+            columns.push(Arc::new(UInt32Array::from(versions)));
+        }
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+    };
+
+    let stored = batch(vec![1, 2], vec![100, 200], Some(vec![1, 1]));
+    client
+        .upsert(&table, stored.clone(), UpsertOptions::default(), None)
+        .await
+        .expect("Initial insert failed");
+    client
+        .collapse_update(&table, &stored, &batch(vec![1, 2], vec![150, 250], None), None)
+        .await
+        .expect("Collapse update failed");
+
+    let batches = client
+        .query(format!("SELECT id, amount, version FROM {table} FINAL ORDER BY id"), None)
+        .await
+        .expect("Query failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to collect results");
+    let batch = &batches[0];
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.column(1).as_primitive::<Int64Type>().values().to_vec(), vec![150, 250]);
+    assert_eq!(batch.column(2).as_primitive::<UInt32Type>().values().to_vec(), vec![2, 2]);
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
+
+/// # Panics
+pub async fn test_insert_coalescing(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap_with_options(
+        ch.as_ref(),
+        None,
+        Some(|builder: ClientBuilder| builder.with_insert_coalescing(InsertCoalescing::new(256))),
+    )
+    .await;
+
+    let query_id = Qid::new();
+    let db = format!("test_db_{query_id}");
+    header(query_id, "Inserting many tiny batches with insert coalescing");
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+    let table = format!("{db}.events");
+    client
+        .execute(format!("CREATE TABLE {table} (id UInt64) ENGINE = MergeTree ORDER BY id"), None)
+        .await
+        .expect("Create table failed");
+
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::UInt64, false)]));
+    let batches = (0..100_u64)
+        .map(|i| {
+            let ids = UInt64Array::from_iter_values(i * 10..(i + 1) * 10);
+            RecordBatch::try_new(Arc::clone(&schema), vec![Arc::new(ids)]).unwrap()
+        })
+        .collect::<Vec<_>>();
+    let _ = client
+        .insert_many(format!("INSERT INTO {table} FORMAT Native"), batches, Some(query_id))
+        .await
+        .expect("Insert failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to insert batches");
+
+    let batch = client
+        .query_one(format!("SELECT count(), sum(id) FROM {table}"), None)
+        .await
+        .expect("Query failed")
+        .expect("Expected a row");
+    assert_eq!(batch.column(0).as_primitive::<UInt64Type>().value(0), 1000);
+    assert_eq!(batch.column(1).as_primitive::<UInt64Type>().value(0), 999 * 1000 / 2);
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+}
@@ -0,0 +1,105 @@
+//! Tests exercising `Distributed`/`Replicated` behavior against a multi-node cluster.
+
+// Test utilities intentionally panic on failure
+#![allow(clippy::missing_panics_doc)]
+
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::datatypes::*;
+use clickhouse_arrow::prelude::*;
+use clickhouse_arrow::test_utils::{CLUSTER_NAME, ClickHouseCluster};
+use clickhouse_arrow::{CompressionMethod, Result as ClickHouseResult};
+use futures_util::StreamExt;
+
+use super::arrow::bootstrap;
+use crate::common::header;
+
+/// Run a `count()` query and return the single value.
+async fn count(client: &ArrowClient, query: &str) -> u64 {
+    let batches = client
+        .query(query, None)
+        .await
+        .expect("Count query failed")
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<ClickHouseResult<Vec<_>>>()
+        .expect("Failed to collect count batches");
+    batches[0].column(0).as_primitive::<UInt64Type>().value(0)
+}
+
+/// Test `ON CLUSTER` DDL, quorum inserts into `ReplicatedMergeTree`, and `Distributed` reads.
+pub async fn test_on_cluster_replication(cluster: Arc<ClickHouseCluster>) {
+    let (s1r1, _) = bootstrap(cluster.node(1, 1), Some(CompressionMethod::LZ4)).await;
+    let (s1r2, _) = bootstrap(cluster.node(1, 2), Some(CompressionMethod::LZ4)).await;
+    let (s2r1, _) = bootstrap(cluster.node(2, 1), Some(CompressionMethod::LZ4)).await;
+
+    let qid = Qid::new();
+    header(qid, "Creating replicated and distributed tables ON CLUSTER");
+    let db = "cluster_test";
+    s1r1.execute(
+        format!("CREATE DATABASE IF NOT EXISTS {db} ON CLUSTER {CLUSTER_NAME}"),
+        Some(qid),
+    )
+    .await
+    .expect("Create database failed");
+    s1r1.execute(
+        format!(
+            "CREATE TABLE {db}.events_local ON CLUSTER {CLUSTER_NAME} (id UInt64, name String) \
+             ENGINE = ReplicatedMergeTree('/clickhouse/tables/{{shard}}/{db}/events_local', \
+             '{{replica}}') ORDER BY id"
+        ),
+        None,
+    )
+    .await
+    .expect("Create replicated table failed");
+    s1r1.execute(
+        format!(
+            "CREATE TABLE {db}.events ON CLUSTER {CLUSTER_NAME} AS {db}.events_local ENGINE = \
+             Distributed({CLUSTER_NAME}, {db}, events_local, id)"
+        ),
+        None,
+    )
+    .await
+    .expect("Create distributed table failed");
+
+    // The DDL must be visible on every node, not only the initiator
+    for node in &cluster.nodes {
+        let (client, _) = bootstrap(node, Some(CompressionMethod::LZ4)).await;
+        assert_eq!(
+            count(&client, &format!("SELECT count() FROM system.tables WHERE database = '{db}'"))
+                .await,
+            2
+        );
+    }
+
+    header(qid, "Inserting with insert_quorum");
+    s1r1.execute(
+        format!(
+            "INSERT INTO {db}.events_local SETTINGS insert_quorum = 2 VALUES (1, 'a'), (2, 'b')"
+        ),
+        None,
+    )
+    .await
+    .expect("Quorum insert into shard 1 failed");
+    s2r1.execute(
+        format!(
+            "INSERT INTO {db}.events_local SETTINGS insert_quorum = 2 VALUES (3, 'c'), (4, 'd'), \
+             (5, 'e')"
+        ),
+        None,
+    )
+    .await
+    .expect("Quorum insert into shard 2 failed");
+
+    // A quorum insert is acknowledged only once the replica has the data
+    assert_eq!(count(&s1r2, &format!("SELECT count() FROM {db}.events_local")).await, 2);
+
+    // The distributed table fans out to one replica per shard
+    assert_eq!(count(&s1r2, &format!("SELECT count() FROM {db}.events")).await, 5);
+
+    s1r1.execute(format!("DROP DATABASE IF EXISTS {db} ON CLUSTER {CLUSTER_NAME} SYNC"), None)
+        .await
+        .expect("Drop database failed");
+}
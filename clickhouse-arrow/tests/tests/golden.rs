@@ -0,0 +1,273 @@
+//! Golden file tests of `ClickHouse` to Arrow type mappings.
+//!
+//! For every case in [`CASES`], a table with a single column of the case's type is filled with
+//! canonical values, typically the minimum, maximum, a `NULL` and any special values, then:
+//! - The Arrow schema and values returned for the column are rendered and compared against
+//!   `tests/golden/types/<case>.txt`, catching silent mapping changes across arrow-rs and server
+//!   upgrades.
+//! - The returned batch is inserted into a second table of the same type and read back, which must
+//!   return the same batch.
+//!
+//! Goldens that don't exist yet are written and the case passes. Set `UPDATE_GOLDENS=1` to
+//! rewrite all goldens after an intended mapping change, and review the diff before committing.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::record_batch::RecordBatch;
+use arrow::util::pretty::pretty_format_batches;
+use clickhouse_arrow::prelude::*;
+use clickhouse_arrow::test_utils::ClickHouseContainer;
+use futures_util::StreamExt;
+
+use super::arrow::bootstrap;
+use crate::common::constants::UPDATE_GOLDENS_ENV;
+use crate::common::header;
+
+/// A golden case: name, `ClickHouse` type, and SQL literals of the values to insert.
+type Case = (&'static str, &'static str, &'static [&'static str]);
+
+/// Cases covering every supported type, alone and in nested combinations.
+const CASES: &[Case] = &[
+    // Integers
+    ("int8", "Int8", &["-128", "127", "0", "NULL"]),
+    ("int16", "Int16", &["-32768", "32767", "0", "NULL"]),
+    ("int32", "Int32", &["-2147483648", "2147483647", "0", "NULL"]),
+    ("int64", "Int64", &["-9223372036854775808", "9223372036854775807", "0", "NULL"]),
+    ("int128", "Int128", &[
+        "-170141183460469231731687303715884105728",
+        "170141183460469231731687303715884105727",
+        "0",
+    ]),
+    ("int256", "Int256", &[
+        "-57896044618658097711785492504343953926634992332820282019728792003956564819968",
+        "57896044618658097711785492504343953926634992332820282019728792003956564819967",
+        "0",
+    ]),
+    ("uint8", "UInt8", &["0", "255", "NULL"]),
+    ("uint16", "UInt16", &["0", "65535", "NULL"]),
+    ("uint32", "UInt32", &["0", "4294967295", "NULL"]),
+    ("uint64", "UInt64", &["0", "18446744073709551615", "NULL"]),
+    ("uint128", "UInt128", &["0", "340282366920938463463374607431768211455"]),
+    ("uint256", "UInt256", &[
+        "0",
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+    ]),
+    // Floats
+    ("float32", "Float32", &[
+        "-3.4028235e38",
+        "3.4028235e38",
+        "1.1754944e-38",
+        "nan",
+        "inf",
+        "-inf",
+    ]),
+    ("float64", "Float64", &[
+        "-1.7976931348623157e308",
+        "1.7976931348623157e308",
+        "nan",
+        "-inf",
+    ]),
+    ("bfloat16", "BFloat16", &["-1.5", "0", "3.0e38"]),
+    // Decimals
+    ("decimal32", "Decimal(9, 2)", &["-9999999.99", "9999999.99", "0.01"]),
+    ("decimal64", "Decimal(18, 6)", &["-999999999999.999999", "999999999999.999999", "0"]),
+    ("decimal128", "Decimal(38, 10)", &[
+        "-9999999999999999999999999999.9999999999",
+        "9999999999999999999999999999.9999999999",
+    ]),
+    ("decimal256", "Decimal(76, 20)", &[
+        "-1.5",
+        "12345678901234567890123456789.01234567890123456789",
+    ]),
+    // Other scalars
+    ("bool", "Bool", &["false", "true", "NULL"]),
+    ("string", "String", &["''", "'hello'", "'unicode \u{1f600} \\n\\t'", "NULL"]),
+    ("fixed_string", "FixedString(4)", &["''", "'abcd'", "'ab'"]),
+    ("uuid", "UUID", &[
+        "'00000000-0000-0000-0000-000000000000'",
+        "'61f0c404-5cb3-11e7-907b-a6006ad3dba0'",
+    ]),
+    ("ipv4", "IPv4", &["'0.0.0.0'", "'255.255.255.255'", "'192.168.0.1'"]),
+    ("ipv6", "IPv6", &[
+        "'::'",
+        "'ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff'",
+        "'::ffff:192.168.0.1'",
+    ]),
+    ("enum8", "Enum8('a' = -128, 'b' = 0, 'c' = 127)", &["'a'", "'b'", "'c'"]),
+    ("enum16", "Enum16('low' = -32768, 'high' = 32767)", &["'low'", "'high'"]),
+    // Dates and times
+    ("date", "Date", &["'1970-01-01'", "'2149-06-06'", "'2024-02-29'"]),
+    ("date32", "Date32", &["'1900-01-01'", "'2299-12-31'", "'1969-12-31'"]),
+    ("datetime", "DateTime('UTC')", &["'1970-01-01 00:00:00'", "'2106-02-07 06:28:15'"]),
+    ("datetime_tz", "DateTime('Asia/Kolkata')", &["'2024-01-01 00:00:00'"]),
+    ("datetime64_3", "DateTime64(3, 'UTC')", &[
+        "'1900-01-01 00:00:00.000'",
+        "'2299-12-31 23:59:59.999'",
+    ]),
+    ("datetime64_9", "DateTime64(9, 'UTC')", &[
+        "'1900-01-01 00:00:00'",
+        "'2262-04-11 23:47:16.854775807'",
+    ]),
+    // Wrappers
+    ("nullable_int32", "Nullable(Int32)", &["-1", "NULL", "2147483647"]),
+    ("nullable_string", "Nullable(String)", &["'a'", "NULL", "''"]),
+    ("nullable_decimal", "Nullable(Decimal(18, 4))", &["NULL", "-0.0001"]),
+    ("nullable_datetime64", "Nullable(DateTime64(6, 'UTC'))", &[
+        "NULL",
+        "'2024-01-01 12:00:00.123456'",
+    ]),
+    ("low_cardinality_string", "LowCardinality(String)", &["'a'", "'b'", "'a'", "''"]),
+    ("low_cardinality_nullable", "LowCardinality(Nullable(String))", &["'a'", "NULL", "'a'"]),
+    // Arrays, maps and tuples
+    ("array_int32", "Array(Int32)", &["[]", "[-2147483648, 0, 2147483647]"]),
+    ("array_nullable_string", "Array(Nullable(String))", &["[]", "['a', NULL, '']"]),
+    ("array_array_uint8", "Array(Array(UInt8))", &["[]", "[[], [0, 255]]", "[[1]]"]),
+    ("array_low_cardinality", "Array(LowCardinality(String))", &["['x', 'y', 'x']", "[]"]),
+    ("map_string_uint64", "Map(String, UInt64)", &[
+        "map()",
+        "map('a', 0, 'b', 18446744073709551615)",
+    ]),
+    ("map_nested", "Map(String, Array(Nullable(Int32)))", &["map('k', [1, NULL])", "map()"]),
+    ("map_low_cardinality", "Map(LowCardinality(String), Nullable(Float64))", &[
+        "map('a', NULL, 'b', 1.5)",
+    ]),
+    ("tuple", "Tuple(Int32, String)", &["(0, '')", "(-1, 'a')"]),
+    ("named_tuple", "Tuple(a Int32, b Nullable(String))", &["(1, NULL)", "(2, 'b')"]),
+    ("array_tuple_map", "Array(Tuple(String, Map(String, Int8)))", &[
+        "[]",
+        "[('a', map('x', -128)), ('b', map())]",
+    ]),
+    (
+        "tuple_nested",
+        "Tuple(a Array(UInt16), b Tuple(c Nullable(Date), d LowCardinality(String)))",
+        &["([], (NULL, ''))", "([1, 65535], ('2024-01-01', 'd'))"],
+    ),
+    // Geo
+    ("point", "Point", &["(0, 0)", "(-1.5, 2.5)"]),
+    ("ring", "Ring", &["[]", "[(0, 0), (1, 0), (1, 1)]"]),
+    ("polygon", "Polygon", &["[[(0, 0), (1, 0), (1, 1)], [(0.25, 0.25)]]"]),
+    ("multi_polygon", "MultiPolygon", &["[[[(0, 0), (1, 0), (1, 1)]]]", "[]"]),
+];
+
+/// # Panics
+pub async fn test_type_goldens(ch: Arc<ClickHouseContainer>) {
+    let (client, _) = bootstrap(ch.as_ref(), None).await;
+
+    let query_id = Qid::new();
+    let db = format!("test_golden_{query_id}");
+    header(query_id, format!("Running {} type golden cases", CASES.len()));
+    client.create_database(Some(&db), None).await.expect("Create database failed");
+
+    let update = std::env::var(UPDATE_GOLDENS_ENV)
+        .ok()
+        .is_some_and(|e| e.eq_ignore_ascii_case("true") || e == "1");
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/types");
+    std::fs::create_dir_all(&dir).expect("Create golden directory");
+
+    let mut failures = Vec::new();
+    for (name, type_, values) in CASES {
+        let batch = round_trip(&client, &db, name, type_, values).await;
+        let rendered = render(type_, &batch);
+        let path = dir.join(format!("{name}.txt"));
+        match std::fs::read_to_string(&path) {
+            Ok(golden) if !update => {
+                if golden != rendered {
+                    failures.push(format!(
+                        "{name}: output differs from {}\n--- golden\n{golden}\n+++ \
+                         actual\n{rendered}",
+                        path.display()
+                    ));
+                }
+            }
+            _ => std::fs::write(&path, rendered).expect("Write golden"),
+        }
+    }
+
+    client.drop_database(&db, true, None).await.expect("Drop database failed");
+    client.shutdown().await.unwrap();
+
+    assert!(
+        failures.is_empty(),
+        "{} golden mismatches, rerun with {UPDATE_GOLDENS_ENV}=1 if intended:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+/// Insert the case's values, read them as Arrow, and check the batch survives a round trip.
+async fn round_trip(
+    client: &ArrowClient,
+    db: &str,
+    name: &str,
+    type_: &str,
+    values: &[&str],
+) -> RecordBatch {
+    let create = |table: &str| {
+        format!("CREATE TABLE {db}.{table} (id UInt32, v {type_}) ENGINE = MergeTree ORDER BY id")
+    };
+    client.execute(create(name), None).await.unwrap_or_else(|e| panic!("{name}: create: {e}"));
+    let rows = values
+        .iter()
+        .enumerate()
+        .map(|(id, value)| format!("({id}, {value})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    client
+        .execute(format!("INSERT INTO {db}.{name} VALUES {rows}"), None)
+        .await
+        .unwrap_or_else(|e| panic!("{name}: insert values: {e}"));
+    let batch = select(client, &format!("{db}.{name}")).await;
+
+    // Insert what was read into an identical table, reading it back must not change it
+    let copy = format!("{name}_copy");
+    client
+        .execute(create(&copy), None)
+        .await
+        .unwrap_or_else(|e| panic!("{name}: create: {e}"));
+    let stream = client
+        .insert(format!("INSERT INTO {db}.{copy} FORMAT Native"), batch.clone(), None)
+        .await
+        .unwrap_or_else(|e| panic!("{name}: insert batch: {e}"));
+    tokio::pin!(stream);
+    while let Some(result) = stream.next().await {
+        result.unwrap_or_else(|e| panic!("{name}: insert batch: {e}"));
+    }
+    let copied = select(client, &format!("{db}.{copy}")).await;
+    assert_eq!(batch, copied, "{name}: batch changed in a round trip");
+
+    batch
+}
+
+async fn select(client: &ArrowClient, table: &str) -> RecordBatch {
+    client
+        .query(format!("SELECT id, v FROM {table} ORDER BY id"), None)
+        .await
+        .unwrap_or_else(|e| panic!("{table}: query: {e}"))
+        .collect_result()
+        .await
+        .and_then(|batches| {
+            let schema = batches.first().expect("Every case has rows").schema();
+            Ok(arrow::compute::concat_batches(&schema, &batches)?)
+        })
+        .unwrap_or_else(|e| panic!("{table}: collect: {e}"))
+}
+
+/// Render the value column's schema and values.
+fn render(type_: &str, batch: &RecordBatch) -> String {
+    let schema = batch.schema();
+    let field = schema.field(1);
+    let mut out = String::new();
+    let _ = writeln!(out, "type: {type_}");
+    let _ = writeln!(out, "arrow: {}", field.data_type());
+    let _ = writeln!(out, "nullable: {}", field.is_nullable());
+    let mut metadata = field.metadata().iter().collect::<Vec<_>>();
+    metadata.sort();
+    for (key, value) in metadata {
+        let _ = writeln!(out, "metadata: {key} = {value}");
+    }
+    let values = pretty_format_batches(std::slice::from_ref(batch)).expect("Format batch");
+    let _ = writeln!(out, "{values}");
+    out
+}
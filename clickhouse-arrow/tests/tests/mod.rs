@@ -1,6 +1,8 @@
 pub mod arrow;
+pub mod cluster;
 pub mod compat;
 pub mod explain;
+pub mod golden;
 pub mod native;
 pub mod new_types;
 pub mod params;
@@ -8,7 +10,7 @@ pub mod params;
 use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 
-use clickhouse_arrow::test_utils::{self, ClickHouseContainer};
+use clickhouse_arrow::test_utils::{self, ClickHouseCluster, ClickHouseContainer};
 use futures_util::FutureExt;
 use tracing::{debug, error};
 
@@ -72,3 +74,55 @@ where
 
     result
 }
+
+/// Macro to run cluster tests using the below test harness.
+#[macro_export]
+macro_rules! e2e_cluster_test {
+    ($name:ident, $test_fn:expr, $dirs:expr, $conf:expr) => {
+        #[tokio::test(flavor = "multi_thread")]
+        async fn $name() {
+            let name = stringify!($name);
+            let result =
+                $crate::tests::run_cluster_test_with_cleanup(name, $test_fn, Some($dirs), $conf)
+                    .await;
+            if let Err(panic) = result {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    };
+}
+
+/// Test harness for cluster tests, shutting down every node of the cluster afterwards
+///
+/// # Errors
+/// # Panics
+pub async fn run_cluster_test_with_cleanup<F, Fut>(
+    name: &str,
+    test_fn: F,
+    directives: Option<&[(&str, &str)]>,
+    clickhouse_conf: Option<&str>,
+) -> Result<(), Box<dyn std::any::Any + Send>>
+where
+    F: FnOnce(Arc<ClickHouseCluster>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let disable_cleanup = std::env::var(DISABLE_CLEANUP_ENV)
+        .ok()
+        .is_some_and(|e| e.eq_ignore_ascii_case("true") || e == "1");
+
+    test_utils::init_tracing(directives);
+    let cluster = Arc::new(
+        ClickHouseCluster::try_new(clickhouse_conf).await.expect("Failed to start cluster"),
+    );
+
+    let result = AssertUnwindSafe(test_fn(Arc::clone(&cluster))).catch_unwind().await;
+
+    if disable_cleanup {
+        debug!(">>> Exiting cluster test w/o shutdown: {name}");
+        return result;
+    }
+
+    cluster.shutdown().await.expect("Shutting down cluster");
+
+    result
+}
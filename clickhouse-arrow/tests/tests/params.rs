@@ -384,3 +384,50 @@ pub async fn test_params_mixed_types(ch: Arc<ClickHouseContainer>) {
 
     header(query_id, "Mixed parameter types test completed");
 }
+
+/// Test string and string array parameters containing quotes and backslashes
+///
+/// # Panics
+pub async fn test_params_quoted_strings(ch: Arc<ClickHouseContainer>) {
+    let client: ArrowClient = ClientBuilder::new()
+        .with_endpoint(ch.get_native_url())
+        .with_username(&ch.user)
+        .with_password(&ch.password)
+        .with_ipv4_only(true)
+        .build()
+        .await
+        .expect("Building client");
+
+    let query_id = Qid::new();
+    header(query_id, "Testing parameters with quotes and backslashes");
+
+    // String values are bound literally, the client escapes them for the server's text parse
+    let params = QueryParams::from(vec![
+        ("name", ParamValue::from("O'Brien \\ Sons\tLtd")),
+        ("names", ParamValue::from(vec!["it's", "plain"])),
+    ]);
+    let batch = client
+        .query_params(
+            "SELECT {name:String} AS name, length({names:Array(String)}) AS count, \
+             {names:Array(String)}[1] AS first",
+            Some(params),
+            Some(query_id),
+        )
+        .await
+        .expect("Querying with quoted params should succeed")
+        .collect_result()
+        .await
+        .expect("Failed to collect results")
+        .into_batches()
+        .remove(0);
+
+    let value = |column: &str| {
+        let column = batch.column_by_name(column).expect("Missing column");
+        arrow::util::display::array_value_to_string(column, 0).unwrap()
+    };
+    assert_eq!(value("name"), "O'Brien \\ Sons\tLtd");
+    assert_eq!(value("count"), "2");
+    assert_eq!(value("first"), "it's");
+
+    client.shutdown().await.unwrap();
+}
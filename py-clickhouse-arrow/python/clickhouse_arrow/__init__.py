@@ -35,24 +35,34 @@ from clickhouse_arrow._internal import (
     ConfigurationError,
     ConnectionError,
     QueryError,
+    QueryStream,
+    SchemaMismatchError,
     SerializationError,
     ServerError,
     __version__,
 )
+from clickhouse_arrow.introspection import ColumnInfo, TableInfo
+from clickhouse_arrow.pylist import to_pylist
 
 __all__ = [
     # Core classes
     "Client",
     "ClientBuilder",
+    "QueryStream",
+    # Introspection results
+    "ColumnInfo",
+    "TableInfo",
     # Exceptions
     "ClickHouseError",
     "ConnectionError",
     "QueryError",
+    "SchemaMismatchError",
     "SerializationError",
     "ServerError",
     "ConfigurationError",
-    # Convenience function
+    # Convenience functions
     "connect",
+    "to_pylist",
     # Metadata
     "__version__",
 ]
@@ -4,10 +4,12 @@ Type stubs for the clickhouse_arrow._internal Rust extension module.
 These stubs provide type information for IDE autocompletion and static analysis.
 """
 
-from typing import List
+from typing import Any, Callable, Dict, Iterable, Iterator, List, Literal, Optional, Union, overload
 
 import pyarrow
 
+from clickhouse_arrow.introspection import TableInfo
+
 # Version string from Cargo.toml
 __version__: str
 
@@ -27,6 +29,19 @@ class QueryError(ClickHouseError):
 
     ...
 
+class SchemaMismatchError(QueryError):
+    """
+    A batch does not match the schema of the table it is inserted into.
+
+    Attributes:
+        columns: One dict per failing column, with the column's `name`, `arrow_type`,
+            `clickhouse_type` (None if the table has no such column), `error`, and
+            the suggested fix as `action` ("cast", "rename", "drop" or "manual")
+            and `target` (the type to cast to or the column to rename to)
+    """
+
+    columns: List[Dict[str, Optional[str]]]
+
 class SerializationError(ClickHouseError):
     """Data serialization/deserialization errors."""
 
@@ -106,6 +121,15 @@ class ClientBuilder:
         """Force IPv4-only address resolution."""
         ...
 
+    def readonly(self, enabled: bool) -> "ClientBuilder":
+        """
+        Refuse statements that write data or change the schema before sending them.
+
+        Raises:
+            QueryError: From queries run by the built client that are not read-only
+        """
+        ...
+
     def build(self) -> "Client":
         """
         Build and connect the client.
@@ -119,6 +143,77 @@ class ClientBuilder:
         """
         ...
 
+class QueryStream:
+    """
+    Lazily evaluated query result.
+
+    Implements the Arrow PyCapsule stream interface, so it can be passed directly
+    to any consumer that accepts `__arrow_c_stream__` objects (pyarrow, polars,
+    duckdb). Batches are pulled from the server as the consumer reads them.
+    A QueryStream can only be consumed once.
+
+    Example:
+        >>> stream = client.query_stream("SELECT * FROM system.numbers LIMIT 10")
+        >>> table = pyarrow.table(stream)
+    """
+
+    def __arrow_c_stream__(self, requested_schema: Optional[Any] = None) -> Any:
+        """
+        Export the result as an `arrow_array_stream` PyCapsule.
+
+        Args:
+            requested_schema: Ignored; batches use the schema returned by ClickHouse
+
+        Raises:
+            RuntimeError: If the stream has already been consumed
+        """
+        ...
+
+    def to_reader(self) -> pyarrow.RecordBatchReader:
+        """
+        Consume the stream into a PyArrow RecordBatchReader.
+
+        Suitable for `duckdb.register` and other APIs accepting a reader.
+
+        Raises:
+            RuntimeError: If the stream has already been consumed
+        """
+        ...
+
+    def to_pylist(
+        self, uuids: Optional[Iterable[str]] = None, named_tuples: bool = False
+    ) -> List[Dict[str, Any]]:
+        """
+        Consume the stream into a list of dicts, one per row.
+
+        Nested Map, Tuple and Array values are converted to dicts, tuples and lists.
+        See `clickhouse_arrow.to_pylist`.
+
+        Args:
+            uuids: Names of the columns holding UUIDs, returned as `uuid.UUID`
+            named_tuples: Return tuples with named elements as namedtuples instead of dicts
+
+        Raises:
+            RuntimeError: If the stream has already been consumed
+        """
+        ...
+
+    def _export_to_c(self, out_ptr: int) -> None:
+        """
+        Export the stream into a caller-allocated `ArrowArrayStream` struct.
+
+        Args:
+            out_ptr: Address of an uninitialised `ArrowArrayStream`
+
+        Raises:
+            ValueError: If out_ptr is null
+            RuntimeError: If the stream has already been consumed
+        """
+        ...
+
+    def __iter__(self) -> Iterator[pyarrow.RecordBatch]: ...
+    def __next__(self) -> pyarrow.RecordBatch: ...
+
 class Client:
     """
     ClickHouse client with Arrow integration.
@@ -127,17 +222,71 @@ class Client:
     All methods are synchronous (blocking) from Python's perspective.
     """
 
-    def query(self, query: str) -> List[pyarrow.RecordBatch]:
+    def query(
+        self,
+        query: str,
+        settings: Optional[Dict[str, Any]] = None,
+        query_id: Optional[str] = None,
+        timeout: Optional[float] = None,
+        progress: Optional[Callable[[Dict[str, Optional[int]]], None]] = None,
+        params: Optional[Dict[str, Any]] = None,
+    ) -> List[pyarrow.RecordBatch]:
         """
         Execute a query and return results as PyArrow RecordBatches.
 
         Args:
             query: SQL query string
+            settings: ClickHouse settings for this query (e.g., {"max_threads": 4}),
+                applied on top of the client's settings
+            query_id: Query ID as a UUID string (default: generated)
+            timeout: Seconds to wait for the query and its result before giving up
+            progress: Called with each progress update the server sends, a dict of
+                `read_rows`, `read_bytes`, `total_rows_to_read`, `total_bytes_to_read`,
+                `written_rows`, `written_bytes` and `elapsed_ns`. Values are deltas
+                since the previous update, None if not sent by the server. Exceptions
+                raised by the callback are printed and otherwise ignored
+            params: Values bound by the server to the `{name:Type}` placeholders of the
+                query (e.g., "WHERE id = {id:UInt64}" with {"id": 42}). Strings are bound
+                literally, lists and tuples as arrays, and other values as their `str()`
 
         Returns:
             List of PyArrow RecordBatch objects
 
         Raises:
+            ValueError: If query_id is not a UUID or timeout is negative
+            TypeError: If a parameter is None
+            TimeoutError: If the query takes longer than timeout
+            QueryError: If query execution fails
+            ConnectionError: If connection is lost
+        """
+        ...
+
+    def query_stream(
+        self,
+        query: str,
+        settings: Optional[Dict[str, Any]] = None,
+        query_id: Optional[str] = None,
+        params: Optional[Dict[str, Any]] = None,
+    ) -> QueryStream:
+        """
+        Execute a query and return a lazy stream of results.
+
+        The first batch is received before this returns so the schema is known;
+        remaining batches are read on demand by the consumer. To bound the run
+        time of a streamed query, use the `max_execution_time` setting.
+
+        Args:
+            query: SQL query string
+            settings: ClickHouse settings for this query, as for `query`
+            query_id: Query ID as a UUID string (default: generated)
+            params: Values bound to the placeholders of the query, as for `query`
+
+        Returns:
+            QueryStream implementing `__arrow_c_stream__`
+
+        Raises:
+            ValueError: If query_id is not a UUID
+            TypeError: If a parameter is None
             QueryError: If query execution fails
             ConnectionError: If connection is lost
         """
@@ -158,7 +307,66 @@ class Client:
         """
         ...
 
-    def execute(self, query: str) -> None:
+    def insert_batches(
+        self,
+        table: str,
+        batches: Iterable[pyarrow.RecordBatch],
+        max_rows_per_block: int = 1048576,
+    ) -> int:
+        """
+        Insert a list or iterator of PyArrow RecordBatches into a table.
+
+        Batches are pulled lazily and sent in inserts of up to `max_rows_per_block`
+        rows, slicing larger batches without copying. Only one insert's worth of
+        rows is held in memory at a time.
+
+        Args:
+            table: Table name, optionally qualified by database (e.g., "db.events")
+            batches: List or iterator of PyArrow RecordBatches
+            max_rows_per_block: Maximum rows sent per insert
+
+        Returns:
+            Number of rows inserted
+
+        Raises:
+            ValueError: If max_rows_per_block is 0
+            SerializationError: If data serialization fails
+            QueryError: If insert fails
+            ConnectionError: If connection is lost
+        """
+        ...
+
+    def validate_batch(self, table: str, batch: pyarrow.RecordBatch) -> None:
+        """
+        Check that a RecordBatch can be inserted into a table without inserting it.
+
+        Args:
+            table: Table name, optionally qualified by database (e.g., "db.events")
+            batch: PyArrow RecordBatch to check
+
+        Raises:
+            SchemaMismatchError: Listing the columns that do not match the table,
+                each with a suggested fix
+            ConfigurationError: If the table does not exist
+            ConnectionError: If connection is lost
+
+        Example:
+            >>> try:
+            ...     client.validate_batch("events", batch)
+            ... except SchemaMismatchError as e:
+            ...     for column in e.columns:
+            ...         print(column["name"], column["action"], column["target"])
+        """
+        ...
+
+    def execute(
+        self,
+        query: str,
+        settings: Optional[Dict[str, Any]] = None,
+        query_id: Optional[str] = None,
+        timeout: Optional[float] = None,
+        params: Optional[Dict[str, Any]] = None,
+    ) -> None:
         """
         Execute a query without returning results.
 
@@ -166,13 +374,77 @@ class Client:
 
         Args:
             query: SQL query string
+            settings: ClickHouse settings for this query, as for `query`
+            query_id: Query ID as a UUID string (default: generated)
+            timeout: Seconds to wait for the query before giving up
+            params: Values bound to the placeholders of the query, as for `query`
 
         Raises:
+            ValueError: If query_id is not a UUID or timeout is negative
+            TypeError: If a parameter is None
+            TimeoutError: If the query takes longer than timeout
             QueryError: If execution fails
             ConnectionError: If connection is lost
         """
         ...
 
+    def list_databases(self) -> List[str]:
+        """
+        List the databases on the server.
+
+        Returns:
+            Database names
+
+        Raises:
+            QueryError: If the query fails
+            ConnectionError: If connection is lost
+        """
+        ...
+
+    def list_tables(self, database: Optional[str] = None) -> List[str]:
+        """
+        List the tables in a database.
+
+        Args:
+            database: Database name (default: the client's database)
+
+        Returns:
+            Table names
+
+        Raises:
+            QueryError: If the query fails
+            ConnectionError: If connection is lost
+        """
+        ...
+
+    @overload
+    def describe(
+        self, table: str, database: Optional[str] = None, as_arrow: Literal[False] = False
+    ) -> TableInfo: ...
+    @overload
+    def describe(
+        self, table: str, database: Optional[str] = None, *, as_arrow: Literal[True]
+    ) -> pyarrow.Schema: ...
+    def describe(
+        self, table: str, database: Optional[str] = None, as_arrow: bool = False
+    ) -> Union[TableInfo, pyarrow.Schema]:
+        """
+        Describe a table's engine, size, and columns.
+
+        Args:
+            table: Table name
+            database: Database name (default: the client's database)
+            as_arrow: Return the table's Arrow schema instead of a TableInfo
+
+        Returns:
+            TableInfo dataclass, or pyarrow.Schema if as_arrow is True
+
+        Raises:
+            QueryError: If the table does not exist or the query fails
+            ConnectionError: If connection is lost
+        """
+        ...
+
     def health_check(self, ping: bool = False) -> None:
         """
         Check connection health.
@@ -0,0 +1,49 @@
+"""
+Schema introspection results returned by `Client.describe`.
+
+Plain dataclasses, so results print nicely in notebooks and can be converted with
+`dataclasses.asdict` for display in a DataFrame.
+"""
+
+from dataclasses import dataclass, field
+from typing import List, Optional
+
+
+@dataclass(frozen=True)
+class ColumnInfo:
+    """A column of a ClickHouse table, as reported by `system.columns`."""
+
+    name: str
+    """Column name."""
+    type: str
+    """ClickHouse type, e.g. `Nullable(String)`."""
+    default_kind: str
+    """`DEFAULT`, `MATERIALIZED`, `ALIAS`, `EPHEMERAL`, or empty for plain columns."""
+    default_expression: str
+    """The default expression, empty if none."""
+    comment: str
+    """Column comment, empty if none."""
+    is_in_primary_key: bool
+    """Whether the column is part of the primary key."""
+    is_in_sorting_key: bool
+    """Whether the column is part of the sorting key."""
+
+
+@dataclass(frozen=True)
+class TableInfo:
+    """A ClickHouse table, as reported by `system.tables` and `system.columns`."""
+
+    database: str
+    """Database containing the table."""
+    name: str
+    """Table name."""
+    engine: str
+    """Table engine, e.g. `MergeTree`."""
+    total_rows: Optional[int]
+    """Total rows, if known to the engine."""
+    total_bytes: Optional[int]
+    """Total bytes on disk, if known to the engine."""
+    comment: str
+    """Table comment, empty if none."""
+    columns: List[ColumnInfo] = field(default_factory=list)
+    """The table's columns, in table order."""
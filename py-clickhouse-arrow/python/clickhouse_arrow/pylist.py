@@ -0,0 +1,118 @@
+"""
+Conversion of query results to plain Python rows, for quick inspection without pyarrow
+knowledge.
+
+`to_pylist` turns results into a list of dicts, one per row, converting nested ClickHouse
+types on the way: `Map` to `dict`, `Tuple` to `tuple` (or a dict or namedtuple when its
+elements are named), and `Array` to `list`, at any depth. Dates, datetimes and decimals are
+returned as `datetime.date`, `datetime.datetime` and `decimal.Decimal`.
+
+`UUID`, `Int128`, `UInt128` and `IPv6` all arrive as 16 byte binary, so UUID columns must be
+named through `uuids` to be returned as `uuid.UUID`.
+"""
+
+from collections import namedtuple
+from typing import Any, Callable, Dict, Iterable, List, Optional
+from uuid import UUID
+
+import pyarrow
+
+# Prefix of the Arrow field names of unnamed ClickHouse tuple elements
+_TUPLE_FIELD_PREFIX = "field_"
+
+Converter = Optional[Callable[[Any], Any]]
+
+
+def to_pylist(
+    data: Any,
+    uuids: Iterable[str] = (),
+    named_tuples: bool = False,
+) -> List[Dict[str, Any]]:
+    """
+    Convert query results to a list of dicts, one per row.
+
+    Args:
+        data: A list of RecordBatches as returned by `Client.query`, a RecordBatch, Table,
+            RecordBatchReader, or any object implementing `__arrow_c_stream__`, such as a
+            `QueryStream`
+        uuids: Names of the columns holding UUIDs, returned as `uuid.UUID`. Applies to 16 byte
+            values nested anywhere in the column
+        named_tuples: Return tuples with named elements as namedtuples instead of dicts
+
+    Returns:
+        The rows of the result, mapping column names to Python values
+
+    Example:
+        >>> batches = client.query("SELECT map('a', (1, 'x')) AS m, generateUUIDv4() AS id")
+        >>> to_pylist(batches, uuids=["id"])
+        [{'m': {'a': (1, 'x')}, 'id': UUID('...')}]
+    """
+    table = _to_table(data)
+    uuids = set(uuids)
+    columns = []
+    for field, column in zip(table.schema, table.columns):
+        values = column.to_pylist()
+        convert = _converter(field.type, field.name in uuids, named_tuples)
+        columns.append(values if convert is None else [_apply(convert, v) for v in values])
+    names = table.schema.names
+    return [dict(zip(names, row)) for row in zip(*columns)]
+
+
+def _to_table(data: Any) -> pyarrow.Table:
+    if isinstance(data, pyarrow.Table):
+        return data
+    if isinstance(data, pyarrow.RecordBatch):
+        return pyarrow.Table.from_batches([data])
+    if isinstance(data, pyarrow.RecordBatchReader):
+        return data.read_all()
+    if isinstance(data, (list, tuple)):
+        if not data:
+            return pyarrow.table({})
+        return pyarrow.Table.from_batches(list(data))
+    return pyarrow.table(data)
+
+
+def _apply(convert: Converter, value: Any) -> Any:
+    return value if convert is None or value is None else convert(value)
+
+
+def _converter(data_type: pyarrow.DataType, uuid: bool, named_tuples: bool) -> Converter:
+    """Build a conversion of `to_pylist` values of `data_type`, or None if none is needed."""
+    if pyarrow.types.is_dictionary(data_type):
+        return _converter(data_type.value_type, uuid, named_tuples)
+
+    if pyarrow.types.is_map(data_type):
+        key = _converter(data_type.key_type, uuid, named_tuples)
+        item = _converter(data_type.item_type, uuid, named_tuples)
+        return lambda pairs: {_apply(key, k): _apply(item, v) for k, v in pairs}
+
+    if (
+        pyarrow.types.is_list(data_type)
+        or pyarrow.types.is_large_list(data_type)
+        or pyarrow.types.is_fixed_size_list(data_type)
+    ):
+        inner = _converter(data_type.value_type, uuid, named_tuples)
+        if inner is None:
+            return None
+        return lambda values: [_apply(inner, v) for v in values]
+
+    if pyarrow.types.is_struct(data_type):
+        fields = [data_type.field(i) for i in range(data_type.num_fields)]
+        names = [field.name for field in fields]
+        inner = [_converter(field.type, uuid, named_tuples) for field in fields]
+
+        def convert(row: Dict[str, Any]) -> List[Any]:
+            return [_apply(c, row[name]) for name, c in zip(names, inner)]
+
+        unnamed = names == [f"{_TUPLE_FIELD_PREFIX}{i}" for i in range(len(names))]
+        if unnamed:
+            return lambda row: tuple(convert(row))
+        if named_tuples:
+            row_type = namedtuple("Tuple", names, rename=True)  # type: ignore[misc]
+            return lambda row: row_type(*convert(row))
+        return lambda row: dict(zip(names, convert(row)))
+
+    if uuid and pyarrow.types.is_fixed_size_binary(data_type) and data_type.byte_width == 16:
+        return lambda value: UUID(bytes=value)
+
+    return None
@@ -16,6 +16,7 @@
 //! - [arrow-rs FFI](https://docs.rs/arrow/latest/arrow/ffi/index.html)
 
 use arrow::array::{Array, RecordBatch, StructArray};
+use arrow::datatypes::Schema;
 use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
@@ -42,30 +43,84 @@ impl From<ArrowFfiError> for PyErr {
 ///
 /// Uses PyArrow's `RecordBatch._import_from_c(array_ptr, schema_ptr)` method.
 pub(crate) fn record_batch_to_pyarrow(py: Python<'_>, batch: &RecordBatch) -> PyResult<PyObject> {
-    // Import PyArrow
-    let pyarrow = py
-        .import("pyarrow")
-        .map_err(|e| ArrowFfiError::PyArrowImport(format!("Failed to import pyarrow: {e}")))?;
+    let pa_record_batch = import_record_batch_type(py)?;
+    let exported = export_record_batch(batch)?;
+    import_record_batch(&pa_record_batch, exported)
+}
+
+/// Export `RecordBatch`es to PyArrow `RecordBatch`es via the C Data Interface.
+///
+/// The Rust side of the export runs with the GIL released, so other Python threads aren't
+/// blocked by large results. Python signals are checked between batches, so Ctrl-C interrupts
+/// the conversion.
+pub(crate) fn record_batches_to_pyarrow(
+    py: Python<'_>,
+    batches: &[RecordBatch],
+) -> PyResult<Vec<PyObject>> {
+    let pa_record_batch = import_record_batch_type(py)?;
+    let exported = py.allow_threads(|| {
+        batches.iter().map(export_record_batch).collect::<Result<Vec<_>, ArrowFfiError>>()
+    })?;
+    exported
+        .into_iter()
+        .map(|exported| {
+            py.check_signals()?;
+            import_record_batch(&pa_record_batch, exported)
+        })
+        .collect()
+}
+
+/// PyArrow's `RecordBatch` type.
+fn import_record_batch_type(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    py.import("pyarrow")
+        .map_err(|e| ArrowFfiError::PyArrowImport(format!("Failed to import pyarrow: {e}")))?
+        .getattr("RecordBatch")
+}
 
+/// Export a `RecordBatch` to FFI structs, without touching Python.
+fn export_record_batch(
+    batch: &RecordBatch,
+) -> Result<(FFI_ArrowArray, FFI_ArrowSchema), ArrowFfiError> {
     // Convert RecordBatch to StructArray for FFI export
     let struct_array: StructArray = batch.clone().into();
+    Ok(arrow::ffi::to_ffi(&struct_array.to_data())?)
+}
 
-    // Export to FFI structs
-    let (ffi_array, ffi_schema) =
-        arrow::ffi::to_ffi(&struct_array.to_data()).map_err(ArrowFfiError::Arrow)?;
-
+/// Import exported FFI structs as a PyArrow `RecordBatch`.
+fn import_record_batch(
+    pa_record_batch: &Bound<'_, PyAny>,
+    (ffi_array, ffi_schema): (FFI_ArrowArray, FFI_ArrowSchema),
+) -> PyResult<PyObject> {
     // Box and get raw pointers for PyArrow
     let array_ptr = Box::into_raw(Box::new(ffi_array)) as usize;
     let schema_ptr = Box::into_raw(Box::new(ffi_schema)) as usize;
 
     // Use PyArrow's RecordBatch._import_from_c(array_ptr, schema_ptr)
     // This takes ownership of the FFI structs
-    let pa_record_batch = pyarrow.getattr("RecordBatch")?;
     let result = pa_record_batch.call_method1("_import_from_c", (array_ptr, schema_ptr))?;
 
     Ok(result.into())
 }
 
+/// Export an Arrow `Schema` to a PyArrow `Schema` via the C Data Interface.
+///
+/// Uses PyArrow's `Schema._import_from_c(schema_ptr)` method.
+pub(crate) fn schema_to_pyarrow(py: Python<'_>, schema: &Schema) -> PyResult<PyObject> {
+    let pa_schema = py
+        .import("pyarrow")
+        .map_err(|e| ArrowFfiError::PyArrowImport(format!("Failed to import pyarrow: {e}")))?
+        .getattr("Schema")?;
+    let ffi_schema = FFI_ArrowSchema::try_from(schema).map_err(ArrowFfiError::Arrow)?;
+
+    // PyArrow moves the schema out of the struct, leaving it released for the drop below
+    let schema_ptr = Box::into_raw(Box::new(ffi_schema));
+    let result = pa_schema.call_method1("_import_from_c", (schema_ptr as usize,));
+    // SAFETY: the pointer was created by `Box::into_raw` above and is not used afterwards
+    drop(unsafe { Box::from_raw(schema_ptr) });
+
+    Ok(result?.into())
+}
+
 /// Import a `RecordBatch` from a PyArrow object via the C Data Interface.
 ///
 /// Uses PyArrow's `_export_to_c(array_ptr, schema_ptr)` method.
@@ -170,6 +170,18 @@ impl PyClientBuilder {
         self.clone()
     }
 
+    /// Refuse statements that write data or change the schema before sending them.
+    ///
+    /// Args:
+    ///     enabled: Whether to only run read-only statements (default: False)
+    ///
+    /// Returns:
+    ///     Self for method chaining
+    fn readonly(&mut self, enabled: bool) -> Self {
+        self.inner = std::mem::take(&mut self.inner).with_readonly(enabled);
+        self.clone()
+    }
+
     /// Build and connect the client.
     ///
     /// This method establishes a connection to the ClickHouse server using
@@ -181,9 +193,9 @@ impl PyClientBuilder {
     /// Raises:
     ///     ConnectionError: If connection fails
     ///     ConfigurationError: If configuration is invalid
-    fn build(&self) -> PyResult<Client> {
+    fn build(&self, py: Python<'_>) -> PyResult<Client> {
         let builder = self.inner.clone();
-        let client = to_py_result(block_on(builder.build_arrow()))?;
+        let client = to_py_result(block_on(py, builder.build_arrow())?)?;
         Ok(Client::new(client))
     }
 }
@@ -1,13 +1,22 @@
 //! Python client wrapper – query, insert, execute w/ PyArrow.
 
+use std::time::Duration;
+
 use arrow::array::RecordBatch;
-use clickhouse_arrow::prelude::ArrowClient;
+use clickhouse_arrow::Uuid;
+use clickhouse_arrow::prelude::{
+    ArrowClient, ParamValue, ProgressHook, Qid, QueryOptions, QueryParams, SettingValue, Settings,
+};
 use futures_util::StreamExt;
+use pyo3::exceptions::{PyTypeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyTuple};
 
-use crate::arrow_ffi::{record_batch_from_pyarrow, record_batch_to_pyarrow};
+use crate::arrow_ffi::{record_batch_from_pyarrow, record_batches_to_pyarrow, schema_to_pyarrow};
 use crate::error::to_py_result;
-use crate::runtime::block_on;
+use crate::introspection::describe_table;
+use crate::runtime::{block_on, block_on_timeout, handle};
+use crate::stream::QueryStream;
 
 /// ClickHouse client w/ Arrow integration. Sync API (blocking).
 #[pyclass(name = "Client")]
@@ -16,59 +25,426 @@ pub struct Client {
     inner: ArrowClient,
 }
 
+/// Default maximum rows per insert, matching ClickHouse's `max_insert_block_size`.
+const DEFAULT_MAX_ROWS_PER_BLOCK: usize = 1_048_576;
+
 impl Client {
     /// Create a new Client wrapper around an ArrowClient.
     pub fn new(client: ArrowClient) -> Self { Self { inner: client } }
+
+    /// Insert `blocks` with a single INSERT, returning the number of rows inserted.
+    fn insert_blocks(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        blocks: Vec<RecordBatch>,
+    ) -> PyResult<u64> {
+        let rows = blocks.iter().map(RecordBatch::num_rows).sum::<usize>() as u64;
+        to_py_result(block_on(py, async {
+            let mut stream = self.inner.insert_many(query, blocks, None).await?;
+            while let Some(result) = stream.next().await {
+                result?;
+            }
+            Ok::<_, clickhouse_arrow::Error>(())
+        })?)?;
+        Ok(rows)
+    }
 }
 
 #[pymethods]
 impl Client {
     /// Execute query, returns list of PyArrow RecordBatches.
-    fn query(&self, py: Python<'_>, query: &str) -> PyResult<Vec<PyObject>> {
+    ///
+    /// `settings` are sent with the query on top of the client's settings, `query_id` must be a
+    /// UUID, and `timeout` is in seconds, covering the query and reading its result. `progress`
+    /// is called with a dict for each progress update the server sends. `params` are bound to
+    /// the `{name:Type}` placeholders of the query by the server.
+    #[pyo3(signature = (
+        query, settings=None, query_id=None, timeout=None, progress=None, params=None
+    ))]
+    #[expect(clippy::too_many_arguments)]
+    fn query(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        settings: Option<&Bound<'_, PyDict>>,
+        query_id: Option<&str>,
+        timeout: Option<f64>,
+        progress: Option<PyObject>,
+        params: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<PyObject>> {
+        let mut options = query_options(settings, query_id, params)?;
+        if let Some(callback) = progress {
+            options = options.with_progress(progress_hook(callback));
+        }
         // Execute query and collect all batches
-        let batches: Vec<RecordBatch> = to_py_result(block_on(async {
-            let stream = self.inner.query(query, None).await?;
-            stream.collect::<Vec<_>>().await.into_iter().collect::<Result<Vec<_>, _>>()
-        }))?;
+        let batches: Vec<RecordBatch> =
+            to_py_result(block_on_timeout(py, parse_timeout(timeout)?, async {
+                let stream = self.inner.query_with_options(query, options).await?;
+                stream.collect::<Vec<_>>().await.into_iter().collect::<Result<Vec<_>, _>>()
+            })?)?;
 
         // Convert to PyArrow RecordBatches
-        batches.iter().map(|batch| record_batch_to_pyarrow(py, batch)).collect()
+        record_batches_to_pyarrow(py, &batches)
+    }
+
+    /// Execute query, returns a lazy QueryStream exposing `__arrow_c_stream__`.
+    ///
+    /// `settings`, `query_id` and `params` are as for `query`. Bound the query's run time with
+    /// the `max_execution_time` setting.
+    #[pyo3(signature = (query, settings=None, query_id=None, params=None))]
+    fn query_stream(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        settings: Option<&Bound<'_, PyDict>>,
+        query_id: Option<&str>,
+        params: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<QueryStream> {
+        let options = query_options(settings, query_id, params)?;
+        let response = to_py_result(block_on(py, self.inner.query_with_options(query, options))?)?;
+        // Wait for the first batch here, interruptibly, so creating the reader doesn't block.
+        // The response keeps its header, so results without rows still report their schema.
+        let response = block_on(py, response.peek_first())?;
+        let reader = to_py_result(response.into_blocking_reader(handle()))?;
+        Ok(QueryStream::new(reader))
     }
 
     /// Insert a PyArrow RecordBatch.
     fn insert(&self, py: Python<'_>, query: &str, batch: &Bound<'_, PyAny>) -> PyResult<()> {
         let record_batch = record_batch_from_pyarrow(py, batch)?;
 
-        to_py_result(block_on(async {
+        to_py_result(block_on(py, async {
             let mut stream = self.inner.insert(query, record_batch, None).await?;
             while let Some(result) = stream.next().await {
                 result?;
             }
             Ok::<_, clickhouse_arrow::Error>(())
-        }))?;
+        })?)?;
 
         Ok(())
     }
 
-    /// Execute query w/o returning results (DDL, DML).
-    fn execute(&self, query: &str) -> PyResult<()> {
-        to_py_result(block_on(self.inner.execute(query, None)))?;
+    /// Insert a list or iterator of PyArrow RecordBatches into `table`, returns rows inserted.
+    ///
+    /// Batches are pulled from `batches` lazily and grouped into inserts of up to
+    /// `max_rows_per_block` rows, slicing larger batches without copying. Only one group is held
+    /// in memory at a time, so arbitrarily large iterators can be inserted.
+    #[pyo3(signature = (table, batches, max_rows_per_block=DEFAULT_MAX_ROWS_PER_BLOCK))]
+    fn insert_batches(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        batches: &Bound<'_, PyAny>,
+        max_rows_per_block: usize,
+    ) -> PyResult<u64> {
+        if max_rows_per_block == 0 {
+            return Err(PyValueError::new_err("max_rows_per_block must be greater than 0"));
+        }
+        let query = format!("INSERT INTO {table} FORMAT Native");
+        let mut chunker = BlockChunker::new(max_rows_per_block);
+        let mut inserted = 0;
+        for batch in batches.try_iter()? {
+            let batch = record_batch_from_pyarrow(py, &batch?)?;
+            for blocks in chunker.push(&batch) {
+                inserted += self.insert_blocks(py, &query, blocks)?;
+            }
+        }
+        if let Some(blocks) = chunker.finish() {
+            inserted += self.insert_blocks(py, &query, blocks)?;
+        }
+        Ok(inserted)
+    }
+
+    /// Check that a PyArrow RecordBatch can be inserted into `table` without inserting it.
+    ///
+    /// Raises `SchemaMismatchError` listing every column that does not match the table, along
+    /// with a suggested fix for each.
+    fn validate_batch(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        batch: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        let record_batch = record_batch_from_pyarrow(py, batch)?;
+        to_py_result(block_on(py, async {
+            self.inner.validate_batch(table, &record_batch, None).await?.into_result().map(drop)
+        })?)
+    }
+
+    /// Execute query w/o returning results (DDL, DML). Keyword arguments are as for `query`.
+    #[pyo3(signature = (query, settings=None, query_id=None, timeout=None, params=None))]
+    fn execute(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        settings: Option<&Bound<'_, PyDict>>,
+        query_id: Option<&str>,
+        timeout: Option<f64>,
+        params: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let options = query_options(settings, query_id, params)?;
+        to_py_result(block_on_timeout(py, parse_timeout(timeout)?, async {
+            let mut stream = self.inner.query_with_options(query, options).await?;
+            while let Some(result) = stream.next().await {
+                drop(result?);
+            }
+            Ok::<_, clickhouse_arrow::Error>(())
+        })?)?;
         Ok(())
     }
 
+    /// List the databases on the server.
+    fn list_databases(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        to_py_result(block_on(py, self.inner.fetch_schemas(None))?)
+    }
+
+    /// List the tables in `database`, or the client's default database.
+    #[pyo3(signature = (database=None))]
+    fn list_tables(&self, py: Python<'_>, database: Option<&str>) -> PyResult<Vec<String>> {
+        to_py_result(block_on(py, self.inner.fetch_tables(database, None))?)
+    }
+
+    /// Describe `table` as a `TableInfo` dataclass, or as a `pyarrow.Schema` if `as_arrow`.
+    #[pyo3(signature = (table, database=None, as_arrow=false))]
+    fn describe(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        database: Option<&str>,
+        as_arrow: bool,
+    ) -> PyResult<PyObject> {
+        if !as_arrow {
+            let description =
+                to_py_result(block_on(py, describe_table(&self.inner, database, table))?)?;
+            return description.into_python(py);
+        }
+        let mut schemas =
+            to_py_result(block_on(py, self.inner.fetch_schema(database, &[table], None))?)?;
+        let schema = to_py_result(schemas.remove(table).ok_or_else(|| {
+            let db = database.unwrap_or("currentDatabase()").to_string();
+            clickhouse_arrow::Error::UndefinedTables { db, tables: vec![table.to_string()] }
+        }))?;
+        schema_to_pyarrow(py, &schema)
+    }
+
     /// Check connection health. Pass ping=True for active server check.
     #[pyo3(signature = (ping=false))]
-    fn health_check(&self, ping: bool) -> PyResult<()> {
-        to_py_result(block_on(self.inner.health_check(ping)))?;
+    fn health_check(&self, py: Python<'_>, ping: bool) -> PyResult<()> {
+        to_py_result(block_on(py, self.inner.health_check(ping))?)?;
         Ok(())
     }
 
     /// Gracefully shutdown the connection.
-    fn shutdown(&self) -> PyResult<()> {
-        to_py_result(block_on(self.inner.shutdown()))?;
+    fn shutdown(&self, py: Python<'_>) -> PyResult<()> {
+        to_py_result(block_on(py, self.inner.shutdown())?)?;
         Ok(())
     }
 
     /// String representation showing connection status.
     fn __repr__(&self) -> String { format!("Client(status={:?})", self.inner.status()) }
 }
+
+/// Build `QueryOptions` from the `settings`, `query_id` and `params` keyword arguments.
+fn query_options(
+    settings: Option<&Bound<'_, PyDict>>,
+    query_id: Option<&str>,
+    params: Option<&Bound<'_, PyDict>>,
+) -> PyResult<QueryOptions> {
+    let mut options = QueryOptions::new();
+    if let Some(settings) = settings {
+        options = options.with_settings(settings_from_dict(settings)?);
+    }
+    if let Some(params) = params {
+        options = options.with_params(params_from_dict(params)?);
+    }
+    if let Some(query_id) = query_id {
+        let uuid = Uuid::parse_str(query_id).map_err(|e| {
+            PyValueError::new_err(format!("query_id must be a UUID, got {query_id:?}: {e}"))
+        })?;
+        options = options.with_qid(Qid::from(uuid));
+    }
+    Ok(options)
+}
+
+/// A hook calling `callback` with each progress update as a dict of its counters. Updates are
+/// deltas, counters the server did not send are `None`. Exceptions raised by the callback are
+/// printed rather than failing the query.
+fn progress_hook(callback: PyObject) -> ProgressHook {
+    ProgressHook::new(move |progress| {
+        Python::with_gil(|py| {
+            let update = PyDict::new(py);
+            let result = [
+                ("read_rows", Some(progress.read_rows)),
+                ("read_bytes", Some(progress.read_bytes)),
+                ("total_rows_to_read", Some(progress.total_rows_to_read)),
+                ("total_bytes_to_read", progress.total_bytes_to_read),
+                ("written_rows", progress.written_rows),
+                ("written_bytes", progress.written_bytes),
+                ("elapsed_ns", progress.elapsed_ns),
+            ]
+            .into_iter()
+            .try_for_each(|(name, value)| update.set_item(name, value))
+            .and_then(|()| callback.call1(py, (update,)));
+            if let Err(error) = result {
+                error.print(py);
+            }
+        });
+    })
+}
+
+/// Convert a dict of setting names to bool, int, float, or str values to `Settings`.
+///
+/// Other values are sent as their `str()`.
+fn settings_from_dict(settings: &Bound<'_, PyDict>) -> PyResult<Settings> {
+    settings
+        .iter()
+        .map(|(name, value)| {
+            let name = name.extract::<String>()?;
+            let value = if value.is_instance_of::<PyBool>() {
+                SettingValue::Bool(value.extract()?)
+            } else if value.is_instance_of::<PyInt>() {
+                SettingValue::Int(value.extract()?)
+            } else if value.is_instance_of::<PyFloat>() {
+                SettingValue::Float(value.extract()?)
+            } else {
+                SettingValue::String(value.str()?.to_string())
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// Convert a dict of parameter names to values to `QueryParams`.
+///
+/// Lists and tuples are bound as array literals, ie `['a','b']`, with `None` elements as `NULL`.
+/// Other values are sent as their `str()`, which suits strings, dates, datetimes, decimals and
+/// UUIDs.
+fn params_from_dict(params: &Bound<'_, PyDict>) -> PyResult<QueryParams> {
+    params
+        .iter()
+        .map(|(name, value)| {
+            let name = name.extract::<String>()?;
+            if value.is_none() {
+                return Err(PyTypeError::new_err(format!(
+                    "Parameter '{name}' is None, use NULL in the query instead"
+                )));
+            }
+            let value = if value.is_instance_of::<PyBool>() {
+                ParamValue::Bool(value.extract()?)
+            } else if value.is_instance_of::<PyInt>() {
+                ParamValue::Int(value.extract()?)
+            } else if value.is_instance_of::<PyFloat>() {
+                ParamValue::Float(value.extract()?)
+            } else if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+                ParamValue::String(param_literal(&value)?)
+            } else {
+                ParamValue::String(value.str()?.to_string())
+            };
+            Ok((name, value))
+        })
+        .collect::<PyResult<Vec<_>>>()
+        .map(QueryParams)
+}
+
+/// Render a parameter value as a `ClickHouse` literal, quoting strings.
+fn param_literal(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    if value.is_none() {
+        Ok("NULL".into())
+    } else if value.is_instance_of::<PyBool>() {
+        Ok(if value.extract::<bool>()? { "true" } else { "false" }.into())
+    } else if value.is_instance_of::<PyInt>() || value.is_instance_of::<PyFloat>() {
+        Ok(value.str()?.to_string())
+    } else if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+        let items = value.try_iter()?.map(|item| param_literal(&item?));
+        Ok(format!("[{}]", items.collect::<PyResult<Vec<_>>>()?.join(",")))
+    } else {
+        let text = value.str()?.to_string();
+        Ok(format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'")))
+    }
+}
+
+/// Convert a timeout in seconds to a `Duration`.
+fn parse_timeout(timeout: Option<f64>) -> PyResult<Option<Duration>> {
+    timeout
+        .map(|secs| {
+            Duration::try_from_secs_f64(secs).map_err(|_| {
+                PyValueError::new_err(format!("timeout must be a non-negative number, got {secs}"))
+            })
+        })
+        .transpose()
+}
+
+/// Groups batches into inserts of exactly `max_rows` rows, the last one possibly smaller.
+///
+/// Batches spanning a group boundary are split with zero-copy slices.
+struct BlockChunker {
+    max_rows:     usize,
+    pending:      Vec<RecordBatch>,
+    pending_rows: usize,
+}
+
+impl BlockChunker {
+    fn new(max_rows: usize) -> Self { Self { max_rows, pending: Vec::new(), pending_rows: 0 } }
+
+    /// Add a batch, returning the groups it completed.
+    fn push(&mut self, batch: &RecordBatch) -> Vec<Vec<RecordBatch>> {
+        let mut groups = Vec::new();
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let len = (self.max_rows - self.pending_rows).min(batch.num_rows() - offset);
+            self.pending.push(batch.slice(offset, len));
+            self.pending_rows += len;
+            offset += len;
+            if self.pending_rows == self.max_rows {
+                groups.push(std::mem::take(&mut self.pending));
+                self.pending_rows = 0;
+            }
+        }
+        groups
+    }
+
+    /// The remaining partial group, if any.
+    fn finish(self) -> Option<Vec<RecordBatch>> {
+        (!self.pending.is_empty()).then_some(self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+
+    fn batch(rows: i32) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Int32, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int32Array::from_iter_values(0..rows))]).unwrap()
+    }
+
+    fn group_rows(groups: &[Vec<RecordBatch>]) -> Vec<Vec<usize>> {
+        groups.iter().map(|g| g.iter().map(RecordBatch::num_rows).collect()).collect()
+    }
+
+    #[test]
+    fn test_block_chunker() {
+        let mut chunker = BlockChunker::new(4);
+        // Small batches are grouped, without emitting until the group is full
+        assert!(chunker.push(&batch(3)).is_empty());
+        assert!(chunker.push(&batch(0)).is_empty());
+        // Batches spanning groups are sliced
+        assert_eq!(group_rows(&chunker.push(&batch(10))), vec![vec![3, 1], vec![4], vec![4]]);
+        assert_eq!(group_rows(&[chunker.finish().unwrap()]), vec![vec![1]]);
+        assert!(BlockChunker::new(4).finish().is_none());
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout(None).unwrap(), None);
+        assert_eq!(parse_timeout(Some(1.5)).unwrap(), Some(Duration::from_millis(1500)));
+        assert!(parse_timeout(Some(-1.0)).is_err());
+        assert!(parse_timeout(Some(f64::NAN)).is_err());
+    }
+}
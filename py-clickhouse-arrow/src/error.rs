@@ -14,14 +14,17 @@
 //! ClickHouseError (base)
 //! ├── ConnectionError      - Network, timeout, connection issues
 //! ├── QueryError           - Protocol, parsing, type errors
+//! │   └── SchemaMismatchError - Batches not matching the table, with suggested fixes
 //! ├── SerializationError   - Data serialization failures
 //! ├── ServerError          - ClickHouse server exceptions
 //! └── ConfigurationError   - Client configuration issues
 //! ```
 
+use clickhouse_arrow::arrow::conversion::SchemaMismatch;
 use pyo3::create_exception;
 use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 
 // Base exception for all clickhouse-arrow errors
 create_exception!(clickhouse_arrow, ClickHouseError, PyException);
@@ -32,6 +35,9 @@ create_exception!(clickhouse_arrow, ConnectionError, ClickHouseError);
 // Query execution errors
 create_exception!(clickhouse_arrow, QueryError, ClickHouseError);
 
+// Batches that cannot be inserted into a table, listing each failing column
+create_exception!(clickhouse_arrow, SchemaMismatchError, QueryError);
+
 // Data serialisation errors
 create_exception!(clickhouse_arrow, SerializationError, ClickHouseError);
 
@@ -55,7 +61,8 @@ impl From<ClickHouseErrorWrapper> for PyErr {
         let err = wrapper.0;
         let msg = err.to_string();
 
-        match err {
+        // Column context only locates the error, classify by the underlying cause
+        match err.root_cause() {
             // Connection errors
             Error::Io(_)
             | Error::ConnectionTimeout(_)
@@ -64,10 +71,12 @@ impl From<ClickHouseErrorWrapper> for PyErr {
             | Error::ChannelClosed
             | Error::OutgoingTimeout(_)
             | Error::StartupError
-            | Error::Network(_) => ConnectionError::new_err(msg),
+            | Error::Network(_)
+            | Error::Overloaded { .. } => ConnectionError::new_err(msg),
 
             // Query/protocol errors
             Error::Protocol(_)
+            | Error::ChunkTooLarge { .. }
             | Error::TypeParseError(_)
             | Error::DeserializeError(_)
             | Error::DeserializeErrorWithColumn(_, _)
@@ -83,7 +92,9 @@ impl From<ClickHouseErrorWrapper> for PyErr {
             | Error::ArrowDeserialize(_)
             | Error::ArrowTypeMismatch { .. }
             | Error::ArrowUnsupportedType(_)
-            | Error::Arrow(_) => QueryError::new_err(msg),
+            | Error::Arrow(_)
+            | Error::ReadOnly { .. } => QueryError::new_err(msg),
+            Error::SchemaMismatch(mismatch) => schema_mismatch_error(msg, mismatch),
 
             // Serialisation errors
             Error::SerializeError(_) | Error::ArrowSerialize(_) | Error::InsertArrowRetry(_) => {
@@ -119,6 +130,34 @@ impl From<ClickHouseErrorWrapper> for PyErr {
     }
 }
 
+/// A `SchemaMismatchError` with the failing columns as dicts in its `columns` attribute, each
+/// holding the column's `name`, `arrow_type`, `clickhouse_type`, `error`, and the suggested fix as
+/// `action` ("cast", "rename", "drop" or "manual") and `target`.
+fn schema_mismatch_error(msg: String, mismatch: &SchemaMismatch) -> PyErr {
+    Python::with_gil(|py| {
+        let err = SchemaMismatchError::new_err(msg);
+        let columns = mismatch
+            .columns
+            .iter()
+            .map(|c| {
+                let column = PyDict::new(py);
+                column.set_item("name", &c.name)?;
+                column.set_item("arrow_type", c.arrow_type.to_string())?;
+                let clickhouse_type = c.clickhouse_type.as_ref().map(ToString::to_string);
+                column.set_item("clickhouse_type", clickhouse_type)?;
+                column.set_item("error", &c.error)?;
+                column.set_item("action", c.fix.action())?;
+                column.set_item("target", c.fix.target())?;
+                Ok(column)
+            })
+            .collect::<PyResult<Vec<_>>>();
+        match columns.and_then(|columns| err.value(py).setattr("columns", columns)) {
+            Ok(()) => err,
+            Err(error) => error,
+        }
+    })
+}
+
 /// Convert a clickhouse-arrow Result to a PyResult.
 pub(crate) fn to_py_result<T>(result: Result<T, clickhouse_arrow::Error>) -> PyResult<T> {
     result.map_err(|e| ClickHouseErrorWrapper(e).into())
@@ -129,6 +168,7 @@ pub(crate) fn register_exceptions(py: Python<'_>, m: &Bound<'_, PyModule>) -> Py
     m.add("ClickHouseError", py.get_type::<ClickHouseError>())?;
     m.add("ConnectionError", py.get_type::<ConnectionError>())?;
     m.add("QueryError", py.get_type::<QueryError>())?;
+    m.add("SchemaMismatchError", py.get_type::<SchemaMismatchError>())?;
     m.add("SerializationError", py.get_type::<SerializationError>())?;
     m.add("ServerError", py.get_type::<ServerError>())?;
     m.add("ConfigurationError", py.get_type::<ConfigurationError>())?;
@@ -0,0 +1,183 @@
+// Project:   py-clickhouse-arrow
+// File:      introspection.rs
+// Purpose:   Schema introspection helpers returning Python dataclasses
+// Language:  Rust
+//
+// License:   Apache-2.0
+// Copyright: (c) 2026 HyperSec
+
+//! Schema introspection for notebooks, without writing system-table SQL.
+//!
+//! [`describe_table`] reads a table's metadata from `system.tables` and `system.columns`, which
+//! is then converted to the `TableInfo` and `ColumnInfo` dataclasses defined in
+//! `clickhouse_arrow.introspection`.
+
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, UInt64Type};
+use clickhouse_arrow::prelude::{ArrowClient, QueryParams};
+use clickhouse_arrow::{Error, Result};
+use futures_util::StreamExt;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Python module defining the introspection dataclasses.
+const DATACLASS_MODULE: &str = "clickhouse_arrow.introspection";
+
+/// A column of a table, see `clickhouse_arrow.introspection.ColumnInfo`.
+pub(crate) struct ColumnDescription {
+    name:               String,
+    type_name:          String,
+    default_kind:       String,
+    default_expression: String,
+    comment:            String,
+    is_in_primary_key:  bool,
+    is_in_sorting_key:  bool,
+}
+
+/// A table and its columns, see `clickhouse_arrow.introspection.TableInfo`.
+pub(crate) struct TableDescription {
+    database:    String,
+    name:        String,
+    engine:      String,
+    total_rows:  Option<u64>,
+    total_bytes: Option<u64>,
+    comment:     String,
+    columns:     Vec<ColumnDescription>,
+}
+
+impl TableDescription {
+    /// Convert to a `TableInfo` dataclass.
+    pub(crate) fn into_python(self, py: Python<'_>) -> PyResult<PyObject> {
+        let module = py.import(DATACLASS_MODULE)?;
+        let column_info = module.getattr("ColumnInfo")?;
+        let columns = self
+            .columns
+            .into_iter()
+            .map(|column| {
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("name", column.name)?;
+                kwargs.set_item("type", column.type_name)?;
+                kwargs.set_item("default_kind", column.default_kind)?;
+                kwargs.set_item("default_expression", column.default_expression)?;
+                kwargs.set_item("comment", column.comment)?;
+                kwargs.set_item("is_in_primary_key", column.is_in_primary_key)?;
+                kwargs.set_item("is_in_sorting_key", column.is_in_sorting_key)?;
+                column_info.call((), Some(&kwargs))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("database", self.database)?;
+        kwargs.set_item("name", self.name)?;
+        kwargs.set_item("engine", self.engine)?;
+        kwargs.set_item("total_rows", self.total_rows)?;
+        kwargs.set_item("total_bytes", self.total_bytes)?;
+        kwargs.set_item("comment", self.comment)?;
+        kwargs.set_item("columns", columns)?;
+        Ok(module.getattr("TableInfo")?.call((), Some(&kwargs))?.into())
+    }
+}
+
+/// Read the metadata of `table` in `database`, or the current database if `None`.
+pub(crate) async fn describe_table(
+    client: &ArrowClient,
+    database: Option<&str>,
+    table: &str,
+) -> Result<TableDescription> {
+    let database_expr = if database.is_some() { "{database:String}" } else { "currentDatabase()" };
+    let mut params = vec![("table", table.to_string())];
+    params.extend(database.map(|db| ("database", db.to_string())));
+
+    let tables = query_batch(
+        client,
+        format!(
+            "SELECT database, name, engine, total_rows, total_bytes, comment FROM system.tables \
+             WHERE database = {database_expr} AND name = {{table:String}}"
+        ),
+        params.clone(),
+    )
+    .await?;
+    if tables.num_rows() == 0 {
+        let db = database.unwrap_or("currentDatabase()").to_string();
+        return Err(Error::UndefinedTables { db, tables: vec![table.to_string()] });
+    }
+
+    let columns = query_batch(
+        client,
+        format!(
+            "SELECT name, type, default_kind, default_expression, comment, is_in_primary_key, \
+             is_in_sorting_key FROM system.columns WHERE database = {database_expr} AND table = \
+             {{table:String}} ORDER BY position"
+        ),
+        params,
+    )
+    .await?;
+
+    let (names, types, kinds, expressions, comments) = (
+        strings(columns.column(0))?,
+        strings(columns.column(1))?,
+        strings(columns.column(2))?,
+        strings(columns.column(3))?,
+        strings(columns.column(4))?,
+    );
+    let (primary, sorting) = (flags(columns.column(5))?, flags(columns.column(6))?);
+    let columns = (0..columns.num_rows())
+        .map(|i| ColumnDescription {
+            name:               names[i].clone(),
+            type_name:          types[i].clone(),
+            default_kind:       kinds[i].clone(),
+            default_expression: expressions[i].clone(),
+            comment:            comments[i].clone(),
+            is_in_primary_key:  primary[i],
+            is_in_sorting_key:  sorting[i],
+        })
+        .collect();
+
+    let total_rows = cast(tables.column(3), &DataType::UInt64)?;
+    let total_bytes = cast(tables.column(4), &DataType::UInt64)?;
+    let optional =
+        |array: &ArrayRef| array.is_valid(0).then(|| array.as_primitive::<UInt64Type>().value(0));
+    Ok(TableDescription {
+        database: strings(tables.column(0))?.swap_remove(0),
+        name: strings(tables.column(1))?.swap_remove(0),
+        engine: strings(tables.column(2))?.swap_remove(0),
+        total_rows: optional(&total_rows),
+        total_bytes: optional(&total_bytes),
+        comment: strings(tables.column(5))?.swap_remove(0),
+        columns,
+    })
+}
+
+/// Run `query` and concatenate its result into a single batch.
+async fn query_batch(
+    client: &ArrowClient,
+    query: String,
+    params: Vec<(&str, String)>,
+) -> Result<RecordBatch> {
+    let batches = client
+        .query_params(query, Some(QueryParams::from(params)), None)
+        .await?
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    match batches.first() {
+        Some(first) => Ok(arrow::compute::concat_batches(&first.schema(), &batches)?),
+        None => Ok(RecordBatch::new_empty(Arc::new(arrow::datatypes::Schema::empty()))),
+    }
+}
+
+/// Read a string column, which may be returned as binary depending on the client's options.
+fn strings(array: &ArrayRef) -> Result<Vec<String>> {
+    let array = cast(array, &DataType::Utf8)?;
+    Ok(array.as_string::<i32>().iter().map(|s| s.unwrap_or_default().to_string()).collect())
+}
+
+/// Read a `UInt8` flag column.
+fn flags(array: &ArrayRef) -> Result<Vec<bool>> {
+    let array = cast(array, &DataType::Boolean)?;
+    Ok(array.as_boolean().iter().map(Option::unwrap_or_default).collect())
+}
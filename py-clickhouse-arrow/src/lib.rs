@@ -9,7 +9,9 @@ mod arrow_ffi;
 mod builder;
 mod client;
 mod error;
+mod introspection;
 mod runtime;
+mod stream;
 
 use pyo3::prelude::*;
 
@@ -24,6 +26,7 @@ fn _internal(py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Register classes
     m.add_class::<client::Client>()?;
     m.add_class::<builder::PyClientBuilder>()?;
+    m.add_class::<stream::QueryStream>()?;
 
     // Add version info
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
@@ -11,11 +11,22 @@
 //! Creates a lazily-initialised multi-threaded Tokio runtime that persists
 //! for the lifetime of the Python module. Provides `block_on()` for executing
 //! async code synchronously from Python.
+//!
+//! Blocking calls release the GIL, so other Python threads keep running, and wake up every
+//! [`SIGNAL_CHECK_INTERVAL`] to check for Python signals, so Ctrl-C interrupts long queries.
 
 use std::future::Future;
+use std::pin::pin;
 use std::sync::LazyLock;
+use std::task::Poll;
+use std::time::Duration;
+
+use pyo3::exceptions::PyTimeoutError;
+use pyo3::prelude::*;
+use tokio::runtime::{Handle, Runtime};
 
-use tokio::runtime::Runtime;
+/// How often blocking calls check for Python signals, ie `KeyboardInterrupt`.
+pub(crate) const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Global Tokio runtime for executing async operations.
 ///
@@ -32,26 +43,83 @@ static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
 
 /// Execute an async future synchronously, blocking until completion.
 ///
-/// This is the primary bridge between async Rust code and sync Python calls.
-/// Uses the global runtime to execute the future.
-pub(crate) fn block_on<F: Future>(future: F) -> F::Output { RUNTIME.block_on(future) }
+/// This is the primary bridge between async Rust code and sync Python calls. The GIL is
+/// released while the future runs. If a signal handler raises, ie `KeyboardInterrupt` on
+/// Ctrl-C, the future is dropped, cancelling the operation, and the exception is returned.
+pub(crate) fn block_on<F>(py: Python<'_>, future: F) -> PyResult<F::Output>
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    let mut future = pin!(future);
+    interruptible(py, || block_on_for(&mut future, SIGNAL_CHECK_INTERVAL))
+}
+
+/// Like [`block_on`], but raises `TimeoutError`, cancelling the operation, if the future takes
+/// longer than `timeout`.
+pub(crate) fn block_on_timeout<F>(
+    py: Python<'_>,
+    timeout: Option<Duration>,
+    future: F,
+) -> PyResult<F::Output>
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    let Some(timeout) = timeout else { return block_on(py, future) };
+    block_on(py, tokio::time::timeout(timeout, future))?
+        .map_err(|_| PyTimeoutError::new_err(format!("Timed out after {timeout:?}")))
+}
+
+/// Run `step` with the GIL released until it is ready, checking for Python signals between
+/// steps. Each step should block for at most [`SIGNAL_CHECK_INTERVAL`].
+pub(crate) fn interruptible<T: Send>(
+    py: Python<'_>,
+    mut step: impl FnMut() -> Poll<T> + Send,
+) -> PyResult<T> {
+    loop {
+        match py.allow_threads(&mut step) {
+            Poll::Ready(output) => return Ok(output),
+            Poll::Pending => py.check_signals()?,
+        }
+    }
+}
+
+/// Drive `future` on the global runtime for at most `timeout`.
+fn block_on_for<F: Future + Unpin>(future: F, timeout: Duration) -> Poll<F::Output> {
+    match RUNTIME.block_on(tokio::time::timeout(timeout, future)) {
+        Ok(output) => Poll::Ready(output),
+        Err(_) => Poll::Pending,
+    }
+}
+
+/// Handle to the global runtime, for Rust APIs that block on it internally.
+pub(crate) fn handle() -> Handle { RUNTIME.handle().clone() }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_block_on_basic() {
-        let result = block_on(async { 42 });
-        assert_eq!(result, 42);
+    fn test_block_on_for_ready() {
+        let result = block_on_for(pin!(async { 42 }), SIGNAL_CHECK_INTERVAL);
+        assert_eq!(result, Poll::Ready(42));
     }
 
     #[test]
-    fn test_block_on_async_sleep() {
+    fn test_block_on_for_resumes() {
         let start = std::time::Instant::now();
-        block_on(async {
-            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        let mut future = pin!(async {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            "done"
         });
-        assert!(start.elapsed() >= std::time::Duration::from_millis(10));
+        // The future is not lost when a step times out, later steps resume it
+        assert!(block_on_for(&mut future, Duration::from_millis(5)).is_pending());
+        let mut result = Poll::Pending;
+        while result.is_pending() {
+            result = block_on_for(&mut future, Duration::from_millis(5));
+        }
+        assert_eq!(result, Poll::Ready("done"));
+        assert!(start.elapsed() >= Duration::from_millis(30));
     }
 }
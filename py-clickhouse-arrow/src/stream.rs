@@ -0,0 +1,160 @@
+// Project:   py-clickhouse-arrow
+// File:      stream.rs
+// Purpose:   Lazy query results exported via the Arrow C Stream interface
+// Language:  Rust
+//
+// License:   Apache-2.0
+// Copyright: (c) 2026 HyperSec
+
+//! Lazy query results exported through the Arrow C Stream interface.
+//!
+//! [`QueryStream`] implements the `__arrow_c_stream__` protocol so that pyarrow, polars, duckdb
+//! and other PyCapsule-aware consumers can pull batches directly from the server response
+//! without materialising the full result set first.
+//!
+//! ## References
+//!
+//! - [Arrow PyCapsule Interface](https://arrow.apache.org/docs/format/CDataInterface/PyCapsuleInterface.html)
+//! - [Arrow C Stream Interface](https://arrow.apache.org/docs/format/CStreamInterface.html)
+
+use std::ffi::CString;
+use std::sync::Mutex;
+
+use clickhouse_arrow::arrow::ffi::{BlockingRecordBatchReader, FFI_ArrowArrayStream};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyCapsule, PyDict};
+
+use crate::arrow_ffi::{ArrowFfiError, record_batch_to_pyarrow};
+use crate::error::to_py_result;
+use crate::runtime::{SIGNAL_CHECK_INTERVAL, interruptible};
+
+/// Capsule name required by the Arrow PyCapsule interface for array streams.
+const ARROW_ARRAY_STREAM_CAPSULE: &str = "arrow_array_stream";
+
+/// Python module converting results to plain Python rows.
+const PYLIST_MODULE: &str = "clickhouse_arrow.pylist";
+
+/// Lazily evaluated query result.
+///
+/// Exposes `__arrow_c_stream__` for zero-copy consumption, and can also be iterated directly
+/// from Python to receive PyArrow RecordBatches one at a time. The result can only be consumed
+/// once.
+#[pyclass(name = "QueryStream")]
+#[expect(unnameable_types)]
+pub struct QueryStream {
+    reader: Mutex<Option<BlockingRecordBatchReader>>,
+}
+
+impl QueryStream {
+    /// Create a new QueryStream wrapping a blocking reader.
+    pub(crate) fn new(reader: BlockingRecordBatchReader) -> Self {
+        Self { reader: Mutex::new(Some(reader)) }
+    }
+
+    fn take_reader(&self) -> PyResult<BlockingRecordBatchReader> {
+        self.reader
+            .lock()
+            .map_err(|_| PyRuntimeError::new_err("QueryStream lock poisoned"))?
+            .take()
+            .ok_or_else(|| PyRuntimeError::new_err("QueryStream has already been consumed"))
+    }
+}
+
+#[pymethods]
+impl QueryStream {
+    /// Export the stream as an `ArrowArrayStream` PyCapsule.
+    ///
+    /// `requested_schema` is accepted for protocol compatibility but ignored; batches are always
+    /// produced in the schema returned by ClickHouse.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyCapsule>> {
+        drop(requested_schema);
+        let reader = self.take_reader()?;
+        let ffi_stream = reader.into_ffi_stream();
+        let name = CString::new(ARROW_ARRAY_STREAM_CAPSULE)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        PyCapsule::new(py, ffi_stream, Some(name))
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> { slf }
+
+    /// Return the next PyArrow RecordBatch, or stop iteration when the result is exhausted.
+    ///
+    /// Waits for the batch with the GIL released, checking for Python signals periodically.
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let mut guard =
+            self.reader.lock().map_err(|_| PyRuntimeError::new_err("QueryStream lock poisoned"))?;
+        let Some(reader) = guard.as_mut() else {
+            return Ok(None);
+        };
+        let next = interruptible(py, || reader.next_batch_timeout(SIGNAL_CHECK_INTERVAL))?;
+        match to_py_result(next)? {
+            Some(batch) => record_batch_to_pyarrow(py, &batch).map(Some),
+            None => {
+                *guard = None;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Consume the stream into a PyArrow `RecordBatchReader`.
+    ///
+    /// Suitable for `duckdb.register`, `pyarrow.dataset`, or any API accepting a reader.
+    fn to_reader(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let pyarrow = py
+            .import("pyarrow")
+            .map_err(|e| ArrowFfiError::PyArrowImport(format!("Failed to import pyarrow: {e}")))?;
+        let stream_ptr = Box::into_raw(Box::new(self.take_reader()?.into_ffi_stream()));
+
+        // PyArrow moves the stream out of the struct, leaving a released stream behind
+        let result = pyarrow
+            .getattr("RecordBatchReader")?
+            .call_method1("_import_from_c", (stream_ptr as usize,));
+
+        // SAFETY: `stream_ptr` was created by `Box::into_raw` above and is not used again
+        drop(unsafe { Box::from_raw(stream_ptr) });
+        Ok(result?.into())
+    }
+
+    /// Consume the stream into a list of dicts, one per row, see `clickhouse_arrow.to_pylist`.
+    #[pyo3(signature = (uuids=None, named_tuples=false))]
+    fn to_pylist(
+        &self,
+        py: Python<'_>,
+        uuids: Option<Vec<String>>,
+        named_tuples: bool,
+    ) -> PyResult<PyObject> {
+        let reader = self.to_reader(py)?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("uuids", uuids.unwrap_or_default())?;
+        kwargs.set_item("named_tuples", named_tuples)?;
+        let rows =
+            py.import(PYLIST_MODULE)?.getattr("to_pylist")?.call((reader,), Some(&kwargs))?;
+        Ok(rows.into())
+    }
+
+    /// Export the stream into a caller-allocated `ArrowArrayStream` struct at `out_ptr`.
+    ///
+    /// Mirrors PyArrow's `_export_to_c` for consumers working with raw C pointers.
+    fn _export_to_c(&self, out_ptr: usize) -> PyResult<()> {
+        if out_ptr == 0 {
+            return Err(PyValueError::new_err("out_ptr must not be null"));
+        }
+        let ffi_stream = self.take_reader()?.into_ffi_stream();
+        // SAFETY: Caller guarantees `out_ptr` points to writable memory for an
+        // `ArrowArrayStream`, per the Arrow C Stream interface contract
+        unsafe { std::ptr::write(out_ptr as *mut FFI_ArrowArrayStream, ffi_stream) };
+        Ok(())
+    }
+
+    /// String representation showing whether the stream has been consumed.
+    fn __repr__(&self) -> String {
+        let consumed = self.reader.lock().is_ok_and(|guard| guard.is_none());
+        format!("QueryStream(consumed={consumed})")
+    }
+}